@@ -59,6 +59,9 @@ pub async fn handle(
     Execution::BatchDeploy(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::ReconcileDeployment(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::PullDeployment(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
@@ -230,9 +233,15 @@ pub async fn handle(
     Execution::GlobalAutoUpdate(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::RedeployVariableReferences(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::Sleep(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::WaitUntil(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
   }
 
   super::wait_for_enter("run execution", yes)?;
@@ -274,6 +283,10 @@ pub async fn handle(
     Execution::BatchDeploy(request) => {
       client.execute(request).await.map(ExecutionResult::Batch)
     }
+    Execution::ReconcileDeployment(request) => client
+      .execute(request)
+      .await
+      .map(|u| ExecutionResult::Single(u.into())),
     Execution::PullDeployment(request) => client
       .execute(request)
       .await
@@ -494,6 +507,9 @@ pub async fn handle(
       .execute(request)
       .await
       .map(|u| ExecutionResult::Single(u.into())),
+    Execution::RedeployVariableReferences(request) => {
+      client.execute(request).await.map(ExecutionResult::Batch)
+    }
     Execution::Sleep(request) => {
       let duration =
         Duration::from_millis(request.duration_ms as u64);
@@ -501,6 +517,9 @@ pub async fn handle(
       println!("Finished sleeping!");
       std::process::exit(0)
     }
+    Execution::WaitUntil(_) => Err(anyhow::anyhow!(
+      "WaitUntil is only supported as a Procedure execution"
+    )),
     Execution::None(_) => unreachable!(),
   };
 
@@ -264,6 +264,14 @@ pub fn cli_config() -> &'static CliConfig {
         otlp_endpoint: env
           .komodo_cli_logging_otlp_endpoint
           .unwrap_or(config.cli_logging.otlp_endpoint),
+        otlp_metrics_endpoint: env
+          .komodo_cli_logging_otlp_metrics_endpoint
+          .unwrap_or(config.cli_logging.otlp_metrics_endpoint),
+        otlp_high_cardinality_attributes: env
+          .komodo_cli_logging_otlp_high_cardinality_attributes
+          .unwrap_or(
+            config.cli_logging.otlp_high_cardinality_attributes,
+          ),
         opentelemetry_service_name: env
           .komodo_cli_logging_opentelemetry_service_name
           .unwrap_or(config.cli_logging.opentelemetry_service_name),
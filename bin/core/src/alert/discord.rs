@@ -77,6 +77,21 @@ pub async fn send_alert(
         "{level} | **{name}**{region} cpu usage at **{percentage:.1}%**\n{link}"
       )
     }
+    AlertData::ServerStatsAnomaly {
+      id,
+      name,
+      region,
+      metric,
+      percentage,
+      baseline,
+      z_score,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | **{name}**{region} {metric} usage spiked to **{percentage:.1}%** 📈\nbaseline: **{baseline:.1}%** ({z_score:.1} std dev above)\n{link}"
+      )
+    }
     AlertData::ServerMem {
       id,
       name,
@@ -120,6 +135,45 @@ pub async fn send_alert(
         "📦 Deployment **{name}** is now **{to}**\nserver: **{server_name}**\nprevious: **{from}**\n{link}"
       )
     }
+    AlertData::ContainerOomKilled {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      exit_code,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      let exit_code = fmt_exit_code(exit_code);
+      format!(
+        "{level} | 💥 Deployment **{name}** was killed by the OOM killer\nserver: **{server_name}**{exit_code}\n{link}"
+      )
+    }
+    AlertData::DeploymentConfigDrift {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      drift,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      let drift = drift.join("\n");
+      format!(
+        "{level} | 🔀 Deployment **{name}** has drifted from its config\nserver: **{server_name}**\n{drift}\n{link}"
+      )
+    }
+    AlertData::ContainerCrashLoop {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      restarts,
+      window,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      format!(
+        "{level} | 🔁 Deployment **{name}** is crash looping\nserver: **{server_name}**\nrestarts: **{restarts}** in **{window}**\n{link}"
+      )
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -229,6 +283,17 @@ pub async fn send_alert(
         "{level} | **{name}** ({resource_type}) | Scheduled run started 🕝\n{link}"
       )
     }
+    AlertData::ApiKeyExpiring {
+      name,
+      user_id: _user_id,
+      username,
+      expires,
+    } => {
+      let expires_in = fmt_expires_in(expires);
+      format!(
+        "{level} | 🔑 Api key **{name}** expires in **{expires_in}**\nuser: **{username}**"
+      )
+    }
     AlertData::Custom { message, details } => {
       format!(
         "{level} | {message}{}",
@@ -1,5 +1,6 @@
 use ::slack::types::Block;
 use anyhow::{Context, anyhow};
+use async_timing_util::ONE_DAY_MS;
 use database::mungos::{find::find_collect, mongodb::bson::doc};
 use derive_variants::ExtractVariant;
 use futures::future::join_all;
@@ -223,6 +224,23 @@ fn fmt_docker_container_state(state: &DeploymentState) -> String {
   }
 }
 
+fn fmt_expires_in(expires: &i64) -> String {
+  let days =
+    (expires - komodo_timestamp()).max(0) / ONE_DAY_MS as i64;
+  match days {
+    0 => String::from("less than a day"),
+    1 => String::from("1 day"),
+    days => format!("{days} days"),
+  }
+}
+
+fn fmt_exit_code(exit_code: &Option<i64>) -> String {
+  match exit_code {
+    Some(code) => format!("\nexit code: {code}"),
+    None => String::new(),
+  }
+}
+
 fn fmt_stack_state(state: &StackState) -> String {
   match state {
     StackState::Running => String::from("Running ▶️"),
@@ -321,6 +339,21 @@ fn standard_alert_content(alert: &Alert) -> String {
         "{level} | {name}{region} cpu usage at {percentage:.1}%\n{link}",
       )
     }
+    AlertData::ServerStatsAnomaly {
+      id,
+      name,
+      region,
+      metric,
+      percentage,
+      baseline,
+      z_score,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | {name}{region} {metric} usage spiked to {percentage:.1}%\nbaseline: {baseline:.1}% ({z_score:.1} std dev above)\n{link}",
+      )
+    }
     AlertData::ServerMem {
       id,
       name,
@@ -364,6 +397,45 @@ fn standard_alert_content(alert: &Alert) -> String {
         "📦Deployment {name} is now {to_state}\nserver: {server_name}\nprevious: {from}\n{link}",
       )
     }
+    AlertData::ContainerOomKilled {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      exit_code,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      let exit_code = fmt_exit_code(exit_code);
+      format!(
+        "{level} | 💥Deployment {name} was killed by the OOM killer\nserver: {server_name}{exit_code}\n{link}",
+      )
+    }
+    AlertData::DeploymentConfigDrift {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      drift,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      let drift = drift.join("\n");
+      format!(
+        "{level} | 🔀Deployment {name} has drifted from its config\nserver: {server_name}\n{drift}\n{link}",
+      )
+    }
+    AlertData::ContainerCrashLoop {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      restarts,
+      window,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      format!(
+        "{level} | 🔁Deployment {name} is crash looping\nserver: {server_name}\nrestarts: {restarts} in {window}\n{link}",
+      )
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -473,6 +545,17 @@ fn standard_alert_content(alert: &Alert) -> String {
         "{level} | {name} ({resource_type}) | Scheduled run started 🕝\n{link}"
       )
     }
+    AlertData::ApiKeyExpiring {
+      name,
+      user_id: _user_id,
+      username,
+      expires,
+    } => {
+      let expires_in = fmt_expires_in(expires);
+      format!(
+        "{level} | 🔑 Api key {name} expires in {expires_in}\nuser: {username}",
+      )
+    }
     AlertData::Custom { message, details } => {
       format!(
         "{level} | {message}{}",
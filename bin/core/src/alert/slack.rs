@@ -135,6 +135,31 @@ pub async fn send_alert(
         }
       }
     }
+    AlertData::ServerStatsAnomaly {
+      id,
+      name,
+      region,
+      metric,
+      percentage,
+      baseline,
+      z_score,
+    } => {
+      let region = fmt_region(region);
+      let text = format!(
+        "{level} | *{name}*{region} {metric} usage spiked to *{percentage:.1}%* 📈"
+      );
+      let blocks = vec![
+        Block::header(level),
+        Block::section(format!(
+          "*{name}*{region} {metric} usage spiked to *{percentage:.1}%* 📈\nbaseline: *{baseline:.1}%* ({z_score:.1} std dev above)"
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Server,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::ServerMem {
       id,
       name,
@@ -258,6 +283,70 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::ContainerOomKilled {
+      id,
+      name,
+      server_name,
+      exit_code,
+      ..
+    } => {
+      let text = format!(
+        "{level} | 💥 Container *{name}* was killed by the OOM killer"
+      );
+      let exit_code = fmt_exit_code(exit_code);
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!("server: {server_name}{exit_code}")),
+        Block::section(resource_link(
+          ResourceTargetVariant::Deployment,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::DeploymentConfigDrift {
+      id,
+      name,
+      server_name,
+      drift,
+      ..
+    } => {
+      let text = format!(
+        "{level} | 🔀 Deployment *{name}* has drifted from its config"
+      );
+      let drift = drift.join("\n");
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!("server: {server_name}\n{drift}")),
+        Block::section(resource_link(
+          ResourceTargetVariant::Deployment,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::ContainerCrashLoop {
+      id,
+      name,
+      server_name,
+      restarts,
+      window,
+      ..
+    } => {
+      let text =
+        format!("{level} | 🔁 Container *{name}* is crash looping");
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!(
+          "server: {server_name}\nrestarts: {restarts} in {window}"
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Deployment,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -458,6 +547,22 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::ApiKeyExpiring {
+      name,
+      username,
+      expires,
+      ..
+    } => {
+      let expires_in = fmt_expires_in(expires);
+      let text = format!(
+        "{level} | 🔑 Api key *{name}* expires in {expires_in}"
+      );
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!("user: *{username}*")),
+      ];
+      (text, blocks.into())
+    }
     AlertData::Custom { message, details } => {
       let text = format!("{level} | {message}");
       let blocks =
@@ -1,5 +1,7 @@
 use std::{sync::OnceLock, time::Instant};
 
+use anyhow::Context;
+
 use axum::{Router, extract::Path, http::HeaderMap, routing::post};
 use derive_variants::{EnumVariants, ExtractVariant};
 use komodo_client::{api::auth::*, entities::user::User};
@@ -14,13 +16,19 @@ use uuid::Uuid;
 
 use crate::{
   auth::{
-    get_user_id_from_headers,
+    auth_jwt_get_user,
+    events::LoginContext,
+    get_user_from_headers,
     github::{self, client::github_oauth_client},
     google::{self, client::google_oauth_client},
-    oidc::{self, client::oidc_client},
+    oidc::{
+      self,
+      client::{
+        DEFAULT_PROVIDER, oidc_provider, oidc_provider_names,
+      },
+    },
   },
   config::core_config,
-  helpers::query::get_user,
   state::jwt_client,
 };
 
@@ -29,6 +37,7 @@ use super::Variant;
 #[derive(Default)]
 pub struct AuthArgs {
   pub headers: HeaderMap,
+  pub context: LoginContext,
 }
 
 #[typeshare]
@@ -47,6 +56,7 @@ pub enum AuthRequest {
   LoginLocalUser(LoginLocalUser),
   ExchangeForJwt(ExchangeForJwt),
   GetUser(GetUser),
+  RefreshJwt(RefreshJwt),
 }
 
 pub fn router() -> Router {
@@ -77,6 +87,7 @@ pub fn router() -> Router {
 }
 
 async fn variant_handler(
+  context: LoginContext,
   headers: HeaderMap,
   Path(Variant { variant }): Path<Variant>,
   Json(params): Json<serde_json::Value>,
@@ -85,11 +96,16 @@ async fn variant_handler(
     "type": variant,
     "params": params,
   }))?;
-  handler(headers, Json(req)).await
+  handler(context, headers, Json(req)).await
 }
 
-#[instrument(name = "AuthHandler", level = "debug", skip(headers))]
+#[instrument(
+  name = "AuthHandler",
+  level = "debug",
+  skip(context, headers)
+)]
 async fn handler(
+  context: LoginContext,
   headers: HeaderMap,
   Json(request): Json<AuthRequest>,
 ) -> serror::Result<axum::response::Response> {
@@ -99,7 +115,7 @@ async fn handler(
     "/auth request {req_id} | METHOD: {:?}",
     request.extract_variant()
   );
-  let res = request.resolve(&AuthArgs { headers }).await;
+  let res = request.resolve(&AuthArgs { headers, context }).await;
   if let Err(e) = &res {
     debug!("/auth request {req_id} | error: {:#}", e.error);
   }
@@ -118,7 +134,8 @@ fn login_options_reponse() -> &'static GetLoginOptionsResponse {
       local: config.local_auth,
       github: github_oauth_client().is_some(),
       google: google_oauth_client().is_some(),
-      oidc: oidc_client().load().is_some(),
+      oidc: oidc_provider(DEFAULT_PROVIDER).is_some(),
+      oidc_providers: oidc_provider_names(),
       registration_disabled: config.disable_user_registration,
     }
   })
@@ -130,7 +147,7 @@ impl Resolve<AuthArgs> for GetLoginOptions {
     self,
     _: &AuthArgs,
   ) -> serror::Result<GetLoginOptionsResponse> {
-    Ok(*login_options_reponse())
+    Ok(login_options_reponse().clone())
   }
 }
 
@@ -151,13 +168,38 @@ impl Resolve<AuthArgs> for GetUser {
   #[instrument(name = "GetUser", level = "debug", skip(self))]
   async fn resolve(
     self,
-    AuthArgs { headers }: &AuthArgs,
+    AuthArgs { headers, .. }: &AuthArgs,
   ) -> serror::Result<User> {
-    let user_id = get_user_id_from_headers(headers)
+    get_user_from_headers(headers)
+      .await
+      .status_code(StatusCode::UNAUTHORIZED)
+  }
+}
+
+impl Resolve<AuthArgs> for RefreshJwt {
+  #[instrument(name = "RefreshJwt", level = "debug", skip(self))]
+  async fn resolve(
+    self,
+    AuthArgs { headers, .. }: &AuthArgs,
+  ) -> serror::Result<RefreshJwtResponse> {
+    let jwt = headers
+      .get("authorization")
+      .context("Must attach AUTHORIZATION header with jwt")
+      .and_then(|jwt| jwt.to_str().context("jwt is not str"))
+      .status_code(StatusCode::UNAUTHORIZED)?;
+    let user = auth_jwt_get_user(jwt)
       .await
       .status_code(StatusCode::UNAUTHORIZED)?;
-    get_user(&user_id)
+    let session = jwt_client()
+      .decode(jwt)
+      .status_code(StatusCode::UNAUTHORIZED)?
+      .session;
+    oidc::session::refresh_session(&user.id, &session)
       .await
-      .status_code(StatusCode::UNAUTHORIZED)
+      .status_code(StatusCode::UNAUTHORIZED)?;
+    jwt_client()
+      .encode_session(user.id, session)
+      .context("failed to generate jwt")
+      .map_err(Into::into)
   }
 }
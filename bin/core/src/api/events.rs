@@ -0,0 +1,220 @@
+use std::{collections::HashSet, convert::Infallible};
+
+use anyhow::Context;
+use axum::{
+  Extension, Router,
+  extract::Query,
+  http::HeaderMap,
+  middleware,
+  response::sse::{Event, KeepAlive, Sse},
+  routing::get,
+};
+use derive_variants::ExtractVariant;
+use futures::{Stream, stream};
+use komodo_client::{
+  api::{ResourceEvent, ResourceEventKind, ResourceEventsQuery},
+  entities::{
+    ResourceTarget, ResourceTargetVariant,
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::Deployment,
+    procedure::Procedure,
+    repo::Repo,
+    server::Server,
+    stack::Stack,
+    sync::ResourceSync,
+    update::{UpdateListItem, UpdateStatus},
+    user::User,
+  },
+  parsers::parse_string_list,
+};
+use reqwest::StatusCode;
+use serror::AddStatusCode;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+
+use crate::{
+  auth::{auth_request, recheck_request_auth},
+  helpers::channel::update_channel,
+  permission::get_resource_ids_for_user,
+  resource::KomodoResource,
+  ws::user_can_see_update,
+};
+
+pub fn router() -> Router {
+  Router::new()
+    .route("/", get(handler))
+    .layer(middleware::from_fn(auth_request))
+}
+
+struct EventFilters {
+  resource_types: Vec<ResourceTargetVariant>,
+  kinds: Vec<ResourceEventKind>,
+}
+
+impl EventFilters {
+  fn matches(&self, event: &ResourceEvent) -> bool {
+    (self.resource_types.is_empty()
+      || self
+        .resource_types
+        .contains(&event.target.extract_variant()))
+      && (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+  }
+}
+
+/// The resources the user can see. Once a resource is deleted
+/// its permissions are gone, so deletions are only sent
+/// for resources the user could see before.
+#[derive(Default)]
+struct VisibleResources {
+  /// The resource types the user can see all of.
+  all: HashSet<ResourceTargetVariant>,
+  targets: HashSet<ResourceTarget>,
+}
+
+impl VisibleResources {
+  async fn load(user: &User) -> anyhow::Result<VisibleResources> {
+    let mut visible = VisibleResources::default();
+    visible.add::<Server>(user).await?;
+    visible.add::<Stack>(user).await?;
+    visible.add::<Deployment>(user).await?;
+    visible.add::<Build>(user).await?;
+    visible.add::<Repo>(user).await?;
+    visible.add::<Procedure>(user).await?;
+    visible.add::<Action>(user).await?;
+    visible.add::<Builder>(user).await?;
+    visible.add::<Alerter>(user).await?;
+    visible.add::<ResourceSync>(user).await?;
+    Ok(visible)
+  }
+
+  async fn add<T: KomodoResource>(
+    &mut self,
+    user: &User,
+  ) -> anyhow::Result<()> {
+    match get_resource_ids_for_user::<T>(user).await? {
+      None => {
+        self.all.insert(T::resource_type());
+      }
+      Some(ids) => {
+        self.targets.extend(ids.into_iter().map(T::resource_target))
+      }
+    }
+    Ok(())
+  }
+
+  /// Checks the user can see the event, keeping track of
+  /// the resources they have seen events for since the stream started.
+  async fn can_see(
+    &mut self,
+    user: &User,
+    event: &ResourceEvent,
+  ) -> bool {
+    if event.kind == ResourceEventKind::Deleted {
+      let seen = self.targets.remove(&event.target);
+      return seen
+        || user.admin
+        || self.all.contains(&event.target.extract_variant());
+    }
+    if user_can_see_update(user, &event.target).await.is_err() {
+      return false;
+    }
+    self.targets.insert(event.target.clone());
+    true
+  }
+}
+
+/// Streams resource lifecycle events as server-sent events,
+/// for tooling which can't use the update websocket.
+/// Events are sent as their Update completes,
+/// only for resources the user has permission on.
+#[instrument(
+  name = "ResourceEvents",
+  level = "debug",
+  skip(user, headers)
+)]
+async fn handler(
+  Extension(user): Extension<User>,
+  headers: HeaderMap,
+  Query(query): Query<ResourceEventsQuery>,
+) -> serror::Result<Sse<impl Stream<Item = Result<Event, Infallible>>>>
+{
+  let filters = EventFilters {
+    resource_types: parse_string_list(&query.resource_types)
+      .iter()
+      .map(|t| t.parse())
+      .collect::<Result<_, _>>()
+      .context("Invalid resource type")
+      .status_code(StatusCode::BAD_REQUEST)?,
+    kinds: parse_string_list(&query.kinds)
+      .iter()
+      .map(|k| k.parse())
+      .collect::<Result<_, _>>()
+      .context("Invalid event kind")
+      .status_code(StatusCode::BAD_REQUEST)?,
+  };
+  let visible = VisibleResources::load(&user)
+    .await
+    .context("Failed to get the resources the user can see")?;
+  let receiver = update_channel().receiver.resubscribe();
+  let stream = stream::unfold(
+    (receiver, headers, filters, visible),
+    |(mut receiver, headers, filters, mut visible)| async move {
+      let event =
+        next_event(&mut receiver, &headers, &filters, &mut visible)
+          .await?;
+      Some((Ok(event), (receiver, headers, filters, visible)))
+    },
+  );
+  Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Waits for the next event passing the filters.
+/// Returns None to end the stream, eg if the user is disabled
+/// or their credentials are no longer valid.
+async fn next_event(
+  receiver: &mut Receiver<UpdateListItem>,
+  headers: &HeaderMap,
+  filters: &EventFilters,
+  visible: &mut VisibleResources,
+) -> Option<Event> {
+  loop {
+    let update = match receiver.recv().await {
+      Ok(update) => update,
+      // Slow consumers miss events rather than end the stream.
+      Err(RecvError::Lagged(_)) => continue,
+      Err(RecvError::Closed) => return None,
+    };
+    if update.status != UpdateStatus::Complete
+      || matches!(update.target, ResourceTarget::System(_))
+    {
+      continue;
+    }
+    let Some(kind) =
+      ResourceEventKind::from_operation(update.operation)
+    else {
+      continue;
+    };
+    let event = ResourceEvent {
+      kind,
+      target: update.target,
+      operation: update.operation,
+      success: update.success,
+      update_id: update.id,
+      ts: update.start_ts,
+    };
+    if !filters.matches(&event) {
+      continue;
+    }
+    // Verify the user and credentials are still valid before every event.
+    let user = recheck_request_auth(headers).await.ok()?;
+    if !visible.can_see(&user, &event).await {
+      continue;
+    }
+    match Event::default().event(kind.as_ref()).json_data(&event) {
+      Ok(event) => return Some(event),
+      Err(e) => warn!("Failed to serialize resource event | {e:?}"),
+    }
+  }
+}
@@ -3,14 +3,16 @@ use std::{
   path::{Path, PathBuf},
   str::FromStr,
   sync::OnceLock,
+  time::Duration,
 };
 
 use anyhow::Context;
-use command::run_komodo_command;
+use command::run_komodo_command_with_timeout;
 use config::merge_objects;
 use database::mungos::{
   by_id::update_one_by_id, mongodb::bson::to_document,
 };
+use formatting::format_serror;
 use interpolate::Interpolator;
 use komodo_client::{
   api::{
@@ -19,12 +21,12 @@ use komodo_client::{
   },
   entities::{
     FileFormat, JsonObject,
-    action::Action,
+    action::{ACTION_OUTPUT_LOG_STAGE, Action},
     alert::{Alert, AlertData, SeverityLevel},
     config::core::CoreConfig,
     komodo_timestamp,
     permission::PermissionLevel,
-    update::Update,
+    update::{Log, Update},
     user::action_user,
   },
   parsers::parse_key_value_list,
@@ -39,7 +41,7 @@ use crate::{
   helpers::{
     query::{VariablesAndSecrets, get_variables_and_secrets},
     random_string,
-    update::update_update,
+    update::{truncate_update_logs, update_update},
   },
   permission::get_check_permissions,
   resource::refresh_action_state_cache,
@@ -166,14 +168,29 @@ impl Resolve<ExecuteArgs> for RunAction {
       ""
     };
 
-    let mut res = run_komodo_command(
+    let memory_flag = if action.config.memory_limit_mb > 0 {
+      format!(
+        " --v8-flags=--max-old-space-size={}",
+        action.config.memory_limit_mb
+      )
+    } else {
+      String::new()
+    };
+
+    let timeout = (action.config.timeout_seconds > 0).then(|| {
+      Duration::from_secs(action.config.timeout_seconds as u64)
+    });
+
+    let mut res = run_komodo_command_with_timeout(
       // Keep this stage name as is, the UI will find the latest update log by matching the stage name
       "Execute Action",
       None,
+      // 'exec' so the timeout kills deno itself, not just the shell.
       format!(
-        "deno run --allow-all{https_cert_flag}{reload} {}",
+        "exec deno run --allow-all{https_cert_flag}{reload}{memory_flag} {}",
         path.display()
       ),
+      timeout,
     )
     .await;
 
@@ -196,8 +213,17 @@ impl Resolve<ExecuteArgs> for RunAction {
       );
     };
 
+    let output = extract_output(&mut res.stdout);
+    let limit_log = limit_breach_log(&res, timeout, &action);
     update.logs.push(res);
+    if let Some(log) = limit_log {
+      update.logs.push(log);
+    }
+    if let Some(output) = output {
+      update.logs.push(output_log(&output));
+    }
     update.finalize();
+    truncate_update_logs(&mut update);
 
     // Need to manually update the update before cache refresh,
     // and before broadcast with update_update.
@@ -240,6 +266,76 @@ impl Resolve<ExecuteArgs> for RunAction {
   }
 }
 
+/// Removes the output marker lines from stdout,
+/// returning the raw output of the last one.
+fn extract_output(stdout: &mut String) -> Option<String> {
+  let mut output = None;
+  let mut lines = Vec::new();
+  for line in stdout.lines() {
+    if let Some(out) = line.strip_prefix(OUTPUT_MARKER) {
+      output = Some(out.to_string());
+    } else {
+      lines.push(line);
+    }
+  }
+  if output.is_some() {
+    *stdout = lines.join("\n");
+  }
+  output
+}
+
+fn output_log(output: &str) -> Log {
+  match serde_json::from_str::<serde_json::Value>(output)
+    .context("Action output is not valid JSON")
+    .and_then(|output| {
+      serde_json::to_string_pretty(&output)
+        .context("Failed to serialize Action output")
+    }) {
+    Ok(output) => Log::simple(ACTION_OUTPUT_LOG_STAGE, output),
+    Err(e) => {
+      Log::error(ACTION_OUTPUT_LOG_STAGE, format_serror(&e.into()))
+    }
+  }
+}
+
+/// Produces an error log if the action was killed
+/// for breaching its timeout or memory limit.
+fn limit_breach_log(
+  res: &Log,
+  timeout: Option<Duration>,
+  action: &Action,
+) -> Option<Log> {
+  if res.success {
+    return None;
+  }
+  let duration = (res.end_ts - res.start_ts).max(0) as u64;
+  if let Some(timeout) = timeout
+    && Duration::from_millis(duration) >= timeout
+  {
+    return Log::error(
+      "Action Limits",
+      format!(
+        "Action was killed after exceeding the timeout of {} seconds",
+        action.config.timeout_seconds
+      ),
+    )
+    .into();
+  }
+  if action.config.memory_limit_mb > 0
+    && res.stderr.contains("heap out of memory")
+  {
+    return Log::error(
+      "Action Limits",
+      format!(
+        "Action was killed after exceeding the memory limit of {} MB",
+        action.config.memory_limit_mb
+      ),
+    )
+    .into();
+  }
+  None
+}
+
 async fn interpolate(
   contents: &mut String,
   update: &mut Update,
@@ -264,6 +360,9 @@ async fn interpolate(
   Ok(interpolator.secret_replacers)
 }
 
+/// Prefixes the stdout line carrying the Action output.
+const OUTPUT_MARKER: &str = "__KOMODO_ACTION_OUTPUT__";
+
 fn full_contents(
   contents: &str,
   // Pre-serialized to JSON string.
@@ -297,6 +396,13 @@ const TOML = {{
 
 const ARGS = {args};
 
+let __OUTPUT__: unknown = undefined;
+
+/** Set the structured output of the Action, available on the Update and to Procedures. */
+function setOutput(output: unknown) {{
+  __OUTPUT__ = output;
+}}
+
 const komodo = KomodoClient('{base_url}', {{
   type: 'api-key',
   params: {{ key: '{key}', secret: '{secret}' }}
@@ -309,6 +415,11 @@ console.log('🦎 Action completed successfully 🦎');
 }}
 
 main()
+.then(() => {{
+  if (__OUTPUT__ !== undefined) {{
+    console.log('{OUTPUT_MARKER}' + JSON.stringify(__OUTPUT__));
+  }}
+}})
 .catch(error => {{
   console.error('🚨 Action exited early with errors 🚨')
   if (error.status !== undefined && error.result !== undefined) {{
@@ -424,3 +535,41 @@ fn parse_action_arguments(
       .context("Failed to parse Json to action args"),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn extract_output_removes_marker_lines() {
+    let mut stdout = format!(
+      "starting\n{OUTPUT_MARKER}{{\"a\":1}}\n{OUTPUT_MARKER}{{\"a\":2}}\ndone"
+    );
+    let output = extract_output(&mut stdout);
+    assert_eq!(output.as_deref(), Some("{\"a\":2}"));
+    assert_eq!(stdout, "starting\ndone");
+  }
+
+  #[test]
+  fn extract_output_leaves_stdout_without_marker() {
+    let mut stdout = String::from("starting\ndone\n");
+    assert_eq!(extract_output(&mut stdout), None);
+    assert_eq!(stdout, "starting\ndone\n");
+  }
+
+  #[test]
+  fn output_log_pretty_prints_json() {
+    let log = output_log("{\"tag\":\"v1\"}");
+    assert_eq!(log.stage, ACTION_OUTPUT_LOG_STAGE);
+    assert!(log.success);
+    assert_eq!(log.stdout, "{\n  \"tag\": \"v1\"\n}");
+  }
+
+  #[test]
+  fn output_log_errors_on_invalid_json() {
+    let log = output_log("{not json");
+    assert!(!log.success);
+    assert!(log.stdout.is_empty());
+    assert!(log.stderr.contains("Action output is not valid JSON"));
+  }
+}
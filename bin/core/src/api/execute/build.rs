@@ -185,7 +185,14 @@ impl Resolve<ExecuteArgs> for RunBuild {
       }
     });
 
+    let clone_repo = !build.config.files_on_host
+      && (!build.config.repo.is_empty()
+        || !build.config.linked_repo.is_empty());
+    let total_stages = if clone_repo { 3 } else { 2 };
+
     // GET BUILDER PERIPHERY
+    update.set_progress(1, total_stages, "Get Builder");
+    update_update(update.clone()).await?;
     let (periphery, cleanup_data) = match get_builder_periphery(
       build.name.clone(),
       Some(build.config.version),
@@ -229,11 +236,10 @@ impl Resolve<ExecuteArgs> for RunBuild {
       Default::default()
     };
 
-    let commit_message = if !build.config.files_on_host
-      && (!build.config.repo.is_empty()
-        || !build.config.linked_repo.is_empty())
-    {
+    let commit_message = if clone_repo {
       // PULL OR CLONE REPO
+      update.set_progress(2, total_stages, "Clone Repo");
+      update_update(update.clone()).await?;
       let res = tokio::select! {
         res = periphery
           .request(api::git::PullOrCloneRepo {
@@ -283,6 +289,8 @@ impl Resolve<ExecuteArgs> for RunBuild {
 
     if all_logs_success(&update.logs) {
       // RUN BUILD
+      update.set_progress(total_stages, total_stages, "Build");
+      update_update(update.clone()).await?;
       let res = tokio::select! {
         res = periphery
           .request(api::build::Build {
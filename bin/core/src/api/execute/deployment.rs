@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::{sync::OnceLock, time::Duration};
 
 use anyhow::{Context, anyhow};
 use cache::TimeoutCache;
@@ -7,24 +7,33 @@ use interpolate::Interpolator;
 use komodo_client::{
   api::execute::*,
   entities::{
-    Version,
+    TerminationSignal, Version, all_logs_success,
     build::{Build, ImageRegistryConfig},
     deployment::{
-      Deployment, DeploymentImage, extract_registry_domain,
+      Deployment, DeploymentConfig, DeploymentImage, DeploymentState,
+      DeploymentStrategy, RestartMode, extract_registry_domain,
     },
-    komodo_timestamp, optional_string,
+    docker::container::{
+      Container, ContainerState, ContainerStateStatusEnum,
+      HealthStatusEnum, HostConfig, RestartPolicyNameEnum,
+    },
+    komodo_timestamp,
+    logger::LogLevel,
+    optional_string,
     permission::PermissionLevel,
+    repo::Repo,
     server::Server,
     update::{Log, Update},
     user::User,
   },
 };
-use periphery_client::api;
+use periphery_client::{PeripheryClient, api};
 use resolver_api::Resolve;
 
 use crate::{
   helpers::{
-    periphery_client,
+    drift::deployment_config_drift,
+    git_token, periphery_client,
     query::{VariablesAndSecrets, get_variables_and_secrets},
     registry_token,
     update::update_update,
@@ -32,7 +41,7 @@ use crate::{
   monitor::update_cache_for_server,
   permission::get_check_permissions,
   resource,
-  state::action_states,
+  state::{action_states, deployment_status_cache},
 };
 
 use super::{ExecuteArgs, ExecuteRequest};
@@ -203,25 +212,46 @@ impl Resolve<ExecuteArgs> for Deploy {
     update.version = version;
     update_update(update.clone()).await?;
 
-    match periphery_client(&server)?
-      .request(api::container::Deploy {
-        deployment,
-        stop_signal: self.stop_signal,
-        stop_time: self.stop_time,
-        registry_token,
-        replacers: secret_replacers.into_iter().collect(),
-      })
-      .await
+    let periphery = periphery_client(&server)?;
+
+    if let Err(e) = resolve_env_files(
+      &periphery,
+      &mut deployment.config,
+      &mut update,
+    )
+    .await
     {
-      Ok(log) => update.logs.push(log),
-      Err(e) => {
-        update.push_error_log(
-          "Deploy Container",
-          format_serror(&e.into()),
-        );
-      }
+      update.push_error_log("Env Files", format_serror(&e.into()));
+      update.finalize();
+      update_update(update.clone()).await?;
+      return Ok(update);
+    }
+
+    let deploy = api::container::Deploy {
+      deployment,
+      stop_signal: self.stop_signal,
+      stop_time: self.stop_time,
+      registry_token,
+      replacers: secret_replacers.into_iter().collect(),
     };
 
+    let name = deploy.deployment.name.clone();
+    let config = deploy.deployment.config.clone();
+
+    match config.deploy_strategy {
+      DeploymentStrategy::Recreate => {
+        recreate_deploy(&periphery, deploy, &mut update).await
+      }
+      DeploymentStrategy::StartFirst => {
+        start_first_deploy(&periphery, deploy, &mut update).await
+      }
+    }
+
+    if all_logs_success(&update.logs) {
+      post_deploy_checks(&periphery, &name, &config, &mut update)
+        .await;
+    }
+
     update_cache_for_server(&server, true).await;
 
     update.finalize();
@@ -231,6 +261,474 @@ impl Resolve<ExecuteArgs> for Deploy {
   }
 }
 
+/// Inspects the deployed container to confirm docker
+/// actually applied the deployment configuration.
+/// Mismatches are reported as warnings, and don't fail the deploy.
+async fn post_deploy_checks(
+  periphery: &PeripheryClient,
+  name: &str,
+  config: &DeploymentConfig,
+  update: &mut Update,
+) {
+  let container = match periphery
+    .request(api::container::InspectContainer {
+      name: name.to_string(),
+    })
+    .await
+  {
+    Ok(container) => container,
+    Err(e) => {
+      update.push_simple_log(
+        "Post Deploy Checks",
+        format_serror(
+          &e.context(
+            "Failed to inspect container, skipping post deploy checks",
+          )
+          .into(),
+        ),
+      );
+      return;
+    }
+  };
+  let host_config = container.host_config.unwrap_or_default();
+  check_restart_policy(config.restart, &host_config, update);
+  report_resource_limits(&config.extra_args, &host_config, update);
+}
+
+fn check_restart_policy(
+  restart: RestartMode,
+  host_config: &HostConfig,
+  update: &mut Update,
+) {
+  let expected = RestartPolicyNameEnum::from(restart);
+  let actual = host_config
+    .restart_policy
+    .as_ref()
+    .map(|policy| policy.name)
+    .unwrap_or_default();
+  // Docker may report an empty policy name when no policy is set.
+  let matches = expected == actual
+    || (expected == RestartPolicyNameEnum::No
+      && actual == RestartPolicyNameEnum::Empty);
+  if !matches {
+    update.logs.push(Log::warn(
+      "Restart Policy",
+      format!(
+        "WARNING: Container restart policy is {actual:?}, but the Deployment is configured with '{restart}'. The container may not be restarted as expected. Check 'extra_args' for a conflicting '--restart' flag."
+      ),
+    ));
+  }
+}
+
+const BYTES_PER_MB: f64 = 1048576.0;
+
+/// Reports the effective CPU / memory limits docker applied to the container.
+/// Docker will silently drop limits when the host lacks cgroup support
+/// for them, so warn when limits requested in `extra_args` are missing.
+fn report_resource_limits(
+  extra_args: &[String],
+  host_config: &HostConfig,
+  update: &mut Update,
+) {
+  let requested_memory =
+    extra_arg_value(extra_args, &["--memory", "-m"]);
+  let requested_cpus = extra_arg_value(extra_args, &["--cpus"]);
+
+  let memory = host_config.memory.filter(|memory| *memory > 0);
+  let cpus = match (
+    host_config.nano_cpus,
+    host_config.cpu_quota,
+    host_config.cpu_period,
+  ) {
+    (Some(nano_cpus), _, _) if nano_cpus > 0 => {
+      Some(nano_cpus as f64 / 1_000_000_000.0)
+    }
+    (_, Some(quota), Some(period)) if quota > 0 && period > 0 => {
+      Some(quota as f64 / period as f64)
+    }
+    _ => None,
+  };
+
+  if requested_memory.is_none()
+    && requested_cpus.is_none()
+    && memory.is_none()
+    && cpus.is_none()
+  {
+    return;
+  }
+
+  let mut lines = vec![
+    format!(
+      "Memory: {}",
+      memory
+        .map(|memory| format!(
+          "{:.1} MB",
+          memory as f64 / BYTES_PER_MB
+        ))
+        .unwrap_or_else(|| String::from("unlimited"))
+    ),
+    format!(
+      "CPUs: {}",
+      cpus
+        .map(|cpus| format!("{cpus:.2}"))
+        .unwrap_or_else(|| String::from("unlimited"))
+    ),
+  ];
+  let mut level = LogLevel::Info;
+
+  if let Some(requested) = requested_memory
+    && memory.is_none()
+  {
+    level = LogLevel::Warn;
+    lines.push(format!(
+      "WARNING: Requested memory limit '{requested}' was not applied. The host may lack cgroup support for memory limits."
+    ));
+  }
+  if let Some(requested) = requested_cpus
+    && cpus.is_none()
+  {
+    level = LogLevel::Warn;
+    lines.push(format!(
+      "WARNING: Requested CPU limit '{requested}' was not applied. The host may lack cgroup support for CPU limits."
+    ));
+  }
+
+  update.logs.push(
+    Log::simple("Resource Limits", lines.join("\n"))
+      .with_level(level),
+  );
+}
+
+/// Finds the value given to any of the flags in the extra args,
+/// supporting both `--flag value` and `--flag=value` forms.
+fn extra_arg_value<'a>(
+  extra_args: &'a [String],
+  flags: &[&str],
+) -> Option<&'a str> {
+  let mut args = extra_args
+    .iter()
+    .flat_map(|arg| arg.split_whitespace())
+    .peekable();
+  while let Some(arg) = args.next() {
+    for flag in flags {
+      if arg == *flag {
+        return args.peek().copied();
+      }
+      if let Some(value) = arg
+        .strip_prefix(flag)
+        .and_then(|rest| rest.strip_prefix('='))
+      {
+        return Some(value);
+      }
+    }
+  }
+  None
+}
+
+/// Pulls the linked repo on the Deployment's server,
+/// and resolves the relative env files against the repo folder.
+async fn resolve_env_files(
+  periphery: &PeripheryClient,
+  config: &mut DeploymentConfig,
+  update: &mut Update,
+) -> anyhow::Result<()> {
+  if config.linked_repo.is_empty() {
+    if let Some(path) =
+      config.env_files.iter().find(|path| !path.starts_with('/'))
+    {
+      return Err(anyhow!(
+        "Env file {path} is relative, but no Repo is linked"
+      ));
+    }
+    return Ok(());
+  }
+
+  let mut repo = resource::get::<Repo>(&config.linked_repo).await?;
+  let git_token = git_token(
+    &repo.config.git_provider,
+    &repo.config.git_account,
+    |https| repo.config.git_https = https,
+  )
+  .await
+  .with_context(|| {
+    format!(
+      "Failed to get git token. Stopping run. | {} | {}",
+      repo.config.git_provider, repo.config.git_account
+    )
+  })?;
+
+  let res = periphery
+    .request(api::git::PullOrCloneRepo {
+      args: (&repo).into(),
+      git_token,
+      environment: Default::default(),
+      env_file_path: Default::default(),
+      on_clone: Default::default(),
+      on_pull: Default::default(),
+      skip_secret_interp: Default::default(),
+      replacers: Default::default(),
+    })
+    .await
+    .context("Failed to pull linked repo")?;
+
+  let success = all_logs_success(&res.res.logs);
+  update.logs.extend(res.res.logs);
+  if !success {
+    return Err(anyhow!("Failed to pull linked repo {}", repo.name));
+  }
+
+  for path in &mut config.env_files {
+    if !path.starts_with('/') {
+      *path = res.res.path.join(&path).display().to_string();
+    }
+  }
+
+  Ok(())
+}
+
+/// The container operations used to deploy,
+/// so the deploy strategies can be tested without periphery.
+trait ContainerRuntime {
+  async fn container_state(
+    &self,
+    name: &str,
+  ) -> anyhow::Result<ContainerState>;
+  async fn deploy(
+    &self,
+    deploy: api::container::Deploy,
+  ) -> anyhow::Result<Log>;
+  async fn remove_container(
+    &self,
+    name: String,
+    signal: TerminationSignal,
+    time: i32,
+  ) -> anyhow::Result<Log>;
+  async fn rename_container(
+    &self,
+    curr_name: String,
+    new_name: String,
+  ) -> anyhow::Result<Log>;
+}
+
+impl ContainerRuntime for PeripheryClient {
+  async fn container_state(
+    &self,
+    name: &str,
+  ) -> anyhow::Result<ContainerState> {
+    self
+      .request(api::container::InspectContainer {
+        name: name.to_string(),
+      })
+      .await
+      .map(|container| container.state.unwrap_or_default())
+  }
+  async fn deploy(
+    &self,
+    deploy: api::container::Deploy,
+  ) -> anyhow::Result<Log> {
+    self.request(deploy).await
+  }
+  async fn remove_container(
+    &self,
+    name: String,
+    signal: TerminationSignal,
+    time: i32,
+  ) -> anyhow::Result<Log> {
+    self
+      .request(api::container::RemoveContainer {
+        name,
+        signal: signal.into(),
+        time: time.into(),
+      })
+      .await
+  }
+  async fn rename_container(
+    &self,
+    curr_name: String,
+    new_name: String,
+  ) -> anyhow::Result<Log> {
+    self
+      .request(api::container::RenameContainer {
+        curr_name,
+        new_name,
+      })
+      .await
+  }
+}
+
+/// Removes any existing container before running the new one.
+async fn recreate_deploy(
+  runtime: &impl ContainerRuntime,
+  deploy: api::container::Deploy,
+  update: &mut Update,
+) {
+  match runtime.deploy(deploy).await {
+    Ok(log) => update.logs.push(log),
+    Err(e) => {
+      update
+        .push_error_log("Deploy Container", format_serror(&e.into()));
+    }
+  };
+}
+
+/// The new container is run under this suffixed name
+/// alongside the existing container, and renamed once
+/// the existing container is removed.
+const START_FIRST_SUFFIX: &str = "-komodo-next";
+/// Max time to wait for the new container to become healthy.
+const START_FIRST_HEALTHY_TIMEOUT_MS: i64 = 120_000;
+const START_FIRST_POLL_INTERVAL_MS: u64 = 2_000;
+
+/// Runs the new container next to the existing one, and only
+/// removes the existing container once the new one is healthy.
+/// If the new container doesn't become healthy, it is removed
+/// and the existing container is left running.
+async fn start_first_deploy(
+  runtime: &impl ContainerRuntime,
+  mut deploy: api::container::Deploy,
+  update: &mut Update,
+) {
+  let name = deploy.deployment.name.clone();
+
+  // Without an existing container, there is no downtime to avoid.
+  if runtime.container_state(&name).await.is_err() {
+    return recreate_deploy(runtime, deploy, update).await;
+  }
+
+  let next_name = format!("{name}{START_FIRST_SUFFIX}");
+  let signal = deploy
+    .stop_signal
+    .unwrap_or(deploy.deployment.config.termination_signal);
+  let time = deploy
+    .stop_time
+    .unwrap_or(deploy.deployment.config.termination_timeout);
+  deploy.deployment.name = next_name.clone();
+
+  recreate_deploy(runtime, deploy, update).await;
+
+  if !update
+    .logs
+    .last()
+    .map(|log| log.success)
+    .unwrap_or_default()
+  {
+    update.push_error_log(
+      "Start First",
+      format!(
+        "Failed to run new container {next_name}. Existing container {name} was left running."
+      ),
+    );
+    return;
+  }
+
+  if let Err(e) = wait_for_healthy(runtime, &next_name, update).await
+  {
+    update.push_error_log(
+      "Await Healthy",
+      format_serror(
+        &e.context(format!(
+          "New container {next_name} did not become healthy. Existing container {name} was left running."
+        ))
+        .into(),
+      ),
+    );
+    match runtime.remove_container(next_name, signal, time).await {
+      Ok(log) => update.logs.push(log),
+      Err(e) => update.push_error_log(
+        "Remove New Container",
+        format_serror(&e.into()),
+      ),
+    }
+    return;
+  }
+
+  match runtime.remove_container(name.clone(), signal, time).await {
+    Ok(log) => update.logs.push(log),
+    Err(e) => {
+      update.push_error_log(
+        "Remove Existing Container",
+        format_serror(&e.into()),
+      );
+      return;
+    }
+  }
+
+  match runtime.rename_container(next_name, name).await {
+    Ok(log) => update.logs.push(log),
+    Err(e) => update.push_error_log(
+      "Rename New Container",
+      format_serror(&e.into()),
+    ),
+  }
+}
+
+/// Polls the container until it is running and, if it defines
+/// a healthcheck, reports healthy. Containers without a
+/// healthcheck are considered healthy once running.
+async fn wait_for_healthy(
+  runtime: &impl ContainerRuntime,
+  name: &str,
+  update: &mut Update,
+) -> anyhow::Result<()> {
+  let start_ts = komodo_timestamp();
+  loop {
+    let state = runtime
+      .container_state(name)
+      .await
+      .context("Failed to inspect new container")?;
+    if container_healthy(&state)? {
+      let mut log = Log::simple(
+        "Await Healthy",
+        format!("Container {name} is healthy"),
+      );
+      log.start_ts = start_ts;
+      update.logs.push(log);
+      return Ok(());
+    }
+    if komodo_timestamp() - start_ts > START_FIRST_HEALTHY_TIMEOUT_MS
+    {
+      return Err(anyhow!(
+        "Timed out after {}s",
+        START_FIRST_HEALTHY_TIMEOUT_MS / 1_000
+      ));
+    }
+    tokio::time::sleep(Duration::from_millis(
+      START_FIRST_POLL_INTERVAL_MS,
+    ))
+    .await;
+  }
+}
+
+/// Whether the container is running and healthy.
+/// Ok(false) while it is still starting, and Err
+/// once it can no longer become healthy.
+fn container_healthy(state: &ContainerState) -> anyhow::Result<bool> {
+  let health = state
+    .health
+    .as_ref()
+    .map(|health| health.status)
+    .unwrap_or_default();
+  match (state.status, health) {
+    (
+      ContainerStateStatusEnum::Running,
+      HealthStatusEnum::Unhealthy,
+    ) => Err(anyhow!("Container is unhealthy")),
+    (
+      ContainerStateStatusEnum::Running,
+      HealthStatusEnum::Healthy
+      | HealthStatusEnum::None
+      | HealthStatusEnum::Empty,
+    ) => Ok(true),
+    (
+      ContainerStateStatusEnum::Running
+      | ContainerStateStatusEnum::Created
+      | ContainerStateStatusEnum::Restarting,
+      _,
+    ) => Ok(false),
+    (status, _) => Err(anyhow!("Container is {status}")),
+  }
+}
+
 /// Wait this long after a pull to allow another pull through
 const PULL_TIMEOUT: i64 = 5_000;
 type ServerId = String;
@@ -355,6 +853,74 @@ pub async fn pull_deployment_inner(
   res
 }
 
+impl Resolve<ExecuteArgs> for ReconcileDeployment {
+  #[instrument(name = "ReconcileDeployment", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let (deployment, server) =
+      setup_deployment_execution(&self.deployment, user).await?;
+
+    let mut update = update.clone();
+
+    let state = deployment_status_cache()
+      .get(&deployment.id)
+      .await
+      .map(|status| status.curr.state)
+      .unwrap_or_default();
+    let drift =
+      reconcile_drift(&deployment.config, state, || async {
+        periphery_client(&server)?
+          .request(api::container::InspectContainer {
+            name: deployment.name.clone(),
+          })
+          .await
+          .context("Failed to inspect container")
+      })
+      .await?;
+
+    if drift.is_empty() {
+      update.push_simple_log(
+        "Check drift",
+        "Container matches the Deployment config, nothing to do.",
+      );
+      update.finalize();
+      update_update(update.clone()).await?;
+      return Ok(update);
+    }
+
+    update.push_simple_log("Check drift", drift.join("\n"));
+
+    Deploy {
+      deployment: deployment.id,
+      stop_signal: None,
+      stop_time: None,
+    }
+    .resolve(&ExecuteArgs {
+      user: user.clone(),
+      update,
+    })
+    .await
+  }
+}
+
+/// The differences between the container and the config,
+/// only inspecting the container if it is deployed.
+async fn reconcile_drift<
+  F: Future<Output = anyhow::Result<Container>>,
+>(
+  config: &DeploymentConfig,
+  state: DeploymentState,
+  inspect: impl FnOnce() -> F,
+) -> anyhow::Result<Vec<String>> {
+  if state == DeploymentState::NotDeployed {
+    return Ok(vec![String::from("container: not deployed")]);
+  }
+  let container = inspect().await?;
+  Ok(deployment_config_drift(config, &container))
+}
+
 impl Resolve<ExecuteArgs> for PullDeployment {
   #[instrument(name = "PullDeployment", skip(user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
@@ -717,3 +1283,445 @@ impl Resolve<ExecuteArgs> for DestroyDeployment {
     Ok(update)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashMap, sync::Mutex};
+
+  use komodo_client::entities::docker::{
+    ContainerConfig,
+    container::{ContainerHealth, RestartPolicy},
+  };
+
+  use super::*;
+
+  fn state(
+    status: ContainerStateStatusEnum,
+    health: HealthStatusEnum,
+  ) -> ContainerState {
+    ContainerState {
+      status,
+      health: Some(ContainerHealth {
+        status: health,
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn container_health() {
+    use ContainerStateStatusEnum as Status;
+    use HealthStatusEnum as Health;
+    for (status, health, expected) in [
+      (Status::Running, Health::Healthy, Some(true)),
+      (Status::Running, Health::None, Some(true)),
+      (Status::Running, Health::Empty, Some(true)),
+      (Status::Running, Health::Starting, Some(false)),
+      (Status::Created, Health::Empty, Some(false)),
+      (Status::Restarting, Health::Starting, Some(false)),
+      (Status::Running, Health::Unhealthy, None),
+      (Status::Exited, Health::Empty, None),
+      (Status::Dead, Health::Empty, None),
+    ] {
+      assert_eq!(
+        container_healthy(&state(status, health)).ok(),
+        expected,
+        "{status} {health:?}"
+      );
+    }
+  }
+
+  /// Tracks containers in memory and records the calls
+  /// which change them.
+  struct MockRuntime {
+    containers: Mutex<HashMap<String, ContainerState>>,
+    /// The state reported by newly deployed containers.
+    new_state: ContainerState,
+    deploy_fails: bool,
+    calls: Mutex<Vec<String>>,
+  }
+
+  impl MockRuntime {
+    fn new(existing: bool, new_state: ContainerState) -> MockRuntime {
+      let mut containers = HashMap::new();
+      if existing {
+        containers.insert(
+          String::from("app"),
+          state(
+            ContainerStateStatusEnum::Running,
+            HealthStatusEnum::Healthy,
+          ),
+        );
+      }
+      MockRuntime {
+        containers: Mutex::new(containers),
+        new_state,
+        deploy_fails: false,
+        calls: Default::default(),
+      }
+    }
+
+    fn calls(&self) -> Vec<String> {
+      self.calls.lock().unwrap().clone()
+    }
+
+    fn container_names(&self) -> Vec<String> {
+      let mut names = self
+        .containers
+        .lock()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+      names.sort();
+      names
+    }
+  }
+
+  impl ContainerRuntime for MockRuntime {
+    async fn container_state(
+      &self,
+      name: &str,
+    ) -> anyhow::Result<ContainerState> {
+      self
+        .containers
+        .lock()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .context("No such container")
+    }
+    async fn deploy(
+      &self,
+      deploy: api::container::Deploy,
+    ) -> anyhow::Result<Log> {
+      let name = deploy.deployment.name;
+      self.calls.lock().unwrap().push(format!("deploy {name}"));
+      if self.deploy_fails {
+        return Ok(Log::error("Deploy Container", String::new()));
+      }
+      self
+        .containers
+        .lock()
+        .unwrap()
+        .insert(name, self.new_state.clone());
+      Ok(Log::simple("Deploy Container", String::new()))
+    }
+    async fn remove_container(
+      &self,
+      name: String,
+      _signal: TerminationSignal,
+      _time: i32,
+    ) -> anyhow::Result<Log> {
+      self.calls.lock().unwrap().push(format!("remove {name}"));
+      self.containers.lock().unwrap().remove(&name);
+      Ok(Log::simple("Remove Container", String::new()))
+    }
+    async fn rename_container(
+      &self,
+      curr_name: String,
+      new_name: String,
+    ) -> anyhow::Result<Log> {
+      self
+        .calls
+        .lock()
+        .unwrap()
+        .push(format!("rename {curr_name} {new_name}"));
+      let mut containers = self.containers.lock().unwrap();
+      let state =
+        containers.remove(&curr_name).context("No such container")?;
+      containers.insert(new_name, state);
+      Ok(Log::simple("Rename Container", String::new()))
+    }
+  }
+
+  fn deploy() -> api::container::Deploy {
+    api::container::Deploy {
+      deployment: Deployment {
+        id: String::new(),
+        name: String::from("app"),
+        description: String::new(),
+        template: false,
+        locked: false,
+        tags: Vec::new(),
+        info: (),
+        config: Default::default(),
+        base_permission: Default::default(),
+        updated_at: 0,
+      },
+      stop_signal: None,
+      stop_time: None,
+      registry_token: None,
+      replacers: Vec::new(),
+    }
+  }
+
+  fn healthy() -> ContainerState {
+    state(
+      ContainerStateStatusEnum::Running,
+      HealthStatusEnum::Healthy,
+    )
+  }
+
+  #[tokio::test]
+  async fn start_first_without_existing_container_recreates() {
+    let runtime = MockRuntime::new(false, healthy());
+    let mut update = Update::default();
+    start_first_deploy(&runtime, deploy(), &mut update).await;
+    assert_eq!(runtime.calls(), ["deploy app"]);
+    assert_eq!(runtime.container_names(), ["app"]);
+    assert!(all_logs_success(&update.logs));
+  }
+
+  #[tokio::test]
+  async fn start_first_replaces_container_once_healthy() {
+    let runtime = MockRuntime::new(true, healthy());
+    let mut update = Update::default();
+    start_first_deploy(&runtime, deploy(), &mut update).await;
+    assert_eq!(
+      runtime.calls(),
+      [
+        "deploy app-komodo-next",
+        "remove app",
+        "rename app-komodo-next app"
+      ]
+    );
+    assert_eq!(runtime.container_names(), ["app"]);
+    assert!(all_logs_success(&update.logs));
+  }
+
+  #[tokio::test]
+  async fn start_first_keeps_existing_when_unhealthy() {
+    let runtime = MockRuntime::new(
+      true,
+      state(
+        ContainerStateStatusEnum::Running,
+        HealthStatusEnum::Unhealthy,
+      ),
+    );
+    let mut update = Update::default();
+    start_first_deploy(&runtime, deploy(), &mut update).await;
+    assert_eq!(
+      runtime.calls(),
+      ["deploy app-komodo-next", "remove app-komodo-next"]
+    );
+    assert_eq!(runtime.container_names(), ["app"]);
+    assert!(!all_logs_success(&update.logs));
+  }
+
+  #[tokio::test]
+  async fn start_first_keeps_existing_when_run_fails() {
+    let mut runtime = MockRuntime::new(true, healthy());
+    runtime.deploy_fails = true;
+    let mut update = Update::default();
+    start_first_deploy(&runtime, deploy(), &mut update).await;
+    assert_eq!(runtime.calls(), ["deploy app-komodo-next"]);
+    assert_eq!(runtime.container_names(), ["app"]);
+    assert!(!all_logs_success(&update.logs));
+  }
+
+  #[tokio::test]
+  async fn wait_for_healthy_logs_healthy_container() {
+    let runtime = MockRuntime::new(true, healthy());
+    let mut update = Update::default();
+    wait_for_healthy(&runtime, "app", &mut update)
+      .await
+      .unwrap();
+    assert_eq!(update.logs[0].stage, "Await Healthy");
+  }
+
+  #[tokio::test]
+  async fn wait_for_healthy_fails_missing_container() {
+    let runtime = MockRuntime::new(false, healthy());
+    let mut update = Update::default();
+    assert!(
+      wait_for_healthy(&runtime, "app", &mut update)
+        .await
+        .is_err()
+    );
+    assert!(update.logs.is_empty());
+  }
+
+  fn restart_policy(name: RestartPolicyNameEnum) -> HostConfig {
+    HostConfig {
+      restart_policy: Some(RestartPolicy {
+        name,
+        maximum_retry_count: None,
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn restart_policy_matches() {
+    for (restart, host_config) in [
+      (
+        RestartMode::UnlessStopped,
+        restart_policy(RestartPolicyNameEnum::UnlessStopped),
+      ),
+      (
+        RestartMode::NoRestart,
+        restart_policy(RestartPolicyNameEnum::Empty),
+      ),
+      (RestartMode::NoRestart, HostConfig::default()),
+    ] {
+      let mut update = Update::default();
+      check_restart_policy(restart, &host_config, &mut update);
+      assert!(update.logs.is_empty(), "{restart}");
+    }
+  }
+
+  #[test]
+  fn restart_policy_mismatch_warns() {
+    let mut update = Update::default();
+    check_restart_policy(
+      RestartMode::Always,
+      &restart_policy(RestartPolicyNameEnum::No),
+      &mut update,
+    );
+    let [log] = update.logs.as_slice() else {
+      panic!("Expected one log, got {:?}", update.logs);
+    };
+    assert!(log.success);
+    assert_eq!(log.level, LogLevel::Warn);
+  }
+
+  fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+  }
+
+  #[test]
+  fn extra_arg_value_forms() {
+    let flags = &["--memory", "-m"];
+    for (extra_args, expected) in [
+      (args(&["--memory=512m"]), Some("512m")),
+      (args(&["--memory 512m"]), Some("512m")),
+      (args(&["--memory", "512m"]), Some("512m")),
+      (args(&["--init", "-m 1g"]), Some("1g")),
+      (args(&["--memory"]), None),
+      (args(&["--memory-swap=1g", "--init"]), None),
+      (args(&["--cpus=2"]), None),
+      (Vec::new(), None),
+    ] {
+      assert_eq!(
+        extra_arg_value(&extra_args, flags),
+        expected,
+        "{extra_args:?}"
+      );
+    }
+  }
+
+  fn resource_limits_log(
+    extra_args: &[&str],
+    host_config: HostConfig,
+  ) -> Option<Log> {
+    let mut update = Update::default();
+    report_resource_limits(
+      &args(extra_args),
+      &host_config,
+      &mut update,
+    );
+    update.logs.pop()
+  }
+
+  #[test]
+  fn no_resource_limits_not_reported() {
+    assert!(
+      resource_limits_log(&[], HostConfig::default()).is_none()
+    );
+  }
+
+  #[test]
+  fn applied_resource_limits_reported() {
+    let log = resource_limits_log(
+      &["--memory=512m", "--cpus 1.5"],
+      HostConfig {
+        memory: Some(512 * 1048576),
+        nano_cpus: Some(1_500_000_000),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(log.level, LogLevel::Info);
+    assert_eq!(log.stdout, "Memory: 512.0 MB\nCPUs: 1.50");
+
+    let log = resource_limits_log(
+      &[],
+      HostConfig {
+        cpu_quota: Some(50_000),
+        cpu_period: Some(100_000),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(log.stdout, "Memory: unlimited\nCPUs: 0.50");
+  }
+
+  #[test]
+  fn dropped_resource_limits_warn() {
+    let log = resource_limits_log(
+      &["-m", "512m", "--cpus=2"],
+      HostConfig::default(),
+    )
+    .unwrap();
+    assert!(log.success);
+    assert_eq!(log.level, LogLevel::Warn);
+    assert!(
+      log.stdout.contains("memory limit '512m' was not applied")
+    );
+    assert!(log.stdout.contains("CPU limit '2' was not applied"));
+  }
+
+  #[tokio::test]
+  async fn reconcile_not_deployed_without_inspecting() {
+    let drift = reconcile_drift(
+      &DeploymentConfig::default(),
+      DeploymentState::NotDeployed,
+      || async {
+        panic!("Inspected a container which isn't deployed")
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(drift, vec!["container: not deployed"]);
+  }
+
+  #[tokio::test]
+  async fn reconcile_inspects_deployed_container() {
+    let config = DeploymentConfig {
+      image: DeploymentImage::Image {
+        image: String::from("nginx"),
+      },
+      ..Default::default()
+    };
+    let container = |image: &str| Container {
+      config: Some(ContainerConfig {
+        image: Some(image.to_string()),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+    let drift =
+      reconcile_drift(&config, DeploymentState::Exited, || async {
+        Ok(container("nginx:latest"))
+      })
+      .await
+      .unwrap();
+    assert!(drift.is_empty());
+    let drift =
+      reconcile_drift(&config, DeploymentState::Running, || async {
+        Ok(container("nginx:1.27"))
+      })
+      .await
+      .unwrap();
+    assert_eq!(drift.len(), 1);
+    assert!(
+      reconcile_drift(&config, DeploymentState::Running, || async {
+        Err(anyhow!("Failed to inspect container"))
+      })
+      .await
+      .is_err()
+    );
+  }
+}
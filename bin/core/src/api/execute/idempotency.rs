@@ -0,0 +1,148 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, OnceLock},
+};
+
+use anyhow::{Context, anyhow};
+use komodo_client::entities::komodo_timestamp;
+use reqwest::StatusCode;
+use serror::AddStatusCodeError;
+use tokio::sync::OnceCell;
+
+use super::ExecuteRequest;
+
+/// How long the response is returned for retries using the same key.
+const IDEMPOTENCY_WINDOW_MS: i64 = 10 * 60 * 1000;
+
+struct IdempotentExecution {
+  ts: i64,
+  /// The serialized request, to reject reuse of a key with a different one.
+  request: String,
+  /// Concurrent retries wait on the first to finish.
+  response: Arc<OnceCell<String>>,
+}
+
+/// (user id, key) -> execution
+type IdempotentExecutions =
+  Mutex<HashMap<(String, String), IdempotentExecution>>;
+
+fn idempotent_executions() -> &'static IdempotentExecutions {
+  static IDEMPOTENT_EXECUTIONS: OnceLock<IdempotentExecutions> =
+    OnceLock::new();
+  IDEMPOTENT_EXECUTIONS.get_or_init(Default::default)
+}
+
+/// Only calls `execute` for the first request with the given key
+/// (per user), within the window. Retries get the original response.
+///
+/// If the execution fails to start, the key is released,
+/// so a retry will try again, even with a different request.
+pub async fn execute_once<Fut>(
+  user_id: &str,
+  key: &str,
+  request: &ExecuteRequest,
+  execute: impl FnOnce() -> Fut,
+) -> serror::Result<String>
+where
+  Fut: Future<Output = anyhow::Result<String>>,
+{
+  let request = serde_json::to_string(request)
+    .context("Failed to serialize execute request")?;
+  let response = {
+    let mut executions = idempotent_executions().lock().unwrap();
+    let now = komodo_timestamp();
+    executions.retain(|_, execution| {
+      now - execution.ts < IDEMPOTENCY_WINDOW_MS
+    });
+    let execution = executions
+      .entry((user_id.to_string(), key.to_string()))
+      .or_insert_with(|| IdempotentExecution {
+        ts: now,
+        request: request.clone(),
+        response: Default::default(),
+      });
+    if execution.request != request {
+      return Err(
+        anyhow!(
+          "The Idempotency-Key has already been used for a different request"
+        )
+        .status_code(StatusCode::UNPROCESSABLE_ENTITY),
+      );
+    }
+    execution.response.clone()
+  };
+  match response.get_or_try_init(execute).await {
+    Ok(response) => Ok(response.clone()),
+    Err(e) => {
+      let mut executions = idempotent_executions().lock().unwrap();
+      let key = (user_id.to_string(), key.to_string());
+      // Only remove the entry for this execution,
+      // it may have expired and the key been used again.
+      if executions.get(&key).is_some_and(|execution| {
+        Arc::ptr_eq(&execution.response, &response)
+      }) {
+        executions.remove(&key);
+      }
+      Err(e.into())
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use komodo_client::api::execute::{ClearRepoCache, Sleep};
+
+  use super::*;
+
+  #[tokio::test]
+  async fn retry_gets_original_response() {
+    let request = ExecuteRequest::ClearRepoCache(ClearRepoCache {});
+    let first = execute_once("user", "retry", &request, || async {
+      Ok(String::from("first"))
+    })
+    .await
+    .unwrap();
+    let retry = execute_once("user", "retry", &request, || async {
+      Ok(String::from("second"))
+    })
+    .await
+    .unwrap();
+    assert_eq!(first, "first");
+    assert_eq!(retry, "first");
+  }
+
+  #[tokio::test]
+  async fn key_reuse_with_different_request_is_rejected() {
+    let request = ExecuteRequest::ClearRepoCache(ClearRepoCache {});
+    execute_once("user", "reuse", &request, || async {
+      Ok(String::new())
+    })
+    .await
+    .unwrap();
+    let other = ExecuteRequest::Sleep(Sleep { duration_ms: 1 });
+    let e = execute_once("user", "reuse", &other, || async {
+      Ok(String::new())
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(e.status, StatusCode::UNPROCESSABLE_ENTITY);
+  }
+
+  #[tokio::test]
+  async fn failure_releases_key() {
+    let request = ExecuteRequest::ClearRepoCache(ClearRepoCache {});
+    execute_once("user", "failure", &request, || async {
+      Err(anyhow!("Failed to start"))
+    })
+    .await
+    .unwrap_err();
+    // The key can be used again, even for a different request.
+    let other = ExecuteRequest::Sleep(Sleep { duration_ms: 1 });
+    let res = execute_once("user", "failure", &other, || async {
+      Ok(String::from("started"))
+    })
+    .await
+    .unwrap();
+    assert_eq!(res, "started");
+  }
+}
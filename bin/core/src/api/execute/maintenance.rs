@@ -4,9 +4,12 @@ use anyhow::{Context, anyhow};
 use command::run_komodo_command;
 use database::mungos::{find::find_collect, mongodb::bson::doc};
 use formatting::{bold, format_serror};
+use futures::future::join_all;
 use komodo_client::{
   api::execute::{
-    BackupCoreDatabase, ClearRepoCache, GlobalAutoUpdate,
+    BackupCoreDatabase, BatchExecutionResponse,
+    BatchExecutionResponseItemErr, ClearRepoCache, Deploy,
+    DeployStack, GlobalAutoUpdate, RedeployVariableReferences,
   },
   entities::{
     deployment::DeploymentState, server::ServerState,
@@ -20,10 +23,13 @@ use tokio::sync::Mutex;
 
 use crate::{
   api::execute::{
-    ExecuteArgs, pull_deployment_inner, pull_stack_inner,
+    ExecuteArgs, ExecuteRequest, ExecutionResult, inner_handler,
+    pull_deployment_inner, pull_stack_inner,
   },
   config::core_config,
-  helpers::update::update_update,
+  helpers::{
+    query::get_deployed_variable_references, update::update_update,
+  },
   state::{
     db_client, deployment_status_cache, server_status_cache,
     stack_status_cache,
@@ -195,7 +201,8 @@ impl Resolve<ExecuteArgs> for GlobalAutoUpdate {
       "$or": [
         { "config.poll_for_updates": true },
         { "config.auto_update": true }
-      ]
+      ],
+      "locked": { "$ne": true },
     };
 
     let (stacks, repos) = tokio::try_join!(
@@ -317,3 +324,59 @@ impl Resolve<ExecuteArgs> for GlobalAutoUpdate {
     Ok(update)
   }
 }
+
+impl Resolve<ExecuteArgs> for RedeployVariableReferences {
+  #[instrument(
+    name = "RedeployVariableReferences",
+    skip(user),
+    fields(user_id = user.id)
+  )]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, .. }: &ExecuteArgs,
+  ) -> serror::Result<BatchExecutionResponse> {
+    let (deployments, stacks) =
+      get_deployed_variable_references(&self.variable, user).await?;
+    let requests = deployments
+      .into_iter()
+      .map(|deployment| {
+        (
+          deployment.clone(),
+          ExecuteRequest::Deploy(Deploy {
+            deployment,
+            stop_signal: None,
+            stop_time: None,
+          }),
+        )
+      })
+      .chain(stacks.into_iter().map(|stack| {
+        (
+          stack.clone(),
+          ExecuteRequest::DeployStack(DeployStack {
+            stack,
+            services: Vec::new(),
+            stop_time: None,
+          }),
+        )
+      }));
+    let futures = requests.map(|(name, request)| {
+      let user = user.clone();
+      async move {
+        inner_handler(request, user)
+          .await
+          .map(|r| {
+            let ExecutionResult::Single(update) = r else {
+              unreachable!()
+            };
+            update
+          })
+          .map_err(|e| BatchExecutionResponseItemErr {
+            name,
+            error: e.into(),
+          })
+          .into()
+      }
+    });
+    Ok(join_all(futures).await)
+  }
+}
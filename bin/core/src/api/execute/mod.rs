@@ -2,7 +2,8 @@ use std::{pin::Pin, time::Instant};
 
 use anyhow::Context;
 use axum::{
-  Extension, Router, extract::Path, middleware, routing::post,
+  Extension, Router, extract::Path, http::HeaderMap, middleware,
+  routing::post,
 };
 use axum_extra::{TypedHeader, headers::ContentType};
 use database::mungos::by_id::find_one_by_id;
@@ -21,14 +22,18 @@ use komodo_client::{
 use resolver_api::Resolve;
 use response::JsonString;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use serror::Json;
 use typeshare::typeshare;
 use uuid::Uuid;
 
 use crate::{
   auth::auth_request,
-  helpers::update::{init_execution_update, update_update},
+  helpers::{
+    audit::{
+      record_audit_log, record_execution_audit_log, request_target,
+    },
+    update::{init_execution_update, update_update},
+  },
   resource::{KomodoResource, list_full_for_user_using_pattern},
   state::db_client,
 };
@@ -37,8 +42,10 @@ mod action;
 mod alerter;
 mod build;
 mod deployment;
+mod idempotency;
 mod maintenance;
 mod procedure;
+mod queue;
 mod repo;
 mod server;
 mod stack;
@@ -46,8 +53,14 @@ mod sync;
 
 use super::Variant;
 
+use idempotency::execute_once;
+
 pub use {
-  deployment::pull_deployment_inner, stack::pull_stack_inner,
+  deployment::pull_deployment_inner,
+  queue::{
+    resume as resume_queued_executions, take_queued_executions,
+  },
+  stack::pull_stack_inner,
 };
 
 pub struct ExecuteArgs {
@@ -107,6 +120,7 @@ pub enum ExecuteRequest {
   // ==== DEPLOYMENT ====
   Deploy(Deploy),
   BatchDeploy(BatchDeploy),
+  ReconcileDeployment(ReconcileDeployment),
   PullDeployment(PullDeployment),
   StartDeployment(StartDeployment),
   RestartDeployment(RestartDeployment),
@@ -149,6 +163,7 @@ pub enum ExecuteRequest {
   ClearRepoCache(ClearRepoCache),
   BackupCoreDatabase(BackupCoreDatabase),
   GlobalAutoUpdate(GlobalAutoUpdate),
+  RedeployVariableReferences(RedeployVariableReferences),
 }
 
 pub fn router() -> Router {
@@ -160,28 +175,66 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   user: Extension<User>,
-  Path(Variant { variant }): Path<Variant>,
+  headers: HeaderMap,
+  Path(variant): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<(TypedHeader<ContentType>, String)> {
-  let req: ExecuteRequest = serde_json::from_value(json!({
-    "type": variant,
-    "params": params,
-  }))?;
-  handler(user, Json(req)).await
+  let req: ExecuteRequest = variant.parse(params)?;
+  handler(user, headers, Json(req)).await
 }
 
 async fn handler(
   Extension(user): Extension<User>,
+  headers: HeaderMap,
   Json(request): Json<ExecuteRequest>,
 ) -> serror::Result<(TypedHeader<ContentType>, String)> {
-  let res = match inner_handler(request, user).await? {
-    ExecutionResult::Single(update) => serde_json::to_string(&update)
-      .context("Failed to serialize Update")?,
-    ExecutionResult::Batch(res) => res,
+  let idempotency_key = headers
+    .get(IDEMPOTENCY_KEY_HEADER)
+    .and_then(|key| key.to_str().ok())
+    .filter(|key| !key.is_empty());
+  let res = match idempotency_key {
+    Some(key) => {
+      execute_once(&user.id, key, &request, || {
+        execute(request.clone(), user.clone())
+      })
+      .await?
+    }
+    None => execute(request, user).await?,
   };
   Ok((TypedHeader(ContentType::json()), res))
 }
 
+async fn execute(
+  request: ExecuteRequest,
+  user: User,
+) -> anyhow::Result<String> {
+  let operation = format!("{:?}", request.extract_variant());
+  let target = serde_json::to_value(&request)
+    .ok()
+    .and_then(|request| request_target(&request));
+  let res = inner_handler(request, user.clone()).await;
+  match &res {
+    // Recorded with the outcome once the execution finishes,
+    // see [spawn_execution].
+    Ok(ExecutionResult::Single(_)) => {}
+    Ok(ExecutionResult::Batch(_)) => {
+      record_audit_log("execute", &user, operation, target, None)
+    }
+    Err(e) => record_audit_log(
+      "execute",
+      &user,
+      operation,
+      target,
+      Some(format!("{e:#}")),
+    ),
+  }
+  match res? {
+    ExecutionResult::Single(update) => serde_json::to_string(&update)
+      .context("Failed to serialize Update"),
+    ExecutionResult::Batch(res) => Ok(res),
+  }
+}
+
 #[typeshare(serialized_as = "Update")]
 type BoxUpdate = Box<Update>;
 
@@ -221,56 +274,82 @@ pub fn inner_handler(
       ));
     }
 
-    // Spawn a task for the execution which continues
-    // running after this method returns.
-    let handle =
-      tokio::spawn(task(req_id, request, user, update.clone()));
-
-    // Spawns another task to monitor the first for failures,
-    // and add the log to Update about it (which primary task can't do because it errored out)
-    tokio::spawn({
-      let update_id = update.id.clone();
-      async move {
-        let log = match handle.await {
-          Ok(Err(e)) => {
-            warn!("/execute request {req_id} task error: {e:#}",);
-            Log::error("Task Error", format_serror(&e.into()))
-          }
-          Err(e) => {
-            warn!("/execute request {req_id} spawn error: {e:?}",);
-            Log::error("Spawn Error", format!("{e:#?}"))
-          }
-          _ => return,
-        };
-        let res = async {
-          // Nothing to do if update was never actually created,
-          // which is the case when the id is empty.
-          if update_id.is_empty() {
-            return Ok(());
-          }
-          let mut update =
-            find_one_by_id(&db_client().updates, &update_id)
-              .await
-              .context("failed to query to db")?
-              .context("no update exists with given id")?;
-          update.logs.push(log);
-          update.finalize();
-          update_update(update).await
-        }
-        .await;
+    // Persist the execution, so it isn't silently lost on restart.
+    queue::enqueue(&update.id, &user, &request).await?;
 
-        if let Err(e) = res {
-          warn!(
-            "failed to update update with task error log | {e:#}"
-          );
-        }
-      }
-    });
+    spawn_execution(req_id, request, user, update.clone());
 
     Ok(ExecutionResult::Single(update.into()))
   })
 }
 
+/// Spawns a task for the execution which continues running
+/// after the request returns. The execution must already be queued,
+/// and is removed from the queue once it finishes.
+/// The outcome is then recorded in the audit log.
+fn spawn_execution(
+  req_id: Uuid,
+  request: ExecuteRequest,
+  user: User,
+  update: Update,
+) {
+  let update_id = update.id.clone();
+  let operation = format!("{:?}", request.extract_variant());
+  let audit_user = user.clone();
+  let handle = tokio::spawn(async move {
+    queue::mark_running(&update.id).await;
+    task(req_id, request, user, update).await
+  });
+
+  // Spawns another task to monitor the first for failures,
+  // and add the log to Update about it (which primary task can't do because it errored out)
+  tokio::spawn(async move {
+    let res = handle.await;
+    queue::dequeue(&update_id).await;
+    let log = match res {
+      Ok(Err(e)) => {
+        warn!("/execute request {req_id} task error: {e:#}",);
+        Log::error("Task Error", format_serror(&e.into()))
+      }
+      Err(e) => {
+        warn!("/execute request {req_id} spawn error: {e:?}",);
+        Log::error("Spawn Error", format!("{e:#?}"))
+      }
+      _ => {
+        record_execution_audit_log(
+          &audit_user,
+          operation,
+          &update_id,
+        )
+        .await;
+        return;
+      }
+    };
+    let res = async {
+      // Nothing to do if update was never actually created,
+      // which is the case when the id is empty.
+      if update_id.is_empty() {
+        return Ok(());
+      }
+      let mut update =
+        find_one_by_id(&db_client().updates, &update_id)
+          .await
+          .context("failed to query to db")?
+          .context("no update exists with given id")?;
+      update.logs.push(log);
+      update.finalize();
+      update_update(update).await
+    }
+    .await;
+
+    if let Err(e) = res {
+      warn!("failed to update update with task error log | {e:#}");
+    }
+    record_execution_audit_log(&audit_user, operation, &update_id)
+      .await;
+  });
+}
+
 #[instrument(
   name = "ExecuteRequest",
   skip(user, update),
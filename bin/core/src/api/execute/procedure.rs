@@ -22,7 +22,10 @@ use tokio::sync::Mutex;
 
 use crate::{
   alert::send_alerts,
-  helpers::{procedure::execute_procedure, update::update_update},
+  helpers::{
+    procedure::{ProcedureVariables, execute_procedure},
+    update::update_update,
+  },
   permission::get_check_permissions,
   resource::refresh_procedure_state_cache,
   state::{action_states, db_client},
@@ -106,10 +109,24 @@ fn resolve_inner(
     update_update(update.clone()).await?;
 
     let update = Mutex::new(update);
+    let variables = ProcedureVariables::default();
 
-    let res = execute_procedure(&procedure, &update).await;
+    let res =
+      execute_procedure(&procedure, &variables, &update).await;
 
     let mut update = update.into_inner();
+    let variables = variables.into_inner();
+
+    if !variables.is_empty() {
+      update.push_simple_log(
+        "Procedure variables",
+        variables
+          .iter()
+          .map(|(name, value)| format!("{}: {value}", bold(name)))
+          .collect::<Vec<_>>()
+          .join("\n"),
+      );
+    }
 
     match res {
       Ok(_) => {
@@ -0,0 +1,178 @@
+use anyhow::Context;
+use database::mungos::{
+  by_id::find_one_by_id, find::find_collect, mongodb::bson::doc,
+};
+use futures::future::join_all;
+use komodo_client::entities::{
+  komodo_timestamp,
+  update::{Log, QueuedExecution, QueuedExecutionStatus},
+  user::User,
+};
+use uuid::Uuid;
+
+use crate::{
+  helpers::{query::get_user, update::update_update},
+  state::db_client,
+};
+
+use super::{ExecuteRequest, spawn_execution};
+
+/// Persists the execution to the queue before it is spawned.
+pub async fn enqueue(
+  update_id: &str,
+  user: &User,
+  request: &ExecuteRequest,
+) -> anyhow::Result<()> {
+  // The update was never actually created, nothing to resume.
+  if update_id.is_empty() {
+    return Ok(());
+  }
+  let queued = QueuedExecution {
+    id: Default::default(),
+    update_id: update_id.to_string(),
+    user_id: user.id.clone(),
+    request: serde_json::to_string(request)
+      .context("Failed to serialize execute request")?,
+    status: QueuedExecutionStatus::Queued,
+    queued_ts: komodo_timestamp(),
+  };
+  db_client()
+    .execution_queue
+    .insert_one(queued)
+    .await
+    .context("Failed to add execution to queue")?;
+  Ok(())
+}
+
+/// Marks the execution as picked up.
+/// After this it won't be resumed after a restart.
+pub async fn mark_running(update_id: &str) {
+  if let Err(e) = db_client()
+    .execution_queue
+    .update_one(
+      doc! { "update_id": update_id },
+      doc! { "$set": { "status": "Running" } },
+    )
+    .await
+  {
+    warn!("Failed to mark queued execution running | {e:#}");
+  }
+}
+
+/// Removes the finished execution from the queue.
+pub async fn dequeue(update_id: &str) {
+  if let Err(e) = db_client()
+    .execution_queue
+    .delete_one(doc! { "update_id": update_id })
+    .await
+  {
+    warn!("Failed to remove execution from queue | {e:#}");
+  }
+}
+
+/// Run on startup, before the in progress updates are cleaned up.
+///
+/// Removes the executions which were running when Core shut down,
+/// leaving their updates to be marked interrupted,
+/// and returns the ones which never started, to be [resume]d.
+pub async fn take_queued_executions() -> Vec<QueuedExecution> {
+  let queue = &db_client().execution_queue;
+  if let Err(e) =
+    queue.delete_many(doc! { "status": "Running" }).await
+  {
+    error!("Failed to clean up running executions in queue | {e:#}");
+  }
+  find_collect(queue, None, None)
+    .await
+    .inspect_err(|e| {
+      error!("Failed to get queued executions on startup | {e:#}")
+    })
+    .unwrap_or_default()
+}
+
+/// Spawns the executions which were queued
+/// but never started before Core shut down.
+pub async fn resume(queued: Vec<QueuedExecution>) {
+  if !queued.is_empty() {
+    info!("Resuming {} queued executions", queued.len());
+  }
+  join_all(queued.into_iter().map(|queued| async move {
+    let update_id = queued.update_id.clone();
+    if let Err(e) = resume_one(queued).await {
+      warn!("Failed to resume queued execution | {e:#}");
+      dequeue(&update_id).await;
+      if let Err(e) = fail_update(&update_id, e).await {
+        warn!(
+          "Failed to update update {update_id} with resume error | {e:#}"
+        );
+      }
+    }
+  }))
+  .await;
+}
+
+async fn resume_one(queued: QueuedExecution) -> anyhow::Result<()> {
+  let request =
+    serde_json::from_str::<ExecuteRequest>(&queued.request)
+      .context("Failed to parse queued execute request")?;
+  let user = get_user(&queued.user_id).await?;
+  let update =
+    find_one_by_id(&db_client().updates, &queued.update_id)
+      .await
+      .context("Failed to query to db")?
+      .context("No update exists with given id")?;
+  spawn_execution(Uuid::new_v4(), request, user, update);
+  Ok(())
+}
+
+async fn fail_update(
+  update_id: &str,
+  e: anyhow::Error,
+) -> anyhow::Result<()> {
+  let Some(mut update) =
+    find_one_by_id(&db_client().updates, update_id)
+      .await
+      .context("Failed to query to db")?
+  else {
+    return Ok(());
+  };
+  update
+    .logs
+    .push(Log::error("Resume Queued Execution", format!("{e:#}")));
+  update.finalize();
+  update_update(update).await
+}
+
+#[cfg(test)]
+mod tests {
+  use komodo_client::api::execute::RunBuild;
+
+  use super::*;
+
+  #[test]
+  fn queued_request_round_trips() {
+    let request = ExecuteRequest::RunBuild(RunBuild {
+      build: String::from("my-build"),
+    });
+    let request = serde_json::to_string(&request).unwrap();
+    let ExecuteRequest::RunBuild(RunBuild { build }) =
+      serde_json::from_str(&request).unwrap()
+    else {
+      panic!("queued request parsed to the wrong variant");
+    };
+    assert_eq!(build, "my-build");
+  }
+
+  #[test]
+  fn running_status_matches_queries() {
+    // The queue is queried with `{ "status": "Running" }`.
+    assert_eq!(
+      serde_json::to_value(QueuedExecutionStatus::Running).unwrap(),
+      "Running"
+    );
+    assert_eq!(
+      QueuedExecutionStatus::default().to_string(),
+      "Queued"
+    );
+  }
+}
@@ -28,6 +28,7 @@ use crate::{
   helpers::{
     periphery_client,
     query::{VariablesAndSecrets, get_variables_and_secrets},
+    shutdown::start_execution,
     stack_git_token,
     update::{
       add_update_without_send, init_execution_update, update_update,
@@ -348,6 +349,7 @@ impl Resolve<ExecuteArgs> for DeployStackIfChanged {
         // Don't actually send it here, let the handler send it after it can set action state.
         // This is usually done in crate::helpers::update::init_execution_update.
         update.id = add_update_without_send(&update).await?;
+        start_execution(&update.id);
 
         DeployStack {
           stack: stack.name,
@@ -0,0 +1,265 @@
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::{Context, anyhow};
+use axum::{
+  extract::{ConnectInfo, Request},
+  http::HeaderMap,
+  middleware::Next,
+  response::Response,
+};
+use ipnetwork::IpNetwork;
+use komodo_client::deserializers::ForgivingVec;
+use reqwest::StatusCode;
+use serror::{AddStatusCode, AddStatusCodeError};
+
+use crate::config::core_config;
+
+/// Rejects requests from ips in `denied_ips`,
+/// or not in `allowed_ips` when it is configured.
+pub async fn guard_request_by_ip(
+  req: Request,
+  next: Next,
+) -> serror::Result<Response> {
+  let config = core_config();
+  if config.allowed_ips.is_empty() && config.denied_ips.is_empty() {
+    return Ok(next.run(req).await);
+  }
+  let ip = request_ip(&req)
+    .context("could not get ip of request")
+    .status_code(StatusCode::FORBIDDEN)?;
+  check_ip(ip, &config.allowed_ips, &config.denied_ips)
+    .status_code(StatusCode::FORBIDDEN)?;
+  Ok(next.run(req).await)
+}
+
+/// Errors if the ip is in `denied`,
+/// or `allowed` is not empty and doesn't contain it.
+fn check_ip(
+  ip: IpAddr,
+  allowed: &ForgivingVec<IpNetwork>,
+  denied: &ForgivingVec<IpNetwork>,
+) -> anyhow::Result<()> {
+  let ips = ip_forms(ip);
+  let contains =
+    |net: &IpNetwork| ips.iter().any(|ip| net.contains(*ip));
+  if denied.iter().any(contains) {
+    return Err(anyhow!("requesting ip {ip} is denied"));
+  }
+  if !allowed.is_empty() && !allowed.iter().any(contains) {
+    return Err(anyhow!("requesting ip {ip} not allowed"));
+  }
+  Ok(())
+}
+
+/// The client ip. Forwarded headers are only honored when
+/// the direct peer is one of the `trusted_proxies`,
+/// otherwise the socket address is used.
+pub fn request_ip(req: &Request) -> Option<IpAddr> {
+  let peer = req
+    .extensions()
+    .get::<ConnectInfo<SocketAddr>>()
+    .map(|ConnectInfo(addr)| addr.ip())?;
+  Some(client_ip(req.headers(), peer))
+}
+
+/// The client ip given the request headers and the direct peer ip.
+/// See [request_ip].
+pub fn client_ip(headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+  forwarded_client_ip(headers, peer, |ip| {
+    let forms = ip_forms(ip);
+    core_config()
+      .trusted_proxies
+      .iter()
+      .any(|net| forms.iter().any(|ip| net.contains(*ip)))
+  })
+}
+
+/// The ip along with its ipv4 mapped ipv6 equivalent,
+/// so either form matches configured networks.
+fn ip_forms(ip: IpAddr) -> [IpAddr; 2] {
+  [
+    ip,
+    match ip {
+      IpAddr::V4(ipv4) => IpAddr::V6(ipv4.to_ipv6_mapped()),
+      IpAddr::V6(_) => ip.to_canonical(),
+    },
+  ]
+}
+
+/// Each proxy appends the address it received the request from
+/// to `X-Forwarded-For`, and only the entries added by trusted proxies
+/// can be believed. So the entries are walked from the right,
+/// skipping trusted proxies, and the first untrusted address is the client.
+/// `X-Real-IP` is only used when there is no `X-Forwarded-For`.
+fn forwarded_client_ip(
+  headers: &HeaderMap,
+  peer: IpAddr,
+  is_trusted: impl Fn(IpAddr) -> bool,
+) -> IpAddr {
+  if !is_trusted(peer) {
+    return peer;
+  }
+  let forwarded = headers
+    .get_all("x-forwarded-for")
+    .iter()
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .map(|ip| ip.trim().parse::<IpAddr>())
+    .collect::<Vec<_>>();
+  if forwarded.is_empty() {
+    return headers
+      .get("x-real-ip")
+      .and_then(|value| value.to_str().ok())
+      .and_then(|ip| ip.trim().parse().ok())
+      .unwrap_or(peer);
+  }
+  let mut client = peer;
+  for ip in forwarded.into_iter().rev() {
+    match ip {
+      Ok(ip) if is_trusted(ip) => client = ip,
+      Ok(ip) => return ip,
+      // An invalid entry can't be walked past,
+      // so the nearest trusted proxy is used.
+      Err(_) => return client,
+    }
+  }
+  // Every entry is a trusted proxy.
+  client
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::Ipv4Addr;
+
+  use axum::http::HeaderValue;
+
+  use super::*;
+
+  fn is_trusted(ip: IpAddr) -> bool {
+    matches!(ip, IpAddr::V4(ip) if ip.octets()[0] == 10)
+  }
+
+  fn headers(forwarded_for: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      "x-forwarded-for",
+      HeaderValue::from_str(forwarded_for).unwrap(),
+    );
+    headers
+  }
+
+  fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+  }
+
+  #[test]
+  fn untrusted_peer_ignores_headers() {
+    assert_eq!(
+      forwarded_client_ip(
+        &headers("1.1.1.1"),
+        ip("2.2.2.2"),
+        is_trusted
+      ),
+      ip("2.2.2.2")
+    );
+  }
+
+  #[test]
+  fn spoofed_leftmost_entry_is_ignored() {
+    // The client sent "6.6.6.6", the proxy appended the client address.
+    assert_eq!(
+      forwarded_client_ip(
+        &headers("6.6.6.6, 1.1.1.1"),
+        ip("10.0.0.1"),
+        is_trusted
+      ),
+      ip("1.1.1.1")
+    );
+  }
+
+  #[test]
+  fn trusted_proxy_chain_is_skipped() {
+    assert_eq!(
+      forwarded_client_ip(
+        &headers("1.1.1.1, 10.0.0.2, 10.0.0.3"),
+        ip("10.0.0.1"),
+        is_trusted
+      ),
+      ip("1.1.1.1")
+    );
+  }
+
+  #[test]
+  fn multiple_headers_are_joined() {
+    let mut headers = headers("6.6.6.6");
+    headers
+      .append("x-forwarded-for", HeaderValue::from_static("1.1.1.1"));
+    assert_eq!(
+      forwarded_client_ip(&headers, ip("10.0.0.1"), is_trusted),
+      ip("1.1.1.1")
+    );
+  }
+
+  #[test]
+  fn invalid_entry_stops_at_nearest_proxy() {
+    assert_eq!(
+      forwarded_client_ip(
+        &headers("1.1.1.1, garbage, 10.0.0.2"),
+        ip("10.0.0.1"),
+        is_trusted
+      ),
+      ip("10.0.0.2")
+    );
+  }
+
+  #[test]
+  fn real_ip_used_without_forwarded_for() {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-real-ip", HeaderValue::from_static("1.1.1.1"));
+    assert_eq!(
+      forwarded_client_ip(&headers, ip("10.0.0.1"), is_trusted),
+      ip("1.1.1.1")
+    );
+    assert_eq!(
+      forwarded_client_ip(
+        &HeaderMap::new(),
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        is_trusted
+      ),
+      ip("10.0.0.1")
+    );
+  }
+
+  fn nets(nets: &[&str]) -> ForgivingVec<IpNetwork> {
+    nets.iter().map(|net| net.parse().unwrap()).collect()
+  }
+
+  #[test]
+  fn denied_ip_is_rejected() {
+    let denied = nets(&["1.1.1.0/24"]);
+    assert!(check_ip(ip("1.1.1.1"), &nets(&[]), &denied).is_err());
+    assert!(check_ip(ip("2.2.2.2"), &nets(&[]), &denied).is_ok());
+    // Ipv4 mapped ipv6 peers match ipv4 networks
+    assert!(
+      check_ip(ip("::ffff:1.1.1.1"), &nets(&[]), &denied).is_err()
+    );
+  }
+
+  #[test]
+  fn only_allowed_ips_pass() {
+    let allowed = nets(&["10.0.10.0/24", "::ffff:12.34.56.78"]);
+    assert!(check_ip(ip("10.0.10.5"), &allowed, &nets(&[])).is_ok());
+    assert!(
+      check_ip(ip("12.34.56.78"), &allowed, &nets(&[])).is_ok()
+    );
+    assert!(check_ip(ip("10.0.11.5"), &allowed, &nets(&[])).is_err());
+  }
+
+  #[test]
+  fn deny_takes_precedence_over_allow() {
+    let allowed = nets(&["10.0.0.0/8"]);
+    let denied = nets(&["10.0.0.1/32"]);
+    assert!(check_ip(ip("10.0.0.1"), &allowed, &denied).is_err());
+    assert!(check_ip(ip("10.0.0.2"), &allowed, &denied).is_ok());
+  }
+}
@@ -1,5 +1,17 @@
+use anyhow::{Context, anyhow};
+use axum::{
+  extract::Request, http::HeaderValue, middleware::Next,
+  response::Response,
+};
+use komodo_client::{api::API_VERSION_HEADER, entities::Version};
+use reqwest::StatusCode;
+use serde::de::{DeserializeOwned, value::MapDeserializer};
+use serror::{AddStatusCode, AddStatusCodeError};
+
 pub mod auth;
+pub mod events;
 pub mod execute;
+pub mod ip;
 pub mod read;
 pub mod terminal;
 pub mod user;
@@ -9,3 +21,179 @@ pub mod write;
 struct Variant {
   variant: String,
 }
+
+impl Variant {
+  /// Parses the request from the variant and its params.
+  /// Invalid requests are rejected with 400, naming
+  /// the JSON path of the offending field, eg `params.server`.
+  fn parse<T: DeserializeOwned>(
+    self,
+    params: serde_json::Value,
+  ) -> serror::Result<T> {
+    // The tag must come before the params, otherwise serde
+    // buffers the params and the path of the error is lost.
+    let request = MapDeserializer::<_, serde_json::Error>::new(
+      [
+        ("type", serde_json::Value::String(self.variant)),
+        ("params", params),
+      ]
+      .into_iter(),
+    );
+    serde_path_to_error::deserialize(request).map_err(|e| {
+      anyhow!("Invalid request at `{}` | {}", e.path(), e.inner())
+        .status_code(StatusCode::BAD_REQUEST)
+    })
+  }
+}
+
+/// Rejects requests from clients built against a different
+/// major version, and sends the Core version on every response.
+/// See [API_VERSION_HEADER].
+pub async fn api_version(
+  req: Request,
+  next: Next,
+) -> serror::Result<Response> {
+  let core_version = env!("CARGO_PKG_VERSION");
+  if let Some(client_version) = req.headers().get(API_VERSION_HEADER)
+  {
+    check_api_version(client_version, core_version)
+      .status_code(StatusCode::BAD_REQUEST)?;
+  }
+  let mut res = next.run(req).await;
+  res.headers_mut().insert(
+    API_VERSION_HEADER,
+    HeaderValue::from_static(core_version),
+  );
+  Ok(res)
+}
+
+fn check_api_version(
+  client_version: &HeaderValue,
+  core_version: &str,
+) -> anyhow::Result<()> {
+  let client_version = client_version
+    .to_str()
+    .context("Api version header is not valid string")
+    .and_then(Version::try_from)
+    .context("Failed to parse api version header")?;
+  let core_version = Version::try_from(core_version)?;
+  if client_version.major != core_version.major {
+    return Err(anyhow!(
+      "Client api version v{client_version} is not compatible with Komodo Core v{core_version}. \
+      Use a client with major version {}.",
+      core_version.major
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::Deserialize;
+  use serde_json::json;
+  use tower::ServiceExt;
+
+  use super::*;
+
+  #[derive(Deserialize, Debug)]
+  #[serde(tag = "type", content = "params")]
+  enum TestRequest {
+    Sleep(Sleep),
+  }
+
+  #[derive(Deserialize, Debug)]
+  struct Sleep {
+    duration_ms: u64,
+  }
+
+  fn variant(variant: &str) -> Variant {
+    Variant {
+      variant: variant.to_string(),
+    }
+  }
+
+  #[test]
+  fn parses_variant_and_params() {
+    let TestRequest::Sleep(Sleep { duration_ms }) = variant("Sleep")
+      .parse(json!({ "duration_ms": 100 }))
+      .unwrap();
+    assert_eq!(duration_ms, 100);
+  }
+
+  #[test]
+  fn invalid_field_reports_path() {
+    let e = variant("Sleep")
+      .parse::<TestRequest>(json!({ "duration_ms": "soon" }))
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    assert!(
+      e.error.to_string().contains("`params.duration_ms`"),
+      "{:#}",
+      e.error
+    );
+  }
+
+  #[test]
+  fn unknown_variant_is_bad_request() {
+    let e = variant("Unknown")
+      .parse::<TestRequest>(json!({}))
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+
+  fn header(version: &str) -> HeaderValue {
+    HeaderValue::from_str(version).unwrap()
+  }
+
+  #[test]
+  fn same_major_version_is_compatible() {
+    check_api_version(&header("1.0.0"), "1.19.5").unwrap();
+    check_api_version(&header("1.20"), "1.19.5").unwrap();
+  }
+
+  #[test]
+  fn different_major_version_is_rejected() {
+    check_api_version(&header("2.0.0"), "1.19.5").unwrap_err();
+    check_api_version(&header("0.9.0"), "1.19.5").unwrap_err();
+  }
+
+  #[test]
+  fn invalid_version_is_rejected() {
+    check_api_version(&header("latest"), "1.19.5").unwrap_err();
+  }
+
+  async fn api_version_response(
+    client_version: Option<&str>,
+  ) -> Response {
+    let router = axum::Router::new()
+      .route("/", axum::routing::get(|| async {}))
+      .layer(axum::middleware::from_fn(api_version));
+    let mut request = Request::get("/");
+    if let Some(client_version) = client_version {
+      request = request.header(API_VERSION_HEADER, client_version);
+    }
+    router
+      .oneshot(request.body(axum::body::Body::empty()).unwrap())
+      .await
+      .unwrap()
+  }
+
+  #[tokio::test]
+  async fn api_version_sends_core_version() {
+    let res = api_version_response(None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+      res.headers().get(API_VERSION_HEADER).unwrap(),
+      env!("CARGO_PKG_VERSION")
+    );
+  }
+
+  #[tokio::test]
+  async fn api_version_rejects_other_major() {
+    let major =
+      Version::try_from(env!("CARGO_PKG_VERSION")).unwrap().major;
+    let res =
+      api_version_response(Some(&format!("{}.0.0", major + 1))).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+  }
+}
@@ -0,0 +1,46 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::api::read::{
+  ListAuditLogs, ListAuditLogsResponse,
+};
+use resolver_api::Resolve;
+
+use crate::state::db_client;
+
+use super::ReadArgs;
+
+const NUM_AUDIT_LOGS_PER_PAGE: u64 = 100;
+
+impl Resolve<ReadArgs> for ListAuditLogs {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListAuditLogsResponse> {
+    if !user.admin {
+      return Err(anyhow!("This method is admin only.").into());
+    }
+
+    let logs = find_collect(
+      &db_client().audit_logs,
+      self.query.unwrap_or_default(),
+      FindOptions::builder()
+        .sort(doc! { "ts": -1 })
+        .limit(NUM_AUDIT_LOGS_PER_PAGE as i64)
+        .skip(self.page * NUM_AUDIT_LOGS_PER_PAGE)
+        .build(),
+    )
+    .await
+    .context("failed to get audit logs from db")?;
+
+    let next_page = if logs.len() < NUM_AUDIT_LOGS_PER_PAGE as usize {
+      None
+    } else {
+      Some((self.page + 1) as i64)
+    };
+
+    Ok(ListAuditLogsResponse { logs, next_page })
+  }
+}
@@ -0,0 +1,17 @@
+use komodo_client::api::read::*;
+use resolver_api::Resolve;
+
+use crate::helpers::query::get_active_change_freeze;
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetChangeFreeze {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetChangeFreezeResponse> {
+    Ok(GetChangeFreezeResponse {
+      freeze: get_active_change_freeze().await?,
+    })
+  }
+}
@@ -21,7 +21,6 @@ use komodo_client::{
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use serror::Json;
 use typeshare::typeshare;
 use uuid::Uuid;
@@ -36,14 +35,17 @@ use super::Variant;
 mod action;
 mod alert;
 mod alerter;
+mod audit;
 mod build;
 mod builder;
+mod change_freeze;
 mod deployment;
 mod permission;
 mod procedure;
 mod provider;
 mod repo;
 mod schedule;
+mod search;
 mod server;
 mod stack;
 mod sync;
@@ -67,10 +69,14 @@ pub struct ReadArgs {
 enum ReadRequest {
   GetVersion(GetVersion),
   GetCoreInfo(GetCoreInfo),
+  GetJsonSchema(GetJsonSchema),
   ListSecrets(ListSecrets),
   ListGitProvidersFromConfig(ListGitProvidersFromConfig),
   ListDockerRegistriesFromConfig(ListDockerRegistriesFromConfig),
 
+  // ==== SEARCH ====
+  Search(Search),
+
   // ==== USER ====
   GetUsername(GetUsername),
   GetPermission(GetPermission),
@@ -78,6 +84,7 @@ enum ReadRequest {
   ListUsers(ListUsers),
   ListApiKeys(ListApiKeys),
   ListApiKeysForServiceUser(ListApiKeysForServiceUser),
+  ListLoginEvents(ListLoginEvents),
   ListPermissions(ListPermissions),
   ListUserTargetPermissions(ListUserTargetPermissions),
 
@@ -101,6 +108,7 @@ enum ReadRequest {
 
   // ==== SCHEDULE ====
   ListSchedules(ListSchedules),
+  ValidateSchedule(ValidateSchedule),
 
   // ==== SERVER ====
   GetServersSummary(GetServersSummary),
@@ -109,12 +117,15 @@ enum ReadRequest {
   GetPeripheryVersion(GetPeripheryVersion),
   GetServerActionState(GetServerActionState),
   GetHistoricalServerStats(GetHistoricalServerStats),
+  QueryServerStats(QueryServerStats),
   ListServers(ListServers),
   ListFullServers(ListFullServers),
   InspectDockerContainer(InspectDockerContainer),
+  GetComposeFromContainers(GetComposeFromContainers),
   GetResourceMatchingContainer(GetResourceMatchingContainer),
   GetContainerLog(GetContainerLog),
   SearchContainerLog(SearchContainerLog),
+  GetContainerStatsHistory(GetContainerStatsHistory),
   InspectDockerNetwork(InspectDockerNetwork),
   InspectDockerImage(InspectDockerImage),
   ListDockerImageHistory(ListDockerImageHistory),
@@ -130,6 +141,7 @@ enum ReadRequest {
 
   // ==== SERVER STATS ====
   GetSystemInformation(GetSystemInformation),
+  GetDockerInfo(GetDockerInfo),
   GetSystemStats(GetSystemStats),
   ListSystemProcesses(ListSystemProcesses),
 
@@ -215,9 +227,16 @@ enum ReadRequest {
   ListAlerts(ListAlerts),
   GetAlert(GetAlert),
 
+  // ==== AUDIT ====
+  ListAuditLogs(ListAuditLogs),
+
+  // ==== CHANGE FREEZE ====
+  GetChangeFreeze(GetChangeFreeze),
+
   // ==== VARIABLE ====
   GetVariable(GetVariable),
   ListVariables(ListVariables),
+  ListVariableReferences(ListVariableReferences),
 
   // ==== PROVIDER ====
   GetGitProviderAccount(GetGitProviderAccount),
@@ -235,13 +254,10 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   user: Extension<User>,
-  Path(Variant { variant }): Path<Variant>,
+  Path(variant): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<axum::response::Response> {
-  let req: ReadRequest = serde_json::from_value(json!({
-    "type": variant,
-    "params": params,
-  }))?;
+  let req: ReadRequest = variant.parse(params)?;
   handler(user, Json(req)).await
 }
 
@@ -311,6 +327,19 @@ impl Resolve<ReadArgs> for GetCoreInfo {
   }
 }
 
+impl Resolve<ReadArgs> for GetJsonSchema {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetJsonSchemaResponse> {
+    komodo_client::api::execute::json_schema(&self.type_name)
+      .with_context(|| {
+        format!("No JSON schema available for {}", self.type_name)
+      })
+      .map_err(Into::into)
+  }
+}
+
 impl Resolve<ReadArgs> for ListSecrets {
   async fn resolve(
     self,
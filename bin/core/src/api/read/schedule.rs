@@ -1,3 +1,4 @@
+use formatting::format_serror;
 use futures::future::join_all;
 use komodo_client::{
   api::read::*,
@@ -15,7 +16,7 @@ use resolver_api::Resolve;
 use crate::{
   helpers::query::{get_all_tags, get_last_run_at},
   resource::list_full_for_user,
-  schedule::get_schedule_item_info,
+  schedule::{find_next_occurrences, get_schedule_item_info},
 };
 
 use super::ReadArgs;
@@ -105,3 +106,33 @@ impl Resolve<ReadArgs> for ListSchedules {
     )
   }
 }
+
+const MAX_VALIDATE_SCHEDULE_COUNT: i64 = 100;
+
+impl Resolve<ReadArgs> for ValidateSchedule {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<ValidateScheduleResponse> {
+    let count =
+      self.count.clamp(1, MAX_VALIDATE_SCHEDULE_COUNT) as usize;
+    let res = match find_next_occurrences(
+      self.format,
+      &self.schedule,
+      &self.timezone,
+      count,
+    ) {
+      Ok(next_runs) => ValidateScheduleResponse {
+        valid: true,
+        error: None,
+        next_runs,
+      },
+      Err(e) => ValidateScheduleResponse {
+        valid: false,
+        error: Some(format_serror(&e.into())),
+        next_runs: Vec::new(),
+      },
+    };
+    Ok(res)
+  }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use komodo_client::{
+  api::read::*,
+  entities::{
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::{Deployment, DeploymentImage},
+    procedure::Procedure,
+    repo::Repo,
+    resource::Resource,
+    server::Server,
+    stack::Stack,
+    sync::ResourceSync,
+    user::User,
+  },
+};
+use resolver_api::Resolve;
+
+use crate::{
+  helpers::query::get_all_tags,
+  permission::get_resource_ids_for_user, resource::KomodoResource,
+  state::all_resources_cache,
+};
+
+use super::ReadArgs;
+
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+impl Resolve<ReadArgs> for Search {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<SearchResponse> {
+    let query = self.query.trim().to_lowercase();
+    if query.is_empty() {
+      return Ok(Vec::new());
+    }
+    let tags = get_all_tags(None)
+      .await?
+      .into_iter()
+      .map(|tag| (tag.id, tag.name.to_lowercase()))
+      .collect::<HashMap<_, _>>();
+    let all = all_resources_cache().load_full();
+    let search = Searcher {
+      query: &query,
+      tags: &tags,
+      user,
+    };
+    let (
+      servers,
+      stacks,
+      deployments,
+      builds,
+      repos,
+      procedures,
+      actions,
+      builders,
+      alerters,
+      syncs,
+    ) = tokio::try_join!(
+      search.resources::<Server>(&all.servers, |config| vec![(
+        "address",
+        &config.address
+      )]),
+      search.resources::<Stack>(&all.stacks, |config| vec![(
+        "repo",
+        &config.repo
+      )]),
+      search.resources::<Deployment>(&all.deployments, |config| {
+        match &config.image {
+          DeploymentImage::Image { image } => vec![("image", image)],
+          DeploymentImage::Build { .. } => Vec::new(),
+        }
+      }),
+      search.resources::<Build>(&all.builds, |config| vec![
+        ("image", &config.image_name),
+        ("repo", &config.repo)
+      ]),
+      search.resources::<Repo>(&all.repos, |config| vec![(
+        "repo",
+        &config.repo
+      )]),
+      search.resources::<Procedure>(&all.procedures, |_| Vec::new()),
+      search.resources::<Action>(&all.actions, |_| Vec::new()),
+      search.resources::<Builder>(&all.builders, |_| Vec::new()),
+      search.resources::<Alerter>(&all.alerters, |_| Vec::new()),
+      search.resources::<ResourceSync>(&all.syncs, |config| vec![(
+        "repo",
+        &config.repo
+      )]),
+    )?;
+    let mut results = servers
+      .into_iter()
+      .chain(stacks)
+      .chain(deployments)
+      .chain(builds)
+      .chain(repos)
+      .chain(procedures)
+      .chain(actions)
+      .chain(builders)
+      .chain(alerters)
+      .chain(syncs)
+      .collect::<Vec<_>>();
+    rank_results(&mut results, self.limit);
+    Ok(results)
+  }
+}
+
+/// Orders the results best match first, then by name,
+/// keeping at most `limit`.
+fn rank_results(results: &mut Vec<SearchResult>, limit: i64) {
+  results.sort_by(|a, b| {
+    b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name))
+  });
+  results.truncate(limit.clamp(1, MAX_SEARCH_LIMIT) as usize);
+}
+
+struct Searcher<'a> {
+  /// Lowercased
+  query: &'a str,
+  /// Tag id -> lowercased tag name
+  tags: &'a HashMap<String, String>,
+  user: &'a User,
+}
+
+impl Searcher<'_> {
+  async fn resources<T: KomodoResource>(
+    &self,
+    resources: &HashMap<String, Resource<T::Config, T::Info>>,
+    fields: impl Fn(&T::Config) -> Vec<(&'static str, &String)>,
+  ) -> anyhow::Result<Vec<SearchResult>> {
+    let ids = get_resource_ids_for_user::<T>(self.user)
+      .await?
+      .map(HashSet::<String>::from_iter);
+    let results = resources
+      .values()
+      .filter(|resource| {
+        ids.as_ref().is_none_or(|ids| ids.contains(&resource.id))
+      })
+      .filter_map(|resource| {
+        let (score, matched) =
+          self.rank(resource, fields(&resource.config))?;
+        Some(SearchResult {
+          target: T::resource_target(resource.id.clone()),
+          name: resource.name.clone(),
+          matched: matched.to_string(),
+          score,
+        })
+      })
+      .collect();
+    Ok(results)
+  }
+
+  /// Returns the best match rank for the resource,
+  /// and what matched, or None if nothing matches.
+  fn rank<Config: Default, Info: Default>(
+    &self,
+    resource: &Resource<Config, Info>,
+    fields: Vec<(&'static str, &String)>,
+  ) -> Option<(i64, &'static str)> {
+    let name = resource.name.to_lowercase();
+    if name == self.query {
+      return Some((100, "name"));
+    }
+    if name.starts_with(self.query) {
+      return Some((80, "name"));
+    }
+    if name.contains(self.query) {
+      return Some((60, "name"));
+    }
+    let mut tags =
+      resource.tags.iter().filter_map(|id| self.tags.get(id));
+    if let Some(tag) = tags.find(|tag| tag.contains(self.query)) {
+      return Some((if tag == self.query { 50 } else { 40 }, "tag"));
+    }
+    if let Some((field, _)) = fields
+      .into_iter()
+      .find(|(_, value)| value.to_lowercase().contains(self.query))
+    {
+      return Some((30, field));
+    }
+    if resource.description.to_lowercase().contains(self.query) {
+      return Some((20, "description"));
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use komodo_client::entities::ResourceTarget;
+
+  use super::*;
+
+  fn resource(
+    name: &str,
+    tags: &[&str],
+    description: &str,
+  ) -> Resource<(), ()> {
+    Resource {
+      name: name.to_string(),
+      tags: tags.iter().map(|tag| tag.to_string()).collect(),
+      description: description.to_string(),
+      ..Default::default()
+    }
+  }
+
+  fn rank(
+    query: &str,
+    resource: &Resource<(), ()>,
+  ) -> Option<(i64, &'static str)> {
+    let tags = HashMap::from([
+      (String::from("tag-1"), String::from("prod")),
+      (String::from("tag-2"), String::from("production")),
+    ]);
+    let user = User::default();
+    let image = String::from("ghcr.io/org/web:latest");
+    Searcher {
+      query,
+      tags: &tags,
+      user: &user,
+    }
+    .rank(resource, vec![("image", &image)])
+  }
+
+  #[test]
+  fn ranks_name_matches_first() {
+    let web = resource("web", &["tag-1"], "");
+    assert_eq!(rank("web", &web), Some((100, "name")));
+    assert_eq!(rank("we", &web), Some((80, "name")));
+    assert_eq!(rank("eb", &web), Some((60, "name")));
+  }
+
+  #[test]
+  fn ranks_other_matches() {
+    let app = resource("app", &["tag-2"], "Serves the Website");
+    assert_eq!(rank("production", &app), Some((50, "tag")));
+    assert_eq!(rank("prod", &app), Some((40, "tag")));
+    assert_eq!(rank("ghcr", &app), Some((30, "image")));
+    assert_eq!(rank("website", &app), Some((20, "description")));
+    assert_eq!(rank("db", &app), None);
+  }
+
+  #[test]
+  fn results_ordered_and_limited() {
+    let result = |name: &str, score| SearchResult {
+      target: ResourceTarget::Server(name.to_string()),
+      name: name.to_string(),
+      matched: String::from("name"),
+      score,
+    };
+    let mut results =
+      vec![result("c", 60), result("b", 100), result("a", 60)];
+    rank_results(&mut results, 2);
+    let names =
+      results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["b", "a"]);
+    rank_results(&mut results, 0);
+    assert_eq!(results.len(), 1);
+  }
+}
@@ -6,12 +6,17 @@ use std::{
 
 use anyhow::{Context, anyhow};
 use async_timing_util::{
-  FIFTEEN_SECONDS_MS, get_timelength_in_ms, unix_timestamp_ms,
+  FIFTEEN_SECONDS_MS, ONE_DAY_MS, get_timelength_in_ms,
+  unix_timestamp_ms,
 };
 use database::mungos::{
   find::find_collect,
-  mongodb::{bson::doc, options::FindOptions},
+  mongodb::{
+    bson::{doc, from_document},
+    options::FindOptions,
+  },
 };
+use futures::TryStreamExt;
 use komodo_client::{
   api::read::*,
   entities::{
@@ -43,7 +48,9 @@ use periphery_client::api::{
   network::InspectNetwork,
   volume::InspectVolume,
 };
+use reqwest::StatusCode;
 use resolver_api::Resolve;
+use serror::AddStatusCodeError;
 use tokio::sync::Mutex;
 
 use crate::{
@@ -240,6 +247,24 @@ impl Resolve<ReadArgs> for GetSystemInformation {
   }
 }
 
+impl Resolve<ReadArgs> for GetDockerInfo {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetDockerInfoResponse> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let res = periphery_client(&server)?
+      .request(periphery::GetDockerInfo {})
+      .await?;
+    Ok(res)
+  }
+}
+
 impl Resolve<ReadArgs> for GetSystemStats {
   async fn resolve(
     self,
@@ -359,6 +384,107 @@ impl Resolve<ReadArgs> for GetHistoricalServerStats {
   }
 }
 
+/// The maximum number of points returned by [QueryServerStats].
+const MAX_STATS_POINTS: usize = 5_000;
+
+impl Resolve<ReadArgs> for QueryServerStats {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<QueryServerStatsResponse> {
+    let QueryServerStats {
+      server,
+      start_ts,
+      end_ts,
+      interval,
+      metrics,
+    } = self;
+    let server = get_check_permissions::<Server>(
+      &server,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let end_ts = end_ts.unwrap_or_else(komodo_timestamp);
+    let start_ts = start_ts.unwrap_or(end_ts - ONE_DAY_MS as i64);
+    if start_ts >= end_ts {
+      return Err(
+        anyhow!("'start_ts' must be before 'end_ts'")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+    let metrics = if metrics.is_empty() {
+      ServerStatsMetric::ALL.to_vec()
+    } else {
+      metrics
+    };
+
+    let mut pipeline = vec![doc! { "$match": {
+      "sid": &server.id,
+      "ts": { "$gte": start_ts, "$lt": end_ts },
+    } }];
+    match interval {
+      Some(interval) => {
+        let interval = get_timelength_in_ms(
+          interval.to_string().parse().context("Invalid interval")?,
+        ) as i64;
+        let mut group = doc! {
+          "_id": { "$subtract": ["$ts", { "$mod": ["$ts", interval] }] },
+          "count": { "$sum": 1 },
+        };
+        for metric in &metrics {
+          group.insert(
+            metric.point_field(),
+            doc! { "$avg": format!("${}", metric.record_path()) },
+          );
+        }
+        pipeline.extend([
+          doc! { "$group": group },
+          doc! { "$set": { "ts": "$_id" } },
+          doc! { "$unset": "_id" },
+          doc! { "$sort": { "ts": 1 } },
+        ]);
+      }
+      None => {
+        let mut project = doc! {
+          "_id": 0,
+          "ts": 1,
+          "count": { "$literal": 1 },
+        };
+        for metric in &metrics {
+          project.insert(
+            metric.point_field(),
+            format!("${}", metric.record_path()),
+          );
+        }
+        pipeline.extend([
+          doc! { "$sort": { "ts": 1 } },
+          doc! { "$project": project },
+        ]);
+      }
+    }
+    // One extra to know whether it was truncated.
+    pipeline.push(doc! { "$limit": MAX_STATS_POINTS as i64 + 1 });
+
+    let mut points = db_client()
+      .stats
+      .aggregate(pipeline)
+      .await
+      .context("Failed to query stats")?
+      .try_collect::<Vec<_>>()
+      .await
+      .context("Failed to collect stats")?
+      .into_iter()
+      .map(from_document::<ServerStatsPoint>)
+      .collect::<Result<Vec<_>, _>>()
+      .context("Failed to parse stats")?;
+    let truncated = points.len() > MAX_STATS_POINTS;
+    points.truncate(MAX_STATS_POINTS);
+
+    Ok(QueryServerStatsResponse { points, truncated })
+  }
+}
+
 impl Resolve<ReadArgs> for ListDockerContainers {
   async fn resolve(
     self,
@@ -487,6 +613,38 @@ impl Resolve<ReadArgs> for InspectDockerContainer {
   }
 }
 
+impl Resolve<ReadArgs> for GetComposeFromContainers {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetComposeFromContainersResponse> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Read.inspect(),
+    )
+    .await?;
+    let cache = server_status_cache()
+      .get_or_insert_default(&server.id)
+      .await;
+    if cache.state != ServerState::Ok {
+      return Err(
+        anyhow!(
+          "Cannot inspect containers: server is {:?}",
+          cache.state
+        )
+        .into(),
+      );
+    }
+    let contents = periphery_client(&server)?
+      .request(periphery::container::GetComposeFromContainers {
+        names: self.containers,
+      })
+      .await?;
+    Ok(GetComposeFromContainersResponse { contents })
+  }
+}
+
 const MAX_LOG_LENGTH: u64 = 5000;
 
 impl Resolve<ReadArgs> for GetContainerLog {
@@ -551,6 +709,27 @@ impl Resolve<ReadArgs> for SearchContainerLog {
   }
 }
 
+impl Resolve<ReadArgs> for GetContainerStatsHistory {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetContainerStatsHistoryResponse> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let res = periphery_client(&server)?
+      .request(periphery::container::GetContainerStatsHistory {
+        name: self.container,
+      })
+      .await
+      .context("failed at call to periphery")?;
+    Ok(res)
+  }
+}
+
 impl Resolve<ReadArgs> for GetResourceMatchingContainer {
   async fn resolve(
     self,
@@ -7,11 +7,12 @@ use komodo_client::{
     ListUserGroups,
   },
   entities::{
-    ResourceTarget, action::Action, alerter::Alerter, build::Build,
-    builder::Builder, deployment::Deployment,
-    permission::PermissionLevel, procedure::Procedure, repo::Repo,
-    resource::ResourceQuery, server::Server, stack::Stack,
-    sync::ResourceSync, toml::ResourcesToml, user::User,
+    ResourceTarget, ResourceTargetVariant, action::Action,
+    alerter::Alerter, build::Build, builder::Builder,
+    deployment::Deployment, permission::PermissionLevel,
+    procedure::Procedure, repo::Repo, resource::ResourceQuery,
+    server::Server, stack::Stack, sync::ResourceSync, tag::Tag,
+    toml::ResourcesToml, user::User,
   },
 };
 use resolver_api::Resolve;
@@ -21,7 +22,7 @@ use crate::{
     get_all_tags, get_id_to_tags, get_user_user_group_ids,
   },
   permission::get_check_permissions,
-  resource,
+  resource::{self, KomodoResource},
   state::db_client,
   sync::{
     toml::{ToToml, convert_resource},
@@ -34,6 +35,8 @@ use super::ReadArgs;
 
 async fn get_all_targets(
   tags: &[String],
+  resource_types: &[ResourceTargetVariant],
+  pattern: &str,
   user: &User,
 ) -> anyhow::Result<Vec<ResourceTarget>> {
   let mut targets = Vec::<ResourceTarget>::new();
@@ -42,118 +45,75 @@ async fn get_all_targets(
   } else {
     get_all_tags(None).await?
   };
-  targets.extend(
-    resource::list_full_for_user::<Alerter>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Alerter(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Builder>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Builder(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Server>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Server(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Stack>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Stack(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Deployment>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Deployment(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Build>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Build(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Repo>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Repo(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Procedure>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
-      PermissionLevel::Read.into(),
-      &all_tags,
-    )
-    .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Procedure(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<Action>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
+  let filter = TargetFilter {
+    tags,
+    resource_types,
+    pattern,
+    user,
+    all_tags: &all_tags,
+  };
+  targets.extend(get_targets::<Alerter>(&filter).await?);
+  targets.extend(get_targets::<Builder>(&filter).await?);
+  targets.extend(get_targets::<Server>(&filter).await?);
+  targets.extend(get_targets::<Stack>(&filter).await?);
+  targets.extend(get_targets::<Deployment>(&filter).await?);
+  targets.extend(get_targets::<Build>(&filter).await?);
+  targets.extend(get_targets::<Repo>(&filter).await?);
+  targets.extend(get_targets::<Procedure>(&filter).await?);
+  targets.extend(get_targets::<Action>(&filter).await?);
+  // These will already be filtered by [ExportResourcesToToml]
+  targets.extend(get_targets::<ResourceSync>(&filter).await?);
+  Ok(targets)
+}
+
+struct TargetFilter<'a> {
+  tags: &'a [String],
+  /// Empty includes all resource types.
+  resource_types: &'a [ResourceTargetVariant],
+  /// Empty includes all resource names.
+  pattern: &'a str,
+  user: &'a User,
+  all_tags: &'a [Tag],
+}
+
+fn type_included(
+  resource_types: &[ResourceTargetVariant],
+  resource_type: ResourceTargetVariant,
+) -> bool {
+  resource_types.is_empty() || resource_types.contains(&resource_type)
+}
+
+async fn get_targets<T: KomodoResource>(
+  filter: &TargetFilter<'_>,
+) -> anyhow::Result<Vec<ResourceTarget>> {
+  if !type_included(filter.resource_types, T::resource_type()) {
+    return Ok(Vec::new());
+  }
+  let query = ResourceQuery::builder().tags(filter.tags).build();
+  let resources = if filter.pattern.is_empty() {
+    resource::list_full_for_user::<T>(
+      query,
+      filter.user,
       PermissionLevel::Read.into(),
-      &all_tags,
+      filter.all_tags,
     )
     .await?
-    .into_iter()
-    .map(|resource| ResourceTarget::Action(resource.id)),
-  );
-  targets.extend(
-    resource::list_full_for_user::<ResourceSync>(
-      ResourceQuery::builder().tags(tags).build(),
-      user,
+  } else {
+    resource::list_full_for_user_using_pattern::<T>(
+      filter.pattern,
+      query,
+      filter.user,
       PermissionLevel::Read.into(),
-      &all_tags,
+      filter.all_tags,
     )
     .await?
-    .into_iter()
-    // These will already be filtered by [ExportResourcesToToml]
-    .map(|resource| ResourceTarget::ResourceSync(resource.id)),
-  );
-  Ok(targets)
+  };
+  Ok(
+    resources
+      .into_iter()
+      .map(|resource| T::resource_target(resource.id))
+      .collect(),
+  )
 }
 
 impl Resolve<ReadArgs> for ExportAllResourcesToToml {
@@ -162,7 +122,13 @@ impl Resolve<ReadArgs> for ExportAllResourcesToToml {
     args: &ReadArgs,
   ) -> serror::Result<ExportAllResourcesToTomlResponse> {
     let targets = if self.include_resources {
-      get_all_targets(&self.tags, &args.user).await?
+      get_all_targets(
+        &self.tags,
+        &self.resource_types,
+        &self.pattern,
+        &args.user,
+      )
+      .await?
     } else {
       Vec::new()
     };
@@ -514,3 +480,23 @@ fn serialize_resources_toml(
 
   Ok(toml)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_resource_types_include_all() {
+    assert!(type_included(&[], ResourceTargetVariant::Stack));
+    assert!(type_included(&[], ResourceTargetVariant::ResourceSync));
+  }
+
+  #[test]
+  fn resource_types_filter_others() {
+    let types =
+      [ResourceTargetVariant::Stack, ResourceTargetVariant::Build];
+    assert!(type_included(&types, ResourceTargetVariant::Stack));
+    assert!(type_included(&types, ResourceTargetVariant::Build));
+    assert!(!type_included(&types, ResourceTargetVariant::Server));
+  }
+}
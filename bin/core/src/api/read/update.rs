@@ -196,6 +196,7 @@ impl Resolve<ReadArgs> for ListUpdates {
         status: u.status,
         version: u.version,
         other_data: u.other_data,
+        progress: u.progress,
       }
     })
     .collect::<Vec<_>>();
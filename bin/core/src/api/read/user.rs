@@ -9,7 +9,8 @@ use komodo_client::{
     FindUser, FindUserResponse, GetUsername, GetUsernameResponse,
     ListApiKeys, ListApiKeysForServiceUser,
     ListApiKeysForServiceUserResponse, ListApiKeysResponse,
-    ListUsers, ListUsersResponse,
+    ListLoginEvents, ListLoginEventsResponse, ListUsers,
+    ListUsersResponse,
   },
   entities::user::{UserConfig, admin_service_user},
 };
@@ -135,3 +136,36 @@ impl Resolve<ReadArgs> for ListApiKeysForServiceUser {
     Ok(api_keys)
   }
 }
+
+/// The number of login events returned by [ListLoginEvents].
+const NUM_RECENT_LOGINS: i64 = 50;
+
+impl Resolve<ReadArgs> for ListLoginEvents {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListLoginEventsResponse> {
+    let user_id = match self.user {
+      Some(target)
+        if target != user.id && target != user.username =>
+      {
+        if !user.admin {
+          return Err(anyhow!("This method is admin only.").into());
+        }
+        get_user(&target).await?.id
+      }
+      _ => user.id.clone(),
+    };
+    let events = find_collect(
+      &db_client().login_events,
+      doc! { "user_id": &user_id },
+      FindOptions::builder()
+        .sort(doc! { "ts": -1 })
+        .limit(NUM_RECENT_LOGINS)
+        .build(),
+    )
+    .await
+    .context("failed to query db for login events")?;
+    Ok(events)
+  }
+}
@@ -6,7 +6,10 @@ use database::mungos::{
 use komodo_client::api::read::*;
 use resolver_api::Resolve;
 
-use crate::{helpers::query::get_variable, state::db_client};
+use crate::{
+  helpers::query::{get_variable, get_variable_references},
+  state::db_client,
+};
 
 use super::ReadArgs;
 
@@ -51,3 +54,12 @@ impl Resolve<ReadArgs> for ListVariables {
     Ok(variables)
   }
 }
+
+impl Resolve<ReadArgs> for ListVariableReferences {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListVariableReferencesResponse> {
+    Ok(get_variable_references(&self.name, user).await?)
+  }
+}
@@ -23,7 +23,7 @@ use uuid::Uuid;
 use crate::{
   auth::auth_request,
   helpers::{query::get_user, random_string},
-  state::db_client,
+  state::{db_client, jwt_client},
 };
 
 use super::Variant;
@@ -45,6 +45,8 @@ enum UserRequest {
   SetLastSeenUpdate(SetLastSeenUpdate),
   CreateApiKey(CreateApiKey),
   DeleteApiKey(DeleteApiKey),
+  RotateApiKeySecret(RotateApiKeySecret),
+  LogoutEverywhere(LogoutEverywhere),
 }
 
 pub fn router() -> Router {
@@ -157,6 +159,14 @@ impl Resolve<UserArgs> for SetLastSeenUpdate {
 const SECRET_LENGTH: usize = 40;
 const BCRYPT_COST: u32 = 10;
 
+/// Generates a new api key secret, returning it with its hash.
+fn new_api_key_secret() -> anyhow::Result<(String, String)> {
+  let secret = format!("S-{}", random_string(SECRET_LENGTH));
+  let secret_hash = bcrypt::hash(&secret, BCRYPT_COST)
+    .context("failed at hashing secret string")?;
+  Ok((secret, secret_hash))
+}
+
 impl Resolve<UserArgs> for CreateApiKey {
   #[instrument(name = "CreateApiKey", level = "debug", skip(user))]
   async fn resolve(
@@ -166,9 +176,7 @@ impl Resolve<UserArgs> for CreateApiKey {
     let user = get_user(&user.id).await?;
 
     let key = format!("K-{}", random_string(SECRET_LENGTH));
-    let secret = format!("S-{}", random_string(SECRET_LENGTH));
-    let secret_hash = bcrypt::hash(&secret, BCRYPT_COST)
-      .context("failed at hashing secret string")?;
+    let (secret, secret_hash) = new_api_key_secret()?;
 
     let api_key = ApiKey {
       name: self.name,
@@ -177,6 +185,7 @@ impl Resolve<UserArgs> for CreateApiKey {
       user_id: user.id.clone(),
       created_at: komodo_timestamp(),
       expires: self.expires,
+      expiry_alerted: false,
     };
     db_client()
       .api_keys
@@ -211,3 +220,88 @@ impl Resolve<UserArgs> for DeleteApiKey {
     Ok(DeleteApiKeyResponse {})
   }
 }
+
+impl Resolve<UserArgs> for RotateApiKeySecret {
+  #[instrument(
+    name = "RotateApiKeySecret",
+    level = "debug",
+    skip(user)
+  )]
+  async fn resolve(
+    self,
+    UserArgs { user }: &UserArgs,
+  ) -> serror::Result<RotateApiKeySecretResponse> {
+    let client = db_client();
+    let key = client
+      .api_keys
+      .find_one(doc! { "key": &self.key })
+      .await
+      .context("failed at db query")?
+      .context("no api key with key found")?;
+    if user.id != key.user_id {
+      return Err(anyhow!("api key does not belong to user").into());
+    }
+
+    let (secret, secret_hash) = new_api_key_secret()?;
+
+    client
+      .api_keys
+      .update_one(
+        doc! { "key": &key.key },
+        doc! { "$set": { "secret": secret_hash } },
+      )
+      .await
+      .context("failed to update api key secret on db")?;
+
+    Ok(RotateApiKeySecretResponse {
+      key: key.key,
+      secret,
+    })
+  }
+}
+
+impl Resolve<UserArgs> for LogoutEverywhere {
+  #[instrument(
+    name = "LogoutEverywhere",
+    level = "debug",
+    skip(user)
+  )]
+  async fn resolve(
+    self,
+    UserArgs { user }: &UserArgs,
+  ) -> serror::Result<LogoutEverywhereResponse> {
+    update_one_by_id(
+      &db_client().users,
+      &user.id,
+      database::mungos::update::Update::Set(doc! {
+        "tokens_valid_after": komodo_timestamp()
+      }),
+      None,
+    )
+    .await
+    .context("failed to update user tokens_valid_after on db")?;
+    jwt_client().clear_exchange_tokens(&user.id).await;
+    Ok(LogoutEverywhereResponse {})
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn api_key_secret_matches_hash() {
+    let (secret, hash) = new_api_key_secret().unwrap();
+    assert!(secret.starts_with("S-"));
+    assert_eq!(secret.len(), SECRET_LENGTH + 2);
+    assert!(bcrypt::verify(&secret, &hash).unwrap());
+  }
+
+  #[test]
+  fn rotated_secret_invalidates_previous() {
+    let (previous, _) = new_api_key_secret().unwrap();
+    let (secret, hash) = new_api_key_secret().unwrap();
+    assert_ne!(previous, secret);
+    assert!(!bcrypt::verify(&previous, &hash).unwrap());
+  }
+}
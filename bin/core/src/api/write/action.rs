@@ -4,6 +4,7 @@ use komodo_client::{
     action::Action, permission::PermissionLevel, update::Update,
   },
 };
+use partial_derive2::HasPartial;
 use resolver_api::Resolve;
 
 use crate::{permission::get_check_permissions, resource};
@@ -32,6 +33,7 @@ impl Resolve<WriteArgs> for CopyAction {
       PermissionLevel::Write.into(),
     )
     .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<Action>(&self.name, config.into(), user).await
   }
 }
@@ -4,6 +4,7 @@ use komodo_client::{
     alerter::Alerter, permission::PermissionLevel, update::Update,
   },
 };
+use partial_derive2::HasPartial;
 use resolver_api::Resolve;
 
 use crate::{permission::get_check_permissions, resource};
@@ -32,6 +33,7 @@ impl Resolve<WriteArgs> for CopyAlerter {
       PermissionLevel::Write.into(),
     )
     .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<Alerter>(&self.name, config.into(), user).await
   }
 }
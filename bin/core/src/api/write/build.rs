@@ -21,6 +21,7 @@ use komodo_client::{
 use octorust::types::{
   ReposCreateWebhookRequest, ReposCreateWebhookRequestConfig,
 };
+use partial_derive2::HasPartial;
 use periphery_client::{
   PeripheryClient,
   api::build::{
@@ -68,6 +69,7 @@ impl Resolve<WriteArgs> for CopyBuild {
     .await?;
     // reset version to 0.0.0
     config.version = Default::default();
+    let config = config.merge_partial(self.config);
     resource::create::<Build>(&self.name, config.into(), user).await
   }
 }
@@ -131,6 +133,7 @@ impl Resolve<WriteArgs> for WriteBuildFileContents {
           build_path: build.config.build_path,
           dockerfile_path: build.config.dockerfile_path,
           contents: self.contents,
+          validate: self.validate,
         })
         .await
         .context("Failed to write dockerfile contents to host")
@@ -178,7 +181,7 @@ async fn write_dockerfile_contents_git(
   build: Build,
   mut update: Update,
 ) -> serror::Result<Update> {
-  let WriteBuildFileContents { build: _, contents } = req;
+  let WriteBuildFileContents { contents, .. } = req;
 
   let mut repo_args: RepoExecutionArgs = if !build
     .config
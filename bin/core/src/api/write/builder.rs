@@ -1,7 +1,8 @@
 use komodo_client::{
   api::write::*,
   entities::{
-    builder::Builder, permission::PermissionLevel, update::Update,
+    MergePartial, builder::Builder, permission::PermissionLevel,
+    update::Update,
   },
 };
 use resolver_api::Resolve;
@@ -32,6 +33,10 @@ impl Resolve<WriteArgs> for CopyBuilder {
       PermissionLevel::Write.into(),
     )
     .await?;
+    let config = match self.config {
+      Some(partial) => config.merge_partial(partial),
+      None => config,
+    };
     resource::create::<Builder>(&self.name, config.into(), user).await
   }
 }
@@ -0,0 +1,144 @@
+use anyhow::{Context, anyhow};
+use database::mungos::mongodb::bson::doc;
+use komodo_client::{
+  api::write::*,
+  entities::{
+    NoData, Operation, ResourceTarget, change_freeze::ChangeFreeze,
+    komodo_timestamp,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::update::{add_update, make_update},
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for StartChangeFreeze {
+  #[instrument(name = "StartChangeFreeze", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<StartChangeFreezeResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can freeze changes")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let now = komodo_timestamp();
+    validate_change_freeze(&self.reason, self.expires, now)?;
+
+    let freeze = ChangeFreeze {
+      reason: self.reason,
+      started_by: user.id.clone(),
+      started_at: now,
+      expires: self.expires,
+    };
+
+    // There is only ever one freeze, the latest replaces any before it.
+    let db = db_client();
+    db.change_freeze
+      .delete_many(doc! {})
+      .await
+      .context("Failed to clear previous change freeze on db")?;
+    db.change_freeze
+      .insert_one(&freeze)
+      .await
+      .context("Failed to create change freeze on db")?;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::StartChangeFreeze,
+      user,
+    );
+    update
+      .push_simple_log("start change freeze", format!("{freeze:#?}"));
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(freeze)
+  }
+}
+
+fn validate_change_freeze(
+  reason: &str,
+  expires: i64,
+  now: i64,
+) -> serror::Result<()> {
+  if reason.trim().is_empty() {
+    return Err(
+      anyhow!("Must provide a reason for the change freeze")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  if expires != 0 && expires <= now {
+    return Err(
+      anyhow!("Change freeze expiry must be in the future")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  Ok(())
+}
+
+impl Resolve<WriteArgs> for EndChangeFreeze {
+  #[instrument(name = "EndChangeFreeze", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<EndChangeFreezeResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can end the change freeze")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let res = db_client()
+      .change_freeze
+      .delete_many(doc! {})
+      .await
+      .context("Failed to delete change freeze on db")?;
+
+    if res.deleted_count > 0 {
+      let mut update = make_update(
+        ResourceTarget::system(),
+        Operation::EndChangeFreeze,
+        user,
+      );
+      update.push_simple_log(
+        "end change freeze",
+        "Changes are no longer frozen",
+      );
+      update.finalize();
+      add_update(update).await?;
+    }
+
+    Ok(NoData {})
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn change_freeze_requires_reason() {
+    let e = validate_change_freeze(" ", 0, 1_000).unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+
+  #[test]
+  fn change_freeze_expiry_must_be_future() {
+    assert!(validate_change_freeze("incident", 0, 1_000).is_ok());
+    assert!(validate_change_freeze("incident", 1_001, 1_000).is_ok());
+    let e =
+      validate_change_freeze("incident", 1_000, 1_000).unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+}
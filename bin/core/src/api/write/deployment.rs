@@ -8,7 +8,10 @@ use komodo_client::{
       Deployment, DeploymentImage, DeploymentState,
       PartialDeploymentConfig, RestartMode,
     },
-    docker::container::RestartPolicyNameEnum,
+    docker::{
+      container::{Container, RestartPolicyNameEnum},
+      image::Image,
+    },
     komodo_timestamp,
     permission::PermissionLevel,
     server::{Server, ServerState},
@@ -16,7 +19,10 @@ use komodo_client::{
     update::Update,
   },
 };
-use periphery_client::api::{self, container::InspectContainer};
+use partial_derive2::HasPartial;
+use periphery_client::api::{
+  self, container::InspectContainer, image::InspectImage,
+};
 use resolver_api::Resolve;
 
 use crate::{
@@ -56,6 +62,7 @@ impl Resolve<WriteArgs> for CopyDeployment {
         PermissionLevel::Read.into(),
       )
       .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<Deployment>(&self.name, config.into(), user)
       .await
   }
@@ -85,59 +92,123 @@ impl Resolve<WriteArgs> for CreateDeploymentFromContainer {
         .into(),
       );
     }
-    let container = periphery_client(&server)?
+    let periphery = periphery_client(&server)?;
+    let container = periphery
       .request(InspectContainer {
         name: self.name.clone(),
       })
       .await
       .context("Failed to inspect container")?;
-
-    let mut config = PartialDeploymentConfig {
-      server_id: server.id.into(),
-      ..Default::default()
+    // Used to leave out the defaults which come from the image.
+    let image = match &container.image {
+      Some(image) => periphery
+        .request(InspectImage {
+          name: image.clone(),
+        })
+        .await
+        .ok(),
+      None => None,
     };
 
-    if let Some(container_config) = container.config {
-      config.image = container_config
-        .image
-        .map(|image| DeploymentImage::Image { image });
+    let config =
+      container_deployment_config(server.id, container, image);
+
+    resource::create::<Deployment>(&self.name, config, user).await
+  }
+}
+
+/// Generates the Deployment config for an existing container,
+/// leaving out the environment, labels and command
+/// which are already the image defaults.
+fn container_deployment_config(
+  server_id: String,
+  container: Container,
+  image: Option<Image>,
+) -> PartialDeploymentConfig {
+  let mut config = PartialDeploymentConfig {
+    server_id: server_id.into(),
+    ..Default::default()
+  };
+
+  let image_config = image.and_then(|image| image.config);
+
+  if let Some(container_config) = container.config {
+    config.image = container_config
+      .image
+      .map(|image| DeploymentImage::Image { image });
+    let image_cmd = image_config
+      .as_ref()
+      .map(|config| config.cmd.as_slice())
+      .unwrap_or_default();
+    if container_config.cmd != image_cmd {
       config.command = container_config.cmd.join(" ").into();
-      config.environment = container_config
-        .env
-        .into_iter()
-        .map(|env| format!("  {env}"))
-        .collect::<Vec<_>>()
-        .join("\n")
-        .into();
-      config.labels = container_config
-        .labels
-        .into_iter()
-        .map(|(key, val)| format!("  {key}: {val}"))
-        .collect::<Vec<_>>()
-        .join("\n")
-        .into();
     }
-    if let Some(host_config) = container.host_config {
-      config.volumes = host_config
-        .binds
-        .into_iter()
-        .map(|bind| format!("  {bind}"))
-        .collect::<Vec<_>>()
-        .join("\n")
-        .into();
-      config.network = host_config.network_mode;
-      config.ports = host_config
-        .port_bindings
-        .into_iter()
-        .filter_map(|(container, mut host)| {
-          let host = host.pop()?.host_port?;
-          Some(format!("  {host}:{}", container.replace("/tcp", "")))
+    config.environment = container_config
+      .env
+      .into_iter()
+      .filter(|env| {
+        image_config
+          .as_ref()
+          .map(|config| !config.env.contains(env))
+          .unwrap_or(true)
+      })
+      .map(|env| format!("  {env}"))
+      .collect::<Vec<_>>()
+      .join("\n")
+      .into();
+    config.labels = container_config
+      .labels
+      .into_iter()
+      .filter(|(key, val)| {
+        image_config
+          .as_ref()
+          .and_then(|config| config.labels.get(key))
+          != Some(val)
+      })
+      .map(|(key, val)| format!("  {key}: {val}"))
+      .collect::<Vec<_>>()
+      .join("\n")
+      .into();
+    config.termination_signal = container_config
+      .stop_signal
+      .and_then(|signal| signal.parse().ok());
+    config.termination_timeout =
+      container_config.stop_timeout.map(|timeout| timeout as i32);
+  }
+
+  if let Some(host_config) = container.host_config {
+    config.volumes = host_config
+      .binds
+      .into_iter()
+      .map(|bind| format!("  {bind}"))
+      .collect::<Vec<_>>()
+      .join("\n")
+      .into();
+    config.network = host_config.network_mode;
+    config.ports = host_config
+      .port_bindings
+      .into_iter()
+      .flat_map(|(container, host)| {
+        let container = container.replace("/tcp", "");
+        host.into_iter().filter_map(move |binding| {
+          let host_port =
+            binding.host_port.filter(|p| !p.is_empty())?;
+          let host = match binding.host_ip.as_deref() {
+            None | Some("") | Some("0.0.0.0") | Some("::") => {
+              host_port
+            }
+            Some(ip) => format!("{ip}:{host_port}"),
+          };
+          Some(format!("  {host}:{container}"))
         })
-        .collect::<Vec<_>>()
-        .join("\n")
-        .into();
-      config.restart = host_config.restart_policy.map(|restart| {
-        match restart.name {
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+      .into();
+    config.restart =
+      host_config
+        .restart_policy
+        .map(|restart| match restart.name {
           RestartPolicyNameEnum::Always => RestartMode::Always,
           RestartPolicyNameEnum::No
           | RestartPolicyNameEnum::Empty => RestartMode::NoRestart,
@@ -145,12 +216,10 @@ impl Resolve<WriteArgs> for CreateDeploymentFromContainer {
             RestartMode::UnlessStopped
           }
           RestartPolicyNameEnum::OnFailure => RestartMode::OnFailure,
-        }
-      });
-    }
-
-    resource::create::<Deployment>(&self.name, config, user).await
+        });
   }
+
+  config
 }
 
 impl Resolve<WriteArgs> for DeleteDeployment {
@@ -254,3 +323,160 @@ impl Resolve<WriteArgs> for RenameDeployment {
     Ok(update)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use komodo_client::entities::{
+    TerminationSignal,
+    deployment::DeploymentConfig,
+    docker::{
+      ContainerConfig, PortBinding,
+      container::{HostConfig, RestartPolicy},
+    },
+  };
+
+  use super::*;
+
+  #[test]
+  fn copy_overrides_only_given_fields() {
+    let source = DeploymentConfig {
+      server_id: String::from("server-a"),
+      image: DeploymentImage::Image {
+        image: String::from("nginx:latest"),
+      },
+      environment: String::from("KEY=value"),
+      send_alerts: false,
+      ..Default::default()
+    };
+    let copy =
+      source.clone().merge_partial(PartialDeploymentConfig {
+        server_id: Some(String::from("server-b")),
+        send_alerts: Some(true),
+        ..Default::default()
+      });
+    assert_eq!(copy.server_id, "server-b");
+    assert!(copy.send_alerts);
+    // Everything else matches the source.
+    let expected = DeploymentConfig {
+      server_id: copy.server_id.clone(),
+      send_alerts: copy.send_alerts,
+      ..source
+    };
+    assert_eq!(
+      serde_json::to_value(&copy).unwrap(),
+      serde_json::to_value(&expected).unwrap()
+    );
+  }
+
+  fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+  }
+
+  fn container(config: ContainerConfig) -> Container {
+    Container {
+      config: Some(config),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn leaves_out_image_defaults() {
+    let container = container(ContainerConfig {
+      image: Some(String::from("nginx:latest")),
+      cmd: strings(&["nginx", "-g", "daemon off;"]),
+      env: strings(&["PATH=/usr/bin", "MODE=prod"]),
+      labels: HashMap::from([
+        (String::from("maintainer"), String::from("nginx")),
+        (String::from("app"), String::from("web")),
+      ]),
+      stop_signal: Some(String::from("SIGQUIT")),
+      stop_timeout: Some(30),
+      ..Default::default()
+    });
+    let image = Image {
+      config: Some(ContainerConfig {
+        cmd: strings(&["nginx", "-g", "daemon off;"]),
+        env: strings(&["PATH=/usr/bin"]),
+        labels: HashMap::from([(
+          String::from("maintainer"),
+          String::from("nginx"),
+        )]),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+    let config = container_deployment_config(
+      String::from("server"),
+      container,
+      Some(image),
+    );
+    assert_eq!(config.server_id.as_deref(), Some("server"));
+    assert_eq!(config.command, None);
+    assert_eq!(config.environment.as_deref(), Some("  MODE=prod"));
+    assert_eq!(config.labels.as_deref(), Some("  app: web"));
+    assert_eq!(
+      config.termination_signal,
+      Some(TerminationSignal::SigQuit)
+    );
+    assert_eq!(config.termination_timeout, Some(30));
+  }
+
+  #[test]
+  fn keeps_everything_without_image() {
+    let container = container(ContainerConfig {
+      cmd: strings(&["serve", "--port", "80"]),
+      env: strings(&["PATH=/usr/bin", "MODE=prod"]),
+      ..Default::default()
+    });
+    let config = container_deployment_config(
+      String::from("server"),
+      container,
+      None,
+    );
+    assert_eq!(config.command.as_deref(), Some("serve --port 80"));
+    assert_eq!(
+      config.environment.as_deref(),
+      Some("  PATH=/usr/bin\n  MODE=prod")
+    );
+    assert_eq!(config.termination_signal, None);
+  }
+
+  #[test]
+  fn keeps_host_ip_and_all_port_bindings() {
+    let binding = |host_ip: &str, host_port: &str| PortBinding {
+      host_ip: Some(host_ip.to_string()),
+      host_port: Some(host_port.to_string()),
+    };
+    let container = Container {
+      host_config: Some(HostConfig {
+        port_bindings: HashMap::from([(
+          String::from("80/tcp"),
+          vec![
+            binding("0.0.0.0", "8080"),
+            binding("::", "8080"),
+            binding("127.0.0.1", "9090"),
+            binding("", ""),
+          ],
+        )]),
+        restart_policy: Some(RestartPolicy {
+          name: RestartPolicyNameEnum::UnlessStopped,
+          ..Default::default()
+        }),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+    let config = container_deployment_config(
+      String::from("server"),
+      container,
+      None,
+    );
+    assert_eq!(
+      config.ports.as_deref(),
+      Some("  8080:80\n  8080:80\n  127.0.0.1:9090:80")
+    );
+    assert_eq!(config.restart, Some(RestartMode::UnlessStopped));
+  }
+}
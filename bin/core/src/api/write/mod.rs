@@ -9,12 +9,14 @@ use komodo_client::{api::write::*, entities::user::User};
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use serror::Json;
 use typeshare::typeshare;
 use uuid::Uuid;
 
-use crate::auth::auth_request;
+use crate::{
+  auth::auth_request,
+  helpers::audit::{record_audit_log, request_target},
+};
 
 use super::Variant;
 
@@ -22,6 +24,7 @@ mod action;
 mod alerter;
 mod build;
 mod builder;
+mod change_freeze;
 mod deployment;
 mod permissions;
 mod procedure;
@@ -95,6 +98,7 @@ pub enum WriteRequest {
   // ==== STACK ====
   CreateStack(CreateStack),
   CopyStack(CopyStack),
+  CreateStackFromComposeProject(CreateStackFromComposeProject),
   DeleteStack(DeleteStack),
   UpdateStack(UpdateStack),
   RenameStack(RenameStack),
@@ -177,6 +181,12 @@ pub enum WriteRequest {
   DeleteTag(DeleteTag),
   RenameTag(RenameTag),
   UpdateTagColor(UpdateTagColor),
+  BatchAddTags(BatchAddTags),
+  BatchRemoveTags(BatchRemoveTags),
+
+  // ==== CHANGE FREEZE ====
+  StartChangeFreeze(StartChangeFreeze),
+  EndChangeFreeze(EndChangeFreeze),
 
   // ==== VARIABLE ====
   CreateVariable(CreateVariable),
@@ -203,13 +213,10 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   user: Extension<User>,
-  Path(Variant { variant }): Path<Variant>,
+  Path(variant): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<axum::response::Response> {
-  let req: WriteRequest = serde_json::from_value(json!({
-    "type": variant,
-    "params": params,
-  }))?;
+  let req: WriteRequest = variant.parse(params)?;
   handler(user, Json(req)).await
 }
 
@@ -243,12 +250,26 @@ async fn task(
 
   let timer = Instant::now();
 
-  let res = request.resolve(&WriteArgs { user }).await;
+  let operation = format!("{:?}", request.extract_variant());
+  let target = serde_json::to_value(&request)
+    .ok()
+    .and_then(|request| request_target(&request));
+
+  let args = WriteArgs { user };
+  let res = request.resolve(&args).await;
 
   if let Err(e) = &res {
     warn!("/write request {req_id} error: {:#}", e.error);
   }
 
+  record_audit_log(
+    "write",
+    &args.user,
+    operation,
+    target,
+    res.as_ref().err().map(|e| format!("{:#}", e.error)),
+  );
+
   let elapsed = timer.elapsed();
   debug!("/write request {req_id} | resolve time: {elapsed:?}");
 
@@ -4,6 +4,7 @@ use komodo_client::{
     permission::PermissionLevel, procedure::Procedure, update::Update,
   },
 };
+use partial_derive2::HasPartial;
 use resolver_api::Resolve;
 
 use crate::{permission::get_check_permissions, resource};
@@ -33,6 +34,7 @@ impl Resolve<WriteArgs> for CopyProcedure {
         PermissionLevel::Write.into(),
       )
       .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<Procedure>(&self.name, config.into(), user)
       .await
   }
@@ -20,6 +20,7 @@ use komodo_client::{
 use octorust::types::{
   ReposCreateWebhookRequest, ReposCreateWebhookRequestConfig,
 };
+use partial_derive2::HasPartial;
 use periphery_client::api;
 use resolver_api::Resolve;
 
@@ -58,6 +59,7 @@ impl Resolve<WriteArgs> for CopyRepo {
       PermissionLevel::Read.into(),
     )
     .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<Repo>(&self.name, config.into(), user).await
   }
 }
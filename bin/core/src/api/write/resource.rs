@@ -22,6 +22,7 @@ impl Resolve<WriteArgs> for UpdateResourceMeta {
     let meta = ResourceMetaUpdate {
       description: self.description,
       template: self.template,
+      locked: self.locked,
       tags: self.tags,
     };
     match self.target {
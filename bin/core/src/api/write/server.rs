@@ -10,6 +10,7 @@ use komodo_client::{
     update::{Update, UpdateStatus},
   },
 };
+use partial_derive2::HasPartial;
 use periphery_client::api;
 use resolver_api::Resolve;
 
@@ -47,6 +48,7 @@ impl Resolve<WriteArgs> for CopyServer {
     )
     .await?;
 
+    let config = config.merge_partial(self.config);
     resource::create::<Server>(&self.name, config.into(), user).await
   }
 }
@@ -45,6 +45,10 @@ impl Resolve<WriteArgs> for CreateServiceUser {
       create_server_permissions: false,
       create_build_permissions: false,
       last_update_view: 0,
+      tokens_valid_after: 0,
+      failed_logins: 0,
+      last_failed_login_at: 0,
+      locked_until: 0,
       recents: Default::default(),
       all: Default::default(),
       updated_at: komodo_timestamp(),
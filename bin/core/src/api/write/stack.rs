@@ -6,13 +6,13 @@ use formatting::format_serror;
 use komodo_client::{
   api::write::*,
   entities::{
-    FileContents, NoData, Operation, RepoExecutionArgs,
-    all_logs_success,
+    FileContents, GitSyncStrategy, NoData, Operation,
+    RepoExecutionArgs, all_logs_success,
     config::core::CoreConfig,
     permission::PermissionLevel,
     repo::Repo,
-    server::ServerState,
-    stack::{PartialStackConfig, Stack, StackInfo},
+    server::{Server, ServerState},
+    stack::{ComposeProject, PartialStackConfig, Stack, StackInfo},
     update::Update,
     user::stack_user,
   },
@@ -20,6 +20,7 @@ use komodo_client::{
 use octorust::types::{
   ReposCreateWebhookRequest, ReposCreateWebhookRequestConfig,
 };
+use partial_derive2::HasPartial;
 use periphery_client::api::compose::{
   GetComposeContentsOnHost, GetComposeContentsOnHostResponse,
   WriteComposeContentsToHost,
@@ -40,7 +41,7 @@ use crate::{
     remote::{RemoteComposeContents, get_repo_compose_contents},
     services::extract_services_into_res,
   },
-  state::{db_client, github_client},
+  state::{db_client, github_client, server_status_cache},
 };
 
 use super::WriteArgs;
@@ -68,10 +69,129 @@ impl Resolve<WriteArgs> for CopyStack {
     )
     .await?;
 
+    let config = config.merge_partial(self.config);
     resource::create::<Stack>(&self.name, config.into(), user).await
   }
 }
 
+impl Resolve<WriteArgs> for CreateStackFromComposeProject {
+  #[instrument(name = "CreateStackFromComposeProject", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<Stack> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Read.inspect().attach(),
+    )
+    .await?;
+    let cache = server_status_cache()
+      .get_or_insert_default(&server.id)
+      .await;
+    if cache.state != ServerState::Ok {
+      return Err(
+        anyhow!(
+          "Cannot list compose projects: server is {:?}",
+          cache.state
+        )
+        .into(),
+      );
+    }
+    let project = cache
+      .projects
+      .as_ref()
+      .and_then(|projects| {
+        projects.iter().find(|project| project.name == self.project)
+      })
+      .with_context(|| {
+        format!(
+          "Did not find compose project {} on server {}",
+          self.project, server.name
+        )
+      })?;
+
+    let existing = db_client()
+      .stacks
+      .find_one(doc! {
+        "config.server_id": &server.id,
+        "$or": [
+          { "config.project_name": &project.name },
+          { "config.project_name": "", "name": &project.name },
+        ],
+      })
+      .await
+      .context("Failed to query db for stacks")?;
+    if let Some(existing) = existing {
+      return Err(
+        anyhow!(
+          "Compose project {} is already managed by Stack {}",
+          project.name,
+          existing.name
+        )
+        .into(),
+      );
+    }
+
+    let config = compose_project_stack_config(&server.id, project)?;
+    let name = if self.name.is_empty() {
+      &project.name
+    } else {
+      &self.name
+    };
+    resource::create::<Stack>(name, config, user).await
+  }
+}
+
+/// Points a files on host stack at the project's compose files,
+/// using the deepest directory containing all of them
+/// as the run directory.
+fn compose_project_stack_config(
+  server_id: &str,
+  project: &ComposeProject,
+) -> anyhow::Result<PartialStackConfig> {
+  let files = project
+    .compose_files
+    .iter()
+    .map(|file| file.trim())
+    .filter(|file| !file.is_empty())
+    .map(PathBuf::from)
+    .collect::<Vec<_>>();
+  if files.is_empty() {
+    return Err(anyhow!(
+      "Compose project {} has no compose files",
+      project.name
+    ));
+  }
+  let mut run_directory = files[0]
+    .parent()
+    .context("Compose file has no parent directory")?
+    .to_path_buf();
+  while !files.iter().all(|file| file.starts_with(&run_directory)) {
+    if !run_directory.pop() {
+      break;
+    }
+  }
+  let file_paths = files
+    .iter()
+    .map(|file| {
+      file
+        .strip_prefix(&run_directory)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+    })
+    .collect::<Vec<_>>();
+  Ok(PartialStackConfig {
+    server_id: server_id.to_string().into(),
+    project_name: project.name.clone().into(),
+    files_on_host: true.into(),
+    run_directory: run_directory.display().to_string().into(),
+    file_paths: file_paths.into(),
+    ..Default::default()
+  })
+}
+
 impl Resolve<WriteArgs> for DeleteStack {
   #[instrument(name = "DeleteStack", skip(args))]
   async fn resolve(self, args: &WriteArgs) -> serror::Result<Stack> {
@@ -109,6 +229,8 @@ impl Resolve<WriteArgs> for WriteStackFileContents {
       stack,
       file_path,
       contents,
+      validate,
+      sync_strategy,
     } = self;
     let stack = get_check_permissions::<Stack>(
       &stack,
@@ -133,7 +255,7 @@ impl Resolve<WriteArgs> for WriteStackFileContents {
 
     if stack.config.files_on_host {
       write_stack_file_contents_on_host(
-        stack, file_path, contents, update,
+        stack, file_path, contents, validate, update,
       )
       .await
     } else {
@@ -142,6 +264,7 @@ impl Resolve<WriteArgs> for WriteStackFileContents {
         &file_path,
         &contents,
         &user.username,
+        sync_strategy,
         update,
       )
       .await
@@ -153,6 +276,7 @@ async fn write_stack_file_contents_on_host(
   stack: Stack,
   file_path: String,
   contents: String,
+  validate: bool,
   mut update: Update,
 ) -> serror::Result<Update> {
   if stack.config.server_id.is_empty() {
@@ -176,6 +300,7 @@ async fn write_stack_file_contents_on_host(
       run_directory: stack.config.run_directory,
       file_path,
       contents,
+      validate,
     })
     .await
     .context("Failed to write contents to host")
@@ -225,6 +350,7 @@ async fn write_stack_file_contents_git(
   file_path: &str,
   contents: &str,
   username: &str,
+  sync_strategy: GitSyncStrategy,
   mut update: Update,
 ) -> serror::Result<Update> {
   let mut repo = if !stack.config.linked_repo.is_empty() {
@@ -281,6 +407,22 @@ async fn write_stack_file_contents_git(
 
   // Save this for later -- repo_args moved next.
   let branch = repo_args.branch.clone();
+
+  // The pull rebases local commits onto the remote,
+  // so check for divergence before it.
+  match git::sync_with_remote(&root, &branch, sync_strategy)
+    .await
+    .context("Repo is not in sync with remote")
+  {
+    Ok(logs) => update.logs.extend(logs),
+    Err(e) => {
+      update.push_error_log("Sync Repo", format_serror(&e.into()));
+      update.finalize();
+      update.id = add_update(update.clone()).await?;
+      return Ok(update);
+    }
+  }
+
   // Pull latest changes to repo to ensure linear commit history
   match git::pull_or_clone(
     repo_args,
@@ -776,3 +918,78 @@ impl Resolve<WriteArgs> for DeleteStackWebhook {
     Ok(NoData {})
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn project(compose_files: &[&str]) -> ComposeProject {
+    ComposeProject {
+      name: String::from("app"),
+      status: None,
+      compose_files: compose_files
+        .iter()
+        .map(|file| file.to_string())
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn uses_compose_file_directory() {
+    let config = compose_project_stack_config(
+      "server",
+      &project(&["/opt/app/compose.yaml"]),
+    )
+    .unwrap();
+    assert_eq!(config.server_id.as_deref(), Some("server"));
+    assert_eq!(config.project_name.as_deref(), Some("app"));
+    assert_eq!(config.files_on_host, Some(true));
+    assert_eq!(config.run_directory.as_deref(), Some("/opt/app"));
+    assert_eq!(
+      config.file_paths,
+      Some(vec![String::from("compose.yaml")])
+    );
+  }
+
+  #[test]
+  fn uses_common_directory_of_compose_files() {
+    let config = compose_project_stack_config(
+      "server",
+      &project(&[
+        "/opt/app/compose.yaml",
+        " /opt/app/overrides/prod.yaml ",
+      ]),
+    )
+    .unwrap();
+    assert_eq!(config.run_directory.as_deref(), Some("/opt/app"));
+    assert_eq!(
+      config.file_paths,
+      Some(vec![
+        String::from("compose.yaml"),
+        String::from("overrides/prod.yaml")
+      ])
+    );
+
+    let config = compose_project_stack_config(
+      "server",
+      &project(&["/opt/app/compose.yaml", "/srv/extra.yaml"]),
+    )
+    .unwrap();
+    assert_eq!(config.run_directory.as_deref(), Some("/"));
+    assert_eq!(
+      config.file_paths,
+      Some(vec![
+        String::from("opt/app/compose.yaml"),
+        String::from("srv/extra.yaml")
+      ])
+    );
+  }
+
+  #[test]
+  fn rejects_project_without_compose_files() {
+    assert!(
+      compose_project_stack_config("server", &project(&[" "]))
+        .is_err()
+    );
+  }
+}
@@ -39,6 +39,7 @@ use komodo_client::{
 use octorust::types::{
   ReposCreateWebhookRequest, ReposCreateWebhookRequestConfig,
 };
+use partial_derive2::HasPartial;
 use resolver_api::Resolve;
 
 use crate::{
@@ -86,6 +87,7 @@ impl Resolve<WriteArgs> for CopyResourceSync {
         PermissionLevel::Write.into(),
       )
       .await?;
+    let config = config.merge_partial(self.config);
     resource::create::<ResourceSync>(&self.name, config.into(), user)
       .await
   }
@@ -461,6 +463,7 @@ impl Resolve<WriteArgs> for CommitSync {
       tags: sync.config.match_tags.clone(),
       include_variables: sync.config.include_variables,
       include_user_groups: sync.config.include_user_groups,
+      ..Default::default()
     }
     .resolve(&ReadArgs {
       user: sync_user().to_owned(),
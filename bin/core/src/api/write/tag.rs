@@ -6,11 +6,15 @@ use database::mungos::{
   mongodb::bson::{doc, oid::ObjectId},
 };
 use komodo_client::{
-  api::write::{CreateTag, DeleteTag, RenameTag, UpdateTagColor},
+  api::write::{
+    BatchAddTags, BatchRemoveTags, BatchUpdateTagsResponse,
+    CreateTag, DeleteTag, RenameTag, UpdateTagColor,
+  },
   entities::{
-    action::Action, alerter::Alerter, build::Build, builder::Builder,
-    deployment::Deployment, procedure::Procedure, repo::Repo,
-    server::Server, stack::Stack, sync::ResourceSync, tag::Tag,
+    ResourceTargetVariant, action::Action, alerter::Alerter,
+    build::Build, builder::Builder, deployment::Deployment,
+    procedure::Procedure, repo::Repo, server::Server, stack::Stack,
+    sync::ResourceSync, tag::Tag,
   },
 };
 use reqwest::StatusCode;
@@ -139,3 +143,146 @@ impl Resolve<WriteArgs> for DeleteTag {
     Ok(tag)
   }
 }
+
+impl Resolve<WriteArgs> for BatchAddTags {
+  #[instrument(name = "BatchAddTags", skip(args))]
+  async fn resolve(
+    self,
+    args: &WriteArgs,
+  ) -> serror::Result<BatchUpdateTagsResponse> {
+    batch_update_tags(
+      self.resource_type,
+      &self.pattern,
+      &self.tags,
+      true,
+      args,
+    )
+    .await
+  }
+}
+
+impl Resolve<WriteArgs> for BatchRemoveTags {
+  #[instrument(name = "BatchRemoveTags", skip(args))]
+  async fn resolve(
+    self,
+    args: &WriteArgs,
+  ) -> serror::Result<BatchUpdateTagsResponse> {
+    batch_update_tags(
+      self.resource_type,
+      &self.pattern,
+      &self.tags,
+      false,
+      args,
+    )
+    .await
+  }
+}
+
+async fn batch_update_tags(
+  resource_type: ResourceTargetVariant,
+  pattern: &str,
+  tags: &[String],
+  add: bool,
+  args: &WriteArgs,
+) -> serror::Result<BatchUpdateTagsResponse> {
+  if tags.is_empty() {
+    return Err(
+      anyhow!("Must provide at least one tag")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  let res = match resource_type {
+    ResourceTargetVariant::System => {
+      return Err(
+        anyhow!("Cannot tag System resource target")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+    ResourceTargetVariant::Server => {
+      resource::batch_update_tags::<Server>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::Stack => {
+      resource::batch_update_tags::<Stack>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::Deployment => {
+      resource::batch_update_tags::<Deployment>(
+        pattern, tags, add, args,
+      )
+      .await?
+    }
+    ResourceTargetVariant::Build => {
+      resource::batch_update_tags::<Build>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::Repo => {
+      resource::batch_update_tags::<Repo>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::Procedure => {
+      resource::batch_update_tags::<Procedure>(
+        pattern, tags, add, args,
+      )
+      .await?
+    }
+    ResourceTargetVariant::Action => {
+      resource::batch_update_tags::<Action>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::ResourceSync => {
+      resource::batch_update_tags::<ResourceSync>(
+        pattern, tags, add, args,
+      )
+      .await?
+    }
+    ResourceTargetVariant::Builder => {
+      resource::batch_update_tags::<Builder>(pattern, tags, add, args)
+        .await?
+    }
+    ResourceTargetVariant::Alerter => {
+      resource::batch_update_tags::<Alerter>(pattern, tags, add, args)
+        .await?
+    }
+  };
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args() -> WriteArgs {
+    WriteArgs {
+      user: Default::default(),
+    }
+  }
+
+  #[tokio::test]
+  async fn batch_tags_require_a_tag() {
+    let e = batch_update_tags(
+      ResourceTargetVariant::Server,
+      "*",
+      &[],
+      true,
+      &args(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn batch_tags_reject_system() {
+    let e = batch_update_tags(
+      ResourceTargetVariant::System,
+      "*",
+      &[String::from("prod")],
+      false,
+      &args(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+}
@@ -17,7 +17,10 @@ use reqwest::StatusCode;
 use resolver_api::Resolve;
 use serror::AddStatusCodeError;
 
-use crate::{config::core_config, state::db_client};
+use crate::{
+  auth::local::validate_password, config::core_config,
+  state::db_client,
+};
 
 use super::WriteArgs;
 
@@ -50,6 +53,8 @@ impl Resolve<WriteArgs> for CreateLocalUser {
       return Err(anyhow!("Password cannot be empty.").into());
     }
 
+    validate_password(&self.password)?;
+
     let db = db_client();
 
     if db
@@ -75,6 +80,10 @@ impl Resolve<WriteArgs> for CreateLocalUser {
       create_build_permissions: false,
       updated_at: ts,
       last_update_view: 0,
+      tokens_valid_after: 0,
+      failed_logins: 0,
+      last_failed_login_at: 0,
+      locked_until: 0,
       recents: Default::default(),
       all: Default::default(),
       config: UserConfig::Local {
@@ -167,6 +176,7 @@ impl Resolve<WriteArgs> for UpdateUserPassword {
         );
       }
     }
+    validate_password(&self.password)?;
     db_client().set_user_password(user, &self.password).await?;
     Ok(NoData {})
   }
@@ -0,0 +1,153 @@
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::anyhow;
+use colored::Colorize;
+use database::mungos::mongodb::bson::Document;
+
+use crate::{
+  config::core_config, helpers::random_string, state::db_client,
+};
+
+/// The token is taken when the first admin account is claimed.
+fn bootstrap_token() -> &'static Mutex<Option<String>> {
+  static BOOTSTRAP_TOKEN: OnceLock<Mutex<Option<String>>> =
+    OnceLock::new();
+  BOOTSTRAP_TOKEN.get_or_init(Default::default)
+}
+
+/// Generates the bootstrap token and prints it to the logs,
+/// if `require_bootstrap_token` is enabled and no users exist yet.
+pub async fn init_bootstrap_token() {
+  if !core_config().require_bootstrap_token {
+    return;
+  }
+  match db_client().users.find_one(Document::new()).await {
+    Ok(None) => {}
+    Ok(Some(_)) => return,
+    Err(e) => {
+      error!(
+        "Failed to initialize bootstrap token | Failed to query db | {e:?}"
+      );
+      return;
+    }
+  }
+  let token = random_string(40);
+  warn!(
+    "{} | No users exist. Claim the first admin account by signing up with bootstrap token: {}",
+    "BOOTSTRAP".yellow(),
+    token.bold()
+  );
+  *bootstrap_token().lock().unwrap() = Some(token);
+}
+
+/// Called when a user is created. If it is the first user and
+/// `require_bootstrap_token` is enabled, the bootstrap token must be passed.
+///
+/// The token is held by the returned claim, and is only used up
+/// once [BootstrapClaim::complete] is called after the user is created.
+pub fn claim_first_admin(
+  first_user: bool,
+  token: Option<&str>,
+) -> anyhow::Result<BootstrapClaim> {
+  if !first_user || !core_config().require_bootstrap_token {
+    return Ok(BootstrapClaim::default());
+  }
+  claim_token(bootstrap_token(), token)
+}
+
+fn claim_token(
+  slot: &'static Mutex<Option<String>>,
+  token: Option<&str>,
+) -> anyhow::Result<BootstrapClaim> {
+  let mut bootstrap_token = slot.lock().unwrap();
+  let Some(expected) = bootstrap_token.as_deref() else {
+    return Err(anyhow!(
+      "The bootstrap token has already been used. Restart Komodo Core to generate a new one."
+    ));
+  };
+  match token {
+    Some(token) if token == expected => Ok(BootstrapClaim {
+      slot: Some(slot),
+      token: bootstrap_token.take(),
+    }),
+    Some(_) => Err(anyhow!("Invalid bootstrap token")),
+    None => Err(anyhow!(
+      "The first admin account must be claimed using local sign up with the bootstrap token printed in the Komodo Core logs"
+    )),
+  }
+}
+
+/// Holds the bootstrap token while the first admin is created,
+/// so concurrent sign ups can't also use it.
+/// Dropping the claim without completing it restores the token,
+/// so it isn't used up if creating the user fails.
+#[derive(Default)]
+#[must_use]
+pub struct BootstrapClaim {
+  slot: Option<&'static Mutex<Option<String>>>,
+  token: Option<String>,
+}
+
+impl BootstrapClaim {
+  /// Uses up the bootstrap token, once the first admin is created.
+  pub fn complete(mut self) {
+    self.token = None;
+  }
+}
+
+impl Drop for BootstrapClaim {
+  fn drop(&mut self) {
+    if let (Some(slot), Some(token)) = (self.slot, self.token.take())
+    {
+      *slot.lock().unwrap() = Some(token);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn slot(token: &str) -> &'static Mutex<Option<String>> {
+    Box::leak(Box::new(Mutex::new(Some(token.to_string()))))
+  }
+
+  #[test]
+  fn token_is_single_use() {
+    let slot = slot("bootstrap");
+    claim_token(slot, Some("bootstrap")).unwrap().complete();
+    assert!(slot.lock().unwrap().is_none());
+    assert!(claim_token(slot, Some("bootstrap")).is_err());
+  }
+
+  #[test]
+  fn token_is_held_during_claim() {
+    let slot = slot("bootstrap");
+    let claim = claim_token(slot, Some("bootstrap")).unwrap();
+    assert!(claim_token(slot, Some("bootstrap")).is_err());
+    claim.complete();
+  }
+
+  #[test]
+  fn failed_sign_up_restores_token() {
+    let slot = slot("bootstrap");
+    drop(claim_token(slot, Some("bootstrap")).unwrap());
+    assert_eq!(slot.lock().unwrap().as_deref(), Some("bootstrap"));
+    claim_token(slot, Some("bootstrap")).unwrap().complete();
+  }
+
+  #[test]
+  fn rejects_wrong_token() {
+    let slot = slot("bootstrap");
+    let e = claim_token(slot, Some("wrong")).err().unwrap();
+    assert_eq!(e.to_string(), "Invalid bootstrap token");
+    assert_eq!(slot.lock().unwrap().as_deref(), Some("bootstrap"));
+  }
+
+  #[test]
+  fn rejects_missing_token() {
+    let slot = slot("bootstrap");
+    assert!(claim_token(slot, None).is_err());
+    assert_eq!(slot.lock().unwrap().as_deref(), Some("bootstrap"));
+  }
+}
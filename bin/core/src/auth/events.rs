@@ -0,0 +1,239 @@
+use std::{
+  collections::HashMap,
+  convert::Infallible,
+  net::SocketAddr,
+  sync::{Mutex, OnceLock},
+};
+
+use axum::{
+  extract::{ConnectInfo, FromRequestParts},
+  http::{header::USER_AGENT, request::Parts},
+};
+use komodo_client::entities::{
+  I64, komodo_timestamp,
+  user::{LoginEvent, LoginMethod},
+};
+
+use crate::{api::ip::client_ip, state::db_client};
+
+/// Successful api key logins are recorded at most this often per key,
+/// as every api key request is a login.
+const API_KEY_LOGIN_INTERVAL_MS: I64 = 60 * 60 * 1000;
+
+/// Failed logins from the same ip and username are recorded
+/// at most this many times per [FAILED_LOGIN_WINDOW_MS].
+/// The rest are counted and noted on the next recorded failure.
+const FAILED_LOGIN_EVENT_LIMIT: u32 = 5;
+const FAILED_LOGIN_WINDOW_MS: I64 = 60 * 1000;
+
+/// The client information recorded with login events.
+#[derive(Debug, Clone, Default)]
+pub struct LoginContext {
+  pub ip: Option<String>,
+  pub user_agent: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for LoginContext {
+  type Rejection = Infallible;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    _: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let ip = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(
+      |ConnectInfo(addr)| {
+        client_ip(&parts.headers, addr.ip()).to_string()
+      },
+    );
+    let user_agent = parts
+      .headers
+      .get(USER_AGENT)
+      .and_then(|agent| agent.to_str().ok())
+      .map(str::to_string);
+    Ok(LoginContext { ip, user_agent })
+  }
+}
+
+/// Records a login attempt in the background.
+/// Pass empty `user_id` / `username` if the user isn't known.
+pub fn record_login_event(
+  method: LoginMethod,
+  context: &LoginContext,
+  user_id: &str,
+  username: &str,
+  mut error: Option<String>,
+) {
+  if let Some(e) = &mut error {
+    match throttle_failed_login(context, username) {
+      Some(0) => {}
+      Some(suppressed) => e.push_str(&format!(
+        " ({suppressed} more failed logins were not recorded)"
+      )),
+      None => return,
+    }
+  }
+  let event = LoginEvent {
+    id: Default::default(),
+    ts: komodo_timestamp(),
+    user_id: user_id.to_string(),
+    username: username.to_string(),
+    method,
+    ip: context.ip.clone(),
+    user_agent: context.user_agent.clone(),
+    success: error.is_none(),
+    error,
+  };
+  tokio::spawn(async move {
+    if let Err(e) = db_client().login_events.insert_one(&event).await
+    {
+      warn!("Failed to record login event | {e:?}");
+    }
+  });
+}
+
+/// Records api key logins, throttling successful ones
+/// to once per [API_KEY_LOGIN_INTERVAL_MS] for each key.
+pub fn record_api_key_login_event(
+  key: &str,
+  context: &LoginContext,
+  user_id: &str,
+  username: &str,
+  error: Option<String>,
+) {
+  if error.is_none() {
+    static LAST_LOGINS: OnceLock<Mutex<HashMap<String, I64>>> =
+      OnceLock::new();
+    let now = komodo_timestamp();
+    let mut last_logins =
+      LAST_LOGINS.get_or_init(Default::default).lock().unwrap();
+    match last_logins.get(key) {
+      Some(last) if now - last < API_KEY_LOGIN_INTERVAL_MS => return,
+      _ => {
+        last_logins.insert(key.to_string(), now);
+      }
+    }
+  }
+  record_login_event(
+    LoginMethod::ApiKey,
+    context,
+    user_id,
+    username,
+    error,
+  );
+}
+
+/// Failed logins from one ip and username in the current window.
+#[derive(Debug, Clone, Copy, Default)]
+struct FailedLogins {
+  window_start: I64,
+  recorded: u32,
+  suppressed: u32,
+}
+
+/// Returns None if the failed login shouldn't be recorded,
+/// otherwise the number of failures suppressed since the last one recorded.
+fn throttle_failed_login(
+  context: &LoginContext,
+  username: &str,
+) -> Option<u32> {
+  static FAILED_LOGINS: OnceLock<
+    Mutex<HashMap<(Option<String>, String), FailedLogins>>,
+  > = OnceLock::new();
+  let now = komodo_timestamp();
+  let mut failed_logins =
+    FAILED_LOGINS.get_or_init(Default::default).lock().unwrap();
+  if failed_logins.len() > 1000 {
+    failed_logins.retain(|_, failed| {
+      failed.suppressed > 0
+        || now - failed.window_start < FAILED_LOGIN_WINDOW_MS
+    });
+  }
+  let failed = failed_logins
+    .entry((context.ip.clone(), username.to_string()))
+    .or_default();
+  let res = next_failed_login(failed, now);
+  if res.is_none() && failed.suppressed == 1 {
+    warn!(
+      "Throttling failed login events | ip: {:?} | username: {username}",
+      context.ip
+    );
+  }
+  res
+}
+
+/// Counts a failed login towards the window, starting
+/// a new window once [FAILED_LOGIN_WINDOW_MS] has passed.
+fn next_failed_login(
+  failed: &mut FailedLogins,
+  now: I64,
+) -> Option<u32> {
+  if now - failed.window_start >= FAILED_LOGIN_WINDOW_MS {
+    let suppressed = failed.suppressed;
+    *failed = FailedLogins {
+      window_start: now,
+      recorded: 1,
+      suppressed: 0,
+    };
+    return Some(suppressed);
+  }
+  if failed.recorded < FAILED_LOGIN_EVENT_LIMIT {
+    failed.recorded += 1;
+    // Carry failures suppressed in an earlier
+    // window over to the next recorded one.
+    let suppressed = failed.suppressed;
+    failed.suppressed = 0;
+    Some(suppressed)
+  } else {
+    failed.suppressed += 1;
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn records_failed_logins_up_to_limit() {
+    let mut failed = FailedLogins::default();
+    let now = 1_000_000;
+    for _ in 0..FAILED_LOGIN_EVENT_LIMIT {
+      assert_eq!(next_failed_login(&mut failed, now), Some(0));
+    }
+    assert_eq!(next_failed_login(&mut failed, now + 1), None);
+    assert_eq!(next_failed_login(&mut failed, now + 2), None);
+    assert_eq!(failed.suppressed, 2);
+  }
+
+  #[test]
+  fn notes_suppressed_failures_in_next_window() {
+    let mut failed = FailedLogins::default();
+    let now = 1_000_000;
+    for _ in 0..FAILED_LOGIN_EVENT_LIMIT + 3 {
+      next_failed_login(&mut failed, now);
+    }
+    let next = now + FAILED_LOGIN_WINDOW_MS;
+    assert_eq!(next_failed_login(&mut failed, next), Some(3));
+    assert_eq!(next_failed_login(&mut failed, next), Some(0));
+  }
+
+  #[test]
+  fn sources_are_throttled_separately() {
+    let attacker = LoginContext {
+      ip: Some(String::from("203.0.113.7")),
+      user_agent: None,
+    };
+    let other = LoginContext {
+      ip: Some(String::from("203.0.113.8")),
+      user_agent: None,
+    };
+    for _ in 0..FAILED_LOGIN_EVENT_LIMIT {
+      assert!(
+        throttle_failed_login(&attacker, "throttled").is_some()
+      );
+    }
+    assert!(throttle_failed_login(&attacker, "throttled").is_none());
+    assert!(throttle_failed_login(&other, "throttled").is_some());
+    assert!(throttle_failed_login(&attacker, "other").is_some());
+  }
+}
@@ -5,12 +5,16 @@ use axum::{
 };
 use database::mongo_indexed::Document;
 use database::mungos::mongodb::bson::doc;
-use komodo_client::entities::user::{User, UserConfig};
+use komodo_client::entities::user::{LoginMethod, User, UserConfig};
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serror::AddStatusCode;
 
 use crate::{
+  auth::{
+    bootstrap::claim_first_admin,
+    events::{LoginContext, record_login_event},
+  },
   config::core_config,
   helpers::random_string,
   state::{db_client, jwt_client},
@@ -39,8 +43,19 @@ pub fn router() -> Router {
     )
     .route(
       "/callback",
-      get(|query| async {
-        callback(query).await.status_code(StatusCode::UNAUTHORIZED)
+      get(|context: LoginContext, query| async move {
+        callback(&context, query)
+          .await
+          .inspect_err(|e| {
+            record_login_event(
+              LoginMethod::Google,
+              &context,
+              "",
+              "",
+              Some(format!("{e:#}")),
+            )
+          })
+          .status_code(StatusCode::UNAUTHORIZED)
       }),
     )
 }
@@ -54,6 +69,7 @@ struct CallbackQuery {
 
 #[instrument(name = "GoogleCallback", level = "debug")]
 async fn callback(
+  context: &LoginContext,
   Query(query): Query<CallbackQuery>,
 ) -> anyhow::Result<Redirect> {
   // Safe: the method is only called after the client is_some
@@ -81,9 +97,18 @@ async fn callback(
     .await
     .context("failed at find user query from mongo")?;
   let jwt = match user {
-    Some(user) => jwt_client()
-      .encode(user.id)
-      .context("failed to generate jwt")?,
+    Some(user) => {
+      record_login_event(
+        LoginMethod::Google,
+        context,
+        &user.id,
+        &user.username,
+        None,
+      );
+      jwt_client()
+        .encode(user.id)
+        .context("failed to generate jwt")?
+    }
     None => {
       let ts = unix_timestamp_ms() as i64;
       let no_users_exist =
@@ -92,6 +117,7 @@ async fn callback(
       if !no_users_exist && core_config.disable_user_registration {
         return Err(anyhow!("User registration is disabled"));
       }
+      let bootstrap = claim_first_admin(no_users_exist, None)?;
       let mut username = google_user
         .email
         .split('@')
@@ -121,6 +147,10 @@ async fn callback(
         create_build_permissions: no_users_exist,
         updated_at: ts,
         last_update_view: 0,
+        tokens_valid_after: 0,
+        failed_logins: 0,
+        last_failed_login_at: 0,
+        locked_until: 0,
         recents: Default::default(),
         all: Default::default(),
         config: UserConfig::Google {
@@ -130,13 +160,21 @@ async fn callback(
       };
       let user_id = db_client
         .users
-        .insert_one(user)
+        .insert_one(&user)
         .await
         .context("failed to create user on mongo")?
         .inserted_id
         .as_object_id()
         .context("inserted_id is not ObjectId")?
         .to_string();
+      bootstrap.complete();
+      record_login_event(
+        LoginMethod::Google,
+        context,
+        &user_id,
+        &user.username,
+        None,
+      );
       jwt_client()
         .encode(user_id)
         .context("failed to generate jwt")?
@@ -1,22 +1,35 @@
 use std::str::FromStr;
 
 use anyhow::{Context, anyhow};
-use async_timing_util::unix_timestamp_ms;
+use async_timing_util::{get_timelength_in_ms, unix_timestamp_ms};
 use database::{
   hash_password,
-  mungos::mongodb::bson::{Document, doc, oid::ObjectId},
+  mungos::{
+    by_id::update_one_by_id,
+    mongodb::{
+      bson::{Document, doc, oid::ObjectId},
+      options::ReturnDocument,
+    },
+    update::Update,
+  },
 };
 use komodo_client::{
   api::auth::{
     LoginLocalUser, LoginLocalUserResponse, SignUpLocalUser,
     SignUpLocalUserResponse,
   },
-  entities::user::{User, UserConfig},
+  entities::{
+    config::core::CoreConfig,
+    user::{LoginMethod, User, UserConfig},
+  },
 };
+use reqwest::StatusCode;
 use resolver_api::Resolve;
+use serror::AddStatusCodeError;
 
 use crate::{
   api::auth::AuthArgs,
+  auth::{bootstrap::claim_first_admin, events::record_login_event},
   config::core_config,
   state::{db_client, jwt_client},
 };
@@ -27,123 +40,483 @@ impl Resolve<AuthArgs> for SignUpLocalUser {
     self,
     _: &AuthArgs,
   ) -> serror::Result<SignUpLocalUserResponse> {
-    let core_config = core_config();
+    sign_up_local_user(self, true).await
+  }
+}
 
-    if !core_config.local_auth {
-      return Err(anyhow!("Local auth is not enabled").into());
-    }
+/// Creates a local user. The password policy is not enforced
+/// for the init admin user, which is created on first startup.
+pub async fn sign_up_local_user(
+  request: SignUpLocalUser,
+  enforce_password_policy: bool,
+) -> serror::Result<SignUpLocalUserResponse> {
+  let core_config = core_config();
 
-    if self.username.is_empty() {
-      return Err(anyhow!("Username cannot be empty string").into());
-    }
+  if !core_config.local_auth {
+    return Err(anyhow!("Local auth is not enabled").into());
+  }
 
-    if ObjectId::from_str(&self.username).is_ok() {
-      return Err(
-        anyhow!("Username cannot be valid ObjectId").into(),
-      );
-    }
+  if request.username.is_empty() {
+    return Err(anyhow!("Username cannot be empty string").into());
+  }
 
-    if self.password.is_empty() {
-      return Err(anyhow!("Password cannot be empty string").into());
-    }
+  if ObjectId::from_str(&request.username).is_ok() {
+    return Err(anyhow!("Username cannot be valid ObjectId").into());
+  }
 
-    let db = db_client();
+  if request.password.is_empty() {
+    return Err(anyhow!("Password cannot be empty string").into());
+  }
 
-    let no_users_exist =
-      db.users.find_one(Document::new()).await?.is_none();
+  if enforce_password_policy {
+    validate_password(&request.password)?;
+  }
 
-    if !no_users_exist && core_config.disable_user_registration {
-      return Err(anyhow!("User registration is disabled").into());
-    }
+  let db = db_client();
 
-    if db
-      .users
-      .find_one(doc! { "username": &self.username })
-      .await
-      .context("Failed to query for existing users")?
-      .is_some()
-    {
-      return Err(anyhow!("Username already taken.").into());
-    }
+  let no_users_exist =
+    db.users.find_one(Document::new()).await?.is_none();
 
-    let ts = unix_timestamp_ms() as i64;
-    let hashed_password = hash_password(self.password)?;
-
-    let user = User {
-      id: Default::default(),
-      username: self.username,
-      enabled: no_users_exist || core_config.enable_new_users,
-      admin: no_users_exist,
-      super_admin: no_users_exist,
-      create_server_permissions: no_users_exist,
-      create_build_permissions: no_users_exist,
-      updated_at: ts,
-      last_update_view: 0,
-      recents: Default::default(),
-      all: Default::default(),
-      config: UserConfig::Local {
-        password: hashed_password,
-      },
-    };
+  if !no_users_exist && core_config.disable_user_registration {
+    return Err(anyhow!("User registration is disabled").into());
+  }
 
-    let user_id = db_client()
-      .users
-      .insert_one(user)
-      .await
-      .context("failed to create user")?
-      .inserted_id
-      .as_object_id()
-      .context("inserted_id is not ObjectId")?
-      .to_string();
+  if db
+    .users
+    .find_one(doc! { "username": &request.username })
+    .await
+    .context("Failed to query for existing users")?
+    .is_some()
+  {
+    return Err(anyhow!("Username already taken.").into());
+  }
 
-    jwt_client()
-      .encode(user_id.clone())
-      .context("failed to generate jwt for user")
-      .map_err(Into::into)
+  let bootstrap = claim_first_admin(
+    no_users_exist,
+    request.bootstrap_token.as_deref(),
+  )?;
+
+  let ts = unix_timestamp_ms() as i64;
+  let hashed_password = hash_password(request.password)?;
+
+  let user = User {
+    id: Default::default(),
+    username: request.username,
+    enabled: no_users_exist || core_config.enable_new_users,
+    admin: no_users_exist,
+    super_admin: no_users_exist,
+    create_server_permissions: no_users_exist,
+    create_build_permissions: no_users_exist,
+    updated_at: ts,
+    last_update_view: 0,
+    tokens_valid_after: 0,
+    failed_logins: 0,
+    last_failed_login_at: 0,
+    locked_until: 0,
+    recents: Default::default(),
+    all: Default::default(),
+    config: UserConfig::Local {
+      password: hashed_password,
+    },
+  };
+
+  let user_id = db_client()
+    .users
+    .insert_one(user)
+    .await
+    .context("failed to create user")?
+    .inserted_id
+    .as_object_id()
+    .context("inserted_id is not ObjectId")?
+    .to_string();
+  bootstrap.complete();
+
+  jwt_client()
+    .encode(user_id.clone())
+    .context("failed to generate jwt for user")
+    .map_err(Into::into)
+}
+
+/// Commonly used passwords, rejected when `password_deny_common` is enabled.
+const COMMON_PASSWORDS: [&str; 20] = [
+  "changeme",
+  "password",
+  "password1",
+  "passw0rd",
+  "12345678",
+  "123456789",
+  "1234567890",
+  "qwerty123",
+  "qwertyuiop",
+  "iloveyou",
+  "admin123",
+  "administrator",
+  "letmein1",
+  "welcome1",
+  "sunshine",
+  "football",
+  "baseball",
+  "abc12345",
+  "11111111",
+  "komodo123",
+];
+
+/// Checks a local user password against the configured password policy.
+pub fn validate_password(password: &str) -> anyhow::Result<()> {
+  check_password_policy(password, core_config())
+}
+
+fn check_password_policy(
+  password: &str,
+  config: &CoreConfig,
+) -> anyhow::Result<()> {
+  if password.chars().count() < config.password_min_length {
+    return Err(anyhow!(
+      "Password must be at least {} characters",
+      config.password_min_length
+    ));
+  }
+  let classes = [
+    password.chars().any(|c| c.is_lowercase()),
+    password.chars().any(|c| c.is_uppercase()),
+    password.chars().any(|c| c.is_numeric()),
+    password.chars().any(|c| !c.is_alphanumeric()),
+  ]
+  .into_iter()
+  .filter(|class| *class)
+  .count();
+  if classes < config.password_min_character_classes as usize {
+    return Err(anyhow!(
+      "Password must contain at least {} of: lowercase letters, uppercase letters, digits, symbols",
+      config.password_min_character_classes
+    ));
   }
+  if config.password_deny_common
+    && COMMON_PASSWORDS
+      .iter()
+      .any(|common| common.eq_ignore_ascii_case(password))
+  {
+    return Err(anyhow!("Password is too common"));
+  }
+  Ok(())
 }
 
 impl Resolve<AuthArgs> for LoginLocalUser {
   #[instrument(name = "LoginLocalUser", level = "debug", skip(self))]
   async fn resolve(
     self,
-    _: &AuthArgs,
+    AuthArgs { context, .. }: &AuthArgs,
   ) -> serror::Result<LoginLocalUserResponse> {
     if !core_config().local_auth {
       return Err(anyhow!("local auth is not enabled").into());
     }
 
-    let user = db_client()
+    let Some(user) = db_client()
       .users
       .find_one(doc! { "username": &self.username })
       .await
       .context("failed at db query for users")?
-      .with_context(|| {
-        format!("did not find user with username {}", self.username)
-      })?;
+    else {
+      let e =
+        format!("did not find user with username {}", self.username);
+      record_login_event(
+        LoginMethod::Local,
+        context,
+        "",
+        &self.username,
+        Some(e.clone()),
+      );
+      return Err(anyhow!(e).into());
+    };
 
     let UserConfig::Local {
       password: user_pw_hash,
-    } = user.config
+    } = &user.config
     else {
-      return Err(
-        anyhow!(
-          "non-local auth users can not log in with a password"
-        )
-        .into(),
+      let e = "non-local auth users can not log in with a password";
+      record_login_event(
+        LoginMethod::Local,
+        context,
+        &user.id,
+        &user.username,
+        Some(e.to_string()),
       );
+      return Err(anyhow!(e).into());
     };
 
-    let verified = bcrypt::verify(self.password, &user_pw_hash)
+    let now = unix_timestamp_ms() as i64;
+
+    if user.locked_until > now {
+      let e = account_locked_error(user.locked_until - now);
+      record_login_event(
+        LoginMethod::Local,
+        context,
+        &user.id,
+        &user.username,
+        Some(e.to_string()),
+      );
+      return Err(e.status_code(StatusCode::LOCKED));
+    }
+
+    let verified = bcrypt::verify(self.password, user_pw_hash)
       .context("failed at verify password")?;
 
     if !verified {
-      return Err(anyhow!("invalid credentials").into());
+      let locked_for = record_failed_login(&user, now).await?;
+      let e = match locked_for {
+        Some(locked_for) => account_locked_error(locked_for),
+        None => anyhow!("invalid credentials"),
+      };
+      record_login_event(
+        LoginMethod::Local,
+        context,
+        &user.id,
+        &user.username,
+        Some(e.to_string()),
+      );
+      return if locked_for.is_some() {
+        Err(e.status_code(StatusCode::LOCKED))
+      } else {
+        Err(e.into())
+      };
+    }
+
+    // Concurrent failed guesses may have locked the account
+    // after the user was loaded, so the lock is checked again.
+    let locked_until = db_client()
+      .users
+      .find_one_and_update(
+        doc! {
+          "_id": ObjectId::from_str(&user.id)
+            .context("user id is not valid ObjectId")?
+        },
+        doc! { "$set": { "failed_logins": 0 } },
+      )
+      .await
+      .context("failed to reset user failed logins")?
+      .context("user not found")?
+      .locked_until;
+    if locked_until > now {
+      let e = account_locked_error(locked_until - now);
+      record_login_event(
+        LoginMethod::Local,
+        context,
+        &user.id,
+        &user.username,
+        Some(e.to_string()),
+      );
+      return Err(e.status_code(StatusCode::LOCKED));
     }
 
+    record_login_event(
+      LoginMethod::Local,
+      context,
+      &user.id,
+      &user.username,
+      None,
+    );
+
     jwt_client()
       .encode(user.id.clone())
       .context("failed at generating jwt for user")
       .map_err(Into::into)
   }
 }
+
+fn account_locked_error(locked_for_ms: i64) -> anyhow::Error {
+  anyhow!(
+    "Account is locked after too many failed logins. Try again in {} seconds.",
+    (locked_for_ms + 999) / 1000
+  )
+}
+
+/// Counts the failed login towards the lockout,
+/// locking the account once `login_lockout_attempts` is reached.
+/// Returns how long the account is locked for, in ms, if it was locked.
+///
+/// The count is incremented atomically, so concurrent guesses
+/// can't all read the same count and slip past the lockout.
+async fn record_failed_login(
+  user: &User,
+  now: i64,
+) -> anyhow::Result<Option<i64>> {
+  let config = core_config();
+  if config.login_lockout_attempts == 0 {
+    return Ok(None);
+  }
+  let window = get_timelength_in_ms(
+    config.login_lockout_window.to_string().parse()?,
+  ) as i64;
+  let id = ObjectId::from_str(&user.id)
+    .context("user id is not valid ObjectId")?;
+  let users = &db_client().users;
+
+  // Start counting again if the last failure is outside the window.
+  users
+    .update_one(
+      doc! {
+        "_id": id,
+        "last_failed_login_at": { "$lt": now - window },
+      },
+      doc! { "$set": { "failed_logins": 0 } },
+    )
+    .await
+    .context("failed to reset user failed logins")?;
+
+  let failed_logins = users
+    .find_one_and_update(
+      doc! { "_id": id },
+      doc! {
+        "$inc": { "failed_logins": 1 },
+        "$set": { "last_failed_login_at": now },
+      },
+    )
+    .return_document(ReturnDocument::After)
+    .await
+    .context("failed to record failed login on user")?
+    .context("user not found")?
+    .failed_logins;
+
+  let duration = get_timelength_in_ms(
+    config.login_lockout_duration.to_string().parse()?,
+  ) as i64;
+  let Some(locked_for) = lock_duration(
+    failed_logins,
+    config.login_lockout_attempts,
+    duration,
+  ) else {
+    return Ok(None);
+  };
+
+  warn!(
+    "Locking user {} after {failed_logins} failed logins",
+    user.username
+  );
+  update_one_by_id(
+    users,
+    &user.id,
+    Update::Set(doc! {
+      "failed_logins": 0,
+      "locked_until": now + locked_for,
+    }),
+    None,
+  )
+  .await
+  .context("failed to lock user")?;
+
+  Ok(Some(locked_for))
+}
+
+/// How long to lock the account for given the failed login count,
+/// or None if it shouldn't be locked.
+fn lock_duration(
+  failed_logins: i64,
+  attempts: u32,
+  duration: i64,
+) -> Option<i64> {
+  (attempts > 0 && failed_logins >= attempts as i64)
+    .then_some(duration)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn locks_once_attempts_reached() {
+    assert_eq!(lock_duration(4, 5, 900_000), None);
+    assert_eq!(lock_duration(5, 5, 900_000), Some(900_000));
+    // Concurrent failures may count past the limit.
+    assert_eq!(lock_duration(7, 5, 900_000), Some(900_000));
+  }
+
+  #[test]
+  fn zero_attempts_never_locks() {
+    assert_eq!(lock_duration(100, 0, 900_000), None);
+  }
+
+  fn policy(
+    min_length: usize,
+    min_character_classes: u8,
+    deny_common: bool,
+  ) -> CoreConfig {
+    CoreConfig {
+      password_min_length: min_length,
+      password_min_character_classes: min_character_classes,
+      password_deny_common: deny_common,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn enforces_min_length() {
+    let config = policy(8, 0, false);
+    let e = check_password_policy("abcdefg", &config).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "Password must be at least 8 characters"
+    );
+    check_password_policy("abcdefgh", &config).unwrap();
+
+    let config = policy(12, 0, false);
+    assert!(check_password_policy("abcdefghijk", &config).is_err());
+    check_password_policy("abcdefghijkl", &config).unwrap();
+  }
+
+  #[test]
+  fn min_length_counts_characters() {
+    let config = policy(8, 0, false);
+    // 14 bytes, but only 7 characters.
+    assert!(check_password_policy("ééééééé", &config).is_err());
+    check_password_policy("éééééééé", &config).unwrap();
+  }
+
+  #[test]
+  fn counts_character_classes() {
+    for (password, classes) in [
+      ("abcdefgh", 1),
+      ("ABCDEFGH", 1),
+      ("12345670", 1),
+      ("!@#$%^&*", 1),
+      ("abcdEFGH", 2),
+      ("abcd1234", 2),
+      ("abcd !?.", 2),
+      ("abCD1234", 3),
+      ("abCD12!?", 4),
+    ] {
+      check_password_policy(password, &policy(8, classes, false))
+        .unwrap();
+      let e = check_password_policy(
+        password,
+        &policy(8, classes + 1, false),
+      )
+      .unwrap_err();
+      assert!(e.to_string().starts_with(&format!(
+        "Password must contain at least {}",
+        classes + 1
+      )));
+    }
+  }
+
+  #[test]
+  fn denies_common_passwords() {
+    for password in ["password1", "PassWord1", "komodo123"] {
+      let e = check_password_policy(password, &policy(8, 1, true))
+        .unwrap_err();
+      assert_eq!(
+        e.to_string(),
+        "Password is too common",
+        "{password}"
+      );
+      check_password_policy(password, &policy(8, 1, false)).unwrap();
+    }
+  }
+
+  #[test]
+  fn default_policy() {
+    let config = CoreConfig::default();
+    check_password_policy("correct horse", &config).unwrap();
+    assert!(check_password_policy("short", &config).is_err());
+    assert!(check_password_policy("password", &config).is_err());
+  }
+}
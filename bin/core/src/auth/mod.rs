@@ -15,15 +15,19 @@ use crate::{
   state::{db_client, jwt_client},
 };
 
-use self::jwt::JwtClaims;
+use self::{
+  events::{LoginContext, record_api_key_login_event},
+  jwt::JwtClaims,
+};
 
+pub mod bootstrap;
+pub mod events;
 pub mod github;
 pub mod google;
 pub mod jwt;
+pub mod local;
 pub mod oidc;
 
-mod local;
-
 const STATE_PREFIX_LENGTH: usize = 20;
 
 #[derive(Debug, Deserialize)]
@@ -33,13 +37,35 @@ struct RedirectQuery {
 
 #[instrument(level = "debug")]
 pub async fn auth_request(
+  context: LoginContext,
   headers: HeaderMap,
   mut req: Request,
   next: Next,
 ) -> serror::Result<Response> {
-  let user = authenticate_check_enabled(&headers)
-    .await
-    .status_code(StatusCode::UNAUTHORIZED)?;
+  let res = authenticate_check_enabled(&headers).await;
+  // Requests using a jwt were recorded at login.
+  if headers.get("authorization").is_none()
+    && let Some(key) =
+      headers.get("x-api-key").and_then(|key| key.to_str().ok())
+  {
+    match &res {
+      Ok(user) => record_api_key_login_event(
+        key,
+        &context,
+        &user.id,
+        &user.username,
+        None,
+      ),
+      Err(e) => record_api_key_login_event(
+        key,
+        &context,
+        "",
+        "",
+        Some(format!("{e:#}")),
+      ),
+    }
+  }
+  let user = res.status_code(StatusCode::UNAUTHORIZED)?;
   req.extensions_mut().insert(user);
   Ok(next.run(req).await)
 }
@@ -48,6 +74,13 @@ pub async fn auth_request(
 pub async fn get_user_id_from_headers(
   headers: &HeaderMap,
 ) -> anyhow::Result<String> {
+  get_user_from_headers(headers).await.map(|user| user.id)
+}
+
+#[instrument(level = "debug")]
+pub async fn get_user_from_headers(
+  headers: &HeaderMap,
+) -> anyhow::Result<User> {
   match (
     headers.get("authorization"),
     headers.get("x-api-key"),
@@ -56,7 +89,7 @@ pub async fn get_user_id_from_headers(
     (Some(jwt), _, _) => {
       // USE JWT
       let jwt = jwt.to_str().context("jwt is not str")?;
-      auth_jwt_get_user_id(jwt)
+      auth_jwt_get_user(jwt)
         .await
         .context("failed to authenticate jwt")
     }
@@ -64,9 +97,10 @@ pub async fn get_user_id_from_headers(
       // USE API KEY / SECRET
       let key = key.to_str().context("key is not str")?;
       let secret = secret.to_str().context("secret is not str")?;
-      auth_api_key_get_user_id(key, secret)
+      let user_id = auth_api_key_get_user_id(key, secret)
         .await
-        .context("failed to authenticate api key")
+        .context("failed to authenticate api key")?;
+      get_user(&user_id).await
     }
     _ => {
       // AUTH FAIL
@@ -81,8 +115,7 @@ pub async fn get_user_id_from_headers(
 pub async fn authenticate_check_enabled(
   headers: &HeaderMap,
 ) -> anyhow::Result<User> {
-  let user_id = get_user_id_from_headers(headers).await?;
-  let user = get_user(&user_id).await?;
+  let user = get_user_from_headers(headers).await?;
   if user.enabled {
     Ok(user)
   } else {
@@ -94,20 +127,43 @@ pub async fn authenticate_check_enabled(
 pub async fn auth_jwt_get_user_id(
   jwt: &str,
 ) -> anyhow::Result<String> {
+  auth_jwt_get_user(jwt).await.map(|user| user.id)
+}
+
+/// Gets the user the jwt was issued to, rejecting
+/// tokens issued before the user's `tokens_valid_after`.
+#[instrument(level = "debug")]
+pub async fn auth_jwt_get_user(jwt: &str) -> anyhow::Result<User> {
   let claims: JwtClaims = jwt_client().decode(jwt)?;
-  if claims.exp > unix_timestamp_ms() {
-    Ok(claims.id)
-  } else {
-    Err(anyhow!("token has expired"))
+  if claims.exp <= unix_timestamp_ms() {
+    return Err(anyhow!("token has expired"));
   }
+  let user = get_user(&claims.id).await?;
+  if token_revoked(&claims, user.tokens_valid_after) {
+    return Err(anyhow!("token has been revoked"));
+  }
+  Ok(user)
+}
+
+/// Tokens issued before the user's `tokens_valid_after`
+/// were revoked by `LogoutEverywhere`.
+fn token_revoked(
+  claims: &JwtClaims,
+  tokens_valid_after: i64,
+) -> bool {
+  (claims.iat as i64) < tokens_valid_after
 }
 
 #[instrument(level = "debug")]
 pub async fn auth_jwt_check_enabled(
   jwt: &str,
 ) -> anyhow::Result<User> {
-  let user_id = auth_jwt_get_user_id(jwt).await?;
-  check_enabled(user_id).await
+  let user = auth_jwt_get_user(jwt).await?;
+  if user.enabled {
+    Ok(user)
+  } else {
+    Err(anyhow!("user not enabled"))
+  }
 }
 
 #[instrument(level = "debug")]
@@ -153,3 +209,63 @@ async fn check_enabled(user_id: String) -> anyhow::Result<User> {
     Err(anyhow!("user not enabled"))
   }
 }
+
+/// Re-checks the credentials of a long lived request, like the event stream,
+/// which was already authenticated by [auth_request].
+/// Fails if the user was disabled, the jwt has expired or was revoked
+/// by `tokens_valid_after`, or the api key was deleted or has expired.
+/// The api secret isn't verified again, as it can't change.
+#[instrument(level = "debug")]
+pub async fn recheck_request_auth(
+  headers: &HeaderMap,
+) -> anyhow::Result<User> {
+  if let Some(jwt) = headers.get("authorization") {
+    let jwt = jwt.to_str().context("jwt is not str")?;
+    return auth_jwt_check_enabled(jwt).await;
+  }
+  let key = headers
+    .get("x-api-key")
+    .context("request has no credentials")?
+    .to_str()
+    .context("key is not str")?;
+  let key = db_client()
+    .api_keys
+    .find_one(doc! { "key": key })
+    .await
+    .context("failed to query db")?
+    .context("api key has been deleted")?;
+  if key.expires != 0 && key.expires < komodo_timestamp() {
+    return Err(anyhow!("api key expired"));
+  }
+  check_enabled(key.user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn claims(iat: u128) -> JwtClaims {
+    JwtClaims {
+      id: String::from("user"),
+      iat,
+      exp: iat + 1_000,
+      session: String::new(),
+    }
+  }
+
+  #[test]
+  fn token_issued_before_logout_revoked() {
+    assert!(token_revoked(&claims(999), 1_000));
+  }
+
+  #[test]
+  fn token_issued_after_logout_accepted() {
+    assert!(!token_revoked(&claims(1_000), 1_000));
+    assert!(!token_revoked(&claims(1_001), 1_000));
+  }
+
+  #[test]
+  fn tokens_accepted_when_never_logged_out() {
+    assert!(!token_revoked(&claims(1), 0));
+  }
+}
@@ -1,7 +1,12 @@
-use std::{sync::OnceLock, time::Duration};
+use std::{
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
 
 use anyhow::Context;
-use arc_swap::ArcSwapOption;
+use dashmap::DashMap;
+use jsonwebtoken::jwk::JwkSet;
+use komodo_client::entities::config::core::OidcProviderConfig;
 use openidconnect::{
   Client, ClientId, ClientSecret, EmptyAdditionalClaims,
   EndpointMaybeSet, EndpointNotSet, EndpointSet, IssuerUrl,
@@ -10,7 +15,7 @@ use openidconnect::{
 
 use crate::config::core_config;
 
-type OidcClient = Client<
+pub type OidcClient = Client<
   EmptyAdditionalClaims,
   CoreAuthDisplay,
   CoreGenderClaim,
@@ -30,64 +35,195 @@ type OidcClient = Client<
   EndpointMaybeSet,
 >;
 
-pub fn oidc_client() -> &'static ArcSwapOption<OidcClient> {
-  static OIDC_CLIENT: OnceLock<ArcSwapOption<OidcClient>> =
-    OnceLock::new();
-  OIDC_CLIENT.get_or_init(Default::default)
+/// The provider configured with the top level `oidc_*` fields.
+pub const DEFAULT_PROVIDER: &str = "";
+
+/// An initialized OIDC provider.
+pub struct OidcProvider {
+  pub config: OidcProviderConfig,
+  pub client: OidcClient,
+  /// The provider issuer, used to verify backchannel logout tokens.
+  pub issuer: String,
+  /// The provider signing keys, used to verify backchannel logout tokens.
+  pub keys: JwkSet,
+}
+
+/// Provider name -> provider
+fn oidc_providers() -> &'static DashMap<String, Arc<OidcProvider>> {
+  static OIDC_PROVIDERS: OnceLock<
+    DashMap<String, Arc<OidcProvider>>,
+  > = OnceLock::new();
+  OIDC_PROVIDERS.get_or_init(Default::default)
+}
+
+pub fn oidc_provider(name: &str) -> Option<Arc<OidcProvider>> {
+  oidc_providers()
+    .get(name)
+    .map(|provider| provider.value().clone())
+}
+
+/// The names of the initialized, named providers.
+pub fn oidc_provider_names() -> Vec<String> {
+  let mut names = oidc_providers()
+    .iter()
+    .map(|provider| provider.key().clone())
+    .filter(|name| name != DEFAULT_PROVIDER)
+    .collect::<Vec<_>>();
+  names.sort();
+  names
+}
+
+/// The path the provider routes are nested under.
+pub fn oidc_provider_path(name: &str) -> String {
+  if name == DEFAULT_PROVIDER {
+    String::from("/auth/oidc")
+  } else {
+    format!("/auth/oidc/{name}")
+  }
+}
+
+/// The configured providers, including the one configured with
+/// the top level `oidc_*` fields, as [DEFAULT_PROVIDER].
+fn oidc_provider_configs() -> Vec<OidcProviderConfig> {
+  let config = core_config();
+  if !config.oidc_enabled {
+    return Vec::new();
+  }
+  let mut providers = Vec::new();
+  if !config.oidc_provider.is_empty()
+    && !config.oidc_client_id.is_empty()
+  {
+    providers.push(OidcProviderConfig {
+      name: DEFAULT_PROVIDER.to_string(),
+      provider: config.oidc_provider.clone(),
+      redirect_host: config.oidc_redirect_host.clone(),
+      client_id: config.oidc_client_id.clone(),
+      client_secret: config.oidc_client_secret.clone(),
+      use_full_email: config.oidc_use_full_email,
+      additional_audiences: config.oidc_additional_audiences.clone(),
+    });
+  }
+  for provider in &config.oidc_providers {
+    let valid_name = !provider.name.is_empty()
+      && provider
+        .name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+      error!(
+        "Invalid OIDC provider name '{}'. Only alphanumeric characters, '-' and '_' are allowed.",
+        provider.name
+      );
+      continue;
+    }
+    if providers.iter().any(|p| p.name == provider.name) {
+      error!("Duplicate OIDC provider name '{}'", provider.name);
+      continue;
+    }
+    if provider.provider.is_empty() || provider.client_id.is_empty() {
+      error!(
+        "OIDC provider '{}' is missing provider or client_id",
+        provider.name
+      );
+      continue;
+    }
+    providers.push(provider.clone());
+  }
+  providers
 }
 
-/// The OIDC client must be reinitialized to
+/// The OIDC clients must be reinitialized to
 /// pick up the latest provider JWKs. This
 /// function spawns a management thread to do this
 /// on a loop.
 pub async fn spawn_oidc_client_management() {
-  let config = core_config();
-  if !config.oidc_enabled
-    || config.oidc_provider.is_empty()
-    || config.oidc_client_id.is_empty()
-  {
+  let providers = oidc_provider_configs();
+  if providers.is_empty() {
     return;
   }
-  if let Err(e) = reset_oidc_client().await {
-    error!("Failed to initialize OIDC client | {e:#}");
+  for provider in &providers {
+    if let Err(e) = reset_oidc_provider(provider.clone()).await {
+      error!(
+        "Failed to initialize OIDC client | provider: {} | {e:#}",
+        provider_label(&provider.name)
+      );
+    }
   }
   tokio::spawn(async move {
     loop {
       tokio::time::sleep(Duration::from_secs(60)).await;
-      if let Err(e) = reset_oidc_client().await {
-        warn!("Failed to reinitialize OIDC client | {e:#}");
+      for provider in &providers {
+        if let Err(e) = reset_oidc_provider(provider.clone()).await {
+          warn!(
+            "Failed to reinitialize OIDC client | provider: {} | {e:#}",
+            provider_label(&provider.name)
+          );
+        }
       }
     }
   });
 }
 
-async fn reset_oidc_client() -> anyhow::Result<()> {
-  let config = core_config();
+fn provider_label(name: &str) -> &str {
+  if name == DEFAULT_PROVIDER {
+    "default"
+  } else {
+    name
+  }
+}
+
+async fn reset_oidc_provider(
+  config: OidcProviderConfig,
+) -> anyhow::Result<()> {
   // Use OpenID Connect Discovery to fetch the provider metadata.
   let provider_metadata = CoreProviderMetadata::discover_async(
-    IssuerUrl::new(config.oidc_provider.clone())?,
+    IssuerUrl::new(config.provider.clone())?,
     super::reqwest_client(),
   )
   .await
   .context("Failed to get OIDC /.well-known/openid-configuration")?;
 
+  let issuer = provider_metadata.issuer().to_string();
+  // Keys of unsupported types are skipped.
+  let keys = serde_json::to_value(provider_metadata.jwks())
+    .context("Failed to serialize OIDC provider keys")?
+    .get("keys")
+    .and_then(|keys| keys.as_array())
+    .map(|keys| {
+      keys
+        .iter()
+        .filter_map(|key| serde_json::from_value(key.clone()).ok())
+        .collect()
+    })
+    .unwrap_or_default();
+
   let client = CoreClient::from_provider_metadata(
     provider_metadata,
-    ClientId::new(config.oidc_client_id.to_string()),
+    ClientId::new(config.client_id.to_string()),
     // The secret may be empty / ommitted if auth provider supports PKCE
-    if config.oidc_client_secret.is_empty() {
+    if config.client_secret.is_empty() {
       None
     } else {
-      Some(ClientSecret::new(config.oidc_client_secret.to_string()))
+      Some(ClientSecret::new(config.client_secret.to_string()))
     },
   )
   // Set the URL the user will be redirected to after the authorization process.
   .set_redirect_uri(RedirectUrl::new(format!(
-    "{}/auth/oidc/callback",
-    core_config().host
+    "{}{}/callback",
+    core_config().host,
+    oidc_provider_path(&config.name)
   ))?);
 
-  oidc_client().store(Some(client.into()));
+  oidc_providers().insert(
+    config.name.clone(),
+    OidcProvider {
+      config,
+      client,
+      issuer,
+      keys: JwkSet { keys },
+    }
+    .into(),
+  );
 
   Ok(())
 }
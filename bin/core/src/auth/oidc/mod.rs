@@ -1,27 +1,39 @@
-use std::sync::OnceLock;
+use std::{collections::HashMap, sync::OnceLock};
 
 use anyhow::{Context, anyhow};
 use axum::{
-  Router, extract::Query, response::Redirect, routing::get,
+  Router,
+  extract::{Path, Query},
+  response::Redirect,
+  routing::{get, post},
 };
-use client::oidc_client;
+use client::{DEFAULT_PROVIDER, oidc_provider};
 use dashmap::DashMap;
-use database::mungos::mongodb::bson::{Document, doc};
+use database::mungos::{
+  by_id::update_one_by_id,
+  find::find_collect,
+  mongodb::bson::{Document, doc},
+  update::Update,
+};
 use komodo_client::entities::{
   komodo_timestamp,
-  user::{User, UserConfig},
+  user::{LoginMethod, User, UserConfig},
 };
 use openidconnect::{
-  AccessTokenHash, AuthorizationCode, CsrfToken,
-  EmptyAdditionalClaims, Nonce, OAuth2TokenResponse,
-  PkceCodeChallenge, PkceCodeVerifier, Scope, TokenResponse,
+  AccessTokenHash, AdditionalClaims, AuthorizationCode, CsrfToken,
+  Nonce, OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier,
+  Scope, TokenResponse,
   core::{CoreAuthenticationFlow, CoreGenderClaim},
 };
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serror::AddStatusCode;
 
 use crate::{
+  auth::{
+    bootstrap::claim_first_admin,
+    events::{LoginContext, record_login_event},
+  },
   config::core_config,
   helpers::random_string,
   state::{db_client, jwt_client},
@@ -30,6 +42,7 @@ use crate::{
 use super::RedirectQuery;
 
 pub mod client;
+pub mod session;
 
 static APP_USER_AGENT: &str =
   concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
@@ -50,38 +63,100 @@ fn reqwest_client() -> &'static reqwest::Client {
 const CSRF_VALID_FOR_MS: i64 = 120_000; // 2 minutes for user to log in.
 
 type RedirectUrl = Option<String>;
+type ProviderName = String;
 /// Maps the csrf secrets to other information added in the "login" method (before auth provider redirect).
 /// This information is retrieved in the "callback" method (after auth provider redirect).
-type VerifierMap =
-  DashMap<String, (PkceCodeVerifier, Nonce, RedirectUrl, i64)>;
+type VerifierMap = DashMap<
+  String,
+  (PkceCodeVerifier, Nonce, RedirectUrl, ProviderName, i64),
+>;
 fn verifier_tokens() -> &'static VerifierMap {
   static VERIFIERS: OnceLock<VerifierMap> = OnceLock::new();
   VERIFIERS.get_or_init(Default::default)
 }
 
+/// The default provider routes are `/login`, `/callback`
+/// and `/backchannel-logout`, while the named provider routes
+/// are nested under their name, eg `/{provider}/login`.
 pub fn router() -> Router {
   Router::new()
     .route(
       "/login",
       get(|query| async {
-        login(query).await.status_code(StatusCode::UNAUTHORIZED)
+        login(DEFAULT_PROVIDER, query)
+          .await
+          .status_code(StatusCode::UNAUTHORIZED)
       }),
     )
     .route(
       "/callback",
-      get(|query| async {
-        callback(query).await.status_code(StatusCode::UNAUTHORIZED)
+      get(|context: LoginContext, query| async move {
+        callback_handler(DEFAULT_PROVIDER, context, query).await
+      }),
+    )
+    .route(
+      "/backchannel-logout",
+      post(|form| async {
+        session::backchannel_logout(DEFAULT_PROVIDER, form)
+          .await
+          .status_code(StatusCode::BAD_REQUEST)
+      }),
+    )
+    .route(
+      "/{provider}/login",
+      get(|Path(provider): Path<String>, query| async move {
+        login(&provider, query)
+          .await
+          .status_code(StatusCode::UNAUTHORIZED)
+      }),
+    )
+    .route(
+      "/{provider}/callback",
+      get(
+        |Path(provider): Path<String>,
+         context: LoginContext,
+         query| async move {
+          callback_handler(&provider, context, query).await
+        },
+      ),
+    )
+    .route(
+      "/{provider}/backchannel-logout",
+      post(|Path(provider): Path<String>, form| async move {
+        session::backchannel_logout(&provider, form)
+          .await
+          .status_code(StatusCode::BAD_REQUEST)
       }),
     )
 }
 
+async fn callback_handler(
+  provider: &str,
+  context: LoginContext,
+  query: Query<CallbackQuery>,
+) -> serror::Result<Redirect> {
+  callback(provider, &context, query)
+    .await
+    .inspect_err(|e| {
+      record_login_event(
+        LoginMethod::Oidc,
+        &context,
+        "",
+        "",
+        Some(format!("{e:#}")),
+      )
+    })
+    .status_code(StatusCode::UNAUTHORIZED)
+}
+
 #[instrument(name = "OidcRedirect", level = "debug")]
 async fn login(
+  provider: &str,
   Query(RedirectQuery { redirect }): Query<RedirectQuery>,
 ) -> anyhow::Result<Redirect> {
-  let client = oidc_client().load();
-  let client =
-    client.as_ref().context("OIDC Client not configured")?;
+  let provider = oidc_provider(provider)
+    .context("OIDC provider not configured")?;
+  let client = &provider.client;
 
   let (pkce_challenge, pkce_verifier) =
     PkceCodeChallenge::new_random_sha256();
@@ -106,12 +181,13 @@ async fn login(
       pkce_verifier,
       nonce,
       redirect,
+      provider.config.name.clone(),
       komodo_timestamp() + CSRF_VALID_FOR_MS,
     ),
   );
 
-  let config = core_config();
-  let redirect = if !config.oidc_redirect_host.is_empty() {
+  let config = &provider.config;
+  let redirect = if !config.redirect_host.is_empty() {
     let auth_url = auth_url.as_str();
     let (protocol, rest) = auth_url
       .split_once("://")
@@ -122,7 +198,7 @@ async fn login(
       .unwrap_or(rest);
     Redirect::to(&auth_url.replace(
       &format!("{protocol}://{host}"),
-      &config.oidc_redirect_host,
+      &config.redirect_host,
     ))
   } else {
     Redirect::to(auth_url.as_str())
@@ -140,11 +216,12 @@ struct CallbackQuery {
 
 #[instrument(name = "OidcCallback", level = "debug")]
 async fn callback(
+  provider: &str,
+  context: &LoginContext,
   Query(query): Query<CallbackQuery>,
 ) -> anyhow::Result<Redirect> {
-  let client = oidc_client().load();
-  let client =
-    client.as_ref().context("OIDC Client not initialized successfully. Is the provider properly configured?")?;
+  let provider = oidc_provider(provider).context("OIDC Client not initialized successfully. Is the provider properly configured?")?;
+  let client = &provider.client;
 
   if let Some(e) = query.error {
     return Err(anyhow!("Provider returned error: {e}"));
@@ -155,10 +232,17 @@ async fn callback(
     query.state.context("Provider did not return state")?,
   );
 
-  let (_, (pkce_verifier, nonce, redirect, valid_until)) =
-    verifier_tokens()
-      .remove(state.secret())
-      .context("CSRF token invalid")?;
+  let (
+    _,
+    (pkce_verifier, nonce, redirect, provider_name, valid_until),
+  ) = verifier_tokens()
+    .remove(state.secret())
+    .context("CSRF token invalid")?;
+
+  // The login must have been started with the same provider.
+  if provider_name != provider.config.name {
+    return Err(anyhow!("CSRF token invalid (Provider mismatch)"));
+  }
 
   if komodo_timestamp() > valid_until {
     return Err(anyhow!(
@@ -183,7 +267,7 @@ async fn callback(
   // Some providers attach additional audiences, they must be added here
   // so token verification succeeds.
   let verifier = client.id_token_verifier();
-  let additional_audiences = &core_config().oidc_additional_audiences;
+  let additional_audiences = &provider.config.additional_audiences;
   let verifier = if additional_audiences.is_empty() {
     verifier
   } else {
@@ -216,36 +300,46 @@ async fn callback(
   let user = db_client
     .users
     .find_one(doc! {
-      "config.data.provider": &core_config().oidc_provider,
+      "config.data.provider": &provider.config.provider,
       "config.data.user_id": user_id
     })
     .await
     .context("failed at find user query from database")?;
 
-  let jwt = match user {
-    Some(user) => jwt_client()
-      .encode(user.id)
-      .context("failed to generate jwt")?,
+  let core_config = core_config();
+  let groups_managed = !core_config.oidc_admin_groups.is_empty()
+    || core_config.oidc_sync_user_groups;
+
+  let user_info = if user.is_none() || groups_managed {
+    let user_info = client
+      .user_info(
+        token_response.access_token().clone(),
+        claims.subject().clone().into(),
+      )
+      .context("Invalid user info request")?
+      .request_async::<OtherClaims, _, CoreGenderClaim>(
+        reqwest_client,
+      )
+      .await
+      .context("Failed to fetch user info")?;
+    Some(user_info)
+  } else {
+    None
+  };
+
+  let (user_id, username, super_admin) = match user {
+    Some(user) => (user.id, user.username, user.super_admin),
     None => {
       let ts = komodo_timestamp();
       let no_users_exist =
         db_client.users.find_one(Document::new()).await?.is_none();
-      let core_config = core_config();
       if !no_users_exist && core_config.disable_user_registration {
         return Err(anyhow!("User registration is disabled"));
       }
+      let bootstrap = claim_first_admin(no_users_exist, None)?;
 
-      // Fetch user info
-      let user_info = client
-        .user_info(
-          token_response.access_token().clone(),
-          claims.subject().clone().into(),
-        )
-        .context("Invalid user info request")?
-        .request_async::<EmptyAdditionalClaims, _, CoreGenderClaim>(
-          reqwest_client,
-        )
-        .await
+      let user_info = user_info
+        .as_ref()
         .context("Failed to fetch user info for new user")?;
 
       // Will use preferred_username, then email, then user_id if it isn't available.
@@ -257,7 +351,7 @@ async fn callback(
             .email()
             .map(|email| email.as_str())
             .unwrap_or(user_id);
-          if core_config.oidc_use_full_email {
+          if provider.config.use_full_email {
             email
           } else {
             email
@@ -290,35 +384,224 @@ async fn callback(
         create_build_permissions: no_users_exist,
         updated_at: ts,
         last_update_view: 0,
+        tokens_valid_after: 0,
+        failed_logins: 0,
+        last_failed_login_at: 0,
+        locked_until: 0,
         recents: Default::default(),
         all: Default::default(),
         config: UserConfig::Oidc {
-          provider: core_config.oidc_provider.clone(),
+          provider: provider.config.provider.clone(),
           user_id: user_id.to_string(),
         },
       };
 
       let user_id = db_client
         .users
-        .insert_one(user)
+        .insert_one(&user)
         .await
         .context("failed to create user on database")?
         .inserted_id
         .as_object_id()
         .context("inserted_id is not ObjectId")?
         .to_string();
+      bootstrap.complete();
 
-      jwt_client()
-        .encode(user_id)
-        .context("failed to generate jwt")?
+      (user_id, user.username, no_users_exist)
     }
   };
+
+  if let Some(user_info) = &user_info
+    && groups_managed
+  {
+    let groups = provider_groups(
+      user_info.additional_claims(),
+      &core_config.oidc_groups_claim,
+    );
+    apply_provider_groups(&user_id, super_admin, &groups).await?;
+  }
+
+  let session = session::record_session(
+    user_id.clone(),
+    provider.config.name.clone(),
+    &id_token.to_string(),
+    token_response.refresh_token(),
+  )
+  .await?;
+
+  record_login_event(
+    LoginMethod::Oidc,
+    context,
+    &user_id,
+    &username,
+    None,
+  );
+
+  let jwt = jwt_client()
+    .encode_session(user_id, session)
+    .context("failed to generate jwt")?;
   let exchange_token = jwt_client().create_exchange_token(jwt).await;
   let redirect_url = if let Some(redirect) = redirect {
     let splitter = if redirect.contains('?') { '&' } else { '?' };
     format!("{redirect}{splitter}token={exchange_token}")
   } else {
-    format!("{}?token={exchange_token}", core_config().host)
+    format!("{}?token={exchange_token}", core_config.host)
   };
   Ok(Redirect::to(&redirect_url))
 }
+
+/// The user info claims beyond the standard ones,
+/// where providers put the groups claim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+struct OtherClaims(HashMap<String, serde_json::Value>);
+
+impl AdditionalClaims for OtherClaims {}
+
+/// Reads the groups claim, which may be a list
+/// of group names or a single group name.
+fn provider_groups(claims: &OtherClaims, claim: &str) -> Vec<String> {
+  match claims.0.get(claim) {
+    Some(serde_json::Value::Array(groups)) => groups
+      .iter()
+      .filter_map(|group| group.as_str().map(str::to_string))
+      .collect(),
+    Some(serde_json::Value::String(group)) => vec![group.clone()],
+    _ => Vec::new(),
+  }
+}
+
+/// Applies the configured provider group mapping to the user,
+/// setting admin from `oidc_admin_groups` and syncing
+/// User Group membership if `oidc_sync_user_groups` is enabled.
+async fn apply_provider_groups(
+  user_id: &str,
+  super_admin: bool,
+  groups: &[String],
+) -> anyhow::Result<()> {
+  let config = core_config();
+  let db = db_client();
+
+  if !config.oidc_admin_groups.is_empty() {
+    let admin = super_admin
+      || groups
+        .iter()
+        .any(|group| config.oidc_admin_groups.contains(group));
+    update_one_by_id(
+      &db.users,
+      user_id,
+      Update::Set(doc! { "admin": admin }),
+      None,
+    )
+    .await
+    .context("failed to update user admin from provider groups")?;
+  }
+
+  if config.oidc_sync_user_groups {
+    let prefix = &config.oidc_user_group_prefix;
+    let add = managed_groups(groups, prefix);
+    db.user_groups
+      .update_many(
+        doc! { "name": { "$in": add } },
+        doc! { "$addToSet": { "users": user_id } },
+      )
+      .await
+      .context("failed to add user to provider user groups")?;
+    let member_of =
+      find_collect(&db.user_groups, doc! { "users": user_id }, None)
+        .await
+        .context("failed to query for user groups")?
+        .into_iter()
+        .map(|group| group.name)
+        .collect::<Vec<_>>();
+    let remove = groups_to_leave(&member_of, groups, prefix);
+    if !remove.is_empty() {
+      db.user_groups
+        .update_many(
+          doc! { "name": { "$in": remove } },
+          doc! { "$pull": { "users": user_id } },
+        )
+        .await
+        .context("failed to remove user from user groups")?;
+    }
+  }
+
+  Ok(())
+}
+
+/// The provider groups which are synced to User Groups.
+/// With a prefix, only those starting with it.
+fn managed_groups<'a>(
+  groups: &'a [String],
+  prefix: &str,
+) -> Vec<&'a str> {
+  groups
+    .iter()
+    .map(String::as_str)
+    .filter(|group| group.starts_with(prefix))
+    .collect()
+}
+
+/// The User Groups to remove the user from: those managed with
+/// the prefix which aren't in the provider groups.
+/// Without a prefix, no User Groups are managed for removal.
+fn groups_to_leave<'a>(
+  member_of: &'a [String],
+  groups: &[String],
+  prefix: &str,
+) -> Vec<&'a str> {
+  if prefix.is_empty() {
+    return Vec::new();
+  }
+  member_of
+    .iter()
+    .map(String::as_str)
+    .filter(|name| {
+      name.starts_with(prefix)
+        && !groups.iter().any(|group| group == name)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+  }
+
+  #[test]
+  fn prefix_limits_managed_groups() {
+    let groups = names(&["oidc-dev", "oidc-ops", "everyone"]);
+    assert_eq!(
+      managed_groups(&groups, "oidc-"),
+      ["oidc-dev", "oidc-ops"]
+    );
+    assert_eq!(
+      managed_groups(&groups, ""),
+      ["oidc-dev", "oidc-ops", "everyone"]
+    );
+  }
+
+  #[test]
+  fn leaves_only_prefixed_groups_no_longer_claimed() {
+    let member_of = names(&["oidc-dev", "oidc-ops", "local-admins"]);
+    let groups = names(&["oidc-dev"]);
+    assert_eq!(
+      groups_to_leave(&member_of, &groups, "oidc-"),
+      ["oidc-ops"]
+    );
+  }
+
+  #[test]
+  fn keeps_groups_managed_in_komodo() {
+    let member_of = names(&["local-admins", "oidc-dev"]);
+    assert!(
+      groups_to_leave(&member_of, &names(&["oidc-dev"]), "oidc-")
+        .is_empty()
+    );
+    // Without a prefix, users are never removed.
+    assert!(groups_to_leave(&member_of, &[], "").is_empty());
+  }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, anyhow};
+use axum::Form;
+use database::mungos::{
+  by_id::{find_one_by_id, update_one_by_id},
+  find::find_collect,
+  mongodb::bson::{Document, doc},
+  update::Update,
+};
+use jsonwebtoken::{DecodingKey, Validation, decode, decode_header};
+use komodo_client::entities::{
+  I64, komodo_timestamp, user::OidcSession,
+};
+use openidconnect::{
+  OAuth2TokenResponse, RefreshToken, RequestTokenError,
+};
+use serde::Deserialize;
+
+use crate::state::{db_client, jwt_client};
+
+use super::client::{OidcProvider, oidc_provider};
+
+const BACKCHANNEL_LOGOUT_EVENT: &str =
+  "http://schemas.openid.net/event/backchannel-logout";
+
+/// Stores the provider session of the login,
+/// returning the session id to issue the jwt for.
+/// Sessions are stored in the database,
+/// so they survive restarts and are shared between Core replicas.
+pub async fn record_session(
+  user_id: String,
+  provider: String,
+  id_token: &str,
+  refresh_token: Option<&RefreshToken>,
+) -> anyhow::Result<String> {
+  let session = OidcSession {
+    id: Default::default(),
+    user_id,
+    provider,
+    sid: id_token_sid(id_token),
+    refresh_token: refresh_token
+      .map(|token| token.secret().to_string()),
+    expires_at: session_expiry(komodo_timestamp()),
+  };
+  db_client()
+    .oidc_sessions
+    .insert_one(session)
+    .await
+    .context("Failed to store OIDC session")?
+    .inserted_id
+    .as_object_id()
+    .context("inserted_id is not ObjectId")
+    .map(|id| id.to_string())
+}
+
+/// Sessions expire with the last jwt issued for them.
+fn session_expiry(now: I64) -> I64 {
+  now + jwt_client().ttl_ms() as I64
+}
+
+/// Uses the provider refresh token to check the provider
+/// session is still active before a new jwt is issued.
+/// If the provider rejects the refresh token,
+/// the user's Komodo sessions are ended.
+pub async fn refresh_session(
+  user_id: &str,
+  session_id: &str,
+) -> anyhow::Result<()> {
+  if session_id.is_empty() {
+    return Err(anyhow!(
+      "Token was not issued for an OIDC session. Log in again."
+    ));
+  }
+  let sessions = &db_client().oidc_sessions;
+  let session = find_one_by_id(sessions, session_id)
+    .await
+    .context("Failed to query for OIDC session")?;
+  let now = komodo_timestamp();
+  let session = check_session(session.as_ref(), user_id, now)?;
+  let refresh_token = session.refresh_token.clone().context(
+    "Provider did not issue a refresh token for the OIDC session. Log in again.",
+  )?;
+
+  let provider = oidc_provider(&session.provider)
+    .context("OIDC provider not configured")?;
+
+  let res = provider
+    .client
+    .exchange_refresh_token(&RefreshToken::new(refresh_token))
+    .context("OIDC provider does not support refresh tokens")?
+    .request_async(super::reqwest_client())
+    .await;
+
+  match res {
+    Ok(token_response) => {
+      update_one_by_id(
+        sessions,
+        session_id,
+        Update::Set(refreshed_session(
+          session_expiry(now),
+          token_response.refresh_token(),
+        )),
+        None,
+      )
+      .await
+      .context("Failed to update OIDC session")?;
+      Ok(())
+    }
+    Err(RequestTokenError::ServerResponse(e)) => {
+      end_sessions(user_id).await?;
+      Err(anyhow!(
+        "OIDC provider session has ended. Log in again. | {e}"
+      ))
+    }
+    Err(e) => Err(
+      anyhow::Error::from(e).context("Failed to refresh OIDC token"),
+    ),
+  }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackchannelLogoutForm {
+  logout_token: String,
+}
+
+#[derive(Deserialize)]
+struct LogoutTokenClaims {
+  sub: Option<String>,
+  sid: Option<String>,
+  #[serde(default)]
+  events: HashMap<String, serde_json::Value>,
+  nonce: Option<String>,
+}
+
+/// Handles the provider's OpenID Connect Back-Channel Logout request,
+/// ending the Komodo sessions of the user logged out at the provider.
+/// See https://openid.net/specs/openid-connect-backchannel-1_0.html.
+#[instrument(name = "OidcBackchannelLogout", level = "debug")]
+pub async fn backchannel_logout(
+  provider: &str,
+  Form(BackchannelLogoutForm { logout_token }): Form<
+    BackchannelLogoutForm,
+  >,
+) -> anyhow::Result<()> {
+  let provider = oidc_provider(provider)
+    .context("OIDC provider not configured")?;
+  let claims = verify_logout_token(&provider, &logout_token)?;
+
+  let mut user_ids = Vec::new();
+  if let Some(sid) = &claims.sid {
+    user_ids.extend(
+      find_collect(
+        &db_client().oidc_sessions,
+        sid_filter(&provider.config.name, sid),
+        None,
+      )
+      .await
+      .context("Failed to query for OIDC sessions")?
+      .into_iter()
+      .map(|session| session.user_id),
+    );
+    user_ids.sort();
+    user_ids.dedup();
+  }
+  if let Some(sub) = &claims.sub
+    && let Some(user) = db_client()
+      .users
+      .find_one(doc! {
+        "config.data.provider": &provider.config.provider,
+        "config.data.user_id": sub
+      })
+      .await
+      .context("failed at find user query from database")?
+    && !user_ids.contains(&user.id)
+  {
+    user_ids.push(user.id);
+  }
+
+  for user_id in user_ids {
+    end_sessions(&user_id).await?;
+    info!("OIDC backchannel logout | user: {user_id}");
+  }
+
+  Ok(())
+}
+
+fn verify_logout_token(
+  provider: &OidcProvider,
+  logout_token: &str,
+) -> anyhow::Result<LogoutTokenClaims> {
+  let header = decode_header(logout_token)
+    .context("Failed to decode logout token header")?;
+  let jwk = match &header.kid {
+    Some(kid) => provider.keys.find(kid),
+    None => provider.keys.keys.first(),
+  }
+  .context("No matching OIDC provider key for logout token")?;
+  let key = DecodingKey::from_jwk(jwk)
+    .context("Invalid OIDC provider key")?;
+
+  let mut validation = Validation::new(header.alg);
+  validation.set_issuer(&[&provider.issuer]);
+  validation.set_audience(&[&provider.config.client_id]);
+  validation.set_required_spec_claims(&["iss", "aud", "iat"]);
+
+  let claims =
+    decode::<LogoutTokenClaims>(logout_token, &key, &validation)
+      .context("Failed to verify logout token")?
+      .claims;
+
+  if !claims.events.contains_key(BACKCHANNEL_LOGOUT_EVENT) {
+    return Err(anyhow!(
+      "Logout token missing backchannel logout event"
+    ));
+  }
+  if claims.nonce.is_some() {
+    return Err(anyhow!("Logout token must not contain nonce"));
+  }
+  if claims.sub.is_none() && claims.sid.is_none() {
+    return Err(anyhow!("Logout token must contain sub or sid"));
+  }
+
+  Ok(claims)
+}
+
+/// Rejects all the user's existing jwts and drops their provider sessions.
+async fn end_sessions(user_id: &str) -> anyhow::Result<()> {
+  db_client()
+    .oidc_sessions
+    .delete_many(doc! { "user_id": user_id })
+    .await
+    .context("Failed to delete OIDC sessions")?;
+  update_one_by_id(
+    &db_client().users,
+    user_id,
+    Update::Set(doc! { "tokens_valid_after": komodo_timestamp() }),
+    None,
+  )
+  .await
+  .context("failed to update user tokens_valid_after on db")?;
+  jwt_client().clear_exchange_tokens(user_id).await;
+  Ok(())
+}
+
+/// Checks the session can be refreshed by the user.
+/// Sessions ended by a backchannel logout no longer exist.
+fn check_session<'a>(
+  session: Option<&'a OidcSession>,
+  user_id: &str,
+  now: I64,
+) -> anyhow::Result<&'a OidcSession> {
+  let session = session
+    .filter(|session| session.user_id == user_id)
+    .context("OIDC session has ended. Log in again.")?;
+  if session.expires_at <= now {
+    return Err(anyhow!("OIDC session has expired. Log in again."));
+  }
+  Ok(session)
+}
+
+/// The update to a refreshed session, extending its expiry
+/// and storing the new refresh token if the provider rotated it.
+fn refreshed_session(
+  expires_at: I64,
+  refresh_token: Option<&RefreshToken>,
+) -> Document {
+  let mut update = doc! { "expires_at": expires_at };
+  if let Some(refresh_token) = refresh_token {
+    update.insert("refresh_token", refresh_token.secret());
+  }
+  update
+}
+
+/// Matches the sessions ended by a backchannel logout with `sid`.
+fn sid_filter(provider: &str, sid: &str) -> Document {
+  doc! { "provider": provider, "sid": sid }
+}
+
+/// Deletes the sessions whose last jwt has expired.
+pub async fn prune_sessions() -> anyhow::Result<()> {
+  let res = db_client()
+    .oidc_sessions
+    .delete_many(doc! { "expires_at": { "$lt": komodo_timestamp() } })
+    .await
+    .context("Failed to delete expired OIDC sessions")?;
+  if res.deleted_count > 0 {
+    info!("deleted {} expired OIDC sessions", res.deleted_count);
+  }
+  Ok(())
+}
+
+/// Reads the `sid` claim of the (already verified) ID token.
+fn id_token_sid(id_token: &str) -> Option<String> {
+  #[derive(Deserialize)]
+  struct SidClaim {
+    sid: Option<String>,
+  }
+  let mut validation = Validation::default();
+  validation.insecure_disable_signature_validation();
+  validation.validate_aud = false;
+  validation.validate_exp = false;
+  validation.set_required_spec_claims::<&str>(&[]);
+  decode::<SidClaim>(
+    id_token,
+    &DecodingKey::from_secret(&[]),
+    &validation,
+  )
+  .ok()?
+  .claims
+  .sid
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn session(expires_at: I64) -> OidcSession {
+    OidcSession {
+      id: String::from("session"),
+      user_id: String::from("user"),
+      provider: String::from("default"),
+      sid: Some(String::from("sid")),
+      refresh_token: Some(String::from("refresh")),
+      expires_at,
+    }
+  }
+
+  #[test]
+  fn refreshes_active_session() {
+    let session = session(2_000);
+    assert!(check_session(Some(&session), "user", 1_000).is_ok());
+    let update = refreshed_session(5_000, None);
+    assert_eq!(update, doc! { "expires_at": 5_000_i64 });
+    let rotated = RefreshToken::new(String::from("rotated"));
+    let update = refreshed_session(5_000, Some(&rotated));
+    assert_eq!(
+      update,
+      doc! { "expires_at": 5_000_i64, "refresh_token": "rotated" }
+    );
+  }
+
+  #[test]
+  fn rejects_expired_session() {
+    let session = session(1_000);
+    let e = check_session(Some(&session), "user", 1_000).unwrap_err();
+    assert!(e.to_string().contains("expired"));
+  }
+
+  #[test]
+  fn rejects_ended_session() {
+    // Backchannel logouts delete the session.
+    assert!(check_session(None, "user", 1_000).is_err());
+    // Tokens can't refresh another user's session.
+    let session = session(2_000);
+    assert!(check_session(Some(&session), "other", 1_000).is_err());
+  }
+
+  #[test]
+  fn backchannel_logout_matches_provider_sid() {
+    assert_eq!(
+      sid_filter("default", "sid"),
+      doc! { "provider": "default", "sid": "sid" }
+    );
+  }
+}
@@ -93,6 +93,8 @@ pub fn core_config() -> &'static CoreConfig {
         .unwrap_or(config.passkey),
       webhook_secret: maybe_read_item_from_file(env.komodo_webhook_secret_file, env.komodo_webhook_secret)
         .unwrap_or(config.webhook_secret),
+      metrics_token: maybe_read_item_from_file(env.komodo_metrics_token_file, env.komodo_metrics_token)
+        .unwrap_or(config.metrics_token),
       database: DatabaseConfig {
         uri: maybe_read_item_from_file(env.komodo_database_uri_file,env.komodo_database_uri).unwrap_or(config.database.uri),
         address: env.komodo_database_address.unwrap_or(config.database.address),
@@ -117,6 +119,27 @@ pub fn core_config() -> &'static CoreConfig {
         env.komodo_init_admin_password_file,
         env.komodo_init_admin_password
       ).unwrap_or(config.init_admin_password),
+      require_bootstrap_token: env
+        .komodo_require_bootstrap_token
+        .unwrap_or(config.require_bootstrap_token),
+      password_min_length: env
+        .komodo_password_min_length
+        .unwrap_or(config.password_min_length),
+      password_min_character_classes: env
+        .komodo_password_min_character_classes
+        .unwrap_or(config.password_min_character_classes),
+      password_deny_common: env
+        .komodo_password_deny_common
+        .unwrap_or(config.password_deny_common),
+      login_lockout_attempts: env
+        .komodo_login_lockout_attempts
+        .unwrap_or(config.login_lockout_attempts),
+      login_lockout_window: env
+        .komodo_login_lockout_window
+        .unwrap_or(config.login_lockout_window),
+      login_lockout_duration: env
+        .komodo_login_lockout_duration
+        .unwrap_or(config.login_lockout_duration),
       oidc_enabled: env.komodo_oidc_enabled.unwrap_or(config.oidc_enabled),
       oidc_provider: env.komodo_oidc_provider.unwrap_or(config.oidc_provider),
       oidc_redirect_host: env.komodo_oidc_redirect_host.unwrap_or(config.oidc_redirect_host),
@@ -131,6 +154,16 @@ pub fn core_config() -> &'static CoreConfig {
       oidc_additional_audiences: maybe_read_list_from_file(env.komodo_oidc_additional_audiences_file,env
         .komodo_oidc_additional_audiences)
         .unwrap_or(config.oidc_additional_audiences),
+      oidc_groups_claim: env.komodo_oidc_groups_claim
+        .unwrap_or(config.oidc_groups_claim),
+      oidc_admin_groups: env.komodo_oidc_admin_groups
+        .unwrap_or(config.oidc_admin_groups),
+      oidc_sync_user_groups: env.komodo_oidc_sync_user_groups
+        .unwrap_or(config.oidc_sync_user_groups),
+      oidc_user_group_prefix: env.komodo_oidc_user_group_prefix
+        .unwrap_or(config.oidc_user_group_prefix),
+      oidc_providers: config.oidc_providers,
+      execution_webhooks: config.execution_webhooks,
       google_oauth: OauthCredentials {
         enabled: env
           .komodo_google_oauth_enabled
@@ -176,6 +209,26 @@ pub fn core_config() -> &'static CoreConfig {
       host: env.komodo_host.unwrap_or(config.host),
       port: env.komodo_port.unwrap_or(config.port),
       bind_ip: env.komodo_bind_ip.unwrap_or(config.bind_ip),
+      max_request_body_bytes: env
+        .komodo_max_request_body_bytes
+        .unwrap_or(config.max_request_body_bytes),
+      request_timeout_seconds: env
+        .komodo_request_timeout_seconds
+        .unwrap_or(config.request_timeout_seconds),
+      max_query_length: env
+        .komodo_max_query_length
+        .unwrap_or(config.max_query_length),
+      max_query_depth: env
+        .komodo_max_query_depth
+        .unwrap_or(config.max_query_depth),
+      allowed_ips: env.komodo_allowed_ips.unwrap_or(config.allowed_ips),
+      denied_ips: env.komodo_denied_ips.unwrap_or(config.denied_ips),
+      trusted_proxies: env
+        .komodo_trusted_proxies
+        .unwrap_or(config.trusted_proxies),
+      wait_until_allowed_ips: env
+        .komodo_wait_until_allowed_ips
+        .unwrap_or(config.wait_until_allowed_ips),
       timezone: env.komodo_timezone.unwrap_or(config.timezone),
       first_server: env.komodo_first_server.or(config.first_server),
       first_server_name: env.komodo_first_server_name.unwrap_or(config.first_server_name),
@@ -183,6 +236,19 @@ pub fn core_config() -> &'static CoreConfig {
       jwt_ttl: env
         .komodo_jwt_ttl
         .unwrap_or(config.jwt_ttl),
+      jwt_algorithm: env
+        .komodo_jwt_algorithm
+        .unwrap_or(config.jwt_algorithm),
+      jwt_key_id: env
+        .komodo_jwt_key_id
+        .unwrap_or(config.jwt_key_id),
+      jwt_private_key_file: env
+        .komodo_jwt_private_key_file
+        .unwrap_or(config.jwt_private_key_file),
+      jwt_public_key_file: env
+        .komodo_jwt_public_key_file
+        .unwrap_or(config.jwt_public_key_file),
+      jwt_previous_keys: config.jwt_previous_keys,
       sync_directory: env
         .komodo_sync_directory
         .unwrap_or(config.sync_directory),
@@ -204,6 +270,36 @@ pub fn core_config() -> &'static CoreConfig {
       keep_alerts_for_days: env
         .komodo_keep_alerts_for_days
         .unwrap_or(config.keep_alerts_for_days),
+      keep_audit_logs_for_days: env
+        .komodo_keep_audit_logs_for_days
+        .unwrap_or(config.keep_audit_logs_for_days),
+      keep_login_events_for_days: env
+        .komodo_keep_login_events_for_days
+        .unwrap_or(config.keep_login_events_for_days),
+      api_key_expiry_alert_days: env
+        .komodo_api_key_expiry_alert_days
+        .unwrap_or(config.api_key_expiry_alert_days),
+      crash_loop_restarts: env
+        .komodo_crash_loop_restarts
+        .unwrap_or(config.crash_loop_restarts),
+      crash_loop_window: env
+        .komodo_crash_loop_window
+        .unwrap_or(config.crash_loop_window),
+      deployment_drift_alerts: env
+        .komodo_deployment_drift_alerts
+        .unwrap_or(config.deployment_drift_alerts),
+      keep_hourly_stats_for_days: env
+        .komodo_keep_hourly_stats_for_days
+        .unwrap_or(config.keep_hourly_stats_for_days),
+      keep_daily_stats_for_days: env
+        .komodo_keep_daily_stats_for_days
+        .unwrap_or(config.keep_daily_stats_for_days),
+      max_update_log_bytes: env
+        .komodo_max_update_log_bytes
+        .unwrap_or(config.max_update_log_bytes),
+      shutdown_timeout_seconds: env
+        .komodo_shutdown_timeout_seconds
+        .unwrap_or(config.shutdown_timeout_seconds),
       webhook_base_url: env
         .komodo_webhook_base_url
         .unwrap_or(config.webhook_base_url),
@@ -245,6 +341,12 @@ pub fn core_config() -> &'static CoreConfig {
         otlp_endpoint: env
           .komodo_logging_otlp_endpoint
           .unwrap_or(config.logging.otlp_endpoint),
+        otlp_metrics_endpoint: env
+          .komodo_logging_otlp_metrics_endpoint
+          .unwrap_or(config.logging.otlp_metrics_endpoint),
+        otlp_high_cardinality_attributes: env
+          .komodo_logging_otlp_high_cardinality_attributes
+          .unwrap_or(config.logging.otlp_high_cardinality_attributes),
         opentelemetry_service_name: env
           .komodo_logging_opentelemetry_service_name
           .unwrap_or(config.logging.opentelemetry_service_name),
@@ -0,0 +1,353 @@
+use anyhow::Context;
+use database::mungos::by_id::find_one_by_id;
+use komodo_client::entities::{
+  ResourceTarget, ResourceTargetVariant, audit::AuditLog,
+  komodo_timestamp, update::Log, user::User,
+};
+
+use crate::state::db_client;
+
+/// The words of a request type which give the type of resource
+/// it targets, along with the params fields holding the resource,
+/// eg `RunSync { sync }` targets a ResourceSync.
+/// Checked in order, so `CancelRepoBuild` targets the Repo,
+/// and the resource types come before the server objects,
+/// so `CreateDeploymentFromContainer` targets the Deployment.
+const TARGET_WORDS: [(&str, ResourceTargetVariant, &[&str]); 25] = [
+  (
+    "Sync",
+    ResourceTargetVariant::ResourceSync,
+    &["sync", "id", "name"],
+  ),
+  (
+    "Stack",
+    ResourceTargetVariant::Stack,
+    &["stack", "id", "name"],
+  ),
+  (
+    "Deployment",
+    ResourceTargetVariant::Deployment,
+    &["deployment", "id", "name"],
+  ),
+  ("Repo", ResourceTargetVariant::Repo, &["repo", "id", "name"]),
+  (
+    "Build",
+    ResourceTargetVariant::Build,
+    &["build", "id", "name"],
+  ),
+  (
+    "Dockerfile",
+    ResourceTargetVariant::Build,
+    &["build", "id", "name"],
+  ),
+  (
+    "Procedure",
+    ResourceTargetVariant::Procedure,
+    &["procedure", "id", "name"],
+  ),
+  (
+    "Action",
+    ResourceTargetVariant::Action,
+    &["action", "id", "name"],
+  ),
+  (
+    "Builder",
+    ResourceTargetVariant::Builder,
+    &["builder", "id", "name"],
+  ),
+  (
+    "Alerter",
+    ResourceTargetVariant::Alerter,
+    &["alerter", "id", "name"],
+  ),
+  (
+    "Server",
+    ResourceTargetVariant::Server,
+    &["server", "id", "name"],
+  ),
+  // Operations on the objects of a server.
+  ("Container", ResourceTargetVariant::Server, &["server"]),
+  ("Containers", ResourceTargetVariant::Server, &["server"]),
+  ("Network", ResourceTargetVariant::Server, &["server"]),
+  ("Networks", ResourceTargetVariant::Server, &["server"]),
+  ("Image", ResourceTargetVariant::Server, &["server"]),
+  ("Images", ResourceTargetVariant::Server, &["server"]),
+  ("Volume", ResourceTargetVariant::Server, &["server"]),
+  ("Volumes", ResourceTargetVariant::Server, &["server"]),
+  ("Builders", ResourceTargetVariant::Server, &["server"]),
+  ("Buildx", ResourceTargetVariant::Server, &["server"]),
+  ("System", ResourceTargetVariant::Server, &["server"]),
+  ("Terminal", ResourceTargetVariant::Server, &["server"]),
+  ("Terminals", ResourceTargetVariant::Server, &["server"]),
+  // `Deploy` / `BatchDeploy`
+  (
+    "Deploy",
+    ResourceTargetVariant::Deployment,
+    &["deployment", "id", "name"],
+  ),
+];
+
+/// Records a `/write` or `/execute` call in the audit log.
+/// The insert happens in the background,
+/// so it doesn't delay the response.
+pub fn record_audit_log(
+  api: &str,
+  user: &User,
+  operation: String,
+  target: Option<ResourceTarget>,
+  error: Option<String>,
+) {
+  let log = AuditLog {
+    id: Default::default(),
+    ts: komodo_timestamp(),
+    user_id: user.id.clone(),
+    username: user.username.clone(),
+    api: api.to_string(),
+    operation,
+    target,
+    success: error.is_none(),
+    error,
+  };
+  tokio::spawn(async move {
+    if let Err(e) = db_client().audit_logs.insert_one(&log).await {
+      warn!(
+        "Failed to record audit log | {} {} | {e:?}",
+        log.api, log.operation
+      );
+    }
+  });
+}
+
+/// Records an execution in the audit log once it has finished,
+/// with the outcome of its Update.
+pub async fn record_execution_audit_log(
+  user: &User,
+  operation: String,
+  update_id: &str,
+) {
+  let update = match find_one_by_id(&db_client().updates, update_id)
+    .await
+    .context("failed to query to db")
+    .and_then(|update| {
+      update.context("no update exists with given id")
+    }) {
+    Ok(update) => update,
+    Err(e) => {
+      warn!("Failed to get update {update_id} for audit log | {e:#}");
+      return;
+    }
+  };
+  let error =
+    (!update.success).then(|| execution_error(&update.logs));
+  record_audit_log(
+    "execute",
+    user,
+    operation,
+    Some(update.target),
+    error,
+  );
+}
+
+/// The error of a failed execution, from the logs of its failed stages.
+fn execution_error(logs: &[Log]) -> String {
+  let failed = logs
+    .iter()
+    .filter(|log| !log.success)
+    .map(|log| format!("{}: {}", log.stage, log.stderr.trim()))
+    .collect::<Vec<_>>();
+  if failed.is_empty() {
+    String::from("Execution failed")
+  } else {
+    failed.join("\n")
+  }
+}
+
+/// Infers the target of a serialized request, eg
+/// `{ "type": "UpdateServer", "params": { "id": "..." } }`,
+/// from the resource type in the request type and the
+/// matching field of the params.
+pub fn request_target(
+  request: &serde_json::Value,
+) -> Option<ResourceTarget> {
+  let operation = request.get("type")?.as_str()?;
+  let params = request.get("params")?;
+  let (variant, keys) = target_variant(operation)?;
+  let id = keys.iter().find_map(|key| params.get(key)?.as_str())?;
+  serde_json::from_value(serde_json::json!({
+    "type": variant,
+    "id": id,
+  }))
+  .ok()
+}
+
+/// The resource type targeted by the request type, along with
+/// the params fields which may hold the resource.
+/// Words match exactly, so `CreateBuilder` targets a Builder, not a Build.
+/// The leading verb is skipped, so `BuildRepo` targets the Repo.
+fn target_variant(
+  operation: &str,
+) -> Option<(ResourceTargetVariant, &'static [&'static str])> {
+  let words = camel_case_words(operation);
+  let words = if words.len() > 1 { &words[1..] } else { &words };
+  TARGET_WORDS.into_iter().find_map(|(word, variant, keys)| {
+    words.contains(&word).then_some((variant, keys))
+  })
+}
+
+/// Splits `CancelRepoBuild` into `["Cancel", "Repo", "Build"]`.
+fn camel_case_words(s: &str) -> Vec<&str> {
+  let mut words = Vec::new();
+  let mut start = 0;
+  for (i, c) in s.char_indices().skip(1) {
+    if c.is_ascii_uppercase() {
+      words.push(&s[start..i]);
+      start = i;
+    }
+  }
+  if start < s.len() {
+    words.push(&s[start..]);
+  }
+  words
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use komodo_client::entities::Operation;
+  use strum::VariantNames;
+
+  use super::*;
+
+  /// The resource type each operation targets.
+  /// The match is exhaustive, so new operations must be added here.
+  fn expected_variant(
+    operation: Operation,
+  ) -> Option<ResourceTargetVariant> {
+    use Operation::*;
+    use ResourceTargetVariant as V;
+    match operation {
+      CreateServer | UpdateServer | DeleteServer | RenameServer
+      | StartContainer | RestartContainer | PauseContainer
+      | UnpauseContainer | StopContainer | DestroyContainer
+      | StartAllContainers | RestartAllContainers
+      | PauseAllContainers | UnpauseAllContainers
+      | StopAllContainers | PruneContainers | CreateNetwork
+      | DeleteNetwork | PruneNetworks | DeleteImage | PruneImages
+      | DeleteVolume | PruneVolumes | PruneDockerBuilders
+      | PruneBuildx | PruneSystem => Some(V::Server),
+      CreateStack | UpdateStack | RenameStack | DeleteStack
+      | WriteStackContents | RefreshStackCache | PullStack
+      | DeployStack | StartStack | RestartStack | PauseStack
+      | UnpauseStack | StopStack | DestroyStack | RunStackService
+      | DeployStackService | PullStackService | StartStackService
+      | RestartStackService | PauseStackService
+      | UnpauseStackService | StopStackService
+      | DestroyStackService => Some(V::Stack),
+      CreateDeployment | UpdateDeployment | RenameDeployment
+      | DeleteDeployment | Deploy | ReconcileDeployment
+      | PullDeployment | StartDeployment | RestartDeployment
+      | PauseDeployment | UnpauseDeployment | StopDeployment
+      | DestroyDeployment => Some(V::Deployment),
+      CreateBuild | UpdateBuild | RenameBuild | DeleteBuild
+      | RunBuild | CancelBuild | WriteDockerfile => Some(V::Build),
+      // ClearRepoCache has no params, so no target is recorded.
+      CreateRepo | UpdateRepo | RenameRepo | DeleteRepo
+      | CloneRepo | PullRepo | BuildRepo | CancelRepoBuild
+      | ClearRepoCache => Some(V::Repo),
+      CreateProcedure | UpdateProcedure | RenameProcedure
+      | DeleteProcedure | RunProcedure => Some(V::Procedure),
+      CreateAction | UpdateAction | RenameAction | DeleteAction
+      | RunAction => Some(V::Action),
+      CreateBuilder | UpdateBuilder | RenameBuilder
+      | DeleteBuilder => Some(V::Builder),
+      CreateAlerter | UpdateAlerter | RenameAlerter
+      | DeleteAlerter | TestAlerter => Some(V::Alerter),
+      CreateResourceSync | UpdateResourceSync
+      | RenameResourceSync | DeleteResourceSync
+      | WriteSyncContents | CommitSync | RunSync => {
+        Some(V::ResourceSync)
+      }
+      None
+      | SendAlert
+      | BackupCoreDatabase
+      | GlobalAutoUpdate
+      | StartChangeFreeze
+      | EndChangeFreeze
+      | CreateVariable
+      | UpdateVariableValue
+      | DeleteVariable
+      | CreateGitProviderAccount
+      | UpdateGitProviderAccount
+      | DeleteGitProviderAccount
+      | CreateDockerRegistryAccount
+      | UpdateDockerRegistryAccount
+      | DeleteDockerRegistryAccount => Option::None,
+    }
+  }
+
+  #[test]
+  fn every_operation_targets_its_resource_type() {
+    for name in Operation::VARIANTS {
+      let operation = Operation::from_str(name).unwrap();
+      assert_eq!(
+        target_variant(name).map(|(variant, _)| variant),
+        expected_variant(operation),
+        "{name}"
+      );
+    }
+  }
+
+  fn target(request: serde_json::Value) -> Option<ResourceTarget> {
+    request_target(&request)
+  }
+
+  #[test]
+  fn target_id_from_params() {
+    assert_eq!(
+      target(serde_json::json!({
+        "type": "RenameBuilder",
+        "params": { "id": "builder-id", "name": "new-name" }
+      })),
+      Some(ResourceTarget::Builder(String::from("builder-id")))
+    );
+    assert_eq!(
+      target(serde_json::json!({
+        "type": "RunSync",
+        "params": { "sync": "my-sync" }
+      })),
+      Some(ResourceTarget::ResourceSync(String::from("my-sync")))
+    );
+    assert_eq!(
+      target(serde_json::json!({
+        "type": "CreateNetwork",
+        "params": { "server": "my-server", "name": "my-network" }
+      })),
+      Some(ResourceTarget::Server(String::from("my-server")))
+    );
+    assert_eq!(
+      target(serde_json::json!({
+        "type": "CreateDeploymentFromContainer",
+        "params": { "name": "app", "server": "my-server" }
+      })),
+      Some(ResourceTarget::Deployment(String::from("app")))
+    );
+    assert_eq!(
+      target(serde_json::json!({
+        "type": "BatchDeployStack",
+        "params": { "pattern": "*" }
+      })),
+      Option::None
+    );
+  }
+
+  #[test]
+  fn execution_error_from_failed_logs() {
+    let logs = [
+      Log::simple("Pull", String::from("pulled")),
+      Log::error("Deploy", String::from("container exited\n")),
+    ];
+    assert_eq!(execution_error(&logs), "Deploy: container exited");
+    assert_eq!(execution_error(&[]), "Execution failed");
+  }
+}
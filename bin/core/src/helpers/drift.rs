@@ -0,0 +1,342 @@
+use std::collections::{BTreeSet, HashMap};
+
+use komodo_client::entities::{
+  deployment::{
+    DeploymentConfig, DeploymentImage, conversions_from_str,
+  },
+  docker::container::Container,
+};
+
+/// Compares the inspected container against the Deployment config,
+/// returning a description of each difference.
+///
+/// Only what the config sets is checked, as the image adds its own
+/// environment. Values containing interpolated variables / secrets
+/// (`[[VARIABLE]]`) are only checked for presence.
+pub fn deployment_config_drift(
+  config: &DeploymentConfig,
+  container: &Container,
+) -> Vec<String> {
+  let mut drift = Vec::new();
+  if let (DeploymentImage::Image { image }, Some(container_config)) =
+    (&config.image, &container.config)
+  {
+    let expected = normalize_image(image);
+    let actual = normalize_image(
+      container_config.image.as_deref().unwrap_or_default(),
+    );
+    if expected != actual {
+      drift.push(format!(
+        "image: expected '{expected}', found '{actual}'"
+      ));
+    }
+  }
+  environment_drift(config, container, &mut drift);
+  ports_drift(config, container, &mut drift);
+  limits_drift(config, container, &mut drift);
+  drift
+}
+
+/// Docker strips `docker.io/`, and the tag defaults to latest.
+fn normalize_image(image: &str) -> String {
+  let image = image.strip_prefix("docker.io/").unwrap_or(image);
+  let name = image.rsplit('/').next().unwrap_or(image);
+  if name.contains(':') || name.contains('@') {
+    image.to_string()
+  } else {
+    format!("{image}:latest")
+  }
+}
+
+fn is_interpolated(value: &str) -> bool {
+  value.contains("[[")
+}
+
+fn environment_drift(
+  config: &DeploymentConfig,
+  container: &Container,
+  drift: &mut Vec<String>,
+) {
+  let Ok(expected) = config.env_vars() else {
+    return;
+  };
+  let actual = container
+    .config
+    .as_ref()
+    .map(|config| {
+      config
+        .env
+        .iter()
+        .filter_map(|var| var.split_once('='))
+        .collect::<HashMap<_, _>>()
+    })
+    .unwrap_or_default();
+  for var in expected {
+    // Quotes around the value are removed by the shell on deploy.
+    let value = var
+      .value
+      .strip_prefix('"')
+      .and_then(|value| value.strip_suffix('"'))
+      .unwrap_or(&var.value);
+    match actual.get(var.variable.as_str()) {
+      None => drift.push(format!("env {}: missing", var.variable)),
+      Some(actual) if !is_interpolated(value) && *actual != value => {
+        drift.push(format!(
+          "env {}: expected '{value}', found '{actual}'",
+          var.variable
+        ))
+      }
+      Some(_) => {}
+    }
+  }
+}
+
+fn ports_drift(
+  config: &DeploymentConfig,
+  container: &Container,
+  drift: &mut Vec<String>,
+) {
+  // Ports aren't published on the host network.
+  if config.network == "host" {
+    return;
+  }
+  let Ok(conversions) = conversions_from_str(&config.ports) else {
+    return;
+  };
+  // Port ranges are expanded by docker into individual bindings.
+  if conversions.iter().any(|conversion| {
+    is_interpolated(&conversion.local)
+      || is_interpolated(&conversion.container)
+      || conversion.local.contains('-')
+      || conversion.container.contains('-')
+  }) {
+    return;
+  }
+  let expected = conversions
+    .into_iter()
+    .map(|conversion| {
+      let container = conversion
+        .container
+        .strip_suffix("/tcp")
+        .unwrap_or(&conversion.container)
+        .to_string();
+      format!("{}:{container}", conversion.local)
+    })
+    .collect::<BTreeSet<_>>();
+  let actual = container
+    .host_config
+    .as_ref()
+    .map(|host_config| {
+      host_config
+        .port_bindings
+        .iter()
+        .flat_map(|(container, bindings)| {
+          let container = container.replace("/tcp", "");
+          bindings.iter().filter_map(move |binding| {
+            let host_port = binding
+              .host_port
+              .as_deref()
+              .filter(|port| !port.is_empty())?;
+            let host = match binding.host_ip.as_deref() {
+              None | Some("") | Some("0.0.0.0") | Some("::") => {
+                host_port.to_string()
+              }
+              Some(ip) => format!("{ip}:{host_port}"),
+            };
+            Some(format!("{host}:{container}"))
+          })
+        })
+        .collect::<BTreeSet<_>>()
+    })
+    .unwrap_or_default();
+  for port in expected.difference(&actual) {
+    drift.push(format!("port {port}: missing"));
+  }
+  for port in actual.difference(&expected) {
+    drift.push(format!("port {port}: not in config"));
+  }
+}
+
+/// Checks the `--memory` / `-m` and `--cpus` limits
+/// passed in the extra args.
+fn limits_drift(
+  config: &DeploymentConfig,
+  container: &Container,
+  drift: &mut Vec<String>,
+) {
+  let Some(host_config) = &container.host_config else {
+    return;
+  };
+  let expected_memory =
+    extra_arg(&config.extra_args, &["--memory", "-m"])
+      .and_then(parse_memory)
+      .unwrap_or_default();
+  let actual_memory = host_config.memory.unwrap_or_default();
+  if expected_memory != actual_memory {
+    drift.push(format!(
+      "memory limit: expected {expected_memory} bytes, found {actual_memory} bytes"
+    ));
+  }
+  let expected_cpus = extra_arg(&config.extra_args, &["--cpus"])
+    .and_then(|cpus| cpus.parse::<f64>().ok())
+    .map(|cpus| (cpus * 1e9) as i64)
+    .unwrap_or_default();
+  let actual_cpus = host_config.nano_cpus.unwrap_or_default();
+  if expected_cpus != actual_cpus {
+    drift.push(format!(
+      "cpu limit: expected {} cpus, found {} cpus",
+      expected_cpus as f64 / 1e9,
+      actual_cpus as f64 / 1e9
+    ));
+  }
+}
+
+/// Finds the value of the flag in the extra args, which may be given
+/// either as `--flag=value` or `--flag value`.
+fn extra_arg<'a>(
+  extra_args: &'a [String],
+  flags: &[&str],
+) -> Option<&'a str> {
+  let mut args =
+    extra_args.iter().flat_map(|arg| arg.split_whitespace());
+  while let Some(arg) = args.next() {
+    for flag in flags {
+      if arg == *flag {
+        return args.next();
+      }
+      if let Some(value) = arg
+        .strip_prefix(flag)
+        .and_then(|rest| rest.strip_prefix('='))
+      {
+        return Some(value);
+      }
+    }
+  }
+  None
+}
+
+/// Parses a docker memory size, eg `512m`, into bytes.
+fn parse_memory(memory: &str) -> Option<i64> {
+  let memory = memory.trim_matches('"').to_lowercase();
+  let memory = memory.strip_suffix('b').unwrap_or(&memory);
+  let (number, multiplier) = match memory.chars().last()? {
+    'k' => (&memory[..memory.len() - 1], 1 << 10),
+    'm' => (&memory[..memory.len() - 1], 1 << 20),
+    'g' => (&memory[..memory.len() - 1], 1 << 30),
+    't' => (&memory[..memory.len() - 1], 1_i64 << 40),
+    _ => (memory, 1),
+  };
+  let number = number.parse::<f64>().ok()?;
+  Some((number * multiplier as f64) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+  use komodo_client::entities::docker::{
+    ContainerConfig, PortBinding, container::HostConfig,
+  };
+
+  use super::*;
+
+  fn config() -> DeploymentConfig {
+    DeploymentConfig {
+      image: DeploymentImage::Image {
+        image: String::from("nginx"),
+      },
+      environment: String::from("LOG=info\nTOKEN=[[SECRET]]"),
+      ports: String::from("8080:80"),
+      network: String::from("bridge"),
+      extra_args: vec![String::from("--memory 512m --cpus=1.5")],
+      ..Default::default()
+    }
+  }
+
+  fn container() -> Container {
+    Container {
+      config: Some(ContainerConfig {
+        image: Some(String::from("nginx:latest")),
+        env: vec![
+          String::from("LOG=info"),
+          String::from("TOKEN=resolved"),
+          String::from("PATH=/usr/bin"),
+        ],
+        ..Default::default()
+      }),
+      host_config: Some(HostConfig {
+        memory: Some(512 << 20),
+        nano_cpus: Some(1_500_000_000),
+        port_bindings: HashMap::from([(
+          String::from("80/tcp"),
+          vec![PortBinding {
+            host_ip: Some(String::new()),
+            host_port: Some(String::from("8080")),
+          }],
+        )]),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn no_drift_when_container_matches_config() {
+    assert!(
+      deployment_config_drift(&config(), &container()).is_empty()
+    );
+  }
+
+  #[test]
+  fn detects_drift() {
+    let mut container = container();
+    let container_config = container.config.as_mut().unwrap();
+    container_config.image = Some(String::from("nginx:1.27"));
+    container_config.env = vec![String::from("LOG=debug")];
+    let host_config = container.host_config.as_mut().unwrap();
+    host_config.memory = Some(1 << 30);
+    host_config.port_bindings.clear();
+    assert_eq!(
+      deployment_config_drift(&config(), &container),
+      vec![
+        "image: expected 'nginx:latest', found 'nginx:1.27'",
+        "env LOG: expected 'info', found 'debug'",
+        "env TOKEN: missing",
+        "port 8080:80: missing",
+        "memory limit: expected 536870912 bytes, found 1073741824 bytes",
+      ]
+    );
+  }
+
+  #[test]
+  fn normalizes_image() {
+    assert_eq!(normalize_image("nginx"), "nginx:latest");
+    assert_eq!(
+      normalize_image("docker.io/library/nginx"),
+      "library/nginx:latest"
+    );
+    assert_eq!(
+      normalize_image("localhost:5000/app"),
+      "localhost:5000/app:latest"
+    );
+    assert_eq!(
+      normalize_image("ghcr.io/org/app:1.0"),
+      "ghcr.io/org/app:1.0"
+    );
+  }
+
+  #[test]
+  fn finds_extra_arg_either_form() {
+    let args =
+      vec![String::from("--memory=1g"), String::from("--cpus 2")];
+    assert_eq!(extra_arg(&args, &["--memory", "-m"]), Some("1g"));
+    assert_eq!(extra_arg(&args, &["--cpus"]), Some("2"));
+    assert_eq!(extra_arg(&args, &["--restart"]), None);
+  }
+
+  #[test]
+  fn parses_memory_sizes() {
+    assert_eq!(parse_memory("512m"), Some(512 << 20));
+    assert_eq!(parse_memory("1.5GB"), Some(3 << 29));
+    assert_eq!(parse_memory("1024"), Some(1024));
+    assert_eq!(parse_memory("lots"), None);
+  }
+}
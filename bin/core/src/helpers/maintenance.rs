@@ -4,6 +4,7 @@ use anyhow::Context;
 use chrono::{Datelike, Local};
 use komodo_client::entities::{
   DayOfWeek, MaintenanceScheduleType, MaintenanceWindow,
+  server::Server,
 };
 
 use crate::config::core_config;
@@ -18,6 +19,19 @@ pub fn is_in_maintenance(
     .any(|window| is_maintenance_window_active(window, timestamp))
 }
 
+/// Check if a server is in maintenance mode,
+/// or in one of its maintenance windows, at the timestamp.
+pub fn is_server_in_maintenance(
+  server: &Server,
+  timestamp: i64,
+) -> bool {
+  server.config.maintenance_mode_active(timestamp)
+    || is_in_maintenance(
+      &server.config.maintenance_windows,
+      timestamp,
+    )
+}
+
 /// Check if the current timestamp falls within this maintenance window
 pub fn is_maintenance_window_active(
   window: &MaintenanceWindow,
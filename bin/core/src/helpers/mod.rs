@@ -22,15 +22,19 @@ use crate::{config::core_config, state::db_client};
 
 pub mod action_state;
 pub mod all_resources;
+pub mod audit;
 pub mod builder;
 pub mod cache;
 pub mod channel;
+pub mod drift;
 pub mod maintenance;
 pub mod matcher;
 pub mod procedure;
 pub mod prune;
 pub mod query;
+pub mod shutdown;
 pub mod update;
+pub mod webhook;
 
 // pub mod resource;
 
@@ -200,7 +204,8 @@ pub fn periphery_client(
       &server.config.passkey
     },
     Duration::from_secs(server.config.timeout_seconds as u64),
-  );
+  )
+  .with_compression(server.config.compression);
 
   Ok(client)
 }
@@ -1,23 +1,31 @@
-use std::time::{Duration, Instant};
+use std::{
+  net::{IpAddr, SocketAddr},
+  time::{Duration, Instant},
+};
 
 use anyhow::{Context, anyhow};
 use database::mungos::by_id::find_one_by_id;
 use formatting::{Color, bold, colored, format_serror, muted};
-use futures::future::join_all;
+use futures::{StreamExt, stream};
+use indexmap::IndexMap;
 use komodo_client::{
   api::execute::*,
   entities::{
-    action::Action,
+    I64,
+    action::{ACTION_OUTPUT_LOG_STAGE, Action},
     build::Build,
     deployment::Deployment,
+    docker::container::{ContainerStateStatusEnum, HealthStatusEnum},
     permission::PermissionLevel,
-    procedure::Procedure,
+    procedure::{Procedure, ProcedureStage, StageCondition},
     repo::Repo,
+    server::Server,
     stack::Stack,
     update::{Log, Update},
     user::procedure_user,
   },
 };
+use periphery_client::api::container::InspectContainer;
 use resolver_api::Resolve;
 use tokio::sync::Mutex;
 
@@ -26,19 +34,77 @@ use crate::{
     execute::{ExecuteArgs, ExecuteRequest},
     write::WriteArgs,
   },
-  resource::{KomodoResource, list_full_for_user_using_pattern},
+  config::core_config,
+  resource::{
+    self, KomodoResource, list_full_for_user_using_pattern,
+  },
   state::db_client,
 };
 
-use super::update::{init_execution_update, update_update};
+use super::{
+  periphery_client,
+  query::get_deployed_variable_references,
+  update::{init_execution_update, update_update},
+};
+
+/// Values recorded from finished executions, keyed by `{stage name}.{field}`.
+/// Later stages can reference them in execution parameters using `{{name}}`.
+pub type ProcedureVariables = Mutex<IndexMap<String, String>>;
 
 #[instrument(skip_all)]
 pub async fn execute_procedure(
   procedure: &Procedure,
+  variables: &ProcedureVariables,
   update: &Mutex<Update>,
 ) -> anyhow::Result<()> {
+  // Holds the error of the first failed stage.
+  // Later stages still run if their condition allows.
+  let mut failure: Option<anyhow::Error> = None;
+  // The outcome of each stage reached so far, for stage conditions.
+  let mut outcomes = Vec::<(&str, StageOutcome)>::new();
+  let total = procedure
+    .config
+    .stages
+    .iter()
+    .filter(|stage| stage.enabled)
+    .count() as u32;
+  let mut i = 0;
   for stage in &procedure.config.stages {
     if !stage.enabled {
+      outcomes.push((&stage.name, StageOutcome::Skipped));
+      continue;
+    }
+    i += 1;
+    update.lock().await.set_progress(i, total, &stage.name);
+    let should_run = should_run_stage(
+      stage.condition,
+      &stage.condition_stage,
+      &outcomes,
+    )
+    .with_context(|| {
+      format!("Invalid condition on stage '{}'", stage.name)
+    });
+    let should_run = match should_run {
+      Ok(should_run) => should_run,
+      Err(e) => {
+        if failure.is_none() {
+          failure = Some(e);
+        }
+        false
+      }
+    };
+    if !should_run {
+      outcomes.push((&stage.name, StageOutcome::Skipped));
+      add_line_to_update(
+        update,
+        &format!(
+          "{}: Skipping stage: '{}' | condition: {}",
+          muted("INFO"),
+          bold(&stage.name),
+          condition_label(stage),
+        ),
+      )
+      .await;
       continue;
     }
     add_line_to_update(
@@ -51,13 +117,16 @@ pub async fn execute_procedure(
     )
     .await;
     let timer = Instant::now();
-    execute_stage(
+    let res = execute_stage(
       stage
         .executions
         .iter()
         .filter(|item| item.enabled)
         .map(|item| item.execution.clone())
         .collect(),
+      stage.max_concurrency,
+      &stage.name,
+      variables,
       &procedure.id,
       &procedure.name,
       update,
@@ -69,7 +138,26 @@ pub async fn execute_procedure(
         bold(&stage.name),
         timer.elapsed(),
       )
-    })?;
+    });
+    if let Err(e) = res {
+      add_line_to_update(
+        update,
+        &format!(
+          "{}: {} stage '{}' execution in {:?}",
+          muted("INFO"),
+          colored("Failed", Color::Red),
+          bold(&stage.name),
+          timer.elapsed()
+        ),
+      )
+      .await;
+      outcomes.push((&stage.name, StageOutcome::Failed));
+      if failure.is_none() {
+        failure = Some(e);
+      }
+      continue;
+    }
+    outcomes.push((&stage.name, StageOutcome::Succeeded));
     add_line_to_update(
       update,
       &format!(
@@ -83,13 +171,97 @@ pub async fn execute_procedure(
     .await;
   }
 
+  match failure {
+    Some(e) => Err(e),
+    None => Ok(()),
+  }
+}
+
+/// The result of a stage reached during a procedure run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StageOutcome {
+  Succeeded,
+  Failed,
+  /// Disabled, or its condition was not met.
+  Skipped,
+}
+
+/// Decides whether a stage runs given the outcomes of the
+/// stages before it. With an empty `condition_stage`, the
+/// condition checks all previous stages, otherwise only the
+/// named stage. A skipped named stage meets neither
+/// `Success` nor `Failure`.
+fn should_run_stage(
+  condition: StageCondition,
+  condition_stage: &str,
+  outcomes: &[(&str, StageOutcome)],
+) -> anyhow::Result<bool> {
+  if condition == StageCondition::Always {
+    return Ok(true);
+  }
+  let failed = if condition_stage.is_empty() {
+    outcomes
+      .iter()
+      .any(|(_, outcome)| *outcome == StageOutcome::Failed)
+  } else {
+    let outcome = outcomes
+      .iter()
+      .rev()
+      .find(|(name, _)| *name == condition_stage)
+      .map(|(_, outcome)| *outcome)
+      .with_context(|| {
+        format!("No stage '{condition_stage}' runs before this stage")
+      })?;
+    match outcome {
+      StageOutcome::Succeeded => false,
+      StageOutcome::Failed => true,
+      StageOutcome::Skipped => return Ok(false),
+    }
+  };
+  Ok(match condition {
+    StageCondition::Success => !failed,
+    StageCondition::Failure => failed,
+    StageCondition::Always => true,
+  })
+}
+
+/// Checks each stage `condition_stage` names an earlier stage.
+pub fn validate_stage_conditions(
+  stages: &[ProcedureStage],
+) -> anyhow::Result<()> {
+  for (i, stage) in stages.iter().enumerate() {
+    if stage.condition_stage.is_empty() {
+      continue;
+    }
+    if !stages[..i]
+      .iter()
+      .any(|earlier| earlier.name == stage.condition_stage)
+    {
+      return Err(anyhow!(
+        "Stage '{}' condition references '{}', which is not an earlier stage",
+        stage.name,
+        stage.condition_stage
+      ));
+    }
+  }
   Ok(())
 }
 
+fn condition_label(stage: &ProcedureStage) -> String {
+  if stage.condition_stage.is_empty() {
+    stage.condition.to_string()
+  } else {
+    format!("{} of '{}'", stage.condition, stage.condition_stage)
+  }
+}
+
 #[allow(dependency_on_unit_never_type_fallback)]
 #[instrument(skip(update))]
 async fn execute_stage(
   _executions: Vec<Execution>,
+  max_concurrency: I64,
+  stage_name: &str,
+  variables: &ProcedureVariables,
   parent_id: &str,
   parent_name: &str,
   update: &Mutex<Update>,
@@ -181,9 +353,39 @@ async fn execute_stage(
         )
         .await?;
       }
+      Execution::RedeployVariableReferences(exec) => {
+        let (deployments, stacks) = get_deployed_variable_references(
+          &exec.variable,
+          procedure_user(),
+        )
+        .await?;
+        executions.extend(deployments.into_iter().map(
+          |deployment| {
+            Execution::Deploy(Deploy {
+              deployment,
+              stop_signal: None,
+              stop_time: None,
+            })
+          },
+        ));
+        executions.extend(stacks.into_iter().map(|stack| {
+          Execution::DeployStack(DeployStack {
+            stack,
+            services: Vec::new(),
+            stop_time: None,
+          })
+        }));
+      }
       execution => executions.push(execution),
     }
   }
+  let executions = {
+    let variables = variables.lock().await;
+    executions
+      .into_iter()
+      .map(|execution| interpolate_variables(execution, &variables))
+      .collect::<anyhow::Result<Vec<_>>>()?
+  };
   let futures = executions.into_iter().map(|execution| async move {
     let now = Instant::now();
     add_line_to_update(
@@ -199,6 +401,9 @@ async fn execute_stage(
       execute_execution(execution.clone(), parent_id, parent_name)
         .await
         .context(fail_log);
+    if let Ok(update) = &res {
+      record_variables(stage_name, update, variables).await;
+    }
     add_line_to_update(
       update,
       &format!(
@@ -211,22 +416,39 @@ async fn execute_stage(
     .await;
     res
   });
-  join_all(futures)
+  run_concurrently(futures, max_concurrency)
     .await
     .into_iter()
     .collect::<anyhow::Result<Vec<_>>>()?;
   Ok(())
 }
 
+/// Runs the futures with at most `max_concurrency` running at once.
+/// 0 (the default) means no limit, all the futures run at once.
+async fn run_concurrently<F: Future>(
+  futures: impl ExactSizeIterator<Item = F>,
+  max_concurrency: I64,
+) -> Vec<F::Output> {
+  let max_concurrency = if max_concurrency > 0 {
+    max_concurrency as usize
+  } else {
+    futures.len().max(1)
+  };
+  stream::iter(futures)
+    .buffer_unordered(max_concurrency)
+    .collect()
+    .await
+}
+
 async fn execute_execution(
   execution: Execution,
   // used to prevent recursive procedure
   parent_id: &str,
   parent_name: &str,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Update> {
   let user = procedure_user().to_owned();
   let update = match execution {
-    Execution::None(_) => return Ok(()),
+    Execution::None(_) => return Ok(Update::default()),
     Execution::RunProcedure(req) => {
       if req.procedure == parent_id || req.procedure == parent_name {
         return Err(anyhow!("Self referential procedure detected"));
@@ -339,6 +561,23 @@ async fn execute_execution(
         "Batch method BatchDeploy not implemented correctly"
       ));
     }
+    Execution::ReconcileDeployment(req) => {
+      let req = ExecuteRequest::ReconcileDeployment(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::ReconcileDeployment(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at ReconcileDeployment"),
+        &update_id,
+      )
+      .await?
+    }
     Execution::PullDeployment(req) => {
       let req = ExecuteRequest::PullDeployment(req);
       let update = init_execution_update(&req, &user).await?;
@@ -1209,6 +1448,12 @@ async fn execute_execution(
       )
       .await?
     }
+    Execution::RedeployVariableReferences(_) => {
+      // All batch executions must be expanded in `execute_stage`
+      return Err(anyhow!(
+        "Batch method RedeployVariableReferences not implemented correctly"
+      ));
+    }
     Execution::Sleep(req) => {
       let duration = Duration::from_millis(req.duration_ms as u64);
       tokio::time::sleep(duration).await;
@@ -1217,9 +1462,16 @@ async fn execute_execution(
         ..Default::default()
       }
     }
+    Execution::WaitUntil(req) => {
+      wait_until(req).await?;
+      Update {
+        success: true,
+        ..Default::default()
+      }
+    }
   };
   if update.success {
-    Ok(())
+    Ok(update)
   } else {
     Err(anyhow!(
       "{}: execution not successful. see update '{}'",
@@ -1229,6 +1481,304 @@ async fn execute_execution(
   }
 }
 
+/// Replaces `{{name}}` in the execution parameters with the
+/// matching procedure variable. Unknown names are left as is,
+/// as parameters may contain other templates (eg. go templates).
+fn interpolate_variables(
+  execution: Execution,
+  variables: &IndexMap<String, String>,
+) -> anyhow::Result<Execution> {
+  if variables.is_empty() {
+    return Ok(execution);
+  }
+  let mut value = serde_json::to_value(&execution)
+    .context("Failed to serialize execution")?;
+  interpolate_value(&mut value, variables);
+  serde_json::from_value(value).context(
+    "Failed to parse execution after interpolating procedure variables",
+  )
+}
+
+/// Only string values are interpolated,
+/// so the keys and the structure of the parameters can't change.
+fn interpolate_value(
+  value: &mut serde_json::Value,
+  variables: &IndexMap<String, String>,
+) {
+  match value {
+    serde_json::Value::String(string) => {
+      for (name, variable) in variables {
+        let reference = format!("{{{{{name}}}}}");
+        if string.contains(&reference) {
+          *string = string.replace(&reference, variable);
+        }
+      }
+    }
+    serde_json::Value::Array(values) => {
+      for value in values {
+        interpolate_value(value, variables);
+      }
+    }
+    serde_json::Value::Object(map) => {
+      for value in map.values_mut() {
+        interpolate_value(value, variables);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Records the outputs of a finished execution for use by later stages.
+/// If a stage runs multiple executions, the last one to finish is kept.
+async fn record_variables(
+  stage_name: &str,
+  update: &Update,
+  variables: &ProcedureVariables,
+) {
+  // Executions like Sleep don't produce an Update.
+  if update.id.is_empty() {
+    return;
+  }
+  let mut variables = variables.lock().await;
+  variables
+    .insert(format!("{stage_name}.update_id"), update.id.clone());
+  if !update.version.is_none() {
+    variables.insert(
+      format!("{stage_name}.version"),
+      update.version.to_string(),
+    );
+  }
+  if !update.commit_hash.is_empty() {
+    variables.insert(
+      format!("{stage_name}.commit_hash"),
+      update.commit_hash.clone(),
+    );
+  }
+  let Some(output) = update
+    .logs
+    .iter()
+    .find(|log| log.stage == ACTION_OUTPUT_LOG_STAGE && log.success)
+    .and_then(|log| {
+      serde_json::from_str::<serde_json::Value>(&log.stdout).ok()
+    })
+  else {
+    return;
+  };
+  if let serde_json::Value::Object(fields) = &output {
+    for (field, value) in fields {
+      variables.insert(
+        format!("{stage_name}.output.{field}"),
+        output_variable(value),
+      );
+    }
+  }
+  variables
+    .insert(format!("{stage_name}.output"), output_variable(&output));
+}
+
+/// Strings are recorded without quotes,
+/// other values as their JSON representation.
+fn output_variable(value: &serde_json::Value) -> String {
+  match value {
+    serde_json::Value::String(value) => value.clone(),
+    value => value.to_string(),
+  }
+}
+
+/// Polls the condition until it is met, or errors after the timeout.
+/// Errors checking the condition are retried, as the target may
+/// not be reachable until it finishes starting up.
+async fn wait_until(
+  WaitUntil {
+    condition,
+    target,
+    timeout_ms,
+    interval_ms,
+  }: WaitUntil,
+) -> anyhow::Result<()> {
+  let timeout = Duration::from_millis(timeout_ms.max(0) as u64);
+  let interval = Duration::from_millis(interval_ms.max(500) as u64);
+  let res = match condition {
+    WaitCondition::Duration => {
+      tokio::time::sleep(timeout).await;
+      return Ok(());
+    }
+    WaitCondition::DeploymentHealthy => {
+      poll_until(timeout, interval, || deployment_healthy(&target))
+        .await
+    }
+    WaitCondition::HttpOk => {
+      // Fail immediately rather than retrying a disallowed url.
+      resolve_http_target(&target).await?;
+      poll_until(timeout, interval, || http_ok(&target)).await
+    }
+  };
+  res.with_context(|| {
+    format!(
+      "{}: Timed out after {timeout:?} waiting for {condition} | {target}",
+      colored("ERROR", Color::Red),
+    )
+  })
+}
+
+/// Calls `check` every `interval` until it returns true.
+/// Returns the last error, or "Condition not met",
+/// once the timeout has passed.
+async fn poll_until<F, Fut>(
+  timeout: Duration,
+  interval: Duration,
+  mut check: F,
+) -> anyhow::Result<()>
+where
+  F: FnMut() -> Fut,
+  Fut: Future<Output = anyhow::Result<bool>>,
+{
+  let timer = Instant::now();
+  loop {
+    let e = match check().await {
+      Ok(true) => return Ok(()),
+      Ok(false) => anyhow!("Condition not met"),
+      Err(e) => e,
+    };
+    if timer.elapsed() >= timeout {
+      return Err(e);
+    }
+    tokio::time::sleep(interval).await;
+  }
+}
+
+async fn deployment_healthy(
+  deployment: &str,
+) -> anyhow::Result<bool> {
+  let deployment = resource::get::<Deployment>(deployment).await?;
+  let server =
+    resource::get::<Server>(&deployment.config.server_id).await?;
+  let state = periphery_client(&server)?
+    .request(InspectContainer {
+      name: deployment.name,
+    })
+    .await?
+    .state
+    .unwrap_or_default();
+  let health =
+    state.health.map(|health| health.status).unwrap_or_default();
+  Ok(
+    state.status == ContainerStateStatusEnum::Running
+      && matches!(
+        health,
+        HealthStatusEnum::Healthy
+          | HealthStatusEnum::None
+          | HealthStatusEnum::Empty
+      ),
+  )
+}
+
+/// Redirects are not followed, so they can't be used
+/// to reach an address which isn't allowed.
+async fn http_ok(url: &str) -> anyhow::Result<bool> {
+  let (url, domain, addr) = resolve_http_target(url).await?;
+  let mut client = reqwest::Client::builder()
+    .redirect(reqwest::redirect::Policy::none())
+    .timeout(Duration::from_secs(10));
+  // Connect to the address which was checked,
+  // rather than resolving the domain again.
+  if let Some(domain) = domain {
+    client = client.resolve(&domain, addr);
+  }
+  let res = client
+    .build()
+    .context("Failed to build http client")?
+    .get(url.clone())
+    .send()
+    .await
+    .with_context(|| format!("Failed to reach {url}"))?;
+  Ok(res.status() == reqwest::StatusCode::OK)
+}
+
+/// Parses the url and resolves its host, returning the domain
+/// (if it isn't an ip) and the address to connect to.
+/// To prevent using Core to reach internal services, every address the host
+/// resolves to must be public or in `wait_until_allowed_ips`.
+async fn resolve_http_target(
+  url: &str,
+) -> anyhow::Result<(reqwest::Url, Option<String>, SocketAddr)> {
+  let url = reqwest::Url::parse(url)
+    .with_context(|| format!("Invalid url: {url}"))?;
+  if !matches!(url.scheme(), "http" | "https") {
+    return Err(anyhow!(
+      "Url scheme must be http or https, got {}",
+      url.scheme()
+    ));
+  }
+  let port = url
+    .port_or_known_default()
+    .context("Url does not have a port")?;
+  let (domain, addrs) = match url.domain() {
+    Some(domain) => {
+      let addrs = tokio::net::lookup_host((domain, port))
+        .await
+        .with_context(|| format!("Failed to resolve {domain}"))?
+        .collect::<Vec<_>>();
+      (Some(domain.to_string()), addrs)
+    }
+    None => {
+      let ip = url
+        .host_str()
+        .context("Url does not have a host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .context("Url host is not a valid ip")?;
+      (None, vec![SocketAddr::new(ip, port)])
+    }
+  };
+  let allowed_ips = &core_config().wait_until_allowed_ips;
+  for addr in &addrs {
+    let ip = addr.ip().to_canonical();
+    if !public_ip(ip)
+      && !allowed_ips.iter().any(|net| net.contains(ip))
+    {
+      return Err(anyhow!(
+        "{url} resolves to {ip}, which is not a public address. Add it to 'wait_until_allowed_ips' to allow."
+      ));
+    }
+  }
+  let addr = addrs.into_iter().next().with_context(|| {
+    format!("{url} did not resolve to any address")
+  })?;
+  Ok((url, domain, addr))
+}
+
+/// Whether the ip is routable on the public internet.
+fn public_ip(ip: IpAddr) -> bool {
+  match ip.to_canonical() {
+    IpAddr::V4(ip) => {
+      let [a, b, ..] = ip.octets();
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // Shared address space (carrier grade NAT), 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // "This network", 0.0.0.0/8
+        || a == 0)
+    }
+    IpAddr::V6(ip) => {
+      let first = ip.segments()[0];
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link local, fe80::/10
+        || (first & 0xffc0) == 0xfe80)
+    }
+  }
+}
+
 /// If the call to .resolve returns Err, the update may not be closed.
 /// This will ensure it is closed with error log attached.
 async fn handle_resolve_result(
@@ -1399,3 +1949,318 @@ impl ExtendBatch for BatchDestroyStack {
     })
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use super::*;
+
+  const INTERVAL: Duration = Duration::from_millis(10);
+
+  #[tokio::test]
+  async fn poll_until_blocks_until_condition_met() {
+    let checks = AtomicUsize::new(0);
+    poll_until(Duration::from_secs(5), INTERVAL, || async {
+      Ok(checks.fetch_add(1, Ordering::Relaxed) == 3)
+    })
+    .await
+    .unwrap();
+    assert_eq!(checks.load(Ordering::Relaxed), 4);
+  }
+
+  #[tokio::test]
+  async fn poll_until_retries_errors() {
+    let checks = AtomicUsize::new(0);
+    poll_until(Duration::from_secs(5), INTERVAL, || async {
+      if checks.fetch_add(1, Ordering::Relaxed) < 2 {
+        Err(anyhow!("connection refused"))
+      } else {
+        Ok(true)
+      }
+    })
+    .await
+    .unwrap();
+  }
+
+  #[tokio::test]
+  async fn poll_until_times_out() {
+    let timer = Instant::now();
+    let e =
+      poll_until(Duration::from_millis(50), INTERVAL, || async {
+        Ok(false)
+      })
+      .await
+      .unwrap_err();
+    assert!(timer.elapsed() >= Duration::from_millis(50));
+    assert_eq!(e.to_string(), "Condition not met");
+  }
+
+  #[tokio::test]
+  async fn poll_until_times_out_with_last_error() {
+    let e = poll_until(Duration::ZERO, INTERVAL, || async {
+      Err(anyhow!("connection refused"))
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(e.to_string(), "connection refused");
+  }
+
+  /// Runs 10 tasks with the limit, returning
+  /// the most which ran at once.
+  async fn peak_concurrency(max_concurrency: I64) -> usize {
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+    let futures = (0..10).map(|_| async {
+      let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+      peak.fetch_max(now, Ordering::SeqCst);
+      tokio::time::sleep(INTERVAL).await;
+      running.fetch_sub(1, Ordering::SeqCst);
+    });
+    let finished = run_concurrently(futures, max_concurrency).await;
+    assert_eq!(finished.len(), 10);
+    peak.load(Ordering::SeqCst)
+  }
+
+  #[tokio::test]
+  async fn max_concurrency_limits_running_executions() {
+    assert_eq!(peak_concurrency(1).await, 1);
+    assert_eq!(peak_concurrency(3).await, 3);
+    assert_eq!(peak_concurrency(20).await, 10);
+  }
+
+  #[tokio::test]
+  async fn zero_max_concurrency_is_unlimited() {
+    assert_eq!(peak_concurrency(0).await, 10);
+    assert_eq!(peak_concurrency(-1).await, 10);
+  }
+
+  #[test]
+  fn public_ips() {
+    for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+      assert!(public_ip(ip.parse().unwrap()), "{ip}");
+    }
+  }
+
+  #[test]
+  fn non_public_ips() {
+    for ip in [
+      "127.0.0.1",
+      "0.0.0.0",
+      "10.1.2.3",
+      "172.16.0.1",
+      "192.168.1.1",
+      "169.254.169.254",
+      "100.64.0.1",
+      "::1",
+      "::",
+      "fd00::1",
+      "fe80::1",
+      "::ffff:127.0.0.1",
+      "::ffff:169.254.169.254",
+    ] {
+      assert!(!public_ip(ip.parse().unwrap()), "{ip}");
+    }
+  }
+
+  use StageOutcome::*;
+
+  const OUTCOMES: &[(&str, StageOutcome)] = &[
+    ("Build", Succeeded),
+    ("Deploy", Failed),
+    ("Notify", Skipped),
+  ];
+
+  #[test]
+  fn conditions_on_all_previous_stages() {
+    let ok = &OUTCOMES[..1];
+    for (condition, outcomes, expected) in [
+      (StageCondition::Success, ok, true),
+      (StageCondition::Success, OUTCOMES, false),
+      (StageCondition::Failure, ok, false),
+      (StageCondition::Failure, OUTCOMES, true),
+      (StageCondition::Always, OUTCOMES, true),
+      (StageCondition::Success, &[][..], true),
+      (StageCondition::Failure, &[][..], false),
+    ] {
+      assert_eq!(
+        should_run_stage(condition, "", outcomes).unwrap(),
+        expected,
+        "{condition} {outcomes:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn conditions_on_named_stage() {
+    for (condition, stage, expected) in [
+      (StageCondition::Success, "Build", true),
+      (StageCondition::Failure, "Build", false),
+      (StageCondition::Success, "Deploy", false),
+      (StageCondition::Failure, "Deploy", true),
+      (StageCondition::Success, "Notify", false),
+      (StageCondition::Failure, "Notify", false),
+      (StageCondition::Always, "Notify", true),
+    ] {
+      assert_eq!(
+        should_run_stage(condition, stage, OUTCOMES).unwrap(),
+        expected,
+        "{condition} of {stage}"
+      );
+    }
+  }
+
+  #[test]
+  fn condition_on_unknown_stage_errors() {
+    assert!(
+      should_run_stage(StageCondition::Failure, "Other", OUTCOMES)
+        .is_err()
+    );
+  }
+
+  fn stage(name: &str, condition_stage: &str) -> ProcedureStage {
+    ProcedureStage {
+      name: name.to_string(),
+      enabled: true,
+      condition: StageCondition::Failure,
+      condition_stage: condition_stage.to_string(),
+      max_concurrency: 0,
+      executions: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn stage_conditions_must_reference_earlier_stage() {
+    validate_stage_conditions(&[
+      stage("Deploy", ""),
+      stage("Alert", "Deploy"),
+    ])
+    .unwrap();
+    for stages in [
+      [stage("Alert", "Deploy"), stage("Deploy", "")],
+      [stage("Deploy", ""), stage("Alert", "Alert")],
+      [stage("Deploy", ""), stage("Alert", "Build")],
+    ] {
+      assert!(validate_stage_conditions(&stages).is_err());
+    }
+  }
+
+  fn deploy_stack(stack: &str, services: &[&str]) -> Execution {
+    Execution::DeployStack(DeployStack {
+      stack: stack.to_string(),
+      services: services.iter().map(|s| s.to_string()).collect(),
+      stop_time: None,
+    })
+  }
+
+  #[test]
+  fn interpolates_variable_from_earlier_stage() {
+    let variables = IndexMap::from([(
+      String::from("Build.version"),
+      String::from("1.2.3"),
+    )]);
+    let execution = interpolate_variables(
+      deploy_stack("app-{{Build.version}}", &["{{Build.version}}"]),
+      &variables,
+    )
+    .unwrap();
+    let Execution::DeployStack(deploy) = execution else {
+      panic!("Execution type changed");
+    };
+    assert_eq!(deploy.stack, "app-1.2.3");
+    assert_eq!(deploy.services, ["1.2.3"]);
+  }
+
+  #[test]
+  fn leaves_unknown_references() {
+    let variables = IndexMap::from([(
+      String::from("Build.version"),
+      String::from("1.2.3"),
+    )]);
+    let execution = interpolate_variables(
+      deploy_stack("{{ .Name }}", &["{{Other.version}}"]),
+      &variables,
+    )
+    .unwrap();
+    let Execution::DeployStack(deploy) = execution else {
+      panic!("Execution type changed");
+    };
+    assert_eq!(deploy.stack, "{{ .Name }}");
+    assert_eq!(deploy.services, ["{{Other.version}}"]);
+  }
+
+  #[test]
+  fn variable_values_are_not_parsed() {
+    // Quotes and backslashes stay inside the string value.
+    let value = String::from(r#"a", "stop_time": 1, "b\"#);
+    let variables = IndexMap::from([(
+      String::from("Build.version"),
+      value.clone(),
+    )]);
+    let execution = interpolate_variables(
+      deploy_stack("{{Build.version}}", &[]),
+      &variables,
+    )
+    .unwrap();
+    let Execution::DeployStack(deploy) = execution else {
+      panic!("Execution type changed");
+    };
+    assert_eq!(deploy.stack, value);
+    assert_eq!(deploy.stop_time, None);
+  }
+
+  fn action_update(output: Log) -> Update {
+    Update {
+      id: String::from("update"),
+      logs: vec![
+        Log::simple("Execute Action", String::new()),
+        output,
+      ],
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn records_action_output_fields() {
+    let variables = ProcedureVariables::default();
+    let output = Log::simple(
+      ACTION_OUTPUT_LOG_STAGE,
+      String::from(r#"{"tag": "v1.2", "count": 3}"#),
+    );
+    record_variables("Build", &action_update(output), &variables)
+      .await;
+    let variables = variables.lock().await;
+    assert_eq!(variables["Build.update_id"], "update");
+    assert_eq!(variables["Build.output.tag"], "v1.2");
+    assert_eq!(variables["Build.output.count"], "3");
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(
+        &variables["Build.output"]
+      )
+      .unwrap(),
+      serde_json::json!({ "tag": "v1.2", "count": 3 })
+    );
+  }
+
+  #[tokio::test]
+  async fn records_non_object_action_output() {
+    let variables = ProcedureVariables::default();
+    let output =
+      Log::simple(ACTION_OUTPUT_LOG_STAGE, String::from("\"done\""));
+    record_variables("Build", &action_update(output), &variables)
+      .await;
+    let variables = variables.lock().await;
+    assert_eq!(variables["Build.output"], "done");
+    assert!(!variables.contains_key("Build.output.done"));
+  }
+
+  #[tokio::test]
+  async fn ignores_failed_action_output() {
+    let variables = ProcedureVariables::default();
+    let output =
+      Log::error(ACTION_OUTPUT_LOG_STAGE, String::from("{}"));
+    record_variables("Build", &action_update(output), &variables)
+      .await;
+    assert!(!variables.lock().await.contains_key("Build.output"));
+  }
+}
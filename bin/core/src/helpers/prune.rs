@@ -2,20 +2,42 @@ use anyhow::Context;
 use async_timing_util::{
   ONE_DAY_MS, Timelength, unix_timestamp_ms, wait_until_timelength,
 };
-use database::mungos::{find::find_collect, mongodb::bson::doc};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{Collection, bson::doc},
+};
 use futures::{StreamExt, stream::FuturesUnordered};
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  api_key::ApiKey,
+  komodo_timestamp,
+};
 use periphery_client::api::image::PruneImages;
 
-use crate::{config::core_config, state::db_client};
+use crate::{
+  alert::send_alerts, auth::oidc::session::prune_sessions,
+  config::core_config, metrics, monitor::rollup_stats,
+  state::db_client,
+};
 
-use super::periphery_client;
+use super::{periphery_client, query::get_user};
 
 pub fn spawn_prune_loop() {
   tokio::spawn(async move {
     loop {
       wait_until_timelength(Timelength::OneDay, 5000).await;
-      let (images_res, stats_res, alerts_res) =
-        tokio::join!(prune_images(), prune_stats(), prune_alerts());
+      // Roll up the stats before they are pruned.
+      if let Err(e) = rollup_stats().await {
+        error!("error in rolling up stats | {e:#}");
+      }
+      let (images_res, stats_res, alerts_res, audit_res, login_res) = tokio::join!(
+        prune_images(),
+        prune_stats(),
+        prune_alerts(),
+        prune_audit_logs(),
+        prune_login_events()
+      );
       if let Err(e) = images_res {
         error!("error in pruning images | {e:#}");
       }
@@ -25,6 +47,19 @@ pub fn spawn_prune_loop() {
       if let Err(e) = alerts_res {
         error!("error in pruning alerts | {e:#}");
       }
+      if let Err(e) = audit_res {
+        error!("error in pruning audit logs | {e:#}");
+      }
+      if let Err(e) = login_res {
+        error!("error in pruning login events | {e:#}");
+      }
+      if let Err(e) = prune_sessions().await {
+        error!("error in pruning oidc sessions | {e:#}");
+      }
+      if let Err(e) = alert_expiring_api_keys().await {
+        error!("error in alerting expiring api keys | {e:#}");
+      }
+      metrics::heartbeat("prune");
     }
   });
 }
@@ -62,20 +97,42 @@ async fn prune_images() -> anyhow::Result<()> {
 }
 
 async fn prune_stats() -> anyhow::Result<()> {
-  if core_config().keep_stats_for_days == 0 {
+  let config = core_config();
+  let db = db_client();
+  prune_by_ts(&db.stats, config.keep_stats_for_days, "stats").await?;
+  prune_by_ts(
+    &db.stats_hourly,
+    config.keep_hourly_stats_for_days,
+    "hourly stats",
+  )
+  .await?;
+  prune_by_ts(
+    &db.stats_daily,
+    config.keep_daily_stats_for_days,
+    "daily stats",
+  )
+  .await
+}
+
+/// Deletes the documents older than `keep_for_days`,
+/// or does nothing if it is 0.
+async fn prune_by_ts<T: Send + Sync>(
+  collection: &Collection<T>,
+  keep_for_days: u64,
+  name: &str,
+) -> anyhow::Result<()> {
+  if keep_for_days == 0 {
     return Ok(());
   }
-  let delete_before_ts = (unix_timestamp_ms()
-    - core_config().keep_stats_for_days as u128 * ONE_DAY_MS)
-    as i64;
-  let res = db_client()
-    .stats
+  let delete_before_ts =
+    (unix_timestamp_ms() - keep_for_days as u128 * ONE_DAY_MS) as i64;
+  let res = collection
     .delete_many(doc! {
       "ts": { "$lt": delete_before_ts }
     })
     .await?;
   if res.deleted_count > 0 {
-    info!("deleted {} stats from db", res.deleted_count);
+    info!("deleted {} {name} from db", res.deleted_count);
   }
   Ok(())
 }
@@ -98,3 +155,153 @@ async fn prune_alerts() -> anyhow::Result<()> {
   }
   Ok(())
 }
+
+async fn prune_audit_logs() -> anyhow::Result<()> {
+  prune_by_ts(
+    &db_client().audit_logs,
+    core_config().keep_audit_logs_for_days,
+    "audit logs",
+  )
+  .await
+}
+
+async fn prune_login_events() -> anyhow::Result<()> {
+  prune_by_ts(
+    &db_client().login_events,
+    core_config().keep_login_events_for_days,
+    "login events",
+  )
+  .await
+}
+
+/// Alerts on the api keys expiring within
+/// `api_key_expiry_alert_days`, once per key.
+async fn alert_expiring_api_keys() -> anyhow::Result<()> {
+  let Some((now, alert_before_ts)) = api_key_expiry_alert_range(
+    unix_timestamp_ms(),
+    core_config().api_key_expiry_alert_days,
+  ) else {
+    return Ok(());
+  };
+  let db = db_client();
+  let keys = find_collect(
+    &db.api_keys,
+    doc! {
+      "expires": { "$gt": now, "$lte": alert_before_ts },
+      "expiry_alerted": { "$ne": true },
+    },
+    None,
+  )
+  .await
+  .context("failed to get expiring api keys from db")?;
+  if keys.is_empty() {
+    return Ok(());
+  }
+
+  let mut alerts = Vec::with_capacity(keys.len());
+  for key in &keys {
+    let username = get_user(&key.user_id)
+      .await
+      .map(|user| user.username)
+      .unwrap_or_else(|_| key.user_id.clone());
+    alerts.push(api_key_expiring_alert(
+      key,
+      username,
+      komodo_timestamp(),
+    ));
+  }
+  send_alerts(&alerts).await;
+
+  let keys = keys.into_iter().map(|key| key.key).collect::<Vec<_>>();
+  db.api_keys
+    .update_many(
+      doc! { "key": { "$in": keys } },
+      doc! { "$set": { "expiry_alerted": true } },
+    )
+    .await
+    .context("failed to mark api keys as expiry alerted")?;
+
+  Ok(())
+}
+
+/// The (now, alert before) range of api key expiry timestamps
+/// to alert on, or None if expiry alerts are disabled.
+fn api_key_expiry_alert_range(
+  now: u128,
+  alert_days: u64,
+) -> Option<(i64, i64)> {
+  if alert_days == 0 {
+    return None;
+  }
+  Some((now as i64, (now + alert_days as u128 * ONE_DAY_MS) as i64))
+}
+
+fn api_key_expiring_alert(
+  key: &ApiKey,
+  username: String,
+  ts: i64,
+) -> Alert {
+  Alert {
+    id: Default::default(),
+    ts,
+    resolved: true,
+    resolved_ts: Some(ts),
+    level: SeverityLevel::Warning,
+    target: ResourceTarget::system(),
+    data: AlertData::ApiKeyExpiring {
+      name: key.name.clone(),
+      user_id: key.user_id.clone(),
+      username,
+      expires: key.expires,
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn expiry_alerts_disabled_with_zero_days() {
+    assert_eq!(api_key_expiry_alert_range(1_000, 0), None);
+  }
+
+  #[test]
+  fn expiry_alert_range_covers_alert_days() {
+    let now = 1_700_000_000_000;
+    assert_eq!(
+      api_key_expiry_alert_range(now, 7),
+      Some((now as i64, (now + 7 * ONE_DAY_MS) as i64))
+    );
+  }
+
+  #[test]
+  fn expiring_alert_is_resolved_warning() {
+    let key = ApiKey {
+      name: String::from("ci"),
+      key: String::from("K-key"),
+      user_id: String::from("user-id"),
+      expires: 2_000,
+      ..Default::default()
+    };
+    let alert =
+      api_key_expiring_alert(&key, String::from("user"), 1_000);
+    assert!(alert.resolved);
+    assert_eq!(alert.resolved_ts, Some(1_000));
+    assert_eq!(alert.level, SeverityLevel::Warning);
+    assert_eq!(alert.target, ResourceTarget::system());
+    let AlertData::ApiKeyExpiring {
+      name,
+      user_id,
+      username,
+      expires,
+    } = alert.data
+    else {
+      panic!("Expected ApiKeyExpiring alert");
+    };
+    assert_eq!(name, "ci");
+    assert_eq!(user_id, "user-id");
+    assert_eq!(username, "user");
+    assert_eq!(expires, 2_000);
+  }
+}
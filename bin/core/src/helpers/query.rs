@@ -21,13 +21,16 @@ use komodo_client::{
     alerter::Alerter,
     build::Build,
     builder::Builder,
+    change_freeze::ChangeFreeze,
     deployment::{Deployment, DeploymentState},
     docker::container::{
       ContainerListItem, ContainerStateStatusEnum,
     },
+    komodo_timestamp,
     permission::{PermissionLevel, PermissionLevelAndSpecifics},
     procedure::{Procedure, ProcedureState},
     repo::Repo,
+    resource::Resource,
     server::{Server, ServerState},
     stack::{Stack, StackServiceNames, StackState},
     stats::SystemInformation,
@@ -363,6 +366,17 @@ pub async fn get_variable(name: &str) -> anyhow::Result<Variable> {
     })
 }
 
+/// The change freeze, if one is in effect.
+pub async fn get_active_change_freeze()
+-> anyhow::Result<Option<ChangeFreeze>> {
+  let freeze = db_client()
+    .change_freeze
+    .find_one(Document::new())
+    .await
+    .context("failed to query db for change freeze")?;
+  Ok(freeze.filter(|freeze| freeze.is_active(komodo_timestamp())))
+}
+
 pub async fn get_latest_update(
   resource_type: ResourceTargetVariant,
   id: &str,
@@ -413,6 +427,105 @@ pub async fn get_variables_and_secrets()
   Ok(VariablesAndSecrets { variables, secrets })
 }
 
+/// Lists the resources which reference the variable / secret
+/// as `[[NAME]]` in their config, and the user has the permissions on.
+pub async fn get_variable_references(
+  name: &str,
+  user: &User,
+) -> anyhow::Result<Vec<ResourceTarget>> {
+  let level = PermissionLevel::Read;
+  let (deployments, stacks, builds, repos, actions, alerters) = tokio::try_join!(
+    list_variable_references::<Deployment>(name, user, level),
+    list_variable_references::<Stack>(name, user, level),
+    list_variable_references::<Build>(name, user, level),
+    list_variable_references::<Repo>(name, user, level),
+    list_variable_references::<Action>(name, user, level),
+    list_variable_references::<Alerter>(name, user, level),
+  )?;
+  let targets = deployments
+    .into_iter()
+    .map(|d| ResourceTarget::Deployment(d.id))
+    .chain(stacks.into_iter().map(|s| ResourceTarget::Stack(s.id)))
+    .chain(builds.into_iter().map(|b| ResourceTarget::Build(b.id)))
+    .chain(repos.into_iter().map(|r| ResourceTarget::Repo(r.id)))
+    .chain(actions.into_iter().map(|a| ResourceTarget::Action(a.id)))
+    .chain(
+      alerters.into_iter().map(|a| ResourceTarget::Alerter(a.id)),
+    )
+    .collect();
+  Ok(targets)
+}
+
+/// The names of the running Deployments and Stacks referencing
+/// the variable / secret, which need a redeploy to pick up a new value.
+pub async fn get_deployed_variable_references(
+  name: &str,
+  user: &User,
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+  let level = PermissionLevel::Execute;
+  let (deployments, stacks) = tokio::try_join!(
+    list_variable_references::<Deployment>(name, user, level),
+    list_variable_references::<Stack>(name, user, level),
+  )?;
+  let mut deployment_names = Vec::new();
+  for deployment in deployments {
+    if get_deployment_state(&deployment.id).await?
+      == DeploymentState::Running
+    {
+      deployment_names.push(deployment.name);
+    }
+  }
+  let mut stack_names = Vec::new();
+  for stack in stacks {
+    let state = stack_status_cache()
+      .get(&stack.id)
+      .await
+      .map(|status| status.curr.state)
+      .unwrap_or_default();
+    if matches!(state, StackState::Running | StackState::Unhealthy) {
+      stack_names.push(stack.name);
+    }
+  }
+  Ok((deployment_names, stack_names))
+}
+
+async fn list_variable_references<T: KomodoResource>(
+  name: &str,
+  user: &User,
+  level: PermissionLevel,
+) -> anyhow::Result<Vec<Resource<T::Config, T::Info>>> {
+  let reference = format!("[[{name}]]");
+  let resources = resource::list_full_for_user::<T>(
+    Default::default(),
+    user,
+    level.into(),
+    &[],
+  )
+  .await?
+  .into_iter()
+  .filter(|resource| {
+    serde_json::to_value(&resource.config)
+      .map(|config| references_variable(&config, &reference))
+      .unwrap_or_default()
+  })
+  .collect();
+  Ok(resources)
+}
+
+/// Whether the serialized config contains the `[[NAME]]` reference
+/// and will actually interpolate it.
+fn references_variable(
+  config: &serde_json::Value,
+  reference: &str,
+) -> bool {
+  // These resources are deployed with the literal value.
+  let skip_secret_interp = config
+    .get("skip_secret_interp")
+    .and_then(serde_json::Value::as_bool)
+    .unwrap_or_default();
+  !skip_secret_interp && config.to_string().contains(reference)
+}
+
 // This protects the peripheries from spam requests
 const SYSTEM_INFO_EXPIRY: u128 = ONE_MIN_MS;
 type SystemInfoCache =
@@ -498,3 +611,36 @@ pub async fn get_procedure_state(id: &String) -> ProcedureState {
   }
   procedure_state_cache().get(id).await.unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn finds_reference_in_nested_config() {
+    let config = json!({
+      "image": "nginx",
+      "environment": "TOKEN=[[API_TOKEN]]",
+      "skip_secret_interp": false,
+    });
+    assert!(references_variable(&config, "[[API_TOKEN]]"));
+    assert!(!references_variable(&config, "[[API]]"));
+  }
+
+  #[test]
+  fn skip_secret_interp_is_not_a_reference() {
+    let config = json!({
+      "environment": "TOKEN=[[API_TOKEN]]",
+      "skip_secret_interp": true,
+    });
+    assert!(!references_variable(&config, "[[API_TOKEN]]"));
+  }
+
+  #[test]
+  fn bare_name_is_not_a_reference() {
+    let config = json!({ "environment": "API_TOKEN=abc" });
+    assert!(!references_variable(&config, "[[API_TOKEN]]"));
+  }
+}
@@ -0,0 +1,131 @@
+use std::{
+  collections::HashSet,
+  sync::{
+    Mutex, OnceLock,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::Duration,
+};
+
+use anyhow::Context;
+use database::mungos::by_id::find_one_by_id;
+use komodo_client::entities::update::Log;
+
+use crate::{config::core_config, metrics, state::db_client};
+
+use super::update::update_update;
+
+/// How often to check whether the in flight executions have finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Whether Core is shutting down,
+/// in which case new executions are rejected.
+pub fn shutting_down() -> bool {
+  SHUTTING_DOWN.load(Ordering::Relaxed)
+}
+
+/// The update ids of the executions currently running.
+fn in_flight() -> &'static Mutex<HashSet<String>> {
+  static IN_FLIGHT: OnceLock<Mutex<HashSet<String>>> =
+    OnceLock::new();
+  IN_FLIGHT.get_or_init(Default::default)
+}
+
+/// Tracks an execution as in flight until its update is finalized,
+/// so shutdown can wait for it. Called when the update is created,
+/// which covers every execution path, not just the execute api.
+pub fn start_execution(update_id: &str) {
+  if !update_id.is_empty()
+    && in_flight().lock().unwrap().insert(update_id.to_string())
+  {
+    metrics::execution_started();
+  }
+}
+
+/// Called when an update is finalized.
+/// Returns whether the update was an in flight execution.
+pub fn finish_execution(update_id: &str) -> bool {
+  let finished = in_flight().lock().unwrap().remove(update_id);
+  if finished {
+    metrics::execution_finished();
+  }
+  finished
+}
+
+/// Stops accepting new executions, and waits up to
+/// `shutdown_timeout_seconds` for the in flight ones to finish.
+/// The updates of any which don't, including executions which
+/// errored without finalizing their update, are finalized as interrupted.
+pub async fn drain_executions() {
+  SHUTTING_DOWN.store(true, Ordering::Relaxed);
+  let timeout =
+    Duration::from_secs(core_config().shutdown_timeout_seconds);
+  let drained = tokio::time::timeout(timeout, async {
+    while !in_flight().lock().unwrap().is_empty() {
+      tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+  })
+  .await;
+  if drained.is_ok() {
+    return;
+  }
+  // Finalizing the update removes it from in flight.
+  let interrupted = in_flight()
+    .lock()
+    .unwrap()
+    .iter()
+    .cloned()
+    .collect::<Vec<_>>();
+  warn!(
+    "{} executions did not finish before shutdown, marking interrupted",
+    interrupted.len()
+  );
+  for update_id in interrupted {
+    if let Err(e) = mark_interrupted(&update_id).await {
+      warn!(
+        "Failed to mark update {update_id} as interrupted | {e:#}"
+      );
+    }
+  }
+}
+
+async fn mark_interrupted(update_id: &str) -> anyhow::Result<()> {
+  let mut update = find_one_by_id(&db_client().updates, update_id)
+    .await
+    .context("Failed to query to db")?
+    .context("No update exists with given id")?;
+  update.logs.push(Log::error(
+    "Interrupted",
+    String::from(
+      "Komodo Core shut down before the execution finished.",
+    ),
+  ));
+  update.finalize();
+  update_update(update).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn tracks_execution_until_finished() {
+    start_execution("shutdown-test-1");
+    assert!(in_flight().lock().unwrap().contains("shutdown-test-1"));
+    assert!(finish_execution("shutdown-test-1"));
+    assert!(!finish_execution("shutdown-test-1"));
+  }
+
+  #[test]
+  fn ignores_empty_update_id() {
+    start_execution("");
+    assert!(!finish_execution(""));
+  }
+
+  #[test]
+  fn finishing_untracked_update() {
+    assert!(!finish_execution("shutdown-test-untracked"));
+  }
+}
@@ -1,4 +1,4 @@
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use database::mungos::{
   by_id::{find_one_by_id, update_one_by_id},
   mongodb::bson::to_document,
@@ -15,15 +15,21 @@ use komodo_client::entities::{
   server::Server,
   stack::Stack,
   sync::ResourceSync,
-  update::{Update, UpdateListItem},
+  update::{Update, UpdateListItem, UpdateStatus},
   user::User,
 };
 
 use crate::{
-  api::execute::ExecuteRequest, resource, state::db_client,
+  api::execute::ExecuteRequest, config::core_config, resource,
+  state::db_client,
 };
 
-use super::channel::update_channel;
+use super::{
+  channel::update_channel,
+  query::get_active_change_freeze,
+  shutdown::{finish_execution, shutting_down, start_execution},
+  webhook::send_execution_webhooks,
+};
 
 pub fn make_update(
   target: impl Into<ResourceTarget>,
@@ -44,6 +50,7 @@ pub fn make_update(
 pub async fn add_update(
   mut update: Update,
 ) -> anyhow::Result<String> {
+  truncate_update_logs(&mut update);
   update.id = db_client()
     .updates
     .insert_one(&update)
@@ -76,15 +83,31 @@ pub async fn add_update_without_send(
 }
 
 #[instrument(level = "debug")]
-pub async fn update_update(update: Update) -> anyhow::Result<()> {
+pub async fn update_update(mut update: Update) -> anyhow::Result<()> {
+  truncate_update_logs(&mut update);
   update_one_by_id(&db_client().updates, &update.id, database::mungos::update::Update::Set(to_document(&update)?), None)
     .await
     .context("failed to update the update on db. the update build process was deleted")?;
+  // Every execution finalizes its update here,
+  // however it was started.
+  if update.status == UpdateStatus::Complete
+    && finish_execution(&update.id)
+  {
+    send_execution_webhooks(&update);
+  }
   let update = update_list_item(update).await?;
   let _ = send_update(update).await;
   Ok(())
 }
 
+/// Bounds the size of the update logs to `max_update_log_bytes`.
+pub fn truncate_update_logs(update: &mut Update) {
+  let max_bytes = core_config().max_update_log_bytes;
+  if max_bytes > 0 {
+    update.truncate_logs(max_bytes as usize);
+  }
+}
+
 #[instrument(level = "debug")]
 async fn update_list_item(
   update: Update,
@@ -110,6 +133,7 @@ async fn update_list_item(
     status: update.status,
     version: update.version,
     other_data: update.other_data,
+    progress: update.progress,
     username,
   };
   Ok(update)
@@ -121,10 +145,31 @@ async fn send_update(update: UpdateListItem) -> anyhow::Result<()> {
   Ok(())
 }
 
+/// Every execution, whether from the api, a schedule, a webhook,
+/// a sync or a procedure, creates its update here,
+/// so this is where shutdown and the change freeze are enforced,
+/// and where the execution is tracked as in flight.
 pub async fn init_execution_update(
   request: &ExecuteRequest,
   user: &User,
 ) -> anyhow::Result<Update> {
+  if shutting_down() {
+    return Err(anyhow!(
+      "Komodo Core is shutting down, not accepting new executions"
+    ));
+  }
+
+  // Super admins can still execute during a change freeze,
+  // eg to roll out an incident fix.
+  if !user.super_admin
+    && let Some(freeze) = get_active_change_freeze().await?
+  {
+    return Err(anyhow!(
+      "Changes are frozen, only super admins can run executions | reason: {}",
+      freeze.reason
+    ));
+  }
+
   let (operation, target) = match &request {
     // Server
     ExecuteRequest::StartContainer(data) => (
@@ -264,6 +309,12 @@ pub async fn init_execution_update(
     ExecuteRequest::BatchDeploy(_data) => {
       return Ok(Default::default());
     }
+    ExecuteRequest::ReconcileDeployment(data) => (
+      Operation::ReconcileDeployment,
+      ResourceTarget::Deployment(
+        resource::get::<Deployment>(&data.deployment).await?.id,
+      ),
+    ),
     ExecuteRequest::PullDeployment(data) => (
       Operation::PullDeployment,
       ResourceTarget::Deployment(
@@ -520,8 +571,13 @@ pub async fn init_execution_update(
     ExecuteRequest::GlobalAutoUpdate(_data) => {
       (Operation::GlobalAutoUpdate, ResourceTarget::system())
     }
+    ExecuteRequest::RedeployVariableReferences(_data) => {
+      return Ok(Default::default());
+    }
   };
 
+  resource::check_not_locked(&target).await?;
+
   let mut update = make_update(target, operation, user);
   update.in_progress();
 
@@ -529,6 +585,7 @@ pub async fn init_execution_update(
   if !matches!(&request, ExecuteRequest::DeployStackIfChanged(_)) {
     // Don't actually send it here, let the handlers send it after they can set action state.
     update.id = add_update_without_send(&update).await?;
+    start_execution(&update.id);
   }
 
   Ok(update)
@@ -0,0 +1,157 @@
+use std::{sync::OnceLock, time::Duration};
+
+use anyhow::Context;
+use hex::ToHex;
+use hmac::{Hmac, Mac};
+use komodo_client::entities::{
+  config::core::ExecutionWebhookConfig, update::Update,
+};
+use sha2::Sha256;
+
+use crate::config::core_config;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNATURE_HEADER: &str = "X-Komodo-Signature-256";
+
+fn webhook_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(10))
+      .build()
+      .expect("Invalid execution webhook client configuration")
+  })
+}
+
+/// Calls the configured `execution_webhooks` matching
+/// the completed execution Update, in the background.
+pub fn send_execution_webhooks(update: &Update) {
+  let webhooks = core_config()
+    .execution_webhooks
+    .iter()
+    .filter(|webhook| webhook_matches(webhook, update))
+    .collect::<Vec<_>>();
+  if webhooks.is_empty() {
+    return;
+  }
+  let body = match serde_json::to_string(update) {
+    Ok(body) => body,
+    Err(e) => {
+      warn!(
+        "Failed to serialize Update for execution webhooks | {e:?}"
+      );
+      return;
+    }
+  };
+  for webhook in webhooks {
+    let body = body.clone();
+    tokio::spawn(async move {
+      if let Err(e) = send_execution_webhook(webhook, body).await {
+        warn!(
+          "Failed to send execution webhook | url: {} | {e:#}",
+          webhook.url
+        );
+      }
+    });
+  }
+}
+
+fn webhook_matches(
+  webhook: &ExecutionWebhookConfig,
+  update: &Update,
+) -> bool {
+  (webhook.operations.is_empty()
+    || webhook.operations.contains(&update.operation))
+    && (!webhook.failures_only || !update.success)
+}
+
+async fn send_execution_webhook(
+  webhook: &ExecutionWebhookConfig,
+  body: String,
+) -> anyhow::Result<()> {
+  let mut req = webhook_client()
+    .post(&webhook.url)
+    .header("Content-Type", "application/json");
+  if !webhook.secret.is_empty() {
+    let signature = sign_body(&webhook.secret, &body)?;
+    req = req.header(SIGNATURE_HEADER, format!("sha256={signature}"));
+  }
+  req
+    .body(body)
+    .send()
+    .await
+    .context("Failed to reach webhook url")?
+    .error_for_status()
+    .context("Webhook url returned error status")?;
+  Ok(())
+}
+
+/// The hex encoded HMAC SHA256 of the body.
+fn sign_body(secret: &str, body: &str) -> anyhow::Result<String> {
+  let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+    .context("Failed to create hmac sha256 from secret")?;
+  mac.update(body.as_bytes());
+  Ok(mac.finalize().into_bytes().encode_hex())
+}
+
+#[cfg(test)]
+mod tests {
+  use komodo_client::entities::Operation;
+
+  use super::*;
+
+  fn update(operation: Operation, success: bool) -> Update {
+    Update {
+      operation,
+      success,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn webhook_matches_operations() {
+    let webhook = ExecutionWebhookConfig {
+      operations: vec![Operation::DeployStack],
+      ..Default::default()
+    };
+    assert!(webhook_matches(
+      &webhook,
+      &update(Operation::DeployStack, true)
+    ));
+    assert!(!webhook_matches(
+      &webhook,
+      &update(Operation::RunBuild, true)
+    ));
+    let all = ExecutionWebhookConfig::default();
+    assert!(webhook_matches(
+      &all,
+      &update(Operation::RunBuild, true)
+    ));
+  }
+
+  #[test]
+  fn webhook_matches_failures_only() {
+    let webhook = ExecutionWebhookConfig {
+      failures_only: true,
+      ..Default::default()
+    };
+    assert!(webhook_matches(
+      &webhook,
+      &update(Operation::RunBuild, false)
+    ));
+    assert!(!webhook_matches(
+      &webhook,
+      &update(Operation::RunBuild, true)
+    ));
+  }
+
+  #[test]
+  fn body_signed_with_hmac_sha256() {
+    // RFC 4231 test case 2
+    assert_eq!(
+      sign_body("Jefe", "what do ya want for nothing?").unwrap(),
+      "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+  }
+}
@@ -1,14 +1,16 @@
 #[macro_use]
 extern crate tracing;
 
-use std::{net::SocketAddr, str::FromStr};
+use std::{net::SocketAddr, str::FromStr, time::Duration};
 
 use anyhow::Context;
-use axum::Router;
+use axum::{Router, extract::DefaultBodyLimit, middleware};
 use axum_server::{Handle, tls_rustls::RustlsConfig};
 use tower_http::{
   cors::{Any, CorsLayer},
+  limit::RequestBodyLimitLayer,
   services::{ServeDir, ServeFile},
+  timeout::TimeoutLayer,
 };
 
 use crate::config::core_config;
@@ -20,6 +22,7 @@ mod cloud;
 mod config;
 mod helpers;
 mod listener;
+mod metrics;
 mod monitor;
 mod network;
 mod permission;
@@ -55,6 +58,8 @@ async fn app() -> anyhow::Result<()> {
     (false, false) => info!("{:?}", config.sanitized()),
   }
 
+  // Register metrics observers before any clients are created
+  metrics::init();
   // Init jwt client to crash on failure
   state::jwt_client();
   tokio::join!(
@@ -68,6 +73,8 @@ async fn app() -> anyhow::Result<()> {
 
   // Spawn background tasks
   monitor::spawn_monitor_loop();
+  monitor::spawn_container_event_listeners();
+  monitor::spawn_deployment_drift_check_loop();
   resource::spawn_resource_refresh_loop();
   resource::spawn_all_resources_cache_refresh_loop();
   resource::spawn_build_state_refresh_loop();
@@ -84,16 +91,38 @@ async fn app() -> anyhow::Result<()> {
   let serve_frontend = ServeDir::new(frontend_path)
     .not_found_service(frontend_index.clone());
 
-  let app = Router::new()
+  // The websocket routes (terminal, ws) and the
+  // event stream (events) are long lived,
+  // so are exempt from the body limit and timeout.
+  let api_routes = Router::new()
     .nest("/auth", api::auth::router())
     .nest("/user", api::user::router())
     .nest("/read", api::read::router())
-    .nest("/write", api::write::router())
-    .nest("/execute", api::execute::router())
+    .nest(
+      "/write",
+      api::write::router()
+        .layer(middleware::from_fn(api::ip::guard_request_by_ip)),
+    )
+    .nest(
+      "/execute",
+      api::execute::router()
+        .layer(middleware::from_fn(api::ip::guard_request_by_ip)),
+    )
+    .nest("/listener", listener::router());
+  let api_routes = limit_requests(
+    api_routes,
+    config.max_request_body_bytes,
+    Duration::from_secs(config.request_timeout_seconds),
+  )
+  .layer(middleware::from_fn(api::api_version));
+
+  let app = Router::new()
+    .merge(api_routes)
     .nest("/terminal", api::terminal::router())
-    .nest("/listener", listener::router())
+    .nest("/events", api::events::router())
     .nest("/ws", ws::router())
     .nest("/client", ts_client::router())
+    .nest("/metrics", metrics::router())
     .fallback_service(serve_frontend)
     .layer(
       CorsLayer::new()
@@ -101,7 +130,7 @@ async fn app() -> anyhow::Result<()> {
         .allow_methods(Any)
         .allow_headers(Any),
     )
-    .into_make_service();
+    .into_make_service_with_connect_info::<SocketAddr>();
 
   let addr =
     format!("{}:{}", core_config().bind_ip, core_config().port);
@@ -148,6 +177,19 @@ async fn app() -> anyhow::Result<()> {
   }
 }
 
+/// Applies the configurable body limit and timeout to the routes.
+fn limit_requests(
+  router: Router,
+  max_body_bytes: usize,
+  timeout: Duration,
+) -> Router {
+  router
+    // Replaced by the configurable limit
+    .layer(DefaultBodyLimit::disable())
+    .layer(RequestBodyLimitLayer::new(max_body_bytes))
+    .layer(TimeoutLayer::new(timeout))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
   let mut term_signal = tokio::signal::unix::signal(
@@ -155,6 +197,60 @@ async fn main() -> anyhow::Result<()> {
   )?;
   tokio::select! {
     res = tokio::spawn(app()) => res?,
-    _ = term_signal.recv() => Ok(()),
+    _ = term_signal.recv() => {
+      info!("Received SIGTERM, draining executions before shutdown");
+      helpers::shutdown::drain_executions().await;
+      Ok(())
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+  };
+  use tower::ServiceExt;
+
+  use super::*;
+
+  fn router() -> Router {
+    let router = Router::new()
+      .route("/echo", post(|body: String| async move { body }))
+      .route(
+        "/slow",
+        post(|| async {
+          tokio::time::sleep(Duration::from_secs(5)).await;
+        }),
+      );
+    limit_requests(router, 8, Duration::from_millis(50))
+  }
+
+  async fn status(path: &str, body: &'static str) -> StatusCode {
+    let request = Request::post(path).body(Body::from(body)).unwrap();
+    router().oneshot(request).await.unwrap().status()
+  }
+
+  #[tokio::test]
+  async fn accepts_body_within_limit() {
+    assert_eq!(status("/echo", "12345678").await, StatusCode::OK);
+  }
+
+  #[tokio::test]
+  async fn rejects_body_over_limit() {
+    assert_eq!(
+      status("/echo", "123456789").await,
+      StatusCode::PAYLOAD_TOO_LARGE
+    );
+  }
+
+  #[tokio::test]
+  async fn times_out_slow_requests() {
+    assert_eq!(
+      status("/slow", "").await,
+      StatusCode::REQUEST_TIMEOUT
+    );
   }
 }
@@ -0,0 +1,532 @@
+//! Prometheus metrics, scraped at `/metrics`
+//! with the configured `metrics_token` as bearer token.
+
+use std::{
+  collections::BTreeMap,
+  fmt::Write,
+  sync::{
+    Mutex, OnceLock,
+    atomic::{AtomicI64, AtomicU64, Ordering},
+  },
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use axum::{
+  Router,
+  http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+  },
+  routing::get,
+};
+use komodo_client::entities::{
+  komodo_timestamp, server::ServerState,
+};
+use opentelemetry::{
+  KeyValue, global,
+  metrics::{
+    Counter, Histogram as OtelHistogram, ObservableGauge,
+    UpDownCounter,
+  },
+};
+use serror::AddStatusCode;
+
+use crate::{config::core_config, state::server_status_cache};
+
+pub fn router() -> Router {
+  Router::new().route("/", get(scrape))
+}
+
+/// Call before the periphery client is used.
+/// Call after the logger is initialized,
+/// so the OpenTelemetry instruments use the configured meter provider.
+pub fn init() {
+  otel();
+  periphery_client::set_request_observer(observe_periphery_request);
+}
+
+async fn scrape(
+  headers: HeaderMap,
+) -> serror::Result<(HeaderMap, String)> {
+  check_metrics_token(&headers)?;
+  let mut headers = HeaderMap::new();
+  headers.insert(
+    CONTENT_TYPE,
+    HeaderValue::from_static("text/plain; version=0.0.4"),
+  );
+  Ok((headers, render()))
+}
+
+/// Metrics are disabled unless `metrics_token` is configured.
+fn check_metrics_token(headers: &HeaderMap) -> serror::Result<()> {
+  let metrics_token = &core_config().metrics_token;
+  if metrics_token.is_empty() {
+    return Err(
+      anyhow!(
+        "Metrics are disabled. Configure 'metrics_token' to enable."
+      )
+      .status_code(StatusCode::NOT_FOUND),
+    );
+  }
+  let Some(req_token) = headers.get(AUTHORIZATION) else {
+    return Err(
+      anyhow!("Request was not sent with metrics token")
+        .status_code(StatusCode::UNAUTHORIZED),
+    );
+  };
+  let req_token = req_token
+    .to_str()
+    .context("Failed to convert metrics token to str")
+    .status_code(StatusCode::UNAUTHORIZED)?;
+  if req_token.strip_prefix("Bearer ") == Some(metrics_token.as_str())
+  {
+    Ok(())
+  } else {
+    Err(
+      anyhow!("Request metrics token invalid")
+        .status_code(StatusCode::UNAUTHORIZED),
+    )
+  }
+}
+
+fn render() -> String {
+  let mut out = String::new();
+
+  let servers = servers();
+  write_header(
+    &mut out,
+    "komodo_servers_connected",
+    "gauge",
+    "Servers which responded to the last status check.",
+  );
+  let _ = writeln!(
+    out,
+    "komodo_servers_connected {}",
+    servers.connected.load(Ordering::Relaxed)
+  );
+  write_header(
+    &mut out,
+    "komodo_servers_total",
+    "gauge",
+    "All servers known to Core.",
+  );
+  let _ = writeln!(
+    out,
+    "komodo_servers_total {}",
+    servers.total.load(Ordering::Relaxed)
+  );
+
+  let executions = executions();
+  write_header(
+    &mut out,
+    "komodo_executions_in_flight",
+    "gauge",
+    "Executions currently running.",
+  );
+  let _ = writeln!(
+    out,
+    "komodo_executions_in_flight {}",
+    executions.in_flight.load(Ordering::Relaxed)
+  );
+  write_header(
+    &mut out,
+    "komodo_executions_total",
+    "counter",
+    "Executions started since Core started.",
+  );
+  let _ = writeln!(
+    out,
+    "komodo_executions_total {}",
+    executions.total.load(Ordering::Relaxed)
+  );
+
+  write_histograms(
+    &mut out,
+    "komodo_periphery_request_duration_seconds",
+    "Duration of requests from Core to Periphery.",
+    "type",
+    periphery_requests(),
+  );
+
+  write_header(
+    &mut out,
+    "komodo_background_loop_heartbeat_timestamp_seconds",
+    "gauge",
+    "Last time each background loop completed an iteration.",
+  );
+  for (name, ts) in heartbeats().lock().unwrap().iter() {
+    let _ = writeln!(
+      out,
+      "komodo_background_loop_heartbeat_timestamp_seconds{{loop=\"{}\"}} {}",
+      escape_label_value(name),
+      *ts as f64 / 1000.0
+    );
+  }
+
+  out
+}
+
+/// Escapes `\`, `"` and newlines in a label value,
+/// as required by the text exposition format.
+fn escape_label_value(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '\\' => escaped.push_str("\\\\"),
+      '"' => escaped.push_str("\\\""),
+      '\n' => escaped.push_str("\\n"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+fn write_header(
+  out: &mut String,
+  name: &str,
+  kind: &str,
+  help: &str,
+) {
+  let _ = writeln!(out, "# HELP {name} {help}");
+  let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+// =====================
+//  SERVERS
+// =====================
+
+#[derive(Default)]
+struct Servers {
+  connected: AtomicU64,
+  total: AtomicU64,
+}
+
+fn servers() -> &'static Servers {
+  static SERVERS: OnceLock<Servers> = OnceLock::new();
+  SERVERS.get_or_init(Default::default)
+}
+
+/// Updates the server counts from the status cache.
+pub async fn record_servers() {
+  let statuses = server_status_cache().get_list().await;
+  let connected = statuses
+    .iter()
+    .filter(|status| status.state == ServerState::Ok)
+    .count();
+  let servers = servers();
+  servers.connected.store(connected as u64, Ordering::Relaxed);
+  servers
+    .total
+    .store(statuses.len() as u64, Ordering::Relaxed);
+}
+
+// =====================
+//  EXECUTIONS
+// =====================
+
+#[derive(Default)]
+struct Executions {
+  in_flight: AtomicI64,
+  total: AtomicU64,
+}
+
+fn executions() -> &'static Executions {
+  static EXECUTIONS: OnceLock<Executions> = OnceLock::new();
+  EXECUTIONS.get_or_init(Default::default)
+}
+
+/// Counts the execution as in flight until [execution_finished].
+/// Called from the in flight tracking in `helpers::shutdown`,
+/// which covers every execution path.
+pub fn execution_started() {
+  let executions = executions();
+  executions.in_flight.fetch_add(1, Ordering::Relaxed);
+  executions.total.fetch_add(1, Ordering::Relaxed);
+  let otel = otel();
+  otel.in_flight.add(1, &[]);
+  otel.executions.add(1, &[]);
+}
+
+pub fn execution_finished() {
+  executions().in_flight.fetch_sub(1, Ordering::Relaxed);
+  otel().in_flight.add(-1, &[]);
+}
+
+// =====================
+//  LATENCY
+// =====================
+
+/// Upper bounds of the histogram buckets, in seconds.
+const BUCKETS: [f64; 12] = [
+  0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+#[derive(Default)]
+struct Histogram {
+  /// Non-cumulative count per bucket, the last is +Inf.
+  buckets: [u64; BUCKETS.len() + 1],
+  sum: f64,
+  count: u64,
+  errors: u64,
+}
+
+impl Histogram {
+  fn observe(&mut self, duration: Duration, success: bool) {
+    let secs = duration.as_secs_f64();
+    let bucket = BUCKETS
+      .iter()
+      .position(|bound| secs <= *bound)
+      .unwrap_or(BUCKETS.len());
+    self.buckets[bucket] += 1;
+    self.sum += secs;
+    self.count += 1;
+    if !success {
+      self.errors += 1;
+    }
+  }
+}
+
+type Histograms = Mutex<BTreeMap<String, Histogram>>;
+
+fn periphery_requests() -> &'static Histograms {
+  static PERIPHERY_REQUESTS: OnceLock<Histograms> = OnceLock::new();
+  PERIPHERY_REQUESTS.get_or_init(Default::default)
+}
+
+fn observe(
+  histograms: &Histograms,
+  label: &str,
+  duration: Duration,
+  success: bool,
+) {
+  let mut histograms = histograms.lock().unwrap();
+  match histograms.get_mut(label) {
+    Some(histogram) => histogram.observe(duration, success),
+    None => {
+      let mut histogram = Histogram::default();
+      histogram.observe(duration, success);
+      histograms.insert(label.to_string(), histogram);
+    }
+  }
+}
+
+fn observe_periphery_request(
+  req_type: &str,
+  duration: Duration,
+  success: bool,
+) {
+  observe(periphery_requests(), req_type, duration, success);
+  otel().periphery_requests.record(
+    duration.as_secs_f64(),
+    &[
+      KeyValue::new("type", req_type.to_string()),
+      KeyValue::new("success", success),
+    ],
+  );
+}
+
+fn write_histograms(
+  out: &mut String,
+  name: &str,
+  help: &str,
+  label: &str,
+  histograms: &Histograms,
+) {
+  write_header(out, name, "histogram", help);
+  let histograms = histograms.lock().unwrap();
+  for (value, histogram) in histograms.iter() {
+    let value = escape_label_value(value);
+    let mut cumulative = 0;
+    for (bound, count) in BUCKETS.iter().zip(&histogram.buckets) {
+      cumulative += count;
+      let _ = writeln!(
+        out,
+        "{name}_bucket{{{label}=\"{value}\",le=\"{bound}\"}} {cumulative}"
+      );
+    }
+    let _ = writeln!(
+      out,
+      "{name}_bucket{{{label}=\"{value}\",le=\"+Inf\"}} {}",
+      histogram.count
+    );
+    let _ = writeln!(
+      out,
+      "{name}_sum{{{label}=\"{value}\"}} {}",
+      histogram.sum
+    );
+    let _ = writeln!(
+      out,
+      "{name}_count{{{label}=\"{value}\"}} {}",
+      histogram.count
+    );
+  }
+  let errors = format!(
+    "{}_errors_total",
+    name.trim_end_matches("_duration_seconds")
+  );
+  write_header(out, &errors, "counter", "Failed operations.");
+  for (value, histogram) in histograms.iter() {
+    let _ = writeln!(
+      out,
+      "{errors}{{{label}=\"{}\"}} {}",
+      escape_label_value(value),
+      histogram.errors
+    );
+  }
+}
+
+// =====================
+//  HEARTBEATS
+// =====================
+
+fn heartbeats() -> &'static Mutex<BTreeMap<&'static str, i64>> {
+  static HEARTBEATS: OnceLock<Mutex<BTreeMap<&'static str, i64>>> =
+    OnceLock::new();
+  HEARTBEATS.get_or_init(Default::default)
+}
+
+/// Records that the background loop completed an iteration.
+pub fn heartbeat(name: &'static str) {
+  heartbeats()
+    .lock()
+    .unwrap()
+    .insert(name, komodo_timestamp());
+}
+
+// =====================
+//  OPENTELEMETRY
+// =====================
+
+/// Mirrors the metrics to OpenTelemetry.
+/// These are exported when `logging.otlp_endpoint` is configured,
+/// otherwise they are no-ops.
+struct OtelInstruments {
+  executions: Counter<u64>,
+  in_flight: UpDownCounter<i64>,
+  periphery_requests: OtelHistogram<f64>,
+  _servers_connected: ObservableGauge<u64>,
+  _servers_total: ObservableGauge<u64>,
+  _heartbeats: ObservableGauge<f64>,
+}
+
+fn otel() -> &'static OtelInstruments {
+  static OTEL: OnceLock<OtelInstruments> = OnceLock::new();
+  OTEL.get_or_init(|| {
+    let meter = global::meter("komodo_core");
+    OtelInstruments {
+      executions: meter
+        .u64_counter("komodo_executions")
+        .with_description("Executions started since Core started.")
+        .build(),
+      in_flight: meter
+        .i64_up_down_counter("komodo_executions_in_flight")
+        .with_description("Executions currently running.")
+        .build(),
+      periphery_requests: meter
+        .f64_histogram("komodo_periphery_request_duration")
+        .with_description(
+          "Duration of requests from Core to Periphery.",
+        )
+        .with_unit("s")
+        .build(),
+      _servers_connected: meter
+        .u64_observable_gauge("komodo_servers_connected")
+        .with_description(
+          "Servers which responded to the last status check.",
+        )
+        .with_callback(|observer| {
+          observer
+            .observe(servers().connected.load(Ordering::Relaxed), &[])
+        })
+        .build(),
+      _servers_total: meter
+        .u64_observable_gauge("komodo_servers_total")
+        .with_description("All servers known to Core.")
+        .with_callback(|observer| {
+          observer
+            .observe(servers().total.load(Ordering::Relaxed), &[])
+        })
+        .build(),
+      _heartbeats: meter
+        .f64_observable_gauge(
+          "komodo_background_loop_heartbeat_timestamp",
+        )
+        .with_description(
+          "Last time each background loop completed an iteration.",
+        )
+        .with_unit("s")
+        .with_callback(|observer| {
+          for (name, ts) in heartbeats().lock().unwrap().iter() {
+            observer.observe(
+              *ts as f64 / 1000.0,
+              &[KeyValue::new("loop", *name)],
+            );
+          }
+        })
+        .build(),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn escapes_label_values() {
+    assert_eq!(escape_label_value("GetStack"), "GetStack");
+    assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+  }
+
+  #[test]
+  fn writes_histogram_exposition() {
+    let histograms = Histograms::default();
+    observe(
+      &histograms,
+      "Get\"Stack",
+      Duration::from_millis(20),
+      true,
+    );
+    observe(
+      &histograms,
+      "Get\"Stack",
+      Duration::from_secs(60),
+      false,
+    );
+    let mut out = String::new();
+    write_histograms(
+      &mut out,
+      "test_duration_seconds",
+      "Test durations.",
+      "type",
+      &histograms,
+    );
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(
+      lines[0],
+      "# HELP test_duration_seconds Test durations."
+    );
+    assert_eq!(lines[1], "# TYPE test_duration_seconds histogram");
+    // Buckets are cumulative.
+    assert!(lines.contains(
+      &"test_duration_seconds_bucket{type=\"Get\\\"Stack\",le=\"0.01\"} 0"
+    ));
+    assert!(lines.contains(
+      &"test_duration_seconds_bucket{type=\"Get\\\"Stack\",le=\"0.025\"} 1"
+    ));
+    assert!(lines.contains(
+      &"test_duration_seconds_bucket{type=\"Get\\\"Stack\",le=\"30\"} 1"
+    ));
+    assert!(lines.contains(
+      &"test_duration_seconds_bucket{type=\"Get\\\"Stack\",le=\"+Inf\"} 2"
+    ));
+    assert!(lines.contains(
+      &"test_duration_seconds_count{type=\"Get\\\"Stack\"} 2"
+    ));
+    assert!(lines.contains(&"# TYPE test_errors_total counter"));
+    assert!(
+      lines.contains(&"test_errors_total{type=\"Get\\\"Stack\"} 1")
+    );
+  }
+}
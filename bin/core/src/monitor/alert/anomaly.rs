@@ -0,0 +1,63 @@
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  optional_string,
+};
+
+use crate::{
+  alert::send_alerts, helpers::maintenance::is_server_in_maintenance,
+  monitor::record::StatsAnomaly, state::db_client,
+};
+
+use super::get_all_servers_map;
+
+/// Sends the anomalies for servers with `send_anomaly_alerts` enabled.
+#[instrument(level = "debug")]
+pub async fn alert_stats_anomalies(
+  ts: i64,
+  anomalies: Vec<StatsAnomaly>,
+) {
+  let (servers, _) = match get_all_servers_map().await {
+    Ok(res) => res,
+    Err(e) => {
+      error!("{e:#?}");
+      return;
+    }
+  };
+  let alerts = anomalies
+    .into_iter()
+    .filter_map(|anomaly| {
+      let server = servers.get(&anomaly.server_id)?;
+      if !server.config.send_anomaly_alerts
+        || is_server_in_maintenance(server, ts)
+      {
+        return None;
+      }
+      Some(Alert {
+        id: Default::default(),
+        ts,
+        resolved: true,
+        resolved_ts: ts.into(),
+        level: SeverityLevel::Warning,
+        target: ResourceTarget::Server(server.id.clone()),
+        data: AlertData::ServerStatsAnomaly {
+          id: server.id.clone(),
+          name: server.name.clone(),
+          region: optional_string(&server.config.region),
+          metric: anomaly.metric.to_string(),
+          percentage: anomaly.percentage,
+          baseline: anomaly.baseline,
+          z_score: anomaly.z_score,
+        },
+      })
+    })
+    .collect::<Vec<_>>();
+  if alerts.is_empty() {
+    return;
+  }
+  send_alerts(&alerts).await;
+  let res = db_client().alerts.insert_many(alerts).await;
+  if let Err(e) = res {
+    error!("failed to record stats anomaly alerts to db | {e:#}");
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use komodo_client::entities::{
   ResourceTarget,
@@ -8,6 +8,7 @@ use komodo_client::entities::{
 
 use crate::{
   alert::send_alerts,
+  config::core_config,
   monitor::deployment_status_cache,
   resource,
   state::{action_states, db_client},
@@ -17,6 +18,7 @@ use crate::{
 pub async fn alert_deployments(
   ts: i64,
   server_names: &HashMap<String, String>,
+  servers_in_maintenance: &HashSet<String>,
 ) {
   let mut alerts = Vec::<Alert>::new();
   let action_states = action_states();
@@ -48,7 +50,8 @@ pub async fn alert_deployments(
       continue;
     }
 
-    if status.curr.state != prev {
+    let state_changed = status.curr.state != prev;
+    if state_changed || status.curr.crash_loop.is_some() {
       // send alert
       let Ok(deployment) =
         resource::get::<Deployment>(&status.curr.id)
@@ -59,24 +62,66 @@ pub async fn alert_deployments(
       else {
         continue;
       };
-      if !deployment.config.send_alerts {
+      if !deployment.config.send_alerts
+        || servers_in_maintenance
+          .contains(&deployment.config.server_id)
+      {
         continue;
       }
       let target: ResourceTarget = (&deployment).into();
-      let data = AlertData::ContainerStateChange {
-        id: status.curr.id.clone(),
-        name: deployment.name,
-        server_name: server_names
-          .get(&deployment.config.server_id)
-          .cloned()
-          .unwrap_or(String::from("unknown")),
-        server_id: deployment.config.server_id,
-        from: prev,
-        to: status.curr.state,
+      let server_name = server_names
+        .get(&deployment.config.server_id)
+        .cloned()
+        .unwrap_or(String::from("unknown"));
+      if let Some(restarts) = status.curr.crash_loop {
+        alerts.push(Alert {
+          id: Default::default(),
+          level: SeverityLevel::Critical,
+          resolved: true,
+          resolved_ts: ts.into(),
+          target: target.clone(),
+          data: AlertData::ContainerCrashLoop {
+            id: status.curr.id.clone(),
+            name: deployment.name.clone(),
+            server_name: server_name.clone(),
+            server_id: deployment.config.server_id.clone(),
+            restarts,
+            window: core_config().crash_loop_window.to_string(),
+          },
+          ts,
+        });
+      }
+      if !state_changed {
+        continue;
+      }
+      // OOM kills get their own, more severe alert
+      // in place of the state change.
+      let (level, data) = match status.curr.oom_killed {
+        Some(exit_code) => (
+          SeverityLevel::Critical,
+          AlertData::ContainerOomKilled {
+            id: status.curr.id.clone(),
+            name: deployment.name,
+            server_name,
+            server_id: deployment.config.server_id,
+            exit_code: exit_code.into(),
+          },
+        ),
+        None => (
+          SeverityLevel::Warning,
+          AlertData::ContainerStateChange {
+            id: status.curr.id.clone(),
+            name: deployment.name,
+            server_name,
+            server_id: deployment.config.server_id,
+            from: prev,
+            to: status.curr.state,
+          },
+        ),
       };
       let alert = Alert {
         id: Default::default(),
-        level: SeverityLevel::Warning,
+        level,
         resolved: true,
         resolved_ts: ts.into(),
         target,
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Context;
 use komodo_client::entities::{
@@ -6,8 +6,13 @@ use komodo_client::entities::{
   server::Server, user::User,
 };
 
-use crate::resource;
+use crate::{
+  helpers::maintenance::is_server_in_maintenance, resource,
+};
+
+pub use anomaly::alert_stats_anomalies;
 
+mod anomaly;
 mod deployment;
 mod server;
 mod stack;
@@ -23,10 +28,21 @@ pub async fn check_alerts(ts: i64) {
     }
   };
 
+  // Deployments and Stacks on these servers don't alert either.
+  let servers_in_maintenance = servers
+    .values()
+    .filter(|server| is_server_in_maintenance(server, ts))
+    .map(|server| server.id.clone())
+    .collect::<HashSet<_>>();
+
   tokio::join!(
     server::alert_servers(ts, servers),
-    deployment::alert_deployments(ts, &server_names),
-    stack::alert_stacks(ts, &server_names)
+    deployment::alert_deployments(
+      ts,
+      &server_names,
+      &servers_in_maintenance
+    ),
+    stack::alert_stacks(ts, &server_names, &servers_in_maintenance)
   );
 }
 
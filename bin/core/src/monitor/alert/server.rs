@@ -22,7 +22,7 @@ use komodo_client::entities::{
 
 use crate::{
   alert::send_alerts,
-  helpers::maintenance::is_in_maintenance,
+  helpers::maintenance::is_server_in_maintenance,
   state::{db_client, server_status_cache},
 };
 
@@ -33,7 +33,8 @@ type OpenDiskAlertMap = OpenAlertMap<PathBuf>;
 
 /// Alert buffer to prevent immediate alerts on transient issues
 struct AlertBuffer {
-  buffer: Mutex<HashMap<(String, AlertDataVariant), bool>>,
+  /// The consecutive polls each alert has been ready to open.
+  buffer: Mutex<HashMap<(String, AlertDataVariant), u32>>,
 }
 
 impl AlertBuffer {
@@ -48,14 +49,26 @@ impl AlertBuffer {
     &self,
     server_id: String,
     variant: AlertDataVariant,
+  ) -> bool {
+    self.ready_to_open_after(server_id, variant, 2)
+  }
+
+  /// Check if alert should be opened.
+  /// Requires `polls` consecutive calls to return true.
+  fn ready_to_open_after(
+    &self,
+    server_id: String,
+    variant: AlertDataVariant,
+    polls: u32,
   ) -> bool {
     let mut lock = self.buffer.lock().unwrap();
-    let ready = lock.entry((server_id, variant)).or_default();
-    if *ready {
-      *ready = false;
+    let key = (server_id, variant);
+    let count = lock.entry(key.clone()).or_default();
+    *count += 1;
+    if *count >= polls {
+      lock.remove(&key);
       true
     } else {
-      *ready = true;
       false
     }
   }
@@ -103,8 +116,7 @@ pub async fn alert_servers(
       .get(&ResourceTarget::Server(server_status.id.clone()));
 
     // Check if server is in maintenance mode
-    let in_maintenance =
-      is_in_maintenance(&server.config.maintenance_windows, ts);
+    let in_maintenance = is_server_in_maintenance(&server, ts);
 
     // ===================
     // SERVER HEALTH
@@ -114,11 +126,13 @@ pub async fn alert_servers(
     });
     match (server_status.state, health_alert) {
       (ServerState::NotOk, None) => {
-        // Only open unreachable alert if not in maintenance and buffer is ready
+        // Only open unreachable alert if not in maintenance and
+        // the server has been unreachable for enough consecutive polls.
         if !in_maintenance
-          && buffer.ready_to_open(
+          && buffer.ready_to_open_after(
             server_status.id.clone(),
             AlertDataVariant::ServerUnreachable,
+            server.config.unreachable_alert_polls,
           )
         {
           let alert = Alert {
@@ -729,3 +743,52 @@ async fn get_open_alerts()
 
   Ok((map, disk_map))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn opens_after_consecutive_polls() {
+    let buffer = AlertBuffer::new();
+    let variant = AlertDataVariant::ServerUnreachable;
+    for _ in 0..2 {
+      assert!(!buffer.ready_to_open_after(
+        String::from("server"),
+        variant,
+        3
+      ));
+    }
+    assert!(buffer.ready_to_open_after(
+      String::from("server"),
+      variant,
+      3
+    ));
+    // The count starts over once opened.
+    assert!(!buffer.ready_to_open_after(
+      String::from("server"),
+      variant,
+      3
+    ));
+  }
+
+  #[test]
+  fn opens_immediately_with_one_poll() {
+    let buffer = AlertBuffer::new();
+    assert!(buffer.ready_to_open_after(
+      String::from("server"),
+      AlertDataVariant::ServerUnreachable,
+      1
+    ));
+  }
+
+  #[test]
+  fn reset_restarts_count() {
+    let buffer = AlertBuffer::new();
+    let variant = AlertDataVariant::ServerUnreachable;
+    assert!(!buffer.ready_to_open(String::from("server"), variant));
+    buffer.reset(String::from("server"), variant);
+    assert!(!buffer.ready_to_open(String::from("server"), variant));
+    assert!(buffer.ready_to_open(String::from("server"), variant));
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use komodo_client::entities::{
   ResourceTarget,
@@ -16,6 +16,7 @@ use crate::{
 pub async fn alert_stacks(
   ts: i64,
   server_names: &HashMap<String, String>,
+  servers_in_maintenance: &HashSet<String>,
 ) {
   let action_states = action_states();
   let mut alerts = Vec::<Alert>::new();
@@ -56,7 +57,9 @@ pub async fn alert_stacks(
       else {
         continue;
       };
-      if !stack.config.send_alerts {
+      if !stack.config.send_alerts
+        || servers_in_maintenance.contains(&stack.config.server_id)
+      {
         continue;
       }
       let target: ResourceTarget = (&stack).into();
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_timing_util::{Timelength, get_timelength_in_ms};
+use database::mungos::find::find_collect;
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  deployment::{Deployment, DeploymentState},
+  komodo_timestamp,
+  server::Server,
+};
+use periphery_client::api::container::InspectContainer;
+
+use crate::{
+  alert::send_alerts,
+  config::core_config,
+  helpers::{
+    drift::deployment_config_drift,
+    maintenance::is_server_in_maintenance, periphery_client,
+  },
+  state::{
+    db_client, deployment_drift_cache, deployment_status_cache,
+  },
+};
+
+/// Inspects the running Deployment containers on the resource poll interval,
+/// recording how they differ from their config, eg after a manual `docker update`.
+pub fn spawn_deployment_drift_check_loop() {
+  let interval: Timelength = core_config()
+    .resource_poll_interval
+    .try_into()
+    .expect("Invalid resource poll interval");
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_millis(
+      get_timelength_in_ms(interval) as u64,
+    ));
+    loop {
+      interval.tick().await;
+      check_deployment_drift().await;
+    }
+  });
+}
+
+async fn check_deployment_drift() {
+  let deployments = match find_collect(
+    &db_client().deployments,
+    None,
+    None,
+  )
+  .await
+  {
+    Ok(deployments) => deployments,
+    Err(e) => {
+      error!(
+        "Failed to get Deployments from database (drift check) | {e:#}"
+      );
+      return;
+    }
+  };
+  let servers =
+    match find_collect(&db_client().servers, None, None).await {
+      Ok(servers) => servers
+        .into_iter()
+        .map(|server| (server.id.clone(), server))
+        .collect::<HashMap<_, _>>(),
+      Err(e) => {
+        error!(
+          "Failed to get Servers from database (drift check) | {e:#}"
+        );
+        return;
+      }
+    };
+  let ts = komodo_timestamp();
+  let mut alerts = Vec::new();
+  for deployment in deployments {
+    let running = deployment_status_cache()
+      .get(&deployment.id)
+      .await
+      .map(|status| status.curr.state == DeploymentState::Running)
+      .unwrap_or_default();
+    let Some(server) = servers.get(&deployment.config.server_id)
+    else {
+      continue;
+    };
+    if !running {
+      deployment_drift_cache()
+        .insert(deployment.id.clone(), Vec::new())
+        .await;
+      continue;
+    }
+    let Some(drift) = deployment_drift(server, &deployment).await
+    else {
+      continue;
+    };
+    let prev = deployment_drift_cache()
+      .get(&deployment.id)
+      .await
+      .unwrap_or_default();
+    if !drift.is_empty()
+      && drift != prev
+      && core_config().deployment_drift_alerts
+      && deployment.config.send_alerts
+      && !is_server_in_maintenance(server, ts)
+    {
+      alerts.push(Alert {
+        id: Default::default(),
+        ts,
+        resolved: true,
+        resolved_ts: ts.into(),
+        level: SeverityLevel::Warning,
+        target: ResourceTarget::Deployment(deployment.id.clone()),
+        data: AlertData::DeploymentConfigDrift {
+          id: deployment.id.clone(),
+          name: deployment.name.clone(),
+          server_id: server.id.clone(),
+          server_name: server.name.clone(),
+          drift: drift.clone(),
+        },
+      });
+    }
+    deployment_drift_cache().insert(deployment.id, drift).await;
+  }
+  if alerts.is_empty() {
+    return;
+  }
+  send_alerts(&alerts).await;
+  let res = db_client().alerts.insert_many(alerts).await;
+  if let Err(e) = res {
+    error!("Failed to record deployment drift alerts to db | {e:#}");
+  }
+}
+
+async fn deployment_drift(
+  server: &Server,
+  deployment: &Deployment,
+) -> Option<Vec<String>> {
+  let res = match periphery_client(server) {
+    Ok(periphery) => {
+      periphery
+        .request(InspectContainer {
+          name: deployment.name.clone(),
+        })
+        .await
+    }
+    Err(e) => Err(e),
+  };
+  let container = res
+    .inspect_err(|e| {
+      warn!(
+        "Failed to inspect container {} for drift | server: {} | {e:#}",
+        deployment.name, server.name
+      )
+    })
+    .ok()?;
+  Some(deployment_config_drift(&deployment.config, &container))
+}
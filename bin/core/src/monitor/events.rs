@@ -0,0 +1,133 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+  time::Duration,
+};
+
+use database::mungos::find::find_collect;
+use futures::StreamExt;
+use komodo_client::entities::{
+  docker::container::ContainerEvent, server::Server,
+};
+use tokio::{task::JoinHandle, time::Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{helpers::periphery_client, state::db_client};
+
+use super::update_cache_for_server;
+
+/// How often to (re)connect to the event streams
+/// of servers which aren't being listened to.
+const CONNECT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events arriving together, eg when a stack is brought down,
+/// are handled with a single refresh.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Server id -> (address, listener)
+type Listeners = Mutex<HashMap<String, (String, JoinHandle<()>)>>;
+
+fn listeners() -> &'static Listeners {
+  static LISTENERS: OnceLock<Listeners> = OnceLock::new();
+  LISTENERS.get_or_init(Default::default)
+}
+
+/// Listens to the container events streamed by each enabled server,
+/// refreshing its status cache as soon as a container dies,
+/// is OOM killed, or changes health, rather than on the next poll.
+pub fn spawn_container_event_listeners() {
+  tokio::spawn(async move {
+    loop {
+      manage_listeners().await;
+      tokio::time::sleep(CONNECT_INTERVAL).await;
+    }
+  });
+}
+
+async fn manage_listeners() {
+  let servers = match find_collect(&db_client().servers, None, None)
+    .await
+  {
+    Ok(servers) => servers,
+    Err(e) => {
+      error!(
+        "failed to get server list (container event listeners) | {e:#}"
+      );
+      return;
+    }
+  };
+  let mut listeners = listeners().lock().unwrap();
+  // Stop listeners for servers which were removed, disabled,
+  // or had their address changed.
+  listeners.retain(|id, (address, listener)| {
+    let keep = !listener.is_finished()
+      && servers.iter().any(|server| {
+        &server.id == id
+          && server.config.enabled
+          && &server.config.address == address
+      });
+    if !keep {
+      listener.abort();
+    }
+    keep
+  });
+  for server in servers {
+    if !server.config.enabled || listeners.contains_key(&server.id) {
+      continue;
+    }
+    let id = server.id.clone();
+    let address = server.config.address.clone();
+    let listener = tokio::spawn(listen_to_server(server));
+    listeners.insert(id, (address, listener));
+  }
+}
+
+async fn listen_to_server(server: Server) {
+  let socket = match periphery_client(&server) {
+    Ok(periphery) => periphery.connect_container_events().await,
+    Err(e) => Err(e),
+  };
+  let mut socket = match socket {
+    Ok(socket) => socket,
+    Err(e) => {
+      // The server being unreachable is reported by the monitor loop.
+      debug!(
+        "Failed to connect to container events | server: {} | {e:#}",
+        server.name
+      );
+      return;
+    }
+  };
+  let mut refresh_at: Option<Instant> = None;
+  loop {
+    tokio::select! {
+      msg = socket.next() => match msg {
+        Some(Ok(Message::Text(text))) => {
+          match serde_json::from_str::<ContainerEvent>(&text) {
+            Ok(event) => {
+              debug!(
+                "Container event | server: {} | container: {} | action: {}",
+                server.name, event.name, event.action
+              );
+              refresh_at
+                .get_or_insert_with(|| Instant::now() + REFRESH_DEBOUNCE);
+            }
+            Err(e) => warn!(
+              "Failed to parse container event | server: {} | {e:?}",
+              server.name
+            ),
+          }
+        }
+        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+        // Do nothing (ping, etc.)
+        Some(Ok(_)) => {}
+      },
+      _ = tokio::time::sleep_until(refresh_at.unwrap_or_else(Instant::now)),
+        if refresh_at.is_some() =>
+      {
+        refresh_at = None;
+        update_cache_for_server(&server, true).await;
+      }
+    }
+  }
+}
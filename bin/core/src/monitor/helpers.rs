@@ -3,7 +3,8 @@ use komodo_client::entities::{
   deployment::{Deployment, DeploymentState},
   docker::{
     container::ContainerListItem, image::ImageListItem,
-    network::NetworkListItem, volume::VolumeListItem,
+    info::DockerVersion, network::NetworkListItem,
+    volume::VolumeListItem,
   },
   repo::Repo,
   server::{
@@ -42,6 +43,8 @@ pub async fn insert_deployments_status_unknown(
             state: DeploymentState::Unknown,
             container: None,
             update_available: false,
+            oom_killed: None,
+            crash_loop: None,
           },
           prev,
         }
@@ -104,6 +107,7 @@ pub async fn insert_server_status(
   server: &Server,
   state: ServerState,
   version: String,
+  docker: Option<DockerVersion>,
   stats: Option<SystemStats>,
   (containers, networks, images, volumes, projects): DockerLists,
   err: impl Into<Option<Serror>>,
@@ -116,6 +120,7 @@ pub async fn insert_server_status(
         id: server.id.clone(),
         state,
         version,
+        docker,
         stats,
         health,
         containers,
@@ -147,8 +152,6 @@ fn get_server_health(
     cpu_critical,
     mem_warning,
     mem_critical,
-    disk_warning,
-    disk_critical,
     ..
   } = &server.config;
   let mut health = ServerHealth::default();
@@ -180,10 +183,12 @@ fn get_server_health(
   } in disks
   {
     let perc = 100.0 * used_gb / total_gb;
+    let (disk_warning, disk_critical) =
+      server.config.disk_thresholds(mount);
     let mut state = ServerHealthState::default();
-    if perc >= *disk_critical {
+    if perc >= disk_critical {
       state.level = SeverityLevel::Critical;
-    } else if perc >= *disk_warning {
+    } else if perc >= disk_warning {
       state.level = SeverityLevel::Warning;
     } else if perc
       < disk_warning - (ALERT_PERCENTAGE_THRESHOLD as f64)
@@ -25,7 +25,17 @@ pub async fn get_docker_lists(
     images,
     volumes,
     projects,
-  } = periphery.request(GetDockerLists {}).await?;
+  } = periphery
+    .request(GetDockerLists {
+      include_containers: true,
+      include_networks: true,
+      include_images: true,
+      include_volumes: true,
+      include_projects: true,
+      images: Default::default(),
+      volumes: Default::default(),
+    })
+    .await?;
   // TODO: handle the errors
   let (
     mut containers,
@@ -8,7 +8,8 @@ use komodo_client::entities::{
   deployment::DeploymentState,
   docker::{
     container::ContainerListItem, image::ImageListItem,
-    network::NetworkListItem, volume::VolumeListItem,
+    info::DockerVersion, network::NetworkListItem,
+    volume::VolumeListItem,
   },
   komodo_timestamp, optional_string,
   server::{Server, ServerHealth, ServerState},
@@ -22,6 +23,7 @@ use tokio::sync::Mutex;
 use crate::{
   config::core_config,
   helpers::{cache::Cache, periphery_client},
+  metrics,
   monitor::{alert::check_alerts, record::record_server_stats},
   state::{db_client, deployment_status_cache, repo_status_cache},
 };
@@ -31,7 +33,13 @@ use self::helpers::{
   insert_server_status,
 };
 
+pub use drift::spawn_deployment_drift_check_loop;
+pub use events::spawn_container_event_listeners;
+pub use record::rollup_stats;
+
 mod alert;
+mod drift;
+mod events;
 mod helpers;
 mod lists;
 mod record;
@@ -48,6 +56,8 @@ pub struct CachedServerStatus {
   pub id: String,
   pub state: ServerState,
   pub version: String,
+  /// The docker versions and capabilities reported by Periphery.
+  pub docker: Option<DockerVersion>,
   pub stats: Option<SystemStats>,
   pub health: Option<ServerHealth>,
   pub containers: Option<Vec<ContainerListItem>>,
@@ -66,6 +76,11 @@ pub struct CachedDeploymentStatus {
   pub state: DeploymentState,
   pub container: Option<ContainerListItem>,
   pub update_available: bool,
+  /// The exit code, if the container was just stopped by the OOM killer.
+  pub oom_killed: Option<i64>,
+  /// The restarts within the `crash_loop_window`,
+  /// if the container just started crash looping.
+  pub crash_loop: Option<i64>,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -97,6 +112,8 @@ pub fn spawn_monitor_loop() {
       let ts = (wait_until_timelength(interval, ADDITIONAL_MS).await
         - ADDITIONAL_MS) as i64;
       refresh_server_cache(ts).await;
+      metrics::record_servers().await;
+      metrics::heartbeat("monitor");
     }
   });
 }
@@ -188,6 +205,7 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
       ServerState::Disabled,
       String::from("unknown"),
       None,
+      None,
       (None, None, None, None, None),
       None,
     )
@@ -202,24 +220,26 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
     return;
   };
 
-  let version = match periphery.request(api::GetVersion {}).await {
-    Ok(version) => version.version,
-    Err(e) => {
-      insert_deployments_status_unknown(deployments).await;
-      insert_stacks_status_unknown(stacks).await;
-      insert_repos_status_unknown(repos).await;
-      insert_server_status(
-        server,
-        ServerState::NotOk,
-        String::from("Unknown"),
-        None,
-        (None, None, None, None, None),
-        Serror::from(&e),
-      )
-      .await;
-      return;
-    }
-  };
+  let (version, docker) =
+    match periphery.request(api::GetVersion {}).await {
+      Ok(version) => (version.version, version.docker),
+      Err(e) => {
+        insert_deployments_status_unknown(deployments).await;
+        insert_stacks_status_unknown(stacks).await;
+        insert_repos_status_unknown(repos).await;
+        insert_server_status(
+          server,
+          ServerState::NotOk,
+          String::from("Unknown"),
+          None,
+          None,
+          (None, None, None, None, None),
+          Serror::from(&e),
+        )
+        .await;
+        return;
+      }
+    };
 
   let stats = if server.config.stats_monitoring {
     match periphery.request(api::stats::GetSystemStats {}).await {
@@ -233,6 +253,7 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
           ServerState::NotOk,
           String::from("unknown"),
           None,
+          None,
           (None, None, None, None, None),
           Serror::from(&e),
         )
@@ -251,7 +272,7 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
       });
       tokio::join!(
         resources::update_deployment_cache(
-          server.name.clone(),
+          server,
           deployments,
           &containers,
           &images,
@@ -268,6 +289,7 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
         server,
         ServerState::Ok,
         version,
+        docker,
         stats,
         (
           Some(containers.clone()),
@@ -287,6 +309,7 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
         server,
         ServerState::Ok,
         version,
+        docker,
         stats,
         (None, None, None, None, None),
         Some(e.into()),
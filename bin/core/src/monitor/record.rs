@@ -1,9 +1,27 @@
-use komodo_client::entities::stats::{
-  SystemStatsRecord, TotalDiskUsage, sum_disk_usage,
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Mutex, OnceLock},
+};
+
+use anyhow::Context;
+use async_timing_util::{ONE_DAY_MS, ONE_MIN_MS};
+use database::mungos::mongodb::{
+  Collection,
+  bson::{Document, doc, from_document},
+};
+use futures::TryStreamExt;
+use komodo_client::entities::{
+  komodo_timestamp,
+  stats::{
+    SystemStatsRecord, SystemStatsRollup, TotalDiskUsage,
+    sum_disk_usage,
+  },
 };
 
 use crate::state::{db_client, server_status_cache};
 
+use super::alert::alert_stats_anomalies;
+
 #[instrument(level = "debug")]
 pub async fn record_server_stats(ts: i64) {
   let status = server_status_cache().get_list().await;
@@ -32,10 +50,291 @@ pub async fn record_server_stats(ts: i64) {
       })
     })
     .collect::<Vec<_>>();
+  let anomalies = detect_anomalies(&records);
   if !records.is_empty() {
     let res = db_client().stats.insert_many(records).await;
     if let Err(e) = res {
       error!("failed to record server stats | {e:#}");
     }
   }
+  if !anomalies.is_empty() {
+    alert_stats_anomalies(ts, anomalies).await;
+  }
+}
+
+// ===================
+//  ANOMALY DETECTION
+// ===================
+
+/// The number of recent records per server used as the baseline.
+const BASELINE_WINDOW: usize = 30;
+/// Anomalies aren't detected until the baseline has this many records.
+const BASELINE_MIN_RECORDS: usize = 10;
+/// Keeps a perfectly flat baseline from flagging tiny changes.
+const MIN_STD_DEV: f64 = 2.0;
+/// How many standard deviations above the baseline is an anomaly.
+const ANOMALY_Z_SCORE: f64 = 3.0;
+
+/// A CPU or MEM usage spike far above the server's recent baseline.
+#[derive(Debug)]
+pub struct StatsAnomaly {
+  pub server_id: String,
+  /// `CPU` or `MEM`
+  pub metric: &'static str,
+  pub percentage: f64,
+  pub baseline: f64,
+  pub z_score: f64,
+}
+
+#[derive(Default)]
+struct Baseline {
+  records: VecDeque<f64>,
+  /// Whether the last record was an anomaly,
+  /// so a sustained spike only alerts once.
+  anomalous: bool,
+}
+
+impl Baseline {
+  /// Returns the (mean, z score) of the percentage against the baseline,
+  /// then adds the percentage to the baseline.
+  fn observe(&mut self, percentage: f64) -> Option<(f64, f64)> {
+    let res =
+      (self.records.len() >= BASELINE_MIN_RECORDS).then(|| {
+        let len = self.records.len() as f64;
+        let mean = self.records.iter().sum::<f64>() / len;
+        let variance = self
+          .records
+          .iter()
+          .map(|record| (record - mean).powi(2))
+          .sum::<f64>()
+          / len;
+        let std_dev = variance.sqrt().max(MIN_STD_DEV);
+        (mean, (percentage - mean) / std_dev)
+      });
+    if self.records.len() == BASELINE_WINDOW {
+      self.records.pop_front();
+    }
+    self.records.push_back(percentage);
+    res
+  }
+}
+
+/// Server id -> (CPU, MEM) baselines
+fn baselines() -> &'static Mutex<HashMap<String, (Baseline, Baseline)>>
+{
+  static BASELINES: OnceLock<
+    Mutex<HashMap<String, (Baseline, Baseline)>>,
+  > = OnceLock::new();
+  BASELINES.get_or_init(Default::default)
+}
+
+/// Records which spike at least [ANOMALY_Z_SCORE] standard deviations
+/// above the server's baseline. Baselines are kept for every server,
+/// whether or not it sends anomaly alerts.
+fn detect_anomalies(
+  records: &[SystemStatsRecord],
+) -> Vec<StatsAnomaly> {
+  let mut baselines = baselines().lock().unwrap();
+  let mut anomalies = Vec::new();
+  for record in records {
+    let (cpu, mem) = baselines.entry(record.sid.clone()).or_default();
+    let mem_perc = 100.0 * record.mem_used_gb / record.mem_total_gb;
+    for (metric, baseline, percentage) in
+      [("CPU", cpu, record.cpu_perc as f64), ("MEM", mem, mem_perc)]
+    {
+      if !percentage.is_finite() {
+        continue;
+      }
+      let Some((mean, z_score)) = baseline.observe(percentage) else {
+        continue;
+      };
+      let anomalous = z_score >= ANOMALY_Z_SCORE;
+      if anomalous && !baseline.anomalous {
+        anomalies.push(StatsAnomaly {
+          server_id: record.sid.clone(),
+          metric,
+          percentage,
+          baseline: mean,
+          z_score,
+        });
+      }
+      baseline.anomalous = anomalous;
+    }
+  }
+  anomalies
+}
+
+// =========
+//  ROLLUPS
+// =========
+
+/// Averages the stats of every complete hour and day
+/// which isn't rolled up yet into `stats_hourly` and `stats_daily`.
+/// Call before the raw stats are pruned.
+pub async fn rollup_stats() -> anyhow::Result<()> {
+  let db = db_client();
+  let (hourly, daily) = tokio::join!(
+    rollup_stats_period(&db.stats_hourly, (60 * ONE_MIN_MS) as i64),
+    rollup_stats_period(&db.stats_daily, ONE_DAY_MS as i64),
+  );
+  hourly.context("Failed to roll up hourly stats")?;
+  daily.context("Failed to roll up daily stats")?;
+  Ok(())
+}
+
+async fn rollup_stats_period(
+  rollups: &Collection<SystemStatsRollup>,
+  period_ms: i64,
+) -> anyhow::Result<()> {
+  // Continue after the latest rollup,
+  // or from the start of the stats on the first run.
+  let latest = rollups
+    .find_one(Document::new())
+    .sort(doc! { "ts": -1 })
+    .await
+    .context("Failed to get latest rollup")?;
+  let Some((since, until)) = rollup_range(
+    latest.map(|latest| latest.ts),
+    komodo_timestamp(),
+    period_ms,
+  ) else {
+    return Ok(());
+  };
+  let pipeline = [
+    doc! { "$match": { "ts": { "$gte": since, "$lt": until } } },
+    doc! { "$group": {
+      "_id": {
+        "sid": "$sid",
+        "ts": { "$subtract": ["$ts", { "$mod": ["$ts", period_ms] }] },
+      },
+      "count": { "$sum": 1 },
+      "cpu_perc": { "$avg": "$cpu_perc" },
+      "cpu_perc_max": { "$max": "$cpu_perc" },
+      "mem_used_gb": { "$avg": "$mem_used_gb" },
+      "mem_used_gb_max": { "$max": "$mem_used_gb" },
+      "mem_total_gb": { "$avg": "$mem_total_gb" },
+      "disk_used_gb": { "$avg": "$disk_used_gb" },
+      "disk_total_gb": { "$avg": "$disk_total_gb" },
+      "network_ingress_bytes": { "$avg": "$network_ingress_bytes" },
+      "network_egress_bytes": { "$avg": "$network_egress_bytes" },
+    } },
+    doc! { "$set": { "sid": "$_id.sid", "ts": "$_id.ts" } },
+    doc! { "$unset": "_id" },
+  ];
+  let records = db_client()
+    .stats
+    .aggregate(pipeline)
+    .await
+    .context("Failed to aggregate stats")?
+    .try_collect::<Vec<_>>()
+    .await
+    .context("Failed to collect aggregated stats")?
+    .into_iter()
+    .map(from_document::<SystemStatsRollup>)
+    .collect::<Result<Vec<_>, _>>()
+    .context("Failed to parse aggregated stats")?;
+  if !records.is_empty() {
+    rollups
+      .insert_many(records)
+      .await
+      .context("Failed to insert rollups")?;
+  }
+  Ok(())
+}
+
+/// The (since, until) range of complete periods to roll up,
+/// or None if there are none yet.
+fn rollup_range(
+  latest_ts: Option<i64>,
+  now: i64,
+  period_ms: i64,
+) -> Option<(i64, i64)> {
+  let since = match latest_ts {
+    Some(latest_ts) => latest_ts + period_ms,
+    None => 0,
+  };
+  // Only roll up complete periods.
+  let until = now / period_ms * period_ms;
+  (since < until).then_some((since, until))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn baseline_needs_min_records() {
+    let mut baseline = Baseline::default();
+    for _ in 0..BASELINE_MIN_RECORDS {
+      assert!(baseline.observe(10.0).is_none());
+    }
+    assert!(baseline.observe(10.0).is_some());
+  }
+
+  #[test]
+  fn baseline_uses_min_std_dev_when_flat() {
+    let mut baseline = Baseline::default();
+    for _ in 0..BASELINE_MIN_RECORDS {
+      baseline.observe(20.0);
+    }
+    let (mean, z_score) = baseline.observe(26.0).unwrap();
+    assert_eq!(mean, 20.0);
+    assert_eq!(z_score, 6.0 / MIN_STD_DEV);
+  }
+
+  #[test]
+  fn baseline_keeps_window() {
+    let mut baseline = Baseline::default();
+    for _ in 0..BASELINE_WINDOW * 2 {
+      baseline.observe(50.0);
+    }
+    assert_eq!(baseline.records.len(), BASELINE_WINDOW);
+  }
+
+  #[test]
+  fn sustained_spike_alerts_once() {
+    let record = |cpu_perc| SystemStatsRecord {
+      sid: String::from("anomaly-test-server"),
+      cpu_perc,
+      mem_used_gb: 4.0,
+      mem_total_gb: 16.0,
+      ..Default::default()
+    };
+    for _ in 0..BASELINE_MIN_RECORDS {
+      assert!(detect_anomalies(&[record(10.0)]).is_empty());
+    }
+    let anomalies = detect_anomalies(&[record(90.0)]);
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].metric, "CPU");
+    assert_eq!(anomalies[0].percentage, 90.0);
+    assert_eq!(anomalies[0].baseline, 10.0);
+    assert!(detect_anomalies(&[record(95.0)]).is_empty());
+  }
+
+  const HOUR: i64 = 60 * 60 * 1000;
+
+  #[test]
+  fn rollup_range_starts_from_beginning() {
+    assert_eq!(
+      rollup_range(None, 5 * HOUR + 123, HOUR),
+      Some((0, 5 * HOUR))
+    );
+  }
+
+  #[test]
+  fn rollup_range_continues_after_latest() {
+    assert_eq!(
+      rollup_range(Some(2 * HOUR), 5 * HOUR + 123, HOUR),
+      Some((3 * HOUR, 5 * HOUR))
+    );
+  }
+
+  #[test]
+  fn rollup_range_skips_incomplete_period() {
+    assert_eq!(
+      rollup_range(Some(4 * HOUR), 5 * HOUR + 123, HOUR),
+      None
+    );
+    assert_eq!(rollup_range(None, HOUR - 1, HOUR), None);
+  }
 }
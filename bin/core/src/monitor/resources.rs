@@ -1,9 +1,10 @@
 use std::{
-  collections::HashSet,
+  collections::{HashMap, HashSet, VecDeque},
   sync::{Mutex, OnceLock},
 };
 
 use anyhow::Context;
+use async_timing_util::get_timelength_in_ms;
 use komodo_client::{
   api::execute::{Deploy, DeployStack},
   entities::{
@@ -12,19 +13,28 @@ use komodo_client::{
     build::Build,
     deployment::{Deployment, DeploymentImage, DeploymentState},
     docker::{
-      container::{ContainerListItem, ContainerStateStatusEnum},
+      container::{
+        Container, ContainerListItem, ContainerState,
+        ContainerStateStatusEnum,
+      },
       image::ImageListItem,
     },
     komodo_timestamp,
+    server::Server,
     stack::{Stack, StackService, StackServiceNames, StackState},
     user::auto_redeploy_user,
   },
 };
 
+use periphery_client::api::container::InspectContainer;
+
 use crate::{
   alert::send_alerts,
   api::execute::{self, ExecuteRequest},
-  helpers::query::get_stack_state_from_containers,
+  config::core_config,
+  helpers::{
+    periphery_client, query::get_stack_state_from_containers,
+  },
   stack::{
     compose_container_match_regex,
     services::extract_services_from_stack,
@@ -43,12 +53,13 @@ fn deployment_alert_sent_cache() -> &'static Mutex<HashSet<String>> {
 }
 
 pub async fn update_deployment_cache(
-  server_name: String,
+  server: &Server,
   deployments: Vec<Deployment>,
   containers: &[ContainerListItem],
   images: &[ImageListItem],
   builds: &[Build],
 ) {
+  let server_name = server.name.clone();
   let deployment_status_cache = deployment_status_cache();
   for deployment in deployments {
     let container = containers
@@ -63,6 +74,22 @@ pub async fn update_deployment_cache(
       .as_ref()
       .map(|c| c.state.into())
       .unwrap_or(DeploymentState::NotDeployed);
+    // The container list doesn't report OOM kills,
+    // so inspect containers which just stopped running.
+    let oom_killed = if just_stopped(prev, state) {
+      container_oom_killed(server, &deployment.name).await
+    } else {
+      None
+    };
+    let crash_loop = if matches!(
+      state,
+      DeploymentState::Running | DeploymentState::Restarting
+    ) {
+      container_crash_loop(server, &deployment.id, &deployment.name)
+        .await
+    } else {
+      None
+    };
     let image = match deployment.config.image {
       DeploymentImage::Build { build_id, version } => {
         let (build_name, build_version) = builds
@@ -215,6 +242,8 @@ pub async fn update_deployment_cache(
             state,
             container,
             update_available,
+            oom_killed,
+            crash_loop,
           },
           prev,
         }
@@ -224,6 +253,165 @@ pub async fn update_deployment_cache(
   }
 }
 
+/// Whether the container was running on the last poll,
+/// and has since exited or is restarting.
+fn just_stopped(
+  prev: Option<DeploymentState>,
+  state: DeploymentState,
+) -> bool {
+  prev == Some(DeploymentState::Running)
+    && matches!(
+      state,
+      DeploymentState::Exited
+        | DeploymentState::Dead
+        | DeploymentState::Restarting
+    )
+}
+
+/// Returns the exit code if the container was killed by the OOM killer.
+async fn container_oom_killed(
+  server: &Server,
+  container: &str,
+) -> Option<i64> {
+  let state = inspect_container(server, container, "OOM kill")
+    .await?
+    .state?;
+  oom_exit_code(&state)
+}
+
+fn oom_exit_code(state: &ContainerState) -> Option<i64> {
+  if state.oom_killed.unwrap_or_default() {
+    Some(state.exit_code.unwrap_or(137))
+  } else {
+    None
+  }
+}
+
+/// Deployment id -> (ts, restart count) samples within the crash loop window.
+type RestartCounts = HashMap<String, VecDeque<(i64, i64)>>;
+
+fn restart_counts() -> &'static Mutex<RestartCounts> {
+  static RESTART_COUNTS: OnceLock<Mutex<RestartCounts>> =
+    OnceLock::new();
+  RESTART_COUNTS.get_or_init(Default::default)
+}
+
+/// Deployments already alerted for their ongoing crash loop.
+fn crash_loop_alert_sent_cache() -> &'static Mutex<HashSet<String>> {
+  static CACHE: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+  CACHE.get_or_init(Default::default)
+}
+
+/// Tracks the container's restart count, which a crash looping
+/// container increases while appearing to be running between restarts.
+/// Returns the restarts within the `crash_loop_window`
+/// when they first reach `crash_loop_restarts`.
+async fn container_crash_loop(
+  server: &Server,
+  deployment_id: &str,
+  container: &str,
+) -> Option<i64> {
+  let config = core_config();
+  if config.crash_loop_restarts == 0 {
+    return None;
+  }
+  let window = get_timelength_in_ms(
+    config.crash_loop_window.to_string().parse().ok()?,
+  ) as i64;
+  let restart_count =
+    inspect_container(server, container, "restart count")
+      .await?
+      .restart_count?;
+  let restarts = record_restart_count(
+    restart_counts()
+      .lock()
+      .unwrap()
+      .entry(deployment_id.to_string())
+      .or_default(),
+    komodo_timestamp(),
+    restart_count,
+    window,
+  );
+  crash_loop_alert(
+    &mut crash_loop_alert_sent_cache().lock().unwrap(),
+    deployment_id,
+    restarts,
+    config.crash_loop_restarts,
+  )
+}
+
+/// Returns the restarts when they first reach the threshold,
+/// alerting once per crash loop.
+fn crash_loop_alert(
+  alert_sent: &mut HashSet<String>,
+  deployment_id: &str,
+  restarts: i64,
+  threshold: u64,
+) -> Option<i64> {
+  if restarts < threshold as i64 {
+    // Crash loop is over, the next one alerts again.
+    alert_sent.remove(deployment_id);
+    None
+  } else if alert_sent.insert(deployment_id.to_string()) {
+    Some(restarts)
+  } else {
+    None
+  }
+}
+
+/// Adds the restart count sample, dropping the samples
+/// older than the window, and returns the restarts within the window.
+fn record_restart_count(
+  samples: &mut VecDeque<(i64, i64)>,
+  ts: i64,
+  restart_count: i64,
+  window: i64,
+) -> i64 {
+  // The count starts over when the container is recreated.
+  if samples
+    .back()
+    .is_some_and(|(_, count)| *count > restart_count)
+  {
+    samples.clear();
+  }
+  samples.push_back((ts, restart_count));
+  while samples
+    .front()
+    .is_some_and(|(sample_ts, _)| ts - sample_ts > window)
+  {
+    samples.pop_front();
+  }
+  samples
+    .front()
+    .map(|(_, count)| restart_count - count)
+    .unwrap_or_default()
+}
+
+async fn inspect_container(
+  server: &Server,
+  container: &str,
+  purpose: &str,
+) -> Option<Container> {
+  let res = match periphery_client(server) {
+    Ok(periphery) => {
+      periphery
+        .request(InspectContainer {
+          name: container.to_string(),
+        })
+        .await
+    }
+    Err(e) => Err(e),
+  };
+  res
+    .inspect_err(|e| {
+      warn!(
+        "Failed to inspect container {container} for {purpose} | server: {} | {e:#}",
+        server.name
+      )
+    })
+    .ok()
+}
+
 /// (StackId, Service)
 fn stack_alert_sent_cache()
 -> &'static Mutex<HashSet<(String, String)>> {
@@ -425,3 +613,68 @@ pub async fn update_stack_cache(
       .await;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detects_just_stopped_containers() {
+    let running = Some(DeploymentState::Running);
+    assert!(just_stopped(running, DeploymentState::Exited));
+    assert!(just_stopped(running, DeploymentState::Dead));
+    assert!(just_stopped(running, DeploymentState::Restarting));
+    assert!(!just_stopped(running, DeploymentState::Running));
+    assert!(!just_stopped(running, DeploymentState::NotDeployed));
+    assert!(!just_stopped(
+      Some(DeploymentState::Exited),
+      DeploymentState::Exited
+    ));
+    assert!(!just_stopped(None, DeploymentState::Exited));
+  }
+
+  #[test]
+  fn oom_exit_code_only_when_oom_killed() {
+    let state = |oom_killed, exit_code| ContainerState {
+      oom_killed,
+      exit_code,
+      ..Default::default()
+    };
+    assert_eq!(
+      oom_exit_code(&state(Some(true), Some(137))),
+      Some(137)
+    );
+    assert_eq!(oom_exit_code(&state(Some(true), None)), Some(137));
+    assert_eq!(oom_exit_code(&state(Some(false), Some(1))), None);
+    assert_eq!(oom_exit_code(&state(None, Some(0))), None);
+  }
+
+  #[test]
+  fn restarts_counted_within_window() {
+    let mut samples = VecDeque::new();
+    assert_eq!(record_restart_count(&mut samples, 0, 2, 100), 0);
+    assert_eq!(record_restart_count(&mut samples, 50, 4, 100), 2);
+    assert_eq!(record_restart_count(&mut samples, 100, 5, 100), 3);
+    // The sample at 0 falls out of the window.
+    assert_eq!(record_restart_count(&mut samples, 120, 6, 100), 2);
+  }
+
+  #[test]
+  fn restarts_start_over_when_recreated() {
+    let mut samples = VecDeque::new();
+    record_restart_count(&mut samples, 0, 5, 100);
+    assert_eq!(record_restart_count(&mut samples, 10, 1, 100), 0);
+    assert_eq!(samples, VecDeque::from([(10, 1)]));
+  }
+
+  #[test]
+  fn crash_loop_alerts_once_per_loop() {
+    let mut alert_sent = HashSet::new();
+    assert_eq!(crash_loop_alert(&mut alert_sent, "d", 2, 3), None);
+    assert_eq!(crash_loop_alert(&mut alert_sent, "d", 3, 3), Some(3));
+    assert_eq!(crash_loop_alert(&mut alert_sent, "d", 4, 3), None);
+    // Loop ends, then starts again.
+    assert_eq!(crash_loop_alert(&mut alert_sent, "d", 0, 3), None);
+    assert_eq!(crash_loop_alert(&mut alert_sent, "d", 3, 3), Some(3));
+  }
+}
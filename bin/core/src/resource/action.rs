@@ -21,6 +21,7 @@ use crate::{
   helpers::query::{get_action_state, get_last_run_at},
   schedule::{
     cancel_schedule, get_schedule_item_info, update_schedule,
+    validate_partial_schedule,
   },
   state::{action_state_cache, action_states, db_client},
 };
@@ -98,7 +99,7 @@ impl super::KomodoResource for Action {
       config.file_contents =
         Some(DEFAULT_ACTION_FILE_CONTENTS.to_string());
     }
-    Ok(())
+    validate_config(config, None).await
   }
 
   async fn post_create(
@@ -117,11 +118,11 @@ impl super::KomodoResource for Action {
   }
 
   async fn validate_update_config(
-    _id: &str,
-    _config: &mut Self::PartialConfig,
+    id: &str,
+    config: &mut Self::PartialConfig,
     _user: &User,
   ) -> anyhow::Result<()> {
-    Ok(())
+    validate_config(config, Some(id)).await
   }
 
   async fn post_update(
@@ -160,6 +161,29 @@ impl super::KomodoResource for Action {
   }
 }
 
+async fn validate_config(
+  config: &PartialActionConfig,
+  id: Option<&str>,
+) -> anyhow::Result<()> {
+  if config.schedule_format.is_some()
+    || config.schedule.is_some()
+    || config.schedule_timezone.is_some()
+  {
+    let existing = match id {
+      Some(id) => Some(super::get::<Action>(id).await?),
+      None => None,
+    };
+    validate_partial_schedule(
+      config.schedule_format,
+      config.schedule.as_deref(),
+      config.schedule_timezone.as_deref(),
+      existing.as_ref(),
+    )
+    .context("Invalid schedule")?;
+  }
+  Ok(())
+}
+
 pub fn spawn_action_state_refresh_loop() {
   tokio::spawn(async move {
     loop {
@@ -1,4 +1,4 @@
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use database::mungos::mongodb::Collection;
 use formatting::format_serror;
 use indexmap::IndexSet;
@@ -13,6 +13,7 @@ use komodo_client::entities::{
   },
   environment_vars_from_str,
   permission::{PermissionLevel, SpecificPermission},
+  repo::Repo,
   resource::Resource,
   server::Server,
   to_container_compatible_name,
@@ -28,7 +29,10 @@ use crate::{
     query::get_deployment_state,
   },
   monitor::update_cache_for_server,
-  state::{action_states, db_client, deployment_status_cache},
+  state::{
+    action_states, db_client, deployment_drift_cache,
+    deployment_status_cache,
+  },
 };
 
 use super::get_check_permissions;
@@ -138,6 +142,10 @@ impl super::KomodoResource for Deployment {
         }),
         image,
         update_available,
+        config_drift: deployment_drift_cache()
+          .get(&deployment.id)
+          .await
+          .unwrap_or_default(),
         server_id: deployment.config.server_id,
         build_id,
       },
@@ -355,5 +363,29 @@ async fn validate_config(
   if let Some(extra_args) = &mut config.extra_args {
     extra_args.retain(|v| !empty_or_only_spaces(v))
   }
+  if let Some(env_files) = &mut config.env_files {
+    env_files.retain(|v| !empty_or_only_spaces(v));
+    if let Some(path) = env_files.iter().find(|path| {
+      !path.starts_with('/')
+        && path.split('/').any(|component| component == "..")
+    }) {
+      return Err(anyhow!(
+        "Relative env file path must not contain '..': {path}"
+      ));
+    }
+  }
+  if let Some(linked_repo) = &config.linked_repo
+    && !linked_repo.is_empty()
+  {
+    let repo = get_check_permissions::<Repo>(
+      linked_repo,
+      user,
+      PermissionLevel::Read.attach(),
+    )
+    .await
+    .context("Cannot attach Repo to this Deployment")?;
+    // in case it comes in as name
+    config.linked_repo = Some(repo.id);
+  }
   Ok(())
 }
@@ -13,19 +13,33 @@ use database::mungos::{
     options::FindOptions,
   },
 };
+use derive_variants::ExtractVariant;
 use formatting::format_serror;
 use futures::future::join_all;
 use indexmap::IndexSet;
 use komodo_client::{
-  api::{read::ExportResourcesToToml, write::CreateTag},
+  api::{
+    read::ExportResourcesToToml,
+    write::{BatchUpdateTagsResponse, CreateTag},
+  },
   entities::{
     Operation, ResourceTarget, ResourceTargetVariant,
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::Deployment,
     komodo_timestamp,
     permission::{
       PermissionLevel, PermissionLevelAndSpecifics,
       SpecificPermission,
     },
+    procedure::Procedure,
+    repo::Repo,
     resource::{AddFilters, Resource, ResourceQuery},
+    server::Server,
+    stack::Stack,
+    sync::ResourceSync,
     tag::Tag,
     to_general_name,
     update::Update,
@@ -43,7 +57,7 @@ use crate::{
   api::{read::ReadArgs, write::WriteArgs},
   helpers::{
     create_permission, flatten_document,
-    query::{get_tag, id_or_name_filter},
+    query::{get_all_tags, get_tag, id_or_name_filter},
     update::{add_update, make_update},
   },
   permission::{get_check_permissions, get_resource_ids_for_user},
@@ -249,6 +263,47 @@ pub async fn get<T: KomodoResource>(
     })
 }
 
+/// Fails if the target resource is locked,
+/// which blocks all executions on it.
+pub async fn check_not_locked(
+  target: &ResourceTarget,
+) -> anyhow::Result<()> {
+  let locked = match target {
+    ResourceTarget::System(_) => return Ok(()),
+    ResourceTarget::Server(id) => get::<Server>(id).await?.locked,
+    ResourceTarget::Stack(id) => get::<Stack>(id).await?.locked,
+    ResourceTarget::Deployment(id) => {
+      get::<Deployment>(id).await?.locked
+    }
+    ResourceTarget::Build(id) => get::<Build>(id).await?.locked,
+    ResourceTarget::Repo(id) => get::<Repo>(id).await?.locked,
+    ResourceTarget::Procedure(id) => {
+      get::<Procedure>(id).await?.locked
+    }
+    ResourceTarget::Action(id) => get::<Action>(id).await?.locked,
+    ResourceTarget::ResourceSync(id) => {
+      get::<ResourceSync>(id).await?.locked
+    }
+    ResourceTarget::Builder(id) => get::<Builder>(id).await?.locked,
+    ResourceTarget::Alerter(id) => get::<Alerter>(id).await?.locked,
+  };
+  locked_check(target, locked)
+}
+
+fn locked_check(
+  target: &ResourceTarget,
+  locked: bool,
+) -> anyhow::Result<()> {
+  if locked {
+    Err(anyhow!(
+      "{} is locked. An admin must unlock it before it can be executed.",
+      target.extract_variant()
+    ))
+  } else {
+    Ok(())
+  }
+}
+
 // ======
 // LIST
 // ======
@@ -515,6 +570,7 @@ pub async fn create<T: KomodoResource>(
     name,
     description: Default::default(),
     template: Default::default(),
+    locked: Default::default(),
     tags: Default::default(),
     config: config.into(),
     info: T::default_info().await?,
@@ -691,6 +747,7 @@ fn resource_target<T: KomodoResource>(id: String) -> ResourceTarget {
 pub struct ResourceMetaUpdate {
   pub description: Option<String>,
   pub template: Option<bool>,
+  pub locked: Option<bool>,
   pub tags: Option<Vec<String>>,
 }
 
@@ -698,6 +755,7 @@ impl ResourceMetaUpdate {
   pub fn is_none(&self) -> bool {
     self.description.is_none()
       && self.template.is_none()
+      && self.locked.is_none()
       && self.tags.is_none()
   }
 }
@@ -720,26 +778,16 @@ pub async fn update_meta<T: KomodoResource>(
   if let Some(template) = meta.template {
     set.insert("template", template);
   }
+  if let Some(locked) = meta.locked {
+    if !args.user.admin {
+      return Err(anyhow!(
+        "Only admins can lock or unlock resources"
+      ));
+    }
+    set.insert("locked", locked);
+  }
   if let Some(tags) = meta.tags {
-    // First normalize to tag ids only
-    let futures = tags.iter().map(|tag| async {
-      match get_tag(tag).await {
-        Ok(tag) => Ok(tag.id),
-        Err(_) => CreateTag {
-          name: tag.to_string(),
-          color: None,
-        }
-        .resolve(args)
-        .await
-        .map(|tag| tag.id),
-      }
-    });
-    let tags = join_all(futures)
-      .await
-      .into_iter()
-      .flatten()
-      .collect::<Vec<_>>();
-    set.insert("tags", tags);
+    set.insert("tags", get_or_create_tag_ids(&tags, args).await?);
   }
   T::coll()
     .update_one(id_or_name_filter(id_or_name), doc! { "$set": set })
@@ -748,6 +796,99 @@ pub async fn update_meta<T: KomodoResource>(
   Ok(())
 }
 
+/// Normalizes tag names / ids to ids,
+/// creating the tags which don't exist yet.
+pub async fn get_or_create_tag_ids(
+  tags: &[String],
+  args: &WriteArgs,
+) -> anyhow::Result<Vec<String>> {
+  let futures = tags.iter().map(|tag| async {
+    match get_tag(tag).await {
+      Ok(tag) => Ok(tag.id),
+      Err(_) => CreateTag {
+        name: tag.to_string(),
+        color: None,
+      }
+      .resolve(args)
+      .await
+      .map(|tag| tag.id)
+      .map_err(|e| e.error)
+      .with_context(|| format!("Failed to create tag {tag}")),
+    }
+  });
+  join_all(futures).await.into_iter().collect()
+}
+
+/// Adds (or removes) the tags (names or ids) on all the resources
+/// matching the pattern which the user has write permissions on.
+///
+/// When adding, tags which don't exist yet are created,
+/// but only if the pattern matches any resources.
+pub async fn batch_update_tags<T: KomodoResource>(
+  pattern: &str,
+  tags: &[String],
+  add: bool,
+  args: &WriteArgs,
+) -> anyhow::Result<BatchUpdateTagsResponse> {
+  let all_tags = get_all_tags(None).await?;
+  let resources = list_full_for_user_using_pattern::<T>(
+    pattern,
+    Default::default(),
+    &args.user,
+    PermissionLevel::Write.into(),
+    &all_tags,
+  )
+  .await?;
+  let mut res = BatchUpdateTagsResponse::default();
+  if resources.is_empty() {
+    return Ok(res);
+  }
+  let tag_ids = if add {
+    get_or_create_tag_ids(tags, args).await?
+  } else {
+    let mut tag_ids = Vec::with_capacity(tags.len());
+    for tag in tags {
+      tag_ids.push(get_tag(tag).await?.id);
+    }
+    tag_ids
+  };
+  let mut ids = Vec::new();
+  for resource in resources {
+    if tags_change(&resource.tags, &tag_ids, add) {
+      ids.push(
+        ObjectId::from_str(&resource.id)
+          .context("Resource id is not ObjectId")?,
+      );
+      res.updated.push(resource.name);
+    } else {
+      res.unchanged.push(resource.name);
+    }
+  }
+  if ids.is_empty() {
+    return Ok(res);
+  }
+  let update = if add {
+    doc! { "$addToSet": { "tags": { "$each": tag_ids } } }
+  } else {
+    doc! { "$pull": { "tags": { "$in": tag_ids } } }
+  };
+  T::coll()
+    .update_many(doc! { "_id": { "$in": ids } }, update)
+    .await
+    .context("Failed to update resource tags on db")?;
+  refresh_all_resources_cache().await;
+  Ok(res)
+}
+
+/// Whether adding (or removing) the tag ids changes the resource tags.
+fn tags_change(
+  resource_tags: &[String],
+  tag_ids: &[String],
+  add: bool,
+) -> bool {
+  tag_ids.iter().any(|tag| resource_tags.contains(tag) != add)
+}
+
 pub async fn remove_tag_from_all<T: KomodoResource>(
   tag_id: &str,
 ) -> anyhow::Result<()> {
@@ -984,3 +1125,51 @@ where
     warn!("{e:#}");
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|tag| tag.to_string()).collect()
+  }
+
+  #[test]
+  fn add_changes_only_missing_tags() {
+    let resource = tags(&["a", "b"]);
+    assert!(!tags_change(&resource, &tags(&["a"]), true));
+    assert!(!tags_change(&resource, &tags(&["a", "b"]), true));
+    assert!(tags_change(&resource, &tags(&["a", "c"]), true));
+  }
+
+  #[test]
+  fn remove_changes_only_present_tags() {
+    let resource = tags(&["a", "b"]);
+    assert!(tags_change(&resource, &tags(&["a"]), false));
+    assert!(tags_change(&resource, &tags(&["a", "c"]), false));
+    assert!(!tags_change(&resource, &tags(&["c"]), false));
+  }
+
+  #[test]
+  fn locked_resource_rejected() {
+    let target = ResourceTarget::Stack(String::from("stack-id"));
+    assert!(locked_check(&target, false).is_ok());
+    assert_eq!(
+      locked_check(&target, true).unwrap_err().to_string(),
+      "Stack is locked. An admin must unlock it before it can be executed."
+    );
+  }
+
+  #[test]
+  fn lock_is_a_meta_update() {
+    let mut meta = ResourceMetaUpdate {
+      description: None,
+      template: None,
+      locked: None,
+      tags: None,
+    };
+    assert!(meta.is_none());
+    meta.locked = Some(false);
+    assert!(!meta.is_none());
+  }
+}
@@ -7,7 +7,7 @@ use database::mungos::{
 };
 use futures::{TryStreamExt, stream::FuturesUnordered};
 use komodo_client::{
-  api::execute::Execution,
+  api::execute::{Execution, WaitCondition},
   entities::{
     Operation, ResourceTarget, ResourceTargetVariant,
     action::Action,
@@ -32,9 +32,13 @@ use komodo_client::{
 
 use crate::{
   config::core_config,
-  helpers::query::{get_last_run_at, get_procedure_state},
+  helpers::{
+    procedure::validate_stage_conditions,
+    query::{get_last_run_at, get_procedure_state},
+  },
   schedule::{
     cancel_schedule, get_schedule_item_info, update_schedule,
+    validate_partial_schedule,
   },
   state::{action_states, db_client, procedure_state_cache},
 };
@@ -177,9 +181,26 @@ async fn validate_config(
   user: &User,
   id: Option<&str>,
 ) -> anyhow::Result<()> {
+  if config.schedule_format.is_some()
+    || config.schedule.is_some()
+    || config.schedule_timezone.is_some()
+  {
+    let existing = match id {
+      Some(id) => Some(super::get::<Procedure>(id).await?),
+      None => None,
+    };
+    validate_partial_schedule(
+      config.schedule_format,
+      config.schedule.as_deref(),
+      config.schedule_timezone.as_deref(),
+      existing.as_ref(),
+    )
+    .context("Invalid schedule")?;
+  }
   let Some(stages) = &mut config.stages else {
     return Ok(());
   };
+  validate_stage_conditions(stages)?;
   for stage in stages {
     for exec in &mut stage.executions {
       match &mut exec.execution {
@@ -266,6 +287,16 @@ async fn validate_config(
             ));
           }
         }
+        Execution::ReconcileDeployment(params) => {
+          let deployment =
+            super::get_check_permissions::<Deployment>(
+              &params.deployment,
+              user,
+              PermissionLevel::Execute.into(),
+            )
+            .await?;
+          params.deployment = deployment.id;
+        }
         Execution::PullDeployment(params) => {
           let deployment =
             super::get_check_permissions::<Deployment>(
@@ -774,7 +805,27 @@ async fn validate_config(
             ));
           }
         }
+        Execution::RedeployVariableReferences(_params) => {
+          if !user.admin {
+            return Err(anyhow!(
+              "Non admin user cannot configure Batch executions"
+            ));
+          }
+        }
         Execution::Sleep(_) => {}
+        Execution::WaitUntil(params) => match params.condition {
+          WaitCondition::DeploymentHealthy => {
+            let deployment =
+              super::get_check_permissions::<Deployment>(
+                &params.target,
+                user,
+                PermissionLevel::Read.into(),
+              )
+              .await?;
+            params.target = deployment.id;
+          }
+          WaitCondition::HttpOk | WaitCondition::Duration => {}
+        },
       }
     }
   }
@@ -15,6 +15,7 @@ use crate::{
   api::write::WriteArgs,
   config::core_config,
   helpers::all_resources::AllResourcesById,
+  metrics,
   state::{all_resources_cache, db_client},
 };
 
@@ -51,6 +52,7 @@ pub fn spawn_resource_refresh_loop() {
     loop {
       interval.tick().await;
       refresh_all().await;
+      metrics::heartbeat("resource_refresh");
     }
   });
 }
@@ -27,6 +27,7 @@ use crate::{
   api::execute::{ExecuteArgs, ExecuteRequest},
   config::core_config,
   helpers::update::init_execution_update,
+  metrics,
   state::db_client,
 };
 
@@ -189,6 +190,8 @@ pub fn spawn_schedule_executor() {
           }
         };
       }
+      drop(lock);
+      metrics::heartbeat("schedule");
     }
   });
 }
@@ -289,46 +292,106 @@ fn cron_parser() -> &'static CronParser {
 fn find_next_occurrence(
   schedule: impl HasSchedule,
 ) -> anyhow::Result<i64> {
-  let cron = match schedule.format() {
+  find_next_occurrences(
+    schedule.format(),
+    schedule.schedule(),
+    schedule.timezone(),
+    1,
+  )?
+  .pop()
+  .context("Failed to find next run time")
+}
+
+/// Finds the next `count` run occurences in UTC ms.
+/// Errors if the schedule or timezone is invalid.
+pub fn find_next_occurrences(
+  format: ScheduleFormat,
+  schedule: &str,
+  timezone: &str,
+  count: usize,
+) -> anyhow::Result<Vec<i64>> {
+  let cron = parse_schedule(format, schedule)?;
+  let timezone = if timezone.is_empty() {
+    core_config().timezone.as_str()
+  } else {
+    timezone
+  };
+  if timezone.is_empty() {
+    let tz_time = chrono::Local::now().with_timezone(&Local);
+    next_occurrences(&cron, tz_time, count)
+  } else {
+    let tz: chrono_tz::Tz =
+      timezone.parse().context("Failed to parse timezone")?;
+    let tz_time = chrono::Local::now().with_timezone(&tz);
+    next_occurrences(&cron, tz_time, count)
+  }
+}
+
+fn parse_schedule(
+  format: ScheduleFormat,
+  schedule: &str,
+) -> anyhow::Result<croner::Cron> {
+  let cron = match format {
     ScheduleFormat::Cron => cron_parser()
-      .parse(schedule.schedule())
+      .parse(schedule)
       .context("Invalid CRON schedule")?,
     ScheduleFormat::English => {
-      let cron =
-        english_to_cron::str_cron_syntax(schedule.schedule())
-          .map_err(|e| {
-            anyhow!("Failed to parse english to cron | {e:?}")
-          })?
-          .split(' ')
-          // croner does not accept year
-          .take(6)
-          .collect::<Vec<_>>()
-          .join(" ");
+      let cron = english_to_cron::str_cron_syntax(schedule)
+        .map_err(|e| {
+          anyhow!("Failed to parse english to cron | {e:?}")
+        })?
+        .split(' ')
+        // croner does not accept year
+        .take(6)
+        .collect::<Vec<_>>()
+        .join(" ");
       cron_parser()
         .parse(&cron)
         .with_context(|| format!("English expression produced invalid CRON schedule | produced: {cron}"))?
     }
   };
-  let next =
-    match (schedule.timezone(), core_config().timezone.as_str()) {
-      ("", "") => {
-        let tz_time = chrono::Local::now().with_timezone(&Local);
-        cron
-          .find_next_occurrence(&tz_time, false)
-          .context("Failed to find next run time")?
-          .timestamp_millis()
-      }
-      ("", timezone) | (timezone, _) => {
-        let tz: chrono_tz::Tz =
-          timezone.parse().context("Failed to parse timezone")?;
-        let tz_time = chrono::Local::now().with_timezone(&tz);
-        cron
-          .find_next_occurrence(&tz_time, false)
-          .context("Failed to find next run time")?
-          .timestamp_millis()
-      }
-    };
-  Ok(next)
+  Ok(cron)
+}
+
+fn next_occurrences<Tz: chrono::TimeZone>(
+  cron: &croner::Cron,
+  mut time: chrono::DateTime<Tz>,
+  count: usize,
+) -> anyhow::Result<Vec<i64>> {
+  let mut occurrences = Vec::with_capacity(count);
+  for _ in 0..count {
+    time = cron
+      .find_next_occurrence(&time, false)
+      .context("Failed to find next run time")?;
+    occurrences.push(time.timestamp_millis());
+  }
+  Ok(occurrences)
+}
+
+/// Validates the schedule in a partial config at save time.
+/// Fields not included in the partial config fall back to the existing config.
+pub fn validate_partial_schedule(
+  format: Option<ScheduleFormat>,
+  schedule: Option<&str>,
+  timezone: Option<&str>,
+  existing: Option<impl HasSchedule>,
+) -> anyhow::Result<()> {
+  if format.is_none() && schedule.is_none() && timezone.is_none() {
+    return Ok(());
+  }
+  let format = format
+    .or(existing.as_ref().map(|existing| existing.format()))
+    .unwrap_or_default();
+  let schedule = schedule
+    .or(existing.as_ref().map(|existing| existing.schedule()))
+    .unwrap_or_default();
+  let timezone = timezone
+    .or(existing.as_ref().map(|existing| existing.timezone()))
+    .unwrap_or_default();
+  if schedule.is_empty() {
+    return Ok(());
+  }
+  find_next_occurrences(format, schedule, timezone, 1).map(|_| ())
 }
 
 pub trait HasSchedule {
@@ -374,3 +437,95 @@ impl HasSchedule for &Action {
     &self.config.schedule_timezone
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use super::*;
+
+  const NEW_YORK: chrono_tz::Tz = chrono_tz::America::New_York;
+
+  /// Runs the schedule from local midnight on the given day,
+  /// returning the occurrences as RFC 3339 local times.
+  fn occurrences(
+    schedule: &str,
+    (year, month, day): (i32, u32, u32),
+    count: usize,
+  ) -> Vec<String> {
+    let cron =
+      parse_schedule(ScheduleFormat::Cron, schedule).unwrap();
+    let start = NEW_YORK
+      .with_ymd_and_hms(year, month, day, 0, 0, 0)
+      .unwrap();
+    next_occurrences(&cron, start, count)
+      .unwrap()
+      .into_iter()
+      .map(|ms| {
+        NEW_YORK.timestamp_millis_opt(ms).unwrap().to_rfc3339()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn spring_forward_runs_skipped_time_after_gap() {
+    // 02:00 - 03:00 does not exist on 2025-03-09 in New York.
+    assert_eq!(
+      occurrences("0 30 2 * * *", (2025, 3, 8), 3),
+      [
+        "2025-03-08T02:30:00-05:00",
+        "2025-03-09T03:00:00-04:00",
+        "2025-03-10T02:30:00-04:00",
+      ]
+    );
+    assert_eq!(
+      occurrences("0 0 * * * *", (2025, 3, 9), 3),
+      [
+        "2025-03-09T01:00:00-05:00",
+        "2025-03-09T03:00:00-04:00",
+        "2025-03-09T04:00:00-04:00",
+      ]
+    );
+  }
+
+  #[test]
+  fn fall_back_runs_repeated_time_once() {
+    // 01:00 - 02:00 happens twice on 2025-11-02 in New York.
+    assert_eq!(
+      occurrences("0 30 1 * * *", (2025, 11, 1), 3),
+      [
+        "2025-11-01T01:30:00-04:00",
+        "2025-11-02T01:30:00-04:00",
+        "2025-11-03T01:30:00-05:00",
+      ]
+    );
+    assert_eq!(
+      occurrences("0 0 * * * *", (2025, 11, 2), 3),
+      [
+        "2025-11-02T01:00:00-04:00",
+        "2025-11-02T02:00:00-05:00",
+        "2025-11-02T03:00:00-05:00",
+      ]
+    );
+  }
+
+  #[test]
+  fn validates_schedule_in_dst_timezone() {
+    validate_partial_schedule(
+      Some(ScheduleFormat::Cron),
+      Some("0 30 2 * * *"),
+      Some("America/New_York"),
+      None::<&Procedure>,
+    )
+    .unwrap();
+    assert!(
+      validate_partial_schedule(
+        Some(ScheduleFormat::Cron),
+        Some("0 30 2 * * *"),
+        Some("America/Nowhere"),
+        None::<&Procedure>,
+      )
+      .is_err()
+    );
+  }
+}
@@ -33,10 +33,16 @@ use resolver_api::Resolve;
 
 use crate::{
   api::{
-    auth::AuthArgs,
-    execute::{ExecuteArgs, ExecuteRequest},
+    execute::{
+      ExecuteArgs, ExecuteRequest, resume_queued_executions,
+      take_queued_executions,
+    },
     write::WriteArgs,
   },
+  auth::{
+    bootstrap::init_bootstrap_token,
+    local::{sign_up_local_user, validate_password},
+  },
   config::core_config,
   helpers::update::init_execution_update,
   network, resource,
@@ -103,16 +109,31 @@ pub async fn on_startup() {
   // Configure manual network interface if specified
   network::configure_internet_gateway().await;
 
+  // Take the queued executions first, so their updates
+  // aren't cleaned up as interrupted.
+  let queued = take_queued_executions().await;
+  let queued_update_ids = queued
+    .iter()
+    .map(|queued| queued.update_id.as_str())
+    .collect::<Vec<_>>();
+
   tokio::join!(
-    in_progress_update_cleanup(),
+    init_bootstrap_token(),
+    in_progress_update_cleanup(&queued_update_ids),
     open_alert_cleanup(),
     clean_up_server_templates(),
     ensure_first_server_and_builder(),
     ensure_init_user_and_resources(),
   );
+
+  resume_queued_executions(queued).await;
 }
 
-async fn in_progress_update_cleanup() {
+async fn in_progress_update_cleanup(exclude_update_ids: &[&str]) {
+  let exclude_update_ids = exclude_update_ids
+    .iter()
+    .filter_map(|id| ObjectId::from_str(id).ok())
+    .collect::<Vec<_>>();
   let log = Log::error(
     "Komodo shutdown",
     String::from(
@@ -124,7 +145,10 @@ async fn in_progress_update_cleanup() {
   if let Err(e) = db_client()
     .updates
     .update_many(
-      doc! { "status": "InProgress" },
+      doc! {
+        "status": "InProgress",
+        "_id": { "$nin": exclude_update_ids },
+      },
       doc! {
         "$set": {
           "status": "Complete",
@@ -277,13 +301,35 @@ async fn ensure_init_user_and_resources() {
   let config = core_config();
 
   // Init admin user if set in config.
-  if let Some(username) = &config.init_admin_username {
+  if let Some(username) = &config.init_admin_username
+    && config.require_bootstrap_token
+  {
+    info!(
+      "Not creating init admin user {username}, the first admin is claimed with the bootstrap token."
+    );
+  } else if let Some(username) = &config.init_admin_username {
     info!("Creating init admin user...");
-    SignUpLocalUser {
-      username: username.clone(),
-      password: config.init_admin_password.clone(),
+    if config.init_admin_password == "changeme" {
+      warn!(
+        "{} | The init admin password is still the default 'changeme'. Set KOMODO_INIT_ADMIN_PASSWORD, or change it after logging in.",
+        "WARNING".red()
+      );
+    } else if let Err(e) =
+      validate_password(&config.init_admin_password)
+    {
+      warn!(
+        "{} | The init admin password is weak ({e:#}). Change it after logging in.",
+        "WARNING".red()
+      );
     }
-    .resolve(&AuthArgs::default())
+    sign_up_local_user(
+      SignUpLocalUser {
+        username: username.clone(),
+        password: config.init_admin_password.clone(),
+        bootstrap_token: None,
+      },
+      false,
+    )
     .await
     .expect("Failed to initialize default admin user.");
     db.users
@@ -325,6 +371,9 @@ async fn ensure_init_user_and_resources() {
       .stages(vec![ProcedureStage {
         name: String::from("Stage 1"),
         enabled: true,
+        condition: Default::default(),
+        condition_stage: Default::default(),
+        max_concurrency: Default::default(),
         executions: vec![
           EnabledExecution {
             execution: Execution::BackupCoreDatabase(BackupCoreDatabase {}),
@@ -357,6 +406,7 @@ async fn ensure_init_user_and_resources() {
         "Triggers the Core database backup at the scheduled time.",
       )),
       template: None,
+      locked: None,
     }).resolve(&write_args).await {
       warn!("Failed to update default database backup Procedure tags / description | {:#}", e.error);
     }
@@ -368,6 +418,9 @@ async fn ensure_init_user_and_resources() {
       .stages(vec![ProcedureStage {
         name: String::from("Stage 1"),
         enabled: true,
+        condition: Default::default(),
+        condition_stage: Default::default(),
+        max_concurrency: Default::default(),
         executions: vec![
           EnabledExecution {
             execution: Execution::GlobalAutoUpdate(GlobalAutoUpdate {}),
@@ -403,6 +456,7 @@ async fn ensure_init_user_and_resources() {
         "Pulls and auto updates Stacks and Deployments using 'poll_for_updates' or 'auto_update'.",
       )),
       template: None,
+      locked: None,
     })
     .resolve(&write_args)
     .await
@@ -148,6 +148,16 @@ pub fn deployment_status_cache() -> &'static DeploymentStatusCache {
   DEPLOYMENT_STATUS_CACHE.get_or_init(Default::default)
 }
 
+/// Cache of deployment ids to the differences between
+/// the running container and the deployment config.
+pub type DeploymentDriftCache = Cache<String, Vec<String>>;
+
+pub fn deployment_drift_cache() -> &'static DeploymentDriftCache {
+  static DEPLOYMENT_DRIFT_CACHE: OnceLock<DeploymentDriftCache> =
+    OnceLock::new();
+  DEPLOYMENT_DRIFT_CACHE.get_or_init(Default::default)
+}
+
 pub type StackStatusCache =
   Cache<String, Arc<History<CachedStackStatus, StackState>>>;
 
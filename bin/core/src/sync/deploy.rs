@@ -384,6 +384,8 @@ fn build_cache_for_deployment<'a>(
           || diff.ports.is_some()
           || diff.volumes.is_some()
           || diff.environment.is_some()
+          || diff.env_files.is_some()
+          || diff.linked_repo.is_some()
           || diff.labels.is_some();
         if changed {
           cache.insert(
@@ -167,6 +167,7 @@ pub trait ExecuteResourceSync: ResourceSyncTrait {
         ResourceMetaUpdate {
           description: Some(resource.description),
           template: Some(resource.template),
+          locked: None,
           tags: Some(resource.tags),
         },
         &mut log,
@@ -196,6 +197,7 @@ pub trait ExecuteResourceSync: ResourceSyncTrait {
         description: update_description
           .then(|| resource.description.clone()),
         template: update_template.then_some(resource.template),
+        locked: None,
         tags: update_tags.then(|| resource.tags.clone()),
       };
 
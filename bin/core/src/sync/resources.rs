@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use formatting::{Color, bold, colored, muted};
 use komodo_client::{
-  api::execute::Execution,
+  api::execute::{Execution, WaitCondition},
   entities::{
     ResourceTargetVariant,
     action::Action,
@@ -72,6 +72,12 @@ impl ResourceSyncTrait for Deployment {
         version: *version,
       };
     }
+    // Replace linked repo with name
+    original.linked_repo = resources
+      .repos
+      .get(&original.linked_repo)
+      .map(|r| r.name.clone())
+      .unwrap_or_default();
 
     Ok(original.partial_diff(update))
   }
@@ -353,6 +359,13 @@ impl ResourceSyncTrait for Procedure {
               .unwrap_or_default();
           }
           Execution::BatchDeploy(_config) => {}
+          Execution::ReconcileDeployment(config) => {
+            config.deployment = resources
+              .deployments
+              .get(&config.deployment)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
           Execution::PullDeployment(config) => {
             config.deployment = resources
               .deployments
@@ -692,7 +705,18 @@ impl ResourceSyncTrait for Procedure {
           Execution::ClearRepoCache(_) => {}
           Execution::BackupCoreDatabase(_) => {}
           Execution::GlobalAutoUpdate(_) => {}
+          Execution::RedeployVariableReferences(_) => {}
           Execution::Sleep(_) => {}
+          Execution::WaitUntil(config) => match config.condition {
+            WaitCondition::DeploymentHealthy => {
+              config.target = resources
+                .deployments
+                .get(&config.target)
+                .map(|d| d.name.clone())
+                .unwrap_or_default();
+            }
+            WaitCondition::HttpOk | WaitCondition::Duration => {}
+          },
         }
       }
     }
@@ -771,6 +795,7 @@ impl ExecuteResourceSync for Procedure {
           description: update_description
             .then(|| resource.description.clone()),
           template: update_template.then(|| resource.template),
+          locked: None,
           tags: update_tags.then(|| resource.tags.clone()),
         };
 
@@ -847,6 +872,7 @@ impl ExecuteResourceSync for Procedure {
           ResourceMetaUpdate {
             description: Some(resource.description.clone()),
             template: Some(resource.template),
+            locked: None,
             tags: Some(resource.tags.clone()),
           },
           &mut log,
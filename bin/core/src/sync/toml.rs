@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use anyhow::Context;
 use indexmap::IndexMap;
 use komodo_client::{
-  api::execute::Execution,
+  api::execute::{Execution, WaitCondition},
   entities::{
     action::Action,
     alerter::Alerter,
@@ -232,6 +232,13 @@ impl ToToml for Deployment {
           .unwrap_or(&String::new()),
       );
     }
+    resource.config.linked_repo.clone_from(
+      all
+        .repos
+        .get(&resource.config.linked_repo)
+        .map(|r| &r.name)
+        .unwrap_or(&String::new()),
+    );
   }
 
   fn edit_config_object(
@@ -438,6 +445,15 @@ impl ToToml for Procedure {
               .unwrap_or(&String::new()),
           ),
           Execution::BatchDeploy(_exec) => {}
+          Execution::ReconcileDeployment(exec) => {
+            exec.deployment.clone_from(
+              all
+                .deployments
+                .get(&exec.deployment)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
           Execution::PullDeployment(exec) => {
             exec.deployment.clone_from(
               all
@@ -806,11 +822,24 @@ impl ToToml for Procedure {
               )
             })
           }
+          Execution::WaitUntil(exec) => match exec.condition {
+            WaitCondition::DeploymentHealthy => {
+              exec.target.clone_from(
+                all
+                  .deployments
+                  .get(&exec.target)
+                  .map(|r| &r.name)
+                  .unwrap_or(&String::new()),
+              )
+            }
+            WaitCondition::HttpOk | WaitCondition::Duration => {}
+          },
           Execution::None(_)
           | Execution::Sleep(_)
           | Execution::ClearRepoCache(_)
           | Execution::BackupCoreDatabase(_)
-          | Execution::GlobalAutoUpdate(_) => {}
+          | Execution::GlobalAutoUpdate(_)
+          | Execution::RedeployVariableReferences(_) => {}
         }
       }
     }
@@ -839,14 +868,27 @@ impl ToToml for Procedure {
         .context("failed to serialize procedures to toml")?,
     );
 
-    if let Some(stages) = stages {
+    if let Some(mut stages) = stages {
       let stages =
-        stages.as_array().context("stages is not array")?;
+        stages.as_array_mut().context("stages is not array")?;
       for stage in stages {
+        // Don't include the default stage condition / concurrency
+        if let Some(stage) = stage.as_object_mut() {
+          if stage.get("condition").and_then(|c| c.as_str())
+            == Some("Success")
+          {
+            stage.remove("condition");
+          }
+          if stage.get("max_concurrency").and_then(|c| c.as_i64())
+            == Some(0)
+          {
+            stage.remove("max_concurrency");
+          }
+        }
         toml.push_str("\n\n[[procedure.config.stage]]\n");
         toml.push_str(
           &toml_pretty::to_string(
-            stage,
+            &*stage,
             // If the execution.params are fully missing,
             // deserialization will fail.
             TOML_PRETTY_OPTIONS.skip_empty_object(false),
@@ -1,5 +1,5 @@
 use axum::{
-  extract::{Query, WebSocketUpgrade, ws::Message},
+  extract::{WebSocketUpgrade, ws::Message},
   response::IntoResponse,
 };
 use futures::SinkExt;
@@ -8,15 +8,15 @@ use komodo_client::{
   entities::{permission::PermissionLevel, server::Server},
 };
 
-use crate::permission::get_check_permissions;
+use crate::{permission::get_check_permissions, ws::Qs};
 
 #[instrument(name = "ConnectContainerExec", skip(ws))]
 pub async fn terminal(
-  Query(ConnectContainerExecQuery {
+  Qs(ConnectContainerExecQuery {
     server,
     container,
     shell,
-  }): Query<ConnectContainerExecQuery>,
+  }): Qs<ConnectContainerExecQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
   ws.on_upgrade(|socket| async move {
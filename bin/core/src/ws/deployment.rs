@@ -1,5 +1,5 @@
 use axum::{
-  extract::{Query, WebSocketUpgrade, ws::Message},
+  extract::{WebSocketUpgrade, ws::Message},
   response::IntoResponse,
 };
 use futures::SinkExt;
@@ -11,11 +11,13 @@ use komodo_client::{
   },
 };
 
-use crate::{permission::get_check_permissions, resource::get};
+use crate::{
+  permission::get_check_permissions, resource::get, ws::Qs,
+};
 
 #[instrument(name = "ConnectDeploymentExec", skip(ws))]
 pub async fn terminal(
-  Query(ConnectDeploymentExecQuery { deployment, shell }): Query<
+  Qs(ConnectDeploymentExecQuery { deployment, shell }): Qs<
     ConnectDeploymentExecQuery,
   >,
   ws: WebSocketUpgrade,
@@ -1,11 +1,16 @@
 use crate::{
   auth::{auth_api_key_check_enabled, auth_jwt_check_enabled},
+  config::core_config,
   helpers::query::get_user,
 };
 use anyhow::anyhow;
 use axum::{
   Router,
-  extract::ws::{CloseFrame, Message, Utf8Bytes, WebSocket},
+  extract::{
+    FromRequestParts,
+    ws::{CloseFrame, Message, Utf8Bytes, WebSocket},
+  },
+  http::request::Parts,
   routing::get,
 };
 use futures::{SinkExt, StreamExt};
@@ -13,6 +18,9 @@ use komodo_client::{
   entities::{server::Server, user::User},
   ws::WsLoginMessage,
 };
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serror::AddStatusCodeError;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{
   MaybeTlsStream, WebSocketStream, tungstenite,
@@ -25,6 +33,8 @@ mod stack;
 mod terminal;
 mod update;
 
+pub use update::user_can_see_update;
+
 pub fn router() -> Router {
   Router::new()
     .route("/update", get(update::handler))
@@ -34,6 +44,72 @@ pub fn router() -> Router {
     .route("/stack/terminal", get(stack::terminal))
 }
 
+/// Query string extractor using [serde_qs].
+///
+/// Queries longer than `max_query_length` or nested deeper than
+/// `max_query_depth` are rejected before parsing.
+pub struct Qs<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Qs<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = serror::Error;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    _: &S,
+  ) -> Result<Self, Self::Rejection> {
+    let config = core_config();
+    parse_query(
+      parts.uri.query().unwrap_or_default(),
+      config.max_query_length,
+      config.max_query_depth,
+    )
+    .map(Qs)
+  }
+}
+
+fn parse_query<T: DeserializeOwned>(
+  query: &str,
+  max_length: usize,
+  max_depth: usize,
+) -> serror::Result<T> {
+  if query.len() > max_length {
+    return Err(
+      anyhow!(
+        "Query string length {} exceeds maximum of {max_length}",
+        query.len(),
+      )
+      .status_code(StatusCode::URI_TOO_LONG),
+    );
+  }
+  if let Some(key) = query.split('&').find_map(|param| {
+    let key = param.split('=').next().unwrap_or_default();
+    let key = urlencoding::decode(key).ok()?;
+    (key.matches('[').count() > max_depth).then(|| key.into_owned())
+  }) {
+    return Err(
+      anyhow!(
+        "Query parameter '{key}' exceeds maximum depth of {max_depth}"
+      )
+      .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  // Not strict, so percent encoded brackets are accepted.
+  serde_qs::Config::new(max_depth, false)
+    .deserialize_str(query)
+    .map_err(|e| {
+      let e = if query.is_empty() {
+        anyhow!("Missing query string | {e}")
+      } else {
+        anyhow!("Invalid query string | {e}")
+      };
+      e.status_code(StatusCode::BAD_REQUEST)
+    })
+}
+
 #[instrument(level = "debug")]
 async fn ws_login(
   mut socket: WebSocket,
@@ -288,3 +364,53 @@ fn tungstenite_to_axum(msg: tungstenite::Message) -> Message {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use serde::Deserialize;
+
+  use super::*;
+
+  #[derive(Deserialize, Debug)]
+  struct Query {
+    server: String,
+    #[serde(default)]
+    env: std::collections::HashMap<String, String>,
+  }
+
+  #[test]
+  fn parses_query() {
+    let query: Query =
+      parse_query("server=one&env[KEY]=value", 100, 2).unwrap();
+    assert_eq!(query.server, "one");
+    assert_eq!(query.env["KEY"], "value");
+    let query: Query =
+      parse_query("server=one&env%5BKEY%5D=value", 100, 2).unwrap();
+    assert_eq!(query.env["KEY"], "value");
+  }
+
+  #[test]
+  fn rejects_long_query() {
+    let e = parse_query::<Query>("server=one", 5, 2).unwrap_err();
+    assert_eq!(e.status, StatusCode::URI_TOO_LONG);
+  }
+
+  #[test]
+  fn rejects_deep_query() {
+    let e = parse_query::<Query>("server=one&a[b][c]=d", 100, 1)
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    // Encoded brackets count too
+    let e =
+      parse_query::<Query>("server=one&a%5Bb%5D%5Bc%5D=d", 100, 1)
+        .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+  }
+
+  #[test]
+  fn missing_query() {
+    let e = parse_query::<Query>("", 100, 2).unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    assert!(e.error.to_string().starts_with("Missing query string"));
+  }
+}
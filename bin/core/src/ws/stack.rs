@@ -1,5 +1,5 @@
 use axum::{
-  extract::{Query, WebSocketUpgrade, ws::Message},
+  extract::{WebSocketUpgrade, ws::Message},
   response::IntoResponse,
 };
 use futures::SinkExt;
@@ -12,16 +12,16 @@ use komodo_client::{
 
 use crate::{
   permission::get_check_permissions, resource::get,
-  state::stack_status_cache,
+  state::stack_status_cache, ws::Qs,
 };
 
 #[instrument(name = "ConnectStackExec", skip(ws))]
 pub async fn terminal(
-  Query(ConnectStackExecQuery {
+  Qs(ConnectStackExecQuery {
     stack,
     service,
     shell,
-  }): Query<ConnectStackExecQuery>,
+  }): Qs<ConnectStackExecQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
   ws.on_upgrade(|socket| async move {
@@ -1,5 +1,5 @@
 use axum::{
-  extract::{Query, WebSocketUpgrade, ws::Message},
+  extract::{WebSocketUpgrade, ws::Message},
   response::IntoResponse,
 };
 use futures::SinkExt;
@@ -9,13 +9,14 @@ use komodo_client::{
 };
 
 use crate::{
-  helpers::periphery_client, permission::get_check_permissions,
-  ws::core_periphery_forward_ws,
+  helpers::periphery_client,
+  permission::get_check_permissions,
+  ws::{Qs, core_periphery_forward_ws},
 };
 
 #[instrument(name = "ConnectTerminal", skip(ws))]
 pub async fn handler(
-  Query(ConnectTerminalQuery { server, terminal }): Query<
+  Qs(ConnectTerminalQuery { server, terminal }): Qs<
     ConnectTerminalQuery,
   >,
   ws: WebSocketUpgrade,
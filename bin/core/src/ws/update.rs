@@ -83,7 +83,7 @@ pub async fn handler(ws: WebSocketUpgrade) -> impl IntoResponse {
 }
 
 #[instrument(level = "debug")]
-async fn user_can_see_update(
+pub async fn user_can_see_update(
   user: &User,
   update_target: &ResourceTarget,
 ) -> anyhow::Result<()> {
@@ -5,7 +5,8 @@ use std::{
 
 use anyhow::{Context, anyhow};
 use command::{
-  run_komodo_command, run_komodo_command_with_sanitization,
+  run_komodo_command, run_komodo_command_combined,
+  run_komodo_command_with_sanitization, sanitize_log,
 };
 use formatting::format_serror;
 use interpolate::Interpolator;
@@ -25,10 +26,13 @@ use resolver_api::Resolve;
 use tokio::fs;
 
 use crate::{
-  build::{parse_build_args, parse_secret_args, write_dockerfile},
+  build::{
+    lint_dockerfile, parse_build_args, parse_secret_args,
+    write_dockerfile,
+  },
   config::periphery_config,
   docker::docker_login,
-  helpers::{parse_extra_args, parse_labels},
+  helpers::{parse_extra_args, parse_labels, write_file_with_backup},
 };
 
 impl Resolve<super::Args> for GetDockerfileContentsOnHost {
@@ -88,7 +92,11 @@ impl Resolve<super::Args> for WriteDockerfileContentsToHost {
       build_path,
       dockerfile_path,
       contents,
+      validate,
     } = self;
+    if validate {
+      lint_dockerfile(&contents)?;
+    }
     let full_path = periphery_config()
       .build_dir()
       .join(to_path_compatible_name(&name))
@@ -104,13 +112,21 @@ impl Resolve<super::Args> for WriteDockerfileContentsToHost {
         .await
         .with_context(|| format!("Failed to initialize dockerfile parent directory {parent:?}"))?;
     }
-    fs::write(&full_path, contents).await.with_context(|| {
-      format!("Failed to write dockerfile contents to {full_path:?}")
-    })?;
-    Ok(Log::simple(
-      "Write dockerfile to host",
-      format!("dockerfile contents written to {full_path:?}"),
-    ))
+    let backup_path = write_file_with_backup(&full_path, &contents)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to write dockerfile contents to {full_path:?}"
+        )
+      })?;
+    let mut stdout =
+      format!("dockerfile contents written to {full_path:?}");
+    if let Some(backup_path) = backup_path {
+      stdout.push_str(&format!(
+        "\nPrevious contents backed up to {backup_path:?}"
+      ));
+    }
+    Ok(Log::simple("Write dockerfile to host", stdout))
   }
 }
 
@@ -297,17 +313,19 @@ impl Resolve<super::Args> for build::Build {
       "docker{buildx} build{build_args}{command_secret_args}{extra_args}{labels}{image_tags}{maybe_push} -f {dockerfile_path} .",
     );
 
-    if let Some(build_log) = run_komodo_command_with_sanitization(
-      "Docker Build",
-      build_path.as_ref(),
-      command,
-      false,
-      &replacers,
-    )
-    .await
-    {
-      logs.push(build_log);
+    let mut build_log = if periphery_config().combined_build_output {
+      run_komodo_command_combined(
+        "Docker Build",
+        build_path.as_ref(),
+        command,
+      )
+      .await
+    } else {
+      run_komodo_command("Docker Build", build_path.as_ref(), command)
+        .await
     };
+    sanitize_log(&mut build_log, &replacers);
+    logs.push(build_log);
 
     Ok(logs)
   }
@@ -3,7 +3,7 @@ use command::{
   run_komodo_command, run_komodo_command_with_sanitization,
 };
 use formatting::format_serror;
-use git::write_commit_file;
+use git::{sync_with_remote, write_commit_file};
 use interpolate::Interpolator;
 use komodo_client::entities::{
   FileContents, RepoExecutionResponse, all_logs_success,
@@ -25,10 +25,11 @@ use crate::{
   compose::{
     docker_compose, env_file_args, pull_or_clone_stack,
     up::{maybe_login_registry, validate_files},
+    validate_compose_contents,
     write::write_stack,
   },
   config::periphery_config,
-  helpers::{log_grep, parse_extra_args},
+  helpers::{log_grep, parse_extra_args, write_file_with_backup},
 };
 
 impl Resolve<super::Args> for ListComposeProjects {
@@ -220,6 +221,7 @@ impl Resolve<super::Args> for WriteComposeContentsToHost {
       run_directory,
       file_path,
       contents,
+      validate,
     } = self;
     let file_path = periphery_config()
       .stack_dir()
@@ -234,15 +236,24 @@ impl Resolve<super::Args> for WriteComposeContentsToHost {
         .await
         .with_context(|| format!("Failed to initialize compose file parent directory {parent:?}"))?;
     }
-    fs::write(&file_path, contents).await.with_context(|| {
-      format!(
-        "Failed to write compose file contents to {file_path:?}"
-      )
-    })?;
-    Ok(Log::simple(
-      "Write contents to host",
-      format!("File contents written to {file_path:?}"),
-    ))
+    if validate {
+      validate_compose_contents(&file_path, &contents).await?;
+    }
+    let backup_path = write_file_with_backup(&file_path, &contents)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to write compose file contents to {file_path:?}"
+        )
+      })?;
+    let mut stdout =
+      format!("File contents written to {file_path:?}");
+    if let Some(backup_path) = backup_path {
+      stdout.push_str(&format!(
+        "\nPrevious contents backed up to {backup_path:?}"
+      ));
+    }
+    Ok(Log::simple("Write contents to host", stdout))
   }
 }
 
@@ -269,11 +280,18 @@ impl Resolve<super::Args> for WriteCommitComposeContents {
       file_path,
       contents,
       git_token,
+      sync_strategy,
     } = self;
 
     let root =
       pull_or_clone_stack(&stack, repo.as_ref(), git_token).await?;
 
+    // Make sure the commit can be pushed before writing.
+    let sync_logs =
+      sync_with_remote(&root, &stack.config.branch, sync_strategy)
+        .await
+        .context("Repo is not in sync with remote")?;
+
     let file_path = stack
       .config
       .run_directory
@@ -287,15 +305,17 @@ impl Resolve<super::Args> for WriteCommitComposeContents {
       "Write Compose File".to_string()
     };
 
-    write_commit_file(
+    let mut res = write_commit_file(
       &msg,
       &root,
       &file_path,
       &contents,
       &stack.config.branch,
     )
-    .await
-    .map_err(Into::into)
+    .await?;
+    res.logs.splice(0..0, sync_logs);
+
+    Ok(res)
   }
 }
 
@@ -446,6 +466,9 @@ impl Resolve<super::Args> for ComposeUp {
     interpolator
       .interpolate_stack(&mut stack)?
       .push_logs(&mut res.logs);
+    interpolator
+      .ensure_resolved()
+      .context("Failed to interpolate Stack")?;
     replacers.extend(interpolator.secret_replacers);
 
     let (run_directory, env_file_path) = match write_stack(
@@ -1,21 +1,36 @@
 use anyhow::Context;
+use axum::{
+  extract::{Query, WebSocketUpgrade, ws::Message},
+  response::Response,
+};
 use command::run_komodo_command;
-use futures::future::join_all;
+use futures::{SinkExt, StreamExt, future::join_all};
 use komodo_client::entities::{
   docker::{
-    container::{Container, ContainerListItem, ContainerStats},
+    container::{
+      Container, ContainerListItem, ContainerStats,
+      ContainerStatsSample,
+    },
     stats::FullContainerStats,
   },
   update::Log,
 };
 use periphery_client::api::container::*;
 use resolver_api::Resolve;
+use tokio::{sync::broadcast::error::RecvError, time::Instant};
 
 use crate::{
+  compose::generate::containers_to_compose,
   docker::{
-    docker_client, stats::get_container_stats, stop_container_command,
+    docker_client, docker_version,
+    events::container_events,
+    run_container_action,
+    stats::{get_container_stats, get_container_stats_history},
+    stop_container_command,
   },
   helpers::log_grep,
+  ping::AdaptivePing,
+  terminal::auth_tokens,
 };
 
 // ======
@@ -36,6 +51,20 @@ impl Resolve<super::Args> for InspectContainer {
 
 //
 
+impl Resolve<super::Args> for GetComposeFromContainers {
+  #[instrument(name = "GetComposeFromContainers", level = "debug")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<String> {
+    let client = docker_client();
+    let mut containers = Vec::with_capacity(self.names.len());
+    for name in &self.names {
+      containers.push(client.inspect_container(name).await?);
+    }
+    Ok(containers_to_compose(containers)?)
+  }
+}
+
+//
+
 impl Resolve<super::Args> for GetContainerLog {
   #[instrument(name = "GetContainerLog", level = "debug")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -125,6 +154,18 @@ impl Resolve<super::Args> for GetContainerStatsList {
   }
 }
 
+impl Resolve<super::Args> for GetContainerStatsHistory {
+  #[instrument(name = "GetContainerStatsHistory", level = "debug")]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<ContainerStatsSample>> {
+    Ok(get_container_stats_history(&self.name))
+  }
+}
+
+//
+
 // =========
 //  ACTIONS
 // =========
@@ -133,10 +174,11 @@ impl Resolve<super::Args> for StartContainer {
   #[instrument(name = "StartContainer")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
     Ok(
-      run_komodo_command(
+      run_container_action(
         "Docker Start",
-        None,
+        &self.name,
         format!("docker start {}", self.name),
+        |docker| docker.start_container(&self.name),
       )
       .await,
     )
@@ -149,10 +191,11 @@ impl Resolve<super::Args> for RestartContainer {
   #[instrument(name = "RestartContainer")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
     Ok(
-      run_komodo_command(
+      run_container_action(
         "Docker Restart",
-        None,
+        &self.name,
         format!("docker restart {}", self.name),
+        |docker| docker.restart_container(&self.name),
       )
       .await,
     )
@@ -194,9 +237,23 @@ impl Resolve<super::Args> for UnpauseContainer {
 impl Resolve<super::Args> for StopContainer {
   #[instrument(name = "StopContainer")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let StopContainer { name, signal, time } = self;
+    let StopContainer {
+      name,
+      mut signal,
+      time,
+    } = self;
+    if docker_version()
+      .await
+      .is_some_and(|version| !version.capabilities.stop_signal)
+    {
+      signal = None;
+    }
     let command = stop_container_command(&name, signal, time);
-    let log = run_komodo_command("Docker Stop", None, command).await;
+    let log =
+      run_container_action("Docker Stop", &name, command, |docker| {
+        docker.stop_container(&name, signal, time)
+      })
+      .await;
     if log.stderr.contains("unknown flag: --signal") {
       let command = stop_container_command(&name, None, time);
       let mut log =
@@ -221,13 +278,27 @@ impl Resolve<super::Args> for StopContainer {
 impl Resolve<super::Args> for RemoveContainer {
   #[instrument(name = "RemoveContainer")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let RemoveContainer { name, signal, time } = self;
+    let RemoveContainer {
+      name,
+      mut signal,
+      time,
+    } = self;
+    if docker_version()
+      .await
+      .is_some_and(|version| !version.capabilities.stop_signal)
+    {
+      signal = None;
+    }
     let stop_command = stop_container_command(&name, signal, time);
     let command =
       format!("{stop_command} && docker container rm {name}");
-    let log =
-      run_komodo_command("Docker Stop and Remove", None, command)
-        .await;
+    let log = run_container_action(
+      "Docker Stop and Remove",
+      &name,
+      command,
+      |docker| docker.stop_and_remove_container(&name, signal, time),
+    )
+    .await;
     if log.stderr.contains("unknown flag: --signal") {
       let stop_command = stop_container_command(&name, None, time);
       let command =
@@ -412,3 +483,69 @@ impl Resolve<super::Args> for StopAllContainers {
     Ok(join_all(futures).await)
   }
 }
+
+// ========
+//  EVENTS
+// ========
+
+/// Streams significant container events to Core as JSON text messages,
+/// see [ContainerEvent][komodo_client::entities::docker::container::ContainerEvent].
+/// The connection is kept alive with [AdaptivePing].
+pub async fn connect_container_events(
+  Query(ConnectContainerEventsQuery { token }): Query<
+    ConnectContainerEventsQuery,
+  >,
+  ws: WebSocketUpgrade,
+) -> serror::Result<Response> {
+  // Auth the connection with single use token
+  auth_tokens().check_token(token)?;
+
+  Ok(ws.on_upgrade(|socket| async move {
+    let (mut ws_write, mut ws_read) = socket.split();
+    let mut events = container_events().subscribe();
+    let mut ping = AdaptivePing::default();
+    let mut next_ping = Instant::now() + ping.interval();
+    loop {
+      tokio::select! {
+        _ = tokio::time::sleep_until(next_ping) => {
+          if !ping.on_ping() {
+            debug!("Container event stream missed too many pings, closing");
+            break;
+          }
+          if ws_write.send(Message::Ping(Default::default())).await.is_err() {
+            break;
+          }
+          next_ping = Instant::now() + ping.interval();
+        }
+        event = events.recv() => {
+          let event = match event {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+              warn!("Container event stream lagged, skipped {skipped} events");
+              continue;
+            }
+            Err(RecvError::Closed) => break,
+          };
+          let json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+              warn!("Failed to serialize container event | {e:?}");
+              continue;
+            }
+          };
+          if let Err(e) = ws_write.send(Message::Text(json.into())).await {
+            debug!("Failed to send container event to WS: {e:?}");
+            break;
+          }
+        }
+        msg = ws_read.next() => match msg {
+          Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+          Some(Ok(Message::Pong(_))) => ping.on_pong(),
+          // Do nothing (ping, etc.)
+          Some(Ok(_)) => {}
+        }
+      }
+    }
+    let _ = ws_write.close().await;
+  }))
+}
@@ -1,4 +1,4 @@
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use command::run_komodo_command_with_sanitization;
 use formatting::format_serror;
 use interpolate::Interpolator;
@@ -45,6 +45,9 @@ impl Resolve<super::Args> for Deploy {
     let mut interpolator =
       Interpolator::new(None, &periphery_config().secrets);
     interpolator.interpolate_deployment(&mut deployment)?;
+    interpolator
+      .ensure_resolved()
+      .context("Failed to interpolate Deployment")?;
     replacers.extend(interpolator.secret_replacers);
 
     let image = if let DeploymentImage::Image { image } =
@@ -123,6 +126,7 @@ fn docker_run_command(
         command,
         restart,
         environment,
+        env_files,
         labels,
         extra_args,
         ..
@@ -141,6 +145,7 @@ fn docker_run_command(
   );
   let network = parse_network(network);
   let restart = parse_restart(restart);
+  let env_files = parse_env_files(env_files)?;
   let environment = parse_environment(
     &environment_vars_from_str(environment)
       .context("Invalid environment")?,
@@ -151,7 +156,7 @@ fn docker_run_command(
   let command = parse_command(command);
   let extra_args = parse_extra_args(extra_args);
   let command = format!(
-    "docker run -d --name {name}{ports}{volumes}{network}{restart}{environment}{labels}{extra_args} {image}{command}"
+    "docker run -d --name {name}{ports}{volumes}{network}{restart}{env_files}{environment}{labels}{extra_args} {image}{command}"
   );
   Ok(command)
 }
@@ -167,6 +172,22 @@ fn parse_conversions(
     .join("")
 }
 
+/// Docker applies `--env` after all `--env-file`,
+/// so the inline environment overrides the files.
+fn parse_env_files(env_files: &[String]) -> anyhow::Result<String> {
+  env_files
+    .iter()
+    .map(|path| {
+      if path.starts_with('/') {
+        Ok(format!(" --env-file {path}"))
+      } else {
+        // Core resolves relative paths against the linked repo.
+        Err(anyhow!("Env file path must be absolute: {path}"))
+      }
+    })
+    .collect()
+}
+
 fn parse_environment(environment: &[EnvironmentVar]) -> String {
   environment
     .iter()
@@ -203,3 +224,26 @@ fn parse_command(command: &str) -> String {
     format!(" {command}")
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn env_files_keep_order() {
+    let env_files = parse_env_files(&[
+      String::from("/etc/komodo/shared.env"),
+      String::from("/etc/komodo/app.env"),
+    ])
+    .unwrap();
+    assert_eq!(
+      env_files,
+      " --env-file /etc/komodo/shared.env --env-file /etc/komodo/app.env"
+    );
+  }
+
+  #[test]
+  fn relative_env_file_is_rejected() {
+    assert!(parse_env_files(&[String::from("app.env")]).is_err());
+  }
+}
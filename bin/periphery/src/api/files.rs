@@ -0,0 +1,401 @@
+use std::{
+  io::ErrorKind,
+  path::{Path, PathBuf},
+  time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, anyhow};
+use axum::http::StatusCode;
+use periphery_client::api::files::{
+  DiffFileContents, DiffFileContentsResponse, DirEntry, ListDir,
+  ReadFile, ReadFileResponse,
+};
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+use tokio::{fs, io::AsyncReadExt};
+
+use crate::{
+  config::periphery_config, diff::unified_diff,
+  helpers::validate_host_file_path,
+};
+
+/// Only the first 1 MiB of a file is returned by [ReadFile].
+const MAX_READ_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Files larger than 1 MiB are not diffed by [DiffFileContents].
+const MAX_DIFF_FILE_SIZE: u64 = 1024 * 1024;
+
+impl Resolve<super::Args> for DiffFileContents {
+  #[instrument(
+    name = "DiffFileContents",
+    level = "debug",
+    skip_all,
+    fields(path = &self.path)
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<DiffFileContentsResponse> {
+    let path = resolve_allowed_file_path(&self.path).await?;
+    let (exists, contents) =
+      match read_diff_contents(&path, MAX_DIFF_FILE_SIZE).await? {
+        CurrentContents::Missing => (false, String::new()),
+        CurrentContents::Text(contents) => (true, contents),
+        CurrentContents::Binary => {
+          return Ok(DiffFileContentsResponse {
+            exists: true,
+            binary: true,
+            diff: String::new(),
+          });
+        }
+      };
+    let diff = unified_diff(
+      &contents,
+      &self.new_contents,
+      &self.path,
+      &format!("{} (new)", self.path),
+    );
+    Ok(DiffFileContentsResponse {
+      exists,
+      binary: false,
+      diff,
+    })
+  }
+}
+
+/// The current contents of the file being diffed.
+#[derive(Debug, PartialEq)]
+enum CurrentContents {
+  Missing,
+  Text(String),
+  Binary,
+}
+
+/// Reads the file to diff against, failing if it
+/// is larger than `max_size` rather than reading it all.
+/// Files which aren't UTF-8 text are binary.
+async fn read_diff_contents(
+  path: &Path,
+  max_size: u64,
+) -> serror::Result<CurrentContents> {
+  let file = match fs::File::open(path).await {
+    Ok(file) => file,
+    Err(e) if e.kind() == ErrorKind::NotFound => {
+      return Ok(CurrentContents::Missing);
+    }
+    Err(e) => {
+      return Err(
+        anyhow::Error::from(e)
+          .context(format!("Failed to open file at {path:?}"))
+          .into(),
+      );
+    }
+  };
+  let mut contents = Vec::new();
+  file
+    .take(max_size + 1)
+    .read_to_end(&mut contents)
+    .await
+    .with_context(|| format!("Failed to read file at {path:?}"))?;
+  if contents.len() as u64 > max_size {
+    return Err(
+      anyhow!(
+        "File at {path:?} is larger than {max_size} bytes, too large to diff"
+      )
+      .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  match String::from_utf8(contents) {
+    Ok(contents) if !contents.contains('\0') => {
+      Ok(CurrentContents::Text(contents))
+    }
+    _ => Ok(CurrentContents::Binary),
+  }
+}
+
+//
+
+impl Resolve<super::Args> for ListDir {
+  #[instrument(
+    name = "ListDir",
+    level = "debug",
+    skip_all,
+    fields(path = &self.path)
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<DirEntry>> {
+    let path = resolve_allowed_path(Path::new(&self.path)).await?;
+    Ok(list_dir(&path).await?)
+  }
+}
+
+/// Lists the directory entries, directories first.
+async fn list_dir(path: &Path) -> anyhow::Result<Vec<DirEntry>> {
+  let mut entries = fs::read_dir(&path)
+    .await
+    .with_context(|| format!("Failed to read directory {path:?}"))?;
+  let mut res = Vec::new();
+  while let Some(entry) = entries
+    .next_entry()
+    .await
+    .with_context(|| format!("Failed to read directory {path:?}"))?
+  {
+    // Doesn't follow symlinks
+    let Ok(metadata) = entry.metadata().await else {
+      continue;
+    };
+    res.push(DirEntry {
+      name: entry.file_name().to_string_lossy().to_string(),
+      is_dir: metadata.is_dir(),
+      is_symlink: metadata.is_symlink(),
+      size: metadata.len(),
+      modified: metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_millis() as i64),
+    });
+  }
+  res.sort_by(|a, b| {
+    b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name))
+  });
+  Ok(res)
+}
+
+//
+
+impl Resolve<super::Args> for ReadFile {
+  #[instrument(
+    name = "ReadFile",
+    level = "debug",
+    skip_all,
+    fields(path = &self.path)
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<ReadFileResponse> {
+    let path = resolve_allowed_path(Path::new(&self.path)).await?;
+    read_file(&path, MAX_READ_FILE_SIZE).await
+  }
+}
+
+/// Reads at most `max_size` bytes from the start of the file.
+async fn read_file(
+  path: &Path,
+  max_size: u64,
+) -> serror::Result<ReadFileResponse> {
+  let file = fs::File::open(&path)
+    .await
+    .with_context(|| format!("Failed to open file {path:?}"))?;
+  let metadata = file.metadata().await.with_context(|| {
+    format!("Failed to read file metadata {path:?}")
+  })?;
+  if metadata.is_dir() {
+    return Err(
+      anyhow!("Path is a directory: {path:?}")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  let mut contents = Vec::new();
+  file
+    .take(max_size)
+    .read_to_end(&mut contents)
+    .await
+    .with_context(|| format!("Failed to read file {path:?}"))?;
+  Ok(ReadFileResponse {
+    contents: String::from_utf8_lossy(&contents).to_string(),
+    size: metadata.len(),
+    truncated: metadata.len() > max_size,
+  })
+}
+
+/// Resolves `..` and symlinks in the requested path, and checks
+/// the result is inside the stack or repo directory, or one of
+/// the configured `allowed_file_directories`.
+async fn resolve_allowed_path(
+  path: &Path,
+) -> serror::Result<PathBuf> {
+  let config = periphery_config();
+  let allowed = [config.stack_dir(), config.repo_dir()]
+    .into_iter()
+    .chain(config.allowed_file_directories.iter().cloned());
+  resolve_path_in(path, allowed).await
+}
+
+/// Resolves `..` and symlinks in the path,
+/// and checks the result is inside one of the allowed directories.
+async fn resolve_path_in(
+  path: &Path,
+  allowed: impl IntoIterator<Item = PathBuf>,
+) -> serror::Result<PathBuf> {
+  if !path.is_absolute() {
+    return Err(
+      anyhow!("Path must be absolute. Got: {path:?}")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  let resolved = fs::canonicalize(path)
+    .await
+    .with_context(|| format!("Failed to resolve path {path:?}"))?;
+  for dir in allowed {
+    // Directories which don't exist yet can't contain the path.
+    let Ok(dir) = fs::canonicalize(&dir).await else {
+      continue;
+    };
+    if resolved.starts_with(&dir) {
+      return Ok(resolved);
+    }
+  }
+  Err(
+    anyhow!("Path {path:?} is outside the allowed directories")
+      .status_code(StatusCode::FORBIDDEN),
+  )
+}
+
+/// As [resolve_allowed_path], but the file may not exist yet,
+/// in which case its parent directory is checked instead.
+async fn resolve_allowed_file_path(
+  path: &str,
+) -> serror::Result<PathBuf> {
+  let (path, parent, file_name) = validate_host_file_path(path)?;
+  if fs::symlink_metadata(path).await.is_ok() {
+    resolve_allowed_path(path).await
+  } else {
+    Ok(resolve_allowed_path(parent).await?.join(file_name))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A fresh directory with an `allowed` directory holding
+  /// `file.txt` and `sub/`, and an `outside` directory holding `secret.txt`.
+  async fn sandbox(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let dir = std::env::temp_dir()
+      .join(format!("komodo-files-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir).await;
+    let allowed = dir.join("allowed");
+    let outside = dir.join("outside");
+    fs::create_dir_all(allowed.join("sub")).await.unwrap();
+    fs::create_dir_all(&outside).await.unwrap();
+    fs::write(allowed.join("file.txt"), "contents")
+      .await
+      .unwrap();
+    fs::write(outside.join("secret.txt"), "secret")
+      .await
+      .unwrap();
+    let dir = fs::canonicalize(&dir).await.unwrap();
+    (dir.clone(), dir.join("allowed"), dir.join("outside"))
+  }
+
+  async fn resolve(
+    path: &Path,
+    allowed: &Path,
+  ) -> serror::Result<PathBuf> {
+    resolve_path_in(path, [allowed.to_path_buf()]).await
+  }
+
+  #[tokio::test]
+  async fn lists_directory() {
+    let (dir, allowed, _) = sandbox("list").await;
+    let path = resolve(&allowed, &allowed).await.unwrap();
+    let entries = list_dir(&path).await.unwrap();
+    let names = entries
+      .iter()
+      .map(|entry| (entry.name.as_str(), entry.is_dir))
+      .collect::<Vec<_>>();
+    assert_eq!(names, [("sub", true), ("file.txt", false)]);
+    assert_eq!(entries[1].size, 8);
+    fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn reads_file() {
+    let (dir, allowed, _) = sandbox("read").await;
+    let path =
+      resolve(&allowed.join("file.txt"), &allowed).await.unwrap();
+    let res = read_file(&path, MAX_READ_FILE_SIZE).await.unwrap();
+    assert_eq!(res.contents, "contents");
+    assert_eq!(res.size, 8);
+    assert!(!res.truncated);
+    let res = read_file(&path, 4).await.unwrap();
+    assert_eq!(res.contents, "cont");
+    assert!(res.truncated);
+    let e = read_file(&allowed.join("sub"), 4).await.unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn reads_diff_contents() {
+    let (dir, allowed, _) = sandbox("diff").await;
+    assert_eq!(
+      read_diff_contents(&allowed.join("file.txt"), 1024)
+        .await
+        .unwrap(),
+      CurrentContents::Text(String::from("contents"))
+    );
+    assert_eq!(
+      read_diff_contents(&allowed.join("missing.txt"), 1024)
+        .await
+        .unwrap(),
+      CurrentContents::Missing
+    );
+    let binary = allowed.join("binary");
+    fs::write(&binary, [0x89, b'P', b'N', b'G', 0, 0xff])
+      .await
+      .unwrap();
+    assert_eq!(
+      read_diff_contents(&binary, 1024).await.unwrap(),
+      CurrentContents::Binary
+    );
+    let e = read_diff_contents(&allowed.join("file.txt"), 4)
+      .await
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn rejects_paths_outside_sandbox() {
+    let (dir, allowed, outside) = sandbox("outside").await;
+    let e = resolve(&outside.join("secret.txt"), &allowed)
+      .await
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::FORBIDDEN);
+    let e = resolve(&allowed.join("../outside/secret.txt"), &allowed)
+      .await
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::FORBIDDEN);
+    let e = resolve(Path::new("allowed/file.txt"), &allowed)
+      .await
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::BAD_REQUEST);
+    fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn rejects_symlink_escape() {
+    let (dir, allowed, outside) = sandbox("symlink").await;
+    let link = allowed.join("link");
+    std::os::unix::fs::symlink(&outside, &link).unwrap();
+    let e = resolve(&link.join("secret.txt"), &allowed)
+      .await
+      .unwrap_err();
+    assert_eq!(e.status, StatusCode::FORBIDDEN);
+    let e = resolve(&link, &allowed).await.unwrap_err();
+    assert_eq!(e.status, StatusCode::FORBIDDEN);
+    // Symlinks staying inside the sandbox are followed.
+    let inner = allowed.join("inner");
+    std::os::unix::fs::symlink(allowed.join("sub"), &inner).unwrap();
+    assert_eq!(
+      resolve(&inner, &allowed).await.unwrap(),
+      allowed.join("sub")
+    );
+    fs::remove_dir_all(&dir).await.unwrap();
+  }
+}
@@ -1,17 +1,24 @@
-use std::sync::OnceLock;
+use std::{path::Path, sync::OnceLock};
+
+use anyhow::{Context, anyhow};
 
 use cache::TimeoutCache;
 use command::run_komodo_command;
 use komodo_client::entities::{
   deployment::extract_registry_domain,
-  docker::image::{Image, ImageHistoryResponseItem},
+  docker::image::{Image, ImageHistoryResponseItem, ImageScan},
   komodo_timestamp,
   update::Log,
 };
 use periphery_client::api::image::*;
 use resolver_api::Resolve;
+use shell_escape::unix::escape;
 
-use crate::docker::{docker_client, docker_login};
+use crate::{
+  docker::{docker_client, docker_login},
+  helpers::validate_host_file_path,
+  scan::scan_image,
+};
 
 //
 
@@ -36,6 +43,18 @@ impl Resolve<super::Args> for ImageHistory {
 
 //
 
+impl Resolve<super::Args> for ScanImage {
+  #[instrument(name = "ScanImage", level = "debug")]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<ImageScan> {
+    Ok(scan_image(&self.image).await?)
+  }
+}
+
+//
+
 /// Wait this long after a pull to allow another pull through
 const PULL_TIMEOUT: i64 = 5_000;
 
@@ -111,3 +130,113 @@ impl Resolve<super::Args> for PruneImages {
     Ok(run_komodo_command("Prune Images", None, command).await)
   }
 }
+
+//
+
+impl Resolve<super::Args> for SaveImage {
+  #[instrument(name = "SaveImage")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let (path, dir, _) = validate_host_file_path(&self.dest_path)?;
+    tokio::fs::create_dir_all(dir).await.with_context(|| {
+      format!("Failed to create image archive directory {dir:?}")
+    })?;
+    let command = save_image_command(&self.image, path);
+    let mut log =
+      run_komodo_command("Save Image", None, command).await;
+    if log.success {
+      push_archive_size(&mut log, path).await;
+    }
+    Ok(log)
+  }
+}
+
+fn save_image_command(image: &str, path: &Path) -> String {
+  format!(
+    "docker save -o {} {}",
+    escape(path.to_string_lossy()),
+    escape(image.into())
+  )
+}
+
+//
+
+impl Resolve<super::Args> for LoadImage {
+  #[instrument(name = "LoadImage")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let (path, _, _) = validate_host_file_path(&self.src_path)?;
+    if !path.is_file() {
+      return Err(
+        anyhow!("No image archive found at {path:?}").into(),
+      );
+    }
+    let command = load_image_command(path);
+    let mut log =
+      run_komodo_command("Load Image", None, command).await;
+    push_archive_size(&mut log, path).await;
+    Ok(log)
+  }
+}
+
+fn load_image_command(path: &Path) -> String {
+  format!("docker load -i {}", escape(path.to_string_lossy()))
+}
+
+/// Reports the archive size at the end of the log stdout.
+async fn push_archive_size(log: &mut Log, path: &Path) {
+  let Ok(metadata) = tokio::fs::metadata(path).await else {
+    return;
+  };
+  if !log.stdout.is_empty() && !log.stdout.ends_with('\n') {
+    log.stdout.push('\n');
+  }
+  log.stdout.push_str(&format!(
+    "Archive size: {:.2} MiB ({} bytes)",
+    metadata.len() as f64 / (1024.0 * 1024.0),
+    metadata.len()
+  ));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn save_and_load_commands() {
+    let path = Path::new("/srv/images/my app.tar");
+    assert_eq!(
+      save_image_command("ghcr.io/org/app:1.2", path),
+      "docker save -o '/srv/images/my app.tar' 'ghcr.io/org/app:1.2'"
+    );
+    assert_eq!(
+      load_image_command(path),
+      "docker load -i '/srv/images/my app.tar'"
+    );
+  }
+
+  #[tokio::test]
+  async fn reports_archive_size() {
+    let path = std::env::temp_dir().join(format!(
+      "komodo-image-archive-{}.tar",
+      std::process::id()
+    ));
+    tokio::fs::write(&path, vec![0; 2048]).await.unwrap();
+    let mut log = Log {
+      stdout: String::from("Loaded image: app:1.2"),
+      ..Default::default()
+    };
+    push_archive_size(&mut log, &path).await;
+    tokio::fs::remove_file(&path).await.unwrap();
+    assert_eq!(
+      log.stdout,
+      "Loaded image: app:1.2\nArchive size: 0.00 MiB (2048 bytes)"
+    );
+  }
+
+  #[tokio::test]
+  async fn skips_size_of_missing_archive() {
+    let mut log = Log::default();
+    push_archive_size(&mut log, Path::new("/nonexistent/app.tar"))
+      .await;
+    assert!(log.stdout.is_empty());
+  }
+}
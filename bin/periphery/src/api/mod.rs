@@ -5,27 +5,34 @@ use futures::TryFutureExt;
 use komodo_client::entities::{
   SystemCommand,
   config::{DockerRegistry, GitProvider},
+  docker::info::DockerInfo,
   update::Log,
 };
 use periphery_client::api::{
-  build::*, compose::*, container::*, git::*, image::*, network::*,
-  stats::*, terminal::*, volume::*, *,
+  build::*, compose::*, container::*, files::*, git::*, image::*,
+  network::*, stats::*, swarm::*, terminal::*, volume::*, *,
 };
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
+use serror::Serror;
 
-use crate::{config::periphery_config, docker::docker_client};
+use crate::{
+  config::periphery_config,
+  docker::{container_is_reported, docker_client, docker_version},
+};
 
 mod build;
 mod compose;
 mod container;
 mod deploy;
+mod files;
 mod git;
 mod image;
 mod network;
 mod router;
 mod stats;
+mod swarm;
 mod terminal;
 mod volume;
 
@@ -74,6 +81,11 @@ pub enum PeripheryRequest {
   PruneBuilders(PruneBuilders),
   PruneBuildx(PruneBuildx),
 
+  // Files (Read)
+  DiffFileContents(DiffFileContents),
+  ListDir(ListDir),
+  ReadFile(ReadFile),
+
   // Compose (Read)
   GetComposeContentsOnHost(GetComposeContentsOnHost),
   GetComposeLog(GetComposeLog),
@@ -89,11 +101,13 @@ pub enum PeripheryRequest {
 
   // Container (Read)
   InspectContainer(InspectContainer),
+  GetComposeFromContainers(GetComposeFromContainers),
   GetContainerLog(GetContainerLog),
   GetContainerLogSearch(GetContainerLogSearch),
   GetContainerStats(GetContainerStats),
   GetContainerStatsList(GetContainerStatsList),
   GetFullContainerStats(GetFullContainerStats),
+  GetContainerStatsHistory(GetContainerStatsHistory),
 
   // Container (Write)
   Deploy(Deploy),
@@ -117,16 +131,21 @@ pub enum PeripheryRequest {
   // Networks (Write)
   CreateNetwork(CreateNetwork),
   DeleteNetwork(DeleteNetwork),
+  ConnectContainerToNetwork(ConnectContainerToNetwork),
+  DisconnectContainerFromNetwork(DisconnectContainerFromNetwork),
   PruneNetworks(PruneNetworks),
 
   // Image (Read)
   InspectImage(InspectImage),
   ImageHistory(ImageHistory),
+  ScanImage(ScanImage),
 
   // Image (Write)
   PullImage(PullImage),
   DeleteImage(DeleteImage),
   PruneImages(PruneImages),
+  SaveImage(SaveImage),
+  LoadImage(LoadImage),
 
   // Volume (Read)
   InspectVolume(InspectVolume),
@@ -134,8 +153,24 @@ pub enum PeripheryRequest {
   // Volume (Write)
   DeleteVolume(DeleteVolume),
   PruneVolumes(PruneVolumes),
+  BackupVolume(BackupVolume),
+  RestoreVolume(RestoreVolume),
+
+  // Swarm Service (Read)
+  GetSwarmServiceLog(GetSwarmServiceLog),
+  GetSwarmServiceLogSearch(GetSwarmServiceLogSearch),
+
+  // Swarm Service (Write)
+  ScaleSwarmService(ScaleSwarmService),
+  UpdateSwarmServiceImage(UpdateSwarmServiceImage),
+  RollbackSwarmService(RollbackSwarmService),
+
+  // Swarm Node (Write)
+  DrainSwarmNode(DrainSwarmNode),
+  ActivateSwarmNode(ActivateSwarmNode),
 
   // All in one (Read)
+  GetDockerInfo(GetDockerInfo),
   GetDockerLists(GetDockerLists),
 
   // All in one (Write)
@@ -171,6 +206,7 @@ impl Resolve<Args> for GetVersion {
   ) -> serror::Result<GetVersionResponse> {
     Ok(GetVersionResponse {
       version: env!("CARGO_PKG_VERSION").to_string(),
+      docker: docker_version().await.cloned(),
     })
   }
 }
@@ -216,28 +252,79 @@ impl Resolve<Args> for ListSecrets {
   }
 }
 
+impl Resolve<Args> for GetDockerInfo {
+  #[instrument(name = "GetDockerInfo", level = "debug", skip_all)]
+  async fn resolve(self, _: &Args) -> serror::Result<DockerInfo> {
+    Ok(docker_client().docker_info().await?)
+  }
+}
+
 impl Resolve<Args> for GetDockerLists {
   #[instrument(name = "GetDockerLists", level = "debug", skip_all)]
   async fn resolve(
     self,
     _: &Args,
   ) -> serror::Result<GetDockerListsResponse> {
+    let GetDockerLists {
+      include_containers,
+      include_networks,
+      include_images,
+      include_volumes,
+      include_projects,
+      images: images_filter,
+      volumes: volumes_filter,
+    } = self;
     let docker = docker_client();
-    let containers =
-      docker.list_containers().await.map_err(Into::into);
+    // The containers are also needed to know what is "in_use".
+    let containers = if_included(
+      include_containers
+        || include_networks
+        || include_images
+        || include_volumes,
+      docker.list_containers().map_err(Into::into),
+    )
+    .await;
     // Should still try to retrieve other docker lists, but "in_use" will be false for images, networks, volumes
     let _containers = match &containers {
       Ok(containers) => containers.as_slice(),
       Err(_) => &[],
     };
     let (networks, images, volumes, projects) = tokio::join!(
-      docker.list_networks(_containers).map_err(Into::into),
-      docker.list_images(_containers).map_err(Into::into),
-      docker.list_volumes(_containers).map_err(Into::into),
-      ListComposeProjects {}
-        .resolve(&Args)
-        .map_err(|e| e.error.into())
+      if_included(
+        include_networks,
+        docker.list_networks(_containers).map_err(Into::into)
+      ),
+      if_included(
+        include_images,
+        docker
+          .list_images(_containers, &images_filter)
+          .map_err(Into::into)
+      ),
+      if_included(
+        include_volumes,
+        docker
+          .list_volumes(_containers, &volumes_filter)
+          .map_err(Into::into)
+      ),
+      if_included(
+        include_projects,
+        ListComposeProjects {}
+          .resolve(&Args)
+          .map_err(|e| e.error.into())
+      )
     );
+    // Filter after the other lists, so images etc. used by
+    // unreported containers are still marked in use.
+    let containers = if include_containers {
+      containers.map(|containers| {
+        containers
+          .into_iter()
+          .filter(container_is_reported)
+          .collect()
+      })
+    } else {
+      Ok(Vec::new())
+    };
     Ok(GetDockerListsResponse {
       containers,
       networks,
@@ -248,6 +335,15 @@ impl Resolve<Args> for GetDockerLists {
   }
 }
 
+/// Only awaits the `list` if the section is included,
+/// otherwise the section is returned empty.
+async fn if_included<T>(
+  include: bool,
+  list: impl Future<Output = Result<Vec<T>, Serror>>,
+) -> Result<Vec<T>, Serror> {
+  if include { list.await } else { Ok(Vec::new()) }
+}
+
 impl Resolve<Args> for RunCommand {
   #[instrument(name = "RunCommand")]
   async fn resolve(self, _: &Args) -> serror::Result<Log> {
@@ -275,3 +371,23 @@ impl Resolve<Args> for PruneSystem {
     Ok(run_komodo_command("Prune System", None, command).await)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn if_included_lists() {
+    let list = if_included(true, async { Ok(vec![1, 2]) }).await;
+    assert_eq!(list.unwrap(), [1, 2]);
+  }
+
+  #[tokio::test]
+  async fn if_excluded_skips_list() {
+    let list = if_included::<i32>(false, async {
+      panic!("excluded list should not be awaited")
+    })
+    .await;
+    assert!(list.unwrap().is_empty());
+  }
+}
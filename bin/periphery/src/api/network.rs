@@ -43,6 +43,69 @@ impl Resolve<super::Args> for DeleteNetwork {
 
 //
 
+impl Resolve<super::Args> for ConnectContainerToNetwork {
+  #[instrument(name = "ConnectContainerToNetwork", skip(self))]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command = connect_network_command(&self);
+    Ok(
+      run_komodo_command(
+        "Connect Container To Network",
+        None,
+        command,
+      )
+      .await,
+    )
+  }
+}
+
+fn connect_network_command(
+  ConnectContainerToNetwork {
+    network,
+    container,
+    aliases,
+    ip,
+  }: &ConnectContainerToNetwork,
+) -> String {
+  let mut command = String::from("docker network connect");
+  for alias in aliases {
+    command.push_str(&format!(" --alias {alias}"));
+  }
+  if let Some(ip) = ip.as_ref().filter(|ip| !ip.is_empty()) {
+    command.push_str(&format!(" --ip {ip}"));
+  }
+  format!("{command} {network} {container}")
+}
+
+//
+
+impl Resolve<super::Args> for DisconnectContainerFromNetwork {
+  #[instrument(name = "DisconnectContainerFromNetwork", skip(self))]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command = disconnect_network_command(&self);
+    Ok(
+      run_komodo_command(
+        "Disconnect Container From Network",
+        None,
+        command,
+      )
+      .await,
+    )
+  }
+}
+
+fn disconnect_network_command(
+  DisconnectContainerFromNetwork {
+    network,
+    container,
+    force,
+  }: &DisconnectContainerFromNetwork,
+) -> String {
+  let force = if *force { " --force" } else { "" };
+  format!("docker network disconnect{force} {network} {container}")
+}
+
+//
+
 impl Resolve<super::Args> for PruneNetworks {
   #[instrument(name = "PruneNetworks", skip(self))]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -50,3 +113,60 @@ impl Resolve<super::Args> for PruneNetworks {
     Ok(run_komodo_command("Prune Networks", None, command).await)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn connect(
+    aliases: &[&str],
+    ip: Option<&str>,
+  ) -> ConnectContainerToNetwork {
+    ConnectContainerToNetwork {
+      network: String::from("proxy"),
+      container: String::from("web"),
+      aliases: aliases
+        .iter()
+        .map(|alias| alias.to_string())
+        .collect(),
+      ip: ip.map(str::to_string),
+    }
+  }
+
+  #[test]
+  fn connect_command() {
+    assert_eq!(
+      connect_network_command(&connect(&[], None)),
+      "docker network connect proxy web"
+    );
+    assert_eq!(
+      connect_network_command(&connect(&[], Some(""))),
+      "docker network connect proxy web"
+    );
+    assert_eq!(
+      connect_network_command(&connect(
+        &["app", "www"],
+        Some("172.20.0.5")
+      )),
+      "docker network connect --alias app --alias www --ip 172.20.0.5 proxy web"
+    );
+  }
+
+  #[test]
+  fn disconnect_command() {
+    let mut disconnect = DisconnectContainerFromNetwork {
+      network: String::from("proxy"),
+      container: String::from("web"),
+      force: false,
+    };
+    assert_eq!(
+      disconnect_network_command(&disconnect),
+      "docker network disconnect proxy web"
+    );
+    disconnect.force = true;
+    assert_eq!(
+      disconnect_network_command(&disconnect),
+      "docker network disconnect --force proxy web"
+    );
+  }
+}
@@ -3,7 +3,7 @@ use axum::{
   Router,
   body::Body,
   extract::ConnectInfo,
-  http::{Request, StatusCode},
+  http::{HeaderMap, Request, StatusCode},
   middleware::{self, Next},
   response::Response,
   routing::{get, post},
@@ -12,6 +12,8 @@ use derive_variants::ExtractVariant;
 use resolver_api::Resolve;
 use serror::{AddStatusCode, AddStatusCodeError, Json};
 use std::net::{IpAddr, SocketAddr};
+use tower_http::compression::CompressionLayer;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use crate::config::periphery_config;
@@ -21,8 +23,12 @@ pub fn router() -> Router {
     .merge(
       Router::new()
         .route("/", post(handler))
-        .layer(middleware::from_fn(guard_request_by_passkey)),
+        .layer(middleware::from_fn(guard_request_by_passkey))
+        // Only compresses responses when Core sends
+        // `Accept-Encoding: gzip`, ie the server has compression enabled.
+        .layer(CompressionLayer::new()),
     )
+    .route("/events", get(super::container::connect_container_events))
     .nest(
       "/terminal",
       Router::new()
@@ -46,11 +52,25 @@ pub fn router() -> Router {
 }
 
 async fn handler(
+  headers: HeaderMap,
   Json(request): Json<crate::api::PeripheryRequest>,
 ) -> serror::Result<axum::response::Response> {
   let req_id = Uuid::new_v4();
 
-  let res = tokio::spawn(task(req_id, request))
+  let span = info_span!(
+    "PeripheryRequest",
+    req_id = req_id.to_string(),
+    request = format!("{:?}", request.extract_variant())
+  );
+  // Continue the trace started by Core, if it was sent.
+  if let Some(traceparent) = headers
+    .get(logger::TRACEPARENT)
+    .and_then(|traceparent| traceparent.to_str().ok())
+  {
+    logger::set_parent_from_traceparent(&span, traceparent);
+  }
+
+  let res = tokio::spawn(task(req_id, request).instrument(span))
     .await
     .context("task handler spawn error");
 
@@ -6,7 +6,7 @@ use periphery_client::api::stats::{
 };
 use resolver_api::Resolve;
 
-use crate::stats::stats_client;
+use crate::stats::{stats_client, system_stats};
 
 impl Resolve<super::Args> for GetSystemInformation {
   #[instrument(
@@ -30,7 +30,7 @@ impl Resolve<super::Args> for GetSystemStats {
     self,
     _: &super::Args,
   ) -> serror::Result<SystemStats> {
-    Ok(stats_client().read().await.stats.clone())
+    Ok(system_stats().load().as_ref().clone())
   }
 }
 
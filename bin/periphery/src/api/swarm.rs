@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use command::run_komodo_command;
+use komodo_client::entities::{komodo_timestamp, update::Log};
+use periphery_client::api::swarm::*;
+use resolver_api::Resolve;
+
+use crate::helpers::log_grep;
+
+//
+
+impl Resolve<super::Args> for GetSwarmServiceLog {
+  #[instrument(name = "GetSwarmServiceLog", level = "debug")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command =
+      service_log_command(&self.service, self.tail, self.timestamps);
+    Ok(
+      run_komodo_command("Get swarm service log", None, command)
+        .await,
+    )
+  }
+}
+
+//
+
+impl Resolve<super::Args> for GetSwarmServiceLogSearch {
+  #[instrument(name = "GetSwarmServiceLogSearch", level = "debug")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let GetSwarmServiceLogSearch {
+      service,
+      terms,
+      combinator,
+      invert,
+      timestamps,
+    } = self;
+    let log = service_log_command(&service, 5000, timestamps);
+    let grep = log_grep(&terms, combinator, invert);
+    let command = format!("{log} 2>&1 | {grep}");
+    Ok(
+      run_komodo_command("Get swarm service log grep", None, command)
+        .await,
+    )
+  }
+}
+
+fn service_log_command(
+  service: &str,
+  tail: u64,
+  timestamps: bool,
+) -> String {
+  let timestamps = if timestamps {
+    " --timestamps"
+  } else {
+    Default::default()
+  };
+  format!("docker service logs {service} --tail {tail}{timestamps}")
+}
+
+//
+
+impl Resolve<super::Args> for ScaleSwarmService {
+  #[instrument(name = "ScaleSwarmService")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command = scale_service_command(&self.service, self.replicas);
+    Ok(run_komodo_command("Scale Swarm Service", None, command).await)
+  }
+}
+
+fn scale_service_command(service: &str, replicas: u32) -> String {
+  format!("docker service scale --detach {service}={replicas}")
+}
+
+//
+
+impl Resolve<super::Args> for UpdateSwarmServiceImage {
+  #[instrument(name = "UpdateSwarmServiceImage")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command =
+      update_service_image_command(&self.service, &self.image);
+    Ok(
+      run_komodo_command("Update Swarm Service Image", None, command)
+        .await,
+    )
+  }
+}
+
+fn update_service_image_command(
+  service: &str,
+  image: &str,
+) -> String {
+  format!(
+    "docker service update --detach --with-registry-auth --image {image} {service}"
+  )
+}
+
+//
+
+impl Resolve<super::Args> for RollbackSwarmService {
+  #[instrument(name = "RollbackSwarmService")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let command = rollback_service_command(&self.service);
+    Ok(
+      run_komodo_command("Rollback Swarm Service", None, command)
+        .await,
+    )
+  }
+}
+
+fn rollback_service_command(service: &str) -> String {
+  format!("docker service rollback --detach {service}")
+}
+
+//
+
+/// How often to check the running tasks while waiting for a drain.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+impl Resolve<super::Args> for DrainSwarmNode {
+  #[instrument(name = "DrainSwarmNode")]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<Log>> {
+    let DrainSwarmNode {
+      node,
+      wait,
+      timeout_seconds,
+    } = self;
+    let log = run_komodo_command(
+      "Drain Swarm Node",
+      None,
+      node_availability_command(&node, "drain"),
+    )
+    .await;
+    if !log.success || !wait {
+      return Ok(vec![log]);
+    }
+    let mut logs = vec![log];
+    logs.push(wait_for_drain(&node, timeout_seconds).await);
+    Ok(logs)
+  }
+}
+
+/// Polls the node until it has no running tasks,
+/// or the timeout is reached.
+///
+/// The tasks' current state is checked rather than their desired state,
+/// as draining sets the desired state to shutdown straight away,
+/// while the containers keep running until they have stopped.
+async fn wait_for_drain(node: &str, timeout_seconds: u64) -> Log {
+  let start_ts = komodo_timestamp();
+  let timeout_seconds =
+    timeout_seconds.min(MAX_DRAIN_TIMEOUT_SECONDS);
+  let command =
+    format!("docker node ps {node} --format '{{{{.CurrentState}}}}'");
+  let deadline = tokio::time::Instant::now()
+    + Duration::from_secs(timeout_seconds);
+  loop {
+    let log =
+      run_komodo_command("Wait For Drain", None, &command).await;
+    if !log.success {
+      return log;
+    }
+    let running = running_tasks(&log.stdout);
+    if running == 0 {
+      return Log {
+        stdout: format!("Node {node} has no running tasks"),
+        start_ts,
+        ..log
+      };
+    }
+    if tokio::time::Instant::now() >= deadline {
+      let mut log = Log::error(
+        "Wait For Drain",
+        format!(
+          "Node {node} still has {running} running tasks after {timeout_seconds}s"
+        ),
+      );
+      log.command = command;
+      log.start_ts = start_ts;
+      return log;
+    }
+    tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+  }
+}
+
+//
+
+impl Resolve<super::Args> for ActivateSwarmNode {
+  #[instrument(name = "ActivateSwarmNode")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    Ok(
+      run_komodo_command(
+        "Activate Swarm Node",
+        None,
+        node_availability_command(&self.node, "active"),
+      )
+      .await,
+    )
+  }
+}
+
+fn node_availability_command(
+  node: &str,
+  availability: &str,
+) -> String {
+  format!("docker node update --availability {availability} {node}")
+}
+
+/// Counts the tasks from `docker node ps --format '{{.CurrentState}}'`
+/// which are still running, eg `Running 5 minutes ago`.
+fn running_tasks(current_states: &str) -> usize {
+  current_states
+    .lines()
+    .filter(|state| state.trim_start().starts_with("Running"))
+    .count()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn service_log_commands() {
+    assert_eq!(
+      service_log_command("web", 50, false),
+      "docker service logs web --tail 50"
+    );
+    assert_eq!(
+      service_log_command("web", 5000, true),
+      "docker service logs web --tail 5000 --timestamps"
+    );
+  }
+
+  #[test]
+  fn scale_and_update_image_commands() {
+    assert_eq!(
+      scale_service_command("web", 3),
+      "docker service scale --detach web=3"
+    );
+    assert_eq!(
+      update_service_image_command("web", "nginx:1.27"),
+      "docker service update --detach --with-registry-auth --image nginx:1.27 web"
+    );
+  }
+
+  #[test]
+  fn rollback_command() {
+    assert_eq!(
+      rollback_service_command("web"),
+      "docker service rollback --detach web"
+    );
+  }
+
+  #[test]
+  fn counts_running_tasks() {
+    let states = "Running 5 minutes ago\nShutdown 2 seconds ago\nRunning 1 hour ago\nFailed 3 days ago\n";
+    assert_eq!(running_tasks(states), 2);
+  }
+
+  #[test]
+  fn drained_node_has_no_running_tasks() {
+    assert_eq!(
+      running_tasks("Shutdown 2 seconds ago\nComplete 1 minute ago"),
+      0
+    );
+    assert_eq!(running_tasks(""), 0);
+  }
+}
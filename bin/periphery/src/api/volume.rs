@@ -1,9 +1,15 @@
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
 use command::run_komodo_command;
 use komodo_client::entities::{docker::volume::Volume, update::Log};
 use periphery_client::api::volume::*;
 use resolver_api::Resolve;
+use shell_escape::unix::escape;
 
-use crate::docker::docker_client;
+use crate::{
+  docker::docker_client, helpers::validate_host_file_path,
+};
 
 //
 
@@ -33,3 +39,91 @@ impl Resolve<super::Args> for PruneVolumes {
     Ok(run_komodo_command("Prune Volumes", None, command).await)
   }
 }
+
+//
+
+/// The image used to run `tar` against the volume.
+const VOLUME_BACKUP_IMAGE: &str = "busybox";
+
+impl Resolve<super::Args> for BackupVolume {
+  #[instrument(name = "BackupVolume")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let (_, dir, file) = validate_host_file_path(&self.dest_path)?;
+    tokio::fs::create_dir_all(dir).await.with_context(|| {
+      format!("Failed to create backup directory {dir:?}")
+    })?;
+    let command = backup_volume_command(&self.volume, dir, file);
+    Ok(run_komodo_command("Backup Volume", None, command).await)
+  }
+}
+
+fn backup_volume_command(
+  volume: &str,
+  dir: &Path,
+  file: &str,
+) -> String {
+  format!(
+    "docker run --rm -v {}:/data:ro -v {}:/backup {VOLUME_BACKUP_IMAGE} tar czf /backup/{} -C /data .",
+    escape(volume.into()),
+    escape(dir.to_string_lossy()),
+    escape(file.into()),
+  )
+}
+
+//
+
+impl Resolve<super::Args> for RestoreVolume {
+  #[instrument(name = "RestoreVolume")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let (path, dir, file) = validate_host_file_path(&self.src_path)?;
+    if !path.is_file() {
+      return Err(
+        anyhow!("No backup archive found at {path:?}").into(),
+      );
+    }
+    let command = restore_volume_command(&self.volume, dir, file);
+    Ok(run_komodo_command("Restore Volume", None, command).await)
+  }
+}
+
+fn restore_volume_command(
+  volume: &str,
+  dir: &Path,
+  file: &str,
+) -> String {
+  format!(
+    "docker run --rm -v {}:/data -v {}:/backup:ro {VOLUME_BACKUP_IMAGE} tar xzf /backup/{} -C /data",
+    escape(volume.into()),
+    escape(dir.to_string_lossy()),
+    escape(file.into()),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backup_command() {
+    assert_eq!(
+      backup_volume_command(
+        "app_data",
+        Path::new("/srv/backups"),
+        "app data.tar.gz"
+      ),
+      "docker run --rm -v app_data:/data:ro -v /srv/backups:/backup busybox tar czf /backup/'app data.tar.gz' -C /data ."
+    );
+  }
+
+  #[test]
+  fn restore_command() {
+    assert_eq!(
+      restore_volume_command(
+        "app_data",
+        Path::new("/srv/my backups"),
+        "data.tar.gz"
+      ),
+      "docker run --rm -v app_data:/data -v '/srv/my backups':/backup:ro busybox tar xzf /backup/data.tar.gz -C /data"
+    );
+  }
+}
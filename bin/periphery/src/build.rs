@@ -52,6 +52,126 @@ pub async fn write_dockerfile(
   }
 }
 
+const DOCKERFILE_INSTRUCTIONS: [&str; 18] = [
+  "ADD",
+  "ARG",
+  "CMD",
+  "COPY",
+  "ENTRYPOINT",
+  "ENV",
+  "EXPOSE",
+  "FROM",
+  "HEALTHCHECK",
+  "LABEL",
+  "MAINTAINER",
+  "ONBUILD",
+  "RUN",
+  "SHELL",
+  "STOPSIGNAL",
+  "USER",
+  "VOLUME",
+  "WORKDIR",
+];
+
+/// Checks the dockerfile is well formed before it is written:
+///  - Every instruction is known.
+///  - Only ARG instructions come before the first FROM.
+///  - Heredocs are terminated.
+pub fn lint_dockerfile(contents: &str) -> anyhow::Result<()> {
+  let mut errors = Vec::new();
+  let mut seen_from = false;
+  let mut continuation = false;
+  let mut heredoc: Option<String> = None;
+  for (i, line) in contents.lines().enumerate() {
+    let line_number = i + 1;
+    let trimmed = line.trim();
+    if let Some(delimiter) = &heredoc {
+      if trimmed == delimiter {
+        heredoc = None;
+      }
+      continue;
+    }
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+      continue;
+    }
+    let is_continuation = continuation;
+    continuation = trimmed.ends_with('\\');
+    if is_continuation {
+      continue;
+    }
+    let instruction = trimmed
+      .split_whitespace()
+      .next()
+      .unwrap_or_default()
+      .to_uppercase();
+    if !DOCKERFILE_INSTRUCTIONS.contains(&instruction.as_str()) {
+      errors.push(format!(
+        "line {line_number}: unknown instruction '{instruction}'"
+      ));
+      continue;
+    }
+    if instruction == "FROM" {
+      seen_from = true;
+    } else if !seen_from && instruction != "ARG" {
+      errors.push(format!(
+        "line {line_number}: {instruction} before first FROM"
+      ));
+    }
+    heredoc = heredoc_delimiter(trimmed);
+  }
+  if !seen_from {
+    errors.push(String::from("no FROM instruction"));
+  }
+  if let Some(delimiter) = heredoc {
+    errors.push(format!("heredoc '{delimiter}' is not terminated"));
+  }
+  if errors.is_empty() {
+    Ok(())
+  } else {
+    Err(anyhow!("Invalid dockerfile | {}", errors.join(" | ")))
+  }
+}
+
+/// Gets the delimiter of a heredoc opened on the line, eg `<<EOF`,
+/// `<<-EOF` or `<<"EOF"`. A `<<` inside quotes doesn't open one.
+fn heredoc_delimiter(line: &str) -> Option<String> {
+  let rest = &line[find_unquoted(line, "<<")? + 2..];
+  let rest = rest.strip_prefix('-').unwrap_or(rest);
+  let delimiter = rest
+    .split_whitespace()
+    .next()?
+    .trim_matches(|c| c == '"' || c == '\'');
+  (!delimiter.is_empty()
+    && delimiter
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_'))
+  .then(|| delimiter.to_string())
+}
+
+/// Finds the pattern in the line outside of single or double quotes.
+fn find_unquoted(line: &str, pattern: &str) -> Option<usize> {
+  let mut quote = None;
+  let mut escaped = false;
+  for (i, c) in line.char_indices() {
+    if escaped {
+      escaped = false;
+      continue;
+    }
+    match (quote, c) {
+      // Single quotes can't be escaped inside single quotes.
+      (Some('\''), '\'') => quote = None,
+      (Some('\''), _) => {}
+      (_, '\\') => escaped = true,
+      (Some('"'), '"') => quote = None,
+      (Some(_), _) => {}
+      (None, '"' | '\'') => quote = Some(c),
+      (None, _) if line[i..].starts_with(pattern) => return Some(i),
+      (None, _) => {}
+    }
+  }
+  None
+}
+
 pub fn parse_build_args(build_args: &[EnvironmentVar]) -> String {
   build_args
     .iter()
@@ -106,3 +226,73 @@ pub async fn parse_secret_args(
   }
   Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lint_error(contents: &str) -> String {
+    lint_dockerfile(contents).unwrap_err().to_string()
+  }
+
+  #[test]
+  fn accepts_valid_dockerfile() {
+    lint_dockerfile(
+      "# syntax=docker/dockerfile:1
+ARG VERSION=latest
+FROM alpine:${VERSION}
+
+RUN apk add \\
+  curl
+COPY . /app
+CMD [\"/app/run\"]",
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn rejects_unknown_instruction() {
+    assert!(
+      lint_error("FROM alpine\nRUNN echo")
+        .contains("line 2: unknown instruction 'RUNN'")
+    );
+  }
+
+  #[test]
+  fn rejects_instruction_before_from() {
+    assert!(
+      lint_error("RUN echo\nFROM alpine")
+        .contains("line 1: RUN before first FROM")
+    );
+  }
+
+  #[test]
+  fn rejects_missing_from() {
+    assert!(
+      lint_error("ARG VERSION").contains("no FROM instruction")
+    );
+  }
+
+  #[test]
+  fn checks_heredocs_are_terminated() {
+    lint_dockerfile("FROM alpine\nRUN <<EOF\nunknown\nEOF").unwrap();
+    lint_dockerfile("FROM alpine\nRUN <<-\"EOF\" sh\n  ls\n  EOF")
+      .unwrap();
+    assert!(
+      lint_error("FROM alpine\nCOPY <<EOF /file\ncontents")
+        .contains("heredoc 'EOF' is not terminated")
+    );
+  }
+
+  #[test]
+  fn ignores_quoted_heredoc_markers() {
+    lint_dockerfile("FROM alpine\nRUN echo \"a << b\"").unwrap();
+    lint_dockerfile("FROM alpine\nRUN echo 'a << b'").unwrap();
+    lint_dockerfile("FROM alpine\nRUN echo \"\\\"a << b\\\"\"")
+      .unwrap();
+    assert_eq!(
+      heredoc_delimiter("RUN echo \"<<\" <<EOF"),
+      Some(String::from("EOF"))
+    );
+  }
+}
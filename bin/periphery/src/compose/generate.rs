@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Context;
+use komodo_client::entities::docker::container::{
+  Container, MountTypeEnum, RestartPolicyNameEnum,
+};
+use serde::Serialize;
+
+/// Networks every container can use without declaring them.
+const BUILTIN_NETWORKS: [&str; 3] = ["bridge", "host", "none"];
+
+#[derive(Serialize)]
+struct GeneratedCompose {
+  services: BTreeMap<String, GeneratedService>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  networks: BTreeMap<String, External>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  volumes: BTreeMap<String, External>,
+}
+
+#[derive(Serialize, Default)]
+struct GeneratedService {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  image: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  container_name: Option<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  command: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  restart: Option<RestartPolicyNameEnum>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  environment: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  ports: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  volumes: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  network_mode: Option<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  networks: Vec<String>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  labels: BTreeMap<String, String>,
+}
+
+/// Networks and volumes already exist on the host,
+/// so they are declared external rather than created by compose.
+#[derive(Serialize)]
+struct External {
+  external: bool,
+}
+
+/// Generates a compose file approximating the given containers,
+/// with one service per container.
+///
+/// The networks and named volumes they use are declared `external`.
+pub fn containers_to_compose(
+  containers: Vec<Container>,
+) -> anyhow::Result<String> {
+  let mut services = BTreeMap::new();
+  let mut networks = BTreeSet::new();
+  let mut volumes = BTreeSet::new();
+
+  for container in containers {
+    let name = container
+      .name
+      .as_deref()
+      .unwrap_or_default()
+      .trim_start_matches('/')
+      .to_string();
+    let mut service = GeneratedService {
+      container_name: Some(name.clone()),
+      ..Default::default()
+    };
+
+    if let Some(config) = container.config {
+      service.image = config.image;
+      service.command = config.cmd;
+      service.environment = config.env;
+      service.labels = config
+        .labels
+        .into_iter()
+        // Compose adds its own labels on deploy.
+        .filter(|(key, _)| !key.starts_with("com.docker.compose."))
+        .collect();
+    }
+
+    if let Some(host_config) = container.host_config {
+      service.restart = host_config
+        .restart_policy
+        .map(|restart| restart.name)
+        .filter(|name| *name != RestartPolicyNameEnum::Empty);
+      service.ports = host_config
+        .port_bindings
+        .into_iter()
+        .flat_map(|(container, host)| {
+          let container = container.replace("/tcp", "");
+          host.into_iter().filter_map(move |binding| {
+            let host_port =
+              binding.host_port.filter(|port| !port.is_empty())?;
+            let host = match binding.host_ip.as_deref() {
+              None | Some("") | Some("0.0.0.0") | Some("::") => {
+                host_port
+              }
+              Some(ip) => format!("{ip}:{host_port}"),
+            };
+            Some(format!("{host}:{container}"))
+          })
+        })
+        .collect();
+      service.ports.sort();
+      // Attached networks are used instead,
+      // unless the mode is not a network name.
+      service.network_mode =
+        host_config.network_mode.filter(|mode| {
+          mode == "host"
+            || mode == "none"
+            || mode.starts_with("container:")
+            || mode.starts_with("service:")
+        });
+    }
+
+    for mount in container.mounts {
+      let (Some(source), Some(destination)) =
+        (mount.source, mount.destination)
+      else {
+        continue;
+      };
+      let source = match mount.typ {
+        MountTypeEnum::Bind => source,
+        MountTypeEnum::Volume => {
+          let Some(name) = mount.name else {
+            continue;
+          };
+          volumes.insert(name.clone());
+          name
+        }
+        _ => continue,
+      };
+      let read_only =
+        if mount.rw == Some(false) { ":ro" } else { "" };
+      service
+        .volumes
+        .push(format!("{source}:{destination}{read_only}"));
+    }
+
+    if service.network_mode.is_none()
+      && let Some(settings) = container.network_settings
+    {
+      service.networks = settings
+        .networks
+        .into_keys()
+        .filter(|network| {
+          !BUILTIN_NETWORKS.contains(&network.as_str())
+        })
+        .collect();
+      service.networks.sort();
+      networks.extend(service.networks.iter().cloned());
+    }
+
+    services.insert(name, service);
+  }
+
+  let compose = GeneratedCompose {
+    services,
+    networks: networks
+      .into_iter()
+      .map(|network| (network, External { external: true }))
+      .collect(),
+    volumes: volumes
+      .into_iter()
+      .map(|volume| (volume, External { external: true }))
+      .collect(),
+  };
+
+  serde_yaml_ng::to_string(&compose)
+    .context("Failed to serialize generated compose file")
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use komodo_client::entities::docker::{
+    ContainerConfig, PortBinding,
+    container::{
+      EndpointSettings, HostConfig, MountPoint, NetworkSettings,
+      RestartPolicy,
+    },
+  };
+  use serde_yaml_ng::Value;
+
+  use super::*;
+
+  fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+  }
+
+  fn web() -> Container {
+    let binding = |host_ip: &str, host_port: &str| PortBinding {
+      host_ip: Some(host_ip.to_string()),
+      host_port: Some(host_port.to_string()),
+    };
+    Container {
+      name: Some(String::from("/web")),
+      config: Some(ContainerConfig {
+        image: Some(String::from("nginx:1.27")),
+        cmd: strings(&["nginx", "-g", "daemon off;"]),
+        env: strings(&["MODE=prod"]),
+        labels: HashMap::from([
+          (String::from("app"), String::from("web")),
+          (
+            String::from("com.docker.compose.project"),
+            String::from("old"),
+          ),
+        ]),
+        ..Default::default()
+      }),
+      host_config: Some(HostConfig {
+        restart_policy: Some(RestartPolicy {
+          name: RestartPolicyNameEnum::UnlessStopped,
+          ..Default::default()
+        }),
+        port_bindings: HashMap::from([(
+          String::from("80/tcp"),
+          vec![
+            binding("0.0.0.0", "8080"),
+            binding("127.0.0.1", "9090"),
+          ],
+        )]),
+        network_mode: Some(String::from("proxy")),
+        ..Default::default()
+      }),
+      mounts: vec![
+        MountPoint {
+          typ: MountTypeEnum::Bind,
+          source: Some(String::from("/srv/html")),
+          destination: Some(String::from("/usr/share/nginx/html")),
+          rw: Some(false),
+          ..Default::default()
+        },
+        MountPoint {
+          typ: MountTypeEnum::Volume,
+          name: Some(String::from("cache")),
+          source: Some(String::from(
+            "/var/lib/docker/volumes/cache/_data",
+          )),
+          destination: Some(String::from("/cache")),
+          rw: Some(true),
+          ..Default::default()
+        },
+      ],
+      network_settings: Some(NetworkSettings {
+        networks: HashMap::from([
+          (String::from("proxy"), EndpointSettings::default()),
+          (String::from("bridge"), EndpointSettings::default()),
+        ]),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  fn generate(containers: Vec<Container>) -> Value {
+    serde_yaml_ng::from_str(
+      &containers_to_compose(containers).unwrap(),
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn generates_service_from_container() {
+    let compose = generate(vec![web()]);
+    let web = &compose["services"]["web"];
+    assert_eq!(web["image"], "nginx:1.27");
+    assert_eq!(web["container_name"], "web");
+    assert_eq!(web["command"][2], "daemon off;");
+    assert_eq!(web["restart"], "unless-stopped");
+    assert_eq!(web["environment"][0], "MODE=prod");
+    assert_eq!(web["ports"][0], "127.0.0.1:9090:80");
+    assert_eq!(web["ports"][1], "8080:80");
+    assert_eq!(
+      web["volumes"][0],
+      "/srv/html:/usr/share/nginx/html:ro"
+    );
+    assert_eq!(web["volumes"][1], "cache:/cache");
+    assert_eq!(web["labels"]["app"], "web");
+    assert!(
+      web["labels"].get("com.docker.compose.project").is_none()
+    );
+    assert!(web.get("network_mode").is_none());
+    assert_eq!(web["networks"], Value::from(vec!["proxy"]));
+  }
+
+  #[test]
+  fn declares_networks_and_volumes_external() {
+    let compose = generate(vec![web()]);
+    assert_eq!(compose["networks"]["proxy"]["external"], true);
+    assert!(compose["networks"].get("bridge").is_none());
+    assert_eq!(compose["volumes"]["cache"]["external"], true);
+  }
+
+  #[test]
+  fn keeps_non_network_modes() {
+    let mut host = web();
+    host.name = Some(String::from("/host"));
+    host.host_config.as_mut().unwrap().network_mode =
+      Some(String::from("host"));
+    host.mounts.clear();
+    let compose = generate(vec![host]);
+    let host = &compose["services"]["host"];
+    assert_eq!(host["network_mode"], "host");
+    assert!(host.get("networks").is_none());
+    assert!(compose.get("networks").is_none());
+    assert!(compose.get("volumes").is_none());
+  }
+}
@@ -1,4 +1,7 @@
-use std::{fmt::Write, path::PathBuf};
+use std::{
+  fmt::Write,
+  path::{Path, PathBuf},
+};
 
 use anyhow::{Context, anyhow};
 use command::run_komodo_command;
@@ -13,6 +16,7 @@ use resolver_api::Resolve;
 
 use crate::config::periphery_config;
 
+pub mod generate;
 pub mod up;
 pub mod write;
 
@@ -53,6 +57,45 @@ pub fn env_file_args(
   Ok(res)
 }
 
+/// Checks the compose file contents with `docker compose config`,
+/// using a temporary file next to the target path so relative
+/// paths in the contents resolve the same way.
+pub async fn validate_compose_contents(
+  file_path: &Path,
+  contents: &str,
+) -> anyhow::Result<()> {
+  let (Some(parent), Some(file_name)) =
+    (file_path.parent(), file_path.file_name())
+  else {
+    return Err(anyhow!("Invalid compose file path {file_path:?}"));
+  };
+  let validate_path = parent.join(format!(
+    ".{}.komodo-validate",
+    file_name.to_string_lossy()
+  ));
+  tokio::fs::write(&validate_path, contents)
+    .await
+    .with_context(|| {
+      format!("Failed to write compose file for validation to {validate_path:?}")
+    })?;
+  let log = run_komodo_command(
+    "Validate Compose File",
+    parent,
+    format!(
+      "{} -f {} config --quiet --no-interpolate",
+      docker_compose(),
+      validate_path.display()
+    ),
+  )
+  .await;
+  let _ = tokio::fs::remove_file(&validate_path).await;
+  if log.success {
+    Ok(())
+  } else {
+    Err(anyhow!("{}", log.combined()).context("Invalid compose file"))
+  }
+}
+
 pub async fn down(
   project: &str,
   services: &[String],
@@ -77,9 +77,34 @@ pub fn periphery_config() -> &'static PeripheryConfig {
       container_stats_polling_rate: env
         .periphery_container_stats_polling_rate
         .unwrap_or(config.container_stats_polling_rate),
+      container_stats_labels: env
+        .periphery_container_stats_labels
+        .unwrap_or(config.container_stats_labels),
+      container_stats_concurrency: env
+        .periphery_container_stats_concurrency
+        .unwrap_or(config.container_stats_concurrency),
+      container_stats_history: env
+        .periphery_container_stats_history
+        .unwrap_or(config.container_stats_history),
+      min_ping_interval: env
+        .periphery_min_ping_interval
+        .unwrap_or(config.min_ping_interval),
+      max_ping_interval: env
+        .periphery_max_ping_interval
+        .unwrap_or(config.max_ping_interval),
       legacy_compose_cli: env
         .periphery_legacy_compose_cli
         .unwrap_or(config.legacy_compose_cli),
+      image_scanner: env
+        .periphery_image_scanner
+        .unwrap_or(config.image_scanner),
+      prefer_docker_api: env
+        .periphery_prefer_docker_api
+        .unwrap_or(config.prefer_docker_api),
+      shell: env.periphery_shell.unwrap_or(config.shell),
+      combined_build_output: env
+        .periphery_combined_build_output
+        .unwrap_or(config.combined_build_output),
       logging: LogConfig {
         level: args
           .log_level
@@ -98,6 +123,12 @@ pub fn periphery_config() -> &'static PeripheryConfig {
         otlp_endpoint: env
           .periphery_logging_otlp_endpoint
           .unwrap_or(config.logging.otlp_endpoint),
+        otlp_metrics_endpoint: env
+          .periphery_logging_otlp_metrics_endpoint
+          .unwrap_or(config.logging.otlp_metrics_endpoint),
+        otlp_high_cardinality_attributes: env
+          .periphery_logging_otlp_high_cardinality_attributes
+          .unwrap_or(config.logging.otlp_high_cardinality_attributes),
         opentelemetry_service_name: env
           .periphery_logging_opentelemetry_service_name
           .unwrap_or(config.logging.opentelemetry_service_name),
@@ -113,12 +144,24 @@ pub fn periphery_config() -> &'static PeripheryConfig {
         env.periphery_passkey,
       )
       .unwrap_or(config.passkey),
+      allowed_command_directories: env
+        .periphery_allowed_command_directories
+        .unwrap_or(config.allowed_command_directories),
+      allowed_file_directories: env
+        .periphery_allowed_file_directories
+        .unwrap_or(config.allowed_file_directories),
       include_disk_mounts: env
         .periphery_include_disk_mounts
         .unwrap_or(config.include_disk_mounts),
       exclude_disk_mounts: env
         .periphery_exclude_disk_mounts
         .unwrap_or(config.exclude_disk_mounts),
+      include_container_labels: env
+        .periphery_include_container_labels
+        .unwrap_or(config.include_container_labels),
+      include_container_names: env
+        .periphery_include_container_names
+        .unwrap_or(config.include_container_names),
       ssl_enabled: env
         .periphery_ssl_enabled
         .unwrap_or(config.ssl_enabled),
@@ -0,0 +1,226 @@
+/// Lines of unchanged context around each change.
+const CONTEXT: usize = 3;
+
+/// Above this many (changed old lines * changed new lines),
+/// the changed lines are shown as fully replaced
+/// rather than finding the longest common subsequence.
+/// Bounds the table to 4 MB per diff.
+const MAX_TABLE_SIZE: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+  Equal,
+  Delete,
+  Insert,
+}
+
+/// An edit, with the old / new line indexes it applies at.
+struct Op {
+  edit: Edit,
+  old: usize,
+  new: usize,
+}
+
+/// Produces a unified diff (as from `diff -u`) between the contents,
+/// or an empty string if they are the same.
+pub fn unified_diff(
+  old: &str,
+  new: &str,
+  old_label: &str,
+  new_label: &str,
+) -> String {
+  let old = old.lines().collect::<Vec<_>>();
+  let new = new.lines().collect::<Vec<_>>();
+  let ops = diff_ops(&old, &new);
+  let changes = ops
+    .iter()
+    .enumerate()
+    .filter(|(_, op)| op.edit != Edit::Equal)
+    .map(|(i, _)| i)
+    .collect::<Vec<_>>();
+  if changes.is_empty() {
+    return String::new();
+  }
+
+  let mut res = format!("--- {old_label}\n+++ {new_label}\n");
+  let mut i = 0;
+  while i < changes.len() {
+    let start = changes[i].saturating_sub(CONTEXT);
+    // Join changes with overlapping context into one hunk.
+    while i + 1 < changes.len()
+      && changes[i + 1] - changes[i] <= 2 * CONTEXT + 1
+    {
+      i += 1;
+    }
+    let end = (changes[i] + CONTEXT + 1).min(ops.len());
+    let hunk = &ops[start..end];
+    let old_count =
+      hunk.iter().filter(|op| op.edit != Edit::Insert).count();
+    let new_count =
+      hunk.iter().filter(|op| op.edit != Edit::Delete).count();
+    // Ranges with no lines give the line before them.
+    let old_start = hunk[0].old + usize::from(old_count > 0);
+    let new_start = hunk[0].new + usize::from(new_count > 0);
+    res.push_str(&format!(
+      "@@ -{old_start},{old_count} +{new_start},{new_count} @@\n"
+    ));
+    for op in hunk {
+      let line = match op.edit {
+        Edit::Equal => format!(" {}\n", old[op.old]),
+        Edit::Delete => format!("-{}\n", old[op.old]),
+        Edit::Insert => format!("+{}\n", new[op.new]),
+      };
+      res.push_str(&line);
+    }
+    i += 1;
+  }
+  res
+}
+
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+  let prefix = old
+    .iter()
+    .zip(new)
+    .take_while(|(old, new)| old == new)
+    .count();
+  let suffix = old[prefix..]
+    .iter()
+    .rev()
+    .zip(new[prefix..].iter().rev())
+    .take_while(|(old, new)| old == new)
+    .count();
+  let old_changed = &old[prefix..old.len() - suffix];
+  let new_changed = &new[prefix..new.len() - suffix];
+  let (n, m) = (old_changed.len(), new_changed.len());
+
+  let mut ops = Vec::with_capacity(old.len() + new.len());
+  ops.extend((0..prefix).map(|i| Op {
+    edit: Edit::Equal,
+    old: i,
+    new: i,
+  }));
+
+  if n * m <= MAX_TABLE_SIZE {
+    // lengths[i][j]: longest common subsequence
+    // of old_changed[i..] and new_changed[j..].
+    let index = |i: usize, j: usize| i * (m + 1) + j;
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+      for j in (0..m).rev() {
+        lengths[index(i, j)] = if old_changed[i] == new_changed[j] {
+          lengths[index(i + 1, j + 1)] + 1
+        } else {
+          lengths[index(i + 1, j)].max(lengths[index(i, j + 1)])
+        };
+      }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+      let (old_pos, new_pos) = (prefix + i, prefix + j);
+      if i < n && j < m && old_changed[i] == new_changed[j] {
+        ops.push(Op {
+          edit: Edit::Equal,
+          old: old_pos,
+          new: new_pos,
+        });
+        i += 1;
+        j += 1;
+      } else if i < n
+        && (j == m
+          || lengths[index(i + 1, j)] >= lengths[index(i, j + 1)])
+      {
+        ops.push(Op {
+          edit: Edit::Delete,
+          old: old_pos,
+          new: new_pos,
+        });
+        i += 1;
+      } else {
+        ops.push(Op {
+          edit: Edit::Insert,
+          old: old_pos,
+          new: new_pos,
+        });
+        j += 1;
+      }
+    }
+  } else {
+    ops.extend((0..n).map(|i| Op {
+      edit: Edit::Delete,
+      old: prefix + i,
+      new: prefix,
+    }));
+    ops.extend((0..m).map(|j| Op {
+      edit: Edit::Insert,
+      old: prefix + n,
+      new: prefix + j,
+    }));
+  }
+
+  ops.extend((0..suffix).map(|k| Op {
+    edit: Edit::Equal,
+    old: old.len() - suffix + k,
+    new: new.len() - suffix + k,
+  }));
+  ops
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn same_contents_give_empty_diff() {
+    assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+  }
+
+  #[test]
+  fn changed_line_with_context() {
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+    let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+    assert_eq!(
+      unified_diff(old, new, "old", "new"),
+      "--- old\n+++ new\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+    );
+  }
+
+  #[test]
+  fn distant_changes_are_separate_hunks() {
+    let old = (1..=20).map(|i| format!("{i}\n")).collect::<String>();
+    let new = (1..=20)
+      .map(|i| match i {
+        2 => String::from("two\n"),
+        19 => String::from("nineteen\n"),
+        i => format!("{i}\n"),
+      })
+      .collect::<String>();
+    assert_eq!(
+      unified_diff(&old, &new, "old", "new"),
+      "--- old\n+++ new\n@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n@@ -16,5 +16,5 @@\n 16\n 17\n 18\n-19\n+nineteen\n 20\n"
+    );
+  }
+
+  #[test]
+  fn new_file_is_all_inserts() {
+    assert_eq!(
+      unified_diff("", "a\nb\n", "old", "new"),
+      "--- old\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+    );
+  }
+
+  #[test]
+  fn large_change_falls_back_to_replace() {
+    let old =
+      (0..2000).map(|i| format!("old {i}\n")).collect::<String>();
+    let new =
+      (0..2000).map(|i| format!("new {i}\n")).collect::<String>();
+    let diff = unified_diff(&old, &new, "old", "new");
+    assert!(
+      diff.starts_with("--- old\n+++ new\n@@ -1,2000 +1,2000 @@\n")
+    );
+    assert_eq!(
+      diff.lines().filter(|line| line.starts_with('-')).count(),
+      2001
+    );
+  }
+}
@@ -0,0 +1,176 @@
+use bollard::{
+  errors::Error,
+  query_parameters::{
+    RemoveContainerOptions, RestartContainerOptionsBuilder,
+    StartContainerOptions, StopContainerOptionsBuilder,
+  },
+};
+use command::run_komodo_command;
+use komodo_client::entities::{
+  TerminationSignal, komodo_timestamp, logger::LogLevel, update::Log,
+};
+
+use crate::config::periphery_config;
+
+use super::{DockerClient, docker_client};
+
+impl DockerClient {
+  pub async fn start_container(
+    &self,
+    container_name: &str,
+  ) -> Result<(), Error> {
+    self
+      .docker
+      .start_container(container_name, None::<StartContainerOptions>)
+      .await
+  }
+
+  pub async fn restart_container(
+    &self,
+    container_name: &str,
+  ) -> Result<(), Error> {
+    self
+      .docker
+      .restart_container(
+        container_name,
+        Some(RestartContainerOptionsBuilder::default().build()),
+      )
+      .await
+  }
+
+  pub async fn stop_container(
+    &self,
+    container_name: &str,
+    signal: Option<TerminationSignal>,
+    time: Option<i32>,
+  ) -> Result<(), Error> {
+    let mut options = StopContainerOptionsBuilder::default();
+    if let Some(signal) = signal {
+      options = options.signal(&signal.to_string());
+    }
+    if let Some(time) = time {
+      options = options.t(time);
+    }
+    self
+      .docker
+      .stop_container(container_name, Some(options.build()))
+      .await
+  }
+
+  pub async fn stop_and_remove_container(
+    &self,
+    container_name: &str,
+    signal: Option<TerminationSignal>,
+    time: Option<i32>,
+  ) -> Result<(), Error> {
+    // The container may already be stopped.
+    not_modified_ok(
+      self.stop_container(container_name, signal, time).await,
+    )?;
+    self
+      .docker
+      .remove_container(
+        container_name,
+        None::<RemoveContainerOptions>,
+      )
+      .await
+  }
+}
+
+/// Runs a container action through the docker API
+/// when `prefer_docker_api` is enabled, otherwise runs the CLI `command`.
+///
+/// The returned log matches the CLI one: the `command` is recorded,
+/// stdout holds the container name, and daemon errors go to stderr.
+/// If the daemon can't be reached through the API,
+/// falls back to running the CLI `command`.
+///
+/// The daemon responds 304 when the container is already
+/// in the requested state, eg starting a running container,
+/// which is a success like it is with the CLI.
+pub async fn run_container_action<Fut>(
+  stage: &str,
+  container_name: &str,
+  command: String,
+  action: impl FnOnce(&'static DockerClient) -> Fut,
+) -> Log
+where
+  Fut: Future<Output = Result<(), Error>>,
+{
+  if !periphery_config().prefer_docker_api {
+    return run_komodo_command(stage, None, command).await;
+  }
+  let start_ts = komodo_timestamp();
+  let (success, stdout, stderr) = match not_modified_ok(
+    action(docker_client()).await,
+  ) {
+    Ok(()) => (true, container_name.to_string(), String::new()),
+    Err(Error::DockerResponseServerError { message, .. }) => (
+      false,
+      String::new(),
+      format!("Error response from daemon: {message}"),
+    ),
+    Err(e) => {
+      warn!(
+        "{stage} | Docker API unavailable, falling back to CLI | {e:?}"
+      );
+      return run_komodo_command(stage, None, command).await;
+    }
+  };
+  Log {
+    stage: stage.to_string(),
+    level: if success {
+      LogLevel::Info
+    } else {
+      LogLevel::Error
+    },
+    command,
+    stdout,
+    stderr,
+    success,
+    start_ts,
+    end_ts: komodo_timestamp(),
+  }
+}
+
+/// Maps the daemon's 304 Not Modified response, sent when
+/// the container is already in the requested state, to success.
+/// Bollard currently passes 304 through as Ok, this makes sure
+/// it stays a success rather than depending on that.
+fn not_modified_ok(res: Result<(), Error>) -> Result<(), Error> {
+  match res {
+    Err(Error::DockerResponseServerError {
+      status_code: 304,
+      ..
+    }) => Ok(()),
+    res => res,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn server_error(status_code: u16) -> Result<(), Error> {
+    Err(Error::DockerResponseServerError {
+      status_code,
+      message: String::new(),
+    })
+  }
+
+  #[test]
+  fn not_modified_is_success() {
+    assert!(not_modified_ok(server_error(304)).is_ok());
+  }
+
+  #[test]
+  fn other_errors_are_kept() {
+    assert!(matches!(
+      not_modified_ok(server_error(404)),
+      Err(Error::DockerResponseServerError {
+        status_code: 404,
+        ..
+      })
+    ));
+  }
+}
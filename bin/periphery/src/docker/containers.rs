@@ -9,8 +9,50 @@ use komodo_client::entities::docker::{
   container::*,
 };
 
+use crate::config::periphery_config;
+
 use super::{DockerClient, stats::container_stats};
 
+/// Whether the container passes the configured
+/// `include_container_labels` / `include_container_names` filter.
+/// When neither is configured, all containers are reported.
+pub fn container_is_reported(container: &ContainerListItem) -> bool {
+  let config = periphery_config();
+  container_matches(
+    container,
+    &config.include_container_labels.0,
+    &config.include_container_names.0,
+  )
+}
+
+fn container_matches(
+  container: &ContainerListItem,
+  labels: &[String],
+  names: &[String],
+) -> bool {
+  if labels.is_empty() && names.is_empty() {
+    return true;
+  }
+  names.iter().any(|name| name == &container.name)
+    || labels
+      .iter()
+      .any(|label| container_has_label(container, label))
+}
+
+/// Whether the container has the label,
+/// given as either a label key or a `key=value` pair.
+pub fn container_has_label(
+  container: &ContainerListItem,
+  label: &str,
+) -> bool {
+  match label.split_once('=') {
+    Some((key, value)) => {
+      container.labels.get(key).map(String::as_str) == Some(value)
+    }
+    None => container.labels.contains_key(label),
+  }
+}
+
 impl DockerClient {
   pub async fn list_containers(
     &self,
@@ -757,3 +799,57 @@ fn convert_isolation_mode(
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn container() -> ContainerListItem {
+    ContainerListItem {
+      name: String::from("app"),
+      labels: HashMap::from([
+        (String::from("komodo.managed"), String::from("true")),
+        (String::from("team"), String::from("web")),
+      ]),
+      ..Default::default()
+    }
+  }
+
+  fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+  }
+
+  #[test]
+  fn reports_all_without_filter() {
+    assert!(container_matches(&container(), &[], &[]));
+  }
+
+  #[test]
+  fn matches_label_key_or_key_value() {
+    let container = container();
+    assert!(container_has_label(&container, "komodo.managed"));
+    assert!(container_has_label(&container, "team=web"));
+    assert!(!container_has_label(&container, "team=db"));
+    assert!(container_matches(
+      &container,
+      &strings(&["team=db", "komodo.managed"]),
+      &[]
+    ));
+    assert!(!container_matches(
+      &container,
+      &strings(&["team=db", "other"]),
+      &[]
+    ));
+  }
+
+  #[test]
+  fn matches_name() {
+    let container = container();
+    assert!(container_matches(&container, &[], &strings(&["app"])));
+    assert!(!container_matches(
+      &container,
+      &strings(&["other"]),
+      &strings(&["db"])
+    ));
+  }
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashMap, sync::OnceLock, time::Duration};
+
+use bollard::{
+  models::EventMessage, query_parameters::EventsOptions,
+};
+use futures::StreamExt;
+use komodo_client::entities::{
+  docker::container::ContainerEvent, komodo_timestamp,
+};
+use tokio::sync::broadcast;
+
+use super::docker_client;
+
+/// Only these container actions are streamed to Core.
+/// `health_status` actions carry the status, eg `health_status: unhealthy`.
+const STREAMED_ACTIONS: [&str; 3] = ["die", "oom", "health_status"];
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Receives the significant container events from the docker daemon.
+/// Subscribe to get a receiver for new events.
+pub fn container_events() -> &'static broadcast::Sender<ContainerEvent>
+{
+  static CONTAINER_EVENTS: OnceLock<
+    broadcast::Sender<ContainerEvent>,
+  > = OnceLock::new();
+  CONTAINER_EVENTS.get_or_init(|| broadcast::channel(256).0)
+}
+
+/// Subscribes to `docker events`, forwarding the significant
+/// container events to [container_events].
+/// Reconnects if the event stream ends.
+pub fn spawn_event_stream() {
+  tokio::spawn(async move {
+    loop {
+      stream_events().await;
+      tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+  });
+}
+
+async fn stream_events() {
+  let filters = HashMap::from([
+    (String::from("type"), vec![String::from("container")]),
+    (
+      String::from("event"),
+      STREAMED_ACTIONS.iter().map(|a| a.to_string()).collect(),
+    ),
+  ]);
+  let mut events =
+    docker_client().docker.events(Some(EventsOptions {
+      filters: Some(filters),
+      ..Default::default()
+    }));
+  while let Some(event) = events.next().await {
+    let event = match event {
+      Ok(event) => event,
+      Err(e) => {
+        warn!("Docker event stream error, reconnecting | {e:?}");
+        return;
+      }
+    };
+    let Some(event) = container_event(event) else {
+      continue;
+    };
+    // Only fails when there are no receivers, ie Core isn't listening.
+    let _ = container_events().send(event);
+  }
+}
+
+/// None if the event is missing the action or container name.
+fn container_event(event: EventMessage) -> Option<ContainerEvent> {
+  let action = event.action?;
+  let attributes = event
+    .actor
+    .and_then(|actor| actor.attributes)
+    .unwrap_or_default();
+  let name = attributes.get("name").cloned()?;
+  let ts = event
+    .time_nano
+    .map(|nanos| nanos / 1_000_000)
+    .or(event.time.map(|secs| secs * 1_000))
+    .unwrap_or_else(komodo_timestamp);
+  Some(ContainerEvent {
+    name,
+    action,
+    exit_code: attributes.get("exitCode").cloned(),
+    ts,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use bollard::models::EventActor;
+
+  use super::*;
+
+  fn event(
+    action: Option<&str>,
+    attributes: &[(&str, &str)],
+  ) -> EventMessage {
+    EventMessage {
+      action: action.map(str::to_string),
+      actor: Some(EventActor {
+        attributes: Some(
+          attributes
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        ),
+        ..Default::default()
+      }),
+      time: Some(1_700_000_000),
+      time_nano: Some(1_700_000_000_123_456_789),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn converts_die_event() {
+    let event = container_event(event(
+      Some("die"),
+      &[("name", "web"), ("exitCode", "137")],
+    ))
+    .unwrap();
+    assert_eq!(
+      event,
+      ContainerEvent {
+        name: String::from("web"),
+        action: String::from("die"),
+        exit_code: Some(String::from("137")),
+        ts: 1_700_000_000_123,
+      }
+    );
+  }
+
+  #[test]
+  fn falls_back_to_event_time_in_seconds() {
+    let mut message =
+      event(Some("health_status: unhealthy"), &[("name", "web")]);
+    message.time_nano = None;
+    let event = container_event(message).unwrap();
+    assert_eq!(event.action, "health_status: unhealthy");
+    assert_eq!(event.exit_code, None);
+    assert_eq!(event.ts, 1_700_000_000_000);
+  }
+
+  #[test]
+  fn skips_events_without_action_or_name() {
+    assert!(
+      container_event(event(None, &[("name", "web")])).is_none()
+    );
+    assert!(
+      container_event(event(Some("die"), &[("image", "nginx")]))
+        .is_none()
+    );
+  }
+}
@@ -3,6 +3,7 @@ use komodo_client::entities::docker::{
   ContainerConfig, GraphDriverData, HealthConfig,
   container::ContainerListItem, image::*,
 };
+use periphery_client::api::DockerListFilter;
 
 use super::DockerClient;
 
@@ -10,8 +11,9 @@ impl DockerClient {
   pub async fn list_images(
     &self,
     containers: &[ContainerListItem],
+    filter: &DockerListFilter,
   ) -> anyhow::Result<Vec<ImageListItem>> {
-    let images = self
+    let mut images: Vec<_> = self
       .docker
       .list_images(Option::<ListImagesOptions>::None)
       .await?
@@ -37,7 +39,9 @@ impl DockerClient {
           in_use,
         }
       })
+      .filter(|image| filter.includes(image.in_use))
       .collect();
+    filter.truncate(&mut images);
     Ok(images)
   }
 
@@ -46,7 +50,14 @@ impl DockerClient {
     image_name: &str,
   ) -> anyhow::Result<Image> {
     let image = self.docker.inspect_image(image_name).await?;
+    // The breakdown is best effort, and shouldn't fail the inspect.
+    let layers = self
+      .image_history(image_name)
+      .await
+      .map(image_layers)
+      .unwrap_or_default();
     Ok(Image {
+      layers,
       id: image.id,
       repo_tags: image.repo_tags.unwrap_or_default(),
       repo_digests: image.repo_digests.unwrap_or_default(),
@@ -141,3 +152,64 @@ impl DockerClient {
     Ok(res)
   }
 }
+
+/// Keeps the history items which add to the image size.
+/// Their sizes sum to the total image size.
+fn image_layers(
+  history: Vec<ImageHistoryResponseItem>,
+) -> Vec<ImageLayer> {
+  let total = history.iter().map(|item| item.size).sum::<i64>();
+  history
+    .into_iter()
+    .filter(|item| item.size > 0)
+    .map(|item| ImageLayer {
+      created_by: item.created_by,
+      size: item.size,
+      size_percent: if total > 0 {
+        item.size as f64 / total as f64 * 100.0
+      } else {
+        0.0
+      },
+      created: item.created,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn history_item(
+    created_by: &str,
+    size: i64,
+  ) -> ImageHistoryResponseItem {
+    ImageHistoryResponseItem {
+      created_by: created_by.to_string(),
+      size,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn keeps_layers_adding_size() {
+    let layers = image_layers(vec![
+      history_item("CMD [\"serve\"]", 0),
+      history_item("COPY . /app", 750),
+      history_item("ENV MODE=prod", 0),
+      history_item("ADD rootfs.tar.xz /", 250),
+    ]);
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].created_by, "COPY . /app");
+    assert_eq!(layers[0].size, 750);
+    assert_eq!(layers[0].size_percent, 75.0);
+    assert_eq!(layers[1].size_percent, 25.0);
+  }
+
+  #[test]
+  fn empty_image_has_no_layers() {
+    assert!(
+      image_layers(vec![history_item("CMD [\"serve\"]", 0)])
+        .is_empty()
+    );
+  }
+}
@@ -0,0 +1,198 @@
+use std::{
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use bollard::models::{SystemInfo, SystemVersion};
+use command::run_komodo_command;
+use komodo_client::entities::docker::info::{
+  DockerInfo, DockerVersion, MINIMUM_DOCKER_VERSION,
+};
+use tokio::sync::OnceCell;
+
+use crate::compose::docker_compose;
+
+use super::{DockerClient, docker_client};
+
+impl DockerClient {
+  pub async fn docker_info(&self) -> anyhow::Result<DockerInfo> {
+    let (info, version) =
+      tokio::try_join!(self.docker.info(), self.docker.version())?;
+    Ok(docker_info(info, version))
+  }
+}
+
+fn docker_info(
+  info: SystemInfo,
+  version: SystemVersion,
+) -> DockerInfo {
+  DockerInfo {
+    version: version.version.or(info.server_version),
+    api_version: version.api_version,
+    operating_system: info.operating_system,
+    kernel_version: info.kernel_version,
+    architecture: info.architecture,
+    storage_driver: info.driver,
+    cgroup_version: info
+      .cgroup_version
+      .map(|version| version.to_string()),
+    cgroup_driver: info
+      .cgroup_driver
+      .map(|driver| driver.to_string()),
+    total_memory: info.mem_total,
+    cpus: info.ncpu,
+    swarm_state: info
+      .swarm
+      .and_then(|swarm| swarm.local_node_state)
+      .map(|state| state.to_string()),
+    containers: info.containers,
+    containers_running: info.containers_running,
+    images: info.images,
+    warnings: info.warnings.unwrap_or_default(),
+  }
+}
+
+/// How long to wait after a failed detection before trying again.
+const DOCKER_VERSION_RETRY_INTERVAL: Duration =
+  Duration::from_secs(60);
+
+/// Detected once, the docker versions don't change
+/// while Periphery is running.
+///
+/// If detection fails, it isn't retried until
+/// [DOCKER_VERSION_RETRY_INTERVAL] has passed,
+/// and the failure is only logged at warn level the first time.
+pub async fn docker_version() -> Option<&'static DockerVersion> {
+  static DOCKER_VERSION: OnceCell<DockerVersion> =
+    OnceCell::const_new();
+  /// (last failure, whether the failure has been logged)
+  static LAST_FAILURE: Mutex<Option<(Instant, bool)>> =
+    Mutex::new(None);
+
+  if let Some(version) = DOCKER_VERSION.get() {
+    return Some(version);
+  }
+
+  let logged = match *LAST_FAILURE.lock().unwrap() {
+    Some((failed_at, _))
+      if failed_at.elapsed() < DOCKER_VERSION_RETRY_INTERVAL =>
+    {
+      return None;
+    }
+    Some((_, logged)) => logged,
+    None => false,
+  };
+
+  match DOCKER_VERSION.get_or_try_init(detect_docker_version).await {
+    Ok(version) => {
+      LAST_FAILURE.lock().unwrap().take();
+      Some(version)
+    }
+    Err(e) => {
+      if logged {
+        debug!("Failed to detect docker version | {e:#}");
+      } else {
+        warn!(
+          "Failed to detect docker version, retrying every {}s | {e:#}",
+          DOCKER_VERSION_RETRY_INTERVAL.as_secs()
+        );
+      }
+      *LAST_FAILURE.lock().unwrap() = Some((Instant::now(), true));
+      None
+    }
+  }
+}
+
+async fn detect_docker_version() -> anyhow::Result<DockerVersion> {
+  let version = docker_client().docker.version().await?.version;
+  let (compose, buildx) = tokio::join!(
+    run_komodo_command(
+      "Compose Version",
+      None,
+      format!("{} version --short", docker_compose()),
+    ),
+    run_komodo_command(
+      "Buildx Version",
+      None,
+      "docker buildx version"
+    ),
+  );
+  let compose_version =
+    compose.success.then(|| compose.stdout.trim().to_string());
+  // Output is like `github.com/docker/buildx v0.17.1 257815a`
+  let buildx_version = buildx.success.then(|| {
+    buildx
+      .stdout
+      .split_whitespace()
+      .nth(1)
+      .unwrap_or_default()
+      .to_string()
+  });
+  let version =
+    DockerVersion::new(version, compose_version, buildx_version);
+  if !version.supported {
+    warn!(
+      "Docker version {} is older than the minimum supported {}.{}",
+      version.version.as_deref().unwrap_or("unknown"),
+      MINIMUM_DOCKER_VERSION.0,
+      MINIMUM_DOCKER_VERSION.1
+    );
+  }
+  Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+  use bollard::models::{
+    LocalNodeState, SwarmInfo, SystemInfoCgroupDriverEnum,
+    SystemInfoCgroupVersionEnum,
+  };
+
+  use super::*;
+
+  #[test]
+  fn combines_info_and_version() {
+    let info = SystemInfo {
+      server_version: Some(String::from("27.3.0")),
+      operating_system: Some(String::from("Ubuntu 24.04.1 LTS")),
+      driver: Some(String::from("overlay2")),
+      cgroup_version: Some(SystemInfoCgroupVersionEnum::_2),
+      cgroup_driver: Some(SystemInfoCgroupDriverEnum::SYSTEMD),
+      mem_total: Some(8_000_000_000),
+      ncpu: Some(4),
+      swarm: Some(SwarmInfo {
+        local_node_state: Some(LocalNodeState::ACTIVE),
+        ..Default::default()
+      }),
+      containers_running: Some(3),
+      ..Default::default()
+    };
+    let version = SystemVersion {
+      version: Some(String::from("27.3.1")),
+      api_version: Some(String::from("1.47")),
+      ..Default::default()
+    };
+    let info = docker_info(info, version);
+    assert_eq!(info.version.as_deref(), Some("27.3.1"));
+    assert_eq!(info.api_version.as_deref(), Some("1.47"));
+    assert_eq!(info.storage_driver.as_deref(), Some("overlay2"));
+    assert_eq!(info.cgroup_version.as_deref(), Some("2"));
+    assert_eq!(info.cgroup_driver.as_deref(), Some("systemd"));
+    assert_eq!(info.swarm_state.as_deref(), Some("active"));
+    assert_eq!(info.total_memory, Some(8_000_000_000));
+    assert_eq!(info.cpus, Some(4));
+    assert_eq!(info.containers_running, Some(3));
+    assert!(info.warnings.is_empty());
+  }
+
+  #[test]
+  fn falls_back_to_info_server_version() {
+    let info = SystemInfo {
+      server_version: Some(String::from("27.3.0")),
+      ..Default::default()
+    };
+    let info = docker_info(info, SystemVersion::default());
+    assert_eq!(info.version.as_deref(), Some("27.3.0"));
+    assert_eq!(info.swarm_state, None);
+  }
+}
@@ -6,10 +6,17 @@ use command::run_komodo_command;
 use komodo_client::entities::{TerminationSignal, update::Log};
 use run_command::async_run_command;
 
+pub mod events;
 pub mod stats;
 
+pub use actions::run_container_action;
+pub use containers::container_is_reported;
+pub use info::docker_version;
+
+mod actions;
 mod containers;
 mod images;
+mod info;
 mod networks;
 mod volumes;
 
@@ -1,6 +1,6 @@
 use std::{
-  collections::HashMap,
-  sync::{Arc, OnceLock},
+  collections::{HashMap, VecDeque},
+  sync::{Arc, Mutex, OnceLock},
 };
 
 use anyhow::{Context, anyhow};
@@ -8,18 +8,30 @@ use arc_swap::ArcSwap;
 use async_timing_util::wait_until_timelength;
 use bollard::{models, query_parameters::StatsOptionsBuilder};
 use futures::StreamExt;
-use komodo_client::entities::docker::{
-  container::ContainerStats,
-  stats::{
-    ContainerBlkioStatEntry, ContainerBlkioStats, ContainerCpuStats,
-    ContainerCpuUsage, ContainerMemoryStats, ContainerNetworkStats,
-    ContainerPidsStats, ContainerStorageStats,
-    ContainerThrottlingData, FullContainerStats,
+use komodo_client::entities::{
+  docker::{
+    container::{
+      ContainerListItem, ContainerStateStatusEnum, ContainerStats,
+      ContainerStatsSample,
+    },
+    stats::{
+      ContainerBlkioStatEntry, ContainerBlkioStats,
+      ContainerCpuStats, ContainerCpuUsage, ContainerMemoryStats,
+      ContainerNetworkStats, ContainerPidsStats,
+      ContainerStorageStats, ContainerThrottlingData,
+      FullContainerStats,
+    },
   },
+  komodo_timestamp,
 };
 use run_command::async_run_command;
 
-use crate::{config::periphery_config, docker::DockerClient};
+use crate::{
+  config::periphery_config,
+  docker::{
+    DockerClient, containers::container_has_label, docker_client,
+  },
+};
 
 pub type ContainerStatsMap = HashMap<String, ContainerStats>;
 
@@ -29,6 +41,56 @@ pub fn container_stats() -> &'static ArcSwap<ContainerStatsMap> {
   CONTAINER_STATS.get_or_init(Default::default)
 }
 
+/// Container name -> recent stats samples, oldest first.
+type ContainerStatsHistory =
+  HashMap<String, VecDeque<ContainerStatsSample>>;
+
+fn container_stats_history() -> &'static Mutex<ContainerStatsHistory>
+{
+  static CONTAINER_STATS_HISTORY: OnceLock<
+    Mutex<ContainerStatsHistory>,
+  > = OnceLock::new();
+  CONTAINER_STATS_HISTORY.get_or_init(Default::default)
+}
+
+/// The recent stats samples of the container, oldest first.
+pub fn get_container_stats_history(
+  container_name: &str,
+) -> Vec<ContainerStatsSample> {
+  container_stats_history()
+    .lock()
+    .unwrap()
+    .get(container_name)
+    .map(|samples| samples.iter().cloned().collect())
+    .unwrap_or_default()
+}
+
+/// Appends the polled stats to each container's history,
+/// keeping the latest `size` samples.
+/// Containers missing from the poll have their history dropped.
+fn record_container_stats_history(
+  history: &mut ContainerStatsHistory,
+  stats: &[ContainerStats],
+  ts: i64,
+  size: usize,
+) {
+  history.retain(|name, _| stats.iter().any(|s| &s.name == name));
+  if size == 0 {
+    history.clear();
+    return;
+  }
+  for stats in stats {
+    let samples = history.entry(stats.name.clone()).or_default();
+    while samples.len() >= size {
+      samples.pop_front();
+    }
+    samples.push_back(ContainerStatsSample {
+      ts,
+      stats: stats.clone(),
+    });
+  }
+}
+
 pub fn spawn_polling_thread() {
   tokio::spawn(async move {
     let polling_rate = periphery_config()
@@ -45,8 +107,14 @@ pub fn spawn_polling_thread() {
 }
 
 async fn update_container_stats() {
-  match get_container_stats(None).await {
+  match poll_container_stats().await {
     Ok(stats) => {
+      record_container_stats_history(
+        &mut container_stats_history().lock().unwrap(),
+        &stats,
+        komodo_timestamp(),
+        periphery_config().container_stats_history,
+      );
       container_stats().store(Arc::new(
         stats.into_iter().map(|s| (s.name.clone(), s)).collect(),
       ));
@@ -57,6 +125,70 @@ async fn update_container_stats() {
   }
 }
 
+/// Gets the stats of the running containers with one of the
+/// `container_stats_labels`, in batches of `container_stats_concurrency`.
+async fn poll_container_stats() -> anyhow::Result<Vec<ContainerStats>>
+{
+  let config = periphery_config();
+  if config.container_stats_labels.is_empty()
+    && config.container_stats_concurrency == 0
+  {
+    return get_container_stats(None).await;
+  }
+  let containers = docker_client()
+    .list_containers()
+    .await
+    .context("Failed to list containers")?;
+  let batches = stats_batches(
+    containers,
+    &config.container_stats_labels.0,
+    config.container_stats_concurrency,
+  );
+  let mut stats = Vec::new();
+  for batch in batches {
+    // A container stopping after being listed fails its batch,
+    // which shouldn't prevent the other batches from updating.
+    match get_container_stats(Some(batch)).await {
+      Ok(batch) => stats.extend(batch),
+      Err(e) => {
+        warn!("Failed to get container stats for batch | {e:#}")
+      }
+    }
+  }
+  Ok(stats)
+}
+
+/// The names of the running containers with one of the `labels`,
+/// joined into batches of at most `concurrency` containers.
+fn stats_batches(
+  containers: Vec<ContainerListItem>,
+  labels: &[String],
+  concurrency: usize,
+) -> Vec<String> {
+  let names = containers
+    .into_iter()
+    .filter(|container| {
+      container.state == ContainerStateStatusEnum::Running
+        && (labels.is_empty()
+          || labels
+            .iter()
+            .any(|label| container_has_label(container, label)))
+    })
+    .map(|container| container.name)
+    .collect::<Vec<_>>();
+  if names.is_empty() {
+    return Vec::new();
+  }
+  let batch_size = match concurrency {
+    0 => names.len(),
+    concurrency => concurrency,
+  };
+  names
+    .chunks(batch_size)
+    .map(|batch| batch.join(" "))
+    .collect()
+}
+
 pub async fn get_container_stats(
   container_name: Option<String>,
 ) -> anyhow::Result<Vec<ContainerStats>> {
@@ -106,6 +238,9 @@ impl DockerClient {
       .await
       .with_context(|| format!("Unable to get container stats for {container_name} (got None)"))?
       .with_context(|| format!("Unable to get container stats for {container_name}"))?;
+    let cpu_stats = stats.cpu_stats.map(convert_cpu_stats);
+    let precpu_stats = stats.precpu_stats.map(convert_cpu_stats);
+    let memory_stats = stats.memory_stats.map(convert_memory_stats);
     Ok(FullContainerStats {
       name: stats.name.unwrap_or(container_name.to_string()),
       id: stats.id,
@@ -115,14 +250,71 @@ impl DockerClient {
       blkio_stats: stats.blkio_stats.map(convert_blkio_stats),
       num_procs: stats.num_procs,
       storage_stats: stats.storage_stats.map(convert_storage_stats),
-      cpu_stats: stats.cpu_stats.map(convert_cpu_stats),
-      precpu_stats: stats.precpu_stats.map(convert_cpu_stats),
-      memory_stats: stats.memory_stats.map(convert_memory_stats),
+      cpu_perc: cpu_stats
+        .as_ref()
+        .zip(precpu_stats.as_ref())
+        .and_then(|(cpu, precpu)| cpu_percentage(cpu, precpu)),
+      mem_perc: memory_stats.as_ref().and_then(memory_percentage),
+      cpu_stats,
+      precpu_stats,
+      memory_stats,
       networks: stats.networks.map(convert_network_stats),
     })
   }
 }
 
+/// Computes the CPU percentage the same way as the docker CLI:
+/// the container's share of the host CPU time between the samples,
+/// scaled by the number of CPUs.
+fn cpu_percentage(
+  cpu: &ContainerCpuStats,
+  precpu: &ContainerCpuStats,
+) -> Option<f64> {
+  let usage = cpu.cpu_usage.as_ref()?;
+  let cpu_delta = usage
+    .total_usage?
+    .checked_sub(precpu.cpu_usage.as_ref()?.total_usage?)?;
+  let system_delta = cpu
+    .system_cpu_usage?
+    .checked_sub(precpu.system_cpu_usage.unwrap_or_default())?;
+  let online_cpus = match cpu.online_cpus {
+    Some(cpus) if cpus > 0 => cpus as usize,
+    _ => usage
+      .percpu_usage
+      .as_ref()
+      .map(Vec::len)
+      .unwrap_or_default(),
+  };
+  if system_delta == 0 || online_cpus == 0 {
+    return Some(0.0);
+  }
+  Some(
+    cpu_delta as f64 / system_delta as f64
+      * online_cpus as f64
+      * 100.0,
+  )
+}
+
+/// Computes the memory percentage the same way as the docker CLI,
+/// excluding the inactive page cache from the usage.
+fn memory_percentage(memory: &ContainerMemoryStats) -> Option<f64> {
+  let usage = memory.usage?;
+  let limit = memory.limit.filter(|limit| *limit > 0)?;
+  // cgroup v1 reports `total_inactive_file`, cgroup v2 `inactive_file`.
+  let cache = memory
+    .stats
+    .as_ref()
+    .and_then(|stats| {
+      stats
+        .get("total_inactive_file")
+        .or_else(|| stats.get("inactive_file"))
+    })
+    .copied()
+    .filter(|cache| *cache < usage)
+    .unwrap_or_default();
+  Some((usage - cache) as f64 / limit as f64 * 100.0)
+}
+
 fn convert_pids_stats(
   pids_stats: models::ContainerPidsStats,
 ) -> ContainerPidsStats {
@@ -264,3 +456,180 @@ fn convert_network_stats(
     })
     .collect()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn container(
+    name: &str,
+    state: ContainerStateStatusEnum,
+    label: Option<&str>,
+  ) -> ContainerListItem {
+    ContainerListItem {
+      name: name.to_string(),
+      state,
+      labels: label
+        .map(|label| {
+          HashMap::from([(label.to_string(), String::new())])
+        })
+        .unwrap_or_default(),
+      ..Default::default()
+    }
+  }
+
+  fn containers() -> Vec<ContainerListItem> {
+    use ContainerStateStatusEnum::*;
+    vec![
+      container("a", Running, Some("komodo.managed")),
+      container("b", Running, None),
+      container("c", Exited, Some("komodo.managed")),
+      container("d", Running, Some("komodo.managed")),
+    ]
+  }
+
+  #[test]
+  fn stats_batches_only_running() {
+    assert_eq!(stats_batches(containers(), &[], 0), vec!["a b d"]);
+  }
+
+  #[test]
+  fn stats_batches_filter_labels() {
+    let labels = vec![String::from("komodo.managed")];
+    assert_eq!(stats_batches(containers(), &labels, 0), vec!["a d"]);
+    let labels = vec![String::from("other")];
+    assert!(stats_batches(containers(), &labels, 0).is_empty());
+  }
+
+  #[test]
+  fn stats_batches_limit_concurrency() {
+    assert_eq!(stats_batches(containers(), &[], 2), vec!["a b", "d"]);
+  }
+
+  fn stats(name: &str) -> ContainerStats {
+    ContainerStats {
+      name: name.to_string(),
+      cpu_perc: String::new(),
+      mem_perc: String::new(),
+      mem_usage: String::new(),
+      net_io: String::new(),
+      block_io: String::new(),
+      pids: String::new(),
+    }
+  }
+
+  fn sample_ts(
+    history: &ContainerStatsHistory,
+    name: &str,
+  ) -> Vec<i64> {
+    history[name].iter().map(|sample| sample.ts).collect()
+  }
+
+  #[test]
+  fn stats_history_keeps_latest_samples() {
+    let mut history = ContainerStatsHistory::new();
+    for ts in 1..=4 {
+      record_container_stats_history(
+        &mut history,
+        &[stats("a")],
+        ts,
+        3,
+      );
+    }
+    assert_eq!(sample_ts(&history, "a"), vec![2, 3, 4]);
+  }
+
+  #[test]
+  fn stats_history_drops_missing_containers() {
+    let mut history = ContainerStatsHistory::new();
+    record_container_stats_history(
+      &mut history,
+      &[stats("a"), stats("b")],
+      1,
+      3,
+    );
+    record_container_stats_history(&mut history, &[stats("b")], 2, 3);
+    assert!(!history.contains_key("a"));
+    assert_eq!(sample_ts(&history, "b"), vec![1, 2]);
+  }
+
+  #[test]
+  fn stats_history_disabled_with_zero_size() {
+    let mut history = ContainerStatsHistory::new();
+    record_container_stats_history(&mut history, &[stats("a")], 1, 0);
+    assert!(history.is_empty());
+  }
+
+  fn cpu(
+    total_usage: u64,
+    system_cpu_usage: u64,
+    online_cpus: Option<u32>,
+  ) -> ContainerCpuStats {
+    ContainerCpuStats {
+      cpu_usage: Some(ContainerCpuUsage {
+        total_usage: Some(total_usage),
+        percpu_usage: Some(vec![0; 4]),
+        ..Default::default()
+      }),
+      system_cpu_usage: Some(system_cpu_usage),
+      online_cpus,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn cpu_percentage_scaled_by_cpus() {
+    let precpu = cpu(100, 1_000, Some(2));
+    assert_eq!(
+      cpu_percentage(&cpu(200, 2_000, Some(2)), &precpu),
+      Some(20.0)
+    );
+    // Falls back to the number of per CPU usages.
+    assert_eq!(
+      cpu_percentage(&cpu(200, 2_000, None), &precpu),
+      Some(40.0)
+    );
+  }
+
+  #[test]
+  fn cpu_percentage_without_system_delta() {
+    let precpu = cpu(100, 1_000, Some(2));
+    assert_eq!(
+      cpu_percentage(&cpu(200, 1_000, Some(2)), &precpu),
+      Some(0.0)
+    );
+    assert_eq!(
+      cpu_percentage(&ContainerCpuStats::default(), &precpu),
+      None
+    );
+  }
+
+  #[test]
+  fn memory_percentage_excludes_inactive_cache() {
+    let memory = |stat: &str| ContainerMemoryStats {
+      usage: Some(600),
+      limit: Some(1_000),
+      stats: Some(HashMap::from([(stat.to_string(), 100)])),
+      ..Default::default()
+    };
+    assert_eq!(
+      memory_percentage(&memory("total_inactive_file")),
+      Some(50.0)
+    );
+    assert_eq!(
+      memory_percentage(&memory("inactive_file")),
+      Some(50.0)
+    );
+    assert_eq!(memory_percentage(&memory("other")), Some(60.0));
+  }
+
+  #[test]
+  fn memory_percentage_without_limit() {
+    let memory = ContainerMemoryStats {
+      usage: Some(600),
+      limit: Some(0),
+      ..Default::default()
+    };
+    assert_eq!(memory_percentage(&memory), None);
+  }
+}
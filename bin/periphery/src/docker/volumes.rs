@@ -2,6 +2,7 @@ use bollard::query_parameters::ListVolumesOptions;
 use komodo_client::entities::docker::{
   PortBinding, container::ContainerListItem, volume::*,
 };
+use periphery_client::api::DockerListFilter;
 
 use crate::docker::DockerClient;
 
@@ -9,8 +10,9 @@ impl DockerClient {
   pub async fn list_volumes(
     &self,
     containers: &[ContainerListItem],
+    filter: &DockerListFilter,
   ) -> anyhow::Result<Vec<VolumeListItem>> {
-    let volumes = self
+    let mut volumes: Vec<_> = self
       .docker
       .list_volumes(Option::<ListVolumesOptions>::None)
       .await?
@@ -45,7 +47,9 @@ impl DockerClient {
           in_use,
         }
       })
+      .filter(|volume| filter.includes(volume.in_use))
       .collect();
+    filter.truncate(&mut volumes);
     Ok(volumes)
   }
 
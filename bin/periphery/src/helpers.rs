@@ -1,9 +1,13 @@
-use anyhow::Context;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, anyhow};
 use komodo_client::{
   entities::{EnvironmentVar, RepoExecutionArgs, SearchCombinator},
   parsers::QUOTE_PATTERN,
 };
 
+use tokio::{fs, io::AsyncWriteExt};
+
 use crate::config::periphery_config;
 
 pub fn git_token_simple(
@@ -92,3 +96,263 @@ pub fn log_grep(
     }
   }
 }
+
+/// Validates a path on the host given in a request,
+/// which must be absolute and not traverse with `..`.
+/// Returns the path along with its parent directory and file name.
+pub fn validate_host_file_path(
+  path: &str,
+) -> anyhow::Result<(&Path, &Path, &str)> {
+  let path = Path::new(path);
+  if !path.is_absolute() {
+    return Err(anyhow!("Path must be absolute. Got: {path:?}"));
+  }
+  if path
+    .components()
+    .any(|component| component == Component::ParentDir)
+  {
+    return Err(anyhow!("Path must not contain '..'. Got: {path:?}"));
+  }
+  let parent = path.parent().with_context(|| {
+    format!("Path has no parent directory: {path:?}")
+  })?;
+  let file_name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .with_context(|| format!("Path has no file name: {path:?}"))?;
+  Ok((path, parent, file_name))
+}
+
+/// Writes the contents to a temporary file next to the target,
+/// then renames it into place, so the target is never left half written.
+/// Symlinks are followed, so the file they point to is written.
+/// If the rename fails, eg when the target is a bind mounted file,
+/// the target is written in place instead.
+///
+/// An existing file is first copied into the `backup_dir`
+/// so the previous version can be recovered, and its path is returned.
+/// The backups are kept out of the stack / build directories
+/// so they are never committed to the repo.
+pub async fn write_file_with_backup(
+  path: &Path,
+  contents: &str,
+) -> anyhow::Result<Option<PathBuf>> {
+  let config = periphery_config();
+  write_file_with_backup_in(
+    path,
+    contents,
+    &config.backup_dir(),
+    &config.root_directory,
+  )
+  .await
+}
+
+async fn write_file_with_backup_in(
+  path: &Path,
+  contents: &str,
+  backup_dir: &Path,
+  root_directory: &Path,
+) -> anyhow::Result<Option<PathBuf>> {
+  // Write through symlinks rather than replacing them.
+  let path = fs::canonicalize(path)
+    .await
+    .unwrap_or_else(|_| path.to_path_buf());
+  let parent = path.parent().with_context(|| {
+    format!("Path has no parent directory: {path:?}")
+  })?;
+  let file_name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .with_context(|| format!("Path has no file name: {path:?}"))?;
+  let tmp_path = parent.join(format!(".{file_name}.komodo-tmp"));
+
+  let res = async {
+    let mut file =
+      fs::File::create(&tmp_path).await.with_context(|| {
+        format!("Failed to create temporary file {tmp_path:?}")
+      })?;
+    file.write_all(contents.as_bytes()).await.with_context(|| {
+      format!("Failed to write temporary file {tmp_path:?}")
+    })?;
+    file.sync_all().await.with_context(|| {
+      format!("Failed to sync temporary file {tmp_path:?}")
+    })?;
+
+    let backup_path = match fs::metadata(&path).await {
+      Ok(metadata) => {
+        // Keep the permissions of the file being replaced.
+        fs::set_permissions(&tmp_path, metadata.permissions())
+          .await
+          .with_context(|| {
+            format!("Failed to set permissions on {tmp_path:?}")
+          })?;
+        let backup_path =
+          backup_path(backup_dir, root_directory, &path);
+        if let Some(backup_parent) = backup_path.parent() {
+          fs::create_dir_all(backup_parent).await.with_context(|| {
+            format!("Failed to create backup directory {backup_parent:?}")
+          })?;
+        }
+        fs::copy(&path, &backup_path).await.with_context(|| {
+          format!("Failed to back up {path:?} to {backup_path:?}")
+        })?;
+        Some(backup_path)
+      }
+      Err(_) => None,
+    };
+
+    if let Err(e) = fs::rename(&tmp_path, &path).await {
+      warn!(
+        "Failed to move {tmp_path:?} to {path:?}, writing in place | {e:?}"
+      );
+      fs::write(&path, contents).await.with_context(|| {
+        format!("Failed to write contents to {path:?}")
+      })?;
+      let _ = fs::remove_file(&tmp_path).await;
+    }
+
+    anyhow::Ok(backup_path)
+  }
+  .await;
+
+  if res.is_err() {
+    let _ = fs::remove_file(&tmp_path).await;
+  }
+
+  res
+}
+
+/// The backup of the file keeps its path, relative to
+/// the `root_directory` if it is inside it.
+fn backup_path(
+  backup_dir: &Path,
+  root_directory: &Path,
+  path: &Path,
+) -> PathBuf {
+  let relative = path.strip_prefix(root_directory).unwrap_or(path);
+  backup_dir.join(
+    relative
+      .components()
+      .filter(|component| matches!(component, Component::Normal(_)))
+      .collect::<PathBuf>(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backup_path_is_relative_to_root_directory() {
+    assert_eq!(
+      backup_path(
+        Path::new("/etc/komodo/backups"),
+        Path::new("/etc/komodo"),
+        Path::new("/etc/komodo/stacks/app/compose.yaml"),
+      ),
+      Path::new("/etc/komodo/backups/stacks/app/compose.yaml")
+    );
+  }
+
+  #[test]
+  fn backup_path_outside_root_directory() {
+    assert_eq!(
+      backup_path(
+        Path::new("/etc/komodo/backups"),
+        Path::new("/etc/komodo"),
+        Path::new("/srv/stacks/app/compose.yaml"),
+      ),
+      Path::new("/etc/komodo/backups/srv/stacks/app/compose.yaml")
+    );
+  }
+
+  /// A fresh root directory holding a `stack` directory.
+  async fn root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir()
+      .join(format!("komodo-backup-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root).await;
+    fs::create_dir_all(root.join("stack")).await.unwrap();
+    root
+  }
+
+  #[tokio::test]
+  async fn backs_up_existing_file() {
+    let root = root("existing").await;
+    let file = root.join("stack/compose.yaml");
+    fs::write(&file, "old").await.unwrap();
+    let backup = write_file_with_backup_in(
+      &file,
+      "new",
+      &root.join("backups"),
+      &root,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(backup, root.join("backups/stack/compose.yaml"));
+    assert_eq!(fs::read_to_string(&backup).await.unwrap(), "old");
+    assert_eq!(fs::read_to_string(&file).await.unwrap(), "new");
+    assert!(
+      !fs::try_exists(root.join("stack/.compose.yaml.komodo-tmp"))
+        .await
+        .unwrap()
+    );
+    fs::remove_dir_all(&root).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn writes_missing_file_without_backup() {
+    let root = root("missing").await;
+    let file = root.join("stack/compose.yaml");
+    let backup = write_file_with_backup_in(
+      &file,
+      "new",
+      &root.join("backups"),
+      &root,
+    )
+    .await
+    .unwrap();
+    assert_eq!(backup, None);
+    assert_eq!(fs::read_to_string(&file).await.unwrap(), "new");
+    assert!(!fs::try_exists(root.join("backups")).await.unwrap());
+    fs::remove_dir_all(&root).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn original_survives_failed_write() {
+    let root = root("failed").await;
+    let file = root.join("stack/compose.yaml");
+    fs::write(&file, "old").await.unwrap();
+    // The backup directory can't be created over a file.
+    let backup_dir = root.join("backups");
+    fs::write(&backup_dir, "").await.unwrap();
+    write_file_with_backup_in(&file, "new", &backup_dir, &root)
+      .await
+      .unwrap_err();
+    assert_eq!(fs::read_to_string(&file).await.unwrap(), "old");
+    assert!(
+      !fs::try_exists(root.join("stack/.compose.yaml.komodo-tmp"))
+        .await
+        .unwrap()
+    );
+    fs::remove_dir_all(&root).await.unwrap();
+  }
+
+  #[test]
+  fn validates_host_file_path() {
+    let (path, dir, file) =
+      validate_host_file_path("/srv/backups/data.tar.gz").unwrap();
+    assert_eq!(path, Path::new("/srv/backups/data.tar.gz"));
+    assert_eq!(dir, Path::new("/srv/backups"));
+    assert_eq!(file, "data.tar.gz");
+  }
+
+  #[test]
+  fn rejects_relative_host_file_path() {
+    assert!(validate_host_file_path("backups/data.tar.gz").is_err());
+    assert!(
+      validate_host_file_path("/srv/backups/../data.tar.gz").is_err()
+    );
+    assert!(validate_host_file_path("/").is_err());
+  }
+}
@@ -12,9 +12,12 @@ mod api;
 mod build;
 mod compose;
 mod config;
+mod diff;
 mod docker;
 mod git;
 mod helpers;
+mod ping;
+mod scan;
 mod ssl;
 mod stats;
 mod terminal;
@@ -32,8 +35,14 @@ async fn app() -> anyhow::Result<()> {
     info!("{:?}", config.sanitized());
   }
 
+  command::set_shell(&config.shell).map_err(anyhow::Error::msg)?;
+  command::set_allowed_directories(
+    config.allowed_command_directories.0.clone(),
+  );
+
   stats::spawn_polling_thread();
   docker::stats::spawn_polling_thread();
+  docker::events::spawn_event_stream();
 
   let addr = format!(
     "{}:{}",
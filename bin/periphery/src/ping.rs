@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use async_timing_util::get_timelength_in_ms;
+
+use crate::config::periphery_config;
+
+/// Consecutive answered pings before the interval widens.
+const WIDEN_AFTER: u32 = 3;
+
+/// Consecutive missed pings before the connection is considered dead.
+const MAX_MISSED: u32 = 3;
+
+/// Adapts the interval between websocket pings to the connection quality.
+/// Widens (doubles) while pings are answered, so stable links
+/// aren't pinged needlessly, and narrows (halves) after a miss,
+/// so a failing link is detected quickly.
+pub struct AdaptivePing {
+  interval: Duration,
+  min: Duration,
+  max: Duration,
+  awaiting_pong: bool,
+  answered: u32,
+  missed: u32,
+}
+
+impl Default for AdaptivePing {
+  fn default() -> Self {
+    let config = periphery_config();
+    AdaptivePing::new(
+      timelength_duration(config.min_ping_interval),
+      timelength_duration(config.max_ping_interval),
+    )
+  }
+}
+
+impl AdaptivePing {
+  /// Starts at the `min` interval.
+  pub fn new(min: Duration, max: Duration) -> AdaptivePing {
+    let max = max.max(min);
+    AdaptivePing {
+      interval: min,
+      min,
+      max,
+      awaiting_pong: false,
+      answered: 0,
+      missed: 0,
+    }
+  }
+
+  /// The time to wait before the next ping.
+  pub fn interval(&self) -> Duration {
+    self.interval
+  }
+
+  /// Call when sending a ping. Adjusts the interval based on
+  /// whether the previous ping was answered.
+  ///
+  /// Returns false if too many pings in a row have been missed,
+  /// and the connection should be closed.
+  pub fn on_ping(&mut self) -> bool {
+    if self.awaiting_pong {
+      self.missed += 1;
+      self.answered = 0;
+      self.interval = (self.interval / 2).max(self.min);
+    } else if self.answered >= WIDEN_AFTER {
+      self.answered = 0;
+      self.interval = (self.interval * 2).min(self.max);
+    }
+    self.awaiting_pong = true;
+    self.missed < MAX_MISSED
+  }
+
+  /// Call when a pong is received.
+  pub fn on_pong(&mut self) {
+    if self.awaiting_pong {
+      self.awaiting_pong = false;
+      self.answered += 1;
+      self.missed = 0;
+    }
+  }
+}
+
+fn timelength_duration(
+  timelength: komodo_client::entities::Timelength,
+) -> Duration {
+  let timelength = timelength
+    .to_string()
+    .parse()
+    .expect("invalid ping interval");
+  Duration::from_millis(get_timelength_in_ms(timelength) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MIN: Duration = Duration::from_secs(5);
+  const MAX: Duration = Duration::from_secs(40);
+
+  fn answered(ping: &mut AdaptivePing, times: u32) {
+    for _ in 0..times {
+      assert!(ping.on_ping());
+      ping.on_pong();
+    }
+  }
+
+  #[test]
+  fn widens_while_answered() {
+    let mut ping = AdaptivePing::new(MIN, MAX);
+    assert_eq!(ping.interval(), MIN);
+    answered(&mut ping, WIDEN_AFTER);
+    assert_eq!(ping.interval(), MIN);
+    answered(&mut ping, 1);
+    assert_eq!(ping.interval(), MIN * 2);
+    answered(&mut ping, WIDEN_AFTER * 10);
+    assert_eq!(ping.interval(), MAX);
+  }
+
+  #[test]
+  fn narrows_after_miss() {
+    let mut ping = AdaptivePing::new(MIN, MAX);
+    answered(&mut ping, WIDEN_AFTER * 10);
+    assert_eq!(ping.interval(), MAX);
+    // The last ping wasn't answered.
+    assert!(ping.on_ping());
+    assert!(ping.on_ping());
+    assert_eq!(ping.interval(), MAX / 2);
+  }
+
+  #[test]
+  fn closes_after_max_missed() {
+    let mut ping = AdaptivePing::new(MIN, MAX);
+    assert!(ping.on_ping());
+    for _ in 1..MAX_MISSED {
+      assert!(ping.on_ping());
+    }
+    assert!(!ping.on_ping());
+    assert_eq!(ping.interval(), MIN);
+  }
+
+  #[test]
+  fn pong_resets_missed() {
+    let mut ping = AdaptivePing::new(MIN, MAX);
+    assert!(ping.on_ping());
+    assert!(ping.on_ping());
+    ping.on_pong();
+    for _ in 0..MAX_MISSED {
+      assert!(ping.on_ping());
+    }
+  }
+
+  #[test]
+  fn max_is_at_least_min() {
+    let ping = AdaptivePing::new(MAX, MIN);
+    assert_eq!(ping.interval(), MAX);
+  }
+}
@@ -0,0 +1,343 @@
+use anyhow::{Context, anyhow};
+use command::run_komodo_command;
+use komodo_client::entities::docker::image::{
+  ImageScan, ImageScanFinding, ImageScanSeverityCounts,
+};
+use serde::Deserialize;
+use shell_escape::unix::escape;
+
+use crate::config::periphery_config;
+
+/// Only the most severe findings are returned.
+const MAX_FINDINGS: usize = 25;
+
+pub async fn scan_image(image: &str) -> anyhow::Result<ImageScan> {
+  let scanner = periphery_config().image_scanner.as_str();
+  if scanner.is_empty() {
+    return Err(anyhow!(
+      "Image scanning is not enabled on this Periphery. Configure 'image_scanner' to enable it."
+    ));
+  }
+  let grype = is_grype(scanner);
+  let image_arg = escape(image.into());
+  let command = if grype {
+    format!("{scanner} {image_arg} --output json --quiet")
+  } else {
+    format!("{scanner} image --quiet --format json {image_arg}")
+  };
+  let log = run_komodo_command("Scan Image", None, command).await;
+  if !log.success {
+    return Err(
+      anyhow!("{}", log.combined())
+        .context(format!("Failed to scan image {image}")),
+    );
+  }
+  let findings = if grype {
+    parse_grype_output(&log.stdout)?
+  } else {
+    parse_trivy_output(&log.stdout)?
+  };
+  Ok(summarize(
+    image.to_string(),
+    if grype { "grype" } else { "trivy" }.to_string(),
+    findings,
+  ))
+}
+
+fn is_grype(scanner: &str) -> bool {
+  scanner
+    .rsplit('/')
+    .next()
+    .is_some_and(|bin| bin.starts_with("grype"))
+}
+
+fn summarize(
+  image: String,
+  scanner: String,
+  mut findings: Vec<ImageScanFinding>,
+) -> ImageScan {
+  let mut counts = ImageScanSeverityCounts::default();
+  for finding in &findings {
+    match finding.severity.as_str() {
+      "CRITICAL" => counts.critical += 1,
+      "HIGH" => counts.high += 1,
+      "MEDIUM" => counts.medium += 1,
+      "LOW" => counts.low += 1,
+      _ => counts.unknown += 1,
+    }
+  }
+  findings.sort_by_key(|finding| severity_rank(&finding.severity));
+  findings.truncate(MAX_FINDINGS);
+  ImageScan {
+    image,
+    scanner,
+    counts,
+    findings,
+  }
+}
+
+fn severity_rank(severity: &str) -> u8 {
+  match severity {
+    "CRITICAL" => 0,
+    "HIGH" => 1,
+    "MEDIUM" => 2,
+    "LOW" => 3,
+    _ => 4,
+  }
+}
+
+// =======
+//  TRIVY
+// =======
+
+#[derive(Deserialize)]
+struct TrivyReport {
+  #[serde(default, rename = "Results")]
+  results: Option<Vec<TrivyResult>>,
+}
+
+#[derive(Deserialize)]
+struct TrivyResult {
+  #[serde(default, rename = "Vulnerabilities")]
+  vulnerabilities: Option<Vec<TrivyVulnerability>>,
+}
+
+#[derive(Deserialize)]
+struct TrivyVulnerability {
+  #[serde(rename = "VulnerabilityID")]
+  id: String,
+  #[serde(default, rename = "PkgName")]
+  package: String,
+  #[serde(default, rename = "InstalledVersion")]
+  installed_version: String,
+  #[serde(rename = "FixedVersion")]
+  fixed_version: Option<String>,
+  #[serde(default, rename = "Severity")]
+  severity: String,
+  #[serde(rename = "Title")]
+  title: Option<String>,
+}
+
+fn parse_trivy_output(
+  stdout: &str,
+) -> anyhow::Result<Vec<ImageScanFinding>> {
+  let report = serde_json::from_str::<TrivyReport>(stdout)
+    .context("Failed to parse trivy json output")?;
+  Ok(
+    report
+      .results
+      .unwrap_or_default()
+      .into_iter()
+      .flat_map(|result| result.vulnerabilities.unwrap_or_default())
+      .map(|vuln| ImageScanFinding {
+        id: vuln.id,
+        severity: vuln.severity.to_uppercase(),
+        package: vuln.package,
+        installed_version: vuln.installed_version,
+        fixed_version: vuln
+          .fixed_version
+          .filter(|version| !version.is_empty()),
+        title: vuln.title,
+      })
+      .collect(),
+  )
+}
+
+// =======
+//  GRYPE
+// =======
+
+#[derive(Deserialize)]
+struct GrypeReport {
+  #[serde(default)]
+  matches: Vec<GrypeMatch>,
+}
+
+#[derive(Deserialize)]
+struct GrypeMatch {
+  vulnerability: GrypeVulnerability,
+  artifact: GrypeArtifact,
+}
+
+#[derive(Deserialize)]
+struct GrypeVulnerability {
+  id: String,
+  #[serde(default)]
+  severity: String,
+  description: Option<String>,
+  #[serde(default)]
+  fix: GrypeFix,
+}
+
+#[derive(Deserialize, Default)]
+struct GrypeFix {
+  #[serde(default)]
+  versions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct GrypeArtifact {
+  #[serde(default)]
+  name: String,
+  #[serde(default)]
+  version: String,
+}
+
+fn parse_grype_output(
+  stdout: &str,
+) -> anyhow::Result<Vec<ImageScanFinding>> {
+  let report = serde_json::from_str::<GrypeReport>(stdout)
+    .context("Failed to parse grype json output")?;
+  Ok(
+    report
+      .matches
+      .into_iter()
+      .map(|m| ImageScanFinding {
+        id: m.vulnerability.id,
+        severity: m.vulnerability.severity.to_uppercase(),
+        package: m.artifact.name,
+        installed_version: m.artifact.version,
+        fixed_version: m
+          .vulnerability
+          .fix
+          .versions
+          .into_iter()
+          .next(),
+        title: m.vulnerability.description,
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TRIVY_OUTPUT: &str = r#"{
+    "Results": [
+      {
+        "Target": "app:1.2 (debian 12.5)",
+        "Vulnerabilities": [
+          {
+            "VulnerabilityID": "CVE-2024-0001",
+            "PkgName": "openssl",
+            "InstalledVersion": "3.0.11",
+            "FixedVersion": "3.0.13",
+            "Severity": "HIGH",
+            "Title": "openssl: excessive time spent"
+          },
+          {
+            "VulnerabilityID": "CVE-2024-0002",
+            "PkgName": "zlib",
+            "InstalledVersion": "1.2.13",
+            "FixedVersion": "",
+            "Severity": "critical"
+          }
+        ]
+      },
+      { "Target": "app/package-lock.json" }
+    ]
+  }"#;
+
+  const GRYPE_OUTPUT: &str = r#"{
+    "matches": [
+      {
+        "vulnerability": {
+          "id": "GHSA-0001",
+          "severity": "Medium",
+          "description": "prototype pollution",
+          "fix": { "versions": ["4.17.21"], "state": "fixed" }
+        },
+        "artifact": { "name": "lodash", "version": "4.17.20" }
+      },
+      {
+        "vulnerability": { "id": "CVE-2024-0003", "severity": "Negligible" },
+        "artifact": { "name": "bash", "version": "5.2" }
+      }
+    ]
+  }"#;
+
+  fn finding(id: &str, severity: &str) -> ImageScanFinding {
+    ImageScanFinding {
+      id: id.to_string(),
+      severity: severity.to_string(),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn detects_grype_binary() {
+    assert!(is_grype("grype"));
+    assert!(is_grype("/usr/local/bin/grype"));
+    assert!(!is_grype("/usr/local/bin/trivy"));
+    assert!(!is_grype("/opt/grype/trivy"));
+  }
+
+  #[test]
+  fn parses_trivy_output() {
+    let findings = parse_trivy_output(TRIVY_OUTPUT).unwrap();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[0].id, "CVE-2024-0001");
+    assert_eq!(findings[0].package, "openssl");
+    assert_eq!(findings[0].fixed_version.as_deref(), Some("3.0.13"));
+    assert_eq!(
+      findings[0].title.as_deref(),
+      Some("openssl: excessive time spent")
+    );
+    assert_eq!(findings[1].severity, "CRITICAL");
+    assert_eq!(findings[1].fixed_version, None);
+    assert!(
+      parse_trivy_output(r#"{"Results": null}"#)
+        .unwrap()
+        .is_empty()
+    );
+    assert!(parse_trivy_output("not json").is_err());
+  }
+
+  #[test]
+  fn parses_grype_output() {
+    let findings = parse_grype_output(GRYPE_OUTPUT).unwrap();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[0].severity, "MEDIUM");
+    assert_eq!(findings[0].package, "lodash");
+    assert_eq!(findings[0].installed_version, "4.17.20");
+    assert_eq!(findings[0].fixed_version.as_deref(), Some("4.17.21"));
+    assert_eq!(
+      findings[0].title.as_deref(),
+      Some("prototype pollution")
+    );
+    assert_eq!(findings[1].fixed_version, None);
+  }
+
+  #[test]
+  fn summarizes_most_severe_first() {
+    let mut findings = vec![
+      finding("low", "LOW"),
+      finding("negligible", "NEGLIGIBLE"),
+      finding("critical", "CRITICAL"),
+      finding("medium", "MEDIUM"),
+      finding("high", "HIGH"),
+    ];
+    findings.extend(
+      (0..MAX_FINDINGS).map(|i| finding(&format!("low-{i}"), "LOW")),
+    );
+    let scan = summarize(
+      String::from("app:1.2"),
+      String::from("trivy"),
+      findings,
+    );
+    assert_eq!(scan.counts.critical, 1);
+    assert_eq!(scan.counts.high, 1);
+    assert_eq!(scan.counts.medium, 1);
+    assert_eq!(scan.counts.low, MAX_FINDINGS as u32 + 1);
+    assert_eq!(scan.counts.unknown, 1);
+    assert_eq!(scan.findings.len(), MAX_FINDINGS);
+    let ids = scan
+      .findings
+      .iter()
+      .take(4)
+      .map(|finding| finding.id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, ["critical", "high", "medium", "low"]);
+  }
+}
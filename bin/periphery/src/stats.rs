@@ -1,5 +1,9 @@
-use std::{cmp::Ordering, sync::OnceLock};
+use std::{
+  cmp::Ordering,
+  sync::{Arc, OnceLock},
+};
 
+use arc_swap::ArcSwap;
 use async_timing_util::wait_until_timelength;
 use komodo_client::entities::stats::{
   SingleDiskUsage, SystemInformation, SystemLoadAverage,
@@ -16,6 +20,21 @@ pub fn stats_client() -> &'static RwLock<StatsClient> {
   STATS_CLIENT.get_or_init(|| RwLock::new(StatsClient::default()))
 }
 
+/// The most recently sampled system stats.
+/// Reading these never waits on a refresh in progress,
+/// so status polls return immediately even if sampling is slow.
+/// The `refresh_ts` gives the time they were sampled.
+pub fn system_stats() -> &'static ArcSwap<SystemStats> {
+  static SYSTEM_STATS: OnceLock<ArcSwap<SystemStats>> =
+    OnceLock::new();
+  SYSTEM_STATS.get_or_init(|| {
+    ArcSwap::from_pointee(SystemStats {
+      polling_rate: periphery_config().stats_polling_rate,
+      ..Default::default()
+    })
+  })
+}
+
 /// This should be called before starting the server in main.rs.
 /// Keeps the cached stats up to date
 pub fn spawn_polling_thread() {
@@ -28,17 +47,30 @@ pub fn spawn_polling_thread() {
     let client = stats_client();
     loop {
       let ts = wait_until_timelength(polling_rate, 1).await;
-      let mut client = client.write().await;
-      client.refresh();
-      client.stats = client.get_system_stats();
-      client.stats.refresh_ts = ts as i64;
+      let stats = {
+        let mut client = client.write().await;
+        client.refresh();
+        client.get_system_stats()
+      };
+      publish_stats(system_stats(), stats, ts as i64);
     }
   });
 }
 
+/// Swaps in the freshly sampled stats.
+/// Readers holding the previous stats keep them.
+fn publish_stats(
+  cache: &ArcSwap<SystemStats>,
+  stats: SystemStats,
+  refresh_ts: i64,
+) {
+  cache.store(Arc::new(SystemStats {
+    refresh_ts,
+    ..stats
+  }));
+}
+
 pub struct StatsClient {
-  /// Cached system stats
-  pub stats: SystemStats,
   /// Cached system information
   pub info: SystemInformation,
 
@@ -57,16 +89,11 @@ impl Default for StatsClient {
     let system = sysinfo::System::new_all();
     let disks = sysinfo::Disks::new_with_refreshed_list();
     let networks = sysinfo::Networks::new_with_refreshed_list();
-    let stats = SystemStats {
-      polling_rate: periphery_config().stats_polling_rate,
-      ..Default::default()
-    };
     StatsClient {
       info: get_system_information(&system),
       system,
       disks,
       networks,
-      stats,
     }
   }
 }
@@ -84,6 +111,8 @@ impl StatsClient {
     self.networks.refresh(true);
   }
 
+  /// Get the system stats from the last refresh.
+  /// The `refresh_ts` is left for the caller to set.
   pub fn get_system_stats(&self) -> SystemStats {
     let total_mem = self.system.total_memory();
     let available_mem = self.system.available_memory();
@@ -111,9 +140,9 @@ impl StatsClient {
       network_ingress_bytes: network_ingress_bytes as f64,
       network_egress_bytes: network_egress_bytes as f64,
       disks: self.get_disks(),
-      polling_rate: self.stats.polling_rate,
-      refresh_ts: self.stats.refresh_ts,
-      refresh_list_ts: self.stats.refresh_list_ts,
+      polling_rate: periphery_config().stats_polling_rate,
+      refresh_ts: 0,
+      refresh_list_ts: 0,
     }
   }
 
@@ -218,3 +247,33 @@ fn get_system_information(
     container_exec_disabled: config.disable_container_exec,
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn publish_stats_sets_refresh_ts() {
+    let cache = ArcSwap::from_pointee(SystemStats::default());
+    publish_stats(
+      &cache,
+      SystemStats {
+        cpu_perc: 42.0,
+        ..Default::default()
+      },
+      1_000,
+    );
+    let stats = cache.load();
+    assert_eq!(stats.refresh_ts, 1_000);
+    assert_eq!(stats.cpu_perc, 42.0);
+  }
+
+  #[test]
+  fn publish_stats_keeps_previous_for_readers() {
+    let cache = ArcSwap::from_pointee(SystemStats::default());
+    let previous = cache.load_full();
+    publish_stats(&cache, SystemStats::default(), 1_000);
+    assert_eq!(previous.refresh_ts, 0);
+    assert_eq!(cache.load().refresh_ts, 1_000);
+  }
+}
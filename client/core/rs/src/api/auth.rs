@@ -33,7 +33,7 @@ pub struct GetLoginOptions {}
 
 /// The response for [GetLoginOptions].
 #[typeshare]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetLoginOptionsResponse {
   /// Whether local auth is enabled.
   pub local: bool,
@@ -43,6 +43,10 @@ pub struct GetLoginOptionsResponse {
   pub google: bool,
   /// Whether OIDC login is enabled.
   pub oidc: bool,
+  /// The names of the additional OIDC providers.
+  /// Log in with them at `/auth/oidc/{name}/login`.
+  #[serde(default)]
+  pub oidc_providers: Vec<String>,
   /// Whether user registration (Sign Up) has been disabled
   pub registration_disabled: bool,
 }
@@ -68,6 +72,11 @@ pub struct SignUpLocalUser {
   /// The password for the new user.
   /// This cannot be retreived later.
   pub password: String,
+  /// The one time token printed to the Core logs,
+  /// required to claim the first admin account
+  /// when `require_bootstrap_token` is enabled.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub bootstrap_token: Option<String>,
 }
 
 /// Response for [SignUpLocalUser].
@@ -136,3 +145,24 @@ pub struct GetUser {}
 pub type GetUserResponse = User;
 
 //
+
+/// Exchange a valid jwt for a new one, extending the session.
+/// Only available to users logged in with OIDC,
+/// when the provider issued a refresh token.
+/// The provider session is checked to still be active,
+/// and if it has ended, all the user's jwts are invalidated.
+/// Response: [RefreshJwtResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoAuthRequest)]
+#[response(RefreshJwtResponse)]
+#[error(serror::Error)]
+pub struct RefreshJwt {}
+
+/// Response for [RefreshJwt].
+#[typeshare]
+pub type RefreshJwtResponse = JwtResponse;
+
+//
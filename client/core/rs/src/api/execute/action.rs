@@ -24,6 +24,7 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RunAction {
   /// Id or name
   pub action: String,
@@ -55,6 +56,7 @@ fn args_parser(args: &str) -> anyhow::Result<JsonObject> {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(BatchExecutionResponse)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BatchRunAction {
   /// Id or name or wildcard pattern or regex.
   /// Supports multiline and comma delineated combinations of the above.
@@ -23,6 +23,7 @@ use super::KomodoExecuteRequest;
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TestAlerter {
   /// Name or id
   pub alerter: String,
@@ -45,6 +46,7 @@ pub struct TestAlerter {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SendAlert {
   /// The alert level.
   #[serde(default)]
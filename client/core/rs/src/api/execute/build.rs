@@ -37,6 +37,7 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RunBuild {
   /// Can be build id or name
   pub build: String,
@@ -59,6 +60,7 @@ pub struct RunBuild {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(BatchExecutionResponse)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BatchRunBuild {
   /// Id or name or wildcard pattern or regex.
   /// Supports multiline and comma delineated combinations of the above.
@@ -92,6 +94,7 @@ pub struct BatchRunBuild {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CancelBuild {
   /// Can be id or name
   pub build: String,
@@ -15,6 +15,10 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 /// it will be stopped and removed using `docker container rm ${container_name}`.
 /// 3. The container will be run using `docker run {...params}`,
 /// where params are determined by the deployment's configuration.
+///
+/// With the `StartFirst` deploy strategy, the new container is instead run
+/// alongside the existing one, and the existing container is only removed
+/// once the new container is healthy.
 #[typeshare]
 #[derive(
   Serialize,
@@ -29,6 +33,7 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Deploy {
   /// Name or id
   pub deployment: String,
@@ -57,6 +62,7 @@ pub struct Deploy {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(BatchExecutionResponse)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BatchDeploy {
   /// Id or name or wildcard pattern or regex.
   /// Supports multiline and comma delineated combinations of the above.
@@ -88,6 +94,7 @@ pub struct BatchDeploy {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PullDeployment {
   /// Name or id
   pub deployment: String,
@@ -95,6 +102,35 @@ pub struct PullDeployment {
 
 //
 
+/// Redeploys the target deployment only if its container
+/// has drifted from the deployment config, otherwise does nothing.
+/// Suitable for scheduled reconciliation. Response: [Update]
+///
+/// 1. Inspects the container and compares the image, environment,
+/// ports, and memory / cpu limits against the config.
+/// 2. If any differ, or the container isn't deployed, runs [Deploy].
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReconcileDeployment {
+  /// Name or id
+  pub deployment: String,
+}
+
+//
+
 /// Starts the container for the target deployment. Response: [Update]
 ///
 /// 1. Runs `docker start ${container_name}`.
@@ -112,6 +148,7 @@ pub struct PullDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StartDeployment {
   /// Name or id
   pub deployment: String,
@@ -136,6 +173,7 @@ pub struct StartDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RestartDeployment {
   /// Name or id
   pub deployment: String,
@@ -160,6 +198,7 @@ pub struct RestartDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PauseDeployment {
   /// Name or id
   pub deployment: String,
@@ -186,6 +225,7 @@ pub struct PauseDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UnpauseDeployment {
   /// Name or id
   pub deployment: String,
@@ -210,6 +250,7 @@ pub struct UnpauseDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StopDeployment {
   /// Name or id
   pub deployment: String,
@@ -239,6 +280,7 @@ pub struct StopDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DestroyDeployment {
   /// Name or id.
   pub deployment: String,
@@ -265,6 +307,7 @@ pub struct DestroyDeployment {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(BatchExecutionResponse)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BatchDestroyDeployment {
   /// Id or name or wildcard pattern or regex.
   /// Supports multiline and comma delineated combinations of the above.
@@ -6,7 +6,7 @@ use typeshare::typeshare;
 
 use crate::entities::update::Update;
 
-use super::KomodoExecuteRequest;
+use super::{BatchExecutionResponse, KomodoExecuteRequest};
 
 /// Clears all repos from the Core repo cache. Admin only.
 /// Response: [Update]
@@ -24,6 +24,7 @@ use super::KomodoExecuteRequest;
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClearRepoCache {}
 
 /// Backs up the Komodo Core database to compressed jsonl files.
@@ -48,6 +49,7 @@ pub struct ClearRepoCache {}
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BackupCoreDatabase {}
 
 /// Trigger a global poll for image updates on Stacks and Deployments
@@ -70,4 +72,33 @@ pub struct BackupCoreDatabase {}
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GlobalAutoUpdate {}
+
+//
+
+/// Redeploys the running Deployments and Stacks which reference
+/// the variable / secret, so they pick up its new value after rotation.
+/// Response: [BatchExecutionResponse].
+///
+/// Use [ListVariableReferences][crate::api::read::ListVariableReferences]
+/// to see all the resources referencing the variable.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  PartialEq,
+  Serialize,
+  Deserialize,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(BatchExecutionResponse)]
+#[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RedeployVariableReferences {
+  /// The name of the variable / secret.
+  pub variable: String,
+}
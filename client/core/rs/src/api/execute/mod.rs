@@ -34,6 +34,11 @@ use crate::{
 
 pub trait KomodoExecuteRequest: HasResponse {}
 
+/// Clients can send a unique key in this header with `/execute` requests.
+/// Retries with the same key (within 10 minutes) return the
+/// original response, rather than starting a second execution.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
 /// A wrapper for all Komodo exections.
 #[typeshare]
 #[derive(
@@ -55,6 +60,7 @@ pub trait KomodoExecuteRequest: HasResponse {}
   EnumString
 )]
 #[serde(tag = "type", content = "params")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Execution {
   /// The "null" execution. Does nothing.
   None(NoData),
@@ -83,6 +89,7 @@ pub enum Execution {
   #[clap(alias = "dp")]
   Deploy(Deploy),
   BatchDeploy(BatchDeploy),
+  ReconcileDeployment(ReconcileDeployment),
   PullDeployment(PullDeployment),
   StartDeployment(StartDeployment),
   RestartDeployment(RestartDeployment),
@@ -163,19 +170,84 @@ pub enum Execution {
   ClearRepoCache(ClearRepoCache),
   BackupCoreDatabase(BackupCoreDatabase),
   GlobalAutoUpdate(GlobalAutoUpdate),
+  RedeployVariableReferences(RedeployVariableReferences),
 
   // SLEEP
   Sleep(Sleep),
+  WaitUntil(WaitUntil),
 }
 
 /// Sleeps for the specified time.
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Parser)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Sleep {
   #[serde(default)]
   pub duration_ms: I64,
 }
 
+/// Waits until the condition is met, failing if it
+/// isn't met before the timeout.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Parser)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WaitUntil {
+  /// The condition to wait for.
+  #[serde(default)]
+  #[clap(long, short = 'c', default_value_t = WaitCondition::DeploymentHealthy)]
+  pub condition: WaitCondition,
+  /// The target of the condition.
+  /// - `DeploymentHealthy`: The Deployment name or id.
+  /// - `HttpOk`: The url to send GET requests to.
+  /// - `Duration`: Not used.
+  #[serde(default)]
+  pub target: String,
+  /// Fail if the condition isn't met within this time.
+  /// For `Duration`, the time to wait.
+  #[serde(default = "default_wait_timeout_ms")]
+  #[clap(long, default_value_t = default_wait_timeout_ms())]
+  pub timeout_ms: I64,
+  /// The time between condition checks.
+  #[serde(default = "default_wait_interval_ms")]
+  #[clap(long, default_value_t = default_wait_interval_ms())]
+  pub interval_ms: I64,
+}
+
+fn default_wait_timeout_ms() -> I64 {
+  300_000
+}
+
+fn default_wait_interval_ms() -> I64 {
+  5_000
+}
+
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  Display,
+  EnumString,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum WaitCondition {
+  /// The Deployment container is running, and healthy
+  /// if it has a healthcheck configured.
+  #[default]
+  DeploymentHealthy,
+  /// A GET request to the url returns 200.
+  /// Redirects are not followed, and the url must resolve to
+  /// a public address unless allowed by `wait_until_allowed_ips`.
+  HttpOk,
+  /// Waits for `timeout_ms`, then proceeds.
+  Duration,
+}
+
 #[typeshare]
 pub type BatchExecutionResponse = Vec<BatchExecutionResponseItem>;
 
@@ -206,3 +278,52 @@ pub struct BatchExecutionResponseItemErr {
   pub name: String,
   pub error: _Serror,
 }
+
+/// Get the JSON schema for an execute request type by name,
+/// or for the [Execution] wrapper itself.
+/// The types are taken from the [Execution] schema definitions,
+/// so every execution and the types they use are included.
+/// Returns `None` if the type is not supported.
+#[cfg(feature = "schema")]
+pub fn json_schema(type_name: &str) -> Option<serde_json::Value> {
+  let mut schema = schemars::schema_for!(Execution);
+  if type_name != "Execution" {
+    schema.schema =
+      schema.definitions.get(type_name)?.clone().into_object();
+  }
+  serde_json::to_value(schema).ok()
+}
+
+#[cfg(all(test, feature = "schema"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn every_execution_has_a_schema() {
+    let execution = json_schema("Execution").unwrap();
+    let variants = execution["oneOf"].as_array().unwrap();
+    assert!(!variants.is_empty());
+    for variant in variants {
+      let name =
+        variant["properties"]["type"]["enum"][0].as_str().unwrap();
+      if name == "None" {
+        continue;
+      }
+      assert!(json_schema(name).is_some(), "{name}");
+    }
+  }
+
+  #[test]
+  fn schema_has_required_fields() {
+    let schema = json_schema("CommitSync").unwrap();
+    assert_eq!(schema["required"], serde_json::json!(["sync"]));
+    let schema = json_schema("Deploy").unwrap();
+    let required = schema["required"].as_array().unwrap();
+    assert!(required.contains(&serde_json::json!("deployment")));
+  }
+
+  #[test]
+  fn unknown_type_has_no_schema() {
+    assert!(json_schema("NotARequest").is_none());
+  }
+}
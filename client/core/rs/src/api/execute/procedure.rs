@@ -23,6 +23,7 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RunProcedure {
   /// Id or name
   pub procedure: String,
@@ -43,6 +44,7 @@ pub struct RunProcedure {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(BatchExecutionResponse)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BatchRunProcedure {
   /// Id or name or wildcard pattern or regex.
   /// Supports multiline and comma delineated combinations of the above.
@@ -29,6 +29,7 @@ use super::KomodoExecuteRequest;
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StartContainer {
   /// Name or id
   pub server: String,
@@ -55,6 +56,7 @@ pub struct StartContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RestartContainer {
   /// Name or id
   pub server: String,
@@ -81,6 +83,7 @@ pub struct RestartContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PauseContainer {
   /// Name or id
   pub server: String,
@@ -109,6 +112,7 @@ pub struct PauseContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UnpauseContainer {
   /// Name or id
   pub server: String,
@@ -135,6 +139,7 @@ pub struct UnpauseContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StopContainer {
   /// Name or id
   pub server: String,
@@ -166,6 +171,7 @@ pub struct StopContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DestroyContainer {
   /// Name or id
   pub server: String,
@@ -194,6 +200,7 @@ pub struct DestroyContainer {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StartAllContainers {
   /// Name or id
   pub server: String,
@@ -216,6 +223,7 @@ pub struct StartAllContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RestartAllContainers {
   /// Name or id
   pub server: String,
@@ -238,6 +246,7 @@ pub struct RestartAllContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PauseAllContainers {
   /// Name or id
   pub server: String,
@@ -260,6 +269,7 @@ pub struct PauseAllContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UnpauseAllContainers {
   /// Name or id
   pub server: String,
@@ -282,6 +292,7 @@ pub struct UnpauseAllContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StopAllContainers {
   /// Name or id
   pub server: String,
@@ -306,6 +317,7 @@ pub struct StopAllContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneContainers {
   /// Id or name
   pub server: String,
@@ -331,6 +343,7 @@ pub struct PruneContainers {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeleteNetwork {
   /// Id or name.
   pub server: String,
@@ -357,6 +370,7 @@ pub struct DeleteNetwork {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneNetworks {
   /// Id or name
   pub server: String,
@@ -380,6 +394,7 @@ pub struct PruneNetworks {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeleteImage {
   /// Id or name.
   pub server: String,
@@ -406,6 +421,7 @@ pub struct DeleteImage {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneImages {
   /// Id or name
   pub server: String,
@@ -429,6 +445,7 @@ pub struct PruneImages {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeleteVolume {
   /// Id or name.
   pub server: String,
@@ -455,6 +472,7 @@ pub struct DeleteVolume {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneVolumes {
   /// Id or name
   pub server: String,
@@ -479,6 +497,7 @@ pub struct PruneVolumes {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneDockerBuilders {
   /// Id or name
   pub server: String,
@@ -503,6 +522,7 @@ pub struct PruneDockerBuilders {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneBuildx {
   /// Id or name
   pub server: String,
@@ -527,6 +547,7 @@ pub struct PruneBuildx {
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PruneSystem {
   /// Id or name
   pub server: String,
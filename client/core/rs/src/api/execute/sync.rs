@@ -23,11 +23,13 @@ use super::KomodoExecuteRequest;
 #[empty_traits(KomodoExecuteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RunSync {
   /// Id or name
   pub sync: String,
   /// Only execute sync on a specific resource type.
   /// Combine with `resource_id` to specify resource.
+  #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
   pub resource_type: Option<ResourceTargetVariant>,
   /// Only execute sync on a specific resources.
   /// Combine with `resource_type` to specify resources.
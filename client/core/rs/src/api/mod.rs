@@ -11,6 +11,7 @@
 //!   - X-Api-Key: `your_api_key`
 //!   - X-Api-Secret: `your_api_secret`
 //!   - Use either Authorization *or* X-Api-Key and X-Api-Secret to authenticate requests.
+//!   - X-Komodo-Api-Version: `client_version` (optional, see [API_VERSION_HEADER])
 //! - Body: JSON specifying the request type (`type`) and the parameters (`params`).
 //!
 //! You can create API keys for your user, or for a Service User with limited permissions,
@@ -66,9 +67,188 @@
 //! }
 //! ```
 
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, Display, EnumString};
+use typeshare::typeshare;
+
+use crate::entities::{I64, Operation, ResourceTarget};
+
 pub mod auth;
 pub mod execute;
 pub mod read;
 pub mod terminal;
 pub mod user;
 pub mod write;
+
+/// Clients can send the Komodo version they were built against
+/// in this header, eg `1.19.5`. Core rejects requests with a
+/// different major version with `400`, rather than letting
+/// a breaking change fail in unexpected ways.
+///
+/// Core sends its own version back in this header on every response.
+pub const API_VERSION_HEADER: &str = "x-komodo-api-version";
+
+/// Query to filter the resource event stream (server-sent events) at `/events`.
+/// Passing empty is the same as not filtering by that field.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ResourceEventsQuery {
+  /// Comma separated resource types, eg `Stack,Deployment`.
+  #[serde(default)]
+  pub resource_types: String,
+  /// Comma separated event kinds, eg `Created,Deleted`.
+  #[serde(default)]
+  pub kinds: String,
+}
+
+/// A resource lifecycle event sent on the `/events` stream.
+/// The server-sent event name is the [ResourceEventKind].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceEvent {
+  /// What happened to the resource.
+  pub kind: ResourceEventKind,
+  /// The resource the event is about.
+  pub target: ResourceTarget,
+  /// The operation which caused the event.
+  pub operation: Operation,
+  /// Whether the operation was successful.
+  pub success: bool,
+  /// The id of the Update recording the operation.
+  pub update_id: String,
+  /// Timestamp the operation started.
+  pub ts: I64,
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Display,
+  EnumString,
+  AsRefStr,
+)]
+pub enum ResourceEventKind {
+  /// The resource was created.
+  Created,
+  /// The resource config or name was updated.
+  Updated,
+  /// The resource was deleted.
+  Deleted,
+  /// An execution on the resource completed,
+  /// eg a deploy, which may change its state.
+  StateChanged,
+}
+
+impl ResourceEventKind {
+  /// Classifies the operation, or None if it doesn't target a resource.
+  /// Operations on things within a resource, like `DeleteNetwork`
+  /// on a Server, are classified as `StateChanged`.
+  pub fn from_operation(operation: Operation) -> Option<Self> {
+    use Operation::*;
+    let kind =
+      match operation {
+        CreateServer | CreateStack | CreateDeployment
+        | CreateBuild | CreateRepo | CreateProcedure
+        | CreateAction | CreateBuilder | CreateAlerter
+        | CreateResourceSync => Self::Created,
+
+        DeleteServer | DeleteStack | DeleteDeployment
+        | DeleteBuild | DeleteRepo | DeleteProcedure
+        | DeleteAction | DeleteBuilder | DeleteAlerter
+        | DeleteResourceSync => Self::Deleted,
+
+        UpdateServer | RenameServer | UpdateStack | RenameStack
+        | WriteStackContents | UpdateDeployment
+        | RenameDeployment | UpdateBuild | RenameBuild
+        | WriteDockerfile | UpdateRepo | RenameRepo
+        | UpdateProcedure | RenameProcedure | UpdateAction
+        | RenameAction | UpdateBuilder | RenameBuilder
+        | UpdateAlerter | RenameAlerter | UpdateResourceSync
+        | RenameResourceSync | WriteSyncContents => Self::Updated,
+
+        None
+        | ClearRepoCache
+        | BackupCoreDatabase
+        | GlobalAutoUpdate
+        | StartChangeFreeze
+        | EndChangeFreeze
+        | CreateVariable
+        | UpdateVariableValue
+        | DeleteVariable
+        | CreateGitProviderAccount
+        | UpdateGitProviderAccount
+        | DeleteGitProviderAccount
+        | CreateDockerRegistryAccount
+        | UpdateDockerRegistryAccount
+        | DeleteDockerRegistryAccount => {
+          return Option::None;
+        }
+
+        _ => Self::StateChanged,
+      };
+    Some(kind)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn classifies_resource_lifecycle() {
+    assert_eq!(
+      ResourceEventKind::from_operation(Operation::CreateStack),
+      Some(ResourceEventKind::Created)
+    );
+    assert_eq!(
+      ResourceEventKind::from_operation(Operation::DeleteServer),
+      Some(ResourceEventKind::Deleted)
+    );
+    assert_eq!(
+      ResourceEventKind::from_operation(Operation::RenameBuild),
+      Some(ResourceEventKind::Updated)
+    );
+    assert_eq!(
+      ResourceEventKind::from_operation(Operation::Deploy),
+      Some(ResourceEventKind::StateChanged)
+    );
+  }
+
+  #[test]
+  fn server_docker_operations_change_state() {
+    for operation in [
+      Operation::CreateNetwork,
+      Operation::DeleteNetwork,
+      Operation::DeleteImage,
+      Operation::DeleteVolume,
+    ] {
+      assert_eq!(
+        ResourceEventKind::from_operation(operation),
+        Some(ResourceEventKind::StateChanged),
+        "{operation}"
+      );
+    }
+  }
+
+  #[test]
+  fn non_resource_operations_are_skipped() {
+    for operation in [
+      Operation::None,
+      Operation::CreateVariable,
+      Operation::DeleteGitProviderAccount,
+      Operation::StartChangeFreeze,
+    ] {
+      assert_eq!(
+        ResourceEventKind::from_operation(operation),
+        None,
+        "{operation}"
+      );
+    }
+  }
+}
@@ -0,0 +1,49 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{I64, MongoDocument, U64, audit::AuditLog};
+
+use super::KomodoReadRequest;
+
+/// Get a paginated list of audit logs, ie the `/write` and `/execute`
+/// calls made, sorted by timestamp descending. Admin only.
+/// Response: [ListAuditLogsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListAuditLogsResponse)]
+#[error(serror::Error)]
+pub struct ListAuditLogs {
+  /// Pass a custom mongo query to filter the audit logs.
+  ///
+  /// ## Example JSON
+  /// ```json
+  /// {
+  ///   "username": "admin",
+  ///   "api": "execute",
+  ///   "success": false,
+  ///   "ts": { "$gte": 1735689600000 }
+  /// }
+  /// ```
+  /// This will filter to only include failed executions
+  /// by the `admin` user since the given time.
+  pub query: Option<MongoDocument>,
+  /// Retrieve older results by incrementing the page.
+  /// `page: 0` is default, and returns the most recent results.
+  #[serde(default)]
+  pub page: U64,
+}
+
+/// Response for [ListAuditLogs].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListAuditLogsResponse {
+  pub logs: Vec<AuditLog>,
+  /// If more audit logs exist, the next page will be given here.
+  /// Otherwise it will be `null`
+  pub next_page: Option<I64>,
+}
@@ -0,0 +1,27 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::change_freeze::ChangeFreeze;
+
+use super::KomodoReadRequest;
+
+/// Get the active change freeze, if any.
+/// Response: [GetChangeFreezeResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetChangeFreezeResponse)]
+#[error(serror::Error)]
+pub struct GetChangeFreeze {}
+
+/// Response for [GetChangeFreeze].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetChangeFreezeResponse {
+  /// The active change freeze. Null if changes aren't frozen.
+  pub freeze: Option<ChangeFreeze>,
+}
@@ -6,14 +6,17 @@ use typeshare::typeshare;
 mod action;
 mod alert;
 mod alerter;
+mod audit;
 mod build;
 mod builder;
+mod change_freeze;
 mod deployment;
 mod permission;
 mod procedure;
 mod provider;
 mod repo;
 mod schedule;
+mod search;
 mod server;
 mod stack;
 mod sync;
@@ -27,14 +30,17 @@ mod variable;
 pub use action::*;
 pub use alert::*;
 pub use alerter::*;
+pub use audit::*;
 pub use build::*;
 pub use builder::*;
+pub use change_freeze::*;
 pub use deployment::*;
 pub use permission::*;
 pub use procedure::*;
 pub use provider::*;
 pub use repo::*;
 pub use schedule::*;
+pub use search::*;
 pub use server::*;
 pub use stack::*;
 pub use sync::*;
@@ -46,7 +52,7 @@ pub use user_group::*;
 pub use variable::*;
 
 use crate::entities::{
-  ResourceTarget, Timelength,
+  JsonValue, ResourceTarget, Timelength,
   config::{DockerRegistry, GitProvider},
 };
 
@@ -116,6 +122,31 @@ pub struct GetCoreInfoResponse {
 
 //
 
+/// Get the JSON schema for a request type,
+/// for validating requests before sending them.
+/// Currently supports the [Execution], every request it wraps,
+/// and the types they use, eg `Execution`, `Deploy`, `CommitSync`.
+/// Response: [GetJsonSchemaResponse].
+///
+/// [Execution]: crate::api::execute::Execution
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetJsonSchemaResponse)]
+#[error(serror::Error)]
+pub struct GetJsonSchema {
+  /// The name of the type, eg `Deploy`.
+  pub type_name: String,
+}
+
+/// The JSON schema (draft 7) of the type.
+#[typeshare]
+pub type GetJsonSchemaResponse = JsonValue;
+
+//
+
 /// List the git providers available in Core / Periphery config files.
 /// Response: [ListGitProvidersFromConfigResponse].
 ///
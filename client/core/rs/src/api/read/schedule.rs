@@ -5,7 +5,10 @@ use typeshare::typeshare;
 
 use crate::{
   deserializers::string_list_deserializer,
-  entities::{resource::TagQueryBehavior, schedule::Schedule},
+  entities::{
+    I64, ScheduleFormat, resource::TagQueryBehavior,
+    schedule::Schedule,
+  },
 };
 
 use super::KomodoReadRequest;
@@ -30,3 +33,45 @@ pub struct ListSchedules {
 
 #[typeshare]
 pub type ListSchedulesResponse = Vec<Schedule>;
+
+//
+
+/// Validate a schedule expression, and preview the next run times.
+/// Response: [ValidateScheduleResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateScheduleResponse)]
+#[error(serror::Error)]
+pub struct ValidateSchedule {
+  /// Whether the schedule is a regular CRON expression,
+  /// or uses the english to CRON parser.
+  #[serde(default)]
+  pub format: ScheduleFormat,
+  /// The schedule expression.
+  pub schedule: String,
+  /// Optional. A TZ Identifier. If not provided, will use Core timezone.
+  #[serde(default)]
+  pub timezone: String,
+  /// The number of next run times to return. Default: 5. Max: 100.
+  #[serde(default = "default_validate_schedule_count")]
+  pub count: I64,
+}
+
+fn default_validate_schedule_count() -> I64 {
+  5
+}
+
+/// Response for [ValidateSchedule].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValidateScheduleResponse {
+  /// Whether the schedule and timezone are valid.
+  pub valid: bool,
+  /// The reason the schedule is invalid, if it is.
+  pub error: Option<String>,
+  /// The next run times in unix ms.
+  pub next_runs: Vec<I64>,
+}
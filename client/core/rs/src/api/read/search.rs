@@ -0,0 +1,49 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{I64, ResourceTarget};
+
+use super::KomodoReadRequest;
+
+/// Search across all resource types by name, tags, description,
+/// and key config fields, like the Deployment image or the Stack repo.
+/// Only includes resources the user can read.
+/// Response: [SearchResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(SearchResponse)]
+#[error(serror::Error)]
+pub struct Search {
+  /// The text to search for. Case insensitive.
+  pub query: String,
+  /// The maximum number of results. Default: 50. Max: 500.
+  #[serde(default = "default_search_limit")]
+  pub limit: I64,
+}
+
+fn default_search_limit() -> I64 {
+  50
+}
+
+/// The results, best match first.
+#[typeshare]
+pub type SearchResponse = Vec<SearchResult>;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+  /// The matching resource.
+  pub target: ResourceTarget,
+  /// The resource name.
+  pub name: String,
+  /// What the query matched, eg. `name`, `tag`, `description`,
+  /// or a config field like `image`.
+  pub matched: String,
+  /// The match rank. Higher is a better match.
+  pub score: I64,
+}
@@ -6,8 +6,9 @@ use typeshare::typeshare;
 use crate::entities::{
   I64, ResourceTarget, SearchCombinator, Timelength, U64,
   docker::{
-    container::{Container, ContainerListItem},
+    container::{Container, ContainerListItem, ContainerStatsSample},
     image::{Image, ImageHistoryResponseItem, ImageListItem},
+    info::DockerInfo,
     network::{Network, NetworkListItem},
     volume::{Volume, VolumeListItem},
   },
@@ -344,6 +345,34 @@ pub type InspectDockerContainerResponse = Container;
 
 //
 
+/// Generate a compose file approximating a set of containers
+/// on the server, for migrating them to a Stack.
+/// Response: [GetComposeFromContainersResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetComposeFromContainersResponse)]
+#[error(serror::Error)]
+pub struct GetComposeFromContainers {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub server: String,
+  /// The container names
+  pub containers: Vec<String>,
+}
+
+/// Response for [GetComposeFromContainers].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetComposeFromContainersResponse {
+  /// The generated compose file contents.
+  pub contents: String,
+}
+
+//
+
 /// Get the container log's tail, split by stdout/stderr.
 /// Response: [Log].
 ///
@@ -418,6 +447,30 @@ pub type SearchContainerLogResponse = Log;
 
 //
 
+/// Get the recent stats samples of a container, oldest first.
+/// Periphery keeps a limited number of samples in memory,
+/// configured with `container_stats_history`.
+/// Response: [GetContainerStatsHistoryResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetContainerStatsHistoryResponse)]
+#[error(serror::Error)]
+pub struct GetContainerStatsHistory {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub server: String,
+  /// The container name
+  pub container: String,
+}
+
+#[typeshare]
+pub type GetContainerStatsHistoryResponse = Vec<ContainerStatsSample>;
+
+//
+
 /// Find the attached resource for a container. Either Deployment or Stack. Response: [GetResourceMatchingContainerResponse].
 #[typeshare]
 #[derive(
@@ -524,6 +577,27 @@ pub type GetSystemInformationResponse = SystemInformation;
 
 //
 
+/// Get information about the docker daemon on the target server,
+/// such as the version, storage driver and cgroup version.
+/// Response: [DockerInfo].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetDockerInfoResponse)]
+#[error(serror::Error)]
+pub struct GetDockerInfo {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub server: String,
+}
+
+#[typeshare]
+pub type GetDockerInfoResponse = DockerInfo;
+
+//
+
 /// Get the system stats on the target server. Response: [SystemStats].
 ///
 /// Note. This does not hit the server directly. The stats come from an
@@ -604,6 +678,130 @@ pub struct GetHistoricalServerStatsResponse {
 
 //
 
+/// Query historical server stats over a time range,
+/// optionally averaged over fixed intervals, for charting.
+/// Only the stats still kept (see `keep_stats_for_days`) are queried.
+/// Response: [QueryServerStatsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(QueryServerStatsResponse)]
+#[error(serror::Error)]
+pub struct QueryServerStats {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub server: String,
+  /// Unix timestamp in milliseconds of the start of the range (inclusive).
+  /// Default: 1 day before `end_ts`.
+  pub start_ts: Option<I64>,
+  /// Unix timestamp in milliseconds of the end of the range (exclusive).
+  /// Default: now.
+  pub end_ts: Option<I64>,
+  /// Average the stats over intervals of this length.
+  /// If not provided, returns the raw stats.
+  pub interval: Option<Timelength>,
+  /// The metrics to include. If empty, includes all metrics.
+  #[serde(default)]
+  pub metrics: Vec<ServerStatsMetric>,
+}
+
+/// A metric which can be queried with [QueryServerStats].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum ServerStatsMetric {
+  CpuPerc,
+  LoadAverage,
+  MemUsedGb,
+  MemTotalGb,
+  DiskUsedGb,
+  DiskTotalGb,
+  NetworkIngressBytes,
+  NetworkEgressBytes,
+}
+
+impl ServerStatsMetric {
+  pub const ALL: [ServerStatsMetric; 8] = [
+    ServerStatsMetric::CpuPerc,
+    ServerStatsMetric::LoadAverage,
+    ServerStatsMetric::MemUsedGb,
+    ServerStatsMetric::MemTotalGb,
+    ServerStatsMetric::DiskUsedGb,
+    ServerStatsMetric::DiskTotalGb,
+    ServerStatsMetric::NetworkIngressBytes,
+    ServerStatsMetric::NetworkEgressBytes,
+  ];
+
+  /// The field of [ServerStatsPoint] holding the metric.
+  pub fn point_field(self) -> &'static str {
+    match self {
+      ServerStatsMetric::CpuPerc => "cpu_perc",
+      ServerStatsMetric::LoadAverage => "load_average",
+      ServerStatsMetric::MemUsedGb => "mem_used_gb",
+      ServerStatsMetric::MemTotalGb => "mem_total_gb",
+      ServerStatsMetric::DiskUsedGb => "disk_used_gb",
+      ServerStatsMetric::DiskTotalGb => "disk_total_gb",
+      ServerStatsMetric::NetworkIngressBytes => {
+        "network_ingress_bytes"
+      }
+      ServerStatsMetric::NetworkEgressBytes => "network_egress_bytes",
+    }
+  }
+
+  /// The path of the metric on the stored [SystemStatsRecord].
+  pub fn record_path(self) -> &'static str {
+    match self {
+      ServerStatsMetric::LoadAverage => "load_average.one",
+      metric => metric.point_field(),
+    }
+  }
+}
+
+/// Response for [QueryServerStats].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueryServerStatsResponse {
+  /// The points in the range, oldest first.
+  pub points: Vec<ServerStatsPoint>,
+  /// Whether the range had more points than can be returned at once.
+  /// Narrow the range or use a larger interval to get all of them.
+  pub truncated: bool,
+}
+
+/// The stats at a point in time, or averaged over an interval.
+/// Only the queried metrics are included.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ServerStatsPoint {
+  /// Unix timestamp in milliseconds.
+  /// For intervals, the start of the interval.
+  pub ts: I64,
+  /// The number of raw stats in the point.
+  pub count: I64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cpu_perc: Option<f64>,
+  /// The 1 minute load average
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub load_average: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub mem_used_gb: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub mem_total_gb: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub disk_used_gb: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub disk_total_gb: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub network_ingress_bytes: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub network_egress_bytes: Option<f64>,
+}
+
+//
+
 /// Gets a summary of data relating to all servers.
 /// Response: [GetServersSummaryResponse].
 #[typeshare]
@@ -654,3 +852,54 @@ pub struct ListTerminals {
 
 #[typeshare]
 pub type ListTerminalsResponse = Vec<TerminalInfo>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn metric_point_fields_match_point() {
+    let point = ServerStatsPoint {
+      cpu_perc: Some(0.0),
+      load_average: Some(0.0),
+      mem_used_gb: Some(0.0),
+      mem_total_gb: Some(0.0),
+      disk_used_gb: Some(0.0),
+      disk_total_gb: Some(0.0),
+      network_ingress_bytes: Some(0.0),
+      network_egress_bytes: Some(0.0),
+      ..Default::default()
+    };
+    let point = serde_json::to_value(point).unwrap();
+    for metric in ServerStatsMetric::ALL {
+      assert!(
+        point.get(metric.point_field()).is_some(),
+        "{metric:?} has no field on ServerStatsPoint"
+      );
+    }
+  }
+
+  #[test]
+  fn metric_record_paths() {
+    assert_eq!(
+      ServerStatsMetric::LoadAverage.record_path(),
+      "load_average.one"
+    );
+    assert_eq!(ServerStatsMetric::CpuPerc.record_path(), "cpu_perc");
+  }
+
+  #[test]
+  fn unset_metrics_are_skipped() {
+    let point = ServerStatsPoint {
+      ts: 1,
+      count: 2,
+      cpu_perc: Some(50.0),
+      ..Default::default()
+    };
+    let point = serde_json::to_value(point).unwrap();
+    assert_eq!(
+      point,
+      serde_json::json!({ "ts": 1, "count": 2, "cpu_perc": 50.0 })
+    );
+  }
+}
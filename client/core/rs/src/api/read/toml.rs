@@ -3,7 +3,7 @@ use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::ResourceTarget;
+use crate::entities::{ResourceTarget, ResourceTargetVariant};
 
 use super::KomodoReadRequest;
 
@@ -36,6 +36,16 @@ pub struct ExportAllResourcesToToml {
   /// Accepts tag name or id. Empty array will not filter by tag.
   #[serde(default)]
   pub tags: Vec<String>,
+  /// Filter resources by type.
+  /// Empty array will include all resource types.
+  #[serde(default)]
+  pub resource_types: Vec<ResourceTargetVariant>,
+  /// Filter resources by name.
+  /// Supports wildcard syntax, or regex if wrapped with "\\".
+  /// Multiple patterns can be comma or newline separated.
+  /// Empty string will not filter by name.
+  #[serde(default)]
+  pub pattern: String,
   /// Whether to include variables in the exported contents.
   /// Default: false
   #[serde(default)]
@@ -3,7 +3,10 @@ use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::{api_key::ApiKey, user::User};
+use crate::entities::{
+  api_key::ApiKey,
+  user::{LoginEvent, User},
+};
 
 use super::KomodoReadRequest;
 
@@ -45,6 +48,28 @@ pub type ListApiKeysForServiceUserResponse = Vec<ApiKey>;
 
 //
 
+/// List the most recent logins to an account, successful or failed,
+/// sorted by timestamp descending.
+/// Only admins can list the logins of other users.
+/// Response: [ListLoginEventsResponse]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListLoginEventsResponse)]
+#[error(serror::Error)]
+pub struct ListLoginEvents {
+  /// Id or username. Default is the calling user.
+  #[serde(default, alias = "id", alias = "username")]
+  pub user: Option<String>,
+}
+
+#[typeshare]
+pub type ListLoginEventsResponse = Vec<LoginEvent>;
+
+//
+
 /// **Admin only.**
 /// Find a user.
 /// Response: [FindUserResponse]
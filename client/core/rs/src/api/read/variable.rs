@@ -3,7 +3,7 @@ use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::variable::Variable;
+use crate::entities::{ResourceTarget, variable::Variable};
 
 use super::KomodoReadRequest;
 
@@ -45,3 +45,23 @@ pub struct ListVariables {}
 
 #[typeshare]
 pub type ListVariablesResponse = Vec<Variable>;
+
+//
+
+/// List the resources which reference the variable / secret
+/// as `[[NAME]]` in their config. Resources which skip secret
+/// interpolation are not included. Response: [ListVariableReferencesResponse]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListVariableReferencesResponse)]
+#[error(serror::Error)]
+pub struct ListVariableReferences {
+  /// The name of the variable / secret.
+  pub name: String,
+}
+
+#[typeshare]
+pub type ListVariableReferencesResponse = Vec<ResourceTarget>;
@@ -99,3 +99,45 @@ pub struct DeleteApiKey {
 
 #[typeshare]
 pub type DeleteApiKeyResponse = NoData;
+
+//
+
+/// Generate a new secret for one of the calling user's api keys.
+/// The key stays the same, and the old secret stops working.
+/// Response: [RotateApiKeySecretResponse].
+///
+/// Note. After the response is served, there will be no way
+/// to get the secret later.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoUserRequest)]
+#[response(RotateApiKeySecretResponse)]
+#[error(serror::Error)]
+pub struct RotateApiKeySecret {
+  /// The key to rotate the secret of.
+  pub key: String,
+}
+
+/// Response for [RotateApiKeySecret].
+#[typeshare]
+pub type RotateApiKeySecretResponse = CreateApiKeyResponse;
+
+//
+
+/// Log out of all sessions for the calling user.
+/// All JWTs issued before this call are rejected,
+/// including the one used to make it. Api keys are unaffected.
+/// Response: [NoData]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoUserRequest)]
+#[response(LogoutEverywhereResponse)]
+#[error(serror::Error)]
+pub struct LogoutEverywhere {}
+
+#[typeshare]
+pub type LogoutEverywhereResponse = NoData;
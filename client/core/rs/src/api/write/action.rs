@@ -45,6 +45,10 @@ pub struct CopyAction {
   pub name: String,
   /// The id of the action to copy.
   pub id: String,
+  /// Config fields to override on the new action.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialActionConfig,
 }
 
 //
@@ -44,6 +44,10 @@ pub struct CopyAlerter {
   pub name: String,
   /// The id of the alerter to copy.
   pub id: String,
+  /// Config fields to override on the new alerter.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialAlerterConfig,
 }
 
 //
@@ -45,6 +45,10 @@ pub struct CopyBuild {
   pub name: String,
   /// The id of the build to copy.
   pub id: String,
+  /// Config fields to override on the new build.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialBuildConfig,
 }
 
 //
@@ -121,6 +125,10 @@ pub struct WriteBuildFileContents {
   pub build: String,
   /// The dockerfile contents to write.
   pub contents: String,
+  /// Lint the dockerfile before writing.
+  /// Only for Files on Host Builds.
+  #[serde(default)]
+  pub validate: bool,
 }
 
 //
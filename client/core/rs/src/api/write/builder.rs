@@ -44,6 +44,10 @@ pub struct CopyBuilder {
   pub name: String,
   /// The id of the builder to copy.
   pub id: String,
+  /// Config to use instead of the copied config.
+  /// If the builder type is the same, only the given fields are replaced.
+  #[serde(default)]
+  pub config: Option<PartialBuilderConfig>,
 }
 
 //
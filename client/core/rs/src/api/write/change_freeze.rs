@@ -0,0 +1,46 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{I64, NoData, change_freeze::ChangeFreeze};
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Freeze changes across all resources.
+/// Until the freeze ends, all executions are rejected
+/// unless run by a super admin.
+/// Replaces any existing freeze. Response: [ChangeFreeze].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(StartChangeFreezeResponse)]
+#[error(serror::Error)]
+pub struct StartChangeFreeze {
+  /// Why changes are frozen.
+  pub reason: String,
+  /// Timestamp to end the freeze automatically.
+  /// Default: 0, which lasts until [EndChangeFreeze] is called.
+  #[serde(default)]
+  pub expires: I64,
+}
+
+#[typeshare]
+pub type StartChangeFreezeResponse = ChangeFreeze;
+
+//
+
+/// **Admin only.** End the active change freeze. Response: [NoData].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(EndChangeFreezeResponse)]
+#[error(serror::Error)]
+pub struct EndChangeFreeze {}
+
+#[typeshare]
+pub type EndChangeFreezeResponse = NoData;
@@ -44,6 +44,10 @@ pub struct CopyDeployment {
   pub name: String,
   /// The id of the deployment to copy.
   pub id: String,
+  /// Config fields to override on the new deployment.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialDeploymentConfig,
 }
 
 //
@@ -3,6 +3,7 @@ mod alerter;
 mod api_key;
 mod build;
 mod builder;
+mod change_freeze;
 mod deployment;
 mod permissions;
 mod procedure;
@@ -22,6 +23,7 @@ pub use alerter::*;
 pub use api_key::*;
 pub use build::*;
 pub use builder::*;
+pub use change_freeze::*;
 pub use deployment::*;
 pub use permissions::*;
 pub use procedure::*;
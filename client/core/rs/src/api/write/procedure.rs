@@ -47,6 +47,10 @@ pub struct CopyProcedure {
   pub name: String,
   /// The id of the procedure to copy.
   pub id: String,
+  /// Config fields to override on the new procedure.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialProcedureConfig,
 }
 
 #[typeshare]
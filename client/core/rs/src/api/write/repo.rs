@@ -45,6 +45,10 @@ pub struct CopyRepo {
   pub name: String,
   /// The id of the repo to copy.
   pub id: String,
+  /// Config fields to override on the new repo.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialRepoConfig,
 }
 
 //
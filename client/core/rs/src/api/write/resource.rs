@@ -28,6 +28,10 @@ pub struct UpdateResourceMeta {
   /// New template value (true or false),
   /// or null for no update
   pub template: Option<bool>,
+  /// New locked value (true or false),
+  /// or null for no update. Admin only.
+  #[serde(default)]
+  pub locked: Option<bool>,
   /// The exact tags to set,
   /// or null for no update
   pub tags: Option<Vec<String>>,
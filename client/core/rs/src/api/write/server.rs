@@ -45,6 +45,10 @@ pub struct CopyServer {
   pub name: String,
   /// The id of the server to copy.
   pub id: String,
+  /// Config fields to override on the new server.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialServerConfig,
 }
 
 //
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData,
+  GitSyncStrategy, NoData,
   stack::{_PartialStackConfig, Stack},
   update::Update,
 };
@@ -45,6 +45,37 @@ pub struct CopyStack {
   pub name: String,
   /// The id of the stack to copy.
   pub id: String,
+  /// Config fields to override on the new stack.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialStackConfig,
+}
+
+//
+
+/// Create a Stack from a compose project already running on a server,
+/// as listed by [ListComposeProjects]. Response: [Stack].
+///
+/// The stack uses `files_on_host`, pointing at the project's compose files,
+/// and the project name, so Komodo picks up the existing containers.
+///
+/// [ListComposeProjects]: crate::api::read::ListComposeProjects
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(Stack)]
+#[error(serror::Error)]
+pub struct CreateStackFromComposeProject {
+  /// The name of the compose project.
+  pub project: String,
+  /// The server id or name on which the project exists.
+  pub server: String,
+  /// The name given to the new stack.
+  /// If empty, uses the project name.
+  #[serde(default)]
+  pub name: String,
 }
 
 //
@@ -126,6 +157,15 @@ pub struct WriteStackFileContents {
   pub file_path: String,
   /// The contents to write.
   pub contents: String,
+  /// Validate the contents with `docker compose config` before writing.
+  /// Only for Files on Host Stacks.
+  #[serde(default)]
+  pub validate: bool,
+  /// How to handle the local repo having diverged from the remote
+  /// branch, which would cause the push to fail.
+  /// Only for Git Repo Stacks.
+  #[serde(default)]
+  pub sync_strategy: GitSyncStrategy,
 }
 
 //
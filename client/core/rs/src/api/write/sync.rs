@@ -46,6 +46,10 @@ pub struct CopyResourceSync {
   pub name: String,
   /// The id of the sync to copy.
   pub id: String,
+  /// Config fields to override on the new sync.
+  /// Fields which aren't given are copied.
+  #[serde(default)]
+  pub config: _PartialResourceSyncConfig,
 }
 
 //
@@ -164,6 +168,7 @@ pub struct WriteSyncFileContents {
 #[empty_traits(KomodoWriteRequest)]
 #[response(Update)]
 #[error(serror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CommitSync {
   /// Id or name
   #[serde(alias = "id", alias = "name")]
@@ -3,7 +3,10 @@ use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::tag::{Tag, TagColor};
+use crate::entities::{
+  ResourceTargetVariant,
+  tag::{Tag, TagColor},
+};
 
 use super::KomodoWriteRequest;
 
@@ -72,3 +75,65 @@ pub struct UpdateTagColor {
   /// The new color for the tag.
   pub color: TagColor,
 }
+
+//
+
+/// Add tags to all the resources of a type matching the pattern,
+/// with a single update. Tags which don't exist yet are created.
+/// Response: [BatchUpdateTagsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(BatchUpdateTagsResponse)]
+#[error(serror::Error)]
+pub struct BatchAddTags {
+  /// The type of resource to tag.
+  pub resource_type: ResourceTargetVariant,
+  /// Id or name or wildcard pattern or regex.
+  /// Supports multiline and comma delineated combinations of the above.
+  ///
+  /// Example:
+  /// ```text
+  /// # match all foo-* deployments
+  /// foo-*
+  /// # add some more
+  /// extra-deployment-1, extra-deployment-2
+  /// ```
+  pub pattern: String,
+  /// The names or ids of the tags to add.
+  pub tags: Vec<String>,
+}
+
+//
+
+/// Remove tags from all the resources of a type matching the pattern,
+/// with a single update. Response: [BatchUpdateTagsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(BatchUpdateTagsResponse)]
+#[error(serror::Error)]
+pub struct BatchRemoveTags {
+  /// The type of resource to untag.
+  pub resource_type: ResourceTargetVariant,
+  /// Id or name or wildcard pattern or regex.
+  /// Supports multiline and comma delineated combinations of the above.
+  pub pattern: String,
+  /// The names or ids of the tags to remove.
+  pub tags: Vec<String>,
+}
+
+/// Response for [BatchAddTags] and [BatchRemoveTags].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BatchUpdateTagsResponse {
+  /// The names of the matched resources whose tags changed.
+  pub updated: Vec<String>,
+  /// The names of the matched resources which already
+  /// had (or didn't have) all the tags.
+  pub unchanged: Vec<String>,
+}
@@ -18,6 +18,10 @@ use super::{
   resource::{Resource, ResourceListItem, ResourceQuery},
 };
 
+/// The stage of the Update log holding the structured output
+/// set by the Action using `setOutput`, serialized as JSON.
+pub const ACTION_OUTPUT_LOG_STAGE: &str = "Action Output";
+
 #[typeshare]
 pub type ActionListItem = ResourceListItem<ActionListItemInfo>;
 
@@ -147,6 +151,19 @@ pub struct ActionConfig {
   #[builder(default)]
   pub reload_deno_deps: bool,
 
+  /// Kill the Action if it is still running after this many seconds.
+  /// 0 means no timeout.
+  #[serde(default)]
+  #[builder(default)]
+  pub timeout_seconds: I64,
+
+  /// Limit the memory available to the Action runtime heap, in MB.
+  /// Passed to deno as the V8 `--max-old-space-size`.
+  /// 0 means no limit.
+  #[serde(default)]
+  #[builder(default)]
+  pub memory_limit_mb: I64,
+
   /// Typescript file contents using pre-initialized `komodo` client.
   /// Supports variable / secret interpolation.
   #[serde(default, deserialize_with = "file_contents_deserializer")]
@@ -212,6 +229,8 @@ impl Default for ActionConfig {
       webhook_enabled: default_webhook_enabled(),
       webhook_secret: Default::default(),
       reload_deno_deps: Default::default(),
+      timeout_seconds: Default::default(),
+      memory_limit_mb: Default::default(),
       arguments_format: Default::default(),
       file_contents: Default::default(),
       arguments: Default::default(),
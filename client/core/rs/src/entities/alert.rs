@@ -137,6 +137,24 @@ pub enum AlertData {
     total_gb: f64,
   },
 
+  /// A server's CPU or MEM usage spiked far above its recent baseline.
+  ServerStatsAnomaly {
+    /// The id of the server
+    id: String,
+    /// The name of the server
+    name: String,
+    /// The region of the server
+    region: Option<String>,
+    /// The spiking metric, `CPU` or `MEM`
+    metric: String,
+    /// The current usage percentage
+    percentage: f64,
+    /// The average usage percentage over the recent baseline
+    baseline: f64,
+    /// The number of standard deviations above the baseline
+    z_score: f64,
+  },
+
   /// A server has a version mismatch with the core.
   ServerVersionMismatch {
     /// The id of the server
@@ -167,6 +185,50 @@ pub enum AlertData {
     to: DeploymentState,
   },
 
+  /// A container was killed by the OOM killer.
+  ContainerOomKilled {
+    /// The id of the deployment
+    id: String,
+    /// The name of the deployment
+    name: String,
+    /// The server id of server that the deployment is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+    /// The container exit code, usually 137
+    exit_code: Option<I64>,
+  },
+
+  /// A Deployment's container differs from its config.
+  DeploymentConfigDrift {
+    /// The id of the deployment
+    id: String,
+    /// The name of the deployment
+    name: String,
+    /// The server id of server that the deployment is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+    /// The differences found
+    drift: Vec<String>,
+  },
+
+  /// A container is restarting repeatedly.
+  ContainerCrashLoop {
+    /// The id of the deployment
+    id: String,
+    /// The name of the deployment
+    name: String,
+    /// The server id of server that the deployment is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+    /// The number of restarts within the window
+    restarts: I64,
+    /// The window the restarts were counted over, eg `5-min`
+    window: String,
+  },
+
   /// A Deployment has an image update available
   DeploymentImageUpdateAvailable {
     /// The id of the deployment
@@ -301,6 +363,18 @@ pub enum AlertData {
     name: String,
   },
 
+  /// An api key will expire soon.
+  ApiKeyExpiring {
+    /// The api key name
+    name: String,
+    /// The id of the user the key belongs to
+    user_id: String,
+    /// The username of the user the key belongs to
+    username: String,
+    /// The unix timestamp (ms) the key expires at
+    expires: I64,
+  },
+
   /// Custom header / body.
   /// Produced using `/execute/SendAlert`
   Custom {
@@ -342,6 +416,7 @@ impl Default for AlertDataVariant {
 )]
 #[serde(rename_all = "UPPERCASE")]
 #[strum(serialize_all = "UPPERCASE")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum SeverityLevel {
   /// No problem.
   ///
@@ -30,6 +30,10 @@ pub struct ApiKey {
 
   /// Expiry of key, or 0 if never expires
   pub expires: I64,
+
+  /// Whether an alert has been sent that the key will expire soon
+  #[serde(default)]
+  pub expiry_alerted: bool,
 }
 
 impl ApiKey {
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{I64, MongoId, ResourceTarget};
+
+/// A record of a `/write` or `/execute` call,
+/// kept for auditing who did what.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "target.type": 1 }))]
+#[cfg_attr(feature = "mongo", doc_index({ "target.id": 1 }))]
+pub struct AuditLog {
+  /// The Mongo ID of the audit log.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized AuditLog) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// Unix timestamp in milliseconds of the call.
+  /// Executions are recorded when they finish.
+  #[cfg_attr(feature = "mongo", index)]
+  pub ts: I64,
+
+  /// The id of the calling user.
+  #[cfg_attr(feature = "mongo", index)]
+  pub user_id: String,
+
+  /// The username of the calling user.
+  pub username: String,
+
+  /// The api the call was made to, `write` or `execute`.
+  #[cfg_attr(feature = "mongo", index)]
+  pub api: String,
+
+  /// The request type, eg `UpdateServer` or `DeployStack`.
+  #[cfg_attr(feature = "mongo", index)]
+  pub operation: String,
+
+  /// The resource targeted by the call, if any.
+  /// For executions this is the target of the created Update.
+  /// Otherwise the id may be the resource name, as given in the request.
+  pub target: Option<ResourceTarget>,
+
+  /// Whether the call succeeded.
+  /// For executions, whether the execution finished successfully.
+  #[cfg_attr(feature = "mongo", index)]
+  pub success: bool,
+
+  /// The error, if the call failed.
+  pub error: Option<String>,
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::I64;
+
+/// A fleet wide change freeze, eg over the holidays or during an incident.
+/// While active, all executions are rejected unless run by a super admin.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ChangeFreeze {
+  /// Why changes are frozen.
+  /// Included in the error when an execution is rejected.
+  pub reason: String,
+
+  /// The id of the user who started the freeze.
+  pub started_by: String,
+
+  /// Timestamp the freeze started.
+  pub started_at: I64,
+
+  /// Timestamp the freeze ends, or 0 if it lasts until ended manually.
+  #[serde(default)]
+  pub expires: I64,
+}
+
+impl ChangeFreeze {
+  /// Whether the freeze is still in effect at the given timestamp.
+  pub fn is_active(&self, ts: I64) -> bool {
+    self.expires == 0 || self.expires > ts
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn freeze(expires: I64) -> ChangeFreeze {
+    ChangeFreeze {
+      reason: String::from("holidays"),
+      expires,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn freeze_without_expiry_stays_active() {
+    assert!(freeze(0).is_active(i64::MAX));
+  }
+
+  #[test]
+  fn freeze_ends_at_expiry() {
+    assert!(freeze(1_000).is_active(999));
+    assert!(!freeze(1_000).is_active(1_000));
+  }
+}
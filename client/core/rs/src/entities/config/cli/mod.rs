@@ -96,6 +96,11 @@ pub struct Env {
   pub komodo_cli_logging_pretty: Option<bool>,
   /// Override `logging.otlp_endpoint`
   pub komodo_cli_logging_otlp_endpoint: Option<String>,
+  /// Override `logging.otlp_metrics_endpoint`
+  pub komodo_cli_logging_otlp_metrics_endpoint: Option<String>,
+  /// Override `logging.otlp_high_cardinality_attributes`
+  pub komodo_cli_logging_otlp_high_cardinality_attributes:
+    Option<bool>,
   /// Override `logging.opentelemetry_service_name`
   pub komodo_cli_logging_opentelemetry_service_name: Option<String>,
   /// Override `pretty_startup_config`
@@ -11,12 +11,16 @@
 
 use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
+use ipnetwork::IpNetwork;
 use serde::Deserialize;
 
-use crate::entities::{
-  Timelength,
-  config::DatabaseConfig,
-  logger::{LogConfig, LogLevel, StdioLogMode},
+use crate::{
+  deserializers::ForgivingVec,
+  entities::{
+    Operation, Timelength,
+    config::DatabaseConfig,
+    logger::{LogConfig, LogLevel, StdioLogMode},
+  },
 };
 
 use super::{DockerRegistry, GitProvider, empty_or_redacted};
@@ -71,6 +75,22 @@ pub struct Env {
   pub komodo_port: Option<u16>,
   /// Override `bind_ip`
   pub komodo_bind_ip: Option<String>,
+  /// Override `max_request_body_bytes`
+  pub komodo_max_request_body_bytes: Option<usize>,
+  /// Override `request_timeout_seconds`
+  pub komodo_request_timeout_seconds: Option<u64>,
+  /// Override `max_query_length`
+  pub komodo_max_query_length: Option<usize>,
+  /// Override `max_query_depth`
+  pub komodo_max_query_depth: Option<usize>,
+  /// Override `allowed_ips`
+  pub komodo_allowed_ips: Option<ForgivingVec<IpNetwork>>,
+  /// Override `denied_ips`
+  pub komodo_denied_ips: Option<ForgivingVec<IpNetwork>>,
+  /// Override `trusted_proxies`
+  pub komodo_trusted_proxies: Option<ForgivingVec<IpNetwork>>,
+  /// Override `wait_until_allowed_ips`
+  pub komodo_wait_until_allowed_ips: Option<ForgivingVec<IpNetwork>>,
   /// Override `passkey`
   pub komodo_passkey: Option<String>,
   /// Override `passkey` with file
@@ -90,6 +110,14 @@ pub struct Env {
   pub komodo_jwt_secret_file: Option<PathBuf>,
   /// Override `jwt_ttl`
   pub komodo_jwt_ttl: Option<Timelength>,
+  /// Override `jwt_algorithm`
+  pub komodo_jwt_algorithm: Option<JwtAlgorithm>,
+  /// Override `jwt_key_id`
+  pub komodo_jwt_key_id: Option<String>,
+  /// Override `jwt_private_key_file`
+  pub komodo_jwt_private_key_file: Option<PathBuf>,
+  /// Override `jwt_public_key_file`
+  pub komodo_jwt_public_key_file: Option<PathBuf>,
   /// Override `sync_directory`
   pub komodo_sync_directory: Option<PathBuf>,
   /// Override `repo_directory`
@@ -104,6 +132,30 @@ pub struct Env {
   pub komodo_keep_stats_for_days: Option<u64>,
   /// Override `keep_alerts_for_days`
   pub komodo_keep_alerts_for_days: Option<u64>,
+  /// Override `keep_audit_logs_for_days`
+  pub komodo_keep_audit_logs_for_days: Option<u64>,
+  /// Override `keep_login_events_for_days`
+  pub komodo_keep_login_events_for_days: Option<u64>,
+  /// Override `api_key_expiry_alert_days`
+  pub komodo_api_key_expiry_alert_days: Option<u64>,
+  /// Override `crash_loop_restarts`
+  pub komodo_crash_loop_restarts: Option<u64>,
+  /// Override `crash_loop_window`
+  pub komodo_crash_loop_window: Option<Timelength>,
+  /// Override `deployment_drift_alerts`
+  pub komodo_deployment_drift_alerts: Option<bool>,
+  /// Override `keep_hourly_stats_for_days`
+  pub komodo_keep_hourly_stats_for_days: Option<u64>,
+  /// Override `keep_daily_stats_for_days`
+  pub komodo_keep_daily_stats_for_days: Option<u64>,
+  /// Override `max_update_log_bytes`
+  pub komodo_max_update_log_bytes: Option<u64>,
+  /// Override `shutdown_timeout_seconds`
+  pub komodo_shutdown_timeout_seconds: Option<u64>,
+  /// Override `metrics_token`
+  pub komodo_metrics_token: Option<String>,
+  /// Override `metrics_token` with file
+  pub komodo_metrics_token_file: Option<PathBuf>,
   /// Override `webhook_secret`
   pub komodo_webhook_secret: Option<String>,
   /// Override `webhook_secret` with file
@@ -121,6 +173,10 @@ pub struct Env {
   pub komodo_logging_location: Option<bool>,
   /// Override `logging.otlp_endpoint`
   pub komodo_logging_otlp_endpoint: Option<String>,
+  /// Override `logging.otlp_metrics_endpoint`
+  pub komodo_logging_otlp_metrics_endpoint: Option<String>,
+  /// Override `logging.otlp_high_cardinality_attributes`
+  pub komodo_logging_otlp_high_cardinality_attributes: Option<bool>,
   /// Override `logging.opentelemetry_service_name`
   pub komodo_logging_opentelemetry_service_name: Option<String>,
   /// Override `pretty_startup_config`
@@ -159,6 +215,20 @@ pub struct Env {
   pub komodo_init_admin_password: Option<String>,
   /// Override `init_admin_password` from file
   pub komodo_init_admin_password_file: Option<PathBuf>,
+  /// Override `require_bootstrap_token`
+  pub komodo_require_bootstrap_token: Option<bool>,
+  /// Override `password_min_length`
+  pub komodo_password_min_length: Option<usize>,
+  /// Override `password_min_character_classes`
+  pub komodo_password_min_character_classes: Option<u8>,
+  /// Override `password_deny_common`
+  pub komodo_password_deny_common: Option<bool>,
+  /// Override `login_lockout_attempts`
+  pub komodo_login_lockout_attempts: Option<u32>,
+  /// Override `login_lockout_window`
+  pub komodo_login_lockout_window: Option<Timelength>,
+  /// Override `login_lockout_duration`
+  pub komodo_login_lockout_duration: Option<Timelength>,
 
   /// Override `oidc_enabled`
   pub komodo_oidc_enabled: Option<bool>,
@@ -180,6 +250,14 @@ pub struct Env {
   pub komodo_oidc_additional_audiences: Option<Vec<String>>,
   /// Override `oidc_additional_audiences` from file
   pub komodo_oidc_additional_audiences_file: Option<PathBuf>,
+  /// Override `oidc_groups_claim`
+  pub komodo_oidc_groups_claim: Option<String>,
+  /// Override `oidc_admin_groups`
+  pub komodo_oidc_admin_groups: Option<Vec<String>>,
+  /// Override `oidc_sync_user_groups`
+  pub komodo_oidc_sync_user_groups: Option<bool>,
+  /// Override `oidc_user_group_prefix`
+  pub komodo_oidc_user_group_prefix: Option<String>,
 
   /// Override `google_oauth.enabled`
   pub komodo_google_oauth_enabled: Option<bool>,
@@ -313,6 +391,60 @@ pub struct CoreConfig {
   #[serde(default = "default_core_bind_ip")]
   pub bind_ip: String,
 
+  /// The maximum size of API request bodies in bytes.
+  /// Larger requests are rejected with 413.
+  /// Websocket connections are exempt.
+  /// Default: 2097152 (2 MiB)
+  #[serde(default = "default_max_request_body_bytes")]
+  pub max_request_body_bytes: usize,
+
+  /// The maximum time to handle an API request, including
+  /// reading the body. Slower requests are aborted with 408.
+  /// Websocket connections are exempt.
+  /// Default: 300
+  #[serde(default = "default_request_timeout_seconds")]
+  pub request_timeout_seconds: u64,
+
+  /// The maximum length of websocket query strings, in bytes.
+  /// Longer queries are rejected with 414.
+  /// Default: 2048
+  #[serde(default = "default_max_query_length")]
+  pub max_query_length: usize,
+
+  /// The maximum nesting depth of websocket query string parameters,
+  /// eg `a[b][c]=1` has depth 2. Deeper queries are rejected with 400.
+  /// Default: 5
+  #[serde(default = "default_max_query_depth")]
+  pub max_query_depth: usize,
+
+  /// Limits which IP addresses are allowed to call the
+  /// `/write` and `/execute` apis. Other routes are unaffected.
+  /// Default: none, which allows all IPs not in `denied_ips`.
+  #[serde(default)]
+  pub allowed_ips: ForgivingVec<IpNetwork>,
+
+  /// Blocks IP addresses from calling the `/write` and `/execute` apis.
+  /// Takes precedence over `allowed_ips`.
+  /// Default: none
+  #[serde(default)]
+  pub denied_ips: ForgivingVec<IpNetwork>,
+
+  /// The reverse proxies allowed to forward the client ip
+  /// (`X-Forwarded-For` / `X-Real-IP`). These headers are ignored
+  /// on requests from other peers, and the socket address is used.
+  /// The client ip is the rightmost `X-Forwarded-For` entry
+  /// which isn't one of these proxies.
+  /// Default: none, so forwarded headers are never trusted.
+  #[serde(default)]
+  pub trusted_proxies: ForgivingVec<IpNetwork>,
+
+  /// Non-public addresses which procedure `WaitUntil` `HttpOk`
+  /// conditions are allowed to send requests to,
+  /// eg loopback, link-local and private networks.
+  /// Default: none, so only public addresses are allowed.
+  #[serde(default)]
+  pub wait_until_allowed_ips: ForgivingVec<IpNetwork>,
+
   /// Interface to use as default route in multi-NIC environments.
   #[serde(default)]
   pub internet_interface: String,
@@ -383,6 +515,48 @@ pub struct CoreConfig {
   #[serde(default = "default_init_admin_password")]
   pub init_admin_password: String,
 
+  /// Require a one time bootstrap token to claim the first admin account,
+  /// instead of the first user to sign up becoming admin.
+  /// The token is printed to the logs on startup while no users exist,
+  /// and must be passed to local sign up.
+  /// The `init_admin_username` user is not created when enabled.
+  #[serde(default)]
+  pub require_bootstrap_token: bool,
+
+  /// The minimum length of local user passwords,
+  /// enforced when they are set.
+  /// Default: 8
+  #[serde(default = "default_password_min_length")]
+  pub password_min_length: usize,
+
+  /// The minimum number of character classes (lowercase, uppercase,
+  /// digits, symbols) local user passwords must contain.
+  /// Default: 1
+  #[serde(default = "default_password_min_character_classes")]
+  pub password_min_character_classes: u8,
+
+  /// Reject commonly used local user passwords, eg `password`.
+  /// Default: true
+  #[serde(default = "default_password_deny_common")]
+  pub password_deny_common: bool,
+
+  /// Lock a local user account after this many failed logins
+  /// within the `login_lockout_window`. 0 disables lockout.
+  /// Default: 5
+  #[serde(default = "default_login_lockout_attempts")]
+  pub login_lockout_attempts: u32,
+
+  /// The window in which failed logins are counted.
+  /// Default: `15-min`
+  #[serde(default = "default_login_lockout_timelength")]
+  pub login_lockout_window: Timelength,
+
+  /// How long a locked account rejects logins, even with
+  /// the correct password.
+  /// Default: `15-min`
+  #[serde(default = "default_login_lockout_timelength")]
+  pub login_lockout_duration: Timelength,
+
   /// Enable transparent mode, which gives all (enabled) users read access to all resources.
   #[serde(default)]
   pub transparent_mode: bool,
@@ -420,6 +594,39 @@ pub struct CoreConfig {
   #[serde(default = "default_jwt_ttl")]
   pub jwt_ttl: Timelength,
 
+  /// The algorithm used to sign JWTs.
+  /// `HS256` signs with the `jwt_secret`, while `RS256` and `ES256`
+  /// sign with the `jwt_private_key_file`, so external services
+  /// can verify them with the public key.
+  /// Default: `HS256`.
+  #[serde(default)]
+  pub jwt_algorithm: JwtAlgorithm,
+
+  /// The key id (`kid`) included in the header of issued JWTs.
+  /// Change it when rotating the signing key.
+  /// Default: "" (no `kid`).
+  #[serde(default)]
+  pub jwt_key_id: String,
+
+  /// Path to the PEM encoded private key used to sign JWTs
+  /// with `RS256` or `ES256`.
+  /// Default: `/config/jwt/private-key.pem`.
+  #[serde(default = "default_jwt_private_key_file")]
+  pub jwt_private_key_file: PathBuf,
+
+  /// Path to the PEM encoded public key used to verify JWTs
+  /// with `RS256` or `ES256`.
+  /// Default: `/config/jwt/public-key.pem`.
+  #[serde(default = "default_jwt_public_key_file")]
+  pub jwt_public_key_file: PathBuf,
+
+  /// Public keys of previous signing keys, which JWTs carrying
+  /// their `kid` are still verified with. Keep a key here until
+  /// the tokens it signed have expired.
+  /// Only used with `RS256` or `ES256`.
+  #[serde(default)]
+  pub jwt_previous_keys: Vec<JwtPreviousKey>,
+
   // ========
   // = OIDC =
   // ========
@@ -465,6 +672,40 @@ pub struct CoreConfig {
   #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub oidc_additional_audiences: Vec<String>,
 
+  /// The claim holding the user's groups, read from the
+  /// provider user info on each login.
+  /// Default: `groups`
+  #[serde(default = "default_oidc_groups_claim")]
+  pub oidc_groups_claim: String,
+
+  /// Members of these provider groups are made Komodo admins,
+  /// and non-members have admin removed, on each login.
+  /// Super admins are never demoted.
+  /// If empty, admin is managed in Komodo.
+  #[serde(default)]
+  pub oidc_admin_groups: Vec<String>,
+
+  /// Manage Komodo User Group membership from the provider groups.
+  /// On each login, the user is added to the User Groups
+  /// matching the names of their provider groups.
+  /// They are removed from the User Groups starting with
+  /// `oidc_user_group_prefix` which they are no longer in.
+  #[serde(default)]
+  pub oidc_sync_user_groups: bool,
+
+  /// Only User Groups whose names start with this prefix
+  /// are managed by `oidc_sync_user_groups`.
+  /// If empty, users are added to User Groups, but never removed.
+  #[serde(default)]
+  pub oidc_user_group_prefix: String,
+
+  /// Configure additional, named OIDC providers, each offered
+  /// as a separate login option. Their login and callback routes
+  /// are `/auth/oidc/{name}/login` and `/auth/oidc/{name}/callback`.
+  /// Requires `oidc_enabled`.
+  #[serde(default)]
+  pub oidc_providers: Vec<OidcProviderConfig>,
+
   // =========
   // = Oauth =
   // =========
@@ -499,6 +740,11 @@ pub struct CoreConfig {
   #[serde(default)]
   pub github_webhook_app: GithubWebhookAppConfig,
 
+  /// Outbound webhooks called with the Update
+  /// whenever an execution completes.
+  #[serde(default)]
+  pub execution_webhooks: Vec<ExecutionWebhookConfig>,
+
   // ===========
   // = Logging =
   // ===========
@@ -532,6 +778,74 @@ pub struct CoreConfig {
   #[serde(default = "default_prune_days")]
   pub keep_alerts_for_days: u64,
 
+  /// Number of days to keep audit logs, or 0 to disable pruning.
+  /// Audit logs older than this number of days are deleted on a daily cycle
+  /// Default: 90
+  #[serde(default = "default_keep_audit_logs_for_days")]
+  pub keep_audit_logs_for_days: u64,
+
+  /// Number of days to keep login events, or 0 to disable pruning.
+  /// Login events older than this number of days are deleted on a daily cycle
+  /// Default: 30
+  #[serde(default = "default_keep_login_events_for_days")]
+  pub keep_login_events_for_days: u64,
+
+  /// Send an alert when an api key will expire within this number of days,
+  /// or 0 to disable. Api keys are checked on a daily cycle.
+  /// Default: 7
+  #[serde(default = "default_api_key_expiry_alert_days")]
+  pub api_key_expiry_alert_days: u64,
+
+  /// Send an alert when a Deployment's container restarts this many times
+  /// within the `crash_loop_window`, or 0 to disable.
+  /// When enabled, the running Deployment containers are inspected
+  /// for their restart count on each monitoring cycle.
+  /// Default: 0
+  #[serde(default)]
+  pub crash_loop_restarts: u64,
+
+  /// The window the `crash_loop_restarts` are counted over.
+  /// Default: `5-min`
+  #[serde(default = "default_crash_loop_window")]
+  pub crash_loop_window: Timelength,
+
+  /// Send an alert when a running Deployment's container is found to differ
+  /// from its config, eg after a manual `docker update`.
+  /// Drift is checked on the `resource_poll_interval` either way.
+  /// Default: false
+  #[serde(default)]
+  pub deployment_drift_alerts: bool,
+
+  /// Number of days to keep hourly stats averages, or 0 to disable pruning.
+  /// These are computed from the stats on the daily cycle.
+  /// Default: 90
+  #[serde(default = "default_keep_hourly_stats_for_days")]
+  pub keep_hourly_stats_for_days: u64,
+
+  /// Number of days to keep daily stats averages, or 0 to disable pruning.
+  /// These are computed from the stats on the daily cycle.
+  /// Default: 730
+  #[serde(default = "default_keep_daily_stats_for_days")]
+  pub keep_daily_stats_for_days: u64,
+
+  /// Maximum total bytes of logs stored on a single Update, or 0 for no limit.
+  /// When exceeded, log output is truncated, successful logs first, oldest first.
+  /// Default: 5242880 (5 MiB)
+  #[serde(default = "default_max_update_log_bytes")]
+  pub max_update_log_bytes: u64,
+
+  /// On shutdown, Core stops accepting new executions and waits
+  /// up to this many seconds for in flight executions to finish.
+  /// Any still running after are marked as interrupted.
+  /// Default: 10
+  #[serde(default = "default_shutdown_timeout_seconds")]
+  pub shutdown_timeout_seconds: u64,
+
+  /// Bearer token required to scrape the Prometheus metrics at `/metrics`.
+  /// If empty, the metrics endpoint is disabled.
+  #[serde(default)]
+  pub metrics_token: String,
+
   // ==================
   // = Poll Intervals =
   // ==================
@@ -639,6 +953,22 @@ fn default_core_bind_ip() -> String {
   "[::]".to_string()
 }
 
+fn default_max_request_body_bytes() -> usize {
+  2 * 1024 * 1024
+}
+
+fn default_request_timeout_seconds() -> u64 {
+  300
+}
+
+fn default_max_query_length() -> usize {
+  2048
+}
+
+fn default_max_query_depth() -> usize {
+  5
+}
+
 fn default_passkey() -> String {
   String::from("default-passkey-changeme")
 }
@@ -655,10 +985,42 @@ fn default_jwt_ttl() -> Timelength {
   Timelength::OneDay
 }
 
+fn default_oidc_groups_claim() -> String {
+  String::from("groups")
+}
+
+fn default_jwt_private_key_file() -> PathBuf {
+  "/config/jwt/private-key.pem".parse().unwrap()
+}
+
+fn default_jwt_public_key_file() -> PathBuf {
+  "/config/jwt/public-key.pem".parse().unwrap()
+}
+
 fn default_init_admin_password() -> String {
   String::from("changeme")
 }
 
+fn default_password_min_length() -> usize {
+  8
+}
+
+fn default_password_min_character_classes() -> u8 {
+  1
+}
+
+fn default_password_deny_common() -> bool {
+  true
+}
+
+fn default_login_lockout_attempts() -> u32 {
+  5
+}
+
+fn default_login_lockout_timelength() -> Timelength {
+  Timelength::FifteenMinutes
+}
+
 fn default_sync_directory() -> PathBuf {
   // unwrap ok: `/syncs` will always be valid path
   PathBuf::from_str("/syncs").unwrap()
@@ -674,10 +1036,42 @@ fn default_action_directory() -> PathBuf {
   PathBuf::from_str("/action-cache").unwrap()
 }
 
+fn default_max_update_log_bytes() -> u64 {
+  5 * 1024 * 1024
+}
+
+fn default_shutdown_timeout_seconds() -> u64 {
+  10
+}
+
 fn default_prune_days() -> u64 {
   14
 }
 
+fn default_api_key_expiry_alert_days() -> u64 {
+  7
+}
+
+fn default_crash_loop_window() -> Timelength {
+  Timelength::FiveMinutes
+}
+
+fn default_keep_audit_logs_for_days() -> u64 {
+  90
+}
+
+fn default_keep_login_events_for_days() -> u64 {
+  30
+}
+
+fn default_keep_hourly_stats_for_days() -> u64 {
+  90
+}
+
+fn default_keep_daily_stats_for_days() -> u64 {
+  730
+}
+
 fn default_poll_interval() -> Timelength {
   Timelength::OneHour
 }
@@ -701,6 +1095,14 @@ impl Default for CoreConfig {
       host: default_host(),
       port: default_core_port(),
       bind_ip: default_core_bind_ip(),
+      max_request_body_bytes: default_max_request_body_bytes(),
+      request_timeout_seconds: default_request_timeout_seconds(),
+      max_query_length: default_max_query_length(),
+      max_query_depth: default_max_query_depth(),
+      allowed_ips: Default::default(),
+      denied_ips: Default::default(),
+      trusted_proxies: Default::default(),
+      wait_until_allowed_ips: Default::default(),
       internet_interface: Default::default(),
       passkey: default_passkey(),
       timezone: Default::default(),
@@ -716,6 +1118,14 @@ impl Default for CoreConfig {
       local_auth: Default::default(),
       init_admin_username: Default::default(),
       init_admin_password: default_init_admin_password(),
+      require_bootstrap_token: Default::default(),
+      password_min_length: default_password_min_length(),
+      password_min_character_classes:
+        default_password_min_character_classes(),
+      password_deny_common: default_password_deny_common(),
+      login_lockout_attempts: default_login_lockout_attempts(),
+      login_lockout_window: default_login_lockout_timelength(),
+      login_lockout_duration: default_login_lockout_timelength(),
       transparent_mode: Default::default(),
       enable_new_users: Default::default(),
       disable_user_registration: Default::default(),
@@ -723,6 +1133,11 @@ impl Default for CoreConfig {
       disable_non_admin_create: Default::default(),
       jwt_secret: Default::default(),
       jwt_ttl: default_jwt_ttl(),
+      jwt_algorithm: Default::default(),
+      jwt_key_id: Default::default(),
+      jwt_private_key_file: default_jwt_private_key_file(),
+      jwt_public_key_file: default_jwt_public_key_file(),
+      jwt_previous_keys: Default::default(),
       oidc_enabled: Default::default(),
       oidc_provider: Default::default(),
       oidc_redirect_host: Default::default(),
@@ -730,16 +1145,35 @@ impl Default for CoreConfig {
       oidc_client_secret: Default::default(),
       oidc_use_full_email: Default::default(),
       oidc_additional_audiences: Default::default(),
+      oidc_groups_claim: default_oidc_groups_claim(),
+      oidc_admin_groups: Default::default(),
+      oidc_sync_user_groups: Default::default(),
+      oidc_user_group_prefix: Default::default(),
+      oidc_providers: Default::default(),
       google_oauth: Default::default(),
       github_oauth: Default::default(),
       webhook_secret: Default::default(),
       webhook_base_url: Default::default(),
       github_webhook_app: Default::default(),
+      execution_webhooks: Default::default(),
       logging: Default::default(),
       pretty_startup_config: Default::default(),
       unsafe_unsanitized_startup_config: Default::default(),
       keep_stats_for_days: default_prune_days(),
       keep_alerts_for_days: default_prune_days(),
+      keep_audit_logs_for_days: default_keep_audit_logs_for_days(),
+      keep_login_events_for_days: default_keep_login_events_for_days(
+      ),
+      api_key_expiry_alert_days: default_api_key_expiry_alert_days(),
+      crash_loop_restarts: Default::default(),
+      crash_loop_window: default_crash_loop_window(),
+      deployment_drift_alerts: Default::default(),
+      keep_hourly_stats_for_days: default_keep_hourly_stats_for_days(
+      ),
+      keep_daily_stats_for_days: default_keep_daily_stats_for_days(),
+      max_update_log_bytes: default_max_update_log_bytes(),
+      shutdown_timeout_seconds: default_shutdown_timeout_seconds(),
+      metrics_token: Default::default(),
       resource_poll_interval: default_poll_interval(),
       monitoring_interval: default_monitoring_interval(),
       aws: Default::default(),
@@ -764,6 +1198,14 @@ impl CoreConfig {
       host: config.host,
       port: config.port,
       bind_ip: config.bind_ip,
+      max_request_body_bytes: config.max_request_body_bytes,
+      request_timeout_seconds: config.request_timeout_seconds,
+      max_query_length: config.max_query_length,
+      max_query_depth: config.max_query_depth,
+      allowed_ips: config.allowed_ips,
+      denied_ips: config.denied_ips,
+      trusted_proxies: config.trusted_proxies,
+      wait_until_allowed_ips: config.wait_until_allowed_ips,
       passkey: empty_or_redacted(&config.passkey),
       timezone: config.timezone,
       first_server: config.first_server,
@@ -771,6 +1213,11 @@ impl CoreConfig {
       frontend_path: config.frontend_path,
       jwt_secret: empty_or_redacted(&config.jwt_secret),
       jwt_ttl: config.jwt_ttl,
+      jwt_algorithm: config.jwt_algorithm,
+      jwt_key_id: config.jwt_key_id,
+      jwt_private_key_file: config.jwt_private_key_file,
+      jwt_public_key_file: config.jwt_public_key_file,
+      jwt_previous_keys: config.jwt_previous_keys,
       repo_directory: config.repo_directory,
       action_directory: config.action_directory,
       sync_directory: config.sync_directory,
@@ -779,6 +1226,17 @@ impl CoreConfig {
       monitoring_interval: config.monitoring_interval,
       keep_stats_for_days: config.keep_stats_for_days,
       keep_alerts_for_days: config.keep_alerts_for_days,
+      keep_audit_logs_for_days: config.keep_audit_logs_for_days,
+      keep_login_events_for_days: config.keep_login_events_for_days,
+      api_key_expiry_alert_days: config.api_key_expiry_alert_days,
+      crash_loop_restarts: config.crash_loop_restarts,
+      crash_loop_window: config.crash_loop_window,
+      deployment_drift_alerts: config.deployment_drift_alerts,
+      keep_hourly_stats_for_days: config.keep_hourly_stats_for_days,
+      keep_daily_stats_for_days: config.keep_daily_stats_for_days,
+      max_update_log_bytes: config.max_update_log_bytes,
+      shutdown_timeout_seconds: config.shutdown_timeout_seconds,
+      metrics_token: empty_or_redacted(&config.metrics_token),
       logging: config.logging,
       pretty_startup_config: config.pretty_startup_config,
       unsafe_unsanitized_startup_config: config
@@ -800,6 +1258,14 @@ impl CoreConfig {
       init_admin_password: empty_or_redacted(
         &config.init_admin_password,
       ),
+      require_bootstrap_token: config.require_bootstrap_token,
+      password_min_length: config.password_min_length,
+      password_min_character_classes: config
+        .password_min_character_classes,
+      password_deny_common: config.password_deny_common,
+      login_lockout_attempts: config.login_lockout_attempts,
+      login_lockout_window: config.login_lockout_window,
+      login_lockout_duration: config.login_lockout_duration,
       oidc_enabled: config.oidc_enabled,
       oidc_provider: config.oidc_provider,
       oidc_redirect_host: config.oidc_redirect_host,
@@ -813,6 +1279,24 @@ impl CoreConfig {
         .iter()
         .map(|aud| empty_or_redacted(aud))
         .collect(),
+      oidc_groups_claim: config.oidc_groups_claim,
+      oidc_admin_groups: config.oidc_admin_groups,
+      oidc_sync_user_groups: config.oidc_sync_user_groups,
+      oidc_user_group_prefix: config.oidc_user_group_prefix,
+      oidc_providers: config
+        .oidc_providers
+        .into_iter()
+        .map(|provider| OidcProviderConfig {
+          client_id: empty_or_redacted(&provider.client_id),
+          client_secret: empty_or_redacted(&provider.client_secret),
+          additional_audiences: provider
+            .additional_audiences
+            .iter()
+            .map(|aud| empty_or_redacted(aud))
+            .collect(),
+          ..provider
+        })
+        .collect(),
       google_oauth: OauthCredentials {
         enabled: config.google_oauth.enabled,
         id: empty_or_redacted(&config.google_oauth.id),
@@ -826,6 +1310,14 @@ impl CoreConfig {
       webhook_secret: empty_or_redacted(&config.webhook_secret),
       webhook_base_url: config.webhook_base_url,
       github_webhook_app: config.github_webhook_app,
+      execution_webhooks: config
+        .execution_webhooks
+        .into_iter()
+        .map(|webhook| ExecutionWebhookConfig {
+          secret: empty_or_redacted(&webhook.secret),
+          ..webhook
+        })
+        .collect(),
       database: config.database.sanitized(),
       aws: AwsCredentials {
         access_key_id: empty_or_redacted(&config.aws.access_key_id),
@@ -880,6 +1372,73 @@ pub struct OauthCredentials {
   pub secret: String,
 }
 
+/// Configure an OIDC provider.
+/// The fields match the top level `oidc_*` fields.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OidcProviderConfig {
+  /// A unique name for the provider, used in its routes.
+  /// Only alphanumeric characters, `-` and `_` are allowed.
+  pub name: String,
+  /// The OIDC provider address, reachable from Komodo Core.
+  pub provider: String,
+  /// The host users are redirected to in their browser,
+  /// if different from the `provider` host.
+  #[serde(default)]
+  pub redirect_host: String,
+  /// The OIDC client id.
+  pub client_id: String,
+  /// The OIDC client secret.
+  #[serde(default)]
+  pub client_secret: String,
+  /// Use the full email for usernames.
+  #[serde(default)]
+  pub use_full_email: bool,
+  /// Additional audiences the provider sets other than `client_id`.
+  #[serde(default)]
+  pub additional_audiences: Vec<String>,
+}
+
+/// Configure an outbound webhook, called when executions complete.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExecutionWebhookConfig {
+  /// The url the completed execution Update is POSTed to.
+  pub url: String,
+  /// Signs the body with HMAC SHA256, sent in the
+  /// `X-Komodo-Signature-256` header as `sha256={hex}`.
+  /// The body is not signed if empty.
+  #[serde(default)]
+  pub secret: String,
+  /// Only call the webhook for these operations, eg `DeployStack`.
+  /// Empty calls it for all executions.
+  #[serde(default)]
+  pub operations: Vec<Operation>,
+  /// Only call the webhook for failed executions.
+  #[serde(default)]
+  pub failures_only: bool,
+}
+
+/// The algorithm used to sign JWTs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum JwtAlgorithm {
+  /// HMAC using SHA-256, with the `jwt_secret`.
+  #[default]
+  HS256,
+  /// RSASSA-PKCS1-v1_5 using SHA-256, with an RSA key pair.
+  RS256,
+  /// ECDSA using P-256 and SHA-256, with an EC key pair.
+  ES256,
+}
+
+/// A previous JWT signing key, kept to verify
+/// the tokens it signed during a key rotation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtPreviousKey {
+  /// The key id (`kid`) the key signed tokens with.
+  pub kid: String,
+  /// Path to the PEM encoded public key.
+  pub public_key_file: PathBuf,
+}
+
 /// Provide AWS credentials for Komodo to use.
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct AwsCredentials {
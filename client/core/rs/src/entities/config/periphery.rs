@@ -140,8 +140,26 @@ pub struct Env {
   pub periphery_stats_polling_rate: Option<Timelength>,
   /// Override `container_stats_polling_rate`
   pub periphery_container_stats_polling_rate: Option<Timelength>,
+  /// Override `container_stats_labels`
+  pub periphery_container_stats_labels: Option<ForgivingVec<String>>,
+  /// Override `container_stats_concurrency`
+  pub periphery_container_stats_concurrency: Option<usize>,
+  /// Override `container_stats_history`
+  pub periphery_container_stats_history: Option<usize>,
+  /// Override `min_ping_interval`
+  pub periphery_min_ping_interval: Option<Timelength>,
+  /// Override `max_ping_interval`
+  pub periphery_max_ping_interval: Option<Timelength>,
   /// Override `legacy_compose_cli`
   pub periphery_legacy_compose_cli: Option<bool>,
+  /// Override `image_scanner`
+  pub periphery_image_scanner: Option<String>,
+  /// Override `prefer_docker_api`
+  pub periphery_prefer_docker_api: Option<bool>,
+  /// Override `shell`
+  pub periphery_shell: Option<String>,
+  /// Override `combined_build_output`
+  pub periphery_combined_build_output: Option<bool>,
 
   // LOGGING
   /// Override `logging.level`
@@ -154,6 +172,11 @@ pub struct Env {
   pub periphery_logging_location: Option<bool>,
   /// Override `logging.otlp_endpoint`
   pub periphery_logging_otlp_endpoint: Option<String>,
+  /// Override `logging.otlp_metrics_endpoint`
+  pub periphery_logging_otlp_metrics_endpoint: Option<String>,
+  /// Override `logging.otlp_high_cardinality_attributes`
+  pub periphery_logging_otlp_high_cardinality_attributes:
+    Option<bool>,
   /// Override `logging.opentelemetry_service_name`
   pub periphery_logging_opentelemetry_service_name: Option<String>,
   /// Override `pretty_startup_config`
@@ -165,10 +188,21 @@ pub struct Env {
   pub periphery_passkey: Option<Vec<String>>,
   /// Override `passkey` from file
   pub periphery_passkey_file: Option<PathBuf>,
+  /// Override `allowed_command_directories`
+  pub periphery_allowed_command_directories:
+    Option<ForgivingVec<PathBuf>>,
+  /// Override `allowed_file_directories`
+  pub periphery_allowed_file_directories:
+    Option<ForgivingVec<PathBuf>>,
   /// Override `include_disk_mounts`
   pub periphery_include_disk_mounts: Option<ForgivingVec<PathBuf>>,
   /// Override `exclude_disk_mounts`
   pub periphery_exclude_disk_mounts: Option<ForgivingVec<PathBuf>>,
+  /// Override `include_container_labels`
+  pub periphery_include_container_labels:
+    Option<ForgivingVec<String>>,
+  /// Override `include_container_names`
+  pub periphery_include_container_names: Option<ForgivingVec<String>>,
 
   /// Override `ssl_enabled`
   pub periphery_ssl_enabled: Option<bool>,
@@ -198,6 +232,7 @@ pub struct PeripheryConfig {
   /// repo: ${root_directory}/repos
   /// stack: ${root_directory}/stacks
   /// build: ${root_directory}/builds
+  /// backups: ${root_directory}/backups
   ///
   /// Note. These can each be overridden with a specific directory
   /// by specifying `repo_dir`, `stack_dir`, or `build_dir` explicitly
@@ -245,12 +280,72 @@ pub struct PeripheryConfig {
   #[serde(default = "default_container_stats_polling_rate")]
   pub container_stats_polling_rate: Timelength,
 
+  /// If non-empty, only the stats of containers with one of these labels are polled.
+  /// Each entry is either a label key, eg `komodo.managed`,
+  /// or a `key=value` pair.
+  /// Default: empty, which polls all containers.
+  #[serde(default)]
+  pub container_stats_labels: ForgivingVec<String>,
+
+  /// The most containers whose stats are collected at once.
+  /// `docker stats` collects each container concurrently,
+  /// so hosts with many containers are polled in batches of this size.
+  /// Default: 0, which polls all containers at once.
+  #[serde(default)]
+  pub container_stats_concurrency: usize,
+
+  /// The number of polled stats samples kept in memory for each container,
+  /// served to Core for the recent stats history. 0 to disable.
+  /// Default: 60
+  #[serde(default = "default_container_stats_history")]
+  pub container_stats_history: usize,
+
+  /// The shortest interval between pings on the container event stream.
+  /// The interval narrows towards this after a missed ping.
+  /// Default: `5-sec`
+  #[serde(default = "default_min_ping_interval")]
+  pub min_ping_interval: Timelength,
+
+  /// The longest interval between pings on the container event stream.
+  /// The interval widens towards this while the connection is stable.
+  /// Default: `1-min`
+  #[serde(default = "default_max_ping_interval")]
+  pub max_ping_interval: Timelength,
+
   /// Whether stack actions should use `docker-compose ...`
   /// instead of `docker compose ...`.
   /// Default: false
   #[serde(default)]
   pub legacy_compose_cli: bool,
 
+  /// Path to an image vulnerability scanner, `trivy` or `grype`,
+  /// used by `ScanImage`. Scanning is disabled if empty.
+  /// Default: empty
+  #[serde(default)]
+  pub image_scanner: String,
+
+  /// Whether container start, stop, restart and remove
+  /// should go through the docker API rather than the `docker` CLI.
+  /// Falls back to the CLI if the API is unavailable.
+  /// Default: false
+  #[serde(default)]
+  pub prefer_docker_api: bool,
+
+  /// The shell used to run commands, eg `bash`, `zsh` or `/usr/bin/dash`.
+  /// Must be an absolute path or found on the `PATH`,
+  /// and accept the command with `-c`.
+  /// Default: empty, which uses `sh`.
+  #[serde(default)]
+  pub shell: String,
+
+  /// Whether the `docker build` log should hold stdout and stderr
+  /// together in the order they were written, rather than separately.
+  /// The build progress is interleaved with the build step output,
+  /// but the log level can no longer be inferred from stderr.
+  /// Default: false
+  #[serde(default)]
+  pub combined_build_output: bool,
+
   /// Logging configuration
   #[serde(default)]
   pub logging: LogConfig,
@@ -274,6 +369,22 @@ pub struct PeripheryConfig {
   #[serde(default)]
   pub passkey: Vec<String>,
 
+  /// If non-empty, commands may only run with a working directory
+  /// inside one of these directories, after resolving `..` and symlinks.
+  /// Commands given any other working directory fail without running,
+  /// and commands without one run in the first directory.
+  /// Eg. `["/etc/komodo"]` keeps commands inside the `root_directory`.
+  /// Default: empty, which doesn't restrict the working directory.
+  #[serde(default)]
+  pub allowed_command_directories: ForgivingVec<PathBuf>,
+
+  /// Directories, in addition to the `stack_dir` and `repo_dir`,
+  /// whose files may be listed and read through the api
+  /// with `ListDir` and `ReadFile`.
+  /// Default: empty
+  #[serde(default)]
+  pub allowed_file_directories: ForgivingVec<PathBuf>,
+
   /// If non-empty, only includes specific mount paths in the disk report.
   #[serde(default)]
   pub include_disk_mounts: ForgivingVec<PathBuf>,
@@ -282,6 +393,18 @@ pub struct PeripheryConfig {
   #[serde(default)]
   pub exclude_disk_mounts: ForgivingVec<PathBuf>,
 
+  /// If non-empty (or `include_container_names` is non-empty),
+  /// only containers with one of these labels are reported.
+  /// Each entry is either a label key, eg `komodo.managed`,
+  /// or a `key=value` pair.
+  #[serde(default)]
+  pub include_container_labels: ForgivingVec<String>,
+
+  /// If non-empty (or `include_container_labels` is non-empty),
+  /// only containers with one of these names are reported.
+  #[serde(default)]
+  pub include_container_names: ForgivingVec<String>,
+
   /// Mapping on local periphery secrets. These can be interpolated into eg. Deployment environment variables.
   /// Default: none
   #[serde(default)]
@@ -331,6 +454,18 @@ fn default_container_stats_polling_rate() -> Timelength {
   Timelength::ThirtySeconds
 }
 
+fn default_container_stats_history() -> usize {
+  60
+}
+
+fn default_min_ping_interval() -> Timelength {
+  Timelength::FiveSeconds
+}
+
+fn default_max_ping_interval() -> Timelength {
+  Timelength::OneMinute
+}
+
 fn default_ssl_enabled() -> bool {
   true
 }
@@ -349,13 +484,26 @@ impl Default for PeripheryConfig {
       stats_polling_rate: default_stats_polling_rate(),
       container_stats_polling_rate:
         default_container_stats_polling_rate(),
+      container_stats_labels: Default::default(),
+      container_stats_concurrency: Default::default(),
+      container_stats_history: default_container_stats_history(),
+      min_ping_interval: default_min_ping_interval(),
+      max_ping_interval: default_max_ping_interval(),
       legacy_compose_cli: Default::default(),
+      image_scanner: Default::default(),
+      prefer_docker_api: Default::default(),
+      shell: Default::default(),
+      combined_build_output: Default::default(),
       logging: Default::default(),
       pretty_startup_config: Default::default(),
       allowed_ips: Default::default(),
       passkey: Default::default(),
+      allowed_command_directories: Default::default(),
+      allowed_file_directories: Default::default(),
       include_disk_mounts: Default::default(),
       exclude_disk_mounts: Default::default(),
+      include_container_labels: Default::default(),
+      include_container_names: Default::default(),
       secrets: Default::default(),
       git_providers: Default::default(),
       docker_registries: Default::default(),
@@ -379,7 +527,16 @@ impl PeripheryConfig {
       disable_container_exec: self.disable_container_exec,
       stats_polling_rate: self.stats_polling_rate,
       container_stats_polling_rate: self.container_stats_polling_rate,
+      container_stats_labels: self.container_stats_labels.clone(),
+      container_stats_concurrency: self.container_stats_concurrency,
+      container_stats_history: self.container_stats_history,
+      min_ping_interval: self.min_ping_interval,
+      max_ping_interval: self.max_ping_interval,
       legacy_compose_cli: self.legacy_compose_cli,
+      image_scanner: self.image_scanner.clone(),
+      prefer_docker_api: self.prefer_docker_api,
+      shell: self.shell.clone(),
+      combined_build_output: self.combined_build_output,
       logging: self.logging.clone(),
       pretty_startup_config: self.pretty_startup_config,
       allowed_ips: self.allowed_ips.clone(),
@@ -388,8 +545,14 @@ impl PeripheryConfig {
         .iter()
         .map(|passkey| empty_or_redacted(passkey))
         .collect(),
+      allowed_command_directories: self
+        .allowed_command_directories
+        .clone(),
+      allowed_file_directories: self.allowed_file_directories.clone(),
       include_disk_mounts: self.include_disk_mounts.clone(),
       exclude_disk_mounts: self.exclude_disk_mounts.clone(),
+      include_container_labels: self.include_container_labels.clone(),
+      include_container_names: self.include_container_names.clone(),
       secrets: self
         .secrets
         .iter()
@@ -459,6 +622,12 @@ impl PeripheryConfig {
     }
   }
 
+  /// Where the previous contents of files written
+  /// through the api are backed up.
+  pub fn backup_dir(&self) -> PathBuf {
+    self.root_directory.join("backups")
+  }
+
   pub fn ssl_key_file(&self) -> PathBuf {
     if let Some(dir) = &self.ssl_key_file {
       dir.to_owned()
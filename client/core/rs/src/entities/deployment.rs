@@ -22,7 +22,9 @@ use crate::{
 
 use super::{
   TerminationSignal, Version,
-  docker::container::ContainerStateStatusEnum,
+  docker::container::{
+    ContainerStateStatusEnum, RestartPolicyNameEnum,
+  },
   resource::{Resource, ResourceListItem, ResourceQuery},
 };
 
@@ -44,6 +46,10 @@ pub struct DeploymentListItemInfo {
   pub image: String,
   /// Whether there is a newer image available at the same tag.
   pub update_available: bool,
+  /// The differences found between the running container
+  /// and the deployment config, eg after a manual `docker update`.
+  /// Checked on the resource poll interval.
+  pub config_drift: Vec<String>,
   /// The server that deployment sits on.
   pub server_id: String,
   /// An attached Komodo Build, if it exists.
@@ -126,6 +132,16 @@ pub struct DeploymentConfig {
   #[builder(default)]
   pub restart: RestartMode,
 
+  /// How to replace an existing container on redeploy.
+  /// Default is `Recreate`.
+  ///
+  /// `StartFirst` will only minimize downtime when the container
+  /// does not bind any host ports, as the new and old containers
+  /// need to run side by side.
+  #[serde(default)]
+  #[builder(default)]
+  pub deploy_strategy: DeploymentStrategy,
+
   /// This is interpolated at the end of the `docker run` command,
   /// which means they are either passed to the containers inner process,
   /// or replaces the container command, depending on use of ENTRYPOINT or CMD in dockerfile.
@@ -195,6 +211,25 @@ pub struct DeploymentConfig {
   #[builder(default)]
   pub environment: String,
 
+  /// Env files passed to the container with `--env-file`.
+  /// Variables in later files override earlier ones,
+  /// and the variables in `environment` override them all.
+  /// Absolute paths are used directly. Relative paths are taken
+  /// relative to the `linked_repo` folder, and require one to be attached.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub env_files: Vec<String>,
+
+  /// Choose a Komodo Repo (Resource) to source relative `env_files` from.
+  /// The repo is pulled on the Server before each deploy.
+  #[serde(default)]
+  #[builder(default)]
+  pub linked_repo: String,
+
   /// The docker labels given to the container.
   #[serde(default, deserialize_with = "labels_deserializer")]
   #[partial_attr(serde(
@@ -246,9 +281,12 @@ impl Default for DeploymentConfig {
       ports: Default::default(),
       volumes: Default::default(),
       environment: Default::default(),
+      env_files: Default::default(),
+      linked_repo: Default::default(),
       labels: Default::default(),
       network: default_network(),
       restart: Default::default(),
+      deploy_strategy: Default::default(),
       command: Default::default(),
       extra_args: Default::default(),
     }
@@ -416,6 +454,44 @@ pub enum RestartMode {
   UnlessStopped,
 }
 
+impl From<RestartMode> for RestartPolicyNameEnum {
+  fn from(value: RestartMode) -> Self {
+    match value {
+      RestartMode::NoRestart => RestartPolicyNameEnum::No,
+      RestartMode::OnFailure => RestartPolicyNameEnum::OnFailure,
+      RestartMode::Always => RestartPolicyNameEnum::Always,
+      RestartMode::UnlessStopped => {
+        RestartPolicyNameEnum::UnlessStopped
+      }
+    }
+  }
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  PartialEq,
+  Hash,
+  Eq,
+  Clone,
+  Copy,
+  Default,
+  Display,
+  EnumString,
+)]
+pub enum DeploymentStrategy {
+  /// Stop and remove the existing container,
+  /// then run the new container.
+  #[default]
+  Recreate,
+  /// Run the new container alongside the existing one,
+  /// and only stop and remove the existing container
+  /// once the new one is healthy.
+  StartFirst,
+}
+
 #[typeshare]
 #[derive(
   Serialize,
@@ -1184,3 +1184,28 @@ pub struct ContainerStats {
   #[serde(alias = "PIDs")]
   pub pids: String,
 }
+
+/// A [ContainerStats] sample recorded by the Periphery stats polling.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerStatsSample {
+  /// Unix timestamp in milliseconds.
+  pub ts: I64,
+  pub stats: ContainerStats,
+}
+
+/// A significant container event streamed from Periphery,
+/// used to refresh the server status without waiting for the next poll.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerEvent {
+  /// The container name.
+  pub name: String,
+  /// The docker event action, eg `die`, `oom`,
+  /// or `health_status: unhealthy`.
+  pub action: String,
+  /// The exit code reported with `die` events.
+  pub exit_code: Option<String>,
+  /// Unix timestamp in milliseconds.
+  pub ts: I64,
+}
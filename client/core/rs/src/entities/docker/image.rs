@@ -95,6 +95,27 @@ pub struct Image {
 
   #[serde(rename = "Metadata")]
   pub metadata: Option<ImageInspectMetadata>,
+
+  /// The layers which add to the image size, newest first.
+  /// Computed by Komodo from the image history.
+  #[serde(default, rename = "Layers")]
+  pub layers: Vec<ImageLayer>,
+}
+
+/// Size breakdown for a layer of an image.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageLayer {
+  /// The command which created the layer.
+  pub created_by: String,
+  /// Size of the layer in bytes.
+  pub size: I64,
+  /// Percentage of the total image size.
+  pub size_percent: f64,
+  /// Unix timestamp (seconds) when the layer was created.
+  pub created: I64,
 }
 
 /// Information about the image's RootFS, including the layer IDs.
@@ -145,3 +166,50 @@ pub struct ImageHistoryResponseItem {
   #[serde(rename = "Comment")]
   pub comment: String,
 }
+
+/// Vulnerability scan results for an image.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageScan {
+  /// The image which was scanned.
+  pub image: String,
+  /// The scanner used, `trivy` or `grype`.
+  pub scanner: String,
+  /// The number of findings at each severity.
+  pub counts: ImageScanSeverityCounts,
+  /// The most severe findings, most severe first.
+  pub findings: Vec<ImageScanFinding>,
+}
+
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageScanSeverityCounts {
+  pub critical: u32,
+  pub high: u32,
+  pub medium: u32,
+  pub low: u32,
+  pub unknown: u32,
+}
+
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageScanFinding {
+  /// The vulnerability id, eg `CVE-2024-1234`.
+  pub id: String,
+  /// Uppercase severity, eg `CRITICAL`.
+  pub severity: String,
+  /// The affected package.
+  pub package: String,
+  /// The installed package version.
+  pub installed_version: String,
+  /// The version which fixes the vulnerability, if any.
+  pub fixed_version: Option<String>,
+  /// Short description of the vulnerability.
+  pub title: Option<String>,
+}
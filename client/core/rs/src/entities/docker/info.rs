@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::I64;
+
+/// Information about the docker daemon on a server,
+/// from `docker info` and `docker version`.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct DockerInfo {
+  /// The docker engine version, eg `27.3.1`.
+  pub version: Option<String>,
+  /// The docker engine api version, eg `1.47`.
+  pub api_version: Option<String>,
+  /// The host operating system, eg `Ubuntu 24.04.1 LTS`.
+  pub operating_system: Option<String>,
+  /// The host kernel version.
+  pub kernel_version: Option<String>,
+  /// The host architecture, eg `x86_64`.
+  pub architecture: Option<String>,
+  /// The storage driver, eg `overlay2`.
+  pub storage_driver: Option<String>,
+  /// The cgroup version, `1` or `2`.
+  pub cgroup_version: Option<String>,
+  /// The cgroup driver, eg `systemd`.
+  pub cgroup_driver: Option<String>,
+  /// Total memory available to the daemon in bytes.
+  pub total_memory: Option<I64>,
+  /// Number of CPUs available to the daemon.
+  pub cpus: Option<I64>,
+  /// The swarm state of the node, eg `inactive` or `active`.
+  pub swarm_state: Option<String>,
+  /// Total number of containers on the host.
+  pub containers: Option<I64>,
+  /// Number of running containers on the host.
+  pub containers_running: Option<I64>,
+  /// Number of images on the host.
+  pub images: Option<I64>,
+  /// Warnings reported by the daemon.
+  pub warnings: Vec<String>,
+}
+
+/// The oldest docker engine version Komodo supports.
+pub const MINIMUM_DOCKER_VERSION: (u64, u64) = (20, 10);
+
+/// The docker versions on a server,
+/// and the features Komodo can use with them.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct DockerVersion {
+  /// The docker engine version, eg `27.3.1`.
+  pub version: Option<String>,
+  /// The docker compose version, eg `2.29.7`.
+  pub compose_version: Option<String>,
+  /// The docker buildx version, eg `0.17.1`.
+  pub buildx_version: Option<String>,
+  /// Whether the engine is at least [MINIMUM_DOCKER_VERSION].
+  pub supported: bool,
+  pub capabilities: DockerCapabilities,
+}
+
+#[typeshare]
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct DockerCapabilities {
+  /// `docker stop --signal`, added in docker 23.0.
+  pub stop_signal: bool,
+  /// `docker buildx` is installed.
+  pub buildx: bool,
+  /// `docker compose` (v2 plugin) is installed.
+  pub compose_v2: bool,
+}
+
+impl DockerVersion {
+  pub fn new(
+    version: Option<String>,
+    compose_version: Option<String>,
+    buildx_version: Option<String>,
+  ) -> DockerVersion {
+    let engine = version.as_deref().and_then(parse_major_minor);
+    let compose =
+      compose_version.as_deref().and_then(parse_major_minor);
+    DockerVersion {
+      supported: engine
+        .map(|engine| engine >= MINIMUM_DOCKER_VERSION)
+        .unwrap_or_default(),
+      capabilities: DockerCapabilities {
+        stop_signal: engine
+          .map(|engine| engine >= (23, 0))
+          .unwrap_or_default(),
+        buildx: buildx_version.is_some(),
+        compose_v2: compose
+          .map(|(major, _)| major >= 2)
+          .unwrap_or_default(),
+      },
+      version,
+      compose_version,
+      buildx_version,
+    }
+  }
+}
+
+/// Parses the major and minor version out of eg
+/// `27.3.1`, `v2.29.7`, or `20.10.24+dfsg1`.
+fn parse_major_minor(version: &str) -> Option<(u64, u64)> {
+  let mut parts = version.trim().trim_start_matches('v').split('.');
+  let major = parts.next()?.parse().ok()?;
+  let minor = parts
+    .next()
+    .map(|minor| {
+      minor
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+    })
+    .and_then(|minor| minor.parse().ok())
+    .unwrap_or_default();
+  Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_major_minor() {
+    assert_eq!(parse_major_minor("27.3.1"), Some((27, 3)));
+    assert_eq!(parse_major_minor("v2.29.7"), Some((2, 29)));
+    assert_eq!(parse_major_minor("20.10.24+dfsg1"), Some((20, 10)));
+    assert_eq!(parse_major_minor("24"), Some((24, 0)));
+    assert_eq!(parse_major_minor("unknown"), None);
+  }
+
+  #[test]
+  fn capabilities_of_current_docker() {
+    let version = DockerVersion::new(
+      Some(String::from("27.3.1")),
+      Some(String::from("v2.29.7")),
+      Some(String::from("v0.17.1")),
+    );
+    assert!(version.supported);
+    assert_eq!(
+      version.capabilities,
+      DockerCapabilities {
+        stop_signal: true,
+        buildx: true,
+        compose_v2: true,
+      }
+    );
+  }
+
+  #[test]
+  fn capabilities_of_old_docker() {
+    let version = DockerVersion::new(
+      Some(String::from("20.10.24+dfsg1")),
+      Some(String::from("1.29.2")),
+      None,
+    );
+    assert!(version.supported);
+    assert_eq!(version.capabilities, DockerCapabilities::default());
+
+    let version =
+      DockerVersion::new(Some(String::from("19.03.15")), None, None);
+    assert!(!version.supported);
+  }
+
+  #[test]
+  fn unknown_version_is_unsupported() {
+    assert_eq!(
+      DockerVersion::new(None, None, None),
+      DockerVersion::default()
+    );
+  }
+}
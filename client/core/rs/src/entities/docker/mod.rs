@@ -7,6 +7,7 @@ use super::I64;
 
 pub mod container;
 pub mod image;
+pub mod info;
 pub mod network;
 pub mod stats;
 pub mod volume;
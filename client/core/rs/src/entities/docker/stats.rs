@@ -62,6 +62,15 @@ pub struct FullContainerStats {
   #[serde(rename = "networks")]
   #[serde(skip_serializing_if = "Option::is_none")]
   pub networks: Option<HashMap<String, ContainerNetworkStats>>,
+
+  /// The CPU usage between `precpu_stats` and `cpu_stats`,
+  /// as a percentage of one CPU, matching `docker stats`.
+  /// Exceeds 100 when using multiple CPUs.
+  pub cpu_perc: Option<f64>,
+
+  /// The memory usage excluding the page cache,
+  /// as a percentage of the limit, matching `docker stats`.
+  pub mem_perc: Option<f64>,
 }
 
 /// PidsStats contains Linux-specific stats of a container's process-IDs (PIDs).  This type is Linux-specific and omitted for Windows containers.
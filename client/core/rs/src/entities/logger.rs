@@ -1,6 +1,7 @@
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LogConfig {
@@ -25,6 +26,18 @@ pub struct LogConfig {
   #[serde(default)]
   pub otlp_endpoint: String,
 
+  /// Export metrics to a different endpoint than `otlp_endpoint`.
+  /// If empty, metrics are exported to `otlp_endpoint`,
+  /// replacing a trailing `/v1/traces` with `/v1/metrics`.
+  #[serde(default)]
+  pub otlp_metrics_endpoint: String,
+
+  /// Export span attributes which are unique to nearly every span,
+  /// like `update_id` and `user_id`. Some tracing backends
+  /// are slow to index these. default: true
+  #[serde(default = "default_otlp_high_cardinality_attributes")]
+  pub otlp_high_cardinality_attributes: bool,
+
   #[serde(default = "default_opentelemetry_service_name")]
   pub opentelemetry_service_name: String,
 }
@@ -37,6 +50,10 @@ fn default_location() -> bool {
   true
 }
 
+fn default_otlp_high_cardinality_attributes() -> bool {
+  true
+}
+
 impl Default for LogConfig {
   fn default() -> Self {
     Self {
@@ -45,6 +62,9 @@ impl Default for LogConfig {
       pretty: Default::default(),
       location: default_location(),
       otlp_endpoint: Default::default(),
+      otlp_metrics_endpoint: Default::default(),
+      otlp_high_cardinality_attributes:
+        default_otlp_high_cardinality_attributes(),
       opentelemetry_service_name: default_opentelemetry_service_name(
       ),
     }
@@ -62,6 +82,8 @@ impl LogConfig {
   }
 }
 
+/// Severity of a log, also used to configure the logging level.
+#[typeshare]
 #[derive(
   Debug,
   Clone,
@@ -13,7 +13,7 @@ use serde::{
   de::{Visitor, value::MapAccessDeserializer},
 };
 use serror::Serror;
-use strum::{AsRefStr, Display, EnumString};
+use strum::{AsRefStr, Display, EnumString, VariantNames};
 use typeshare::typeshare;
 
 use crate::{
@@ -29,10 +29,14 @@ pub mod alert;
 pub mod alerter;
 /// Subtypes of [ApiKey][api_key::ApiKey].
 pub mod api_key;
+/// Subtypes of [AuditLog][audit::AuditLog].
+pub mod audit;
 /// Subtypes of [Build][build::Build].
 pub mod build;
 /// Subtypes of [Builder][builder::Builder].
 pub mod builder;
+/// Subtypes of [ChangeFreeze][change_freeze::ChangeFreeze].
+pub mod change_freeze;
 /// [core config][config::core] and [periphery config][config::periphery]
 pub mod config;
 /// Subtypes of [Deployment][deployment::Deployment].
@@ -103,6 +107,7 @@ pub type _Serror = Serror;
   Parser,
   EmptyTraits,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NoData {}
 
 pub trait MergePartial: Sized {
@@ -590,6 +595,32 @@ pub struct RepoExecutionResponse {
   pub commit_message: Option<String>,
 }
 
+/// How to handle the local repo having diverged from the remote branch
+/// before committing to it.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Default,
+  Serialize,
+  Deserialize,
+  Display,
+  EnumString,
+)]
+pub enum GitSyncStrategy {
+  /// Fail without writing.
+  #[default]
+  Fail,
+  /// Rebase the local commits onto the remote branch.
+  Rebase,
+  /// Reset the local repo to the remote branch,
+  /// discarding the local commits.
+  Force,
+}
+
 #[typeshare]
 #[derive(
   Debug,
@@ -1003,6 +1034,7 @@ pub enum IanaTimezone {
   Display,
   EnumString,
   AsRefStr,
+  VariantNames,
 )]
 pub enum Operation {
   // do nothing
@@ -1070,6 +1102,7 @@ pub enum Operation {
   RenameDeployment,
   DeleteDeployment,
   Deploy,
+  ReconcileDeployment,
   PullDeployment,
   StartDeployment,
   RestartDeployment,
@@ -1139,6 +1172,10 @@ pub enum Operation {
   BackupCoreDatabase,
   GlobalAutoUpdate,
 
+  // change freeze
+  StartChangeFreeze,
+  EndChangeFreeze,
+
   // variable
   CreateVariable,
   UpdateVariableValue,
@@ -1191,6 +1228,7 @@ pub enum SearchCombinator {
 )]
 #[serde(rename_all = "UPPERCASE")]
 #[strum(serialize_all = "UPPERCASE")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum TerminationSignal {
   #[serde(alias = "1")]
   SigHup,
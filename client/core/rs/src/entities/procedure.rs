@@ -180,7 +180,8 @@ impl Default for ProcedureConfig {
   }
 }
 
-/// A single stage of a procedure. Runs a list of executions in parallel.
+/// A single stage of a procedure. Runs a list of executions in parallel,
+/// optionally limited by `max_concurrency`.
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcedureStage {
@@ -189,11 +190,57 @@ pub struct ProcedureStage {
   /// Whether the stage should be run as part of the procedure.
   #[serde(default = "default_enabled")]
   pub enabled: bool,
-  /// The executions in the stage
+  /// Run the stage depending on the result of the previous stages.
+  /// Default is `Success`.
+  #[serde(default)]
+  pub condition: StageCondition,
+  /// The name of an earlier stage for the condition to check.
+  /// If empty, the condition checks all previous stages.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub condition_stage: String,
+  /// The maximum number of stage executions to run at once.
+  /// The stage finishes once all executions finish.
+  /// 0 means no limit, all executions run at once.
+  #[serde(default)]
+  pub max_concurrency: I64,
+  /// The executions in the stage.
+  ///
+  /// Execution parameters can reference the results of previous stages using
+  /// `{{<stage name>.version}}`, `{{<stage name>.commit_hash}}`, or `{{<stage name>.update_id}}`.
+  /// Output set by an Action with `setOutput` is available at `{{<stage name>.output}}`,
+  /// with top level fields of object outputs at `{{<stage name>.output.<field>}}`.
   #[serde(default, alias = "execution")]
   pub executions: Vec<EnabledExecution>,
 }
 
+/// Determines whether a stage runs based on the
+/// results of the previous stages, or of the stage
+/// named in `condition_stage`.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  Display,
+)]
+pub enum StageCondition {
+  /// Run only if all previous stages succeeded,
+  /// or the named stage succeeded.
+  #[default]
+  Success,
+  /// Run only if a previous stage failed,
+  /// or the named stage failed.
+  /// Use for error handling, eg. sending an alert.
+  Failure,
+  /// Run regardless of the previous stage results.
+  Always,
+}
+
 /// Allows to enable / disabled procedures in the sequence / parallel vec on the fly
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -44,6 +44,13 @@ pub struct Resource<Config: Default, Info: Default = ()> {
   #[builder(default)]
   pub template: bool,
 
+  /// Whether the resource is locked.
+  /// Locked resources reject all executions,
+  /// including scheduled and automated ones, until an admin unlocks them.
+  #[serde(default)]
+  #[builder(default)]
+  pub locked: bool,
+
   /// Tag Ids
   #[serde(default, deserialize_with = "string_list_deserializer")]
   #[builder(default)]
@@ -78,6 +85,7 @@ impl<C: Default, I: Default> Default for Resource<C, I> {
       name: String::from("temp-resource"),
       description: String::new(),
       template: Default::default(),
+      locked: Default::default(),
       tags: Vec::new(),
       info: I::default(),
       config: C::default(),
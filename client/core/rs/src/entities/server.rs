@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+};
 
 use derive_builder::Builder;
 use partial_derive2::Partial;
@@ -106,6 +109,14 @@ pub struct ServerConfig {
   #[builder(default)]
   pub passkey: String,
 
+  /// Whether to request gzip compressed responses from the server.
+  /// Useful when the server is reached over a slow (WAN) link,
+  /// but costs CPU on both ends, so is best left off on a LAN.
+  /// default: false
+  #[serde(default)]
+  #[builder(default)]
+  pub compression: bool,
+
   /// Sometimes the system stats reports a mount path that is not desired.
   /// Use this field to filter it out from the report.
   #[serde(default, deserialize_with = "string_list_deserializer")]
@@ -145,6 +156,15 @@ pub struct ServerConfig {
   #[partial_default(default_send_alerts())]
   pub send_unreachable_alerts: bool,
 
+  /// The number of consecutive polls the server must be unreachable
+  /// before an unreachable alert is opened. The server state still
+  /// shows the failure immediately.
+  /// default: 2
+  #[serde(default = "default_unreachable_alert_polls")]
+  #[builder(default = "default_unreachable_alert_polls()")]
+  #[partial_default(default_unreachable_alert_polls())]
+  pub unreachable_alert_polls: u32,
+
   /// Whether to send alerts about the servers CPU status
   #[serde(default = "default_send_alerts")]
   #[builder(default = "default_send_alerts()")]
@@ -169,6 +189,13 @@ pub struct ServerConfig {
   #[partial_default(default_send_alerts())]
   pub send_version_mismatch_alerts: bool,
 
+  /// Whether to send alerts when CPU or MEM usage
+  /// spikes far above its recent baseline.
+  /// default: false
+  #[serde(default)]
+  #[builder(default)]
+  pub send_anomaly_alerts: bool,
+
   /// The percentage threshhold which triggers WARNING state for CPU.
   #[serde(default = "default_cpu_warning")]
   #[builder(default = "default_cpu_warning()")]
@@ -205,16 +232,65 @@ pub struct ServerConfig {
   #[partial_default(default_disk_critical())]
   pub disk_critical: f64,
 
+  /// Override the DISK thresholds for specific mounts,
+  /// eg to let a data mount run hotter than `/`.
+  /// Mounts not listed use `disk_warning` and `disk_critical`.
+  #[serde(default)]
+  #[builder(default)]
+  pub disk_thresholds: Vec<DiskThreshold>,
+
   /// Scheduled maintenance windows during which alerts will be suppressed.
   #[serde(default)]
   #[builder(default)]
   pub maintenance_windows: Vec<MaintenanceWindow>,
+
+  /// Put the server in maintenance mode for planned work, like a reboot.
+  /// Alerts for the server and its Deployments / Stacks are suppressed,
+  /// while its status is still recorded.
+  #[serde(default)]
+  #[builder(default)]
+  pub maintenance_mode: bool,
+
+  /// Unix timestamp in milliseconds when maintenance mode expires,
+  /// or 0 to stay in maintenance mode until it is turned off.
+  #[serde(default)]
+  #[builder(default)]
+  pub maintenance_mode_until: I64,
 }
 
 impl ServerConfig {
   pub fn builder() -> ServerConfigBuilder {
     ServerConfigBuilder::default()
   }
+
+  /// Whether maintenance mode is on and not yet expired at the timestamp.
+  pub fn maintenance_mode_active(&self, timestamp: I64) -> bool {
+    self.maintenance_mode
+      && (self.maintenance_mode_until == 0
+        || timestamp < self.maintenance_mode_until)
+  }
+
+  /// The (warning, critical) DISK thresholds for the mount.
+  pub fn disk_thresholds(&self, mount: &Path) -> (f64, f64) {
+    self
+      .disk_thresholds
+      .iter()
+      .find(|threshold| Path::new(&threshold.mount) == mount)
+      .map(|threshold| (threshold.warning, threshold.critical))
+      .unwrap_or((self.disk_warning, self.disk_critical))
+  }
+}
+
+/// DISK alert thresholds for a specific mount.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiskThreshold {
+  /// The mount path, eg `/mnt/data`.
+  pub mount: String,
+  /// The percentage threshhold which triggers WARNING state.
+  pub warning: f64,
+  /// The percentage threshhold which triggers CRITICAL state.
+  pub critical: f64,
 }
 
 fn default_address() -> String {
@@ -241,6 +317,10 @@ fn default_send_alerts() -> bool {
   true
 }
 
+fn default_unreachable_alert_polls() -> u32 {
+  2
+}
+
 fn default_cpu_warning() -> f32 {
   90.0
 }
@@ -272,15 +352,18 @@ impl Default for ServerConfig {
       external_address: Default::default(),
       enabled: default_enabled(),
       timeout_seconds: default_timeout_seconds(),
+      compression: Default::default(),
       ignore_mounts: Default::default(),
       stats_monitoring: default_stats_monitoring(),
       auto_prune: default_auto_prune(),
       links: Default::default(),
       send_unreachable_alerts: default_send_alerts(),
+      unreachable_alert_polls: default_unreachable_alert_polls(),
       send_cpu_alerts: default_send_alerts(),
       send_mem_alerts: default_send_alerts(),
       send_disk_alerts: default_send_alerts(),
       send_version_mismatch_alerts: default_send_alerts(),
+      send_anomaly_alerts: Default::default(),
       region: Default::default(),
       passkey: Default::default(),
       cpu_warning: default_cpu_warning(),
@@ -289,7 +372,10 @@ impl Default for ServerConfig {
       mem_critical: default_mem_critical(),
       disk_warning: default_disk_warning(),
       disk_critical: default_disk_critical(),
+      disk_thresholds: Default::default(),
       maintenance_windows: Default::default(),
+      maintenance_mode: Default::default(),
+      maintenance_mode_until: Default::default(),
     }
   }
 }
@@ -390,3 +476,65 @@ pub type ServerQuery = ResourceQuery<ServerQuerySpecifics>;
 pub struct ServerQuerySpecifics {}
 
 impl AddFilters for ServerQuerySpecifics {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn config() -> ServerConfig {
+    ServerConfig {
+      disk_warning: 75.0,
+      disk_critical: 95.0,
+      disk_thresholds: vec![DiskThreshold {
+        mount: String::from("/mnt/data/"),
+        warning: 90.0,
+        critical: 98.0,
+      }],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn disk_thresholds_use_mount_override() {
+    let config = config();
+    assert_eq!(
+      config.disk_thresholds(Path::new("/mnt/data")),
+      (90.0, 98.0)
+    );
+  }
+
+  #[test]
+  fn disk_thresholds_fall_back_to_defaults() {
+    let config = config();
+    assert_eq!(config.disk_thresholds(Path::new("/")), (75.0, 95.0));
+    assert_eq!(
+      config.disk_thresholds(Path::new("/mnt/data/sub")),
+      (75.0, 95.0)
+    );
+  }
+
+  #[test]
+  fn maintenance_mode_active_until_expiry() {
+    let config = ServerConfig {
+      maintenance_mode: true,
+      maintenance_mode_until: 1_000,
+      ..Default::default()
+    };
+    assert!(config.maintenance_mode_active(999));
+    assert!(!config.maintenance_mode_active(1_000));
+  }
+
+  #[test]
+  fn maintenance_mode_without_expiry() {
+    let config = ServerConfig {
+      maintenance_mode: true,
+      ..Default::default()
+    };
+    assert!(config.maintenance_mode_active(i64::MAX));
+    let config = ServerConfig {
+      maintenance_mode_until: 1_000,
+      ..Default::default()
+    };
+    assert!(!config.maintenance_mode_active(0));
+  }
+}
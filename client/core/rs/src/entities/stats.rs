@@ -69,6 +69,38 @@ pub struct SystemStatsRecord {
   // pub network_usage_interface: Vec<SingleNetworkInterfaceUsage>, // interface -> (ingress, egress)
 }
 
+/// System stats averaged over an hour or a day.
+/// These are kept longer than the raw [SystemStatsRecord]s
+/// to show long term trends.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SystemStatsRollup {
+  /// Unix timestamp in milliseconds of the start of the hour / day
+  pub ts: I64,
+  /// Server id
+  pub sid: String,
+  /// The number of records which were averaged
+  pub count: I64,
+  /// Average cpu usage percentage
+  pub cpu_perc: f64,
+  /// Maximum cpu usage percentage
+  pub cpu_perc_max: f64,
+  /// Average memory used in GB
+  pub mem_used_gb: f64,
+  /// Maximum memory used in GB
+  pub mem_used_gb_max: f64,
+  /// Average total memory in GB
+  pub mem_total_gb: f64,
+  /// Average disk used in GB
+  pub disk_used_gb: f64,
+  /// Average total disk size in GB
+  pub disk_total_gb: f64,
+  /// Average network ingress in bytes
+  pub network_ingress_bytes: f64,
+  /// Average network egress in bytes
+  pub network_egress_bytes: f64,
+}
+
 /// Realtime system stats data.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -7,7 +7,7 @@ use crate::entities::{
   I64, MongoId, Operation, all_logs_success, komodo_timestamp,
 };
 
-use super::{ResourceTarget, Version};
+use super::{ResourceTarget, Version, logger::LogLevel};
 
 /// Represents an action performed by Komodo.
 #[typeshare]
@@ -78,6 +78,24 @@ pub struct Update {
   /// If the update is for resource config update, give the current (at time of Update) toml contents
   #[serde(default, skip_serializing_if = "String::is_empty")]
   pub current_toml: String,
+  /// Progress through the stages of a long running operation.
+  /// Only reported by multi stage operations while in progress.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub progress: Option<UpdateProgress>,
+}
+
+/// Progress through the stages of a long running operation.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+pub struct UpdateProgress {
+  /// The current stage, starting from 1.
+  pub current: u32,
+  /// The total number of stages.
+  pub total: u32,
+  /// The name of the current stage.
+  pub stage: String,
 }
 
 impl Update {
@@ -101,11 +119,69 @@ impl Update {
     self.status = UpdateStatus::InProgress;
   }
 
+  pub fn set_progress(
+    &mut self,
+    current: u32,
+    total: u32,
+    stage: impl Into<String>,
+  ) {
+    self.progress = Some(UpdateProgress {
+      current,
+      total,
+      stage: stage.into(),
+    });
+  }
+
   pub fn finalize(&mut self) {
     self.success = all_logs_success(&self.logs);
     self.end_ts = Some(komodo_timestamp());
     self.status = UpdateStatus::Complete;
+    self.progress = None;
   }
+
+  /// Truncates log output until the total log bytes are within `max_bytes`.
+  /// Successful logs are truncated before failed ones, oldest first.
+  /// The end of each output is kept, after a `[truncated N bytes]` marker.
+  pub fn truncate_logs(&mut self, max_bytes: usize) {
+    let total = self.logs.iter().map(Log::size).sum::<usize>();
+    if total <= max_bytes {
+      return;
+    }
+    let mut excess = total - max_bytes;
+    let (failed, successful): (Vec<_>, Vec<_>) =
+      self.logs.iter_mut().partition(|log| !log.success);
+    for log in successful.into_iter().chain(failed) {
+      for output in [&mut log.stdout, &mut log.stderr] {
+        if excess == 0 {
+          return;
+        }
+        excess =
+          excess.saturating_sub(truncate_output(output, excess));
+      }
+    }
+  }
+}
+
+/// Removes about `bytes` from the start of the output,
+/// replacing them with a marker. Returns the bytes actually freed.
+fn truncate_output(output: &mut String, bytes: usize) -> usize {
+  if output.is_empty() {
+    return 0;
+  }
+  // Account for the marker added in place of the removed bytes.
+  let marker_len =
+    format!("[truncated {} bytes]\n", output.len()).len();
+  let mut cut = (bytes + marker_len).min(output.len());
+  while !output.is_char_boundary(cut) {
+    cut += 1;
+  }
+  let marker = format!("[truncated {cut} bytes]\n");
+  if marker.len() >= cut {
+    return 0;
+  }
+  let freed = cut - marker.len();
+  output.replace_range(..cut, &marker);
+  freed
 }
 
 /// Minimal representation of an action performed by Komodo.
@@ -142,11 +218,15 @@ pub struct UpdateListItem {
   /// Some unstructured, operation specific data. Not for general usage.
   #[serde(default, skip_serializing_if = "String::is_empty")]
   pub other_data: String,
+  /// Progress through the stages of a long running operation.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub progress: Option<UpdateProgress>,
 }
 
 /// Represents the output of some command being run
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(from = "LogWithOptionalLevel")]
 pub struct Log {
   /// A label for the log
   pub stage: String,
@@ -162,6 +242,44 @@ pub struct Log {
   pub start_ts: I64,
   /// The end time of the command execution
   pub end_ts: I64,
+  /// The severity of the log.
+  /// Inferred from the output unless set explicitly:
+  /// - `error` if unsuccessful
+  /// - `warn` if successful with output in stderr
+  /// - `info` otherwise
+  ///
+  /// Logs stored before the level was added have it inferred.
+  pub level: LogLevel,
+}
+
+/// Deserializes logs which may not have a level.
+#[derive(Deserialize)]
+struct LogWithOptionalLevel {
+  stage: String,
+  command: String,
+  stdout: String,
+  stderr: String,
+  success: bool,
+  start_ts: I64,
+  end_ts: I64,
+  level: Option<LogLevel>,
+}
+
+impl From<LogWithOptionalLevel> for Log {
+  fn from(log: LogWithOptionalLevel) -> Log {
+    Log {
+      level: log.level.unwrap_or_else(|| {
+        Log::infer_level(log.success, &log.stderr)
+      }),
+      stage: log.stage,
+      command: log.command,
+      stdout: log.stdout,
+      stderr: log.stderr,
+      success: log.success,
+      start_ts: log.start_ts,
+      end_ts: log.end_ts,
+    }
+  }
 }
 
 impl Log {
@@ -173,10 +291,16 @@ impl Log {
       success: true,
       start_ts: ts,
       end_ts: ts,
+      level: LogLevel::Info,
       ..Default::default()
     }
   }
 
+  /// A successful log which should stand out to the user.
+  pub fn warn(stage: &str, msg: String) -> Log {
+    Log::simple(stage, msg).with_level(LogLevel::Warn)
+  }
+
   pub fn error(stage: &str, msg: String) -> Log {
     let ts = unix_timestamp_ms() as i64;
     Log {
@@ -185,10 +309,33 @@ impl Log {
       start_ts: ts,
       end_ts: ts,
       success: false,
+      level: LogLevel::Error,
       ..Default::default()
     }
   }
 
+  /// Explicitly set the level, overriding the inferred one.
+  pub fn with_level(mut self, level: LogLevel) -> Log {
+    self.level = level;
+    self
+  }
+
+  /// Infers the level from the log success and output.
+  pub fn infer_level(success: bool, stderr: &str) -> LogLevel {
+    if !success {
+      LogLevel::Error
+    } else if !stderr.trim().is_empty() {
+      LogLevel::Warn
+    } else {
+      LogLevel::Info
+    }
+  }
+
+  /// Bytes used by the command and its output.
+  pub fn size(&self) -> usize {
+    self.command.len() + self.stdout.len() + self.stderr.len()
+  }
+
   /// Combines stdout / stderr into one log
   pub fn combined(&self) -> String {
     match (self.stdout.is_empty(), self.stderr.is_empty()) {
@@ -226,3 +373,187 @@ pub enum UpdateStatus {
   #[default]
   Complete,
 }
+
+/// An execution persisted to the execution queue,
+/// so it isn't silently lost if Core restarts before it finishes.
+/// Removed once the execution finishes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", collection_name(ExecutionQueue))]
+pub struct QueuedExecution {
+  /// The Mongo ID of the queued execution.
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the Update tracking the execution.
+  #[cfg_attr(feature = "mongo", unique_index)]
+  pub update_id: String,
+
+  /// The id of the user who requested the execution.
+  pub user_id: String,
+
+  /// The execute request, serialized to JSON.
+  pub request: String,
+
+  /// Whether the execution has been picked up.
+  #[cfg_attr(feature = "mongo", index)]
+  pub status: QueuedExecutionStatus,
+
+  /// The time the execution was queued.
+  pub queued_ts: I64,
+}
+
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Display,
+  EnumString,
+  PartialEq,
+  Eq,
+  Clone,
+  Copy,
+  Default,
+)]
+pub enum QueuedExecutionStatus {
+  /// The execution hasn't started yet.
+  /// It is picked up again if Core restarts.
+  #[default]
+  Queued,
+  /// The execution is running.
+  /// It is marked interrupted if Core restarts.
+  Running,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn infers_level_from_output() {
+    assert_eq!(Log::infer_level(false, ""), LogLevel::Error);
+    assert_eq!(Log::infer_level(false, "failed"), LogLevel::Error);
+    assert_eq!(Log::infer_level(true, "warning"), LogLevel::Warn);
+    assert_eq!(Log::infer_level(true, " \n"), LogLevel::Info);
+    assert_eq!(Log::infer_level(true, ""), LogLevel::Info);
+  }
+
+  fn stored_log(success: bool, stderr: &str) -> serde_json::Value {
+    serde_json::json!({
+      "stage": "Deploy",
+      "command": "docker compose up -d",
+      "stdout": "",
+      "stderr": stderr,
+      "success": success,
+      "start_ts": 0,
+      "end_ts": 0,
+    })
+  }
+
+  #[test]
+  fn infers_level_of_stored_logs() {
+    let log: Log =
+      serde_json::from_value(stored_log(false, "")).unwrap();
+    assert_eq!(log.level, LogLevel::Error);
+    let log: Log =
+      serde_json::from_value(stored_log(true, "warning")).unwrap();
+    assert_eq!(log.level, LogLevel::Warn);
+    let log: Log =
+      serde_json::from_value(stored_log(true, "")).unwrap();
+    assert_eq!(log.level, LogLevel::Info);
+  }
+
+  #[test]
+  fn keeps_explicit_level() {
+    let mut log = stored_log(true, "");
+    log["level"] = serde_json::json!("warn");
+    let log: Log = serde_json::from_value(log).unwrap();
+    assert_eq!(log.level, LogLevel::Warn);
+  }
+
+  fn total_size(update: &Update) -> usize {
+    update.logs.iter().map(Log::size).sum()
+  }
+
+  #[test]
+  fn keeps_logs_within_limit() {
+    let mut update = Update {
+      logs: vec![Log::simple("Build", "a".repeat(100))],
+      ..Default::default()
+    };
+    update.truncate_logs(100);
+    assert_eq!(update.logs[0].stdout, "a".repeat(100));
+  }
+
+  #[test]
+  fn truncates_successful_logs_first() {
+    let mut update = Update {
+      logs: vec![
+        Log::error("Pull", "e".repeat(500)),
+        Log::simple("Build", format!("{}end", "a".repeat(997))),
+      ],
+      ..Default::default()
+    };
+    update.truncate_logs(1000);
+    assert!(total_size(&update) <= 1000);
+    assert_eq!(update.logs[0].stderr, "e".repeat(500));
+    let stdout = &update.logs[1].stdout;
+    assert!(stdout.starts_with("[truncated "));
+    assert!(stdout.ends_with("aend"));
+  }
+
+  #[test]
+  fn truncates_failed_logs_when_needed() {
+    let mut update = Update {
+      logs: vec![
+        Log::error("Pull", "e".repeat(500)),
+        Log::simple("Build", "a".repeat(500)),
+      ],
+      ..Default::default()
+    };
+    update.truncate_logs(200);
+    assert!(total_size(&update) <= 200);
+    assert!(update.logs[0].stderr.starts_with("[truncated "));
+    assert!(update.logs[1].stdout.starts_with("[truncated "));
+  }
+
+  #[test]
+  fn truncates_on_char_boundary() {
+    let mut update = Update {
+      logs: vec![Log::simple("Build", "🦎".repeat(100))],
+      ..Default::default()
+    };
+    update.truncate_logs(201);
+    assert!(total_size(&update) <= 201);
+    assert!(update.logs[0].stdout.ends_with('🦎'));
+  }
+
+  #[test]
+  fn finalize_clears_progress() {
+    let mut update = Update::default();
+    update.set_progress(2, 3, "Clone Repo");
+    assert_eq!(
+      update.progress,
+      Some(UpdateProgress {
+        current: 2,
+        total: 3,
+        stage: String::from("Clone Repo"),
+      })
+    );
+    let json = serde_json::to_value(&update).unwrap();
+    assert_eq!(json["progress"]["stage"], "Clone Repo");
+
+    update.finalize();
+    assert_eq!(update.progress, None);
+    let json = serde_json::to_value(&update).unwrap();
+    assert!(json.get("progress").is_none());
+  }
+}
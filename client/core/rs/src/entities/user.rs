@@ -74,6 +74,26 @@ pub struct User {
 
   #[serde(default)]
   pub updated_at: I64,
+
+  /// JWTs issued before this time are rejected.
+  /// Set to the current time by
+  /// [LogoutEverywhere][crate::api::user::LogoutEverywhere].
+  #[serde(default)]
+  pub tokens_valid_after: I64,
+
+  /// The number of consecutive failed local logins
+  /// within the lockout window.
+  #[serde(default)]
+  pub failed_logins: I64,
+
+  /// The time of the last failed local login.
+  #[serde(default)]
+  pub last_failed_login_at: I64,
+
+  /// Local logins are rejected until this time,
+  /// after too many failed attempts.
+  #[serde(default)]
+  pub locked_until: I64,
 }
 
 impl User {
@@ -298,3 +318,103 @@ impl Default for UserConfig {
     }
   }
 }
+
+/// A record of a login attempt, so users can review their account activity.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct LoginEvent {
+  /// The Mongo ID of the login event.
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// Unix timestamp in milliseconds of the login attempt.
+  #[cfg_attr(feature = "mongo", index)]
+  pub ts: I64,
+
+  /// The id of the user logging in.
+  /// Empty if the attempt couldn't be matched to a user.
+  #[cfg_attr(feature = "mongo", index)]
+  pub user_id: String,
+
+  /// The username of the user logging in, if known.
+  pub username: String,
+
+  /// How the user logged in.
+  pub method: LoginMethod,
+
+  /// The client ip address.
+  pub ip: Option<String>,
+
+  /// The client user agent.
+  pub user_agent: Option<String>,
+
+  /// Whether the login succeeded.
+  #[cfg_attr(feature = "mongo", index)]
+  pub success: bool,
+
+  /// The reason the login failed.
+  pub error: Option<String>,
+}
+
+#[typeshare]
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+pub enum LoginMethod {
+  #[default]
+  Local,
+  Github,
+  Google,
+  Oidc,
+  /// Successful api key logins are recorded at most hourly per key.
+  ApiKey,
+}
+
+/// The provider session of an OIDC login. Core uses it to
+/// check the session is still active at the provider
+/// when the jwt is refreshed. Not exposed in the api.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct OidcSession {
+  /// The Mongo ID of the session,
+  /// held in the `session` claim of the jwts issued for it.
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the user who logged in.
+  #[cfg_attr(feature = "mongo", index)]
+  pub user_id: String,
+
+  /// The name of the provider the user logged in with.
+  pub provider: String,
+
+  /// The provider session id (`sid`),
+  /// matched against backchannel logouts.
+  #[cfg_attr(feature = "mongo", index)]
+  pub sid: Option<String>,
+
+  /// The latest provider refresh token.
+  pub refresh_token: Option<String>,
+
+  /// Unix timestamp in milliseconds the session expires,
+  /// when the last jwt issued for it expires.
+  #[cfg_attr(feature = "mongo", index)]
+  pub expires_at: I64,
+}
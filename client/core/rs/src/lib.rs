@@ -43,6 +43,9 @@ pub mod terminal;
 pub mod ws;
 
 mod request;
+mod retry;
+
+pub use retry::RetryPolicy;
 
 /// &'static KomodoClient initialized from environment.
 pub fn komodo_client() -> &'static KomodoClient {
@@ -75,6 +78,7 @@ pub struct KomodoClient {
   address: String,
   key: String,
   secret: String,
+  retry: Option<RetryPolicy>,
 }
 
 impl KomodoClient {
@@ -89,6 +93,7 @@ impl KomodoClient {
       address: address.into(),
       key: key.into(),
       secret: secret.into(),
+      retry: None,
     }
   }
 
@@ -172,6 +177,12 @@ impl KomodoClient {
     self
   }
 
+  /// Retry requests which fail transiently. See [RetryPolicy].
+  pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = Some(retry);
+    self
+  }
+
   /// Poll an [Update][entities::update::Update] (returned by the `execute` calls) until the
   /// [UpdateStatus][entities::update::UpdateStatus] is `Complete`, and then return it.
   #[cfg(not(feature = "blocking"))]
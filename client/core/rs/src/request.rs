@@ -6,8 +6,11 @@ use serror::deserialize_error;
 use crate::{
   KomodoClient,
   api::{
-    auth::KomodoAuthRequest, execute::KomodoExecuteRequest,
-    read::KomodoReadRequest, user::KomodoUserRequest,
+    API_VERSION_HEADER,
+    auth::KomodoAuthRequest,
+    execute::{IDEMPOTENCY_KEY_HEADER, KomodoExecuteRequest},
+    read::KomodoReadRequest,
+    user::KomodoUserRequest,
     write::KomodoWriteRequest,
   },
 };
@@ -197,15 +200,44 @@ impl KomodoClient {
     endpoint: &str,
     body: B,
   ) -> anyhow::Result<R> {
-    let req = self
-      .reqwest
-      .post(format!("{}{endpoint}", self.address))
-      .header("x-api-key", &self.key)
-      .header("x-api-secret", &self.secret)
-      .header("content-type", "application/json")
-      .json(&body);
-    let res =
-      req.send().await.context("failed to reach Komodo API")?;
+    let idempotency_key = self.idempotency_key(endpoint);
+    let mut attempt = 1;
+    let res = loop {
+      let mut req = self
+        .reqwest
+        .post(format!("{}{endpoint}", self.address))
+        .header("x-api-key", &self.key)
+        .header("x-api-secret", &self.secret)
+        .header(API_VERSION_HEADER, env!("CARGO_PKG_VERSION"))
+        .header("content-type", "application/json")
+        .json(&body);
+      if let Some(key) = &idempotency_key {
+        req = req.header(IDEMPOTENCY_KEY_HEADER, key);
+      }
+      let retry = match req.send().await {
+        Ok(res) => match self.retry.as_ref().and_then(|retry| {
+          retry.retry_status(attempt, res.status(), res.headers())
+        }) {
+          Some(delay) => delay,
+          None => break res,
+        },
+        Err(e) => match self
+          .retry
+          .as_ref()
+          .and_then(|retry| retry.retry_error(attempt, &e))
+        {
+          Some(delay) => delay,
+          None => {
+            return Err(
+              anyhow::Error::from(e)
+                .context("failed to reach Komodo API"),
+            );
+          }
+        },
+      };
+      tokio::time::sleep(retry).await;
+      attempt += 1;
+    };
     let status = res.status();
     if status.is_success() {
       match res.json().await {
@@ -226,14 +258,44 @@ impl KomodoClient {
     endpoint: &str,
     body: B,
   ) -> anyhow::Result<R> {
-    let req = self
-      .reqwest
-      .post(format!("{}{endpoint}", self.address))
-      .header("x-api-key", &self.key)
-      .header("x-api-secret", &self.secret)
-      .header("content-type", "application/json")
-      .json(&body);
-    let res = req.send().context("failed to reach Komodo API")?;
+    let idempotency_key = self.idempotency_key(endpoint);
+    let mut attempt = 1;
+    let res = loop {
+      let mut req = self
+        .reqwest
+        .post(format!("{}{endpoint}", self.address))
+        .header("x-api-key", &self.key)
+        .header("x-api-secret", &self.secret)
+        .header(API_VERSION_HEADER, env!("CARGO_PKG_VERSION"))
+        .header("content-type", "application/json")
+        .json(&body);
+      if let Some(key) = &idempotency_key {
+        req = req.header(IDEMPOTENCY_KEY_HEADER, key);
+      }
+      let retry = match req.send() {
+        Ok(res) => match self.retry.as_ref().and_then(|retry| {
+          retry.retry_status(attempt, res.status(), res.headers())
+        }) {
+          Some(delay) => delay,
+          None => break res,
+        },
+        Err(e) => match self
+          .retry
+          .as_ref()
+          .and_then(|retry| retry.retry_error(attempt, &e))
+        {
+          Some(delay) => delay,
+          None => {
+            return Err(
+              anyhow::Error::from(e)
+                .context("failed to reach Komodo API"),
+            );
+          }
+        },
+      };
+      std::thread::sleep(retry);
+      attempt += 1;
+    };
     let status = res.status();
     if status.is_success() {
       match res.json() {
@@ -247,4 +309,10 @@ impl KomodoClient {
       }
     }
   }
+
+  /// Retried executions share a key, so Core only starts one.
+  fn idempotency_key(&self, endpoint: &str) -> Option<String> {
+    (self.retry.is_some() && endpoint == "/execute")
+      .then(|| uuid::Uuid::new_v4().to_string())
+  }
 }
@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use reqwest::{StatusCode, header::RETRY_AFTER};
+
+/// Retries [KomodoClient][crate::KomodoClient] requests
+/// which fail transiently, with exponential backoff.
+///
+/// Retried `/execute` requests are sent with an
+/// [IDEMPOTENCY_KEY_HEADER][crate::api::execute::IDEMPOTENCY_KEY_HEADER],
+/// so a retry can't start a second execution.
+///
+/// ```text
+/// let komodo = KomodoClient::new_from_env()?
+///   .with_retry(RetryPolicy::default());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  /// The total attempts, including the first.
+  /// Default: 3
+  pub max_attempts: u32,
+  /// The delay before the first retry, doubling after each one.
+  /// Default: 500ms
+  pub initial_backoff: Duration,
+  /// The maximum delay between attempts.
+  /// Also caps the delay requested by `429 Retry-After`.
+  /// Default: 10s
+  pub max_backoff: Duration,
+  /// The response status codes which are retried.
+  /// Default: 429, 502, 503, 504
+  pub retry_status_codes: Vec<StatusCode>,
+  /// Whether to retry requests which fail to connect or time out.
+  /// Default: true
+  pub retry_connection_errors: bool,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    RetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(500),
+      max_backoff: Duration::from_secs(10),
+      retry_status_codes: vec![
+        StatusCode::TOO_MANY_REQUESTS,
+        StatusCode::BAD_GATEWAY,
+        StatusCode::SERVICE_UNAVAILABLE,
+        StatusCode::GATEWAY_TIMEOUT,
+      ],
+      retry_connection_errors: true,
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// The delay before retrying after the given (1-indexed) attempt failed.
+  pub fn backoff(&self, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    self
+      .initial_backoff
+      .saturating_mul(factor)
+      .min(self.max_backoff)
+  }
+
+  /// Returns the delay before the next attempt
+  /// if a request failing with this error should be retried.
+  pub(crate) fn retry_error(
+    &self,
+    attempt: u32,
+    e: &reqwest::Error,
+  ) -> Option<Duration> {
+    (attempt < self.max_attempts
+      && self.retry_connection_errors
+      && (e.is_connect() || e.is_timeout()))
+    .then(|| self.backoff(attempt))
+  }
+
+  /// Returns the delay before the next attempt
+  /// if a request with this response status should be retried.
+  /// Respects the `Retry-After` header (in seconds) on `429`.
+  pub(crate) fn retry_status(
+    &self,
+    attempt: u32,
+    status: StatusCode,
+    headers: &reqwest::header::HeaderMap,
+  ) -> Option<Duration> {
+    if attempt >= self.max_attempts
+      || !self.retry_status_codes.contains(&status)
+    {
+      return None;
+    }
+    let retry_after = (status == StatusCode::TOO_MANY_REQUESTS)
+      .then(|| headers.get(RETRY_AFTER))
+      .flatten()
+      .and_then(|retry_after| retry_after.to_str().ok())
+      .and_then(|retry_after| retry_after.trim().parse::<u64>().ok())
+      .map(|secs| Duration::from_secs(secs).min(self.max_backoff));
+    Some(retry_after.unwrap_or_else(|| self.backoff(attempt)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use reqwest::header::{HeaderMap, HeaderValue};
+
+  use super::*;
+
+  #[test]
+  fn backoff_doubles_up_to_max() {
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.backoff(1), Duration::from_millis(500));
+    assert_eq!(policy.backoff(2), Duration::from_secs(1));
+    assert_eq!(policy.backoff(3), Duration::from_secs(2));
+    assert_eq!(policy.backoff(10), Duration::from_secs(10));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(10));
+  }
+
+  #[test]
+  fn retries_configured_status_codes() {
+    let policy = RetryPolicy::default();
+    let headers = HeaderMap::new();
+    assert_eq!(
+      policy.retry_status(
+        1,
+        StatusCode::SERVICE_UNAVAILABLE,
+        &headers
+      ),
+      Some(Duration::from_millis(500))
+    );
+    assert_eq!(
+      policy.retry_status(
+        1,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &headers
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn stops_after_max_attempts() {
+    let policy = RetryPolicy::default();
+    assert_eq!(
+      policy.retry_status(
+        3,
+        StatusCode::SERVICE_UNAVAILABLE,
+        &HeaderMap::new()
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn respects_retry_after_on_429() {
+    let policy = RetryPolicy::default();
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+    assert_eq!(
+      policy.retry_status(1, StatusCode::TOO_MANY_REQUESTS, &headers),
+      Some(Duration::from_secs(3))
+    );
+    // Capped by max_backoff
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+    assert_eq!(
+      policy.retry_status(1, StatusCode::TOO_MANY_REQUESTS, &headers),
+      Some(Duration::from_secs(10))
+    );
+    // Only 429 uses Retry-After
+    assert_eq!(
+      policy.retry_status(
+        1,
+        StatusCode::SERVICE_UNAVAILABLE,
+        &headers
+      ),
+      Some(Duration::from_millis(500))
+    );
+  }
+}
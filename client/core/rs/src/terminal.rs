@@ -1,4 +1,16 @@
-use futures::{Stream, StreamExt, TryStreamExt};
+use anyhow::{Context, anyhow};
+use futures::{SinkExt, Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use tokio_tungstenite::tungstenite::{Bytes, Message};
+
+use crate::{
+  KomodoClient,
+  api::terminal::{
+    ConnectContainerExecQuery, ConnectDeploymentExecQuery,
+    ConnectStackExecQuery, ConnectTerminalQuery,
+  },
+  ws::{WsLoginMessage, WsStream, connect_and_login},
+};
 
 pub struct TerminalStreamResponse(pub reqwest::Response);
 
@@ -16,3 +28,216 @@ impl TerminalStreamResponse {
     .map(|line| line.map(|line| line + "\n"))
   }
 }
+
+/// Prefixes binary messages carrying terminal input.
+const STDIN_PREFIX: u8 = 0x00;
+/// Prefixes binary messages carrying resize dimensions.
+const RESIZE_PREFIX: u8 = 0xFF;
+
+/// An interactive terminal session over the Komodo Core terminal websocket.
+///
+/// ```text
+/// let mut session = komodo
+///   .connect_container_exec(ConnectContainerExecQuery { .. })
+///   .await?;
+/// session.resize(120, 40).await?;
+/// session.write(b"ls\n").await?;
+/// while let Some(output) = session.read().await {
+///   print!("{}", String::from_utf8_lossy(&output?));
+/// }
+/// ```
+pub struct TerminalSession {
+  socket: WsStream,
+}
+
+#[derive(Serialize)]
+struct ResizeDimensions {
+  rows: u16,
+  cols: u16,
+}
+
+impl TerminalSession {
+  /// Writes bytes to the terminal stdin.
+  pub async fn write(
+    &mut self,
+    bytes: impl AsRef<[u8]>,
+  ) -> anyhow::Result<()> {
+    self
+      .socket
+      .send(Message::Binary(stdin_message(bytes.as_ref()).into()))
+      .await
+      .context("Failed to write to terminal")
+  }
+
+  /// Resizes the terminal.
+  pub async fn resize(
+    &mut self,
+    cols: u16,
+    rows: u16,
+  ) -> anyhow::Result<()> {
+    self
+      .socket
+      .send(Message::Binary(resize_message(cols, rows)?.into()))
+      .await
+      .context("Failed to resize terminal")
+  }
+
+  /// Reads the next terminal output.
+  /// Returns None once the terminal or the connection is closed.
+  pub async fn read(&mut self) -> Option<anyhow::Result<Bytes>> {
+    loop {
+      match self.socket.try_next().await {
+        Ok(Some(Message::Binary(bytes))) => return Some(Ok(bytes)),
+        Ok(Some(Message::Text(text))) => {
+          return text_output(text.as_str());
+        }
+        Ok(Some(Message::Close(_))) | Ok(None) => return None,
+        Err(e) => {
+          return Some(Err(
+            anyhow::Error::from(e).context("Failed to read terminal"),
+          ));
+        }
+        // ignore (ping, etc.)
+        Ok(Some(_)) => {}
+      }
+    }
+  }
+
+  /// The terminal output as a stream. See [TerminalSession::read].
+  pub fn output(
+    &mut self,
+  ) -> impl Stream<Item = anyhow::Result<Bytes>> + '_ {
+    futures::stream::unfold(self, |session| async move {
+      let output = session.read().await?;
+      Some((output, session))
+    })
+  }
+
+  /// Closes the session.
+  pub async fn close(mut self) -> anyhow::Result<()> {
+    self
+      .socket
+      .close(None)
+      .await
+      .context("Failed to close terminal session")
+  }
+}
+
+fn stdin_message(bytes: &[u8]) -> Vec<u8> {
+  let mut msg = Vec::with_capacity(bytes.len() + 1);
+  msg.push(STDIN_PREFIX);
+  msg.extend_from_slice(bytes);
+  msg
+}
+
+fn resize_message(cols: u16, rows: u16) -> anyhow::Result<Vec<u8>> {
+  let mut msg = vec![RESIZE_PREFIX];
+  serde_json::to_writer(&mut msg, &ResizeDimensions { rows, cols })
+    .context("Failed to serialize resize dimensions")?;
+  Ok(msg)
+}
+
+/// Text messages carry errors and the kill notices,
+/// otherwise they are terminal output.
+fn text_output(text: &str) -> Option<anyhow::Result<Bytes>> {
+  if let Some(e) = text.strip_prefix("ERROR: ") {
+    return Some(Err(anyhow!("{e}")));
+  }
+  // "PTY KILLED" / "WS KILLED" precede the close
+  if text == "PTY KILLED" || text == "WS KILLED" {
+    return None;
+  }
+  Some(Ok(Bytes::copy_from_slice(text.as_bytes())))
+}
+
+impl KomodoClient {
+  /// Connects to a terminal on a Server. See [ConnectTerminalQuery].
+  pub async fn connect_terminal(
+    &self,
+    query: ConnectTerminalQuery,
+  ) -> anyhow::Result<TerminalSession> {
+    self.connect_terminal_session("/ws/terminal", &query).await
+  }
+
+  /// Connects to a container exec session. See [ConnectContainerExecQuery].
+  pub async fn connect_container_exec(
+    &self,
+    query: ConnectContainerExecQuery,
+  ) -> anyhow::Result<TerminalSession> {
+    self
+      .connect_terminal_session("/ws/container/terminal", &query)
+      .await
+  }
+
+  /// Connects to a Deployment exec session. See [ConnectDeploymentExecQuery].
+  pub async fn connect_deployment_exec(
+    &self,
+    query: ConnectDeploymentExecQuery,
+  ) -> anyhow::Result<TerminalSession> {
+    self
+      .connect_terminal_session("/ws/deployment/terminal", &query)
+      .await
+  }
+
+  /// Connects to a Stack service exec session. See [ConnectStackExecQuery].
+  pub async fn connect_stack_exec(
+    &self,
+    query: ConnectStackExecQuery,
+  ) -> anyhow::Result<TerminalSession> {
+    self
+      .connect_terminal_session("/ws/stack/terminal", &query)
+      .await
+  }
+
+  async fn connect_terminal_session(
+    &self,
+    path: &str,
+    query: &impl Serialize,
+  ) -> anyhow::Result<TerminalSession> {
+    let query = serde_qs::to_string(query)
+      .context("Failed to serialize terminal query")?;
+    let address = format!(
+      "{}{path}?{query}",
+      self.address.replacen("http", "ws", 1)
+    );
+    let login_msg = WsLoginMessage::ApiKeys {
+      key: self.key.clone(),
+      secret: self.secret.clone(),
+    }
+    .to_json_string()?;
+    let socket = connect_and_login(&address, &login_msg).await?;
+    Ok(TerminalSession { socket })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stdin_message_is_prefixed() {
+    assert_eq!(stdin_message(b"ls\n"), b"\x00ls\n");
+  }
+
+  #[test]
+  fn resize_message_is_prefixed_json() {
+    let msg = resize_message(120, 40).unwrap();
+    assert_eq!(msg[0], RESIZE_PREFIX);
+    let dimensions: serde_json::Value =
+      serde_json::from_slice(&msg[1..]).unwrap();
+    assert_eq!(
+      dimensions,
+      serde_json::json!({ "rows": 40, "cols": 120 })
+    );
+  }
+
+  #[test]
+  fn text_output_handles_errors_and_kills() {
+    let e = text_output("ERROR: no such container").unwrap();
+    assert_eq!(e.unwrap_err().to_string(), "no such container");
+    assert!(text_output("PTY KILLED").is_none());
+    assert!(text_output("WS KILLED").is_none());
+    let output = text_output("hello").unwrap().unwrap();
+    assert_eq!(output.as_ref(), b"hello");
+  }
+}
@@ -1,18 +1,26 @@
-use std::time::Duration;
+use std::{collections::VecDeque, time::Duration};
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
+use bson::doc;
 use futures::{SinkExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use serror::serialize_error;
 use thiserror::Error;
-use tokio::sync::broadcast;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::{net::TcpStream, sync::broadcast};
+use tokio_tungstenite::{
+  MaybeTlsStream, WebSocketStream, connect_async,
+  tungstenite::Message,
+};
 use tokio_util::sync::CancellationToken;
 use tracing::{Instrument, debug, info, info_span, warn};
 use typeshare::typeshare;
 use uuid::Uuid;
 
-use crate::{KomodoClient, entities::update::UpdateListItem};
+use crate::{
+  KomodoClient,
+  api::read::ListUpdates,
+  entities::{I64, komodo_timestamp, update::UpdateListItem},
+};
 
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -285,4 +293,305 @@ impl KomodoClient {
 
     Ok((rx, cancel_clone))
   }
+
+  /// Connects and logs in to the Komodo Core websocket at `path`, eg `/ws/update`.
+  /// The returned [ReconnectingWebsocket] reconnects transparently
+  /// if the connection later drops.
+  pub async fn connect_reconnecting_websocket(
+    &self,
+    path: &str,
+  ) -> anyhow::Result<ReconnectingWebsocket> {
+    let address =
+      format!("{}{path}", self.address.replacen("http", "ws", 1));
+    let login_msg = WsLoginMessage::ApiKeys {
+      key: self.key.clone(),
+      secret: self.secret.clone(),
+    }
+    .to_json_string()?;
+    let socket = connect_and_login(&address, &login_msg).await?;
+    Ok(ReconnectingWebsocket {
+      address,
+      login_msg,
+      socket: Some(socket),
+      reconnected: false,
+    })
+  }
+
+  /// Connects to the Komodo Core update websocket.
+  /// See [UpdateWebsocket].
+  #[cfg(not(feature = "blocking"))]
+  pub async fn connect_update_websocket(
+    &self,
+    backfill: bool,
+  ) -> anyhow::Result<UpdateWebsocket> {
+    let since = komodo_timestamp();
+    let socket =
+      self.connect_reconnecting_websocket("/ws/update").await?;
+    Ok(UpdateWebsocket {
+      socket,
+      komodo: self.clone(),
+      backfill,
+      since,
+      pending: VecDeque::new(),
+    })
+  }
+}
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Delay before the first reconnection attempt of a [ReconnectingWebsocket].
+/// Doubles after each failed attempt, up to [MAX_RECONNECT_BACKOFF].
+const INITIAL_RECONNECT_BACKOFF: Duration =
+  Duration::from_millis(500);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A logged in Komodo Core websocket which is transparently
+/// re-established and logged into again when the connection drops.
+///
+/// ```text
+/// let mut ws = komodo.connect_reconnecting_websocket("/ws/update").await?;
+/// loop {
+///   let msg = ws.recv().await;
+///   info!("Got message: {msg}");
+/// }
+/// ```
+pub struct ReconnectingWebsocket {
+  address: String,
+  login_msg: String,
+  socket: Option<WsStream>,
+  reconnected: bool,
+}
+
+impl ReconnectingWebsocket {
+  /// Receives the next text message.
+  /// If the connection drops, reconnects (with backoff)
+  /// until the next message arrives.
+  pub async fn recv(&mut self) -> String {
+    loop {
+      if self.socket.is_none() {
+        self.reconnect().await;
+      }
+      let Some(socket) = self.socket.as_mut() else {
+        continue;
+      };
+      match socket.try_next().await {
+        Ok(Some(Message::Text(msg))) => return msg.to_string(),
+        Ok(Some(Message::Close(_))) | Ok(None) => {
+          warn!("Websocket at {} closed, reconnecting", self.address);
+          self.socket = None;
+        }
+        Err(e) => {
+          warn!(
+            "Websocket at {} errored, reconnecting | {e:?}",
+            self.address
+          );
+          self.socket = None;
+        }
+        // ignore (ping, etc.)
+        Ok(Some(_)) => {}
+      }
+    }
+  }
+
+  /// Whether the connection was re-established since this was last called.
+  pub fn take_reconnected(&mut self) -> bool {
+    std::mem::take(&mut self.reconnected)
+  }
+
+  /// Closes the underlying connection.
+  pub async fn close(mut self) {
+    if let Some(mut socket) = self.socket.take() {
+      let _ = socket.close(None).await;
+    }
+  }
+
+  async fn reconnect(&mut self) {
+    let mut backoff = INITIAL_RECONNECT_BACKOFF;
+    loop {
+      tokio::time::sleep(backoff).await;
+      match connect_and_login(&self.address, &self.login_msg).await {
+        Ok(socket) => {
+          info!("Reconnected to websocket at {}", self.address);
+          self.socket = Some(socket);
+          self.reconnected = true;
+          return;
+        }
+        Err(e) => {
+          warn!("{e:#}");
+          backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+      }
+    }
+  }
+}
+
+pub(crate) async fn connect_and_login(
+  address: &str,
+  login_msg: &str,
+) -> anyhow::Result<WsStream> {
+  let (mut ws, _) =
+    connect_async(address).await.with_context(|| {
+      format!("failed to connect to Komodo websocket at {address}")
+    })?;
+  ws.send(Message::text(login_msg))
+    .await
+    .context("failed to send login message")?;
+  match ws.try_next().await.context("failed to recieve message")? {
+    Some(Message::Text(msg)) if msg == "LOGGED_IN" => Ok(ws),
+    Some(msg) => {
+      let _ = ws.close(None).await;
+      Err(anyhow!("got msg {msg:?} instead of 'LOGGED_IN'"))
+    }
+    None => Err(anyhow!("got None instead of 'LOGGED_IN'")),
+  }
+}
+
+/// Maximum pages of [ListUpdates] fetched to backfill
+/// the updates missed while an [UpdateWebsocket] was disconnected.
+#[cfg(not(feature = "blocking"))]
+const MAX_BACKFILL_PAGES: u32 = 10;
+
+/// Receives updates over a [ReconnectingWebsocket] to `/ws/update`.
+///
+/// With backfill enabled, the updates started while disconnected
+/// are fetched using [ListUpdates] after reconnecting,
+/// and received before any new updates.
+#[cfg(not(feature = "blocking"))]
+pub struct UpdateWebsocket {
+  socket: ReconnectingWebsocket,
+  komodo: KomodoClient,
+  backfill: bool,
+  /// The latest update start_ts received
+  since: I64,
+  pending: VecDeque<UpdateListItem>,
+}
+
+#[cfg(not(feature = "blocking"))]
+impl UpdateWebsocket {
+  /// Receives the next update, reconnecting as needed.
+  pub async fn recv(&mut self) -> UpdateListItem {
+    loop {
+      if let Some(update) = self.pending.pop_front() {
+        self.since = self.since.max(update.start_ts);
+        return update;
+      }
+      let msg = self.socket.recv().await;
+      let update = match serde_json::from_str::<UpdateListItem>(&msg)
+      {
+        Ok(update) => update,
+        Err(_) => {
+          warn!("got unrecognized message: {msg:?}");
+          continue;
+        }
+      };
+      if self.socket.take_reconnected() && self.backfill {
+        self.backfill_since_disconnect().await;
+      }
+      queue_update(&mut self.pending, update);
+    }
+  }
+
+  /// Closes the underlying connection.
+  pub async fn close(self) {
+    self.socket.close().await
+  }
+
+  async fn backfill_since_disconnect(&mut self) {
+    let mut missed = Vec::new();
+    let mut page = 0;
+    while page < MAX_BACKFILL_PAGES {
+      let res = self
+        .komodo
+        .read(ListUpdates {
+          query: Some(doc! { "start_ts": { "$gt": self.since } }),
+          page,
+        })
+        .await;
+      match res {
+        Ok(res) => {
+          missed.extend(res.updates);
+          let Some(next_page) = res.next_page else {
+            break;
+          };
+          page = next_page;
+        }
+        Err(e) => {
+          warn!("failed to backfill updates after reconnect | {e:#}");
+          break;
+        }
+      }
+    }
+    queue_missed(&mut self.pending, missed);
+  }
+}
+
+/// Queues the updates missed while disconnected, oldest first.
+#[cfg(not(feature = "blocking"))]
+fn queue_missed(
+  pending: &mut VecDeque<UpdateListItem>,
+  mut missed: Vec<UpdateListItem>,
+) {
+  // ListUpdates is sorted by timestamp descending
+  missed.sort_by_key(|update| update.start_ts);
+  pending.extend(missed);
+}
+
+/// Queues the received update, unless it was already backfilled.
+#[cfg(not(feature = "blocking"))]
+fn queue_update(
+  pending: &mut VecDeque<UpdateListItem>,
+  update: UpdateListItem,
+) {
+  if !pending.iter().any(|pending| pending.id == update.id) {
+    pending.push_back(update);
+  }
+}
+
+#[cfg(all(test, not(feature = "blocking")))]
+mod tests {
+  use crate::entities::{
+    Operation, ResourceTarget, Version, update::UpdateStatus,
+  };
+
+  use super::*;
+
+  fn update(id: &str, start_ts: I64) -> UpdateListItem {
+    UpdateListItem {
+      id: id.to_string(),
+      operation: Operation::None,
+      start_ts,
+      success: true,
+      username: String::new(),
+      operator: String::new(),
+      target: ResourceTarget::System(String::new()),
+      status: UpdateStatus::Complete,
+      version: Version::default(),
+      other_data: String::new(),
+      progress: None,
+    }
+  }
+
+  fn ids(pending: &VecDeque<UpdateListItem>) -> Vec<&str> {
+    pending.iter().map(|update| update.id.as_str()).collect()
+  }
+
+  #[test]
+  fn missed_updates_queue_oldest_first() {
+    let mut pending = VecDeque::new();
+    queue_missed(
+      &mut pending,
+      vec![update("c", 3), update("b", 2), update("a", 1)],
+    );
+    assert_eq!(ids(&pending), ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn backfilled_update_is_not_queued_twice() {
+    let mut pending = VecDeque::new();
+    queue_missed(&mut pending, vec![update("b", 2), update("a", 1)]);
+    queue_update(&mut pending, update("b", 2));
+    assert_eq!(ids(&pending), ["a", "b"]);
+    queue_update(&mut pending, update("c", 3));
+    assert_eq!(ids(&pending), ["a", "b", "c"]);
+  }
 }
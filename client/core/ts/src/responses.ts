@@ -6,6 +6,7 @@ export type AuthResponses = {
   LoginLocalUser: Types.LoginLocalUserResponse;
   ExchangeForJwt: Types.ExchangeForJwtResponse;
   GetUser: Types.GetUserResponse;
+  RefreshJwt: Types.RefreshJwtResponse;
 };
 
 export type UserResponses = {
@@ -13,15 +14,21 @@ export type UserResponses = {
   SetLastSeenUpdate: Types.SetLastSeenUpdateResponse;
   CreateApiKey: Types.CreateApiKeyResponse;
   DeleteApiKey: Types.DeleteApiKeyResponse;
+  RotateApiKeySecret: Types.RotateApiKeySecretResponse;
+  LogoutEverywhere: Types.LogoutEverywhereResponse;
 };
 
 export type ReadResponses = {
   GetVersion: Types.GetVersionResponse;
   GetCoreInfo: Types.GetCoreInfoResponse;
+  GetJsonSchema: Types.GetJsonSchemaResponse;
   ListSecrets: Types.ListSecretsResponse;
   ListGitProvidersFromConfig: Types.ListGitProvidersFromConfigResponse;
   ListDockerRegistriesFromConfig: Types.ListDockerRegistriesFromConfigResponse;
 
+  // ==== SEARCH ====
+  Search: Types.SearchResponse;
+
   // ==== USER ====
   GetUsername: Types.GetUsernameResponse;
   GetPermission: Types.GetPermissionResponse;
@@ -29,6 +36,7 @@ export type ReadResponses = {
   ListUsers: Types.ListUsersResponse;
   ListApiKeys: Types.ListApiKeysResponse;
   ListApiKeysForServiceUser: Types.ListApiKeysForServiceUserResponse;
+  ListLoginEvents: Types.ListLoginEventsResponse;
   ListPermissions: Types.ListPermissionsResponse;
   ListUserTargetPermissions: Types.ListUserTargetPermissionsResponse;
 
@@ -52,6 +60,7 @@ export type ReadResponses = {
 
   // ==== SCHEDULE ====
   ListSchedules: Types.ListSchedulesResponse;
+  ValidateSchedule: Types.ValidateScheduleResponse;
 
   // ==== SERVER ====
   GetServersSummary: Types.GetServersSummaryResponse;
@@ -62,9 +71,11 @@ export type ReadResponses = {
   ListDockerContainers: Types.ListDockerContainersResponse;
   ListAllDockerContainers: Types.ListAllDockerContainersResponse;
   InspectDockerContainer: Types.InspectDockerContainerResponse;
+  GetComposeFromContainers: Types.GetComposeFromContainersResponse;
   GetResourceMatchingContainer: Types.GetResourceMatchingContainerResponse;
   GetContainerLog: Types.GetContainerLogResponse;
   SearchContainerLog: Types.SearchContainerLogResponse;
+  GetContainerStatsHistory: Types.GetContainerStatsHistoryResponse;
   ListDockerNetworks: Types.ListDockerNetworksResponse;
   InspectDockerNetwork: Types.InspectDockerNetworkResponse;
   ListDockerImages: Types.ListDockerImagesResponse;
@@ -75,6 +86,7 @@ export type ReadResponses = {
   ListComposeProjects: Types.ListComposeProjectsResponse;
   GetServerActionState: Types.GetServerActionStateResponse;
   GetHistoricalServerStats: Types.GetHistoricalServerStatsResponse;
+  QueryServerStats: Types.QueryServerStatsResponse;
   ListServers: Types.ListServersResponse;
   ListFullServers: Types.ListFullServersResponse;
   ListTerminals: Types.ListTerminalsResponse;
@@ -161,14 +173,22 @@ export type ReadResponses = {
   ListAlerts: Types.ListAlertsResponse;
   GetAlert: Types.GetAlertResponse;
 
+  // ==== AUDIT ====
+  ListAuditLogs: Types.ListAuditLogsResponse;
+
+  // ==== CHANGE FREEZE ====
+  GetChangeFreeze: Types.GetChangeFreezeResponse;
+
   // ==== SERVER STATS ====
   GetSystemInformation: Types.GetSystemInformationResponse;
+  GetDockerInfo: Types.GetDockerInfoResponse;
   GetSystemStats: Types.GetSystemStatsResponse;
   ListSystemProcesses: Types.ListSystemProcessesResponse;
 
   // ==== VARIABLE ====
   GetVariable: Types.GetVariableResponse;
   ListVariables: Types.ListVariablesResponse;
+  ListVariableReferences: Types.ListVariableReferencesResponse;
 
   // ==== PROVIDER ====
   GetGitProviderAccount: Types.GetGitProviderAccountResponse;
@@ -222,6 +242,7 @@ export type WriteResponses = {
   // ==== STACK ====
   CreateStack: Types.Stack;
   CopyStack: Types.Stack;
+  CreateStackFromComposeProject: Types.Stack;
   DeleteStack: Types.Stack;
   UpdateStack: Types.Stack;
   RenameStack: Types.Update;
@@ -304,6 +325,12 @@ export type WriteResponses = {
   DeleteTag: Types.Tag;
   RenameTag: Types.Tag;
   UpdateTagColor: Types.Tag;
+  BatchAddTags: Types.BatchUpdateTagsResponse;
+  BatchRemoveTags: Types.BatchUpdateTagsResponse;
+
+  // ==== CHANGE FREEZE ====
+  StartChangeFreeze: Types.StartChangeFreezeResponse;
+  EndChangeFreeze: Types.EndChangeFreezeResponse;
 
   // ==== VARIABLE ====
   CreateVariable: Types.CreateVariableResponse;
@@ -363,6 +390,7 @@ export type ExecuteResponses = {
   // ==== DEPLOYMENT ====
   Deploy: Types.Update;
   BatchDeploy: Types.BatchExecutionResponse;
+  ReconcileDeployment: Types.Update;
   PullDeployment: Types.Update;
   StartDeployment: Types.Update;
   RestartDeployment: Types.Update;
@@ -415,4 +443,5 @@ export type ExecuteResponses = {
   ClearRepoCache: Types.Update;
   BackupCoreDatabase: Types.Update;
   GlobalAutoUpdate: Types.Update;
+  RedeployVariableReferences: Types.BatchExecutionResponse;
 };
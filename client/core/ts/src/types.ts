@@ -43,6 +43,12 @@ export interface Resource<Config, Info> {
 	description?: string;
 	/** Mark resource as a template */
 	template?: boolean;
+	/**
+	 * Whether the resource is locked.
+	 * Locked resources reject all executions,
+	 * including scheduled and automated ones, until an admin unlocks them.
+	 */
+	locked?: boolean;
 	/** Tag Ids */
 	tags?: string[];
 	/** Resource-specific information (not user configurable). */
@@ -120,6 +126,17 @@ export interface ActionConfig {
 	 * this can usually be kept false outside of development.
 	 */
 	reload_deno_deps?: boolean;
+	/**
+	 * Kill the Action if it is still running after this many seconds.
+	 * 0 means no timeout.
+	 */
+	timeout_seconds?: I64;
+	/**
+	 * Limit the memory available to the Action runtime heap, in MB.
+	 * Passed to deno as the V8 `--max-old-space-size`.
+	 * 0 means no limit.
+	 */
+	memory_limit_mb?: I64;
 	/**
 	 * Typescript file contents using pre-initialized `komodo` client.
 	 * Supports variable / secret interpolation.
@@ -400,6 +417,7 @@ export enum Operation {
 	RenameDeployment = "RenameDeployment",
 	DeleteDeployment = "DeleteDeployment",
 	Deploy = "Deploy",
+	ReconcileDeployment = "ReconcileDeployment",
 	PullDeployment = "PullDeployment",
 	StartDeployment = "StartDeployment",
 	RestartDeployment = "RestartDeployment",
@@ -452,6 +470,8 @@ export enum Operation {
 	ClearRepoCache = "ClearRepoCache",
 	BackupCoreDatabase = "BackupCoreDatabase",
 	GlobalAutoUpdate = "GlobalAutoUpdate",
+	StartChangeFreeze = "StartChangeFreeze",
+	EndChangeFreeze = "EndChangeFreeze",
 	CreateVariable = "CreateVariable",
 	UpdateVariableValue = "UpdateVariableValue",
 	DeleteVariable = "DeleteVariable",
@@ -463,6 +483,15 @@ export enum Operation {
 	DeleteDockerRegistryAccount = "DeleteDockerRegistryAccount",
 }
 
+/** Severity of a log, also used to configure the logging level. */
+export enum LogLevel {
+	Trace = "trace",
+	Debug = "debug",
+	Info = "info",
+	Warn = "warn",
+	Error = "error",
+}
+
 /** Represents the output of some command being run */
 export interface Log {
 	/** A label for the log */
@@ -479,6 +508,16 @@ export interface Log {
 	start_ts: I64;
 	/** The end time of the command execution */
 	end_ts: I64;
+	/**
+	 * The severity of the log.
+	 * Inferred from the output unless set explicitly:
+	 * - `error` if unsuccessful
+	 * - `warn` if successful with output in stderr
+	 * - `info` otherwise
+	 * 
+	 * Logs stored before the level was added have it inferred.
+	 */
+	level: LogLevel;
 }
 
 /** An update's status */
@@ -497,6 +536,16 @@ export interface Version {
 	patch: number;
 }
 
+/** Progress through the stages of a long running operation. */
+export interface UpdateProgress {
+	/** The current stage, starting from 1. */
+	current: number;
+	/** The total number of stages. */
+	total: number;
+	/** The name of the current stage. */
+	stage: string;
+}
+
 /** Represents an action performed by Komodo. */
 export interface Update {
 	/**
@@ -543,6 +592,11 @@ export interface Update {
 	prev_toml?: string;
 	/** If the update is for resource config update, give the current (at time of Update) toml contents */
 	current_toml?: string;
+	/**
+	 * Progress through the stages of a long running operation.
+	 * Only reported by multi stage operations while in progress.
+	 */
+	progress?: UpdateProgress;
 }
 
 export type BoxUpdate = Update;
@@ -806,6 +860,27 @@ export interface BuilderQuerySpecifics {
 
 export type BuilderQuery = ResourceQuery<BuilderQuerySpecifics>;
 
+/**
+ * Determines whether a stage runs based on the
+ * results of the previous stages, or of the stage
+ * named in `condition_stage`.
+ */
+export enum StageCondition {
+	/**
+	 * Run only if all previous stages succeeded,
+	 * or the named stage succeeded.
+	 */
+	Success = "Success",
+	/**
+	 * Run only if a previous stage failed,
+	 * or the named stage failed.
+	 * Use for error handling, eg. sending an alert.
+	 */
+	Failure = "Failure",
+	/** Run regardless of the previous stage results. */
+	Always = "Always",
+}
+
 /** A wrapper for all Komodo exections. */
 export type Execution = 
 	/** The "null" execution. Does nothing. */
@@ -823,6 +898,7 @@ export type Execution =
 	/** Deploy the target deployment. (alias: `dp`) */
 	| { type: "Deploy", params: Deploy }
 	| { type: "BatchDeploy", params: BatchDeploy }
+	| { type: "ReconcileDeployment", params: ReconcileDeployment }
 	| { type: "PullDeployment", params: PullDeployment }
 	| { type: "StartDeployment", params: StartDeployment }
 	| { type: "RestartDeployment", params: RestartDeployment }
@@ -884,7 +960,9 @@ export type Execution =
 	| { type: "ClearRepoCache", params: ClearRepoCache }
 	| { type: "BackupCoreDatabase", params: BackupCoreDatabase }
 	| { type: "GlobalAutoUpdate", params: GlobalAutoUpdate }
-	| { type: "Sleep", params: Sleep };
+	| { type: "RedeployVariableReferences", params: RedeployVariableReferences }
+	| { type: "Sleep", params: Sleep }
+	| { type: "WaitUntil", params: WaitUntil };
 
 /** Allows to enable / disabled procedures in the sequence / parallel vec on the fly */
 export interface EnabledExecution {
@@ -894,13 +972,39 @@ export interface EnabledExecution {
 	enabled: boolean;
 }
 
-/** A single stage of a procedure. Runs a list of executions in parallel. */
+/**
+ * A single stage of a procedure. Runs a list of executions in parallel,
+ * optionally limited by `max_concurrency`.
+ */
 export interface ProcedureStage {
 	/** A name for the procedure */
 	name: string;
 	/** Whether the stage should be run as part of the procedure. */
 	enabled: boolean;
-	/** The executions in the stage */
+	/**
+	 * Run the stage depending on the result of the previous stages.
+	 * Default is `Success`.
+	 */
+	condition?: StageCondition;
+	/**
+	 * The name of an earlier stage for the condition to check.
+	 * If empty, the condition checks all previous stages.
+	 */
+	condition_stage?: string;
+	/**
+	 * The maximum number of stage executions to run at once.
+	 * The stage finishes once all executions finish.
+	 * 0 means no limit, all executions run at once.
+	 */
+	max_concurrency?: I64;
+	/**
+	 * The executions in the stage.
+	 * 
+	 * Execution parameters can reference the results of previous stages using
+	 * `{{<stage name>.version}}`, `{{<stage name>.commit_hash}}`, or `{{<stage name>.update_id}}`.
+	 * Output set by an Action with `setOutput` is available at `{{<stage name>.output}}`,
+	 * with top level fields of object outputs at `{{<stage name>.output.<field>}}`.
+	 */
 	executions?: EnabledExecution[];
 }
 
@@ -1090,6 +1194,24 @@ export interface User {
 	/** Give the user elevated permissions on all resources of a certain type */
 	all?: Record<ResourceTarget["type"], PermissionLevelAndSpecifics | PermissionLevel>;
 	updated_at?: I64;
+	/**
+	 * JWTs issued before this time are rejected.
+	 * Set to the current time by
+	 * [LogoutEverywhere][crate::api::user::LogoutEverywhere].
+	 */
+	tokens_valid_after?: I64;
+	/**
+	 * The number of consecutive failed local logins
+	 * within the lockout window.
+	 */
+	failed_logins?: I64;
+	/** The time of the last failed local login. */
+	last_failed_login_at?: I64;
+	/**
+	 * Local logins are rejected until this time,
+	 * after too many failed attempts.
+	 */
+	locked_until?: I64;
 }
 
 export type CreateLocalUserResponse = User;
@@ -1179,6 +1301,20 @@ export enum RestartMode {
 	UnlessStopped = "unless-stopped",
 }
 
+export enum DeploymentStrategy {
+	/**
+	 * Stop and remove the existing container,
+	 * then run the new container.
+	 */
+	Recreate = "Recreate",
+	/**
+	 * Run the new container alongside the existing one,
+	 * and only stop and remove the existing container
+	 * once the new one is healthy.
+	 */
+	StartFirst = "StartFirst",
+}
+
 export enum TerminationSignal {
 	SigHup = "SIGHUP",
 	SigInt = "SIGINT",
@@ -1227,6 +1363,15 @@ export interface DeploymentConfig {
 	network: string;
 	/** The restart mode given to the container. */
 	restart?: RestartMode;
+	/**
+	 * How to replace an existing container on redeploy.
+	 * Default is `Recreate`.
+	 * 
+	 * `StartFirst` will only minimize downtime when the container
+	 * does not bind any host ports, as the new and old containers
+	 * need to run side by side.
+	 */
+	deploy_strategy?: DeploymentStrategy;
 	/**
 	 * This is interpolated at the end of the `docker run` command,
 	 * which means they are either passed to the containers inner process,
@@ -1261,6 +1406,19 @@ export interface DeploymentConfig {
 	volumes?: string;
 	/** The environment variables passed to the container. */
 	environment?: string;
+	/**
+	 * Env files passed to the container with `--env-file`.
+	 * Variables in later files override earlier ones,
+	 * and the variables in `environment` override them all.
+	 * Absolute paths are used directly. Relative paths are taken
+	 * relative to the `linked_repo` folder, and require one to be attached.
+	 */
+	env_files?: string[];
+	/**
+	 * Choose a Komodo Repo (Resource) to source relative `env_files` from.
+	 * The repo is pulled on the Server before each deploy.
+	 */
+	linked_repo?: string;
 	/** The docker labels given to the container. */
 	labels?: string;
 }
@@ -1307,6 +1465,12 @@ export interface DeploymentListItemInfo {
 	image: string;
 	/** Whether there is a newer image available at the same tag. */
 	update_available: boolean;
+	/**
+	 * The differences found between the running container
+	 * and the deployment config, eg after a manual `docker update`.
+	 * Checked on the resource poll interval.
+	 */
+	config_drift: string[];
 	/** The server that deployment sits on. */
 	server_id: string;
 	/** An attached Komodo Build, if it exists. */
@@ -1334,6 +1498,8 @@ export interface DeploymentQuerySpecifics {
 
 export type DeploymentQuery = ResourceQuery<DeploymentQuerySpecifics>;
 
+export type EndChangeFreezeResponse = NoData;
+
 /** JSON containing an authentication token. */
 export interface JwtResponse {
 	/** User ID for signed in user. */
@@ -1451,6 +1617,23 @@ export type AlertData =
 	used_gb: number;
 	/** The total size of the disk in GB */
 	total_gb: number;
+}}
+	/** A server's CPU or MEM usage spiked far above its recent baseline. */
+	| { type: "ServerStatsAnomaly", data: {
+	/** The id of the server */
+	id: string;
+	/** The name of the server */
+	name: string;
+	/** The region of the server */
+	region?: string;
+	/** The spiking metric, `CPU` or `MEM` */
+	metric: string;
+	/** The current usage percentage */
+	percentage: number;
+	/** The average usage percentage over the recent baseline */
+	baseline: number;
+	/** The number of standard deviations above the baseline */
+	z_score: number;
 }}
 	/** A server has a version mismatch with the core. */
 	| { type: "ServerVersionMismatch", data: {
@@ -1479,6 +1662,47 @@ export type AlertData =
 	from: DeploymentState;
 	/** The current container state */
 	to: DeploymentState;
+}}
+	/** A container was killed by the OOM killer. */
+	| { type: "ContainerOomKilled", data: {
+	/** The id of the deployment */
+	id: string;
+	/** The name of the deployment */
+	name: string;
+	/** The server id of server that the deployment is on */
+	server_id: string;
+	/** The server name */
+	server_name: string;
+	/** The container exit code, usually 137 */
+	exit_code?: I64;
+}}
+	/** A Deployment's container differs from its config. */
+	| { type: "DeploymentConfigDrift", data: {
+	/** The id of the deployment */
+	id: string;
+	/** The name of the deployment */
+	name: string;
+	/** The server id of server that the deployment is on */
+	server_id: string;
+	/** The server name */
+	server_name: string;
+	/** The differences found */
+	drift: string[];
+}}
+	/** A container is restarting repeatedly. */
+	| { type: "ContainerCrashLoop", data: {
+	/** The id of the deployment */
+	id: string;
+	/** The name of the deployment */
+	name: string;
+	/** The server id of server that the deployment is on */
+	server_id: string;
+	/** The server name */
+	server_name: string;
+	/** The number of restarts within the window */
+	restarts: I64;
+	/** The window the restarts were counted over, eg `5-min` */
+	window: string;
 }}
 	/** A Deployment has an image update available */
 	| { type: "DeploymentImageUpdateAvailable", data: {
@@ -1601,6 +1825,17 @@ export type AlertData =
 	id: string;
 	/** The resource name */
 	name: string;
+}}
+	/** An api key will expire soon. */
+	| { type: "ApiKeyExpiring", data: {
+	/** The api key name */
+	name: string;
+	/** The id of the user the key belongs to */
+	user_id: string;
+	/** The username of the user the key belongs to */
+	username: string;
+	/** The unix timestamp (ms) the key expires at */
+	expires: I64;
 }}
 	/**
 	 * Custom header / body.
@@ -1651,6 +1886,25 @@ export type GetBuilderResponse = Builder;
 
 export type GetContainerLogResponse = Log;
 
+export interface ContainerStats {
+	name: string;
+	cpu_perc: string;
+	mem_perc: string;
+	mem_usage: string;
+	net_io: string;
+	block_io: string;
+	pids: string;
+}
+
+/** A [ContainerStats] sample recorded by the Periphery stats polling. */
+export interface ContainerStatsSample {
+	/** Unix timestamp in milliseconds. */
+	ts: I64;
+	stats: ContainerStats;
+}
+
+export type GetContainerStatsHistoryResponse = ContainerStatsSample[];
+
 export interface DeploymentActionState {
 	pulling: boolean;
 	deploying: boolean;
@@ -1669,22 +1923,56 @@ export type GetDeploymentLogResponse = Log;
 
 export type GetDeploymentResponse = Deployment;
 
-export interface ContainerStats {
-	name: string;
-	cpu_perc: string;
-	mem_perc: string;
-	mem_usage: string;
-	net_io: string;
-	block_io: string;
-	pids: string;
-}
-
 export type GetDeploymentStatsResponse = ContainerStats;
 
+/**
+ * Information about the docker daemon on a server,
+ * from `docker info` and `docker version`.
+ */
+export interface DockerInfo {
+	/** The docker engine version, eg `27.3.1`. */
+	version?: string;
+	/** The docker engine api version, eg `1.47`. */
+	api_version?: string;
+	/** The host operating system, eg `Ubuntu 24.04.1 LTS`. */
+	operating_system?: string;
+	/** The host kernel version. */
+	kernel_version?: string;
+	/** The host architecture, eg `x86_64`. */
+	architecture?: string;
+	/** The storage driver, eg `overlay2`. */
+	storage_driver?: string;
+	/** The cgroup version, `1` or `2`. */
+	cgroup_version?: string;
+	/** The cgroup driver, eg `systemd`. */
+	cgroup_driver?: string;
+	/** Total memory available to the daemon in bytes. */
+	total_memory?: I64;
+	/** Number of CPUs available to the daemon. */
+	cpus?: I64;
+	/** The swarm state of the node, eg `inactive` or `active`. */
+	swarm_state?: string;
+	/** Total number of containers on the host. */
+	containers?: I64;
+	/** Number of running containers on the host. */
+	containers_running?: I64;
+	/** Number of images on the host. */
+	images?: I64;
+	/** Warnings reported by the daemon. */
+	warnings: string[];
+}
+
+export type GetDockerInfoResponse = DockerInfo;
+
 export type GetDockerRegistryAccountResponse = DockerRegistryAccount;
 
 export type GetGitProviderAccountResponse = GitProviderAccount;
 
+export type JsonValue = any;
+
+/** The JSON schema (draft 7) of the type. */
+export type GetJsonSchemaResponse = JsonValue;
+
 export type GetPermissionResponse = PermissionLevelAndSpecifics;
 
 export interface ProcedureActionState {
@@ -1994,6 +2282,16 @@ export interface ServerActionState {
 
 export type GetServerActionStateResponse = ServerActionState;
 
+/** DISK alert thresholds for a specific mount. */
+export interface DiskThreshold {
+	/** The mount path, eg `/mnt/data`. */
+	mount: string;
+	/** The percentage threshhold which triggers WARNING state. */
+	warning: number;
+	/** The percentage threshhold which triggers CRITICAL state. */
+	critical: number;
+}
+
 /** Server configuration. */
 export interface ServerConfig {
 	/**
@@ -2026,6 +2324,13 @@ export interface ServerConfig {
 	 * If this is empty, will use passkey in core config.
 	 */
 	passkey?: string;
+	/**
+	 * Whether to request gzip compressed responses from the server.
+	 * Useful when the server is reached over a slow (WAN) link,
+	 * but costs CPU on both ends, so is best left off on a LAN.
+	 * default: false
+	 */
+	compression?: boolean;
 	/**
 	 * Sometimes the system stats reports a mount path that is not desired.
 	 * Use this field to filter it out from the report.
@@ -2045,6 +2350,13 @@ export interface ServerConfig {
 	links?: string[];
 	/** Whether to send alerts about the servers reachability */
 	send_unreachable_alerts: boolean;
+	/**
+	 * The number of consecutive polls the server must be unreachable
+	 * before an unreachable alert is opened. The server state still
+	 * shows the failure immediately.
+	 * default: 2
+	 */
+	unreachable_alert_polls: number;
 	/** Whether to send alerts about the servers CPU status */
 	send_cpu_alerts: boolean;
 	/** Whether to send alerts about the servers MEM status */
@@ -2053,6 +2365,12 @@ export interface ServerConfig {
 	send_disk_alerts: boolean;
 	/** Whether to send alerts about the servers version mismatch with core */
 	send_version_mismatch_alerts: boolean;
+	/**
+	 * Whether to send alerts when CPU or MEM usage
+	 * spikes far above its recent baseline.
+	 * default: false
+	 */
+	send_anomaly_alerts?: boolean;
 	/** The percentage threshhold which triggers WARNING state for CPU. */
 	cpu_warning: number;
 	/** The percentage threshhold which triggers CRITICAL state for CPU. */
@@ -2065,8 +2383,25 @@ export interface ServerConfig {
 	disk_warning: number;
 	/** The percentage threshhold which triggers CRITICAL state for DISK. */
 	disk_critical: number;
+	/**
+	 * Override the DISK thresholds for specific mounts,
+	 * eg to let a data mount run hotter than `/`.
+	 * Mounts not listed use `disk_warning` and `disk_critical`.
+	 */
+	disk_thresholds?: DiskThreshold[];
 	/** Scheduled maintenance windows during which alerts will be suppressed. */
 	maintenance_windows?: MaintenanceWindow[];
+	/**
+	 * Put the server in maintenance mode for planned work, like a reboot.
+	 * Alerts for the server and its Deployments / Stacks are suppressed,
+	 * while its status is still recorded.
+	 */
+	maintenance_mode?: boolean;
+	/**
+	 * Unix timestamp in milliseconds when maintenance mode expires,
+	 * or 0 to stay in maintenance mode until it is turned off.
+	 */
+	maintenance_mode_until?: I64;
 }
 
 export type Server = Resource<ServerConfig, undefined>;
@@ -3180,6 +3515,18 @@ export interface ImageInspectMetadata {
 	LastTagTime?: string;
 }
 
+/** Size breakdown for a layer of an image. */
+export interface ImageLayer {
+	/** The command which created the layer. */
+	created_by: string;
+	/** Size of the layer in bytes. */
+	size: I64;
+	/** Percentage of the total image size. */
+	size_percent: number;
+	/** Unix timestamp (seconds) when the layer was created. */
+	created: I64;
+}
+
 /** Information about an image in the local image cache. */
 export interface Image {
 	/** ID is the content-addressable ID of an image.  This identifier is a content-addressable digest calculated from the image's configuration (which includes the digests of layers used by the image).  Note that this digest differs from the `RepoDigests` below, which holds digests of image manifests that reference the image. */
@@ -3213,6 +3560,11 @@ export interface Image {
 	GraphDriver?: GraphDriverData;
 	RootFS?: ImageInspectRootFs;
 	Metadata?: ImageInspectMetadata;
+	/**
+	 * The layers which add to the image size, newest first.
+	 * Computed by Komodo from the image history.
+	 */
+	Layers?: ImageLayer[];
 }
 
 export type InspectDockerImageResponse = Image;
@@ -3420,8 +3772,6 @@ export type InspectStackContainerResponse = Container;
 
 export type JsonObject = any;
 
-export type JsonValue = any;
-
 export type ListActionsResponse = ActionListItem[];
 
 export type ListAlertersResponse = AlerterListItem[];
@@ -3500,6 +3850,8 @@ export interface ApiKey {
 	created_at: I64;
 	/** Expiry of key, or 0 if never expires */
 	expires: I64;
+	/** Whether an alert has been sent that the key will expire soon */
+	expiry_alerted?: boolean;
 }
 
 export type ListApiKeysForServiceUserResponse = ApiKey[];
@@ -3659,6 +4011,42 @@ export interface GitProvider {
 
 export type ListGitProvidersFromConfigResponse = GitProvider[];
 
+export enum LoginMethod {
+	Local = "Local",
+	Github = "Github",
+	Google = "Google",
+	Oidc = "Oidc",
+	/** Successful api key logins are recorded at most hourly per key. */
+	ApiKey = "ApiKey",
+}
+
+/** A record of a login attempt, so users can review their account activity. */
+export interface LoginEvent {
+	/** The Mongo ID of the login event. */
+	_id?: MongoId;
+	/** Unix timestamp in milliseconds of the login attempt. */
+	ts: I64;
+	/**
+	 * The id of the user logging in.
+	 * Empty if the attempt couldn't be matched to a user.
+	 */
+	user_id: string;
+	/** The username of the user logging in, if known. */
+	username: string;
+	/** How the user logged in. */
+	method: LoginMethod;
+	/** The client ip address. */
+	ip?: string;
+	/** The client user agent. */
+	user_agent?: string;
+	/** Whether the login succeeded. */
+	success: boolean;
+	/** The reason the login failed. */
+	error?: string;
+}
+
+export type ListLoginEventsResponse = LoginEvent[];
+
 export type UserTarget = 
 	/** User Id */
 	| { type: "User", id: string }
@@ -4028,11 +4416,15 @@ export type ListUserTargetPermissionsResponse = Permission[];
 
 export type ListUsersResponse = User[];
 
+export type ListVariableReferencesResponse = ResourceTarget[];
+
 export type ListVariablesResponse = Variable[];
 
 /** The response for [LoginLocalUser] */
 export type LoginLocalUserResponse = JwtResponse;
 
+export type LogoutEverywhereResponse = NoData;
+
 export type MongoDocument = any;
 
 export interface ProcedureQuerySpecifics {
@@ -4042,6 +4434,9 @@ export type ProcedureQuery = ResourceQuery<ProcedureQuerySpecifics>;
 
 export type PushRecentlyViewedResponse = NoData;
 
+/** Response for [RefreshJwt]. */
+export type RefreshJwtResponse = JwtResponse;
+
 export interface RepoQuerySpecifics {
 	/** Filter repos by their repo. */
 	repos: string[];
@@ -4056,10 +4451,30 @@ export interface ResourceSyncQuerySpecifics {
 
 export type ResourceSyncQuery = ResourceQuery<ResourceSyncQuerySpecifics>;
 
+/** Response for [RotateApiKeySecret]. */
+export type RotateApiKeySecretResponse = CreateApiKeyResponse;
+
 export type SearchContainerLogResponse = Log;
 
 export type SearchDeploymentLogResponse = Log;
 
+export interface SearchResult {
+	/** The matching resource. */
+	target: ResourceTarget;
+	/** The resource name. */
+	name: string;
+	/**
+	 * What the query matched, eg. `name`, `tag`, `description`,
+	 * or a config field like `image`.
+	 */
+	matched: string;
+	/** The match rank. Higher is a better match. */
+	score: I64;
+}
+
+/** The results, best match first. */
+export type SearchResponse = SearchResult[];
+
 export type SearchStackLogResponse = Log;
 
 export interface ServerQuerySpecifics {
@@ -4093,6 +4508,26 @@ export interface StackQuerySpecifics {
 
 export type StackQuery = ResourceQuery<StackQuerySpecifics>;
 
+/**
+ * A fleet wide change freeze, eg over the holidays or during an incident.
+ * While active, all executions are rejected unless run by a super admin.
+ */
+export interface ChangeFreeze {
+	/**
+	 * Why changes are frozen.
+	 * Included in the error when an execution is rejected.
+	 */
+	reason: string;
+	/** The id of the user who started the freeze. */
+	started_by: string;
+	/** Timestamp the freeze started. */
+	started_at: I64;
+	/** Timestamp the freeze ends, or 0 if it lasts until ended manually. */
+	expires?: I64;
+}
+
+export type StartChangeFreezeResponse = ChangeFreeze;
+
 export type UpdateDockerRegistryAccountResponse = DockerRegistryAccount;
 
 export type UpdateGitProviderAccountResponse = GitProviderAccount;
@@ -4168,6 +4603,45 @@ export interface AddUserToUserGroup {
 	user: string;
 }
 
+/**
+ * A record of a `/write` or `/execute` call,
+ * kept for auditing who did what.
+ */
+export interface AuditLog {
+	/**
+	 * The Mongo ID of the audit log.
+	 * This field is de/serialized from/to JSON as
+	 * `{ "_id": { "$oid": "..." }, ...(rest of serialized AuditLog) }`
+	 */
+	_id?: MongoId;
+	/**
+	 * Unix timestamp in milliseconds of the call.
+	 * Executions are recorded when they finish.
+	 */
+	ts: I64;
+	/** The id of the calling user. */
+	user_id: string;
+	/** The username of the calling user. */
+	username: string;
+	/** The api the call was made to, `write` or `execute`. */
+	api: string;
+	/** The request type, eg `UpdateServer` or `DeployStack`. */
+	operation: string;
+	/**
+	 * The resource targeted by the call, if any.
+	 * For executions this is the target of the created Update.
+	 * Otherwise the id may be the resource name, as given in the request.
+	 */
+	target?: ResourceTarget;
+	/**
+	 * Whether the call succeeded.
+	 * For executions, whether the execution finished successfully.
+	 */
+	success: boolean;
+	/** The error, if the call failed. */
+	error?: string;
+}
+
 /** Configuration for an AWS builder. */
 export interface AwsBuilderConfig {
 	/** The AWS region to create the instance in */
@@ -4230,6 +4704,31 @@ export interface AwsBuilderConfig {
 export interface BackupCoreDatabase {
 }
 
+/**
+ * Add tags to all the resources of a type matching the pattern,
+ * with a single update. Tags which don't exist yet are created.
+ * Response: [BatchUpdateTagsResponse].
+ */
+export interface BatchAddTags {
+	/** The type of resource to tag. */
+	resource_type: ResourceTarget["type"];
+	/**
+	 * Id or name or wildcard pattern or regex.
+	 * Supports multiline and comma delineated combinations of the above.
+	 * 
+	 * Example:
+	 * ```text
+	 * # match all foo-* deployments
+	 * foo-*
+	 * # add some more
+	 * extra-deployment-1, extra-deployment-2
+	 * ```
+	 */
+	pattern: string;
+	/** The names or ids of the tags to add. */
+	tags: string[];
+}
+
 /** Builds multiple Repos in parallel that match pattern. Response: [BatchExecutionResponse]. */
 export interface BatchBuildRepo {
 	/**
@@ -4388,6 +4887,22 @@ export interface BatchPullStack {
 	pattern: string;
 }
 
+/**
+ * Remove tags from all the resources of a type matching the pattern,
+ * with a single update. Response: [BatchUpdateTagsResponse].
+ */
+export interface BatchRemoveTags {
+	/** The type of resource to untag. */
+	resource_type: ResourceTarget["type"];
+	/**
+	 * Id or name or wildcard pattern or regex.
+	 * Supports multiline and comma delineated combinations of the above.
+	 */
+	pattern: string;
+	/** The names or ids of the tags to remove. */
+	tags: string[];
+}
+
 /** Runs multiple Actions in parallel that match pattern. Response: [BatchExecutionResponse] */
 export interface BatchRunAction {
 	/**
@@ -4439,6 +4954,17 @@ export interface BatchRunProcedure {
 	pattern: string;
 }
 
+/** Response for [BatchAddTags] and [BatchRemoveTags]. */
+export interface BatchUpdateTagsResponse {
+	/** The names of the matched resources whose tags changed. */
+	updated: string[];
+	/**
+	 * The names of the matched resources which already
+	 * had (or didn't have) all the tags.
+	 */
+	unchanged: string[];
+}
+
 /**
  * Builds the target repo, using the attached builder. Response: [Update].
  * 
@@ -4682,6 +5208,24 @@ export interface ContainerCpuStats {
 	throttling_data?: ContainerThrottlingData;
 }
 
+/**
+ * A significant container event streamed from Periphery,
+ * used to refresh the server status without waiting for the next poll.
+ */
+export interface ContainerEvent {
+	/** The container name. */
+	name: string;
+	/**
+	 * The docker event action, eg `die`, `oom`,
+	 * or `health_status: unhealthy`.
+	 */
+	action: string;
+	/** The exit code reported with `die` events. */
+	exit_code?: string;
+	/** Unix timestamp in milliseconds. */
+	ts: I64;
+}
+
 /**
  * Aggregates all memory stats since container inception on Linux.
  * Windows returns stats for commit and private working set only.
@@ -4795,6 +5339,11 @@ export interface CopyAction {
 	name: string;
 	/** The id of the action to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new action.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialActionConfig;
 }
 
 /**
@@ -4806,6 +5355,11 @@ export interface CopyAlerter {
 	name: string;
 	/** The id of the alerter to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new alerter.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialAlerterConfig;
 }
 
 /**
@@ -4817,9 +5371,20 @@ export interface CopyBuild {
 	name: string;
 	/** The id of the build to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new build.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialBuildConfig;
 }
 
-/**
+/** Partial representation of [BuilderConfig] */
+export type PartialBuilderConfig = 
+	| { type: "Url", params: _PartialUrlBuilderConfig }
+	| { type: "Server", params: _PartialServerBuilderConfig }
+	| { type: "Aws", params: _PartialAwsBuilderConfig };
+
+/**
  * Creates a new builder with given `name` and the configuration
  * of the builder at the given `id`. Response: [Builder]
  */
@@ -4828,6 +5393,11 @@ export interface CopyBuilder {
 	name: string;
 	/** The id of the builder to copy. */
 	id: string;
+	/**
+	 * Config to use instead of the copied config.
+	 * If the builder type is the same, only the given fields are replaced.
+	 */
+	config?: PartialBuilderConfig;
 }
 
 /**
@@ -4839,6 +5409,11 @@ export interface CopyDeployment {
 	name: string;
 	/** The id of the deployment to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new deployment.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialDeploymentConfig;
 }
 
 /**
@@ -4850,6 +5425,11 @@ export interface CopyProcedure {
 	name: string;
 	/** The id of the procedure to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new procedure.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialProcedureConfig;
 }
 
 /**
@@ -4861,6 +5441,11 @@ export interface CopyRepo {
 	name: string;
 	/** The id of the repo to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new repo.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialRepoConfig;
 }
 
 /**
@@ -4872,6 +5457,11 @@ export interface CopyResourceSync {
 	name: string;
 	/** The id of the sync to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new sync.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialResourceSyncConfig;
 }
 
 /**
@@ -4883,6 +5473,11 @@ export interface CopyServer {
 	name: string;
 	/** The id of the server to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new server.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialServerConfig;
 }
 
 /**
@@ -4894,6 +5489,11 @@ export interface CopyStack {
 	name: string;
 	/** The id of the stack to copy. */
 	id: string;
+	/**
+	 * Config fields to override on the new stack.
+	 * Fields which aren't given are copied.
+	 */
+	config?: _PartialStackConfig;
 }
 
 /** Create a action. Response: [Action]. */
@@ -4971,12 +5571,6 @@ export interface CreateBuildWebhook {
 	build: string;
 }
 
-/** Partial representation of [BuilderConfig] */
-export type PartialBuilderConfig = 
-	| { type: "Url", params: _PartialUrlBuilderConfig }
-	| { type: "Server", params: _PartialServerBuilderConfig }
-	| { type: "Aws", params: _PartialAwsBuilderConfig };
-
 /** Create a builder. Response: [Builder]. */
 export interface CreateBuilder {
 	/** The name given to newly created builder. */
@@ -5117,6 +5711,27 @@ export interface CreateStack {
 	config?: _PartialStackConfig;
 }
 
+/**
+ * Create a Stack from a compose project already running on a server,
+ * as listed by [ListComposeProjects]. Response: [Stack].
+ * 
+ * The stack uses `files_on_host`, pointing at the project's compose files,
+ * and the project name, so Komodo picks up the existing containers.
+ * 
+ * [ListComposeProjects]: crate::api::read::ListComposeProjects
+ */
+export interface CreateStackFromComposeProject {
+	/** The name of the compose project. */
+	project: string;
+	/** The server id or name on which the project exists. */
+	server: string;
+	/**
+	 * The name given to the new stack.
+	 * If empty, uses the project name.
+	 */
+	name?: string;
+}
+
 export enum StackWebhookAction {
 	Refresh = "Refresh",
 	Deploy = "Deploy",
@@ -5493,6 +6108,10 @@ export interface DeleteVolume {
  * it will be stopped and removed using `docker container rm ${container_name}`.
  * 3. The container will be run using `docker run {...params}`,
  * where params are determined by the deployment's configuration.
+ * 
+ * With the `StartFirst` deploy strategy, the new container is instead run
+ * alongside the existing one, and the existing container is only removed
+ * once the new container is healthy.
  */
 export interface Deploy {
 	/** Name or id */
@@ -5593,6 +6212,35 @@ export interface DiscordAlerterEndpoint {
 	url: string;
 }
 
+export interface DockerCapabilities {
+	/** `docker stop --signal`, added in docker 23.0. */
+	stop_signal: boolean;
+	/** `docker buildx` is installed. */
+	buildx: boolean;
+	/** `docker compose` (v2 plugin) is installed. */
+	compose_v2: boolean;
+}
+
+/**
+ * The docker versions on a server,
+ * and the features Komodo can use with them.
+ */
+export interface DockerVersion {
+	/** The docker engine version, eg `27.3.1`. */
+	version?: string;
+	/** The docker compose version, eg `2.29.7`. */
+	compose_version?: string;
+	/** The docker buildx version, eg `0.17.1`. */
+	buildx_version?: string;
+	/** Whether the engine is at least [MINIMUM_DOCKER_VERSION]. */
+	supported: boolean;
+	capabilities: DockerCapabilities;
+}
+
+/** **Admin only.** End the active change freeze. Response: [NoData]. */
+export interface EndChangeFreeze {
+}
+
 export interface EnvironmentVar {
 	variable: string;
 	value: string;
@@ -5686,6 +6334,18 @@ export interface ExportAllResourcesToToml {
 	 * Accepts tag name or id. Empty array will not filter by tag.
 	 */
 	tags?: string[];
+	/**
+	 * Filter resources by type.
+	 * Empty array will include all resource types.
+	 */
+	resource_types?: ResourceTarget["type"][];
+	/**
+	 * Filter resources by name.
+	 * Supports wildcard syntax, or regex if wrapped with "\\".
+	 * Multiple patterns can be comma or newline separated.
+	 * Empty string will not filter by name.
+	 */
+	pattern?: string;
 	/**
 	 * Whether to include variables in the exported contents.
 	 * Default: false
@@ -5763,6 +6423,17 @@ export interface FullContainerStats {
 	memory_stats?: ContainerMemoryStats;
 	/** Network statistics for the container per interface.  This field is omitted if the container has no networking enabled. */
 	networks?: Record<string, ContainerNetworkStats>;
+	/**
+	 * The CPU usage between `precpu_stats` and `cpu_stats`,
+	 * as a percentage of one CPU, matching `docker stats`.
+	 * Exceeds 100 when using multiple CPUs.
+	 */
+	cpu_perc?: number;
+	/**
+	 * The memory usage excluding the page cache,
+	 * as a percentage of the limit, matching `docker stats`.
+	 */
+	mem_perc?: number;
 }
 
 /** Get a specific action. Response: [Action]. */
@@ -5912,6 +6583,37 @@ export interface GetBuildsSummaryResponse {
 	unknown: number;
 }
 
+/**
+ * Get the active change freeze, if any.
+ * Response: [GetChangeFreezeResponse].
+ */
+export interface GetChangeFreeze {
+}
+
+/** Response for [GetChangeFreeze]. */
+export interface GetChangeFreezeResponse {
+	/** The active change freeze. Null if changes aren't frozen. */
+	freeze?: ChangeFreeze;
+}
+
+/**
+ * Generate a compose file approximating a set of containers
+ * on the server, for migrating them to a Stack.
+ * Response: [GetComposeFromContainersResponse].
+ */
+export interface GetComposeFromContainers {
+	/** Id or name */
+	server: string;
+	/** The container names */
+	containers: string[];
+}
+
+/** Response for [GetComposeFromContainers]. */
+export interface GetComposeFromContainersResponse {
+	/** The generated compose file contents. */
+	contents: string;
+}
+
 /**
  * Get the container log's tail, split by stdout/stderr.
  * Response: [Log].
@@ -5933,6 +6635,19 @@ export interface GetContainerLog {
 	timestamps?: boolean;
 }
 
+/**
+ * Get the recent stats samples of a container, oldest first.
+ * Periphery keeps a limited number of samples in memory,
+ * configured with `container_stats_history`.
+ * Response: [GetContainerStatsHistoryResponse].
+ */
+export interface GetContainerStatsHistory {
+	/** Id or name */
+	server: string;
+	/** The container name */
+	container: string;
+}
+
 /**
  * Get info about the core api configuration.
  * Response: [GetCoreInfoResponse].
@@ -6074,6 +6789,16 @@ export interface GetDockerContainersSummaryResponse {
 	unknown: number;
 }
 
+/**
+ * Get information about the docker daemon on the target server,
+ * such as the version, storage driver and cgroup version.
+ * Response: [DockerInfo].
+ */
+export interface GetDockerInfo {
+	/** Id or name */
+	server: string;
+}
+
 /**
  * Get a specific docker registry account.
  * Response: [GetDockerRegistryAccountResponse].
@@ -6140,6 +6865,20 @@ export interface GetHistoricalServerStatsResponse {
 	next_page?: number;
 }
 
+/**
+ * Get the JSON schema for a request type,
+ * for validating requests before sending them.
+ * Currently supports the [Execution], every request it wraps,
+ * and the types they use, eg `Execution`, `Deploy`, `CommitSync`.
+ * Response: [GetJsonSchemaResponse].
+ * 
+ * [Execution]: crate::api::execute::Execution
+ */
+export interface GetJsonSchema {
+	/** The name of the type, eg `Deploy`. */
+	type_name: string;
+}
+
 /**
  * Non authenticated route to see the available options
  * users have to login to Komodo, eg. local auth, github, google.
@@ -6158,6 +6897,11 @@ export interface GetLoginOptionsResponse {
 	google: boolean;
 	/** Whether OIDC login is enabled. */
 	oidc: boolean;
+	/**
+	 * The names of the additional OIDC providers.
+	 * Log in with them at `/auth/oidc/{name}/login`.
+	 */
+	oidc_providers?: string[];
 	/** Whether user registration (Sign Up) has been disabled */
 	registration_disabled: boolean;
 }
@@ -6572,6 +7316,41 @@ export interface GetVersionResponse {
 export interface GlobalAutoUpdate {
 }
 
+export interface ImageScanSeverityCounts {
+	critical: number;
+	high: number;
+	medium: number;
+	low: number;
+	unknown: number;
+}
+
+export interface ImageScanFinding {
+	/** The vulnerability id, eg `CVE-2024-1234`. */
+	id: string;
+	/** Uppercase severity, eg `CRITICAL`. */
+	severity: string;
+	/** The affected package. */
+	package: string;
+	/** The installed package version. */
+	installed_version: string;
+	/** The version which fixes the vulnerability, if any. */
+	fixed_version?: string;
+	/** Short description of the vulnerability. */
+	title?: string;
+}
+
+/** Vulnerability scan results for an image. */
+export interface ImageScan {
+	/** The image which was scanned. */
+	image: string;
+	/** The scanner used, `trivy` or `grype`. */
+	scanner: string;
+	/** The number of findings at each severity. */
+	counts: ImageScanSeverityCounts;
+	/** The most severe findings, most severe first. */
+	findings: ImageScanFinding[];
+}
+
 /**
  * Inspect the docker container associated with the Deployment.
  * Response: [Container].
@@ -6717,6 +7496,45 @@ export interface ListApiKeysForServiceUser {
 	user: string;
 }
 
+/**
+ * Get a paginated list of audit logs, ie the `/write` and `/execute`
+ * calls made, sorted by timestamp descending. Admin only.
+ * Response: [ListAuditLogsResponse].
+ */
+export interface ListAuditLogs {
+	/**
+	 * Pass a custom mongo query to filter the audit logs.
+	 * 
+	 * ## Example JSON
+	 * ```json
+	 * {
+	 * "username": "admin",
+	 * "api": "execute",
+	 * "success": false,
+	 * "ts": { "$gte": 1735689600000 }
+	 * }
+	 * ```
+	 * This will filter to only include failed executions
+	 * by the `admin` user since the given time.
+	 */
+	query?: MongoDocument;
+	/**
+	 * Retrieve older results by incrementing the page.
+	 * `page: 0` is default, and returns the most recent results.
+	 */
+	page?: U64;
+}
+
+/** Response for [ListAuditLogs]. */
+export interface ListAuditLogsResponse {
+	logs: AuditLog[];
+	/**
+	 * If more audit logs exist, the next page will be given here.
+	 * Otherwise it will be `null`
+	 */
+	next_page?: I64;
+}
+
 /**
  * Retrieve versions of the build that were built in the past and available for deployment,
  * sorted by most recent first.
@@ -6959,6 +7777,17 @@ export interface ListGitProvidersFromConfig {
 	target?: ResourceTarget;
 }
 
+/**
+ * List the most recent logins to an account, successful or failed,
+ * sorted by timestamp descending.
+ * Only admins can list the logins of other users.
+ * Response: [ListLoginEventsResponse]
+ */
+export interface ListLoginEvents {
+	/** Id or username. Default is the calling user. */
+	user?: string;
+}
+
 /**
  * List permissions for the calling user.
  * Does not include any permissions on UserGroups they may be a part of.
@@ -7109,6 +7938,8 @@ export interface UpdateListItem {
 	version?: Version;
 	/** Some unstructured, operation specific data. Not for general usage. */
 	other_data?: string;
+	/** Progress through the stages of a long running operation. */
+	progress?: UpdateProgress;
 }
 
 /** Response for [ListUpdates]. */
@@ -7145,6 +7976,16 @@ export interface ListUserTargetPermissions {
 export interface ListUsers {
 }
 
+/**
+ * List the resources which reference the variable / secret
+ * as `[[NAME]]` in their config. Resources which skip secret
+ * interpolation are not included. Response: [ListVariableReferencesResponse]
+ */
+export interface ListVariableReferences {
+	/** The name of the variable / secret. */
+	name: string;
+}
+
 /**
  * List all available global variables.
  * Response: [ListVariablesResponse]
@@ -7168,6 +8009,15 @@ export interface LoginLocalUser {
 	password: string;
 }
 
+/**
+ * Log out of all sessions for the calling user.
+ * All JWTs issued before this call are rejected,
+ * including the one used to make it. Api keys are unaffected.
+ * Response: [NoData]
+ */
+export interface LogoutEverywhere {
+}
+
 export interface NameAndId {
 	name: string;
 	id: string;
@@ -7357,12 +8207,124 @@ export interface PushoverAlerterEndpoint {
 	url: string;
 }
 
+/** A metric which can be queried with [QueryServerStats]. */
+export enum ServerStatsMetric {
+	CpuPerc = "CpuPerc",
+	LoadAverage = "LoadAverage",
+	MemUsedGb = "MemUsedGb",
+	MemTotalGb = "MemTotalGb",
+	DiskUsedGb = "DiskUsedGb",
+	DiskTotalGb = "DiskTotalGb",
+	NetworkIngressBytes = "NetworkIngressBytes",
+	NetworkEgressBytes = "NetworkEgressBytes",
+}
+
+/**
+ * Query historical server stats over a time range,
+ * optionally averaged over fixed intervals, for charting.
+ * Only the stats still kept (see `keep_stats_for_days`) are queried.
+ * Response: [QueryServerStatsResponse].
+ */
+export interface QueryServerStats {
+	/** Id or name */
+	server: string;
+	/**
+	 * Unix timestamp in milliseconds of the start of the range (inclusive).
+	 * Default: 1 day before `end_ts`.
+	 */
+	start_ts?: I64;
+	/**
+	 * Unix timestamp in milliseconds of the end of the range (exclusive).
+	 * Default: now.
+	 */
+	end_ts?: I64;
+	/**
+	 * Average the stats over intervals of this length.
+	 * If not provided, returns the raw stats.
+	 */
+	interval?: Timelength;
+	/** The metrics to include. If empty, includes all metrics. */
+	metrics?: ServerStatsMetric[];
+}
+
+/**
+ * The stats at a point in time, or averaged over an interval.
+ * Only the queried metrics are included.
+ */
+export interface ServerStatsPoint {
+	/**
+	 * Unix timestamp in milliseconds.
+	 * For intervals, the start of the interval.
+	 */
+	ts: I64;
+	/** The number of raw stats in the point. */
+	count: I64;
+	cpu_perc?: number;
+	/** The 1 minute load average */
+	load_average?: number;
+	mem_used_gb?: number;
+	mem_total_gb?: number;
+	disk_used_gb?: number;
+	disk_total_gb?: number;
+	network_ingress_bytes?: number;
+	network_egress_bytes?: number;
+}
+
+/** Response for [QueryServerStats]. */
+export interface QueryServerStatsResponse {
+	/** The points in the range, oldest first. */
+	points: ServerStatsPoint[];
+	/**
+	 * Whether the range had more points than can be returned at once.
+	 * Narrow the range or use a larger interval to get all of them.
+	 */
+	truncated: boolean;
+}
+
+/**
+ * Redeploys the target deployment only if its container
+ * has drifted from the deployment config, otherwise does nothing.
+ * Suitable for scheduled reconciliation. Response: [Update]
+ * 
+ * 1. Inspects the container and compares the image, environment,
+ * ports, and memory / cpu limits against the config.
+ * 2. If any differ, or the container isn't deployed, runs [Deploy].
+ */
+export interface ReconcileDeployment {
+	/** Name or id */
+	deployment: string;
+}
+
+/**
+ * Redeploys the running Deployments and Stacks which reference
+ * the variable / secret, so they pick up its new value after rotation.
+ * Response: [BatchExecutionResponse].
+ * 
+ * Use [ListVariableReferences][crate::api::read::ListVariableReferences]
+ * to see all the resources referencing the variable.
+ */
+export interface RedeployVariableReferences {
+	/** The name of the variable / secret. */
+	variable: string;
+}
+
 /** Trigger a refresh of the cached latest hash and message. */
 export interface RefreshBuildCache {
 	/** Id or name */
 	build: string;
 }
 
+/**
+ * Exchange a valid jwt for a new one, extending the session.
+ * Only available to users logged in with OIDC,
+ * when the provider issued a refresh token.
+ * The provider session is checked to still be active,
+ * and if it has ended, all the user's jwts are invalidated.
+ * Response: [RefreshJwtResponse].
+ */
+export interface RefreshJwt {
+}
+
 /** Trigger a refresh of the cached latest hash and message. */
 export interface RefreshRepoCache {
 	/** Id or name */
@@ -7572,6 +8534,50 @@ export interface RepoExecutionResponse {
 	commit_message?: string;
 }
 
+export enum ResourceEventKind {
+	/** The resource was created. */
+	Created = "Created",
+	/** The resource config or name was updated. */
+	Updated = "Updated",
+	/** The resource was deleted. */
+	Deleted = "Deleted",
+	/**
+	 * An execution on the resource completed,
+	 * eg a deploy, which may change its state.
+	 */
+	StateChanged = "StateChanged",
+}
+
+/**
+ * A resource lifecycle event sent on the `/events` stream.
+ * The server-sent event name is the [ResourceEventKind].
+ */
+export interface ResourceEvent {
+	/** What happened to the resource. */
+	kind: ResourceEventKind;
+	/** The resource the event is about. */
+	target: ResourceTarget;
+	/** The operation which caused the event. */
+	operation: Operation;
+	/** Whether the operation was successful. */
+	success: boolean;
+	/** The id of the Update recording the operation. */
+	update_id: string;
+	/** Timestamp the operation started. */
+	ts: I64;
+}
+
+/**
+ * Query to filter the resource event stream (server-sent events) at `/events`.
+ * Passing empty is the same as not filtering by that field.
+ */
+export interface ResourceEventsQuery {
+	/** Comma separated resource types, eg `Stack,Deployment`. */
+	resource_types?: string;
+	/** Comma separated event kinds, eg `Created,Deleted`. */
+	kinds?: string;
+}
+
 export interface ResourceToml<PartialConfig> {
 	/** The resource name. Required */
 	name: string;
@@ -7668,6 +8674,19 @@ export interface RestartStack {
 	services?: string[];
 }
 
+/**
+ * Generate a new secret for one of the calling user's api keys.
+ * The key stays the same, and the old secret stops working.
+ * Response: [RotateApiKeySecretResponse].
+ * 
+ * Note. After the response is served, there will be no way
+ * to get the secret later.
+ */
+export interface RotateApiKeySecret {
+	/** The key to rotate the secret of. */
+	key: string;
+}
+
 /** Runs the target Action. Response: [Update] */
 export interface RunAction {
 	/** Id or name */
@@ -7752,6 +8771,19 @@ export interface RunSync {
 	resources?: string[];
 }
 
+/**
+ * Search across all resource types by name, tags, description,
+ * and key config fields, like the Deployment image or the Stack repo.
+ * Only includes resources the user can read.
+ * Response: [SearchResponse].
+ */
+export interface Search {
+	/** The text to search for. Case insensitive. */
+	query: string;
+	/** The maximum number of results. Default: 50. Max: 500. */
+	limit: I64;
+}
+
 export enum SearchCombinator {
 	Or = "Or",
 	And = "And",
@@ -7918,6 +8950,12 @@ export interface SignUpLocalUser {
 	 * This cannot be retreived later.
 	 */
 	password: string;
+	/**
+	 * The one time token printed to the Core logs,
+	 * required to claim the first admin account
+	 * when `require_bootstrap_token` is enabled.
+	 */
+	bootstrap_token?: string;
 }
 
 /** Info for network interface usage. */
@@ -7947,6 +8985,22 @@ export interface StartAllContainers {
 	server: string;
 }
 
+/**
+ * **Admin only.** Freeze changes across all resources.
+ * Until the freeze ends, all executions are rejected
+ * unless run by a super admin.
+ * Replaces any existing freeze. Response: [ChangeFreeze].
+ */
+export interface StartChangeFreeze {
+	/** Why changes are frozen. */
+	reason: string;
+	/**
+	 * Timestamp to end the freeze automatically.
+	 * Default: 0, which lasts until [EndChangeFreeze] is called.
+	 */
+	expires?: I64;
+}
+
 /**
  * Starts the container on the target server. Response: [Update]
  * 
@@ -8029,6 +9083,38 @@ export interface StopStack {
 	services?: string[];
 }
 
+/**
+ * System stats averaged over an hour or a day.
+ * These are kept longer than the raw [SystemStatsRecord]s
+ * to show long term trends.
+ */
+export interface SystemStatsRollup {
+	/** Unix timestamp in milliseconds of the start of the hour / day */
+	ts: I64;
+	/** Server id */
+	sid: string;
+	/** The number of records which were averaged */
+	count: I64;
+	/** Average cpu usage percentage */
+	cpu_perc: number;
+	/** Maximum cpu usage percentage */
+	cpu_perc_max: number;
+	/** Average memory used in GB */
+	mem_used_gb: number;
+	/** Maximum memory used in GB */
+	mem_used_gb_max: number;
+	/** Average total memory in GB */
+	mem_total_gb: number;
+	/** Average disk used in GB */
+	disk_used_gb: number;
+	/** Average total disk size in GB */
+	disk_total_gb: number;
+	/** Average network ingress in bytes */
+	network_ingress_bytes: number;
+	/** Average network egress in bytes */
+	network_egress_bytes: number;
+}
+
 export interface TerminationSignalLabel {
 	signal: TerminationSignal;
 	label: string;
@@ -8286,6 +9372,11 @@ export interface UpdateResourceMeta {
 	 * or null for no update
 	 */
 	template?: boolean;
+	/**
+	 * New locked value (true or false),
+	 * or null for no update. Admin only.
+	 */
+	locked?: boolean;
 	/**
 	 * The exact tags to set,
 	 * or null for no update
@@ -8440,12 +9531,100 @@ export interface UrlBuilderConfig {
 	passkey?: string;
 }
 
+/**
+ * Validate a schedule expression, and preview the next run times.
+ * Response: [ValidateScheduleResponse].
+ */
+export interface ValidateSchedule {
+	/**
+	 * Whether the schedule is a regular CRON expression,
+	 * or uses the english to CRON parser.
+	 */
+	format?: ScheduleFormat;
+	/** The schedule expression. */
+	schedule: string;
+	/** Optional. A TZ Identifier. If not provided, will use Core timezone. */
+	timezone?: string;
+	/** The number of next run times to return. Default: 5. Max: 100. */
+	count: I64;
+}
+
+/** Response for [ValidateSchedule]. */
+export interface ValidateScheduleResponse {
+	/** Whether the schedule and timezone are valid. */
+	valid: boolean;
+	/** The reason the schedule is invalid, if it is. */
+	error?: string;
+	/** The next run times in unix ms. */
+	next_runs: I64[];
+}
+
+export enum WaitCondition {
+	/**
+	 * The Deployment container is running, and healthy
+	 * if it has a healthcheck configured.
+	 */
+	DeploymentHealthy = "DeploymentHealthy",
+	/**
+	 * A GET request to the url returns 200.
+	 * Redirects are not followed, and the url must resolve to
+	 * a public address unless allowed by `wait_until_allowed_ips`.
+	 */
+	HttpOk = "HttpOk",
+	/** Waits for `timeout_ms`, then proceeds. */
+	Duration = "Duration",
+}
+
+/**
+ * Waits until the condition is met, failing if it
+ * isn't met before the timeout.
+ */
+export interface WaitUntil {
+	/** The condition to wait for. */
+	condition?: WaitCondition;
+	/**
+	 * The target of the condition.
+	 * - `DeploymentHealthy`: The Deployment name or id.
+	 * - `HttpOk`: The url to send GET requests to.
+	 * - `Duration`: Not used.
+	 */
+	target?: string;
+	/**
+	 * Fail if the condition isn't met within this time.
+	 * For `Duration`, the time to wait.
+	 */
+	timeout_ms: I64;
+	/** The time between condition checks. */
+	interval_ms: I64;
+}
+
 /** Update dockerfile contents in Files on Server or Git Repo mode. Response: [Update]. */
 export interface WriteBuildFileContents {
 	/** The name or id of the target Build. */
 	build: string;
 	/** The dockerfile contents to write. */
 	contents: string;
+	/**
+	 * Lint the dockerfile before writing.
+	 * Only for Files on Host Builds.
+	 */
+	validate?: boolean;
+}
+
+/**
+ * How to handle the local repo having diverged from the remote branch
+ * before committing to it.
+ */
+export enum GitSyncStrategy {
+	/** Fail without writing. */
+	Fail = "Fail",
+	/** Rebase the local commits onto the remote branch. */
+	Rebase = "Rebase",
+	/**
+	 * Reset the local repo to the remote branch,
+	 * discarding the local commits.
+	 */
+	Force = "Force",
 }
 
 /** Update file contents in Files on Server or Git Repo mode. Response: [Update]. */
@@ -8459,6 +9638,17 @@ export interface WriteStackFileContents {
 	file_path: string;
 	/** The contents to write. */
 	contents: string;
+	/**
+	 * Validate the contents with `docker compose config` before writing.
+	 * Only for Files on Host Stacks.
+	 */
+	validate?: boolean;
+	/**
+	 * How to handle the local repo having diverged from the remote
+	 * branch, which would cause the push to fail.
+	 * Only for Git Repo Stacks.
+	 */
+	sync_strategy?: GitSyncStrategy;
 }
 
 /** Rename the stack at id to the given name. Response: [Update]. */
@@ -8481,7 +9671,8 @@ export type AuthRequest =
 	| { type: "SignUpLocalUser", params: SignUpLocalUser }
 	| { type: "LoginLocalUser", params: LoginLocalUser }
 	| { type: "ExchangeForJwt", params: ExchangeForJwt }
-	| { type: "GetUser", params: GetUser };
+	| { type: "GetUser", params: GetUser }
+	| { type: "RefreshJwt", params: RefreshJwt };
 
 /** Days of the week */
 export enum DayOfWeek {
@@ -8532,6 +9723,7 @@ export type ExecuteRequest =
 	| { type: "RunStackService", params: RunStackService }
 	| { type: "Deploy", params: Deploy }
 	| { type: "BatchDeploy", params: BatchDeploy }
+	| { type: "ReconcileDeployment", params: ReconcileDeployment }
 	| { type: "PullDeployment", params: PullDeployment }
 	| { type: "StartDeployment", params: StartDeployment }
 	| { type: "RestartDeployment", params: RestartDeployment }
@@ -8559,7 +9751,8 @@ export type ExecuteRequest =
 	| { type: "RunSync", params: RunSync }
 	| { type: "ClearRepoCache", params: ClearRepoCache }
 	| { type: "BackupCoreDatabase", params: BackupCoreDatabase }
-	| { type: "GlobalAutoUpdate", params: GlobalAutoUpdate };
+	| { type: "GlobalAutoUpdate", params: GlobalAutoUpdate }
+	| { type: "RedeployVariableReferences", params: RedeployVariableReferences };
 
 /**
  * One representative IANA zone for each distinct base UTC offset in the tz database.
@@ -8650,15 +9843,18 @@ export enum IanaTimezone {
 export type ReadRequest = 
 	| { type: "GetVersion", params: GetVersion }
 	| { type: "GetCoreInfo", params: GetCoreInfo }
+	| { type: "GetJsonSchema", params: GetJsonSchema }
 	| { type: "ListSecrets", params: ListSecrets }
 	| { type: "ListGitProvidersFromConfig", params: ListGitProvidersFromConfig }
 	| { type: "ListDockerRegistriesFromConfig", params: ListDockerRegistriesFromConfig }
+	| { type: "Search", params: Search }
 	| { type: "GetUsername", params: GetUsername }
 	| { type: "GetPermission", params: GetPermission }
 	| { type: "FindUser", params: FindUser }
 	| { type: "ListUsers", params: ListUsers }
 	| { type: "ListApiKeys", params: ListApiKeys }
 	| { type: "ListApiKeysForServiceUser", params: ListApiKeysForServiceUser }
+	| { type: "ListLoginEvents", params: ListLoginEvents }
 	| { type: "ListPermissions", params: ListPermissions }
 	| { type: "ListUserTargetPermissions", params: ListUserTargetPermissions }
 	| { type: "GetUserGroup", params: GetUserGroup }
@@ -8674,18 +9870,22 @@ export type ReadRequest =
 	| { type: "ListActions", params: ListActions }
 	| { type: "ListFullActions", params: ListFullActions }
 	| { type: "ListSchedules", params: ListSchedules }
+	| { type: "ValidateSchedule", params: ValidateSchedule }
 	| { type: "GetServersSummary", params: GetServersSummary }
 	| { type: "GetServer", params: GetServer }
 	| { type: "GetServerState", params: GetServerState }
 	| { type: "GetPeripheryVersion", params: GetPeripheryVersion }
 	| { type: "GetServerActionState", params: GetServerActionState }
 	| { type: "GetHistoricalServerStats", params: GetHistoricalServerStats }
+	| { type: "QueryServerStats", params: QueryServerStats }
 	| { type: "ListServers", params: ListServers }
 	| { type: "ListFullServers", params: ListFullServers }
 	| { type: "InspectDockerContainer", params: InspectDockerContainer }
+	| { type: "GetComposeFromContainers", params: GetComposeFromContainers }
 	| { type: "GetResourceMatchingContainer", params: GetResourceMatchingContainer }
 	| { type: "GetContainerLog", params: GetContainerLog }
 	| { type: "SearchContainerLog", params: SearchContainerLog }
+	| { type: "GetContainerStatsHistory", params: GetContainerStatsHistory }
 	| { type: "InspectDockerNetwork", params: InspectDockerNetwork }
 	| { type: "InspectDockerImage", params: InspectDockerImage }
 	| { type: "ListDockerImageHistory", params: ListDockerImageHistory }
@@ -8699,6 +9899,7 @@ export type ReadRequest =
 	| { type: "ListComposeProjects", params: ListComposeProjects }
 	| { type: "ListTerminals", params: ListTerminals }
 	| { type: "GetSystemInformation", params: GetSystemInformation }
+	| { type: "GetDockerInfo", params: GetDockerInfo }
 	| { type: "GetSystemStats", params: GetSystemStats }
 	| { type: "ListSystemProcesses", params: ListSystemProcesses }
 	| { type: "GetStacksSummary", params: GetStacksSummary }
@@ -8761,8 +9962,11 @@ export type ReadRequest =
 	| { type: "ListUpdates", params: ListUpdates }
 	| { type: "ListAlerts", params: ListAlerts }
 	| { type: "GetAlert", params: GetAlert }
+	| { type: "ListAuditLogs", params: ListAuditLogs }
+	| { type: "GetChangeFreeze", params: GetChangeFreeze }
 	| { type: "GetVariable", params: GetVariable }
 	| { type: "ListVariables", params: ListVariables }
+	| { type: "ListVariableReferences", params: ListVariableReferences }
 	| { type: "GetGitProviderAccount", params: GetGitProviderAccount }
 	| { type: "ListGitProviderAccounts", params: ListGitProviderAccounts }
 	| { type: "GetDockerRegistryAccount", params: GetDockerRegistryAccount }
@@ -8811,7 +10015,9 @@ export type UserRequest =
 	| { type: "PushRecentlyViewed", params: PushRecentlyViewed }
 	| { type: "SetLastSeenUpdate", params: SetLastSeenUpdate }
 	| { type: "CreateApiKey", params: CreateApiKey }
-	| { type: "DeleteApiKey", params: DeleteApiKey };
+	| { type: "DeleteApiKey", params: DeleteApiKey }
+	| { type: "RotateApiKeySecret", params: RotateApiKeySecret }
+	| { type: "LogoutEverywhere", params: LogoutEverywhere };
 
 export type WriteRequest = 
 	| { type: "CreateLocalUser", params: CreateLocalUser }
@@ -8845,6 +10051,7 @@ export type WriteRequest =
 	| { type: "DeleteAllTerminals", params: DeleteAllTerminals }
 	| { type: "CreateStack", params: CreateStack }
 	| { type: "CopyStack", params: CopyStack }
+	| { type: "CreateStackFromComposeProject", params: CreateStackFromComposeProject }
 	| { type: "DeleteStack", params: DeleteStack }
 	| { type: "UpdateStack", params: UpdateStack }
 	| { type: "RenameStack", params: RenameStack }
@@ -8909,6 +10116,10 @@ export type WriteRequest =
 	| { type: "DeleteTag", params: DeleteTag }
 	| { type: "RenameTag", params: RenameTag }
 	| { type: "UpdateTagColor", params: UpdateTagColor }
+	| { type: "BatchAddTags", params: BatchAddTags }
+	| { type: "BatchRemoveTags", params: BatchRemoveTags }
+	| { type: "StartChangeFreeze", params: StartChangeFreeze }
+	| { type: "EndChangeFreeze", params: EndChangeFreeze }
 	| { type: "CreateVariable", params: CreateVariable }
 	| { type: "UpdateVariableValue", params: UpdateVariableValue }
 	| { type: "UpdateVariableDescription", params: UpdateVariableDescription }
@@ -61,6 +61,10 @@ pub struct WriteDockerfileContentsToHost {
   pub dockerfile_path: String,
   /// The contents to write.
   pub contents: String,
+  /// Lint the contents first,
+  /// returning the errors without writing if they are invalid.
+  #[serde(default)]
+  pub validate: bool,
 }
 
 //
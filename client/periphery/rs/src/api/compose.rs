@@ -1,5 +1,6 @@
 use komodo_client::entities::{
-  FileContents, RepoExecutionResponse, SearchCombinator,
+  FileContents, GitSyncStrategy, RepoExecutionResponse,
+  SearchCombinator,
   repo::Repo,
   stack::{
     ComposeProject, Stack, StackFileDependency,
@@ -111,6 +112,10 @@ pub struct WriteComposeContentsToHost {
   pub file_path: String,
   /// The contents to write.
   pub contents: String,
+  /// Validate the contents with `docker compose config` first,
+  /// returning the errors without writing if they are invalid.
+  #[serde(default)]
+  pub validate: bool,
 }
 
 //
@@ -133,6 +138,10 @@ pub struct WriteCommitComposeContents {
   pub contents: String,
   /// If provided, use it to login in. Otherwise check periphery local git providers.
   pub git_token: Option<String>,
+  /// How to handle the local repo having diverged from the remote
+  /// branch, which would cause the push to fail.
+  #[serde(default)]
+  pub sync_strategy: GitSyncStrategy,
 }
 
 //
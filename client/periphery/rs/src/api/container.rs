@@ -2,7 +2,7 @@ use komodo_client::entities::{
   SearchCombinator, TerminationSignal,
   deployment::Deployment,
   docker::{
-    container::{Container, ContainerStats},
+    container::{Container, ContainerStats, ContainerStatsSample},
     stats::FullContainerStats,
   },
   update::Log,
@@ -21,6 +21,17 @@ pub struct InspectContainer {
 
 //
 
+/// Generate a compose file approximating the given containers.
+/// Response: The compose file contents.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(String)]
+#[error(serror::Error)]
+pub struct GetComposeFromContainers {
+  pub names: Vec<String>,
+}
+
+//
+
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
 #[response(Log)]
 #[error(serror::Error)]
@@ -72,6 +83,16 @@ pub struct GetContainerStatsList {}
 
 //
 
+/// The recent stats samples of the container, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Vec<ContainerStatsSample>)]
+#[error(serror::Error)]
+pub struct GetContainerStatsHistory {
+  pub name: String,
+}
+
+//
+
 //
 
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
@@ -210,3 +231,12 @@ pub struct UnpauseAllContainers {}
 #[response(Vec<Log>)]
 #[error(serror::Error)]
 pub struct StopAllContainers {}
+
+//
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConnectContainerEventsQuery {
+  /// Use [CreateTerminalAuthToken][super::terminal::CreateTerminalAuthToken]
+  /// to create a single-use token to send in the query.
+  pub token: String,
+}
@@ -0,0 +1,84 @@
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+
+//
+
+/// Diff new contents against the current contents
+/// of the file on the host, eg before writing them.
+/// The file must be inside the stack or repo directory,
+/// or one of the configured `allowed_file_directories`.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(DiffFileContentsResponse)]
+#[error(serror::Error)]
+pub struct DiffFileContents {
+  /// Absolute path of the file on the host.
+  pub path: String,
+  /// The contents to compare against the current file.
+  pub new_contents: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiffFileContentsResponse {
+  /// Whether the file exists on the host.
+  /// If not, the diff is against empty contents.
+  pub exists: bool,
+  /// Whether the current file is binary, ie not UTF-8 text.
+  /// If so, no diff is made.
+  #[serde(default)]
+  pub binary: bool,
+  /// Unified diff from the current to the new contents.
+  /// Empty if they are the same, or the current file is binary.
+  pub diff: String,
+}
+
+//
+
+/// List the entries of a directory on the host.
+/// The directory must be inside the stack or repo directory,
+/// or one of the configured `allowed_file_directories`.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Vec<DirEntry>)]
+#[error(serror::Error)]
+pub struct ListDir {
+  /// Absolute path of the directory on the host.
+  pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DirEntry {
+  /// The file or directory name.
+  pub name: String,
+  /// Whether the entry is a directory.
+  pub is_dir: bool,
+  /// Whether the entry is a symlink.
+  /// Symlinks aren't followed when listing.
+  pub is_symlink: bool,
+  /// The size of the file in bytes.
+  pub size: u64,
+  /// Last modified unix timestamp in milliseconds, if available.
+  pub modified: Option<i64>,
+}
+
+//
+
+/// Read a file on the host.
+/// The file must be inside the stack or repo directory,
+/// or one of the configured `allowed_file_directories`.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(ReadFileResponse)]
+#[error(serror::Error)]
+pub struct ReadFile {
+  /// Absolute path of the file on the host.
+  pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReadFileResponse {
+  /// The file contents, lossily converted to UTF-8.
+  /// Only the beginning of large files is returned.
+  pub contents: String,
+  /// The full size of the file in bytes.
+  pub size: u64,
+  /// Whether the contents were cut off at the size limit.
+  pub truncated: bool,
+}
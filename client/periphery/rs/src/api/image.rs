@@ -1,5 +1,5 @@
 use komodo_client::entities::{
-  docker::image::{Image, ImageHistoryResponseItem},
+  docker::image::{Image, ImageHistoryResponseItem, ImageScan},
   update::Log,
 };
 use resolver_api::Resolve;
@@ -25,6 +25,18 @@ pub struct ImageHistory {
 
 //
 
+/// Scan the image for vulnerabilities
+/// using the scanner configured on Periphery.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(ImageScan)]
+#[error(serror::Error)]
+pub struct ScanImage {
+  /// Id or name
+  pub image: String,
+}
+
+//
+
 #[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
 #[response(Log)]
 #[error(serror::Error)]
@@ -53,3 +65,29 @@ pub struct DeleteImage {
 #[response(Log)]
 #[error(serror::Error)]
 pub struct PruneImages {}
+
+//
+
+/// Save the image to a tar archive on the host with `docker save`,
+/// to move it to another host without a registry.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct SaveImage {
+  /// Id or name
+  pub image: String,
+  /// Absolute path of the archive to write on the host.
+  pub dest_path: String,
+}
+
+//
+
+/// Load images from a tar archive on the host with `docker load`,
+/// as created by [SaveImage].
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct LoadImage {
+  /// Absolute path of the archive to read on the host.
+  pub src_path: String,
+}
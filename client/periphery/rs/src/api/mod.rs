@@ -2,8 +2,11 @@ use komodo_client::entities::{
   SystemCommand,
   config::{DockerRegistry, GitProvider},
   docker::{
-    container::ContainerListItem, image::ImageListItem,
-    network::NetworkListItem, volume::VolumeListItem,
+    container::ContainerListItem,
+    image::ImageListItem,
+    info::{DockerInfo, DockerVersion},
+    network::NetworkListItem,
+    volume::VolumeListItem,
   },
   stack::ComposeProject,
   update::Log,
@@ -15,10 +18,12 @@ use serror::Serror;
 pub mod build;
 pub mod compose;
 pub mod container;
+pub mod files;
 pub mod git;
 pub mod image;
 pub mod network;
 pub mod stats;
+pub mod swarm;
 pub mod terminal;
 pub mod volume;
 
@@ -42,13 +47,81 @@ pub struct GetVersion {}
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetVersionResponse {
   pub version: String,
+  /// The docker versions and capabilities.
+  /// None if they couldn't be detected, or Periphery is older.
+  #[serde(default)]
+  pub docker: Option<DockerVersion>,
 }
 
+/// Get information about the docker daemon.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(DockerInfo)]
+#[error(serror::Error)]
+pub struct GetDockerInfo {}
+
+//
+
 /// Returns all containers, networks, images, compose projects
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
 #[response(GetDockerListsResponse)]
 #[error(serror::Error)]
-pub struct GetDockerLists {}
+pub struct GetDockerLists {
+  /// Whether to list the containers.
+  /// Default: true
+  #[serde(default = "default_include")]
+  pub include_containers: bool,
+  /// Whether to list the networks.
+  /// Default: true
+  #[serde(default = "default_include")]
+  pub include_networks: bool,
+  /// Whether to list the images.
+  /// Default: true
+  #[serde(default = "default_include")]
+  pub include_images: bool,
+  /// Whether to list the volumes.
+  /// Default: true
+  #[serde(default = "default_include")]
+  pub include_volumes: bool,
+  /// Whether to list the compose projects.
+  /// Default: true
+  #[serde(default = "default_include")]
+  pub include_projects: bool,
+  /// Narrow down the returned images.
+  #[serde(default)]
+  pub images: DockerListFilter,
+  /// Narrow down the returned volumes.
+  #[serde(default)]
+  pub volumes: DockerListFilter,
+}
+
+fn default_include() -> bool {
+  true
+}
+
+/// Narrows down a docker list, eg to keep the payload small
+/// on hosts with thousands of dangling images.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct DockerListFilter {
+  /// If set, only return items which are (true)
+  /// or aren't (false) in use by a container.
+  #[serde(default)]
+  pub in_use: Option<bool>,
+  /// If set, return at most this many items.
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+impl DockerListFilter {
+  pub fn includes(&self, in_use: bool) -> bool {
+    self.in_use.is_none_or(|filter| filter == in_use)
+  }
+
+  pub fn truncate<T>(&self, items: &mut Vec<T>) {
+    if let Some(limit) = self.limit {
+      items.truncate(limit);
+    }
+  }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetDockerListsResponse {
@@ -99,3 +172,62 @@ pub struct PruneSystem {}
 pub struct RunCommand {
   pub command: SystemCommand,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn docker_list_filter_in_use() {
+    let all = DockerListFilter::default();
+    assert!(all.includes(true) && all.includes(false));
+    let in_use = DockerListFilter {
+      in_use: Some(true),
+      ..Default::default()
+    };
+    assert!(in_use.includes(true));
+    assert!(!in_use.includes(false));
+    let unused = DockerListFilter {
+      in_use: Some(false),
+      ..Default::default()
+    };
+    assert!(!unused.includes(true));
+    assert!(unused.includes(false));
+  }
+
+  #[test]
+  fn docker_list_filter_limit() {
+    let mut items = vec![1, 2, 3];
+    DockerListFilter::default().truncate(&mut items);
+    assert_eq!(items, [1, 2, 3]);
+    DockerListFilter {
+      limit: Some(2),
+      ..Default::default()
+    }
+    .truncate(&mut items);
+    assert_eq!(items, [1, 2]);
+  }
+
+  #[test]
+  fn get_docker_lists_filters_default() {
+    let GetDockerLists {
+      images, volumes, ..
+    } = serde_json::from_str("{}").unwrap();
+    assert!(images.in_use.is_none() && images.limit.is_none());
+    assert!(volumes.in_use.is_none() && volumes.limit.is_none());
+  }
+
+  #[test]
+  fn get_docker_lists_includes_all_by_default() {
+    let lists: GetDockerLists = serde_json::from_str("{}").unwrap();
+    assert!(lists.include_containers);
+    assert!(lists.include_networks);
+    assert!(lists.include_images);
+    assert!(lists.include_volumes);
+    assert!(lists.include_projects);
+    let lists: GetDockerLists =
+      serde_json::from_str(r#"{ "include_images": false }"#).unwrap();
+    assert!(!lists.include_images);
+    assert!(lists.include_containers);
+  }
+}
@@ -35,6 +35,40 @@ pub struct DeleteNetwork {
 
 //
 
+/// Attach a running container to a network.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct ConnectContainerToNetwork {
+  /// Network id or name
+  pub network: String,
+  /// Container id or name
+  pub container: String,
+  /// Network scoped aliases for the container.
+  #[serde(default)]
+  pub aliases: Vec<String>,
+  /// Static IPv4 address for the container on the network.
+  pub ip: Option<String>,
+}
+
+//
+
+/// Detach a container from a network.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct DisconnectContainerFromNetwork {
+  /// Network id or name
+  pub network: String,
+  /// Container id or name
+  pub container: String,
+  /// Force the container to disconnect.
+  #[serde(default)]
+  pub force: bool,
+}
+
+//
+
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
 #[response(Log)]
 #[error(serror::Error)]
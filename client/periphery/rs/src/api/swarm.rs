@@ -0,0 +1,117 @@
+use komodo_client::entities::{SearchCombinator, update::Log};
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+
+//
+
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct GetSwarmServiceLog {
+  /// Service id or name
+  pub service: String,
+  #[serde(default = "default_tail")]
+  pub tail: u64,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+}
+
+fn default_tail() -> u64 {
+  50
+}
+
+//
+
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct GetSwarmServiceLogSearch {
+  /// Service id or name
+  pub service: String,
+  pub terms: Vec<String>,
+  #[serde(default)]
+  pub combinator: SearchCombinator,
+  #[serde(default)]
+  pub invert: bool,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+}
+
+//
+
+/// Set the number of replicas of a swarm service.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct ScaleSwarmService {
+  /// Service id or name
+  pub service: String,
+  pub replicas: u32,
+}
+
+//
+
+/// Roll the swarm service tasks onto a new image,
+/// using the service's update config.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct UpdateSwarmServiceImage {
+  /// Service id or name
+  pub service: String,
+  /// The new image, eg `nginx:1.27`
+  pub image: String,
+}
+
+//
+
+/// Roll the swarm service back to its previous spec.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct RollbackSwarmService {
+  /// Service id or name
+  pub service: String,
+}
+
+//
+
+/// Set the swarm node availability to `drain`,
+/// so its tasks are moved to other nodes.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Vec<Log>)]
+#[error(serror::Error)]
+pub struct DrainSwarmNode {
+  /// Node id or hostname
+  pub node: String,
+  /// Wait until the node has no running tasks before returning.
+  #[serde(default)]
+  pub wait: bool,
+  /// Give up waiting after this many seconds.
+  /// Capped at [MAX_DRAIN_TIMEOUT_SECONDS].
+  /// Default: 300
+  #[serde(default = "default_drain_timeout_seconds")]
+  pub timeout_seconds: u64,
+}
+
+/// The longest [DrainSwarmNode] will wait for the tasks to stop,
+/// so the request doesn't outlive the connection to Core.
+pub const MAX_DRAIN_TIMEOUT_SECONDS: u64 = 600;
+
+fn default_drain_timeout_seconds() -> u64 {
+  300
+}
+
+//
+
+/// Set the swarm node availability to `active`,
+/// so tasks can be scheduled on it again.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct ActivateSwarmNode {
+  /// Node id or hostname
+  pub node: String,
+}
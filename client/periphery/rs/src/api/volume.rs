@@ -27,3 +27,30 @@ pub struct DeleteVolume {
 #[response(Log)]
 #[error(serror::Error)]
 pub struct PruneVolumes {}
+
+//
+
+/// Archive the volume contents to a `.tar.gz` file on the host.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct BackupVolume {
+  /// Volume name
+  pub volume: String,
+  /// Absolute path of the archive to write on the host.
+  pub dest_path: String,
+}
+
+//
+
+/// Extract a `.tar.gz` file on the host into the volume,
+/// as created by [BackupVolume].
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct RestoreVolume {
+  /// Volume name
+  pub volume: String,
+  /// Absolute path of the archive to read on the host.
+  pub src_path: String,
+}
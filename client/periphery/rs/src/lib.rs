@@ -1,4 +1,7 @@
-use std::{sync::OnceLock, time::Duration};
+use std::{
+  sync::OnceLock,
+  time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use reqwest::StatusCode;
@@ -10,13 +13,36 @@ pub mod api;
 
 mod terminal;
 
-fn periphery_http_client() -> &'static reqwest::Client {
+/// Called with the request type, duration, and success
+/// of every request to Periphery once it finishes.
+pub type RequestObserver = fn(&str, Duration, bool);
+
+static REQUEST_OBSERVER: OnceLock<RequestObserver> = OnceLock::new();
+
+/// Register an observer for Periphery request timings.
+pub fn set_request_observer(observer: RequestObserver) {
+  let _ = REQUEST_OBSERVER.set(observer);
+}
+
+fn periphery_http_client(
+  compression: bool,
+) -> &'static reqwest::Client {
   static PERIPHERY_HTTP_CLIENT: OnceLock<reqwest::Client> =
     OnceLock::new();
-  PERIPHERY_HTTP_CLIENT.get_or_init(|| {
+  static PERIPHERY_HTTP_CLIENT_GZIP: OnceLock<reqwest::Client> =
+    OnceLock::new();
+  let client = if compression {
+    &PERIPHERY_HTTP_CLIENT_GZIP
+  } else {
+    &PERIPHERY_HTTP_CLIENT
+  };
+  client.get_or_init(|| {
     reqwest::Client::builder()
       // Use to allow communication with Periphery self-signed certs.
       .danger_accept_invalid_certs(true)
+      // Sends `Accept-Encoding: gzip`, which Periphery
+      // responds to by compressing the response.
+      .gzip(compression)
       .build()
       .expect("Failed to build Periphery http client")
   })
@@ -26,6 +52,7 @@ pub struct PeripheryClient {
   address: String,
   passkey: String,
   timeout: Duration,
+  compression: bool,
 }
 
 impl PeripheryClient {
@@ -38,9 +65,17 @@ impl PeripheryClient {
       address: address.into(),
       passkey: passkey.into(),
       timeout: timeout.into(),
+      compression: false,
     }
   }
 
+  /// Negotiate gzip compressed responses with Periphery.
+  /// Each client negotiates independently, per request.
+  pub fn with_compression(mut self, compression: bool) -> Self {
+    self.compression = compression;
+    self
+  }
+
   // tracing will skip self, to avoid including passkey in traces
   #[tracing::instrument(
     name = "PeripheryRequest",
@@ -69,12 +104,29 @@ impl PeripheryClient {
     Ok(())
   }
 
-  #[tracing::instrument(level = "debug", skip(self))]
   async fn request_inner<T>(
     &self,
     request: T,
     timeout: Option<Duration>,
   ) -> anyhow::Result<T::Response>
+  where
+    T: std::fmt::Debug + Serialize + HasResponse,
+    T::Response: DeserializeOwned,
+  {
+    let start = Instant::now();
+    let res = self.request_inner_untimed(request, timeout).await;
+    if let Some(observer) = REQUEST_OBSERVER.get() {
+      observer(T::req_type(), start.elapsed(), res.is_ok());
+    }
+    res
+  }
+
+  #[tracing::instrument(level = "debug", skip(self))]
+  async fn request_inner_untimed<T>(
+    &self,
+    request: T,
+    timeout: Option<Duration>,
+  ) -> anyhow::Result<T::Response>
   where
     T: std::fmt::Debug + Serialize + HasResponse,
     T::Response: DeserializeOwned,
@@ -83,13 +135,16 @@ impl PeripheryClient {
     tracing::trace!(
       "sending request | type: {req_type} | body: {request:?}"
     );
-    let mut req = periphery_http_client()
+    let mut req = periphery_http_client(self.compression)
       .post(&self.address)
       .json(&json!({
         "type": req_type,
         "params": request
       }))
       .header("authorization", &self.passkey);
+    if let Some(traceparent) = logger::current_traceparent() {
+      req = req.header(logger::TRACEPARENT, traceparent);
+    }
     if let Some(timeout) = timeout {
       req = req.timeout(timeout);
     }
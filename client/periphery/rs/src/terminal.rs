@@ -7,7 +7,10 @@ use rustls::{ClientConfig, client::danger::ServerCertVerifier};
 use tokio::net::TcpStream;
 use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
 
-use crate::{PeripheryClient, api::terminal::*};
+use crate::{
+  PeripheryClient,
+  api::{container::ConnectContainerEventsQuery, terminal::*},
+};
 
 impl PeripheryClient {
   /// Handles ws connect and login.
@@ -39,6 +42,33 @@ impl PeripheryClient {
     connect_websocket(&url).await
   }
 
+  /// Connects to the stream of significant container events,
+  /// sent as JSON [ContainerEvent][komodo_client::entities::docker::container::ContainerEvent] text messages.
+  /// Does not handle reconnect.
+  pub async fn connect_container_events(
+    &self,
+  ) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    tracing::trace!("request | type: ConnectContainerEvents");
+
+    let token = self
+      .request(CreateTerminalAuthToken {})
+      .await
+      .context("Failed to create container events auth token")?;
+
+    let query_str =
+      serde_qs::to_string(&ConnectContainerEventsQuery {
+        token: token.token,
+      })
+      .context("Failed to serialize query string")?;
+
+    let url = format!(
+      "{}/events?{query_str}",
+      self.address.replacen("http", "ws", 1)
+    );
+
+    connect_websocket(&url).await
+  }
+
   /// Executes command on specified terminal,
   /// and streams the response ending in [KOMODO_EXIT_CODE][komodo_client::entities::KOMODO_EXIT_CODE]
   /// sentinal value as the expected final line of the stream.
@@ -61,7 +91,7 @@ impl PeripheryClient {
     tracing::trace!(
       "sending request | type: ExecuteTerminal | terminal name: {terminal} | command: {command}",
     );
-    let req = crate::periphery_http_client()
+    let req = crate::periphery_http_client(self.compression)
       .post(format!("{}/terminal/execute", self.address))
       .json(&ExecuteTerminalBody { terminal, command })
       .header("authorization", &self.passkey);
@@ -122,7 +152,7 @@ impl PeripheryClient {
     tracing::trace!(
       "sending request | type: ExecuteContainerExec | container: {container} | shell: {shell} | command: {command}",
     );
-    let req = crate::periphery_http_client()
+    let req = crate::periphery_http_client(self.compression)
       .post(format!("{}/terminal/execute/container", self.address))
       .json(&ExecuteContainerExecBody {
         container,
@@ -11,12 +11,16 @@ const ALERT_TYPES: Types.AlertData["type"][] = [
   "ServerCpu",
   "ServerMem",
   "ServerDisk",
+  "ServerStatsAnomaly",
   // Stack
   "StackStateChange",
   "StackImageUpdateAvailable",
   "StackAutoUpdated",
   // Deployment
   "ContainerStateChange",
+  "ContainerOomKilled",
+  "ContainerCrashLoop",
+  "DeploymentConfigDrift",
   "DeploymentImageUpdateAvailable",
   "DeploymentAutoUpdated",
   // Misc
@@ -27,6 +31,7 @@ const ALERT_TYPES: Types.AlertData["type"][] = [
   "ActionFailed",
   "ProcedureFailed",
   "AwsBuilderTerminationFailed",
+  "ApiKeyExpiring",
   "Custom",
 ];
 
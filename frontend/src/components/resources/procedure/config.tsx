@@ -868,6 +868,17 @@ const TARGET_COMPONENTS: ExecutionConfigs = {
       />
     ),
   },
+  ReconcileDeployment: {
+    params: { deployment: "" },
+    Component: ({ params, setParams, disabled }) => (
+      <ResourceSelector
+        type="Deployment"
+        selected={params.deployment}
+        onSelect={(deployment) => setParams({ deployment })}
+        disabled={disabled}
+      />
+    ),
+  },
   PullDeployment: {
     params: { deployment: "" },
     Component: ({ params, setParams, disabled }) => (
@@ -1681,6 +1692,34 @@ const TARGET_COMPONENTS: ExecutionConfigs = {
     params: {},
     Component: () => <></>,
   },
+  RedeployVariableReferences: {
+    params: { variable: "" },
+    Component: ({ params, setParams, disabled }) => {
+      const variables = useRead("ListVariables", {}).data ?? [];
+      return (
+        <Select
+          value={params.variable || undefined}
+          onValueChange={(variable) => setParams({ variable })}
+          disabled={disabled}
+        >
+          <SelectTrigger className="w-[200px]" disabled={disabled}>
+            <SelectValue placeholder="Select Variable" />
+          </SelectTrigger>
+          <SelectContent>
+            {variables.map((variable) => (
+              <SelectItem
+                key={variable.name}
+                value={variable.name}
+                className="cursor-pointer"
+              >
+                {variable.name}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      );
+    },
+  },
 
   SendAlert: {
     params: { message: "" },
@@ -1728,4 +1767,82 @@ const TARGET_COMPONENTS: ExecutionConfigs = {
       );
     },
   },
+  WaitUntil: {
+    params: {
+      condition: Types.WaitCondition.DeploymentHealthy,
+      target: "",
+      timeout_ms: 300_000,
+      interval_ms: 5_000,
+    },
+    Component: ({ params, setParams, disabled }) => {
+      const condition =
+        params.condition ?? Types.WaitCondition.DeploymentHealthy;
+      const [internal, setInternal] = useState(
+        params.timeout_ms?.toString() ?? ""
+      );
+      useEffect(() => {
+        setInternal(params.timeout_ms?.toString() ?? "");
+      }, [params.timeout_ms]);
+      return (
+        <div className="flex gap-2 items-center">
+          <Select
+            value={condition}
+            onValueChange={(condition) =>
+              setParams({
+                ...params,
+                condition: condition as Types.WaitCondition,
+              })
+            }
+            disabled={disabled}
+          >
+            <SelectTrigger className="w-[200px]" disabled={disabled}>
+              <SelectValue placeholder="Select Condition" />
+            </SelectTrigger>
+            <SelectContent>
+              {Object.values(Types.WaitCondition).map((condition) => (
+                <SelectItem
+                  key={condition}
+                  value={condition}
+                  className="cursor-pointer"
+                >
+                  {condition}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          {condition === Types.WaitCondition.DeploymentHealthy && (
+            <ResourceSelector
+              type="Deployment"
+              selected={params.target}
+              onSelect={(target) => setParams({ ...params, target })}
+              disabled={disabled}
+            />
+          )}
+          {condition === Types.WaitCondition.HttpOk && (
+            <Input
+              placeholder="https://example.com/health"
+              value={params.target}
+              onChange={(e) =>
+                setParams({ ...params, target: e.target.value })
+              }
+              disabled={disabled}
+            />
+          )}
+          <Input
+            className="w-[200px]"
+            placeholder="Timeout in milliseconds"
+            value={internal}
+            onChange={(e) => setInternal(e.target.value)}
+            onBlur={() => {
+              const timeout_ms = Number(internal);
+              if (timeout_ms) {
+                setParams({ ...params, timeout_ms });
+              }
+            }}
+            disabled={disabled}
+          />
+        </div>
+      );
+    },
+  },
 };
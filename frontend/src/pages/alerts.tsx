@@ -26,10 +26,19 @@ import { ResourceComponents } from "@components/resources";
 import { ResourceSelector } from "@components/resources/common";
 
 const ALERT_TYPES_BY_RESOURCE: { [key: string]: Types.AlertData["type"][] } = {
-  Server: ["ServerUnreachable", "ServerCpu", "ServerMem", "ServerDisk"],
+  Server: [
+    "ServerUnreachable",
+    "ServerCpu",
+    "ServerMem",
+    "ServerDisk",
+    "ServerStatsAnomaly",
+  ],
   Stack: ["StackStateChange", "StackImageUpdateAvailable", "StackAutoUpdated"],
   Deployment: [
     "ContainerStateChange",
+    "ContainerOomKilled",
+    "ContainerCrashLoop",
+    "DeploymentConfigDrift",
     "DeploymentImageUpdateAvailable",
     "DeploymentAutoUpdated",
   ],
@@ -1,24 +1,331 @@
-use std::path::Path;
+use std::{
+  path::{Path, PathBuf},
+  process::{Output, Stdio},
+  sync::OnceLock,
+  time::Duration,
+};
 
 use komodo_client::{
-  entities::{komodo_timestamp, update::Log},
+  entities::{komodo_timestamp, logger::LogLevel, update::Log},
   parsers::parse_multiline_command,
 };
-use run_command::{CommandOutput, async_run_command};
+use run_command::CommandOutput;
+use shell_escape::unix::escape;
+use tokio::{
+  io::{AsyncRead, AsyncReadExt},
+  process::{Child, Command},
+  task::JoinHandle,
+};
+
+mod output;
+
+pub use output::{DecodedOutput, decode_output};
+
+/// The shell commands are run with. Unset means `sh`.
+static SHELL: OnceLock<String> = OnceLock::new();
+
+/// Sets the shell commands are run with, eg `bash` or `/usr/bin/zsh`.
+/// The shell is called with `-c {command}`.
+///
+/// Takes effect on the first call only, and should be called on startup.
+/// Empty leaves the default `sh`.
+/// Fails if the shell isn't an existing path or found on the `PATH`.
+pub fn set_shell(shell: &str) -> Result<(), String> {
+  if shell.is_empty() {
+    return Ok(());
+  }
+  if !shell_exists(shell) {
+    return Err(format!(
+      "Shell '{shell}' not found. Use an absolute path or a shell on the PATH."
+    ));
+  }
+  let _ = SHELL.set(shell.to_string());
+  Ok(())
+}
+
+fn shell() -> &'static str {
+  SHELL.get().map(String::as_str).unwrap_or("sh")
+}
+
+fn shell_exists(shell: &str) -> bool {
+  if shell.contains('/') {
+    return Path::new(shell).is_file();
+  }
+  std::env::var_os("PATH")
+    .map(|paths| {
+      std::env::split_paths(&paths)
+        .any(|dir| dir.join(shell).is_file())
+    })
+    .unwrap_or_default()
+}
+
+/// The directories commands may run in. Empty means unrestricted.
+static ALLOWED_DIRECTORIES: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+/// Restricts the working directory of commands to
+/// the given directories and their subdirectories.
+/// Commands given a path elsewhere fail without running,
+/// and commands without a path run in the first existing directory.
+///
+/// Takes effect on the first call only, and should be called on startup.
+/// An empty list leaves commands unrestricted.
+pub fn set_allowed_directories(directories: Vec<PathBuf>) {
+  let directories = directories
+    .into_iter()
+    // Directories which don't exist yet (eg the repo dir
+    // before the first clone) are matched as given.
+    .map(|dir| dir.canonicalize().unwrap_or(dir))
+    .collect();
+  let _ = ALLOWED_DIRECTORIES.set(directories);
+}
+
+/// Gets the directory to run the command in, checking it is inside
+/// the allowed directories after resolving `..` and symlinks.
+///
+/// The resolved path is returned, so the command runs in the
+/// directory which was checked even if a symlink is changed after.
+/// Without a path, commands run in the first allowed directory
+/// which exists, rather than wherever Periphery was started.
+/// If none exist yet, the command runs without changing directory.
+fn working_directory(
+  allowed: &[PathBuf],
+  path: Option<&Path>,
+) -> Result<Option<PathBuf>, String> {
+  if allowed.is_empty() {
+    return Ok(path.map(Path::to_path_buf));
+  }
+  let Some(path) = path else {
+    return Ok(allowed.iter().find(|dir| dir.is_dir()).cloned());
+  };
+  let path = path.canonicalize().map_err(|e| {
+    format!("Failed to resolve working directory {path:?} | {e}")
+  })?;
+  if allowed.iter().any(|dir| path.starts_with(dir)) {
+    Ok(Some(path))
+  } else {
+    Err(format!(
+      "Working directory {path:?} is outside the allowed directories {allowed:?}"
+    ))
+  }
+}
+
+/// Prefixes the command with `cd {path}`, with the path shell escaped.
+fn cd_command(path: &Path, command: &str) -> String {
+  format!("cd {} && {command}", escape(path.to_string_lossy()))
+}
 
+/// Prefixes the command with `cd {path}`, or returns a failed log
+/// if the command is empty (or only comments)
+/// or the path is outside the allowed directories.
+///
+/// Running an empty command would succeed without doing anything,
+/// hiding a command field which was left blank.
+fn prepare_command(
+  stage: &str,
+  path: Option<&Path>,
+  command: &str,
+) -> Result<String, Log> {
+  if parse_multiline_command(command).is_empty() {
+    return Err(failed_log(
+      stage,
+      command.to_string(),
+      String::from("Command is empty"),
+    ));
+  }
+  let allowed = ALLOWED_DIRECTORIES
+    .get()
+    .map(Vec::as_slice)
+    .unwrap_or_default();
+  match working_directory(allowed, path) {
+    Ok(Some(path)) => Ok(cd_command(&path, command)),
+    Ok(None) => Ok(command.to_string()),
+    Err(stderr) => {
+      let command = match path {
+        Some(path) => cd_command(path, command),
+        None => command.to_string(),
+      };
+      Err(failed_log(stage, command, stderr))
+    }
+  }
+}
+
+/// A log for a command which failed before it was run.
+fn failed_log(stage: &str, command: String, stderr: String) -> Log {
+  let ts = komodo_timestamp();
+  Log {
+    stage: stage.to_string(),
+    level: LogLevel::Error,
+    command,
+    stdout: String::new(),
+    stderr,
+    success: false,
+    start_ts: ts,
+    end_ts: ts,
+  }
+}
+
+/// Runs the command in the given working directory.
+///
+/// Fails without running if the command is empty,
+/// or the path is outside the allowed directories.
 pub async fn run_komodo_command(
   stage: &str,
   path: impl Into<Option<&Path>>,
   command: impl AsRef<str>,
 ) -> Log {
-  let command = if let Some(path) = path.into() {
-    format!("cd {} && {}", path.display(), command.as_ref())
-  } else {
-    command.as_ref().to_string()
+  let command =
+    match prepare_command(stage, path.into(), command.as_ref()) {
+      Ok(command) => command,
+      Err(log) => return log,
+    };
+  let start_ts = komodo_timestamp();
+  let output =
+    Command::new(shell()).arg("-c").arg(&command).output().await;
+  decoded_into_log(stage, command, start_ts, decode_output(output))
+}
+
+/// Same as [run_komodo_command], but stderr is redirected to stdout,
+/// so the log stdout holds all the output in the order it was written.
+/// The log stderr is only used if the command can't be run.
+pub async fn run_komodo_command_combined(
+  stage: &str,
+  path: impl Into<Option<&Path>>,
+  command: impl AsRef<str>,
+) -> Log {
+  let command =
+    match prepare_command(stage, path.into(), command.as_ref()) {
+      Ok(command) => command,
+      Err(log) => return log,
+    };
+  let start_ts = komodo_timestamp();
+  // Redirecting in the shell itself covers every command in the chain.
+  let output = Command::new(shell())
+    .arg("-c")
+    .arg(format!("exec 2>&1; {command}"))
+    .output()
+    .await;
+  decoded_into_log(stage, command, start_ts, decode_output(output))
+}
+
+/// How long to wait for the output after the
+/// process group is killed on timeout.
+const KILLED_OUTPUT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Same as [run_komodo_command], but the process is killed
+/// if it is still running after the given timeout.
+/// Pass `None` for no timeout.
+///
+/// The command runs in its own process group, and on timeout
+/// the whole group is killed, including any processes it started.
+/// The returned log is unsuccessful, keeps the output written
+/// before the timeout, and the stderr records that the process was killed.
+pub async fn run_komodo_command_with_timeout(
+  stage: &str,
+  path: impl Into<Option<&Path>>,
+  command: impl AsRef<str>,
+  timeout: impl Into<Option<Duration>>,
+) -> Log {
+  let Some(timeout) = timeout.into() else {
+    return run_komodo_command(stage, path, command).await;
   };
+  let command =
+    match prepare_command(stage, path.into(), command.as_ref()) {
+      Ok(command) => command,
+      Err(log) => return log,
+    };
   let start_ts = komodo_timestamp();
-  let output = async_run_command(&command).await;
-  output_into_log(stage, command, start_ts, output)
+  let child = Command::new(shell())
+    .arg("-c")
+    .arg(&command)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .process_group(0)
+    .kill_on_drop(true)
+    .spawn();
+  let mut child = match child {
+    Ok(child) => child,
+    Err(e) => {
+      return decoded_into_log(
+        stage,
+        command,
+        start_ts,
+        decode_output(Err(e)),
+      );
+    }
+  };
+  // Read as the process runs, so the output
+  // is kept if the process is killed.
+  let stdout = read_pipe(child.stdout.take());
+  let stderr = read_pipe(child.stderr.take());
+  let (status, timed_out) =
+    match tokio::time::timeout(timeout, child.wait()).await {
+      Ok(status) => (status, false),
+      Err(_) => {
+        kill_process_group(&mut child).await;
+        (child.wait().await, true)
+      }
+    };
+  let output = match status {
+    Ok(status) => Ok(Output {
+      status,
+      stdout: collect_pipe(stdout).await,
+      stderr: collect_pipe(stderr).await,
+    }),
+    Err(e) => Err(e),
+  };
+  let mut log =
+    decoded_into_log(stage, command, start_ts, decode_output(output));
+  if timed_out {
+    if !log.stderr.is_empty() && !log.stderr.ends_with('\n') {
+      log.stderr.push('\n');
+    }
+    log.stderr.push_str(&format!(
+      "Process killed after exceeding timeout of {timeout:?}"
+    ));
+    log.success = false;
+    log.level = LogLevel::Error;
+  }
+  log
+}
+
+fn read_pipe(
+  pipe: Option<impl AsyncRead + Unpin + Send + 'static>,
+) -> JoinHandle<Vec<u8>> {
+  tokio::spawn(async move {
+    let mut bytes = Vec::new();
+    if let Some(mut pipe) = pipe {
+      let _ = pipe.read_to_end(&mut bytes).await;
+    }
+    bytes
+  })
+}
+
+/// Gets the output read from the pipe. Processes which left the
+/// process group may hold the pipe open, so this doesn't wait
+/// longer than [KILLED_OUTPUT_TIMEOUT].
+async fn collect_pipe(reader: JoinHandle<Vec<u8>>) -> Vec<u8> {
+  let abort = reader.abort_handle();
+  match tokio::time::timeout(KILLED_OUTPUT_TIMEOUT, reader).await {
+    Ok(Ok(bytes)) => bytes,
+    Ok(Err(_)) => Vec::new(),
+    Err(_) => {
+      abort.abort();
+      Vec::new()
+    }
+  }
+}
+
+/// Kills the child's process group, which it leads
+/// as it was spawned with `process_group(0)`.
+async fn kill_process_group(child: &mut Child) {
+  if let Some(pid) = child.id() {
+    let _ = Command::new("sh")
+      .arg("-c")
+      .arg(format!("kill -KILL -{pid}"))
+      .status()
+      .await;
+  }
+  let _ = child.start_kill();
 }
 
 /// Parses commands out of multiline string
@@ -28,6 +335,8 @@ pub async fn run_komodo_command(
 ///
 /// The result may be None if the command is empty after parsing,
 /// ie if all the lines are commented out.
+/// Unlike [run_komodo_command], this isn't an error,
+/// as multiline commands are optional.
 pub async fn run_komodo_command_multiline(
   stage: &str,
   path: impl Into<Option<&Path>>,
@@ -60,13 +369,31 @@ pub async fn run_komodo_command_with_sanitization(
   } else {
     run_komodo_command(stage, path, command).await.into()
   }?;
+  sanitize_log(&mut log, replacers);
+  Some(log)
+}
 
-  // Sanitize the command and output
+/// Sanitizes the command and output to avoid exposing secrets in the log.
+pub fn sanitize_log(log: &mut Log, replacers: &[(String, String)]) {
   log.command = svi::replace_in_string(&log.command, replacers);
   log.stdout = svi::replace_in_string(&log.stdout, replacers);
   log.stderr = svi::replace_in_string(&log.stderr, replacers);
+}
 
-  Some(log)
+/// Same as [output_into_log], but the log is at least a warning
+/// if the output wasn't valid UTF-8.
+fn decoded_into_log(
+  stage: &str,
+  command: String,
+  start_ts: i64,
+  decoded: DecodedOutput,
+) -> Log {
+  let mut log =
+    output_into_log(stage, command, start_ts, decoded.output);
+  if decoded.non_utf8 && log.level == LogLevel::Info {
+    log.level = LogLevel::Warn;
+  }
+  log
 }
 
 pub fn output_into_log(
@@ -78,6 +405,7 @@ pub fn output_into_log(
   let success = output.success();
   Log {
     stage: stage.to_string(),
+    level: Log::infer_level(success, &output.stderr),
     stdout: output.stdout,
     stderr: output.stderr,
     command,
@@ -86,3 +414,232 @@ pub fn output_into_log(
     end_ts: komodo_timestamp(),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A fresh directory under the system temp dir.
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+      .join(format!("komodo-command-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+  }
+
+  #[test]
+  fn unrestricted_keeps_path() {
+    assert_eq!(working_directory(&[], None), Ok(None));
+    assert_eq!(
+      working_directory(&[], Some(Path::new("/anywhere"))),
+      Ok(Some(PathBuf::from("/anywhere")))
+    );
+  }
+
+  #[test]
+  fn no_path_runs_in_first_existing_allowed_directory() {
+    let dir = temp_dir("no-path");
+    let first = dir.join("first");
+    let second = dir.join("second");
+    std::fs::create_dir_all(&second).unwrap();
+    let allowed = [first.clone(), second.clone()];
+    assert_eq!(working_directory(&allowed, None), Ok(Some(second)));
+    std::fs::create_dir_all(&first).unwrap();
+    assert_eq!(working_directory(&allowed, None), Ok(Some(first)));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn no_path_without_existing_allowed_directory() {
+    let dir = temp_dir("no-existing");
+    let allowed = [dir.join("missing")];
+    assert_eq!(working_directory(&allowed, None), Ok(None));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_directory_outside_allowed() {
+    let dir = temp_dir("outside");
+    let allowed = dir.join("allowed");
+    let outside = dir.join("outside");
+    std::fs::create_dir_all(&allowed).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    assert!(working_directory(&[allowed], Some(&outside)).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_missing_directory() {
+    let dir = temp_dir("missing");
+    assert!(
+      working_directory(
+        std::slice::from_ref(&dir),
+        Some(&dir.join("missing"))
+      )
+      .is_err()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn allows_subdirectory() {
+    let dir = temp_dir("allows");
+    let sub = dir.join("stack");
+    std::fs::create_dir_all(&sub).unwrap();
+    assert_eq!(
+      working_directory(std::slice::from_ref(&dir), Some(&sub)),
+      Ok(Some(sub))
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn rejects_parent_traversal() {
+    let dir = temp_dir("traversal");
+    let allowed = dir.join("allowed");
+    std::fs::create_dir_all(&allowed).unwrap();
+    assert!(
+      working_directory(
+        std::slice::from_ref(&allowed),
+        Some(&allowed.join(".."))
+      )
+      .is_err()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn resolves_symlink_to_checked_directory() {
+    let dir = temp_dir("symlink");
+    let allowed = dir.join("allowed");
+    let outside = dir.join("outside");
+    std::fs::create_dir_all(&allowed).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    let link = allowed.join("link");
+    std::os::unix::fs::symlink(&outside, &link).unwrap();
+    assert!(
+      working_directory(std::slice::from_ref(&allowed), Some(&link))
+        .is_err()
+    );
+    let inner = allowed.join("inner");
+    std::fs::create_dir_all(&inner).unwrap();
+    let inner_link = allowed.join("inner-link");
+    std::os::unix::fs::symlink(&inner, &inner_link).unwrap();
+    // Runs in the resolved directory, not through the symlink.
+    assert_eq!(
+      working_directory(&[allowed], Some(&inner_link)),
+      Ok(Some(inner))
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  /// Commands with nothing to run after multiline parsing.
+  const EMPTY_COMMANDS: [&str; 6] = [
+    "",
+    "   ",
+    "\n\t\n  \n",
+    "# comment",
+    "  # indented comment",
+    "# one\n\n   # two\n",
+  ];
+
+  #[test]
+  fn empty_commands_fail_before_running() {
+    for command in EMPTY_COMMANDS {
+      let log = prepare_command("Empty", None, command).unwrap_err();
+      assert!(!log.success, "{command:?}");
+      assert_eq!(log.stderr, "Command is empty", "{command:?}");
+    }
+  }
+
+  #[tokio::test]
+  async fn empty_commands_fail_in_each_mode() {
+    for command in EMPTY_COMMANDS {
+      for log in [
+        run_komodo_command("Empty", None, command).await,
+        run_komodo_command_with_timeout(
+          "Empty",
+          None,
+          command,
+          Duration::from_secs(10),
+        )
+        .await,
+        run_komodo_command_combined("Empty", None, command).await,
+      ] {
+        assert!(!log.success, "{command:?}");
+        assert_eq!(log.stderr, "Command is empty", "{command:?}");
+      }
+      // Multiline commands are optional, so empty isn't a failure.
+      assert!(
+        run_komodo_command_multiline("Empty", None, command)
+          .await
+          .is_none(),
+        "{command:?}"
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn multiline_skips_blank_and_comment_lines() {
+    let log = run_komodo_command_multiline(
+      "Multiline",
+      None,
+      "\n  # setup\necho one # trailing comment\n   \n\n# echo skipped\n  echo two\n",
+    )
+    .await
+    .unwrap();
+    assert!(log.success);
+    assert_eq!(log.stdout, "one\ntwo\n");
+  }
+
+  #[tokio::test]
+  async fn timeout_keeps_output_and_kills_process_group() {
+    let start = std::time::Instant::now();
+    let log = run_komodo_command_with_timeout(
+      "Timeout",
+      None,
+      // The background sleep holds the output open
+      // unless the whole process group is killed.
+      "echo partial; echo err >&2; sleep 30 & sleep 30",
+      Duration::from_millis(500),
+    )
+    .await;
+    assert!(start.elapsed() < KILLED_OUTPUT_TIMEOUT);
+    assert!(!log.success);
+    assert_eq!(log.level, LogLevel::Error);
+    assert_eq!(log.stdout, "partial\n");
+    assert!(log.stderr.starts_with("err\n"));
+    assert!(
+      log
+        .stderr
+        .contains("Process killed after exceeding timeout")
+    );
+  }
+
+  #[tokio::test]
+  async fn finishes_within_timeout() {
+    let log = run_komodo_command_with_timeout(
+      "Timeout",
+      None,
+      "echo done",
+      Duration::from_secs(10),
+    )
+    .await;
+    assert!(log.success);
+    assert_eq!(log.stdout, "done\n");
+  }
+
+  #[tokio::test]
+  async fn combined_output_keeps_order() {
+    let log = run_komodo_command_combined(
+      "Combined",
+      None,
+      "echo out1; echo err1 >&2; echo out2; echo err2 >&2",
+    )
+    .await;
+    assert!(log.success);
+    assert_eq!(log.stdout, "out1\nerr1\nout2\nerr2\n");
+    assert_eq!(log.stderr, "");
+  }
+}
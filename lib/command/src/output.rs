@@ -0,0 +1,110 @@
+use std::{
+  io,
+  os::unix::process::ExitStatusExt,
+  process::{ExitStatus, Output},
+};
+
+use run_command::CommandOutput;
+
+/// Appended to stderr when the output wasn't valid UTF-8.
+const NON_UTF8_NOTE: &str = "Note: the command output was not valid UTF-8. Invalid bytes were replaced with '�'.";
+
+pub struct DecodedOutput {
+  pub output: CommandOutput,
+  /// Whether stdout or stderr was not valid UTF-8,
+  /// and had the invalid bytes replaced.
+  pub non_utf8: bool,
+}
+
+/// Decodes the process output, replacing invalid UTF-8
+/// rather than discarding the output.
+///
+/// If any output was invalid, `non_utf8` is set
+/// and a note is added to stderr.
+///
+/// If the process couldn't be run, the status is exit code 1.
+pub fn decode_output(output: io::Result<Output>) -> DecodedOutput {
+  let output = match output {
+    Ok(output) => output,
+    Err(e) => {
+      return DecodedOutput {
+        output: CommandOutput {
+          // The raw status is the wait status, with the exit code
+          // in the second byte. A raw 1 would mean killed by signal 1.
+          status: ExitStatus::from_raw(1 << 8),
+          stdout: String::new(),
+          stderr: format!("{e:#?}"),
+        },
+        non_utf8: false,
+      };
+    }
+  };
+  let (stdout, stdout_valid) = decode_lossy(output.stdout);
+  let (mut stderr, stderr_valid) = decode_lossy(output.stderr);
+  let non_utf8 = !stdout_valid || !stderr_valid;
+  if non_utf8 {
+    if !stderr.is_empty() && !stderr.ends_with('\n') {
+      stderr.push('\n');
+    }
+    stderr.push_str(NON_UTF8_NOTE);
+  }
+  DecodedOutput {
+    output: CommandOutput {
+      status: output.status,
+      stdout,
+      stderr,
+    },
+    non_utf8,
+  }
+}
+
+/// Returns the decoded string, and whether the bytes were valid UTF-8.
+fn decode_lossy(bytes: Vec<u8>) -> (String, bool) {
+  match String::from_utf8(bytes) {
+    Ok(string) => (string, true),
+    Err(e) => {
+      (String::from_utf8_lossy(e.as_bytes()).into_owned(), false)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn output(stdout: &[u8], stderr: &[u8]) -> io::Result<Output> {
+    Ok(Output {
+      status: ExitStatus::from_raw(0),
+      stdout: stdout.to_vec(),
+      stderr: stderr.to_vec(),
+    })
+  }
+
+  #[test]
+  fn valid_output_is_unchanged() {
+    let decoded = decode_output(output(b"hello\n", b""));
+    assert!(!decoded.non_utf8);
+    assert_eq!(decoded.output.stdout, "hello\n");
+    assert_eq!(decoded.output.stderr, "");
+  }
+
+  #[test]
+  fn invalid_output_is_replaced_with_note() {
+    let decoded = decode_output(output(b"caf\xe9\n", b"warn"));
+    assert!(decoded.non_utf8);
+    assert!(decoded.output.success());
+    assert_eq!(decoded.output.stdout, "caf\u{FFFD}\n");
+    assert_eq!(
+      decoded.output.stderr,
+      format!("warn\n{NON_UTF8_NOTE}")
+    );
+  }
+
+  #[test]
+  fn spawn_failure_is_exit_code_1() {
+    let decoded = decode_output(Err(io::Error::other("not found")));
+    assert!(!decoded.output.success());
+    assert_eq!(decoded.output.status.code(), Some(1));
+    assert_eq!(decoded.output.status.signal(), None);
+  }
+}
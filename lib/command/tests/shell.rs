@@ -0,0 +1,34 @@
+//! The shell is set once per process, so these tests
+//! run in their own test binary.
+
+use command::{run_komodo_command, set_shell};
+
+async fn shell_name() -> String {
+  let log = run_komodo_command("Shell", None, "echo $0").await;
+  assert!(log.success, "{}", log.stderr);
+  log.stdout.trim().to_string()
+}
+
+#[tokio::test]
+async fn set_shell_once() {
+  // Default
+  assert_eq!(shell_name().await, "sh");
+  set_shell("").unwrap();
+  assert_eq!(shell_name().await, "sh");
+
+  // Invalid shells are rejected, and leave the default
+  for shell in ["komodo-missing-shell", "/komodo/missing/sh", "/tmp"]
+  {
+    let e = set_shell(shell).unwrap_err();
+    assert!(e.starts_with(&format!("Shell '{shell}' not found")));
+  }
+  assert_eq!(shell_name().await, "sh");
+
+  // Configured
+  set_shell("/bin/sh").unwrap();
+  assert_eq!(shell_name().await, "/bin/sh");
+
+  // Only the first shell set is used
+  set_shell("sh").unwrap();
+  assert_eq!(shell_name().await, "/bin/sh");
+}
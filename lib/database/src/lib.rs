@@ -6,8 +6,10 @@ use komodo_client::entities::{
   alert::Alert,
   alerter::Alerter,
   api_key::ApiKey,
+  audit::AuditLog,
   build::Build,
   builder::Builder,
+  change_freeze::ChangeFreeze,
   config::DatabaseConfig,
   deployment::Deployment,
   permission::Permission,
@@ -16,11 +18,11 @@ use komodo_client::entities::{
   repo::Repo,
   server::Server,
   stack::Stack,
-  stats::SystemStatsRecord,
+  stats::{SystemStatsRecord, SystemStatsRollup},
   sync::ResourceSync,
   tag::Tag,
-  update::Update,
-  user::{User, UserConfig},
+  update::{QueuedExecution, Update},
+  user::{LoginEvent, OidcSession, User, UserConfig},
   user_group::UserGroup,
   variable::Variable,
 };
@@ -44,13 +46,22 @@ pub struct Client {
   pub user_groups: Collection<UserGroup>,
   pub permissions: Collection<Permission>,
   pub api_keys: Collection<ApiKey>,
+  pub login_events: Collection<LoginEvent>,
+  pub oidc_sessions: Collection<OidcSession>,
   pub tags: Collection<Tag>,
   pub variables: Collection<Variable>,
   pub git_accounts: Collection<GitProviderAccount>,
   pub registry_accounts: Collection<DockerRegistryAccount>,
   pub updates: Collection<Update>,
+  pub execution_queue: Collection<QueuedExecution>,
+  pub audit_logs: Collection<AuditLog>,
+  pub change_freeze: Collection<ChangeFreeze>,
   pub alerts: Collection<Alert>,
   pub stats: Collection<SystemStatsRecord>,
+  /// Hourly averages of `stats`
+  pub stats_hourly: Collection<SystemStatsRollup>,
+  /// Daily averages of `stats`
+  pub stats_daily: Collection<SystemStatsRollup>,
   // RESOURCES
   pub servers: Collection<Server>,
   pub deployments: Collection<Deployment>,
@@ -80,13 +91,21 @@ impl Client {
       user_groups: mongo_indexed::collection(&db, true).await?,
       permissions: mongo_indexed::collection(&db, true).await?,
       api_keys: mongo_indexed::collection(&db, true).await?,
+      login_events: mongo_indexed::collection(&db, true).await?,
+      oidc_sessions: mongo_indexed::collection(&db, true).await?,
       tags: mongo_indexed::collection(&db, true).await?,
       variables: mongo_indexed::collection(&db, true).await?,
       git_accounts: mongo_indexed::collection(&db, true).await?,
       registry_accounts: mongo_indexed::collection(&db, true).await?,
       updates: mongo_indexed::collection(&db, true).await?,
+      execution_queue: mongo_indexed::collection(&db, true).await?,
+      audit_logs: mongo_indexed::collection(&db, true).await?,
+      change_freeze: mongo_indexed::collection(&db, true).await?,
       alerts: mongo_indexed::collection(&db, true).await?,
       stats: mongo_indexed::collection(&db, true).await?,
+      stats_hourly: stats_rollup_collection(&db, "StatsHourly")
+        .await?,
+      stats_daily: stats_rollup_collection(&db, "StatsDaily").await?,
       // RESOURCES
       servers: resource_collection(&db, "Server").await?,
       deployments: resource_collection(&db, "Deployment").await?,
@@ -196,6 +215,19 @@ async fn resource_collection<T: Send + Sync>(
   Ok(coll)
 }
 
+async fn stats_rollup_collection(
+  db: &Database,
+  collection_name: &str,
+) -> anyhow::Result<Collection<SystemStatsRollup>> {
+  let coll = db.collection(collection_name);
+
+  create_index(&coll, "ts").await?;
+
+  create_index(&coll, "sid").await?;
+
+  Ok(coll)
+}
+
 const BCRYPT_COST: u32 = 10;
 pub fn hash_password<P>(password: P) -> anyhow::Result<String>
 where
@@ -3,7 +3,7 @@ use std::path::Path;
 use anyhow::{Context, anyhow};
 use formatting::{bold, muted};
 use komodo_client::entities::{
-  LatestCommit, komodo_timestamp, update::Log,
+  LatestCommit, komodo_timestamp, logger::LogLevel, update::Log,
 };
 use run_command::async_run_command;
 use tracing::instrument;
@@ -13,6 +13,7 @@ mod commit;
 mod init;
 mod pull;
 mod pull_or_clone;
+mod sync;
 
 pub use crate::{
   clone::clone,
@@ -20,6 +21,7 @@ pub use crate::{
   init::init_folder_as_repo,
   pull::pull,
   pull_or_clone::pull_or_clone,
+  sync::sync_with_remote,
 };
 
 #[instrument(level = "debug")]
@@ -82,6 +84,7 @@ pub async fn get_commit_hash_log(
     success: true,
     start_ts,
     end_ts: komodo_timestamp(),
+    level: LogLevel::Info,
   };
   Ok((log, short_hash, msg))
 }
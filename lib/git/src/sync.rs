@@ -0,0 +1,298 @@
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+use command::run_komodo_command;
+use komodo_client::entities::{GitSyncStrategy, update::Log};
+use shell_escape::unix::escape;
+
+/// Checks the local branch is in sync with `origin/{branch}`,
+/// so a following commit can be pushed.
+/// If it has diverged, applies the [GitSyncStrategy].
+///
+/// Returns the logs of any fetch / rebase / reset which was run.
+/// Repo must be initialized with an `origin` remote.
+pub async fn sync_with_remote(
+  repo_dir: &Path,
+  branch: &str,
+  strategy: GitSyncStrategy,
+) -> anyhow::Result<Vec<Log>> {
+  let mut logs = Vec::new();
+  let escaped_branch = escape(branch.into());
+  let remote_branch = escape(format!("origin/{branch}").into());
+
+  // Without a local commit, there is nothing to diverge.
+  let head = run_komodo_command(
+    "Check Head",
+    repo_dir,
+    "git rev-parse --verify --quiet HEAD",
+  )
+  .await;
+  if !head.success {
+    return Ok(logs);
+  }
+
+  // The branch may not exist on the remote yet,
+  // in which case the push will create it.
+  // The full ref only lists the branch itself.
+  let ls_remote = run_komodo_command(
+    "List Remote",
+    repo_dir,
+    format!(
+      "git ls-remote --heads origin {}",
+      escape(format!("refs/heads/{branch}").into())
+    ),
+  )
+  .await;
+  if !ls_remote.success {
+    return Err(anyhow!(
+      "Failed to find origin {branch} | {}",
+      ls_remote.stderr
+    ));
+  }
+  if ls_remote.stdout.trim().is_empty() {
+    return Ok(logs);
+  }
+
+  let fetch_log = run_komodo_command(
+    "Fetch",
+    repo_dir,
+    format!("git fetch origin {escaped_branch}"),
+  )
+  .await;
+  if !fetch_log.success {
+    return Err(anyhow!(
+      "Failed to fetch origin {branch} | {}",
+      fetch_log.stderr
+    ));
+  }
+  logs.push(fetch_log);
+
+  let (ahead, behind) =
+    ahead_behind(repo_dir, branch, &remote_branch).await?;
+  if behind == 0 {
+    // Local commits are pushed along with the new one.
+    return Ok(logs);
+  }
+
+  match strategy {
+    GitSyncStrategy::Fail => Err(anyhow!(
+      "Local repo has diverged from origin/{branch} ({ahead} ahead, {behind} behind). Pull the latest changes or use another sync strategy."
+    )),
+    GitSyncStrategy::Rebase => {
+      let rebase_log = run_komodo_command(
+        "Rebase",
+        repo_dir,
+        format!("git rebase {remote_branch}"),
+      )
+      .await;
+      if !rebase_log.success {
+        run_komodo_command(
+          "Abort Rebase",
+          repo_dir,
+          "git rebase --abort",
+        )
+        .await;
+        return Err(anyhow!(
+          "Failed to rebase onto origin/{branch} | {}",
+          rebase_log.stderr
+        ));
+      }
+      logs.push(rebase_log);
+      Ok(logs)
+    }
+    GitSyncStrategy::Force => {
+      let dropped = if ahead == 0 {
+        String::new()
+      } else {
+        let dropped = run_komodo_command(
+          "Dropped Commits",
+          repo_dir,
+          format!("git log --oneline {remote_branch}..HEAD"),
+        )
+        .await;
+        if !dropped.success {
+          return Err(anyhow!(
+            "Failed to list the local commits | {}",
+            dropped.stderr
+          ));
+        }
+        dropped.stdout
+      };
+      let mut reset_log = run_komodo_command(
+        "Reset",
+        repo_dir,
+        format!("git reset --hard {remote_branch}"),
+      )
+      .await;
+      if !reset_log.success {
+        return Err(anyhow!(
+          "Failed to reset to origin/{branch} | {}",
+          reset_log.stderr
+        ));
+      }
+      if !dropped.is_empty() {
+        reset_log.stdout = format!(
+          "Dropped {ahead} local commit(s):\n{dropped}\n{}",
+          reset_log.stdout
+        );
+      }
+      logs.push(reset_log);
+      Ok(logs)
+    }
+  }
+}
+
+/// Returns the number of commits the local HEAD is (ahead, behind)
+/// `origin/{branch}`. `remote_branch` is the shell escaped `origin/{branch}`.
+async fn ahead_behind(
+  repo_dir: &Path,
+  branch: &str,
+  remote_branch: &str,
+) -> anyhow::Result<(usize, usize)> {
+  let output = run_komodo_command(
+    "Compare",
+    repo_dir,
+    format!(
+      "git rev-list --left-right --count HEAD...{remote_branch}"
+    ),
+  )
+  .await;
+  if !output.success {
+    return Err(anyhow!(
+      "Failed to compare with origin/{branch} | {}",
+      output.stderr
+    ));
+  }
+  let (ahead, behind) = output
+    .stdout
+    .trim()
+    .split_once('\t')
+    .context("Unexpected git rev-list output")?;
+  Ok((
+    ahead.parse().context("Invalid ahead commit count")?,
+    behind.parse().context("Invalid behind commit count")?,
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::*;
+
+  async fn git(dir: &Path, args: &str) {
+    let output =
+      run_komodo_command("Git", dir, format!("git {args}")).await;
+    assert!(output.success, "git {args} | {}", output.stderr);
+  }
+
+  async fn commit(dir: &Path, name: &str) {
+    tokio::fs::write(dir.join(name), name).await.unwrap();
+    git(dir, &format!("add {name}")).await;
+    git(dir, &format!("commit -m {name}")).await;
+  }
+
+  async fn clone(dir: &Path, name: &str) -> PathBuf {
+    git(dir, &format!("clone remote.git {}", escape(name.into())))
+      .await;
+    let repo = dir.join(name);
+    git(&repo, "config user.name test").await;
+    git(&repo, "config user.email test@example.com").await;
+    repo
+  }
+
+  /// Returns a clone which is 1 commit ahead and 1 behind origin/main.
+  async fn diverged_repo(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir()
+      .join(format!("komodo-git-sync-{}-{name}", std::process::id()));
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    git(&dir, "init --bare -b main remote.git").await;
+    let local = clone(&dir, "local").await;
+    git(&local, "checkout -b main").await;
+    commit(&local, "first").await;
+    git(&local, "push origin main").await;
+    let other = clone(&dir, "other").await;
+    commit(&other, "remote-change").await;
+    git(&other, "push origin main").await;
+    commit(&local, "local-change").await;
+    (dir, local)
+  }
+
+  #[tokio::test]
+  async fn missing_remote_branch_is_in_sync() {
+    let (dir, local) = diverged_repo("missing").await;
+    let logs = sync_with_remote(&local, "new", GitSyncStrategy::Fail)
+      .await
+      .unwrap();
+    assert!(logs.is_empty());
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn detects_divergence() {
+    let (dir, local) = diverged_repo("detect").await;
+    let fetch =
+      run_komodo_command("Fetch", local.as_path(), "git fetch").await;
+    assert!(fetch.success);
+    assert_eq!(
+      ahead_behind(&local, "main", "origin/main").await.unwrap(),
+      (1, 1)
+    );
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn fail_strategy_errors_on_divergence() {
+    let (dir, local) = diverged_repo("fail").await;
+    let e = sync_with_remote(&local, "main", GitSyncStrategy::Fail)
+      .await
+      .unwrap_err();
+    assert!(e.to_string().contains("1 ahead, 1 behind"));
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn rebase_strategy_keeps_local_commits() {
+    let (dir, local) = diverged_repo("rebase").await;
+    sync_with_remote(&local, "main", GitSyncStrategy::Rebase)
+      .await
+      .unwrap();
+    assert_eq!(
+      ahead_behind(&local, "main", "origin/main").await.unwrap(),
+      (1, 0)
+    );
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn force_strategy_logs_dropped_commits() {
+    let (dir, local) = diverged_repo("force").await;
+    let logs =
+      sync_with_remote(&local, "main", GitSyncStrategy::Force)
+        .await
+        .unwrap();
+    assert_eq!(
+      ahead_behind(&local, "main", "origin/main").await.unwrap(),
+      (0, 0)
+    );
+    let reset = logs.last().unwrap();
+    assert_eq!(reset.stage, "Reset");
+    assert!(reset.stdout.contains("local-change"));
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn runs_in_path_with_spaces() {
+    let (dir, _) = diverged_repo("spaces").await;
+    let local = clone(&dir, "local with spaces").await;
+    commit(&local, "spaced").await;
+    let logs =
+      sync_with_remote(&local, "main", GitSyncStrategy::Fail)
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].stage, "Fetch");
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+  }
+}
@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use komodo_client::entities::{
   EnvironmentVar, build::Build, deployment::Deployment, repo::Repo,
   stack::Stack, update::Log,
@@ -11,6 +11,8 @@ pub struct Interpolator<'a> {
   secrets: &'a HashMap<String, String>,
   variable_replacers: HashSet<(String, String)>,
   pub secret_replacers: HashSet<(String, String)>,
+  /// The `[[REFERENCES]]` left in the targets after interpolation.
+  unresolved: BTreeSet<String>,
 }
 
 impl<'a> Interpolator<'a> {
@@ -23,9 +25,47 @@ impl<'a> Interpolator<'a> {
       secrets,
       variable_replacers: Default::default(),
       secret_replacers: Default::default(),
+      unresolved: Default::default(),
     }
   }
 
+  /// Core interpolates with both variables and its secrets,
+  /// Periphery only with its own secrets.
+  fn source(&self) -> &'static str {
+    if self.variables.is_some() {
+      "Core"
+    } else {
+      "Periphery"
+    }
+  }
+
+  /// The references which no variable or secret was found for.
+  pub fn unresolved(&self) -> &BTreeSet<String> {
+    &self.unresolved
+  }
+
+  /// Errors naming the unresolved references, so the deploy fails
+  /// rather than passing on the literal placeholders.
+  /// Only Periphery should call this, as it interpolates last.
+  ///
+  /// Only UPPER_SNAKE_CASE names are treated as references
+  /// (see [unresolved_references]), and nothing is checked for
+  /// resources with `skip_secret_interp` enabled.
+  pub fn ensure_resolved(&self) -> anyhow::Result<()> {
+    if self.unresolved.is_empty() {
+      return Ok(());
+    }
+    Err(anyhow!(
+      "Unresolved secrets: {}. Add them as Core Variables / Secrets, or to the Periphery secrets.",
+      self
+        .unresolved
+        .iter()
+        .map(|name| format!("[[{name}]]"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ))
+  }
+
   pub fn interpolate_stack(
     &mut self,
     stack: &mut Stack,
@@ -126,6 +166,8 @@ impl<'a> Interpolator<'a> {
     })?;
     self.secret_replacers.extend(more_replacers);
 
+    self.unresolved.extend(unresolved_references(&res));
+
     // Set with result
     *target = res;
 
@@ -168,14 +210,125 @@ impl<'a> Interpolator<'a> {
 
     // Only show names of interpolated secrets
     if !self.secret_replacers.is_empty() {
+      let source = self.source();
       logs.push(
         Log::simple("Interpolate Secrets",
         self.secret_replacers
           .iter()
-          .map(|(_, variable)| format!("<span class=\"text-muted-foreground\">replaced:</span> {variable}"))
+          .map(|(_, variable)| format!("<span class=\"text-muted-foreground\">replaced ({source}):</span> {variable}"))
           .collect::<Vec<_>>()
           .join("\n"),)
       );
     }
+
+    // Core leaves these for Periphery to resolve.
+    if self.variables.is_some() && !self.unresolved.is_empty() {
+      logs.push(
+        Log::simple("Unresolved Secrets",
+        self.unresolved
+          .iter()
+          .map(|variable| format!("<span class=\"text-muted-foreground\">not in Core, must be Periphery secret:</span> {variable}"))
+          .collect::<Vec<_>>()
+          .join("\n"),)
+      );
+    }
+  }
+}
+
+/// Finds the `[[NAME]]` references in the text.
+/// Only names which look like Komodo Variables / Secrets,
+/// ie UPPER_SNAKE_CASE, are included. Other uses of double brackets,
+/// like TOML arrays of tables (`[[servers]]`), are left alone.
+fn unresolved_references(text: &str) -> Vec<String> {
+  let mut references = Vec::new();
+  let mut rest = text;
+  while let Some(start) = rest.find("[[") {
+    rest = &rest[start + 2..];
+    let Some(end) = rest.find("]]") else {
+      break;
+    };
+    let name = &rest[..end];
+    if is_reference_name(name) {
+      references.push(name.to_string());
+      rest = &rest[end + 2..];
+    }
+  }
+  references
+}
+
+fn is_reference_name(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_uppercase())
+    && name.chars().all(|c| {
+      c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn finds_references() {
+    assert_eq!(
+      unresolved_references(
+        "DB_PASSWORD=[[DB_PASSWORD]]\nTOKEN=[[API_TOKEN_2]]"
+      ),
+      ["DB_PASSWORD", "API_TOKEN_2"]
+    );
+  }
+
+  #[test]
+  fn finds_adjacent_references() {
+    assert_eq!(
+      unresolved_references("[[USER]]:[[PASSWORD]]@host"),
+      ["USER", "PASSWORD"]
+    );
+  }
+
+  #[test]
+  fn ignores_toml_arrays_of_tables() {
+    assert!(
+      unresolved_references(
+        "[[servers]]\nname = \"a\"\n[[inputs.cpu]]\n[[bin]]"
+      )
+      .is_empty()
+    );
+  }
+
+  #[test]
+  fn ignores_non_reference_brackets() {
+    for text in [
+      "[[]]",
+      "[[ SPACED ]]",
+      "[[_LEADING]]",
+      "[[1ST]]",
+      "[[Mixed_Case]]",
+      "[[UNCLOSED",
+      "if [[ -f file ]]; then",
+    ] {
+      assert!(unresolved_references(text).is_empty(), "{text}");
+    }
+  }
+
+  #[test]
+  fn finds_reference_after_ignored_brackets() {
+    assert_eq!(
+      unresolved_references(
+        "[[servers]]\npassword = \"[[PASSWORD]]\""
+      ),
+      ["PASSWORD"]
+    );
+  }
+
+  #[test]
+  fn ensure_resolved_names_references() {
+    let secrets = HashMap::new();
+    let mut interpolator = Interpolator::new(None, &secrets);
+    let mut target =
+      String::from("[[servers]]\npassword = \"[[PASSWORD]]\"");
+    interpolator.interpolate_string(&mut target).unwrap();
+    let e = interpolator.ensure_resolved().unwrap_err();
+    assert!(e.to_string().contains("[[PASSWORD]]"));
+    assert!(!e.to_string().contains("[[servers]]"));
   }
 }
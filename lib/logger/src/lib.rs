@@ -8,6 +8,10 @@ use tracing_subscriber::{
 
 mod otel;
 
+pub use otel::{
+  TRACEPARENT, current_traceparent, set_parent_from_traceparent,
+};
+
 pub fn init(config: &LogConfig) -> anyhow::Result<()> {
   let log_level: tracing::Level = config.level.into();
 
@@ -16,11 +20,22 @@ pub fn init(config: &LogConfig) -> anyhow::Result<()> {
 
   let use_otel = !config.otlp_endpoint.is_empty();
 
+  if use_otel {
+    otel::meter_provider(
+      &otel::metrics_endpoint(
+        &config.otlp_endpoint,
+        &config.otlp_metrics_endpoint,
+      ),
+      config.opentelemetry_service_name.clone(),
+    );
+  }
+
   match (config.stdio, use_otel, config.pretty) {
     (StdioLogMode::Standard, true, true) => {
       let tracer = otel::tracer(
         &config.otlp_endpoint,
         config.opentelemetry_service_name.clone(),
+        config.otlp_high_cardinality_attributes,
       );
       registry
         .with(
@@ -37,6 +52,7 @@ pub fn init(config: &LogConfig) -> anyhow::Result<()> {
       let tracer = otel::tracer(
         &config.otlp_endpoint,
         config.opentelemetry_service_name.clone(),
+        config.otlp_high_cardinality_attributes,
       );
       registry
         .with(
@@ -53,6 +69,7 @@ pub fn init(config: &LogConfig) -> anyhow::Result<()> {
       let tracer = otel::tracer(
         &config.otlp_endpoint,
         config.opentelemetry_service_name.clone(),
+        config.otlp_high_cardinality_attributes,
       );
       registry
         .with(tracing_subscriber::fmt::layer().json())
@@ -86,6 +103,7 @@ pub fn init(config: &LogConfig) -> anyhow::Result<()> {
       let tracer = otel::tracer(
         &config.otlp_endpoint,
         config.opentelemetry_service_name.clone(),
+        config.otlp_high_cardinality_attributes,
       );
       registry.with(OpenTelemetryLayer::new(tracer)).try_init()
     }
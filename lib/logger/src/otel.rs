@@ -1,12 +1,26 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
-use opentelemetry::{KeyValue, global, trace::TracerProvider};
+use opentelemetry::{
+  Context, KeyValue, global,
+  propagation::TextMapPropagator,
+  trace::{TraceContextExt, TracerProvider},
+};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{
   Resource,
-  trace::{Sampler, Tracer},
+  error::OTelSdkResult,
+  metrics::{PeriodicReader, SdkMeterProvider},
+  propagation::TraceContextPropagator,
+  trace::{
+    BatchSpanProcessor, Sampler, Span, SpanData, SpanProcessor,
+    Tracer,
+  },
 };
 use opentelemetry_semantic_conventions::resource::SERVICE_VERSION;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Header used to propagate trace context between Core and Periphery.
+pub const TRACEPARENT: &str = "traceparent";
 
 fn resource(service_name: String) -> Resource {
   Resource::builder()
@@ -18,20 +32,264 @@ fn resource(service_name: String) -> Resource {
     .build()
 }
 
-pub fn tracer(endpoint: &str, service_name: String) -> Tracer {
-  let provider =
+pub fn tracer(
+  endpoint: &str,
+  service_name: String,
+  high_cardinality_attributes: bool,
+) -> Tracer {
+  let exporter = opentelemetry_otlp::SpanExporter::builder()
+    .with_http()
+    .with_endpoint(endpoint)
+    .with_timeout(Duration::from_secs(3))
+    .build()
+    .unwrap();
+  let builder =
     opentelemetry_sdk::trace::TracerProviderBuilder::default()
       .with_resource(resource(service_name.clone()))
-      .with_sampler(Sampler::AlwaysOn)
-      .with_batch_exporter(
-        opentelemetry_otlp::SpanExporter::builder()
-          .with_http()
-          .with_endpoint(endpoint)
-          .with_timeout(Duration::from_secs(3))
-          .build()
-          .unwrap(),
-      )
-      .build();
+      .with_sampler(Sampler::AlwaysOn);
+  let provider = if high_cardinality_attributes {
+    builder.with_batch_exporter(exporter).build()
+  } else {
+    builder
+      .with_span_processor(ExcludeAttributes {
+        inner: BatchSpanProcessor::builder(exporter).build(),
+        exclude: HIGH_CARDINALITY_ATTRIBUTES,
+      })
+      .build()
+  };
   global::set_tracer_provider(provider.clone());
   provider.tracer(service_name)
 }
+
+/// Span fields which are unique to (nearly) every span,
+/// which some tracing backends are slow to index.
+const HIGH_CARDINALITY_ATTRIBUTES: &[&str] =
+  &["update_id", "req_id", "request_id", "user_id"];
+
+/// Removes the given attributes from spans before export.
+#[derive(Debug)]
+struct ExcludeAttributes<P> {
+  inner: P,
+  exclude: &'static [&'static str],
+}
+
+impl<P: SpanProcessor> SpanProcessor for ExcludeAttributes<P> {
+  fn on_start(&self, span: &mut Span, cx: &Context) {
+    self.inner.on_start(span, cx)
+  }
+
+  fn on_end(&self, mut span: SpanData) {
+    span
+      .attributes
+      .retain(|attr| !self.exclude.contains(&attr.key.as_str()));
+    self.inner.on_end(span)
+  }
+
+  fn force_flush(&self) -> OTelSdkResult {
+    self.inner.force_flush()
+  }
+
+  fn shutdown_with_timeout(
+    &self,
+    timeout: Duration,
+  ) -> OTelSdkResult {
+    self.inner.shutdown_with_timeout(timeout)
+  }
+
+  fn set_resource(&mut self, resource: &Resource) {
+    self.inner.set_resource(resource)
+  }
+}
+
+pub fn meter_provider(
+  endpoint: &str,
+  service_name: String,
+) -> SdkMeterProvider {
+  let exporter = opentelemetry_otlp::MetricExporter::builder()
+    .with_http()
+    .with_endpoint(endpoint)
+    .with_timeout(Duration::from_secs(3))
+    .build()
+    .unwrap();
+  let provider = SdkMeterProvider::builder()
+    .with_resource(resource(service_name))
+    .with_reader(
+      PeriodicReader::builder(exporter)
+        .with_interval(Duration::from_secs(15))
+        .build(),
+    )
+    .build();
+  global::set_meter_provider(provider.clone());
+  provider
+}
+
+/// Metrics go to `otlp_metrics_endpoint` if it is set.
+/// Otherwise they go to `otlp_endpoint`, with a trailing
+/// `/v1/traces` swapped for `/v1/metrics`.
+pub fn metrics_endpoint(
+  otlp_endpoint: &str,
+  otlp_metrics_endpoint: &str,
+) -> String {
+  if !otlp_metrics_endpoint.is_empty() {
+    return otlp_metrics_endpoint.to_string();
+  }
+  match otlp_endpoint.strip_suffix("/v1/traces") {
+    Some(base) => format!("{base}/v1/metrics"),
+    None => otlp_endpoint.to_string(),
+  }
+}
+
+/// The W3C `traceparent` header value for the current span.
+/// None if the current span is not being exported with OpenTelemetry.
+pub fn current_traceparent() -> Option<String> {
+  let context = tracing::Span::current().context();
+  if !context.span().span_context().is_valid() {
+    return None;
+  }
+  let mut carrier = HashMap::<String, String>::new();
+  TraceContextPropagator::new()
+    .inject_context(&context, &mut carrier);
+  carrier.remove(TRACEPARENT)
+}
+
+/// Continues the trace described by a W3C `traceparent` header,
+/// making the given span a child of the remote span.
+pub fn set_parent_from_traceparent(
+  span: &tracing::Span,
+  traceparent: &str,
+) {
+  let carrier = HashMap::from([(
+    TRACEPARENT.to_string(),
+    traceparent.to_string(),
+  )]);
+  let context = TraceContextPropagator::new().extract(&carrier);
+  if context.span().span_context().is_valid() {
+    span.set_parent(context);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+
+  use opentelemetry::trace::{Span as _, Tracer as _};
+  use opentelemetry_sdk::trace::SdkTracerProvider;
+  use tracing_opentelemetry::OpenTelemetryLayer;
+  use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+  use super::*;
+
+  const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+  const PARENT_ID: &str = "00f067aa0ba902b7";
+
+  fn with_otel(f: impl FnOnce()) {
+    let tracer = SdkTracerProvider::builder().build().tracer("test");
+    let subscriber =
+      Registry::default().with(OpenTelemetryLayer::new(tracer));
+    tracing::subscriber::with_default(subscriber, f);
+  }
+
+  #[test]
+  fn continues_trace_from_traceparent() {
+    with_otel(|| {
+      let span = tracing::info_span!("PeripheryRequest");
+      set_parent_from_traceparent(
+        &span,
+        &format!("00-{TRACE_ID}-{PARENT_ID}-01"),
+      );
+      let traceparent = span.in_scope(current_traceparent).unwrap();
+      let parts = traceparent.split('-').collect::<Vec<_>>();
+      assert_eq!(parts.len(), 4);
+      assert_eq!(parts[1], TRACE_ID);
+      assert_ne!(parts[2], PARENT_ID);
+      assert_eq!(parts[3], "01");
+    });
+  }
+
+  #[test]
+  fn ignores_invalid_traceparent() {
+    with_otel(|| {
+      let span = tracing::info_span!("PeripheryRequest");
+      set_parent_from_traceparent(&span, "00-not-a-traceparent");
+      let traceparent = span.in_scope(current_traceparent).unwrap();
+      assert!(!traceparent.contains(TRACE_ID));
+    });
+  }
+
+  #[test]
+  fn no_traceparent_without_otel() {
+    let span = tracing::info_span!("PeripheryRequest");
+    assert_eq!(span.in_scope(current_traceparent), None);
+  }
+
+  #[test]
+  fn metrics_endpoint_prefers_explicit_endpoint() {
+    assert_eq!(
+      metrics_endpoint(
+        "http://collector:4318/v1/traces",
+        "http://metrics:4318/v1/metrics"
+      ),
+      "http://metrics:4318/v1/metrics"
+    );
+  }
+
+  #[test]
+  fn metrics_endpoint_derived_from_traces_endpoint() {
+    assert_eq!(
+      metrics_endpoint("http://collector:4318/v1/traces", ""),
+      "http://collector:4318/v1/metrics"
+    );
+    assert_eq!(
+      metrics_endpoint("http://collector:4318", ""),
+      "http://collector:4318"
+    );
+  }
+
+  /// Keeps the ended spans in memory.
+  #[derive(Debug, Default)]
+  struct Recorder(Arc<Mutex<Vec<SpanData>>>);
+
+  impl SpanProcessor for Recorder {
+    fn on_start(&self, _: &mut Span, _: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+      self.0.lock().unwrap().push(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+      Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _: Duration) -> OTelSdkResult {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn excludes_high_cardinality_attributes() {
+    let spans = Arc::new(Mutex::new(Vec::new()));
+    let provider = SdkTracerProvider::builder()
+      .with_span_processor(ExcludeAttributes {
+        inner: Recorder(spans.clone()),
+        exclude: HIGH_CARDINALITY_ATTRIBUTES,
+      })
+      .build();
+    let mut span = provider
+      .tracer("test")
+      .start_with_context("RunBuild", &Context::new());
+    span.set_attributes([
+      KeyValue::new("update_id", "68f0c1"),
+      KeyValue::new("req_id", "0b5f7e"),
+      KeyValue::new("operation", "RunBuild"),
+    ]);
+    span.end();
+
+    let spans = spans.lock().unwrap();
+    let keys = spans[0]
+      .attributes
+      .iter()
+      .map(|attr| attr.key.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(keys, ["operation"]);
+  }
+}